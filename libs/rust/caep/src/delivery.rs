@@ -0,0 +1,415 @@
+//! Delivery guarantees for received SETs.
+//!
+//! Transmitters retry deliveries they could not confirm, so the same SET may
+//! arrive more than once, and events for one subject may arrive out of order.
+//! [`SetDeduplicator`] drops SETs whose `jti` was already processed;
+//! [`OrderingBuffer`] holds events for a short window and releases each
+//! subject's events in `event_timestamp` order.
+
+use crate::{CaepEvent, CaepResult};
+use rust_common::metrics::Counter;
+use rust_common::{CacheClient, CacheClientConfig};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// Default time a processed `jti` is remembered.
+pub const DEFAULT_DEDUP_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Receiver delivery metrics.
+#[derive(Debug)]
+pub struct ReceiverMetrics {
+    /// SETs dropped as duplicates
+    pub duplicates: Counter,
+    /// Events released ahead of an event that arrived before them
+    pub reordered: Counter,
+}
+
+impl ReceiverMetrics {
+    /// Create receiver metrics.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            duplicates: Counter::new(
+                "caep_receiver_duplicate_sets_total",
+                "SETs dropped because their jti was already processed",
+            ),
+            reordered: Counter::new(
+                "caep_receiver_reordered_events_total",
+                "Events released ahead of events that arrived before them",
+            ),
+        }
+    }
+
+    /// Format as Prometheus text.
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        format!(
+            "{}{}",
+            self.duplicates.to_prometheus(),
+            self.reordered.to_prometheus()
+        )
+    }
+}
+
+impl Default for ReceiverMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State of a `jti` seen by this receiver.
+enum JtiState {
+    InFlight,
+    Processed(Instant),
+}
+
+/// `jti`s seen by this receiver, expired ones discarded at most every half TTL
+struct SeenJtis {
+    entries: HashMap<String, JtiState>,
+    swept_at: Instant,
+}
+
+/// Drops SETs whose `jti` was already processed.
+///
+/// A `jti` is reserved while its SET is being processed, so concurrent
+/// redeliveries are dropped too. It is only remembered once processing
+/// succeeds; a failed SET can be redelivered. Processed `jti`s are shared
+/// through `Cache_Service` when configured, so redeliveries to another
+/// replica are dropped as well.
+pub struct SetDeduplicator {
+    seen: Mutex<SeenJtis>,
+    cache_client: Option<CacheClient>,
+    ttl: Duration,
+}
+
+impl SetDeduplicator {
+    /// Create a deduplicator remembering processed `jti`s for `ttl`.
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            seen: Mutex::new(SeenJtis {
+                entries: HashMap::new(),
+                swept_at: Instant::now(),
+            }),
+            cache_client: None,
+            ttl,
+        }
+    }
+
+    /// Create a deduplicator sharing processed `jti`s through `Cache_Service`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache client cannot be created.
+    pub async fn with_cache_service(config: CacheClientConfig, ttl: Duration) -> CaepResult<Self> {
+        let cache_client = CacheClient::new(config).await?;
+        Ok(Self {
+            cache_client: Some(cache_client),
+            ..Self::new(ttl)
+        })
+    }
+
+    fn cache_key(jti: &str) -> String {
+        format!("caep:set:{jti}")
+    }
+
+    fn seen(&self) -> std::sync::MutexGuard<'_, SeenJtis> {
+        self.seen.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Reserve a `jti`; returns `false` if it is a duplicate.
+    pub async fn begin(&self, jti: &str) -> bool {
+        {
+            let now = Instant::now();
+            let expired = |state: &JtiState| match state {
+                JtiState::InFlight => false,
+                JtiState::Processed(at) => now.duration_since(*at) >= self.ttl,
+            };
+            let mut seen = self.seen();
+            if now.duration_since(seen.swept_at) >= self.ttl / 2 {
+                seen.entries.retain(|_, state| !expired(state));
+                seen.swept_at = now;
+            }
+            if seen.entries.get(jti).is_some_and(|state| !expired(state)) {
+                return false;
+            }
+            seen.entries.insert(jti.to_string(), JtiState::InFlight);
+        }
+
+        if let Some(cache) = &self.cache_client {
+            match cache.exists(&Self::cache_key(jti)).await {
+                Ok(true) => {
+                    self.seen()
+                        .entries
+                        .insert(jti.to_string(), JtiState::Processed(Instant::now()));
+                    return false;
+                }
+                Ok(false) => {}
+                Err(e) => warn!(error = %e, "SET deduplication cache read failed"),
+            }
+        }
+        true
+    }
+
+    /// Release a reserved `jti`, remembering it if processing succeeded.
+    pub async fn complete(&self, jti: &str, processed: bool) {
+        if !processed {
+            self.seen().entries.remove(jti);
+            return;
+        }
+
+        self.seen()
+            .entries
+            .insert(jti.to_string(), JtiState::Processed(Instant::now()));
+        if let Some(cache) = &self.cache_client {
+            if let Err(e) = cache.set(&Self::cache_key(jti), b"1", Some(self.ttl)).await {
+                warn!(error = %e, "SET deduplication cache write failed");
+            }
+        }
+    }
+}
+
+/// An event waiting for release.
+pub struct PendingEvent {
+    seq: u64,
+    /// Buffered event
+    pub event: CaepEvent,
+    result: oneshot::Sender<CaepResult<()>>,
+}
+
+impl PendingEvent {
+    /// Deliver the outcome of processing to the caller that submitted it.
+    pub fn finish(self, result: CaepResult<()>) {
+        // The submitter may have gone away; the event was still processed
+        let _ = self.result.send(result);
+    }
+}
+
+/// Events of one subject awaiting release.
+#[derive(Default)]
+pub struct SubjectQueue {
+    pending: Vec<PendingEvent>,
+}
+
+impl SubjectQueue {
+    /// Remove events timestamped at or before `through`, oldest first.
+    ///
+    /// Returns the events and how many of them overtake an event that
+    /// arrived earlier but is released later.
+    pub fn release_through(
+        &mut self,
+        through: chrono::DateTime<chrono::Utc>,
+    ) -> (Vec<PendingEvent>, u64) {
+        let (mut released, kept): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|pending| pending.event.event_timestamp <= through);
+        self.pending = kept;
+        released.sort_by_key(|pending| (pending.event.event_timestamp, pending.seq));
+
+        // An event overtakes if an earlier arrival is released after it
+        let mut reordered = 0;
+        let mut min_later_seq = u64::MAX;
+        for pending in released.iter().rev() {
+            if pending.seq > min_later_seq {
+                reordered += 1;
+            }
+            min_later_seq = min_later_seq.min(pending.seq);
+        }
+        (released, reordered)
+    }
+
+    /// Whether no events are waiting.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Per-subject buffer releasing events in `event_timestamp` order.
+///
+/// Each event is held for the ordering window, then released together with
+/// every buffered event of the same subject timestamped at or before it.
+/// Ordering is only guaranteed among events arriving within one window of
+/// each other.
+pub struct OrderingBuffer {
+    window: Duration,
+    subjects: Mutex<HashMap<String, Arc<tokio::sync::Mutex<SubjectQueue>>>>,
+    next_seq: AtomicU64,
+}
+
+impl OrderingBuffer {
+    /// Create a buffer holding events for `window`.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            subjects: Mutex::new(HashMap::new()),
+            next_seq: AtomicU64::new(0),
+        }
+    }
+
+    /// How long events are held before release.
+    #[must_use]
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+
+    fn subjects(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<tokio::sync::Mutex<SubjectQueue>>>> {
+        self.subjects.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Buffer an event and return its subject queue and outcome receiver.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subject cannot be serialized.
+    pub async fn enqueue(
+        &self,
+        event: CaepEvent,
+    ) -> CaepResult<(
+        Arc<tokio::sync::Mutex<SubjectQueue>>,
+        oneshot::Receiver<CaepResult<()>>,
+    )> {
+        let key = serde_json::to_string(&event.subject)?;
+        let queue = self.subjects().entry(key).or_default().clone();
+
+        let (tx, rx) = oneshot::channel();
+        queue.lock().await.pending.push(PendingEvent {
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            event,
+            result: tx,
+        });
+        Ok((queue, rx))
+    }
+
+    /// Drop queues of subjects with no buffered events.
+    pub fn prune(&self) {
+        self.subjects().retain(|_, queue| {
+            Arc::strong_count(queue) > 1 || queue.try_lock().map_or(true, |q| !q.is_empty())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CaepEventType, SubjectIdentifier};
+
+    fn event_at(subject: &str, at: i64) -> CaepEvent {
+        let mut event = CaepEvent::new(
+            CaepEventType::CredentialChange,
+            SubjectIdentifier::opaque(subject),
+        );
+        event.event_timestamp = chrono::DateTime::from_timestamp(at, 0).unwrap();
+        event
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_jti_dropped_after_success() {
+        let dedup = SetDeduplicator::new(DEFAULT_DEDUP_TTL);
+
+        assert!(dedup.begin("set-1").await);
+        assert!(!dedup.begin("set-1").await);
+        dedup.complete("set-1", true).await;
+        assert!(!dedup.begin("set-1").await);
+        assert!(dedup.begin("set-2").await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_set_can_be_redelivered() {
+        let dedup = SetDeduplicator::new(DEFAULT_DEDUP_TTL);
+
+        assert!(dedup.begin("set-1").await);
+        dedup.complete("set-1", false).await;
+        assert!(dedup.begin("set-1").await);
+    }
+
+    #[tokio::test]
+    async fn test_expired_jti_accepted_again() {
+        let dedup = SetDeduplicator::new(Duration::ZERO);
+
+        assert!(dedup.begin("set-1").await);
+        dedup.complete("set-1", true).await;
+        assert!(dedup.begin("set-1").await);
+    }
+
+    #[tokio::test]
+    async fn test_expired_jtis_swept_on_interval() {
+        let dedup = SetDeduplicator::new(Duration::from_millis(100));
+
+        for jti in ["set-1", "set-2"] {
+            assert!(dedup.begin(jti).await);
+            dedup.complete(jti, true).await;
+        }
+        assert!(dedup.begin("set-3").await);
+        assert_eq!(dedup.seen().entries.len(), 3);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(dedup.begin("set-1").await);
+        assert_eq!(dedup.seen().entries.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_release_in_timestamp_order() {
+        let buffer = OrderingBuffer::new(Duration::from_millis(10));
+        let (queue, _rx1) = buffer.enqueue(event_at("user-1", 300)).await.unwrap();
+        let (_, _rx2) = buffer.enqueue(event_at("user-1", 100)).await.unwrap();
+        let (_, _rx3) = buffer.enqueue(event_at("user-1", 200)).await.unwrap();
+        let (other, _rx4) = buffer.enqueue(event_at("user-2", 50)).await.unwrap();
+        assert!(!Arc::ptr_eq(&queue, &other));
+
+        let mut queue = queue.lock().await;
+        let (released, reordered) =
+            queue.release_through(chrono::DateTime::from_timestamp(200, 0).unwrap());
+        let order: Vec<i64> = released
+            .iter()
+            .map(|p| p.event.event_timestamp.timestamp())
+            .collect();
+
+        assert_eq!(order, vec![100, 200]);
+        assert_eq!(reordered, 0);
+        assert!(!queue.is_empty());
+
+        let (released, _) =
+            queue.release_through(chrono::DateTime::from_timestamp(300, 0).unwrap());
+        assert_eq!(released.len(), 1);
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_overtaking_events_counted() {
+        let buffer = OrderingBuffer::new(Duration::from_millis(10));
+        let (queue, _rx1) = buffer.enqueue(event_at("user-1", 300)).await.unwrap();
+        let (_, _rx2) = buffer.enqueue(event_at("user-1", 100)).await.unwrap();
+        let (_, rx3) = buffer.enqueue(event_at("user-1", 200)).await.unwrap();
+
+        let (released, reordered) = queue
+            .lock()
+            .await
+            .release_through(chrono::DateTime::from_timestamp(300, 0).unwrap());
+        assert_eq!(reordered, 2);
+
+        for pending in released {
+            pending.finish(Ok(()));
+        }
+        assert!(rx3.await.unwrap().is_ok());
+
+        drop(queue);
+        buffer.prune();
+        assert!(buffer.subjects().is_empty());
+    }
+
+    #[test]
+    fn test_metrics_prometheus_format() {
+        let metrics = ReceiverMetrics::new();
+        metrics.duplicates.inc();
+        let text = metrics.to_prometheus();
+        assert!(text.contains("caep_receiver_duplicate_sets_total 1"));
+        assert!(text.contains("caep_receiver_reordered_events_total 0"));
+    }
+}
@@ -1,9 +1,23 @@
 //! Generic event handler trait and implementations.
 //!
 //! This module provides event handling using native async traits (Rust 2024).
+//! Subjects are resolved once per event by the processor's
+//! [`SubjectResolver`](crate::resolver::SubjectResolver), so handlers work
+//! with internal user IDs whatever identifier format the transmitter used.
+//!
+//! Each handler runs isolated under a [`HandlerPolicy`] of bounded
+//! concurrency, timeouts and retries; events a handler still cannot process
+//! go to an optional [`DeadLetterSink`].
 
-use crate::{CaepError, CaepEvent, CaepEventType, CaepResult, SubjectIdentifier};
+use crate::resolver::{DynSubjectResolver, PassthroughResolver, ResolvedSubject, SubjectResolver};
+use crate::{CaepError, CaepEvent, CaepEventType, CaepResult};
+use rust_common::RetryPolicy;
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::warn;
 
 /// Generic event handler trait with associated types.
 ///
@@ -13,7 +27,11 @@ pub trait EventHandler: Send + Sync {
     type Output;
 
     /// Handle the event and return result.
-    fn handle(&self, event: &CaepEvent) -> impl Future<Output = CaepResult<Self::Output>> + Send;
+    fn handle(
+        &self,
+        event: &CaepEvent,
+        subject: &ResolvedSubject,
+    ) -> impl Future<Output = CaepResult<Self::Output>> + Send;
 
     /// Check if this handler can process the event type.
     fn can_handle(&self, event_type: &CaepEventType) -> bool;
@@ -44,17 +62,15 @@ impl<S: SessionStore> SessionRevokedHandler<S> {
 impl<S: SessionStore> EventHandler for SessionRevokedHandler<S> {
     type Output = u64;
 
-    async fn handle(&self, event: &CaepEvent) -> CaepResult<Self::Output> {
-        match &event.subject {
-            SubjectIdentifier::SessionId { session_id } => {
-                self.session_store.terminate_session(session_id).await?;
-                Ok(1)
-            }
-            SubjectIdentifier::IssSub { sub, .. } => {
-                self.session_store.terminate_user_sessions(sub).await
-            }
-            _ => Err(CaepError::processing(
-                "Unsupported subject format for session revocation",
+    async fn handle(&self, _event: &CaepEvent, subject: &ResolvedSubject) -> CaepResult<Self::Output> {
+        if let Some(session_id) = subject.session_id() {
+            self.session_store.terminate_session(session_id).await?;
+            return Ok(1);
+        }
+        match &subject.user_id {
+            Some(user_id) => self.session_store.terminate_user_sessions(user_id).await,
+            None => Err(CaepError::processing(
+                "Unresolved subject for session revocation",
             )),
         }
     }
@@ -86,11 +102,11 @@ impl<C: CredentialCache> CredentialChangeHandler<C> {
 impl<C: CredentialCache> EventHandler for CredentialChangeHandler<C> {
     type Output = ();
 
-    async fn handle(&self, event: &CaepEvent) -> CaepResult<Self::Output> {
-        match &event.subject {
-            SubjectIdentifier::IssSub { sub, .. } => self.cache.invalidate(sub).await,
-            _ => Err(CaepError::processing(
-                "Unsupported subject format for credential change",
+    async fn handle(&self, _event: &CaepEvent, subject: &ResolvedSubject) -> CaepResult<Self::Output> {
+        match &subject.user_id {
+            Some(user_id) => self.cache.invalidate(user_id).await,
+            None => Err(CaepError::processing(
+                "Unresolved subject for credential change",
             )),
         }
     }
@@ -106,48 +122,327 @@ pub type BoxedHandler = Box<dyn DynEventHandler + Send + Sync>;
 /// Dynamic event handler trait for type erasure.
 pub trait DynEventHandler: Send + Sync {
     /// Handle the event.
-    fn handle_dyn(
-        &self,
-        event: &CaepEvent,
-    ) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<()>> + Send + '_>>;
+    fn handle_dyn<'a>(
+        &'a self,
+        event: &'a CaepEvent,
+        subject: &'a ResolvedSubject,
+    ) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<()>> + Send + 'a>>;
 
     /// Check if this handler can process the event type.
     fn can_handle(&self, event_type: &CaepEventType) -> bool;
+
+    /// Handler name used in logs and dead letters.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Default timeout of a single handler invocation.
+pub const DEFAULT_HANDLER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default number of concurrent invocations of one handler.
+pub const DEFAULT_HANDLER_CONCURRENCY: usize = 16;
+
+/// Concurrency, timeout and retry limits of one registered handler.
+#[derive(Debug, Clone)]
+pub struct HandlerPolicy {
+    /// Maximum concurrent invocations; further events wait for a slot
+    pub max_concurrency: usize,
+    /// Timeout of a single invocation
+    pub timeout: Duration,
+    /// Backoff between attempts; retryable errors and timeouts are retried
+    pub retry: RetryPolicy,
+}
+
+impl HandlerPolicy {
+    /// Set the maximum concurrent invocations (at least one).
+    #[must_use]
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Set the timeout of a single invocation.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the retry policy.
+    #[must_use]
+    pub const fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+impl Default for HandlerPolicy {
+    fn default() -> Self {
+        Self {
+            max_concurrency: DEFAULT_HANDLER_CONCURRENCY,
+            timeout: DEFAULT_HANDLER_TIMEOUT,
+            retry: RetryPolicy::with_defaults(),
+        }
+    }
+}
+
+/// An event a handler could not process after all attempts.
+#[derive(Debug)]
+pub struct DeadLetter {
+    /// Name of the failing handler
+    pub handler: String,
+    /// The event
+    pub event: Arc<CaepEvent>,
+    /// The event's resolved subject
+    pub subject: Arc<ResolvedSubject>,
+    /// Error of the last attempt
+    pub error: CaepError,
+    /// Number of attempts made
+    pub attempts: u32,
+}
+
+/// Receives events that handlers failed to process.
+///
+/// Uses native async traits (Rust 2024).
+pub trait DeadLetterSink: Send + Sync {
+    /// Record a dead letter.
+    fn dead_letter(&self, letter: DeadLetter) -> impl Future<Output = ()> + Send;
+}
+
+/// Dynamic dead letter sink trait for type erasure.
+pub trait DynDeadLetterSink: Send + Sync {
+    /// Record a dead letter.
+    fn dead_letter_dyn(
+        &self,
+        letter: DeadLetter,
+    ) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+}
+
+impl<D: DeadLetterSink> DynDeadLetterSink for D {
+    fn dead_letter_dyn(
+        &self,
+        letter: DeadLetter,
+    ) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(self.dead_letter(letter))
+    }
+}
+
+/// Why a single handler invocation failed.
+enum Failure {
+    Error(CaepError),
+    TimedOut(Duration),
+    Panicked(String),
+}
+
+impl Failure {
+    const fn is_retryable(&self) -> bool {
+        match self {
+            Self::Error(e) => e.is_retryable(),
+            Self::TimedOut(_) => true,
+            Self::Panicked(_) => false,
+        }
+    }
+
+    fn into_error(self) -> CaepError {
+        match self {
+            Self::Error(e) => e,
+            Self::TimedOut(timeout) => {
+                CaepError::processing(format!("Handler timed out after {timeout:?}"))
+            }
+            Self::Panicked(message) => {
+                CaepError::processing(format!("Handler panicked: {message}"))
+            }
+        }
+    }
+}
+
+/// A handler together with its policy and concurrency slots.
+#[derive(Clone)]
+struct RegisteredHandler {
+    handler: Arc<dyn DynEventHandler + Send + Sync>,
+    policy: HandlerPolicy,
+    permits: Arc<Semaphore>,
+}
+
+impl RegisteredHandler {
+    /// Runs the handler with retries, returning the attempt count and outcome.
+    async fn run(
+        &self,
+        event: Arc<CaepEvent>,
+        subject: Arc<ResolvedSubject>,
+    ) -> (u32, Result<(), Failure>) {
+        let mut attempts = 0;
+        let result = self
+            .policy
+            .retry
+            .execute_when(
+                || {
+                    attempts += 1;
+                    self.attempt(Arc::clone(&event), Arc::clone(&subject))
+                },
+                Failure::is_retryable,
+            )
+            .await;
+        (attempts, result)
+    }
+
+    /// Runs one invocation in its own task so a panic or hang stays contained.
+    async fn attempt(
+        &self,
+        event: Arc<CaepEvent>,
+        subject: Arc<ResolvedSubject>,
+    ) -> Result<(), Failure> {
+        let _permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .map_err(|_| Failure::Error(CaepError::processing("Handler slots closed")))?;
+
+        let handler = Arc::clone(&self.handler);
+        let mut task = tokio::spawn(async move { handler.handle_dyn(&event, &subject).await });
+        match tokio::time::timeout(self.policy.timeout, &mut task).await {
+            Ok(Ok(result)) => result.map_err(Failure::Error),
+            Ok(Err(join_error)) => Err(Failure::Panicked(panic_message(join_error))),
+            Err(_) => {
+                task.abort();
+                Err(Failure::TimedOut(self.policy.timeout))
+            }
+        }
+    }
+}
+
+/// Extracts the message of a panicked handler task.
+fn panic_message(join_error: tokio::task::JoinError) -> String {
+    if !join_error.is_panic() {
+        return join_error.to_string();
+    }
+    let payload = join_error.into_panic();
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
 }
 
 /// Generic event processor that dispatches to registered handlers.
+///
+/// Matching handlers run concurrently, each in its own task, so a slow,
+/// failing or panicking handler cannot hold up the others. Every handler is
+/// bounded by its [`HandlerPolicy`]: invocations beyond `max_concurrency`
+/// wait for a slot, each attempt is cut off at `timeout`, and retryable
+/// errors and timeouts are retried with backoff. Panics are not retried.
 pub struct EventProcessor {
-    handlers: Vec<BoxedHandler>,
+    handlers: Vec<RegisteredHandler>,
+    resolver: Box<dyn DynSubjectResolver>,
+    dead_letters: Option<Box<dyn DynDeadLetterSink>>,
 }
 
 impl EventProcessor {
-    /// Create a new event processor.
+    /// Create a new event processor resolving subjects with [`PassthroughResolver`].
     #[must_use]
     pub fn new() -> Self {
         Self {
             handlers: Vec::new(),
+            resolver: Box::new(PassthroughResolver),
+            dead_letters: None,
         }
     }
 
-    /// Register a handler.
+    /// Set the resolver applied to event subjects before dispatch.
+    #[must_use]
+    pub fn with_resolver(mut self, resolver: impl SubjectResolver + 'static) -> Self {
+        self.resolver = Box::new(resolver);
+        self
+    }
+
+    /// Send events that handlers fail to process to `sink`.
+    ///
+    /// With a sink configured, handler failures no longer fail
+    /// [`process`](Self::process), so one broken handler cannot cause the
+    /// whole event to be redelivered.
+    #[must_use]
+    pub fn with_dead_letter_sink(mut self, sink: impl DeadLetterSink + 'static) -> Self {
+        self.dead_letters = Some(Box::new(sink));
+        self
+    }
+
+    /// Register a handler with the default [`HandlerPolicy`].
     pub fn register(&mut self, handler: BoxedHandler) {
-        self.handlers.push(handler);
+        self.register_with_policy(handler, HandlerPolicy::default());
+    }
+
+    /// Register a handler with its own concurrency, timeout and retry limits.
+    pub fn register_with_policy(&mut self, handler: BoxedHandler, policy: HandlerPolicy) {
+        self.handlers.push(RegisteredHandler {
+            handler: Arc::from(handler),
+            permits: Arc::new(Semaphore::new(policy.max_concurrency.max(1))),
+            policy,
+        });
     }
 
     /// Process an event through all matching handlers.
     ///
+    /// Returns the number of handlers that processed the event.
+    ///
     /// # Errors
     ///
-    /// Returns an error if any handler fails.
+    /// Returns an error if subject resolution fails, or, without a dead
+    /// letter sink, the first handler error once all handlers have finished.
     pub async fn process(&self, event: &CaepEvent) -> CaepResult<usize> {
+        if !self
+            .handlers
+            .iter()
+            .any(|h| h.handler.can_handle(&event.event_type))
+        {
+            return Ok(0);
+        }
+
+        let subject = Arc::new(self.resolver.resolve_dyn(&event.subject).await?);
+        let event = Arc::new(event.clone());
+
+        let mut tasks = JoinSet::new();
+        for entry in &self.handlers {
+            if entry.handler.can_handle(&event.event_type) {
+                let entry = entry.clone();
+                let event = Arc::clone(&event);
+                let subject = Arc::clone(&subject);
+                tasks.spawn(async move {
+                    let (attempts, result) = entry.run(event, subject).await;
+                    (entry.handler.name().to_string(), attempts, result)
+                });
+            }
+        }
+
         let mut handled = 0;
-        for handler in &self.handlers {
-            if handler.can_handle(&event.event_type) {
-                handler.handle_dyn(event).await?;
+        let mut first_error = None;
+        while let Some(joined) = tasks.join_next().await {
+            let (name, attempts, result) =
+                joined.map_err(|e| CaepError::processing(format!("Handler task failed: {e}")))?;
+            let Err(failure) = result else {
                 handled += 1;
+                continue;
+            };
+
+            let error = failure.into_error();
+            warn!(handler = %name, attempts, error = %error, "CAEP event handler failed");
+            match &self.dead_letters {
+                Some(sink) => {
+                    sink.dead_letter_dyn(DeadLetter {
+                        handler: name,
+                        event: Arc::clone(&event),
+                        subject: Arc::clone(&subject),
+                        error,
+                        attempts,
+                    })
+                    .await;
+                }
+                None => {
+                    first_error.get_or_insert(error);
+                }
             }
         }
-        Ok(handled)
+
+        first_error.map_or(Ok(handled), Err)
     }
 
     /// Get the number of registered handlers.
@@ -166,6 +461,7 @@ impl Default for EventProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::SubjectIdentifier;
     use std::sync::atomic::{AtomicU64, Ordering};
     use std::sync::Arc;
 
@@ -201,11 +497,12 @@ mod tests {
 
         let subject = SubjectIdentifier::session_id("session-123");
         let event = CaepEvent::session_revoked(subject, None);
+        let resolved = PassthroughResolver.resolve(&event.subject).await.unwrap();
 
         assert!(handler.can_handle(&CaepEventType::SessionRevoked));
         assert!(!handler.can_handle(&CaepEventType::CredentialChange));
 
-        let result = handler.handle(&event).await;
+        let result = handler.handle(&event, &resolved).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 1);
     }
@@ -217,9 +514,248 @@ mod tests {
 
         let subject = SubjectIdentifier::iss_sub("https://issuer.com", "user-123");
         let event = CaepEvent::session_revoked(subject, None);
+        let resolved = PassthroughResolver.resolve(&event.subject).await.unwrap();
 
-        let result = handler.handle(&event).await;
+        let result = handler.handle(&event, &resolved).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 5);
     }
+
+    struct EmailDirectory;
+
+    impl SubjectResolver for EmailDirectory {
+        async fn resolve(&self, subject: &SubjectIdentifier) -> CaepResult<ResolvedSubject> {
+            let user_id = match subject {
+                SubjectIdentifier::Email { email } if email == "user@example.com" => {
+                    Some("user-123".to_string())
+                }
+                _ => None,
+            };
+            Ok(ResolvedSubject::new(subject.clone(), user_id))
+        }
+    }
+
+    struct RecordingHandler {
+        user_ids: Arc<std::sync::Mutex<Vec<Option<String>>>>,
+    }
+
+    impl DynEventHandler for RecordingHandler {
+        fn handle_dyn<'a>(
+            &'a self,
+            _event: &'a CaepEvent,
+            subject: &'a ResolvedSubject,
+        ) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<()>> + Send + 'a>> {
+            self.user_ids.lock().unwrap().push(subject.user_id.clone());
+            Box::pin(std::future::ready(Ok(())))
+        }
+
+        fn can_handle(&self, event_type: &CaepEventType) -> bool {
+            matches!(event_type, CaepEventType::CredentialChange)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_processor_resolves_subject_before_dispatch() {
+        let user_ids = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::new().with_resolver(EmailDirectory);
+        processor.register(Box::new(RecordingHandler {
+            user_ids: user_ids.clone(),
+        }));
+
+        let event = CaepEvent::new(
+            CaepEventType::CredentialChange,
+            SubjectIdentifier::email("user@example.com"),
+        );
+        assert_eq!(processor.process(&event).await.unwrap(), 1);
+
+        let unrelated = CaepEvent::session_revoked(SubjectIdentifier::session_id("s-1"), None);
+        assert_eq!(processor.process(&unrelated).await.unwrap(), 0);
+
+        assert_eq!(*user_ids.lock().unwrap(), vec![Some("user-123".to_string())]);
+    }
+
+    /// Handler with a configurable body, counting invocations.
+    struct ScriptedHandler {
+        calls: Arc<AtomicU64>,
+        running: Arc<AtomicU64>,
+        max_running: Arc<AtomicU64>,
+        behavior: fn(u64) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<()>> + Send>>,
+    }
+
+    impl ScriptedHandler {
+        fn new(
+            behavior: fn(u64) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<()>> + Send>>,
+        ) -> Self {
+            Self {
+                calls: Arc::new(AtomicU64::new(0)),
+                running: Arc::new(AtomicU64::new(0)),
+                max_running: Arc::new(AtomicU64::new(0)),
+                behavior,
+            }
+        }
+    }
+
+    impl DynEventHandler for ScriptedHandler {
+        fn handle_dyn<'a>(
+            &'a self,
+            _event: &'a CaepEvent,
+            _subject: &'a ResolvedSubject,
+        ) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<()>> + Send + 'a>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            let body = (self.behavior)(call);
+            Box::pin(async move {
+                let result = body.await;
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                result
+            })
+        }
+
+        fn can_handle(&self, event_type: &CaepEventType) -> bool {
+            matches!(event_type, CaepEventType::SessionRevoked)
+        }
+
+        fn name(&self) -> &str {
+            "scripted"
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct CollectingSink {
+        letters: Arc<std::sync::Mutex<Vec<(String, u32, String)>>>,
+    }
+
+    impl DeadLetterSink for CollectingSink {
+        async fn dead_letter(&self, letter: DeadLetter) {
+            self.letters.lock().unwrap().push((
+                letter.handler,
+                letter.attempts,
+                letter.error.to_string(),
+            ));
+        }
+    }
+
+    fn fast_retry(max_retries: u32) -> RetryPolicy {
+        RetryPolicy::new(
+            rust_common::RetryConfig::default()
+                .with_max_retries(max_retries)
+                .with_initial_delay(Duration::from_millis(1))
+                .without_jitter(),
+        )
+    }
+
+    fn revoked_session() -> CaepEvent {
+        CaepEvent::session_revoked(SubjectIdentifier::session_id("session-123"), None)
+    }
+
+    #[tokio::test]
+    async fn test_panicking_handler_is_dead_lettered_without_blocking_others() {
+        let sink = CollectingSink::default();
+        let store = MockSessionStore::new();
+        let terminated = store.terminated_count.clone();
+        let mut processor = EventProcessor::new().with_dead_letter_sink(sink.clone());
+        processor.register(Box::new(ScriptedHandler::new(|_| {
+            Box::pin(async { panic!("poison event") })
+        })));
+        processor.register(Box::new(SessionRevokedAdapter(SessionRevokedHandler::new(
+            store,
+        ))));
+
+        assert_eq!(processor.process(&revoked_session()).await.unwrap(), 1);
+        assert_eq!(terminated.load(Ordering::SeqCst), 1);
+
+        let letters = sink.letters.lock().unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].0, "scripted");
+        assert_eq!(letters[0].1, 1, "panics are not retried");
+        assert!(letters[0].2.contains("poison event"));
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_handler_is_retried_then_fails() {
+        let handler = ScriptedHandler::new(|_| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            })
+        });
+        let calls = handler.calls.clone();
+        let mut processor = EventProcessor::new();
+        processor.register_with_policy(
+            Box::new(handler),
+            HandlerPolicy::default()
+                .with_timeout(Duration::from_millis(10))
+                .with_retry(fast_retry(2)),
+        );
+
+        let error = processor.process(&revoked_session()).await.unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retryable_error_recovers() {
+        let handler = ScriptedHandler::new(|call| {
+            Box::pin(async move {
+                if call == 0 {
+                    Err(CaepError::NetworkError("connection reset".to_string()))
+                } else {
+                    Ok(())
+                }
+            })
+        });
+        let calls = handler.calls.clone();
+        let mut processor = EventProcessor::new();
+        processor.register_with_policy(
+            Box::new(handler),
+            HandlerPolicy::default().with_retry(fast_retry(3)),
+        );
+
+        assert_eq!(processor.process(&revoked_session()).await.unwrap(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_handler_concurrency_is_bounded() {
+        let handler = ScriptedHandler::new(|_| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok(())
+            })
+        });
+        let max_running = handler.max_running.clone();
+        let mut processor = EventProcessor::new();
+        processor.register_with_policy(
+            Box::new(handler),
+            HandlerPolicy::default().with_max_concurrency(2),
+        );
+
+        let event = revoked_session();
+        let results = tokio::join!(
+            processor.process(&event),
+            processor.process(&event),
+            processor.process(&event),
+            processor.process(&event),
+        );
+        assert!(results.0.is_ok() && results.1.is_ok() && results.2.is_ok() && results.3.is_ok());
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    /// Exposes a typed handler through `DynEventHandler`.
+    struct SessionRevokedAdapter<S: SessionStore>(SessionRevokedHandler<S>);
+
+    impl<S: SessionStore + 'static> DynEventHandler for SessionRevokedAdapter<S> {
+        fn handle_dyn<'a>(
+            &'a self,
+            event: &'a CaepEvent,
+            subject: &'a ResolvedSubject,
+        ) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<()>> + Send + 'a>> {
+            Box::pin(async move { self.0.handle(event, subject).await.map(|_| ()) })
+        }
+
+        fn can_handle(&self, event_type: &CaepEventType) -> bool {
+            self.0.can_handle(event_type)
+        }
+    }
 }
@@ -6,6 +6,9 @@
 //! - SET (Security Event Token) generation and validation with ES256 default
 //! - Event transmission to registered streams with logging integration
 //! - Event reception and processing with cache integration
+//! - SET deduplication by `jti` and per-subject event ordering
+//! - Subject resolution to internal user IDs before handler dispatch
+//! - Handler isolation with bounded concurrency, timeouts, retries and dead letters
 //! - Stream management
 //!
 //! # December 2025 Modernization
@@ -16,10 +19,12 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+pub mod delivery;
 pub mod error;
 pub mod event;
 pub mod handler;
 pub mod receiver;
+pub mod resolver;
 pub mod set;
 pub mod stream;
 pub mod transmitter;
@@ -28,6 +33,7 @@ pub use error::{CaepError, CaepResult};
 pub use event::{CaepEvent, CaepEventType, SubjectIdentifier};
 pub use handler::EventHandler;
 pub use receiver::CaepReceiver;
-pub use set::SecurityEventToken;
+pub use resolver::{ResolvedSubject, SubjectResolver};
+pub use set::{SecurityEventToken, SetProfile};
 pub use stream::{DeliveryMethod, Stream, StreamConfig, StreamStatus};
 pub use transmitter::CaepTransmitter;
@@ -2,6 +2,7 @@
 //!
 //! This module provides the receiver for processing incoming CAEP events using native async traits.
 
+use crate::delivery::{OrderingBuffer, ReceiverMetrics, SetDeduplicator};
 use crate::{CaepError, CaepEvent, CaepEventType, CaepResult, SecurityEventToken, SubjectIdentifier};
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use rust_common::{CacheClient, CacheClientConfig};
@@ -9,7 +10,8 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info, instrument, warn};
+use std::time::Duration;
+use tracing::{debug, error, info, instrument, warn};
 
 /// CAEP Receiver trait for processing incoming security events.
 ///
@@ -32,7 +34,7 @@ pub struct ProcessResult {
     pub event_id: String,
     /// Event type that was processed
     pub event_type: CaepEventType,
-    /// Whether the event was successfully processed
+    /// Whether the event was processed; `false` for dropped duplicates
     pub processed: bool,
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
@@ -207,6 +209,9 @@ pub struct DefaultCaepReceiver {
     expected_audience: String,
     handlers: HashMap<CaepEventType, Vec<BoxedCallback>>,
     retry_config: RetryConfig,
+    dedup: Option<SetDeduplicator>,
+    ordering: Option<OrderingBuffer>,
+    metrics: Arc<ReceiverMetrics>,
 }
 
 impl DefaultCaepReceiver {
@@ -224,6 +229,9 @@ impl DefaultCaepReceiver {
             expected_audience: expected_audience.into(),
             handlers: HashMap::new(),
             retry_config: RetryConfig::default(),
+            dedup: None,
+            ordering: None,
+            metrics: Arc::new(ReceiverMetrics::new()),
         }
     }
 
@@ -241,6 +249,27 @@ impl DefaultCaepReceiver {
         self
     }
 
+    /// Drop SETs whose `jti` was already processed.
+    #[must_use]
+    pub fn with_deduplication(mut self, dedup: SetDeduplicator) -> Self {
+        self.dedup = Some(dedup);
+        self
+    }
+
+    /// Release each subject's events in `event_timestamp` order, holding
+    /// every event for `window` first.
+    #[must_use]
+    pub fn with_ordering_window(mut self, window: Duration) -> Self {
+        self.ordering = Some(OrderingBuffer::new(window));
+        self
+    }
+
+    /// Duplicate and reordering metrics.
+    #[must_use]
+    pub fn metrics(&self) -> Arc<ReceiverMetrics> {
+        self.metrics.clone()
+    }
+
     /// Register a handler for an event type.
     pub fn register_handler(&mut self, event_type: CaepEventType, handler: BoxedCallback) {
         self.handlers
@@ -330,6 +359,36 @@ impl DefaultCaepReceiver {
 
         Err(last_error.unwrap_or_else(|| CaepError::processing("Unknown error")))
     }
+
+    /// Process an event through the ordering buffer.
+    ///
+    /// The event is held for the window, then released with every buffered
+    /// event of its subject timestamped at or before it. An event released by
+    /// another caller reports that caller's processing outcome.
+    async fn process_ordered(&self, buffer: &OrderingBuffer, event: CaepEvent) -> CaepResult<()> {
+        let through = event.event_timestamp;
+        let (queue, outcome) = buffer.enqueue(event).await?;
+        tokio::time::sleep(buffer.window()).await;
+
+        {
+            let mut queue = queue.lock().await;
+            let (released, reordered) = queue.release_through(through);
+            if reordered > 0 {
+                debug!(reordered = reordered, "Released CAEP events out of arrival order");
+                self.metrics.reordered.inc_by(reordered);
+            }
+            for pending in released {
+                let result = self.process_with_retry(&pending.event).await;
+                pending.finish(result);
+            }
+        }
+        drop(queue);
+        buffer.prune();
+
+        outcome
+            .await
+            .unwrap_or_else(|_| Err(CaepError::processing("Buffered event was dropped")))
+    }
 }
 
 impl CaepReceiver for DefaultCaepReceiver {
@@ -340,19 +399,40 @@ impl CaepReceiver for DefaultCaepReceiver {
         // Validate and decode
         let set = self.validate_signature(set_jwt).await?;
         let event = self.parse_event_from_set(&set)?;
+        let event_type = event.event_type.clone();
+
+        if let Some(dedup) = &self.dedup {
+            if !dedup.begin(&set.jti).await {
+                info!(jti = %set.jti, "Dropping duplicate CAEP event");
+                self.metrics.duplicates.inc();
+                return Ok(ProcessResult {
+                    event_id: set.jti,
+                    event_type,
+                    processed: false,
+                    processing_time_ms: start.elapsed().as_millis() as u64,
+                });
+            }
+        }
 
         info!(
-            event_type = ?event.event_type,
+            event_type = ?event_type,
             jti = %set.jti,
             "Processing CAEP event"
         );
 
-        // Process with retry
-        self.process_with_retry(&event).await?;
+        // Process with retry, in subject order when buffering
+        let result = match &self.ordering {
+            Some(buffer) => self.process_ordered(buffer, event).await,
+            None => self.process_with_retry(&event).await,
+        };
+        if let Some(dedup) = &self.dedup {
+            dedup.complete(&set.jti, result.is_ok()).await;
+        }
+        result?;
 
         Ok(ProcessResult {
             event_id: set.jti,
-            event_type: event.event_type,
+            event_type,
             processed: true,
             processing_time_ms: start.elapsed().as_millis() as u64,
         })
@@ -0,0 +1,305 @@
+//! Subject resolution for CAEP events.
+//!
+//! Transmitters identify subjects as `iss`/`sub` pairs, emails, opaque IDs or
+//! sessions. A [`SubjectResolver`] maps them onto the platform's internal user
+//! ID once per event, before handlers are dispatched.
+
+use crate::{CaepError, CaepResult, SubjectIdentifier};
+use rust_common::{CacheClient, CacheClientConfig};
+use serde::Deserialize;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Default TTL of cached resolutions.
+pub const DEFAULT_RESOLUTION_TTL: Duration = Duration::from_secs(300);
+
+/// A subject identifier together with the internal user it resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedSubject {
+    /// Identifier as received from the transmitter
+    pub identifier: SubjectIdentifier,
+    /// Internal user ID, when the subject is known
+    pub user_id: Option<String>,
+}
+
+impl ResolvedSubject {
+    /// Create a resolved subject.
+    #[must_use]
+    pub const fn new(identifier: SubjectIdentifier, user_id: Option<String>) -> Self {
+        Self {
+            identifier,
+            user_id,
+        }
+    }
+
+    /// Session ID, for session subjects.
+    #[must_use]
+    pub fn session_id(&self) -> Option<&str> {
+        match &self.identifier {
+            SubjectIdentifier::SessionId { session_id } => Some(session_id),
+            _ => None,
+        }
+    }
+}
+
+/// Maps CAEP subject identifiers onto internal user IDs.
+///
+/// Uses native async traits (Rust 2024).
+pub trait SubjectResolver: Send + Sync {
+    /// Resolve a subject; unknown subjects resolve without a user ID.
+    fn resolve(
+        &self,
+        subject: &SubjectIdentifier,
+    ) -> impl Future<Output = CaepResult<ResolvedSubject>> + Send;
+}
+
+/// Dynamic subject resolver trait for type erasure.
+pub trait DynSubjectResolver: Send + Sync {
+    /// Resolve a subject.
+    fn resolve_dyn<'a>(
+        &'a self,
+        subject: &'a SubjectIdentifier,
+    ) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<ResolvedSubject>> + Send + 'a>>;
+}
+
+impl<R: SubjectResolver> DynSubjectResolver for R {
+    fn resolve_dyn<'a>(
+        &'a self,
+        subject: &'a SubjectIdentifier,
+    ) -> std::pin::Pin<Box<dyn Future<Output = CaepResult<ResolvedSubject>> + Send + 'a>> {
+        Box::pin(self.resolve(subject))
+    }
+}
+
+/// Treats `sub` and opaque IDs as internal user IDs without a lookup.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PassthroughResolver;
+
+impl SubjectResolver for PassthroughResolver {
+    async fn resolve(&self, subject: &SubjectIdentifier) -> CaepResult<ResolvedSubject> {
+        let user_id = match subject {
+            SubjectIdentifier::IssSub { sub, .. } => Some(sub.clone()),
+            SubjectIdentifier::Opaque { id } => Some(id.clone()),
+            SubjectIdentifier::Email { .. } | SubjectIdentifier::SessionId { .. } => None,
+        };
+        Ok(ResolvedSubject::new(subject.clone(), user_id))
+    }
+}
+
+/// SCIM `ListResponse` subset.
+#[derive(Debug, Deserialize)]
+struct ScimListResponse {
+    #[serde(rename = "totalResults")]
+    total_results: u64,
+    #[serde(rename = "Resources", default)]
+    resources: Vec<ScimUser>,
+}
+
+/// SCIM `User` subset.
+#[derive(Debug, Deserialize)]
+struct ScimUser {
+    id: String,
+}
+
+/// Resolves subjects through the identity service's SCIM `/Users` endpoint.
+///
+/// Subjects issued by the platform itself (`iss` equal to the local issuer)
+/// already carry the internal ID and are not looked up. Emails are matched on
+/// `emails.value`, other `sub` and opaque IDs on `externalId`. Session
+/// subjects resolve without a user ID. Successful lookups are cached in
+/// `Cache_Service` when configured; misses are not cached.
+pub struct IdentityServiceResolver {
+    http_client: reqwest::Client,
+    base_url: String,
+    local_issuer: Option<String>,
+    cache_client: Option<CacheClient>,
+    ttl: Duration,
+}
+
+impl IdentityServiceResolver {
+    /// Create a resolver for the identity service at `base_url`.
+    #[must_use]
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            local_issuer: None,
+            cache_client: None,
+            ttl: DEFAULT_RESOLUTION_TTL,
+        }
+    }
+
+    /// Create a resolver caching resolutions in `Cache_Service`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache client cannot be created.
+    pub async fn with_cache_service(
+        base_url: impl Into<String>,
+        config: CacheClientConfig,
+        ttl: Duration,
+    ) -> CaepResult<Self> {
+        let cache_client = CacheClient::new(config).await?;
+        Ok(Self {
+            cache_client: Some(cache_client),
+            ttl,
+            ..Self::new(base_url)
+        })
+    }
+
+    /// Set the issuer whose `sub` values are internal user IDs.
+    #[must_use]
+    pub fn with_local_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.local_issuer = Some(issuer.into());
+        self
+    }
+
+    /// SCIM filter locating the subject, or `None` when no lookup applies.
+    fn filter_for(&self, subject: &SubjectIdentifier) -> Option<String> {
+        match subject {
+            SubjectIdentifier::IssSub { iss, .. } if self.local_issuer.as_ref() == Some(iss) => {
+                None
+            }
+            SubjectIdentifier::IssSub { sub, .. } => {
+                Some(format!("externalId eq \"{}\"", scim_escape(sub)))
+            }
+            SubjectIdentifier::Opaque { id } => {
+                Some(format!("externalId eq \"{}\"", scim_escape(id)))
+            }
+            SubjectIdentifier::Email { email } => {
+                Some(format!("emails.value eq \"{}\"", scim_escape(email)))
+            }
+            SubjectIdentifier::SessionId { .. } => None,
+        }
+    }
+
+    async fn lookup(&self, filter: &str) -> CaepResult<Option<String>> {
+        let list: ScimListResponse = self
+            .http_client
+            .get(format!("{}/Users", self.base_url))
+            .query(&[("filter", filter), ("attributes", "id")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        match (list.total_results, list.resources.into_iter().next()) {
+            (0, _) | (_, None) => Ok(None),
+            (1, Some(user)) => Ok(Some(user.id)),
+            (total, Some(_)) => Err(CaepError::processing(format!(
+                "Subject matches {total} users"
+            ))),
+        }
+    }
+
+    async fn cached(&self, key: &str) -> Option<String> {
+        let cache = self.cache_client.as_ref()?;
+        match cache.get(key).await {
+            Ok(Some(data)) => String::from_utf8(data).ok(),
+            Ok(None) => None,
+            Err(e) => {
+                warn!(error = %e, "Subject resolution cache read failed");
+                None
+            }
+        }
+    }
+
+    async fn store(&self, key: &str, user_id: &str) {
+        let Some(cache) = &self.cache_client else {
+            return;
+        };
+        if let Err(e) = cache.set(key, user_id.as_bytes(), Some(self.ttl)).await {
+            warn!(error = %e, "Subject resolution cache write failed");
+        }
+    }
+}
+
+impl SubjectResolver for IdentityServiceResolver {
+    async fn resolve(&self, subject: &SubjectIdentifier) -> CaepResult<ResolvedSubject> {
+        let Some(filter) = self.filter_for(subject) else {
+            return PassthroughResolver.resolve(subject).await;
+        };
+
+        let key = format!("subject:{}", serde_json::to_string(subject)?);
+        if let Some(user_id) = self.cached(&key).await {
+            return Ok(ResolvedSubject::new(subject.clone(), Some(user_id)));
+        }
+
+        let user_id = self.lookup(&filter).await?;
+        if let Some(user_id) = &user_id {
+            self.store(&key, user_id).await;
+        } else {
+            debug!(filter = %filter, "CAEP subject not found in identity service");
+        }
+        Ok(ResolvedSubject::new(subject.clone(), user_id))
+    }
+}
+
+/// Escapes a SCIM filter string literal.
+fn scim_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_passthrough_resolver() {
+        let resolved = PassthroughResolver
+            .resolve(&SubjectIdentifier::iss_sub("https://issuer.com", "user-123"))
+            .await
+            .unwrap();
+        assert_eq!(resolved.user_id.as_deref(), Some("user-123"));
+
+        let resolved = PassthroughResolver
+            .resolve(&SubjectIdentifier::session_id("session-123"))
+            .await
+            .unwrap();
+        assert_eq!(resolved.user_id, None);
+        assert_eq!(resolved.session_id(), Some("session-123"));
+    }
+
+    #[test]
+    fn test_identity_service_filters() {
+        let resolver = IdentityServiceResolver::new("http://identity:8080/scim/v2/")
+            .with_local_issuer("https://auth.platform");
+
+        assert_eq!(resolver.base_url, "http://identity:8080/scim/v2");
+        assert_eq!(
+            resolver.filter_for(&SubjectIdentifier::iss_sub("https://auth.platform", "u-1")),
+            None
+        );
+        assert_eq!(
+            resolver
+                .filter_for(&SubjectIdentifier::iss_sub("https://okta.example", "00u1"))
+                .as_deref(),
+            Some(r#"externalId eq "00u1""#)
+        );
+        assert_eq!(
+            resolver
+                .filter_for(&SubjectIdentifier::email("a\"b@example.com"))
+                .as_deref(),
+            Some(r#"emails.value eq "a\"b@example.com""#)
+        );
+        assert_eq!(
+            resolver.filter_for(&SubjectIdentifier::session_id("s-1")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_scim_list_response() {
+        let list: ScimListResponse = serde_json::from_str(
+            r#"{"schemas":["urn:ietf:params:scim:api:messages:2.0:ListResponse"],"totalResults":1,"Resources":[{"id":"u-1"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(list.total_results, 1);
+        assert_eq!(list.resources[0].id, "u-1");
+
+        let empty: ScimListResponse = serde_json::from_str(r#"{"totalResults":0}"#).unwrap();
+        assert!(empty.resources.is_empty());
+    }
+}
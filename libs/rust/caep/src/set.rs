@@ -1,17 +1,33 @@
 //! Security Event Token (SET) implementation per RFC 8417.
 //!
 //! This module provides SET generation and signing with ES256 as the default algorithm.
+//! [`SecurityEventTokenBuilder`] constructs SETs whose claims are validated
+//! against a [`SetProfile`]; `build` is only available once an event was added.
 
 use crate::{CaepError, CaepEvent, CaepResult};
 use chrono::Utc;
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::marker::PhantomData;
 use uuid::Uuid;
 
 /// Default signing algorithm (ES256 per CAEP spec).
 pub const DEFAULT_ALGORITHM: Algorithm = Algorithm::ES256;
 
+/// Maximum seconds `iat` may lie in the future.
+pub const MAX_IAT_SKEW_SECS: i64 = 60;
+
+/// Rules the `events` claim of a SET must satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SetProfile {
+    /// CAEP / Shared Signals: exactly one event per SET
+    #[default]
+    SingleEvent,
+    /// RFC 8417: one or more event types describing the same occurrence
+    MultiEvent,
+}
+
 /// Security Event Token (SET) structure per RFC 8417.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SecurityEventToken {
@@ -25,6 +41,9 @@ pub struct SecurityEventToken {
     pub aud: String,
     /// Events map (event URI -> event data)
     pub events: HashMap<String, serde_json::Value>,
+    /// Transaction identifier correlating SETs about the same occurrence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txn: Option<String>,
 }
 
 impl SecurityEventToken {
@@ -32,14 +51,7 @@ impl SecurityEventToken {
     #[must_use]
     pub fn from_event(event: &CaepEvent, issuer: &str, audience: &str) -> Self {
         let mut events = HashMap::new();
-
-        let event_data = serde_json::json!({
-            "subject": event.subject,
-            "event_timestamp": event.event_timestamp.timestamp(),
-            "reason_admin": event.reason_admin
-        });
-
-        events.insert(event.event_type.uri().to_string(), event_data);
+        events.insert(event.event_type.uri().to_string(), event_payload(event));
 
         Self {
             iss: issuer.to_string(),
@@ -47,7 +59,66 @@ impl SecurityEventToken {
             jti: Uuid::new_v4().to_string(),
             aud: audience.to_string(),
             events,
+            txn: None,
+        }
+    }
+
+    /// Start a validated SET for `issuer` and `audience`.
+    #[must_use]
+    pub fn builder(
+        issuer: impl Into<String>,
+        audience: impl Into<String>,
+    ) -> SecurityEventTokenBuilder<NoEvents> {
+        SecurityEventTokenBuilder::new(issuer, audience)
+    }
+
+    /// Check the required SET claims against `profile`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CaepError::InvalidSet`] if `iss`, `jti` or `aud` is empty,
+    /// `iat` is not positive or too far in the future, `txn` is empty, an
+    /// event URI is not absolute, an event payload is not a JSON object, or
+    /// the number of events violates the profile.
+    pub fn validate(&self, profile: SetProfile) -> CaepResult<()> {
+        if self.iss.is_empty() {
+            return Err(CaepError::invalid_set("iss must not be empty"));
+        }
+        if self.jti.is_empty() {
+            return Err(CaepError::invalid_set("jti must not be empty"));
+        }
+        if self.aud.is_empty() {
+            return Err(CaepError::invalid_set("aud must not be empty"));
+        }
+        if self.iat <= 0 || self.iat > Utc::now().timestamp() + MAX_IAT_SKEW_SECS {
+            return Err(CaepError::invalid_set(format!("iat {} is out of range", self.iat)));
+        }
+        if self.txn.as_deref().is_some_and(str::is_empty) {
+            return Err(CaepError::invalid_set("txn must not be empty"));
         }
+
+        match (profile, self.events.len()) {
+            (_, 0) => return Err(CaepError::invalid_set("events must not be empty")),
+            (SetProfile::SingleEvent, n) if n > 1 => {
+                return Err(CaepError::invalid_set(format!(
+                    "profile allows one event per SET, found {n}"
+                )));
+            }
+            _ => {}
+        }
+        for (uri, payload) in &self.events {
+            if !is_absolute_uri(uri) {
+                return Err(CaepError::invalid_set(format!(
+                    "event type {uri} is not an absolute URI"
+                )));
+            }
+            if !payload.is_object() {
+                return Err(CaepError::invalid_set(format!(
+                    "payload of event {uri} is not a JSON object"
+                )));
+            }
+        }
+        Ok(())
     }
 
     /// Sign the SET using the default ES256 algorithm.
@@ -98,6 +169,157 @@ impl SecurityEventToken {
     }
 }
 
+/// Builder state before any event was added.
+#[derive(Debug, Clone, Copy)]
+pub struct NoEvents;
+
+/// Builder state once at least one event was added.
+#[derive(Debug, Clone, Copy)]
+pub struct WithEvents;
+
+/// Builder for a single validated SET.
+///
+/// `iss` and `aud` are required by [`SecurityEventToken::builder`]; `build`
+/// only exists once an event was added. `iat` and `jti` default to the
+/// current time and a random UUID.
+#[derive(Debug, Clone)]
+pub struct SecurityEventTokenBuilder<E = NoEvents> {
+    iss: String,
+    aud: String,
+    iat: Option<i64>,
+    jti: Option<String>,
+    txn: Option<String>,
+    profile: SetProfile,
+    events: HashMap<String, serde_json::Value>,
+    duplicate: Option<String>,
+    state: PhantomData<E>,
+}
+
+impl SecurityEventTokenBuilder<NoEvents> {
+    /// Create a builder for `issuer` and `audience`.
+    #[must_use]
+    pub fn new(issuer: impl Into<String>, audience: impl Into<String>) -> Self {
+        Self {
+            iss: issuer.into(),
+            aud: audience.into(),
+            iat: None,
+            jti: None,
+            txn: None,
+            profile: SetProfile::default(),
+            events: HashMap::new(),
+            duplicate: None,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<E> SecurityEventTokenBuilder<E> {
+    /// Set the issued-at timestamp.
+    #[must_use]
+    pub const fn iat(mut self, iat: i64) -> Self {
+        self.iat = Some(iat);
+        self
+    }
+
+    /// Set the JWT ID.
+    #[must_use]
+    pub fn jti(mut self, jti: impl Into<String>) -> Self {
+        self.jti = Some(jti.into());
+        self
+    }
+
+    /// Set the transaction identifier.
+    #[must_use]
+    pub fn txn(mut self, txn: impl Into<String>) -> Self {
+        self.txn = Some(txn.into());
+        self
+    }
+
+    /// Set the profile the SET is validated against.
+    #[must_use]
+    pub const fn profile(mut self, profile: SetProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Add an event with a raw payload.
+    #[must_use]
+    pub fn event(
+        mut self,
+        uri: impl Into<String>,
+        payload: serde_json::Value,
+    ) -> SecurityEventTokenBuilder<WithEvents> {
+        let uri = uri.into();
+        if self.events.contains_key(&uri) {
+            self.duplicate.get_or_insert_with(|| uri.clone());
+        }
+        self.events.insert(uri, payload);
+        SecurityEventTokenBuilder {
+            iss: self.iss,
+            aud: self.aud,
+            iat: self.iat,
+            jti: self.jti,
+            txn: self.txn,
+            profile: self.profile,
+            events: self.events,
+            duplicate: self.duplicate,
+            state: PhantomData,
+        }
+    }
+
+    /// Add a CAEP event.
+    #[must_use]
+    pub fn caep_event(self, event: &CaepEvent) -> SecurityEventTokenBuilder<WithEvents> {
+        self.event(event.event_type.uri(), event_payload(event))
+    }
+}
+
+impl SecurityEventTokenBuilder<WithEvents> {
+    /// Build and validate the SET.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CaepError::InvalidSet`] if an event type was added twice or
+    /// the claims fail [`SecurityEventToken::validate`].
+    pub fn build(self) -> CaepResult<SecurityEventToken> {
+        if let Some(uri) = self.duplicate {
+            return Err(CaepError::invalid_set(format!("event type {uri} added twice")));
+        }
+
+        let set = SecurityEventToken {
+            iss: self.iss,
+            iat: self.iat.unwrap_or_else(|| Utc::now().timestamp()),
+            jti: self.jti.unwrap_or_else(|| Uuid::new_v4().to_string()),
+            aud: self.aud,
+            events: self.events,
+            txn: self.txn,
+        };
+        set.validate(self.profile)?;
+        Ok(set)
+    }
+}
+
+/// Event payload carried in the `events` claim.
+fn event_payload(event: &CaepEvent) -> serde_json::Value {
+    serde_json::json!({
+        "subject": event.subject,
+        "event_timestamp": event.event_timestamp.timestamp(),
+        "reason_admin": event.reason_admin
+    })
+}
+
+/// Whether `uri` starts with an RFC 3986 scheme.
+fn is_absolute_uri(uri: &str) -> bool {
+    let Some((scheme, rest)) = uri.split_once(':') else {
+        return false;
+    };
+    !rest.is_empty()
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
 /// SET Builder for fluent construction.
 pub struct SetBuilder {
     issuer: String,
@@ -138,12 +360,7 @@ impl SetBuilder {
         let mut events = HashMap::new();
 
         for event in &self.events {
-            let event_data = serde_json::json!({
-                "subject": event.subject,
-                "event_timestamp": event.event_timestamp.timestamp(),
-                "reason_admin": event.reason_admin
-            });
-            events.insert(event.event_type.uri().to_string(), event_data);
+            events.insert(event.event_type.uri().to_string(), event_payload(event));
         }
 
         SecurityEventToken {
@@ -152,6 +369,7 @@ impl SetBuilder {
             jti: Uuid::new_v4().to_string(),
             aud: self.audience,
             events,
+            txn: None,
         }
     }
 }
@@ -230,4 +448,71 @@ mod tests {
         assert_eq!(set.aud, parsed.aud);
         assert_eq!(set.events.len(), parsed.events.len());
     }
+
+    #[test]
+    fn test_typed_builder_validates_claims() {
+        let subject = SubjectIdentifier::opaque("user-abc");
+        let event = CaepEvent::session_revoked(subject, None);
+
+        let set = SecurityEventToken::builder("https://issuer.com", "https://audience.com")
+            .txn("txn-1")
+            .caep_event(&event)
+            .build()
+            .unwrap();
+        assert_eq!(set.txn.as_deref(), Some("txn-1"));
+        assert!(!set.jti.is_empty());
+        assert!(set.validate(SetProfile::SingleEvent).is_ok());
+
+        let missing_aud = SecurityEventToken::builder("https://issuer.com", "")
+            .caep_event(&event)
+            .build();
+        assert!(matches!(missing_aud, Err(CaepError::InvalidSet(_))));
+
+        let empty_txn = SecurityEventToken::builder("https://issuer.com", "aud")
+            .txn("")
+            .caep_event(&event)
+            .build();
+        assert!(matches!(empty_txn, Err(CaepError::InvalidSet(_))));
+
+        let future = SecurityEventToken::builder("https://issuer.com", "aud")
+            .iat(Utc::now().timestamp() + 3600)
+            .caep_event(&event)
+            .build();
+        assert!(matches!(future, Err(CaepError::InvalidSet(_))));
+    }
+
+    #[test]
+    fn test_typed_builder_events_structure() {
+        let subject = SubjectIdentifier::email("user@example.com");
+        let revoked = CaepEvent::session_revoked(subject.clone(), None);
+        let changed = CaepEvent::credential_change(subject, "update", "password");
+        let builder = || SecurityEventToken::builder("https://issuer.com", "aud");
+
+        let single = builder().caep_event(&revoked).caep_event(&changed).build();
+        assert!(matches!(single, Err(CaepError::InvalidSet(_))));
+
+        let multi = builder()
+            .profile(SetProfile::MultiEvent)
+            .caep_event(&revoked)
+            .caep_event(&changed)
+            .build()
+            .unwrap();
+        assert_eq!(multi.event_count(), 2);
+        assert!(multi.validate(SetProfile::SingleEvent).is_err());
+
+        let twice = builder()
+            .profile(SetProfile::MultiEvent)
+            .caep_event(&revoked)
+            .caep_event(&revoked)
+            .build();
+        assert!(matches!(twice, Err(CaepError::InvalidSet(_))));
+
+        let relative = builder().event("session-revoked", serde_json::json!({})).build();
+        assert!(matches!(relative, Err(CaepError::InvalidSet(_))));
+
+        let scalar = builder()
+            .event("urn:example:event", serde_json::json!("revoked"))
+            .build();
+        assert!(matches!(scalar, Err(CaepError::InvalidSet(_))));
+    }
 }
@@ -187,7 +187,9 @@ impl CaepTransmitter for DefaultCaepTransmitter {
         let mut delivery_times = Vec::new();
 
         for stream in active_streams {
-            let set = SecurityEventToken::from_event(&event, &self.issuer, &stream.config.audience);
+            let set = SecurityEventToken::builder(&self.issuer, &stream.config.audience)
+                .caep_event(&event)
+                .build()?;
             let signed_set = set.sign(&self.signing_key)?;
 
             match self.deliver_to_stream(stream, &signed_set).await {
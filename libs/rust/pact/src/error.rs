@@ -0,0 +1,15 @@
+//! Pact error types.
+
+use thiserror::Error;
+
+/// Pact contract errors.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PactError {
+    /// Interaction is incomplete or inconsistent
+    #[error("Invalid interaction: {0}")]
+    InvalidInteraction(String),
+
+    /// Pact Broker query failed
+    #[error("Pact Broker request failed: {0}")]
+    Broker(String),
+}
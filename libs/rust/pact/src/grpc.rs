@@ -0,0 +1,734 @@
+//! gRPC interaction builder.
+//!
+//! Builds Pact V4 `Synchronous/Messages` interactions for the protobuf
+//! plugin, as stored by the Pact Broker: messages are described by their
+//! JSON form, with matching rules keyed by `$.field` paths.
+//!
+//! ```
+//! use auth_pact::grpc::{GrpcInteraction, GrpcStatus, Matcher};
+//! use serde_json::json;
+//!
+//! let interaction = GrpcInteraction::builder("auth.edge.v1.AuthEdgeService", "ValidateToken")
+//!     .description("an expired token")
+//!     .given("token is expired")
+//!     .request_message("auth.edge.v1.ValidateTokenRequest")
+//!     .request_field("token", json!("eyJhbGciOiJFUzI1NiJ9"), Matcher::like())
+//!     .error(GrpcStatus::Unauthenticated, "token expired")
+//!     .build()
+//!     .unwrap();
+//! assert!(interaction.response[0].is_error());
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::contract::{PactSpecification, Participant};
+use crate::error::PactError;
+
+/// Interaction type of synchronous gRPC calls in Pact V4.
+pub const SYNCHRONOUS_MESSAGES: &str = "Synchronous/Messages";
+
+/// Name of the Pact protobuf plugin.
+pub const PROTOBUF_PLUGIN: &str = "protobuf";
+
+/// Metadata key carrying the gRPC status.
+pub const GRPC_STATUS: &str = "grpc-status";
+
+/// Metadata key carrying the gRPC status message.
+pub const GRPC_MESSAGE: &str = "grpc-message";
+
+/// Pact matcher applied to a message field.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "match", rename_all = "lowercase")]
+pub enum Matcher {
+    /// Value must equal the example
+    Equality,
+    /// Value must have the example's type (arrays: at least `min` items)
+    Type {
+        /// Minimum array length
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<usize>,
+    },
+    /// String must match the regular expression
+    Regex {
+        /// Regular expression
+        regex: String,
+    },
+    /// Value must be an integer
+    Integer,
+    /// Value must be a decimal number
+    Decimal,
+    /// Value must be a boolean
+    Boolean,
+    /// String must contain the value
+    Include {
+        /// Expected substring
+        value: String,
+    },
+}
+
+impl Matcher {
+    /// Match by type.
+    #[must_use]
+    pub const fn like() -> Self {
+        Self::Type { min: None }
+    }
+
+    /// Match an array whose items have the example's type.
+    #[must_use]
+    pub const fn each_like(min: usize) -> Self {
+        Self::Type { min: Some(min) }
+    }
+
+    /// Match strings against a regular expression.
+    #[must_use]
+    pub fn regex(regex: impl Into<String>) -> Self {
+        Self::Regex {
+            regex: regex.into(),
+        }
+    }
+
+    /// Match strings containing `value`.
+    #[must_use]
+    pub fn include(value: impl Into<String>) -> Self {
+        Self::Include {
+            value: value.into(),
+        }
+    }
+
+    /// Check that the example satisfies the matcher.
+    fn check(&self, example: &Value) -> Result<(), String> {
+        let ok = match self {
+            Self::Equality | Self::Type { min: None } => true,
+            Self::Type { min: Some(min) } => example.as_array().is_some_and(|a| a.len() >= *min),
+            Self::Regex { regex } => !regex.is_empty() && example.is_string(),
+            Self::Integer => example.is_i64() || example.is_u64(),
+            Self::Decimal => example.is_f64(),
+            Self::Boolean => example.is_boolean(),
+            Self::Include { value } => example.as_str().is_some_and(|s| s.contains(value.as_str())),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("example {example} does not satisfy {self:?}"))
+        }
+    }
+}
+
+/// Matchers for one path, all of which must pass.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MatcherList {
+    /// How the matchers are combined
+    pub combine: String,
+    /// Matchers
+    pub matchers: Vec<Matcher>,
+}
+
+/// Matching rules of a message.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MatchingRules {
+    /// Rules on message fields, keyed by `$.field` path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub body: BTreeMap<String, MatcherList>,
+    /// Rules on metadata entries, keyed by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, MatcherList>,
+}
+
+impl MatchingRules {
+    /// Check whether no rules are set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.body.is_empty() && self.metadata.is_empty()
+    }
+
+    fn add(rules: &mut BTreeMap<String, MatcherList>, path: String, matcher: Matcher) {
+        rules
+            .entry(path)
+            .or_insert_with(|| MatcherList {
+                combine: "AND".to_string(),
+                matchers: Vec::new(),
+            })
+            .matchers
+            .push(matcher);
+    }
+}
+
+/// Message body and its protobuf type.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageContents {
+    /// Message in JSON form
+    pub content: Value,
+    /// `application/protobuf;message=<type>`
+    pub content_type: String,
+    /// Whether `content` is encoded (JSON contents are not)
+    pub encoded: bool,
+}
+
+/// Request or response message of a gRPC interaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcMessage {
+    /// Message contents (absent for error responses)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<MessageContents>,
+    /// gRPC metadata
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub metadata: Map<String, Value>,
+    /// Matching rules
+    #[serde(default, skip_serializing_if = "MatchingRules::is_empty")]
+    pub matching_rules: MatchingRules,
+}
+
+impl GrpcMessage {
+    /// gRPC status of the message, if set.
+    #[must_use]
+    pub fn status(&self) -> Option<&str> {
+        self.metadata.get(GRPC_STATUS).and_then(Value::as_str)
+    }
+
+    /// Check if the message is an error status.
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        self.status().is_some_and(|s| s != GrpcStatus::Ok.as_str())
+    }
+}
+
+/// Provider state of an interaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderState {
+    /// State name
+    pub name: String,
+}
+
+/// Protobuf plugin configuration of an interaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProtobufConfiguration {
+    /// `package.Service/Method`
+    pub service: String,
+}
+
+/// Plugin configuration of an interaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PluginConfiguration {
+    /// Protobuf plugin configuration
+    pub protobuf: ProtobufConfiguration,
+}
+
+/// A gRPC interaction in a Pact V4 contract.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcInteraction {
+    /// Interaction type (`Synchronous/Messages`)
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Interaction description
+    pub description: String,
+    /// Provider states (preconditions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provider_states: Vec<ProviderState>,
+    /// Transport (`grpc`)
+    pub transport: String,
+    /// Plugin configuration
+    pub plugin_configuration: PluginConfiguration,
+    /// Expected request
+    pub request: GrpcMessage,
+    /// Expected responses
+    pub response: Vec<GrpcMessage>,
+}
+
+impl GrpcInteraction {
+    /// Start building an interaction for `service`/`method`.
+    #[must_use]
+    pub fn builder(
+        service: impl Into<String>,
+        method: impl Into<String>,
+    ) -> GrpcInteractionBuilder {
+        GrpcInteractionBuilder {
+            service: service.into(),
+            method: method.into(),
+            description: String::new(),
+            provider_states: Vec::new(),
+            request: MessageDraft::default(),
+            response: MessageDraft::default(),
+            error: None,
+            invalid: None,
+        }
+    }
+
+    /// Fully qualified method (`package.Service/Method`).
+    #[must_use]
+    pub fn method(&self) -> &str {
+        &self.plugin_configuration.protobuf.service
+    }
+}
+
+/// gRPC status codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrpcStatus {
+    /// Success
+    Ok,
+    /// Cancelled by the caller
+    Cancelled,
+    /// Unknown error
+    Unknown,
+    /// Invalid argument
+    InvalidArgument,
+    /// Deadline exceeded
+    DeadlineExceeded,
+    /// Entity not found
+    NotFound,
+    /// Entity already exists
+    AlreadyExists,
+    /// Permission denied
+    PermissionDenied,
+    /// Resource exhausted
+    ResourceExhausted,
+    /// Failed precondition
+    FailedPrecondition,
+    /// Aborted
+    Aborted,
+    /// Out of range
+    OutOfRange,
+    /// Unimplemented
+    Unimplemented,
+    /// Internal error
+    Internal,
+    /// Unavailable
+    Unavailable,
+    /// Data loss
+    DataLoss,
+    /// Unauthenticated
+    Unauthenticated,
+}
+
+impl GrpcStatus {
+    /// Status name as written to `grpc-status`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Cancelled => "CANCELLED",
+            Self::Unknown => "UNKNOWN",
+            Self::InvalidArgument => "INVALID_ARGUMENT",
+            Self::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            Self::NotFound => "NOT_FOUND",
+            Self::AlreadyExists => "ALREADY_EXISTS",
+            Self::PermissionDenied => "PERMISSION_DENIED",
+            Self::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            Self::FailedPrecondition => "FAILED_PRECONDITION",
+            Self::Aborted => "ABORTED",
+            Self::OutOfRange => "OUT_OF_RANGE",
+            Self::Unimplemented => "UNIMPLEMENTED",
+            Self::Internal => "INTERNAL",
+            Self::Unavailable => "UNAVAILABLE",
+            Self::DataLoss => "DATA_LOSS",
+            Self::Unauthenticated => "UNAUTHENTICATED",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MessageDraft {
+    message_type: Option<String>,
+    content: Map<String, Value>,
+    metadata: Map<String, Value>,
+    rules: MatchingRules,
+}
+
+impl MessageDraft {
+    /// Insert `value` at the dotted `path`, creating nested messages.
+    fn field(&mut self, path: &str, value: Value, matcher: Option<Matcher>) -> Result<(), String> {
+        if path.is_empty() || path.split('.').any(str::is_empty) {
+            return Err(format!("invalid field path '{path}'"));
+        }
+        if let Some(matcher) = &matcher {
+            matcher.check(&value).map_err(|e| format!("{path}: {e}"))?;
+        }
+
+        let mut segments = path.split('.').peekable();
+        let mut message = &mut self.content;
+        while let Some(segment) = segments.next() {
+            if segments.peek().is_none() {
+                message.insert(segment.to_string(), value);
+                break;
+            }
+            let nested = message
+                .entry(segment.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            message = nested
+                .as_object_mut()
+                .ok_or_else(|| format!("'{segment}' in '{path}' is not a message"))?;
+        }
+
+        if let Some(matcher) = matcher {
+            MatchingRules::add(&mut self.rules.body, format!("$.{path}"), matcher);
+        }
+        Ok(())
+    }
+
+    fn finish(self, side: &str) -> Result<GrpcMessage, String> {
+        let message_type = self
+            .message_type
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| format!("{side} message type is required"))?;
+        Ok(GrpcMessage {
+            contents: Some(MessageContents {
+                content: Value::Object(self.content),
+                content_type: format!("application/protobuf;message={message_type}"),
+                encoded: false,
+            }),
+            metadata: self.metadata,
+            matching_rules: self.rules,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.message_type.is_none() && self.content.is_empty() && self.rules.body.is_empty()
+    }
+}
+
+/// Fluent builder for [`GrpcInteraction`].
+///
+/// Field errors are reported by [`build`](Self::build).
+#[derive(Debug)]
+pub struct GrpcInteractionBuilder {
+    service: String,
+    method: String,
+    description: String,
+    provider_states: Vec<ProviderState>,
+    request: MessageDraft,
+    response: MessageDraft,
+    error: Option<(GrpcStatus, String)>,
+    invalid: Option<String>,
+}
+
+impl GrpcInteractionBuilder {
+    /// Set the interaction description.
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Add a provider state.
+    #[must_use]
+    pub fn given(mut self, state: impl Into<String>) -> Self {
+        self.provider_states
+            .push(ProviderState { name: state.into() });
+        self
+    }
+
+    /// Set the fully qualified request message type.
+    #[must_use]
+    pub fn request_message(mut self, message_type: impl Into<String>) -> Self {
+        self.request.message_type = Some(message_type.into());
+        self
+    }
+
+    /// Add a request field at a dotted path, checked with `matcher`.
+    #[must_use]
+    pub fn request_field(self, path: &str, example: Value, matcher: Matcher) -> Self {
+        self.with_field(true, path, example, Some(matcher))
+    }
+
+    /// Add a request field that must equal `value`.
+    #[must_use]
+    pub fn request_value(self, path: &str, value: Value) -> Self {
+        self.with_field(true, path, value, None)
+    }
+
+    /// Add request metadata.
+    #[must_use]
+    pub fn request_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request
+            .metadata
+            .insert(key.into(), Value::String(value.into()));
+        self
+    }
+
+    /// Set the fully qualified response message type.
+    #[must_use]
+    pub fn response_message(mut self, message_type: impl Into<String>) -> Self {
+        self.response.message_type = Some(message_type.into());
+        self
+    }
+
+    /// Add a response field at a dotted path, checked with `matcher`.
+    #[must_use]
+    pub fn response_field(self, path: &str, example: Value, matcher: Matcher) -> Self {
+        self.with_field(false, path, example, Some(matcher))
+    }
+
+    /// Add a response field that must equal `value`.
+    #[must_use]
+    pub fn response_value(self, path: &str, value: Value) -> Self {
+        self.with_field(false, path, value, None)
+    }
+
+    /// Expect the call to fail with `status` instead of a response message.
+    #[must_use]
+    pub fn error(mut self, status: GrpcStatus, message: impl Into<String>) -> Self {
+        self.error = Some((status, message.into()));
+        self
+    }
+
+    fn with_field(
+        mut self,
+        request: bool,
+        path: &str,
+        value: Value,
+        matcher: Option<Matcher>,
+    ) -> Self {
+        if self.invalid.is_none() {
+            let draft = if request {
+                &mut self.request
+            } else {
+                &mut self.response
+            };
+            if let Err(e) = draft.field(path, value, matcher) {
+                self.invalid = Some(e);
+            }
+        }
+        self
+    }
+
+    /// Build the interaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PactError::InvalidInteraction` if the service, method,
+    /// description or a message type is missing, a field example does not
+    /// satisfy its matcher, or an error status is combined with a response
+    /// message.
+    pub fn build(self) -> Result<GrpcInteraction, PactError> {
+        let invalid = PactError::InvalidInteraction;
+        if let Some(e) = self.invalid {
+            return Err(invalid(e));
+        }
+        if self.service.is_empty() || self.method.is_empty() {
+            return Err(invalid("service and method are required".to_string()));
+        }
+        if self.description.is_empty() {
+            return Err(invalid("description is required".to_string()));
+        }
+
+        let response = match self.error {
+            Some((GrpcStatus::Ok, _)) => {
+                return Err(invalid("error status cannot be OK".to_string()));
+            }
+            Some(_) if !self.response.is_empty() => {
+                return Err(invalid(
+                    "error status cannot be combined with a response message".to_string(),
+                ));
+            }
+            Some((status, message)) => {
+                let mut metadata = Map::new();
+                metadata.insert(GRPC_STATUS.to_string(), Value::from(status.as_str()));
+                if !message.is_empty() {
+                    metadata.insert(GRPC_MESSAGE.to_string(), Value::String(message));
+                }
+                GrpcMessage {
+                    contents: None,
+                    metadata,
+                    matching_rules: MatchingRules::default(),
+                }
+            }
+            None => self.response.finish("response").map_err(invalid)?,
+        };
+
+        Ok(GrpcInteraction {
+            kind: SYNCHRONOUS_MESSAGES.to_string(),
+            description: self.description,
+            provider_states: self.provider_states,
+            transport: "grpc".to_string(),
+            plugin_configuration: PluginConfiguration {
+                protobuf: ProtobufConfiguration {
+                    service: format!("{}/{}", self.service, self.method),
+                },
+            },
+            request: self.request.finish("request").map_err(invalid)?,
+            response: vec![response],
+        })
+    }
+}
+
+/// Plugin used by a contract.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PluginInfo {
+    /// Plugin name
+    pub name: String,
+    /// Plugin version
+    pub version: String,
+}
+
+/// Metadata of a gRPC contract.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcContractMetadata {
+    /// Pact specification version (4.0)
+    pub pact_specification: PactSpecification,
+    /// Plugins needed to verify the contract
+    pub plugins: Vec<PluginInfo>,
+}
+
+/// A Pact V4 contract of gRPC interactions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GrpcContract {
+    /// Consumer participant
+    pub consumer: Participant,
+    /// Provider participant
+    pub provider: Participant,
+    /// Contract interactions
+    pub interactions: Vec<GrpcInteraction>,
+    /// Contract metadata
+    pub metadata: GrpcContractMetadata,
+}
+
+impl GrpcContract {
+    /// Create an empty contract verified with protobuf plugin `plugin_version`.
+    #[must_use]
+    pub fn new(
+        consumer: impl Into<String>,
+        provider: impl Into<String>,
+        plugin_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            consumer: Participant::new(consumer),
+            provider: Participant::new(provider),
+            interactions: Vec::new(),
+            metadata: GrpcContractMetadata {
+                pact_specification: PactSpecification {
+                    version: "4.0".to_string(),
+                },
+                plugins: vec![PluginInfo {
+                    name: PROTOBUF_PLUGIN.to_string(),
+                    version: plugin_version.into(),
+                }],
+            },
+        }
+    }
+
+    /// Add an interaction.
+    #[must_use]
+    pub fn interaction(mut self, interaction: GrpcInteraction) -> Self {
+        self.interactions.push(interaction);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn validate_token() -> GrpcInteractionBuilder {
+        GrpcInteraction::builder("auth.edge.v1.AuthEdgeService", "ValidateToken")
+            .description("a valid token")
+            .given("signing key exists")
+            .request_message("auth.edge.v1.ValidateTokenRequest")
+            .request_field("token", json!("eyJhbGciOiJFUzI1NiJ9"), Matcher::like())
+            .request_value("required_claims", json!(["sub"]))
+    }
+
+    #[test]
+    fn test_builds_plugin_interaction() {
+        let interaction = validate_token()
+            .response_message("auth.edge.v1.ValidateTokenResponse")
+            .response_value("valid", json!(true))
+            .response_field("claims.sub", json!("user-123"), Matcher::regex("^user-"))
+            .response_field("scopes", json!(["read"]), Matcher::each_like(1))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            interaction.method(),
+            "auth.edge.v1.AuthEdgeService/ValidateToken"
+        );
+        let json = serde_json::to_value(&interaction).unwrap();
+        assert_eq!(json["type"], "Synchronous/Messages");
+        assert_eq!(json["providerStates"][0]["name"], "signing key exists");
+        assert_eq!(
+            json["request"]["contents"]["contentType"],
+            "application/protobuf;message=auth.edge.v1.ValidateTokenRequest"
+        );
+        assert_eq!(
+            json["request"]["matchingRules"]["body"]["$.token"],
+            json!({"combine": "AND", "matchers": [{"match": "type"}]})
+        );
+        assert_eq!(
+            json["response"][0]["contents"]["content"]["claims"]["sub"],
+            "user-123"
+        );
+        assert_eq!(
+            json["response"][0]["matchingRules"]["body"]["$.scopes"]["matchers"][0],
+            json!({"match": "type", "min": 1})
+        );
+
+        let restored: GrpcInteraction = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, interaction);
+    }
+
+    #[test]
+    fn test_error_status() {
+        let interaction = validate_token()
+            .error(GrpcStatus::Unauthenticated, "token expired")
+            .build()
+            .unwrap();
+        let response = &interaction.response[0];
+        assert!(response.is_error());
+        assert_eq!(response.status(), Some("UNAUTHENTICATED"));
+        assert!(response.contents.is_none());
+
+        let err = validate_token()
+            .response_message("auth.edge.v1.ValidateTokenResponse")
+            .error(GrpcStatus::NotFound, "")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("combined"));
+        assert!(validate_token().error(GrpcStatus::Ok, "").build().is_err());
+    }
+
+    #[test]
+    fn test_invalid_interactions() {
+        assert!(validate_token().build().is_err());
+        assert!(
+            validate_token()
+                .response_message("auth.edge.v1.ValidateTokenResponse")
+                .response_field("expires_in", json!("soon"), Matcher::Integer)
+                .build()
+                .is_err()
+        );
+        assert!(
+            validate_token()
+                .request_value("token.kid", json!("k1"))
+                .response_message("auth.edge.v1.ValidateTokenResponse")
+                .build()
+                .is_err()
+        );
+        assert!(
+            GrpcInteraction::builder("auth.edge.v1.AuthEdgeService", "ValidateToken")
+                .request_message("auth.edge.v1.ValidateTokenRequest")
+                .response_message("auth.edge.v1.ValidateTokenResponse")
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_contract_metadata() {
+        let contract = GrpcContract::new("auth-edge-service", "token-service", "0.4.0")
+            .interaction(
+                validate_token()
+                    .error(GrpcStatus::Unavailable, "")
+                    .build()
+                    .unwrap(),
+            );
+        let json = serde_json::to_value(&contract).unwrap();
+        assert_eq!(json["metadata"]["pactSpecification"]["version"], "4.0");
+        assert_eq!(json["metadata"]["plugins"][0]["name"], "protobuf");
+        assert_eq!(json["interactions"].as_array().unwrap().len(), 1);
+    }
+}
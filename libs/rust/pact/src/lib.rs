@@ -11,9 +11,15 @@
 #![warn(missing_docs)]
 
 pub mod contract;
+pub mod error;
+pub mod grpc;
 pub mod matrix;
 pub mod verification;
 
 pub use contract::{Contract, ContractMetadata, Interaction, PactSpecification, Participant, Request, Response};
-pub use matrix::{CanIDeployResult, MatrixEntry};
+pub use error::PactError;
+pub use grpc::{GrpcContract, GrpcInteraction, GrpcStatus, Matcher};
+pub use matrix::{
+    BlockReason, CanIDeployResult, DeploymentGate, DeploymentReport, MatrixEntry, MatrixSource,
+};
 pub use verification::{ContractVersion, VerificationResult};
@@ -1,7 +1,21 @@
 //! Pact Broker matrix types for can-i-deploy.
+//!
+//! [`DeploymentGate`] wraps can-i-deploy checks for release tooling: matrix
+//! queries are retried on transient broker errors and cached per
+//! pacticipant version and environment, environment aliases are resolved,
+//! and every blocking row is reported as a typed [`BlockReason`].
 
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use rust_common::{PlatformError, RetryPolicy};
 use serde::{Deserialize, Serialize};
 
+use crate::error::PactError;
+
 /// Result of can-i-deploy check.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanIDeployResult {
@@ -55,6 +69,14 @@ pub struct MatrixEntry {
 }
 
 impl MatrixEntry {
+    /// Check if the provider has verified the consumer version.
+    ///
+    /// The broker reports no provider version for unverified rows.
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        !self.provider_version.is_empty()
+    }
+
     /// Create a new matrix entry.
     #[must_use]
     pub fn new(
@@ -74,9 +96,368 @@ impl MatrixEntry {
     }
 }
 
+/// Why a deployment is blocked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockReason {
+    /// The provider has not verified the consumer version
+    MissingVerification {
+        /// Consumer name
+        consumer: String,
+        /// Consumer version
+        consumer_version: String,
+        /// Provider name
+        provider: String,
+    },
+    /// The provider's verification of the consumer version failed
+    VerificationFailed {
+        /// Consumer name
+        consumer: String,
+        /// Consumer version
+        consumer_version: String,
+        /// Provider name
+        provider: String,
+        /// Provider version that failed verification
+        provider_version: String,
+    },
+}
+
+impl BlockReason {
+    fn from_entry(entry: &MatrixEntry) -> Option<Self> {
+        if entry.success {
+            None
+        } else if entry.is_verified() {
+            Some(Self::VerificationFailed {
+                consumer: entry.consumer.clone(),
+                consumer_version: entry.consumer_version.clone(),
+                provider: entry.provider.clone(),
+                provider_version: entry.provider_version.clone(),
+            })
+        } else {
+            Some(Self::MissingVerification {
+                consumer: entry.consumer.clone(),
+                consumer_version: entry.consumer_version.clone(),
+                provider: entry.provider.clone(),
+            })
+        }
+    }
+}
+
+impl fmt::Display for BlockReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingVerification {
+                consumer,
+                consumer_version,
+                provider,
+            } => write!(
+                f,
+                "{provider} has not verified {consumer} {consumer_version}"
+            ),
+            Self::VerificationFailed {
+                consumer,
+                consumer_version,
+                provider,
+                provider_version,
+            } => write!(
+                f,
+                "{provider} {provider_version} failed verification of {consumer} {consumer_version}"
+            ),
+        }
+    }
+}
+
+/// Outcome of a deployment gate check.
+#[derive(Debug, Clone)]
+pub struct DeploymentReport {
+    /// Pacticipant being deployed
+    pub pacticipant: String,
+    /// Version being deployed
+    pub version: String,
+    /// Target environment, with aliases resolved
+    pub environment: String,
+    /// Rows blocking the deployment
+    pub blockers: Vec<BlockReason>,
+    /// Verification matrix
+    pub matrix: Vec<MatrixEntry>,
+    /// Whether the matrix came from the gate's cache
+    pub cached: bool,
+}
+
+impl DeploymentReport {
+    /// Check if deployment is allowed.
+    #[must_use]
+    pub fn can_deploy(&self) -> bool {
+        self.blockers.is_empty()
+    }
+
+    /// One-line summary for release logs.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let target = format!(
+            "{} {} to {}",
+            self.pacticipant, self.version, self.environment
+        );
+        if self.can_deploy() {
+            return format!("Can deploy {target}");
+        }
+        let reasons: Vec<String> = self.blockers.iter().map(ToString::to_string).collect();
+        format!("Cannot deploy {target}: {}", reasons.join("; "))
+    }
+}
+
+/// Source of can-i-deploy matrices, typically a Pact Broker client.
+///
+/// Transient broker failures should be reported as retryable
+/// `PlatformError`s (`Unavailable`, `Timeout`, `RateLimited`).
+pub trait MatrixSource: Send + Sync {
+    /// Matrix for deploying `version` of `pacticipant` to `environment`.
+    fn matrix(
+        &self,
+        pacticipant: &str,
+        version: &str,
+        environment: &str,
+    ) -> impl Future<Output = Result<Vec<MatrixEntry>, PlatformError>> + Send;
+}
+
+/// Default time a matrix stays cached.
+pub const DEFAULT_MATRIX_CACHE_TTL: Duration = Duration::from_secs(60);
+
+type MatrixKey = (String, String, String);
+
+/// can-i-deploy gate with caching, environment aliases and retries.
+pub struct DeploymentGate<S> {
+    source: S,
+    retry: RetryPolicy,
+    aliases: HashMap<String, String>,
+    cache_ttl: Duration,
+    cache: Mutex<HashMap<MatrixKey, (Instant, Vec<MatrixEntry>)>>,
+}
+
+impl<S: MatrixSource> DeploymentGate<S> {
+    /// Create a gate over `source` with default retries and cache TTL.
+    #[must_use]
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            retry: RetryPolicy::with_defaults(),
+            aliases: HashMap::new(),
+            cache_ttl: DEFAULT_MATRIX_CACHE_TTL,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the retry policy for broker queries.
+    #[must_use]
+    pub const fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set how long matrices are cached (zero disables caching).
+    #[must_use]
+    pub const fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Map an environment alias (e.g. `prod`) to its broker name.
+    #[must_use]
+    pub fn with_alias(mut self, alias: impl Into<String>, environment: impl Into<String>) -> Self {
+        self.aliases.insert(alias.into(), environment.into());
+        self
+    }
+
+    /// Broker name of an environment or alias.
+    #[must_use]
+    pub fn environment<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map_or(name, String::as_str)
+    }
+
+    /// Check whether `version` of `pacticipant` can be deployed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PactError::Broker` if the matrix cannot be fetched after
+    /// retries.
+    pub async fn check(
+        &self,
+        pacticipant: &str,
+        version: &str,
+        environment: &str,
+    ) -> Result<DeploymentReport, PactError> {
+        let environment = self.environment(environment).to_string();
+        let key = (
+            pacticipant.to_string(),
+            version.to_string(),
+            environment.clone(),
+        );
+
+        let cached = self.cached(&key);
+        let is_cached = cached.is_some();
+        let matrix = if let Some(matrix) = cached {
+            matrix
+        } else {
+            let matrix = self
+                .retry
+                .execute(|| self.source.matrix(pacticipant, version, &environment))
+                .await
+                .map_err(|e| PactError::Broker(e.to_string()))?;
+            if !self.cache_ttl.is_zero() {
+                self.cache().insert(key, (Instant::now(), matrix.clone()));
+            }
+            matrix
+        };
+
+        Ok(DeploymentReport {
+            pacticipant: pacticipant.to_string(),
+            version: version.to_string(),
+            environment,
+            blockers: matrix.iter().filter_map(BlockReason::from_entry).collect(),
+            matrix,
+            cached: is_cached,
+        })
+    }
+
+    /// Drop all cached matrices, e.g. after publishing verification results.
+    pub fn invalidate(&self) {
+        self.cache().clear();
+    }
+
+    fn cached(&self, key: &MatrixKey) -> Option<Vec<MatrixEntry>> {
+        let mut cache = self.cache();
+        match cache.get(key) {
+            Some((fetched, matrix)) if fetched.elapsed() < self.cache_ttl => Some(matrix.clone()),
+            Some(_) => {
+                cache.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn cache(&self) -> MutexGuard<'_, HashMap<MatrixKey, (Instant, Vec<MatrixEntry>)>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_common::RetryConfig;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Broker failing the first `failures` queries.
+    struct FlakyBroker {
+        failures: u32,
+        calls: AtomicU32,
+        matrix: Vec<MatrixEntry>,
+    }
+
+    impl MatrixSource for FlakyBroker {
+        async fn matrix(
+            &self,
+            _pacticipant: &str,
+            _version: &str,
+            environment: &str,
+        ) -> Result<Vec<MatrixEntry>, PlatformError> {
+            assert_eq!(environment, "production");
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(PlatformError::Unavailable("broker 503".to_string()));
+            }
+            Ok(self.matrix.clone())
+        }
+    }
+
+    fn gate(failures: u32, matrix: Vec<MatrixEntry>) -> DeploymentGate<FlakyBroker> {
+        let retry = RetryConfig::default()
+            .with_initial_delay(Duration::from_millis(1))
+            .without_jitter();
+        DeploymentGate::new(FlakyBroker {
+            failures,
+            calls: AtomicU32::new(0),
+            matrix,
+        })
+        .with_retry(RetryPolicy::new(retry))
+        .with_alias("prod", "production")
+    }
+
+    #[tokio::test]
+    async fn test_gate_reports_blockers() {
+        let gate = gate(
+            1,
+            vec![
+                MatrixEntry::new("auth-edge", "1.0.0", "token-service", "2.0.0", true),
+                MatrixEntry::new("auth-edge", "1.0.0", "session-core", "", false),
+                MatrixEntry::new("auth-edge", "1.0.0", "iam-policy", "3.1.0", false),
+            ],
+        );
+
+        let report = gate.check("auth-edge", "1.0.0", "prod").await.unwrap();
+        assert_eq!(report.environment, "production");
+        assert!(!report.can_deploy());
+        assert!(!report.cached);
+        assert_eq!(
+            report.blockers,
+            vec![
+                BlockReason::MissingVerification {
+                    consumer: "auth-edge".to_string(),
+                    consumer_version: "1.0.0".to_string(),
+                    provider: "session-core".to_string(),
+                },
+                BlockReason::VerificationFailed {
+                    consumer: "auth-edge".to_string(),
+                    consumer_version: "1.0.0".to_string(),
+                    provider: "iam-policy".to_string(),
+                    provider_version: "3.1.0".to_string(),
+                },
+            ]
+        );
+        assert!(
+            report
+                .summary()
+                .contains("session-core has not verified auth-edge 1.0.0")
+        );
+        assert_eq!(gate.source.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_gate_caches_matrix() {
+        let matrix = vec![MatrixEntry::new(
+            "auth-edge",
+            "1.0.0",
+            "token-service",
+            "2.0.0",
+            true,
+        )];
+        let gate = gate(0, matrix);
+
+        assert!(
+            gate.check("auth-edge", "1.0.0", "production")
+                .await
+                .unwrap()
+                .can_deploy()
+        );
+        let report = gate.check("auth-edge", "1.0.0", "prod").await.unwrap();
+        assert!(report.cached);
+        assert_eq!(gate.source.calls.load(Ordering::SeqCst), 1);
+
+        gate.invalidate();
+        assert!(
+            !gate
+                .check("auth-edge", "1.0.0", "prod")
+                .await
+                .unwrap()
+                .cached
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gate_gives_up_after_retries() {
+        let gate = gate(10, vec![]);
+        let err = gate.check("auth-edge", "1.0.0", "prod").await.unwrap_err();
+        assert!(matches!(err, PactError::Broker(_)));
+        assert_eq!(gate.source.calls.load(Ordering::SeqCst), 4);
+    }
 
     #[test]
     fn test_can_deploy_all_success() {
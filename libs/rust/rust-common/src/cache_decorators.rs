@@ -0,0 +1,537 @@
+//! Read-through and write-through decorators over [`CacheClient`].
+//!
+//! Services cache JWKS documents, key metadata and sessions in the cache
+//! service, each with the same surrounding logic: load on a miss, write to the
+//! system of record, and keep concurrent misses from hitting the backend at
+//! once. The decorators here implement that logic once over any
+//! [`CacheStore`] and compose, since each is a [`CacheStore`] itself:
+//!
+//! - [`ReadThrough`] fills misses from the loader registered for the key's
+//!   namespace. Concurrent misses on one key share a single load (stampede
+//!   control); loaded values are cached with the loader's TTL.
+//! - [`WriteThrough`] writes to the namespace's writer before the cache, so
+//!   the cache never holds a value the system of record rejected.
+//! - [`WriteBehind`] writes to the cache at once and hands the write to a
+//!   background flusher through a bounded queue; sets fail when it is full.
+//!
+//! Loaders and writers live in a [`LoaderRegistry`], keyed by namespace: the
+//! part of a cache key before the first `:`. They receive the rest of the
+//! key, so `jwks:https://auth.example.com` loads `https://auth.example.com`
+//! with the `jwks` loader.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use rust_common::{CacheClient, CacheClientConfig, LoaderRegistry, ReadThrough, WriteBehind};
+//!
+//! # async fn example() -> Result<(), rust_common::PlatformError> {
+//! let loaders = Arc::new(
+//!     LoaderRegistry::new()
+//!         .with_loader("jwks", Some(Duration::from_secs(300)), |issuer| async move {
+//!             Ok(Some(format!("{{\"keys\":[],\"iss\":\"{issuer}\"}}").into_bytes()))
+//!         })
+//!         .with_writer("session", |id, _value| async move {
+//!             tracing::debug!(%id, "Persisting session");
+//!             Ok(())
+//!         }),
+//! );
+//! let client = CacheClient::new(CacheClientConfig::default()).await?;
+//! let cache = ReadThrough::new(WriteBehind::new(client, loaders.clone(), 1024), loaders);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::{OnceCell, mpsc, oneshot};
+
+use crate::{CacheClient, PlatformError};
+
+/// Cache operations shared by [`CacheClient`] and the decorators.
+pub trait CacheStore: Send + Sync + 'static {
+    /// Value of `key`, if cached.
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, PlatformError>> + Send;
+
+    /// Caches `value` under `key`; `None` uses the store's default TTL.
+    fn set(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<Duration>,
+    ) -> impl Future<Output = Result<(), PlatformError>> + Send;
+
+    /// Removes `key` from the cache.
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), PlatformError>> + Send;
+}
+
+impl CacheStore for CacheClient {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, PlatformError>> + Send {
+        Self::get(self, key)
+    }
+
+    fn set(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<Duration>,
+    ) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        Self::set(self, key, value, ttl)
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        Self::delete(self, key)
+    }
+}
+
+impl<S: CacheStore> CacheStore for Arc<S> {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, PlatformError>> + Send {
+        S::get(self, key)
+    }
+
+    fn set(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<Duration>,
+    ) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        S::set(self, key, value, ttl)
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        S::delete(self, key)
+    }
+}
+
+type LoadFuture = Pin<Box<dyn Future<Output = Result<Option<Vec<u8>>, PlatformError>> + Send>>;
+type WriteFuture = Pin<Box<dyn Future<Output = Result<(), PlatformError>> + Send>>;
+type LoadFn = Arc<dyn Fn(String) -> LoadFuture + Send + Sync>;
+type WriteFn = Arc<dyn Fn(String, Vec<u8>) -> WriteFuture + Send + Sync>;
+
+/// Loader of one namespace and the TTL of the values it loads.
+#[derive(Clone)]
+struct Loader {
+    load: LoadFn,
+    ttl: Option<Duration>,
+}
+
+/// Splits a cache key into its namespace and the rest of the key.
+fn split_key(key: &str) -> (&str, &str) {
+    key.split_once(':').unwrap_or((key, ""))
+}
+
+/// Loaders and writers of the systems of record, per key namespace.
+#[derive(Clone, Default)]
+pub struct LoaderRegistry {
+    loaders: HashMap<String, Loader>,
+    writers: HashMap<String, WriteFn>,
+}
+
+impl LoaderRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the loader filling misses in `namespace`; `Ok(None)` means
+    /// the value does not exist and nothing is cached. `ttl` of `None` uses
+    /// the store's default TTL.
+    #[must_use]
+    pub fn with_loader<F, Fut>(mut self, namespace: impl Into<String>, ttl: Option<Duration>, load: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Option<Vec<u8>>, PlatformError>> + Send + 'static,
+    {
+        let load: LoadFn = Arc::new(move |id| Box::pin(load(id)));
+        self.loaders.insert(namespace.into(), Loader { load, ttl });
+        self
+    }
+
+    /// Registers the writer persisting values set in `namespace`.
+    #[must_use]
+    pub fn with_writer<F, Fut>(mut self, namespace: impl Into<String>, write: F) -> Self
+    where
+        F: Fn(String, Vec<u8>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), PlatformError>> + Send + 'static,
+    {
+        let write: WriteFn = Arc::new(move |id, value| Box::pin(write(id, value)));
+        self.writers.insert(namespace.into(), write);
+        self
+    }
+
+    fn loader<'a>(&'a self, key: &'a str) -> Option<(&'a Loader, &'a str)> {
+        let (namespace, id) = split_key(key);
+        self.loaders.get(namespace).map(|loader| (loader, id))
+    }
+
+    fn writer<'a>(&'a self, key: &'a str) -> Option<(&'a WriteFn, &'a str)> {
+        let (namespace, id) = split_key(key);
+        self.writers.get(namespace).map(|writer| (writer, id))
+    }
+}
+
+/// Load shared by the concurrent misses of one key
+type InFlight = Arc<OnceCell<Option<Vec<u8>>>>;
+
+/// Fills cache misses from the registered loaders.
+pub struct ReadThrough<S> {
+    inner: S,
+    loaders: Arc<LoaderRegistry>,
+    in_flight: Mutex<HashMap<String, InFlight>>,
+}
+
+impl<S: CacheStore> ReadThrough<S> {
+    /// Decorates `inner` with the loaders of `loaders`.
+    pub fn new(inner: S, loaders: Arc<LoaderRegistry>) -> Self {
+        Self {
+            inner,
+            loaders,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The decorated store.
+    pub const fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Loads a missed key, joining a load of the same key already running.
+    ///
+    /// A failed load is not shared: the next waiter loads again.
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>, PlatformError> {
+        let Some((loader, id)) = self.loaders.loader(key) else {
+            return Ok(None);
+        };
+        let cell = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+
+        let result = cell
+            .get_or_try_init(|| async {
+                let value = (loader.load)(id.to_string()).await?;
+                if let Some(value) = &value {
+                    if let Err(err) = self.inner.set(key, value, loader.ttl).await {
+                        tracing::warn!(key, error = %err, "Failed to cache loaded value");
+                    }
+                }
+                Ok(value)
+            })
+            .await
+            .cloned();
+
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(PoisonError::into_inner);
+        if in_flight.get(key).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+            in_flight.remove(key);
+        }
+        result
+    }
+}
+
+impl<S: CacheStore> CacheStore for ReadThrough<S> {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, PlatformError> {
+        match self.inner.get(key).await? {
+            Some(value) => Ok(Some(value)),
+            None => self.load(key).await,
+        }
+    }
+
+    fn set(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<Duration>,
+    ) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        self.inner.set(key, value, ttl)
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        self.inner.delete(key)
+    }
+}
+
+/// Writes to the system of record before caching.
+pub struct WriteThrough<S> {
+    inner: S,
+    loaders: Arc<LoaderRegistry>,
+}
+
+impl<S: CacheStore> WriteThrough<S> {
+    /// Decorates `inner` with the writers of `loaders`.
+    pub const fn new(inner: S, loaders: Arc<LoaderRegistry>) -> Self {
+        Self { inner, loaders }
+    }
+
+    /// The decorated store.
+    pub const fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: CacheStore> CacheStore for WriteThrough<S> {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, PlatformError>> + Send {
+        self.inner.get(key)
+    }
+
+    /// Writes `value` with the namespace's writer, then caches it; a failed
+    /// write leaves the cache untouched.
+    async fn set(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> Result<(), PlatformError> {
+        if let Some((write, id)) = self.loaders.writer(key) {
+            write(id.to_string(), value.to_vec()).await?;
+        }
+        self.inner.set(key, value, ttl).await
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        self.inner.delete(key)
+    }
+}
+
+/// Entry of the write-behind queue
+enum Pending {
+    Write {
+        write: WriteFn,
+        key: String,
+        id: String,
+        value: Vec<u8>,
+    },
+    Flush(oneshot::Sender<()>),
+}
+
+/// Caches at once and writes to the system of record in the background.
+pub struct WriteBehind<S> {
+    inner: S,
+    loaders: Arc<LoaderRegistry>,
+    queue: mpsc::Sender<Pending>,
+}
+
+impl<S: CacheStore> WriteBehind<S> {
+    /// Decorates `inner` with the writers of `loaders`, queueing at most
+    /// `capacity` writes; must be called within a Tokio runtime.
+    ///
+    /// The flusher writes in order, one at a time, logging failed writes.
+    /// It drains the queue and stops once the decorator is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(inner: S, loaders: Arc<LoaderRegistry>, capacity: usize) -> Self {
+        let (queue, mut pending) = mpsc::channel(capacity);
+        tokio::spawn(async move {
+            while let Some(entry) = pending.recv().await {
+                match entry {
+                    Pending::Write { write, key, id, value } => {
+                        if let Err(err) = write(id, value).await {
+                            tracing::warn!(key, error = %err, "Write-behind write failed");
+                        }
+                    }
+                    Pending::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+        Self { inner, loaders, queue }
+    }
+
+    /// The decorated store.
+    pub const fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Writes queued but not yet started.
+    pub fn pending(&self) -> usize {
+        self.queue.max_capacity() - self.queue.capacity()
+    }
+
+    /// Waits until every write queued before the call has been attempted.
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.queue.send(Pending::Flush(done)).await.is_ok() {
+            let _ = flushed.await;
+        }
+    }
+}
+
+impl<S: CacheStore> CacheStore for WriteBehind<S> {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, PlatformError>> + Send {
+        self.inner.get(key)
+    }
+
+    /// Caches `value` and queues it for the namespace's writer; fails
+    /// without caching when the queue is full.
+    async fn set(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> Result<(), PlatformError> {
+        let Some((write, id)) = self.loaders.writer(key) else {
+            return self.inner.set(key, value, ttl).await;
+        };
+        let slot = self
+            .queue
+            .try_reserve()
+            .map_err(|_| PlatformError::unavailable("write-behind queue full"))?;
+        self.inner.set(key, value, ttl).await?;
+        slot.send(Pending::Write {
+            write: write.clone(),
+            key: key.to_string(),
+            id: id.to_string(),
+            value: value.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), PlatformError>> + Send {
+        self.inner.delete(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CacheClientConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn client() -> CacheClient {
+        CacheClient::new(CacheClientConfig::default()).await.unwrap()
+    }
+
+    /// Registry whose `jwks` loader counts its calls and takes 20ms
+    fn counting_loaders(calls: Arc<AtomicUsize>) -> Arc<LoaderRegistry> {
+        Arc::new(LoaderRegistry::new().with_loader(
+            "jwks",
+            Some(Duration::from_secs(60)),
+            move |issuer| {
+                let calls = calls.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    if issuer == "unknown" {
+                        return Ok(None);
+                    }
+                    Ok(Some(format!("keys of {issuer}").into_bytes()))
+                }
+            },
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_read_through_loads_misses_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cache = ReadThrough::new(client().await, counting_loaders(calls.clone()));
+
+        let loaded = cache.get("jwks:https://auth.example.com").await.unwrap();
+        assert_eq!(loaded.as_deref(), Some(&b"keys of https://auth.example.com"[..]));
+        assert_eq!(
+            cache.inner().get("jwks:https://auth.example.com").await.unwrap(),
+            loaded
+        );
+        cache.get("jwks:https://auth.example.com").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert_eq!(cache.get("jwks:unknown").await.unwrap(), None);
+        assert_eq!(cache.get("session:abc").await.unwrap(), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_misses_share_one_load() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cache = Arc::new(ReadThrough::new(client().await, counting_loaders(calls.clone())));
+
+        let mut gets = tokio::task::JoinSet::new();
+        for _ in 0..16 {
+            let cache = cache.clone();
+            gets.spawn(async move { cache.get("jwks:issuer").await.unwrap() });
+        }
+        while let Some(value) = gets.join_next().await {
+            assert_eq!(value.unwrap().as_deref(), Some(&b"keys of issuer"[..]));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_loads_are_not_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let loaders = Arc::new(LoaderRegistry::new().with_loader("keys", None, move |_| {
+            let attempt = counted.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    Err(PlatformError::unavailable("crypto-service"))
+                } else {
+                    Ok(Some(b"metadata".to_vec()))
+                }
+            }
+        }));
+        let cache = ReadThrough::new(client().await, loaders);
+
+        assert!(cache.get("keys:signing").await.is_err());
+        assert_eq!(cache.get("keys:signing").await.unwrap(), Some(b"metadata".to_vec()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_write_through_writes_before_caching() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let record = written.clone();
+        let loaders = Arc::new(LoaderRegistry::new().with_writer("session", move |id, value| {
+            let record = record.clone();
+            async move {
+                if id == "rejected" {
+                    return Err(PlatformError::invalid_input("session store"));
+                }
+                record.lock().unwrap().push((id, value));
+                Ok(())
+            }
+        }));
+        let cache = WriteThrough::new(client().await, loaders);
+
+        cache.set("session:abc", b"state", None).await.unwrap();
+        assert_eq!(*written.lock().unwrap(), vec![("abc".to_string(), b"state".to_vec())]);
+        assert_eq!(cache.get("session:abc").await.unwrap(), Some(b"state".to_vec()));
+
+        assert!(cache.set("session:rejected", b"state", None).await.is_err());
+        assert_eq!(cache.get("session:rejected").await.unwrap(), None);
+
+        cache.set("other:key", b"value", None).await.unwrap();
+        assert_eq!(written.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_behind_flushes_through_bounded_queue() {
+        let (started_tx, mut started) = mpsc::unbounded_channel();
+        let release = Arc::new(tokio::sync::Semaphore::new(0));
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (gate, record) = (release.clone(), written.clone());
+        let loaders = Arc::new(LoaderRegistry::new().with_writer("session", move |id, _| {
+            let (started_tx, gate, record) = (started_tx.clone(), gate.clone(), record.clone());
+            async move {
+                let _ = started_tx.send(());
+                gate.acquire().await.unwrap().forget();
+                record.lock().unwrap().push(id);
+                Ok(())
+            }
+        }));
+        let cache = WriteBehind::new(client().await, loaders, 1);
+
+        // The first write blocks in the writer, the second fills the queue
+        cache.set("session:a", b"1", None).await.unwrap();
+        started.recv().await.unwrap();
+        cache.set("session:b", b"2", None).await.unwrap();
+        assert_eq!(cache.pending(), 1);
+        assert!(cache.set("session:c", b"3", None).await.is_err());
+        assert_eq!(cache.get("session:c").await.unwrap(), None);
+        assert_eq!(cache.get("session:b").await.unwrap(), Some(b"2".to_vec()));
+
+        release.add_permits(2);
+        cache.flush().await;
+        assert_eq!(*written.lock().unwrap(), vec!["a", "b"]);
+        assert_eq!(cache.pending(), 0);
+    }
+}
@@ -2,10 +2,28 @@
 //!
 //! This module provides a circuit breaker pattern implementation to protect
 //! services from cascading failures when downstream dependencies are unavailable.
-
+//! A closed circuit opens after consecutive failures or, with
+//! [`TripPolicy::FailureRate`], when too many of the recent calls failed.
+//! [`CircuitBreakerObserver`]s are told about every state change.
+//! [`CircuitBreakerRegistry`] keeps one breaker per method of a service.
+//! [`CircuitBreakerLayer`] applies a breaker to a gRPC client channel.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tonic::codegen::http;
+use tonic::{Code, Status};
+use tower::{Layer, Service};
+
+use crate::error::PlatformError;
+
+/// Error type of services wrapped by [`CircuitBreakerLayer`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
 /// Circuit breaker state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,6 +36,63 @@ pub enum CircuitState {
     HalfOpen,
 }
 
+impl CircuitState {
+    /// Label of the state in metrics and logs.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Notified when a [`CircuitBreaker`] changes state.
+///
+/// Called synchronously after the new state is set, so implementations
+/// should hand slow work, such as remote logging, to a task.
+pub trait CircuitBreakerObserver: Send + Sync {
+    /// The circuit moved from `from` to `to`.
+    fn on_state_change(&self, from: CircuitState, to: CircuitState);
+}
+
+impl<F> CircuitBreakerObserver for F
+where
+    F: Fn(CircuitState, CircuitState) + Send + Sync,
+{
+    fn on_state_change(&self, from: CircuitState, to: CircuitState) {
+        self(from, to);
+    }
+}
+
+/// Calls a [`TripPolicy::FailureRate`] breaker looks back on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlidingWindow {
+    /// The last `n` calls
+    Count(u32),
+    /// Calls within the last period
+    Time(Duration),
+}
+
+/// When a closed circuit opens.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TripPolicy {
+    /// After `failure_threshold` consecutive failures
+    #[default]
+    ConsecutiveFailures,
+    /// Once the share of failed calls in `window` reaches `threshold`
+    /// (0.0 to 1.0), as long as the window holds at least `minimum_calls`
+    FailureRate {
+        /// Share of failed calls that opens the circuit
+        threshold: f64,
+        /// Calls the rate is computed over
+        window: SlidingWindow,
+        /// Calls needed in the window before the rate is trusted
+        minimum_calls: u32,
+    },
+}
+
 /// Circuit breaker configuration.
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
@@ -29,6 +104,8 @@ pub struct CircuitBreakerConfig {
     pub timeout: Duration,
     /// Maximum requests allowed in half-open state
     pub half_open_max_requests: u32,
+    /// When a closed circuit opens
+    pub trip: TripPolicy,
 }
 
 impl Default for CircuitBreakerConfig {
@@ -38,6 +115,7 @@ impl Default for CircuitBreakerConfig {
             success_threshold: 2,
             timeout: Duration::from_secs(30),
             half_open_max_requests: 3,
+            trip: TripPolicy::ConsecutiveFailures,
         }
     }
 }
@@ -63,6 +141,70 @@ impl CircuitBreakerConfig {
         self.timeout = timeout;
         self
     }
+
+    /// Create a new config with a custom half-open probe budget.
+    #[must_use]
+    pub const fn with_half_open_max_requests(mut self, max_requests: u32) -> Self {
+        self.half_open_max_requests = max_requests;
+        self
+    }
+
+    /// Open on the failure rate over `window` instead of consecutive
+    /// failures, once it holds `minimum_calls` calls.
+    #[must_use]
+    pub const fn with_failure_rate(
+        mut self,
+        threshold: f64,
+        window: SlidingWindow,
+        minimum_calls: u32,
+    ) -> Self {
+        self.trip = TripPolicy::FailureRate {
+            threshold,
+            window,
+            minimum_calls,
+        };
+        self
+    }
+}
+
+/// Outcomes of recent calls, oldest first; `true` is a failure.
+#[derive(Debug, Default)]
+struct Outcomes {
+    calls: VecDeque<(Instant, bool)>,
+    failures: u32,
+}
+
+impl Outcomes {
+    fn push(&mut self, failed: bool, window: SlidingWindow) {
+        let now = Instant::now();
+        self.calls.push_back((now, failed));
+        self.failures += u32::from(failed);
+        while let Some(&(at, failed)) = self.calls.front() {
+            let expired = match window {
+                SlidingWindow::Count(n) => self.calls.len() > n as usize,
+                SlidingWindow::Time(period) => now.duration_since(at) > period,
+            };
+            if !expired {
+                break;
+            }
+            self.calls.pop_front();
+            self.failures -= u32::from(failed);
+        }
+    }
+
+    /// Failure rate, or `None` below `minimum_calls`.
+    fn rate(&self, minimum_calls: u32) -> Option<f64> {
+        let calls = u32::try_from(self.calls.len()).unwrap_or(u32::MAX);
+        if calls == 0 || calls < minimum_calls {
+            return None;
+        }
+        Some(f64::from(self.failures) / f64::from(calls))
+    }
+
+    fn clear(&mut self) {
+        self.calls.clear();
+        self.failures = 0;
+    }
 }
 
 /// Circuit breaker for protecting external services.
@@ -72,12 +214,14 @@ impl CircuitBreakerConfig {
 /// - Open: Failure threshold exceeded, requests are rejected
 /// - Half-Open: Testing recovery, limited requests allowed
 pub struct CircuitBreaker {
-    config: CircuitBreakerConfig,
+    config: std::sync::RwLock<CircuitBreakerConfig>,
     state: RwLock<CircuitState>,
     failures: AtomicU32,
     successes: AtomicU32,
     last_failure: RwLock<Option<Instant>>,
     half_open_requests: AtomicU32,
+    outcomes: std::sync::Mutex<Outcomes>,
+    observers: std::sync::RwLock<Vec<Arc<dyn CircuitBreakerObserver>>>,
 }
 
 impl CircuitBreaker {
@@ -85,12 +229,14 @@ impl CircuitBreaker {
     #[must_use]
     pub fn new(config: CircuitBreakerConfig) -> Self {
         Self {
-            config,
+            config: std::sync::RwLock::new(config),
             state: RwLock::new(CircuitState::Closed),
             failures: AtomicU32::new(0),
             successes: AtomicU32::new(0),
             last_failure: RwLock::new(None),
             half_open_requests: AtomicU32::new(0),
+            outcomes: std::sync::Mutex::new(Outcomes::default()),
+            observers: std::sync::RwLock::new(Vec::new()),
         }
     }
 
@@ -103,6 +249,8 @@ impl CircuitBreaker {
     /// Check if a request is allowed.
     ///
     /// Returns `true` if the request should proceed, `false` if it should be rejected.
+    /// A half-open circuit lets through `half_open_max_requests` probes, counting
+    /// the call that moved it out of the open state, and rejects the rest.
     pub async fn allow_request(&self) -> bool {
         let state = *self.state.read().await;
         match state {
@@ -110,11 +258,11 @@ impl CircuitBreaker {
             CircuitState::Open => {
                 // Check if timeout has elapsed
                 if let Some(last) = *self.last_failure.read().await {
-                    if last.elapsed() >= self.config.timeout {
-                        // Transition to half-open
-                        *self.state.write().await = CircuitState::HalfOpen;
-                        self.half_open_requests.store(0, Ordering::SeqCst);
+                    if last.elapsed() >= self.config().timeout {
+                        // Transition to half-open; this call is the first probe
+                        self.half_open_requests.store(1, Ordering::SeqCst);
                         self.successes.store(0, Ordering::SeqCst);
+                        self.transition(CircuitState::HalfOpen).await;
                         true
                     } else {
                         false
@@ -126,7 +274,7 @@ impl CircuitBreaker {
             CircuitState::HalfOpen => {
                 // Allow limited requests in half-open state
                 let current = self.half_open_requests.fetch_add(1, Ordering::SeqCst);
-                current < self.config.half_open_max_requests
+                current < self.config().half_open_max_requests
             }
         }
     }
@@ -139,16 +287,18 @@ impl CircuitBreaker {
         match state {
             CircuitState::HalfOpen => {
                 let successes = self.successes.fetch_add(1, Ordering::SeqCst) + 1;
-                if successes >= self.config.success_threshold {
+                if successes >= self.config().success_threshold {
                     // Close the circuit
-                    *self.state.write().await = CircuitState::Closed;
                     self.failures.store(0, Ordering::SeqCst);
                     self.successes.store(0, Ordering::SeqCst);
+                    self.outcomes().clear();
+                    self.transition(CircuitState::Closed).await;
                 }
             }
             CircuitState::Closed => {
                 // Reset failure count on success
                 self.failures.store(0, Ordering::SeqCst);
+                self.record_outcome(false);
             }
             CircuitState::Open => {
                 // Shouldn't happen, but ignore
@@ -158,17 +308,26 @@ impl CircuitBreaker {
 
     /// Record a failed request.
     ///
-    /// Consecutive failures will open the circuit.
+    /// Consecutive failures, or a failure rate above the threshold, will
+    /// open the circuit.
     pub async fn record_failure(&self) {
         let failures = self.failures.fetch_add(1, Ordering::SeqCst) + 1;
         *self.last_failure.write().await = Some(Instant::now());
 
         let state = *self.state.read().await;
+        let trips = match (state, self.config().trip) {
+            (CircuitState::Open, _) => false,
+            (_, TripPolicy::ConsecutiveFailures) => failures >= self.config().failure_threshold,
+            // A failed probe reopens the circuit
+            (CircuitState::HalfOpen, TripPolicy::FailureRate { .. }) => true,
+            (CircuitState::Closed, TripPolicy::FailureRate { .. }) => self.record_outcome(true),
+        };
         match state {
             CircuitState::Closed | CircuitState::HalfOpen => {
-                if failures >= self.config.failure_threshold {
-                    *self.state.write().await = CircuitState::Open;
+                if trips {
                     self.successes.store(0, Ordering::SeqCst);
+                    self.outcomes().clear();
+                    self.transition(CircuitState::Open).await;
                 }
             }
             CircuitState::Open => {
@@ -177,6 +336,83 @@ impl CircuitBreaker {
         }
     }
 
+    /// Register an observer of state changes.
+    pub fn add_observer(&self, observer: Arc<dyn CircuitBreakerObserver>) {
+        self.observers
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(observer);
+    }
+
+    /// Move to `to` and notify the observers if the state changed.
+    async fn transition(&self, to: CircuitState) {
+        let from = std::mem::replace(&mut *self.state.write().await, to);
+        if from == to {
+            return;
+        }
+        let observers = self
+            .observers
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        for observer in observers {
+            observer.on_state_change(from, to);
+        }
+    }
+
+    /// Add an outcome to the sliding window of a failure-rate breaker and
+    /// tell whether the rate now opens the circuit.
+    fn record_outcome(&self, failed: bool) -> bool {
+        let TripPolicy::FailureRate {
+            threshold,
+            window,
+            minimum_calls,
+        } = self.config().trip
+        else {
+            return false;
+        };
+        let mut outcomes = self.outcomes();
+        outcomes.push(failed, window);
+        outcomes
+            .rate(minimum_calls)
+            .is_some_and(|rate| rate >= threshold)
+    }
+
+    fn outcomes(&self) -> std::sync::MutexGuard<'_, Outcomes> {
+        self.outcomes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Failure rate over the sliding window, or `None` while it holds fewer
+    /// calls than required or the breaker counts consecutive failures.
+    #[must_use]
+    pub fn failure_rate(&self) -> Option<f64> {
+        match self.config().trip {
+            TripPolicy::FailureRate { minimum_calls, .. } => self.outcomes().rate(minimum_calls),
+            TripPolicy::ConsecutiveFailures => None,
+        }
+    }
+
+    /// Get the current configuration.
+    #[must_use]
+    pub fn config(&self) -> CircuitBreakerConfig {
+        self.config
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the configuration, keeping the current state and counters.
+    ///
+    /// New thresholds apply from the next recorded outcome.
+    pub fn update_config(&self, config: CircuitBreakerConfig) {
+        *self
+            .config
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = config;
+    }
+
     /// Get the current circuit state.
     pub async fn state(&self) -> CircuitState {
         *self.state.read().await
@@ -190,17 +426,246 @@ impl CircuitBreaker {
 
     /// Reset the circuit breaker to closed state.
     pub async fn reset(&self) {
-        *self.state.write().await = CircuitState::Closed;
         self.failures.store(0, Ordering::SeqCst);
         self.successes.store(0, Ordering::SeqCst);
         self.half_open_requests.store(0, Ordering::SeqCst);
         *self.last_failure.write().await = None;
+        self.outcomes().clear();
+        self.transition(CircuitState::Closed).await;
+    }
+}
+
+/// Circuit states counted across the breakers of a [`CircuitBreakerRegistry`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CircuitBreakerStats {
+    /// Breakers created so far
+    pub breakers: usize,
+    /// Breakers whose circuit is closed
+    pub closed: usize,
+    /// Breakers whose circuit is open
+    pub open: usize,
+    /// Breakers probing in half-open state
+    pub half_open: usize,
+}
+
+/// Circuit breakers of one downstream service, keyed by target method.
+///
+/// Each key, typically the RPC method, gets its own [`CircuitBreaker`] on
+/// first use, so a failing method does not open the circuit of the others.
+/// All breakers share one configuration, and
+/// [`update_config`](Self::update_config) applies to every one of them.
+pub struct CircuitBreakerRegistry {
+    service: Arc<str>,
+    config: std::sync::RwLock<CircuitBreakerConfig>,
+    breakers: std::sync::RwLock<HashMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakerRegistry {
+    /// Create an empty registry for `service` with a shared configuration.
+    #[must_use]
+    pub fn new(service: impl Into<Arc<str>>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            service: service.into(),
+            config: std::sync::RwLock::new(config),
+            breakers: std::sync::RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The guarded service.
+    #[must_use]
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// The breaker of `key`, created with the shared configuration if needed.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Arc<CircuitBreaker> {
+        if let Some(breaker) = self.read().get(key) {
+            return breaker.clone();
+        }
+        let config = self.config();
+        self.write()
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(config)))
+            .clone()
+    }
+
+    /// Every breaker created so far, sorted by key.
+    #[must_use]
+    pub fn breakers(&self) -> Vec<(String, Arc<CircuitBreaker>)> {
+        let mut breakers: Vec<_> = self
+            .read()
+            .iter()
+            .map(|(key, breaker)| (key.clone(), breaker.clone()))
+            .collect();
+        breakers.sort_by(|a, b| a.0.cmp(&b.0));
+        breakers
+    }
+
+    /// Get the shared configuration.
+    #[must_use]
+    pub fn config(&self) -> CircuitBreakerConfig {
+        self.config
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the shared configuration of existing and future breakers.
+    pub fn update_config(&self, config: CircuitBreakerConfig) {
+        for breaker in self.read().values() {
+            breaker.update_config(config.clone());
+        }
+        *self
+            .config
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = config;
+    }
+
+    /// Count the breakers in each state.
+    pub async fn stats(&self) -> CircuitBreakerStats {
+        let mut stats = CircuitBreakerStats::default();
+        for (_, breaker) in self.breakers() {
+            stats.breakers += 1;
+            match breaker.state().await {
+                CircuitState::Closed => stats.closed += 1,
+                CircuitState::Open => stats.open += 1,
+                CircuitState::HalfOpen => stats.half_open += 1,
+            }
+        }
+        stats
+    }
+
+    /// Reset every breaker to closed state.
+    pub async fn reset(&self) {
+        for (_, breaker) in self.breakers() {
+            breaker.reset().await;
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Arc<CircuitBreaker>>> {
+        self.breakers
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, Arc<CircuitBreaker>>> {
+        self.breakers
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Tower layer guarding a gRPC client with a shared [`CircuitBreaker`].
+///
+/// Transport errors and statuses signalling a server fault (`UNAVAILABLE`,
+/// `UNKNOWN`, `INTERNAL`, `DEADLINE_EXCEEDED`, `DATA_LOSS`) count as failures.
+/// While the circuit is open, calls fail with [`PlatformError::CircuitOpen`]
+/// without reaching the server. Thresholds are read on every call, so
+/// [`CircuitBreaker::update_config`], e.g. on a configuration reload, also
+/// applies to clients already built.
+#[derive(Clone)]
+pub struct CircuitBreakerLayer {
+    service: Arc<str>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerLayer {
+    /// Guard calls to `service` with `breaker`.
+    #[must_use]
+    pub fn new(service: impl Into<Arc<str>>, breaker: Arc<CircuitBreaker>) -> Self {
+        Self {
+            service: service.into(),
+            breaker,
+        }
+    }
+
+    /// The breaker, e.g. to update its configuration.
+    #[must_use]
+    pub const fn breaker(&self) -> &Arc<CircuitBreaker> {
+        &self.breaker
+    }
+}
+
+impl<S> Layer<S> for CircuitBreakerLayer {
+    type Service = CircuitBreakerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CircuitBreakerService {
+            inner,
+            service: self.service.clone(),
+            breaker: self.breaker.clone(),
+        }
+    }
+}
+
+/// Service wrapper created by [`CircuitBreakerLayer`].
+#[derive(Clone)]
+pub struct CircuitBreakerService<S> {
+    inner: S,
+    service: Arc<str>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+/// Whether a response status signals a server fault.
+const fn is_server_fault(code: Code) -> bool {
+    matches!(
+        code,
+        Code::Unavailable | Code::Unknown | Code::Internal | Code::DeadlineExceeded | Code::DataLoss
+    )
+}
+
+impl<S, B, R> Service<http::Request<B>> for CircuitBreakerService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<R>> + Clone + Send + 'static,
+    S::Error: Into<BoxError> + Send,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+    R: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        // The ready service handles this call; the clone serves the next one
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let service = self.service.clone();
+        let breaker = self.breaker.clone();
+
+        Box::pin(async move {
+            if !breaker.allow_request().await {
+                return Err(PlatformError::circuit_open(&*service).into());
+            }
+            match inner.call(req).await {
+                Ok(response) => {
+                    let failed = Status::from_header_map(response.headers())
+                        .is_some_and(|status| is_server_fault(status.code()));
+                    if failed {
+                        breaker.record_failure().await;
+                    } else {
+                        breaker.record_success().await;
+                    }
+                    Ok(response)
+                }
+                Err(err) => {
+                    breaker.record_failure().await;
+                    Err(err.into())
+                }
+            }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tower::ServiceExt;
 
     #[tokio::test]
     async fn test_initial_state_closed() {
@@ -237,6 +702,64 @@ mod tests {
         assert_eq!(cb.state().await, CircuitState::Closed);
     }
 
+    #[tokio::test]
+    async fn test_update_config_applies_new_threshold() {
+        let cb = CircuitBreaker::new(CircuitBreakerConfig::default().with_failure_threshold(5));
+        cb.record_failure().await;
+
+        cb.update_config(CircuitBreakerConfig::default().with_failure_threshold(2));
+        assert_eq!(cb.config().failure_threshold, 2);
+        assert_eq!(cb.state().await, CircuitState::Closed);
+
+        cb.record_failure().await;
+        assert_eq!(cb.state().await, CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_registry_isolates_methods() {
+        let registry = CircuitBreakerRegistry::new(
+            "crypto-service",
+            CircuitBreakerConfig::default().with_failure_threshold(2),
+        );
+        let rotate = registry.get("RotateKey");
+        rotate.record_failure().await;
+        rotate.record_failure().await;
+
+        assert!(Arc::ptr_eq(&rotate, &registry.get("RotateKey")));
+        assert_eq!(registry.get("RotateKey").state().await, CircuitState::Open);
+        assert!(registry.get("Encrypt").allow_request().await);
+        assert_eq!(
+            registry.stats().await,
+            CircuitBreakerStats {
+                breakers: 2,
+                closed: 1,
+                open: 1,
+                half_open: 0,
+            }
+        );
+
+        registry.reset().await;
+        assert_eq!(registry.stats().await.open, 0);
+    }
+
+    #[tokio::test]
+    async fn test_registry_update_config_applies_to_all_breakers() {
+        let registry = CircuitBreakerRegistry::new("crypto-service", CircuitBreakerConfig::default());
+        let sign = registry.get("Sign");
+
+        registry.update_config(CircuitBreakerConfig::default().with_failure_threshold(1));
+        assert_eq!(sign.config().failure_threshold, 1);
+        assert_eq!(registry.get("Verify").config().failure_threshold, 1);
+        assert_eq!(
+            registry
+                .breakers()
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .collect::<Vec<_>>(),
+            ["Sign", "Verify"]
+        );
+    }
+
     #[tokio::test]
     async fn test_half_open_transition() {
         let config = CircuitBreakerConfig {
@@ -244,6 +767,7 @@ mod tests {
             success_threshold: 1,
             timeout: Duration::from_millis(1),
             half_open_max_requests: 3,
+            trip: TripPolicy::ConsecutiveFailures,
         };
         let cb = CircuitBreaker::new(config);
 
@@ -260,6 +784,24 @@ mod tests {
         assert_eq!(cb.state().await, CircuitState::HalfOpen);
     }
 
+    #[tokio::test]
+    async fn test_half_open_limits_probes() {
+        let config = CircuitBreakerConfig::default()
+            .with_failure_threshold(1)
+            .with_timeout(Duration::from_millis(1))
+            .with_half_open_max_requests(2);
+        let cb = CircuitBreaker::new(config);
+
+        cb.record_failure().await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        // The transitioning call counts as the first probe
+        assert!(cb.allow_request().await);
+        assert!(cb.allow_request().await);
+        assert!(!cb.allow_request().await);
+        assert_eq!(cb.state().await, CircuitState::HalfOpen);
+    }
+
     #[tokio::test]
     async fn test_closes_after_successes_in_half_open() {
         let config = CircuitBreakerConfig {
@@ -267,6 +809,7 @@ mod tests {
             success_threshold: 2,
             timeout: Duration::from_millis(1),
             half_open_max_requests: 5,
+            trip: TripPolicy::ConsecutiveFailures,
         };
         let cb = CircuitBreaker::new(config);
 
@@ -285,6 +828,93 @@ mod tests {
         assert_eq!(cb.state().await, CircuitState::Closed);
     }
 
+    #[tokio::test]
+    async fn test_observers_see_each_transition() {
+        let config = CircuitBreakerConfig::default()
+            .with_failure_threshold(1)
+            .with_success_threshold(1)
+            .with_timeout(Duration::from_millis(1));
+        let cb = CircuitBreaker::new(config);
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        cb.add_observer(Arc::new(move |from: CircuitState, to: CircuitState| {
+            recorder.lock().unwrap().push((from.as_str(), to.as_str()));
+        }));
+
+        cb.record_failure().await;
+        cb.record_failure().await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        cb.allow_request().await;
+        cb.record_success().await;
+        cb.reset().await;
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                ("closed", "open"),
+                ("open", "half_open"),
+                ("half_open", "closed"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failure_rate_ignores_bursts_within_healthy_rate() {
+        let config = CircuitBreakerConfig::default()
+            .with_failure_threshold(2)
+            .with_failure_rate(0.5, SlidingWindow::Count(10), 4);
+        let cb = CircuitBreaker::new(config);
+
+        // Below the minimum volume the rate is not trusted
+        cb.record_failure().await;
+        cb.record_failure().await;
+        cb.record_failure().await;
+        assert_eq!(cb.state().await, CircuitState::Closed);
+        assert_eq!(cb.failure_rate(), None);
+
+        for _ in 0..5 {
+            cb.record_success().await;
+        }
+        assert_eq!(cb.failure_rate(), Some(3.0 / 8.0));
+        cb.record_failure().await;
+        assert_eq!(cb.failure_rate(), Some(4.0 / 9.0));
+        assert_eq!(cb.state().await, CircuitState::Closed);
+
+        // 5 of the last 10 calls failed
+        cb.record_failure().await;
+        assert_eq!(cb.state().await, CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_failure_rate_count_window_forgets_old_calls() {
+        let config = CircuitBreakerConfig::default()
+            .with_failure_rate(0.5, SlidingWindow::Count(4), 4);
+        let cb = CircuitBreaker::new(config);
+
+        cb.record_failure().await;
+        for _ in 0..4 {
+            cb.record_success().await;
+        }
+        assert_eq!(cb.failure_rate(), Some(0.0));
+    }
+
+    #[tokio::test]
+    async fn test_failure_rate_time_window_forgets_old_calls() {
+        let config = CircuitBreakerConfig::default().with_failure_rate(
+            0.5,
+            SlidingWindow::Time(Duration::from_millis(20)),
+            2,
+        );
+        let cb = CircuitBreaker::new(config);
+
+        cb.record_failure().await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        cb.record_success().await;
+        assert_eq!(cb.failure_rate(), None);
+        cb.record_failure().await;
+        assert_eq!(cb.state().await, CircuitState::Open);
+    }
+
     #[tokio::test]
     async fn test_reset() {
         let cb = CircuitBreaker::with_defaults();
@@ -297,4 +927,41 @@ mod tests {
         assert_eq!(cb.state().await, CircuitState::Closed);
         assert_eq!(cb.failure_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_layer_opens_on_server_faults_and_rejects() {
+        let breaker = Arc::new(CircuitBreaker::new(
+            CircuitBreakerConfig::default().with_failure_threshold(2),
+        ));
+        let layer = CircuitBreakerLayer::new("token-service", breaker.clone());
+        let service = layer.layer(tower::service_fn(|req: http::Request<Code>| async move {
+            Ok::<_, BoxError>(Status::new(*req.body(), "").into_http())
+        }));
+
+        // Client errors are the caller's fault and leave the circuit closed
+        for code in [Code::InvalidArgument, Code::Unavailable, Code::Internal] {
+            service.clone().oneshot(http::Request::new(code)).await.unwrap();
+        }
+        assert_eq!(breaker.state().await, CircuitState::Open);
+
+        let err = service.oneshot(http::Request::new(Code::Ok)).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlatformError>(),
+            Some(PlatformError::CircuitOpen { service }) if service == "token-service"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_layer_follows_config_updates() {
+        let layer = CircuitBreakerLayer::new("iam-service", Arc::new(CircuitBreaker::with_defaults()));
+        let service = layer.layer(tower::service_fn(|_: http::Request<()>| async {
+            Err::<http::Response<()>, BoxError>("connection refused".into())
+        }));
+
+        layer
+            .breaker()
+            .update_config(CircuitBreakerConfig::default().with_failure_threshold(1));
+        assert!(service.oneshot(http::Request::new(())).await.is_err());
+        assert_eq!(layer.breaker().state().await, CircuitState::Open);
+    }
 }
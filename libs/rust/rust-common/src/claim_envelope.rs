@@ -0,0 +1,146 @@
+//! Encrypted private claim envelopes.
+//!
+//! The token service can encrypt selected claims of a signed JWT with
+//! AES-GCM through the crypto-service, replacing each value with a compact
+//! envelope string:
+//!
+//! ```text
+//! enc.<key>.<iv>.<ciphertext>.<tag>
+//! ```
+//!
+//! Every part after the `enc` marker is base64url without padding; `key`
+//! encodes the crypto-service key as `namespace/id/version`. The plaintext is
+//! the claim's JSON value, and the additional authenticated data from [`aad`]
+//! binds the envelope to the token's `jti` and the claim name, so an envelope
+//! copied into another token or claim does not decrypt.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+/// Marker opening every envelope
+pub const ENVELOPE_PREFIX: &str = "enc.";
+
+/// AES-GCM encrypted claim value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimEnvelope {
+    /// Namespace of the crypto-service key
+    pub key_namespace: String,
+    /// Crypto-service key identifier
+    pub key_id: String,
+    /// Key version used for encryption
+    pub key_version: u32,
+    /// Initialization vector
+    pub iv: Vec<u8>,
+    /// Encrypted JSON claim value
+    pub ciphertext: Vec<u8>,
+    /// Authentication tag
+    pub tag: Vec<u8>,
+}
+
+impl ClaimEnvelope {
+    /// Whether a claim value looks like an envelope.
+    #[must_use]
+    pub fn is_envelope(value: &str) -> bool {
+        value.starts_with(ENVELOPE_PREFIX)
+    }
+
+    /// Compact string form stored as the claim value.
+    #[must_use]
+    pub fn to_compact(&self) -> String {
+        let key = format!("{}/{}/{}", self.key_namespace, self.key_id, self.key_version);
+        format!(
+            "{ENVELOPE_PREFIX}{}.{}.{}.{}",
+            URL_SAFE_NO_PAD.encode(key),
+            URL_SAFE_NO_PAD.encode(&self.iv),
+            URL_SAFE_NO_PAD.encode(&self.ciphertext),
+            URL_SAFE_NO_PAD.encode(&self.tag),
+        )
+    }
+
+    /// Parses a compact envelope; `None` for any other value.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.strip_prefix(ENVELOPE_PREFIX)?.split('.');
+        let mut next = || URL_SAFE_NO_PAD.decode(parts.next()?).ok();
+        let key = String::from_utf8(next()?).ok()?;
+        let (iv, ciphertext, tag) = (next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let (key, version) = key.rsplit_once('/')?;
+        let (namespace, id) = key.rsplit_once('/')?;
+        if namespace.is_empty() || id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            key_namespace: namespace.to_string(),
+            key_id: id.to_string(),
+            key_version: version.parse().ok()?,
+            iv,
+            ciphertext,
+            tag,
+        })
+    }
+}
+
+/// Additional authenticated data binding an envelope to a token and claim.
+#[must_use]
+pub fn aad(jti: &str, claim: &str) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(jti.len() + claim.len() + 1);
+    aad.extend_from_slice(jti.as_bytes());
+    aad.push(0);
+    aad.extend_from_slice(claim.as_bytes());
+    aad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope() -> ClaimEnvelope {
+        ClaimEnvelope {
+            key_namespace: "token/claims".to_string(),
+            key_id: "private-claims".to_string(),
+            key_version: 3,
+            iv: vec![7; 12],
+            ciphertext: br#""123-45-6789""#.to_vec(),
+            tag: vec![9; 16],
+        }
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        let compact = envelope().to_compact();
+        assert!(ClaimEnvelope::is_envelope(&compact));
+        assert_eq!(compact.split('.').count(), 5);
+        assert_eq!(ClaimEnvelope::parse(&compact), Some(envelope()));
+    }
+
+    #[test]
+    fn test_parse_rejects_other_values() {
+        let compact = envelope().to_compact();
+        for value in [
+            "acme",
+            "enc.",
+            "enc.a.b.c",
+            &format!("{compact}.extra"),
+            &compact.replacen("enc.", "enc.!", 1),
+        ] {
+            assert_eq!(ClaimEnvelope::parse(value), None, "{value}");
+        }
+
+        let unversioned = ClaimEnvelope::parse(&compact.replacen(
+            &URL_SAFE_NO_PAD.encode("token/claims/private-claims/3"),
+            &URL_SAFE_NO_PAD.encode("private-claims/v3"),
+            1,
+        ));
+        assert_eq!(unversioned, None);
+    }
+
+    #[test]
+    fn test_aad_binds_jti_and_claim() {
+        assert_eq!(aad("jti-1", "national_id"), b"jti-1\0national_id");
+        assert_ne!(aad("jti-1", "national_id"), aad("jti-1n", "ational_id"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Rate, errors and duration of gRPC methods.
+//!
+//! [`GrpcMetricsLayer`] wraps the server, so every tonic service added to the
+//! builder is measured without handlers counting anything themselves. Each
+//! RPC is labelled with its `service`, `method` and canonical status `code`:
+//!
+//! - `<namespace>_grpc_requests_total` counts finished RPCs
+//! - `<namespace>_grpc_errors_total` counts those not ending in `OK`
+//! - `<namespace>_grpc_request_duration_seconds` observes their latency
+//!
+//! The code is taken from the response headers, where tonic puts the status
+//! of a handler error. Responses without one are counted as `OK`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use tonic::codegen::http;
+use tonic::{Code, Status};
+use tower::{Layer, Service};
+
+use crate::span_attributes::code_name;
+
+const LABELS: [&str; 3] = ["service", "method", "code"];
+const BUCKETS: [f64; 11] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// RED metrics of the RPCs served by one service.
+#[derive(Debug, Clone)]
+pub struct GrpcMetrics {
+    requests: IntCounterVec,
+    errors: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl GrpcMetrics {
+    /// Create the metrics under `namespace` and register them in `registry`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metrics are already registered.
+    pub fn new(namespace: &str, registry: &Registry) -> Result<Self, prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new("grpc_requests_total", "Total gRPC requests handled").namespace(namespace),
+            &LABELS,
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+
+        let errors = IntCounterVec::new(
+            Opts::new("grpc_errors_total", "Total gRPC requests not ending in OK")
+                .namespace(namespace),
+            &LABELS,
+        )?;
+        registry.register(Box::new(errors.clone()))?;
+
+        let duration = HistogramVec::new(
+            HistogramOpts::new("grpc_request_duration_seconds", "gRPC request latency in seconds")
+                .namespace(namespace)
+                .buckets(BUCKETS.to_vec()),
+            &LABELS,
+        )?;
+        registry.register(Box::new(duration.clone()))?;
+
+        Ok(Self {
+            requests,
+            errors,
+            duration,
+        })
+    }
+
+    /// Record one RPC on `path` (`/package.Service/Method`) ending in `code`.
+    pub fn record(&self, path: &str, code: Code, duration_secs: f64) {
+        let (service, method) = split_path(path);
+        let labels = [service, method, code_name(code)];
+        self.requests.with_label_values(&labels).inc();
+        if code != Code::Ok {
+            self.errors.with_label_values(&labels).inc();
+        }
+        self.duration.with_label_values(&labels).observe(duration_secs);
+    }
+
+    /// RPCs recorded for `service`, `method` and `code`.
+    #[must_use]
+    pub fn requests(&self, service: &str, method: &str, code: Code) -> u64 {
+        self.requests
+            .with_label_values(&[service, method, code_name(code)])
+            .get()
+    }
+
+    /// Failed RPCs recorded for `service`, `method` and `code`.
+    #[must_use]
+    pub fn errors(&self, service: &str, method: &str, code: Code) -> u64 {
+        self.errors
+            .with_label_values(&[service, method, code_name(code)])
+            .get()
+    }
+}
+
+/// Split `/package.Service/Method` into service and method.
+fn split_path(path: &str) -> (&str, &str) {
+    path.trim_start_matches('/')
+        .split_once('/')
+        .unwrap_or(("unknown", "unknown"))
+}
+
+/// Server layer recording [`GrpcMetrics`] for every RPC.
+#[derive(Debug, Clone)]
+pub struct GrpcMetricsLayer {
+    metrics: GrpcMetrics,
+}
+
+impl GrpcMetricsLayer {
+    /// Create a layer recording into `metrics`.
+    #[must_use]
+    pub const fn new(metrics: GrpcMetrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for GrpcMetricsLayer {
+    type Service = GrpcMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcMetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Service wrapper created by [`GrpcMetricsLayer`].
+#[derive(Debug, Clone)]
+pub struct GrpcMetricsService<S> {
+    inner: S,
+    metrics: GrpcMetrics,
+}
+
+impl<S, B, R> Service<http::Request<B>> for GrpcMetricsService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<R>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let path = req.uri().path().to_owned();
+        let metrics = self.metrics.clone();
+        let started = Instant::now();
+        let response = self.inner.call(req);
+
+        Box::pin(async move {
+            let response = response.await;
+            let code = response.as_ref().map_or(Code::Unknown, |response| {
+                Status::from_header_map(response.headers()).map_or(Code::Ok, |s| s.code())
+            });
+            metrics.record(&path, code, started.elapsed().as_secs_f64());
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    fn request(path: &str) -> http::Request<()> {
+        http::Request::builder().uri(path).body(()).unwrap()
+    }
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(
+            split_path("/auth.v1.TokenService/IssueToken"),
+            ("auth.v1.TokenService", "IssueToken")
+        );
+        assert_eq!(split_path("/"), ("unknown", "unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_layer_records_rate_errors_and_duration() {
+        let registry = Registry::new();
+        let metrics = GrpcMetrics::new("test", &registry).unwrap();
+        let service = GrpcMetricsLayer::new(metrics.clone()).layer(tower::service_fn(
+            |req: http::Request<()>| async move {
+                let response = if req.uri().path().ends_with("Deny") {
+                    Status::permission_denied("denied").into_http()
+                } else {
+                    http::Response::new(tonic::body::empty_body())
+                };
+                Ok::<_, std::convert::Infallible>(response)
+            },
+        ));
+
+        service.clone().oneshot(request("/pkg.Svc/Allow")).await.unwrap();
+        service.clone().oneshot(request("/pkg.Svc/Allow")).await.unwrap();
+        service.oneshot(request("/pkg.Svc/Deny")).await.unwrap();
+
+        assert_eq!(metrics.requests("pkg.Svc", "Allow", Code::Ok), 2);
+        assert_eq!(metrics.errors("pkg.Svc", "Allow", Code::Ok), 0);
+        assert_eq!(metrics.requests("pkg.Svc", "Deny", Code::PermissionDenied), 1);
+        assert_eq!(metrics.errors("pkg.Svc", "Deny", Code::PermissionDenied), 1);
+
+        let families = registry.gather();
+        let duration = families
+            .iter()
+            .find(|family| family.get_name() == "test_grpc_request_duration_seconds")
+            .unwrap();
+        assert_eq!(duration.get_metric().len(), 2);
+    }
+
+    #[test]
+    fn test_registering_twice_fails() {
+        let registry = Registry::new();
+        GrpcMetrics::new("test", &registry).unwrap();
+        assert!(GrpcMetrics::new("test", &registry).is_err());
+    }
+}
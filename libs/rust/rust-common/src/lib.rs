@@ -7,8 +7,18 @@
 //! - Circuit breaker pattern for resilience
 //! - Logging service gRPC client
 //! - Cache service gRPC client
-//! - OpenTelemetry tracing integration
-//! - Prometheus metrics helpers
+//! - Read-through, write-through and write-behind cache decorators
+//! - OpenTelemetry tracing integration and trace context propagation
+//! - Prometheus metrics pushed over OTLP
+//! - Runtime log level overrides that revert on their own
+//! - Standard span attributes shared by the auth services
+//! - Prometheus metrics helpers and per-method gRPC RED metrics
+//! - Per-tenant metrics labelling the busiest tenants, the rest as `other`
+//! - IPv6-aware listen addresses and dual-stack listeners
+//! - Redis-backed distributed locks with fencing tokens
+//! - OAuth `scope` claim formatting
+//! - Encrypted private claim envelopes
+//! - Bloom filters of revoked token IDs shared by the token service and edge
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
@@ -19,12 +29,41 @@ pub mod retry;
 pub mod circuit_breaker;
 pub mod logging_client;
 pub mod cache_client;
+pub mod cache_decorators;
 pub mod tracing_config;
+mod otlp_metrics;
+pub mod log_level;
+pub mod span_attributes;
+pub mod trace_context;
 pub mod metrics;
+pub mod grpc_metrics;
+pub mod tenant_metrics;
+pub mod listen;
+pub mod lock;
+pub mod scope;
+pub mod claim_envelope;
+pub mod revocation_filter;
 
 pub use error::PlatformError;
 pub use http::{HttpConfig, build_http_client};
 pub use retry::{RetryPolicy, RetryConfig};
-pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerLayer, CircuitBreakerObserver,
+    CircuitBreakerRegistry, CircuitBreakerService, CircuitBreakerStats, CircuitState,
+    SlidingWindow, TripPolicy,
+};
 pub use logging_client::{LoggingClient, LoggingClientConfig, LogEntry, LogLevel};
 pub use cache_client::{CacheClient, CacheClientConfig};
+pub use cache_decorators::{CacheStore, LoaderRegistry, ReadThrough, WriteBehind, WriteThrough};
+pub use grpc_metrics::{GrpcMetrics, GrpcMetricsLayer};
+pub use tenant_metrics::{TenantBuckets, TenantMetrics, TenantMetricsLayer};
+pub use listen::ListenHost;
+pub use revocation_filter::RevocationFilter;
+pub use log_level::{LogLevelControl, LogLevelStatus};
+pub use span_attributes::{AuthSpan, AuthSpanLayer};
+pub use trace_context::{extract_context, TraceContextLayer};
+pub use tracing_config::{init_telemetry, Telemetry, TracingConfig};
+pub use lock::{
+    DistributedLock, InMemoryLeaseStore, Lease, LeaseConfig, LeaseLost, LeaseStore, LossReason,
+    RedisLeaseStore,
+};
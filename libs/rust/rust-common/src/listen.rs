@@ -0,0 +1,195 @@
+//! Listen address parsing and listener binding.
+//!
+//! Joining a host and port with `format!("{host}:{port}")` breaks for IPv6
+//! hosts, which need brackets. This module parses the host on its own,
+//! accepting bare and bracketed IPv6 literals, and binds one listener per
+//! address family when dual-stack listening is requested.
+
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+
+use socket2::{Domain, Socket, Type};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::{Stream, StreamExt, StreamMap};
+
+/// Pending connections queued per listener.
+const LISTEN_BACKLOG: i32 = 1024;
+
+/// Host a service listens on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListenHost {
+    /// IP address literal
+    Ip(IpAddr),
+    /// `localhost`: the loopback address
+    Localhost,
+}
+
+impl ListenHost {
+    /// Socket addresses to bind on `port`.
+    ///
+    /// Dual-stack listening binds the IPv4 and the IPv6 wildcard (or
+    /// loopback) address separately, so it requires a wildcard host or
+    /// `localhost`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if dual-stack listening is requested for a specific
+    /// address.
+    pub fn socket_addrs(self, port: u16, dual_stack: bool) -> Result<Vec<SocketAddr>, String> {
+        let ips = match (self, dual_stack) {
+            (Self::Localhost, false) => vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
+            (Self::Localhost, true) => {
+                vec![IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)]
+            }
+            (Self::Ip(ip), false) => vec![ip],
+            (Self::Ip(ip), true) if ip.is_unspecified() => {
+                vec![IpAddr::V4(Ipv4Addr::UNSPECIFIED), IpAddr::V6(Ipv6Addr::UNSPECIFIED)]
+            }
+            (Self::Ip(ip), true) => {
+                return Err(format!(
+                    "dual-stack listening needs a wildcard host or localhost, got {ip}"
+                ));
+            }
+        };
+        Ok(ips.into_iter().map(|ip| SocketAddr::new(ip, port)).collect())
+    }
+}
+
+impl FromStr for ListenHost {
+    type Err = String;
+
+    /// Parses `0.0.0.0`, `::`, `[::1]`, `localhost` and other IP literals.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let host = s.trim();
+        if host.eq_ignore_ascii_case("localhost") {
+            return Ok(Self::Localhost);
+        }
+
+        let bracketed = host.strip_prefix('[').and_then(|h| h.strip_suffix(']'));
+        if let Some(inner) = bracketed {
+            return inner
+                .parse::<Ipv6Addr>()
+                .map(|ip| Self::Ip(IpAddr::V6(ip)))
+                .map_err(|_| format!("{s:?} is not a bracketed IPv6 address"));
+        }
+        host.parse::<IpAddr>().map(Self::Ip).map_err(|_| {
+            if host.parse::<SocketAddr>().is_ok() {
+                format!("{s:?} includes a port; set the port separately")
+            } else {
+                format!("{s:?} is not an IP address or localhost")
+            }
+        })
+    }
+}
+
+impl fmt::Display for ListenHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip(IpAddr::V6(ip)) => write!(f, "[{ip}]"),
+            Self::Ip(IpAddr::V4(ip)) => write!(f, "{ip}"),
+            Self::Localhost => f.write_str("localhost"),
+        }
+    }
+}
+
+/// Bind a listener per address.
+///
+/// IPv6 listeners bound next to an IPv4 one only accept IPv6 connections, so
+/// the two do not compete for IPv4-mapped addresses. Must be called within a
+/// Tokio runtime.
+///
+/// # Errors
+///
+/// Returns the first bind error.
+pub fn bind(addrs: &[SocketAddr]) -> io::Result<Vec<TcpListener>> {
+    let has_ipv4 = addrs.iter().any(SocketAddr::is_ipv4);
+    addrs
+        .iter()
+        .map(|addr| {
+            let socket = Socket::new(Domain::for_address(*addr), Type::STREAM, None)?;
+            if addr.is_ipv6() && has_ipv4 {
+                socket.set_only_v6(true)?;
+            }
+            #[cfg(unix)]
+            socket.set_reuse_address(true)?;
+            socket.set_nonblocking(true)?;
+            socket.bind(&(*addr).into())?;
+            socket.listen(LISTEN_BACKLOG)?;
+            TcpListener::from_std(socket.into())
+        })
+        .collect()
+}
+
+/// Merge the connections accepted by several listeners into one stream,
+/// e.g. for `tonic`'s `serve_with_incoming`.
+pub fn incoming(listeners: Vec<TcpListener>) -> impl Stream<Item = io::Result<TcpStream>> {
+    let mut streams = StreamMap::new();
+    for (index, listener) in listeners.into_iter().enumerate() {
+        streams.insert(index, TcpListenerStream::new(listener));
+    }
+    streams.map(|(_, connection)| connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(s: &str) -> ListenHost {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parses_ipv4_ipv6_and_localhost() {
+        assert_eq!(host("0.0.0.0"), ListenHost::Ip(IpAddr::V4(Ipv4Addr::UNSPECIFIED)));
+        assert_eq!(host("::"), ListenHost::Ip(IpAddr::V6(Ipv6Addr::UNSPECIFIED)));
+        assert_eq!(host("[::1]"), ListenHost::Ip(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        assert_eq!(host("LocalHost"), ListenHost::Localhost);
+
+        let addrs = host("fd00::1").socket_addrs(50051, false).unwrap();
+        assert_eq!(addrs[0].to_string(), "[fd00::1]:50051");
+        assert_eq!(host("::1").to_string(), "[::1]");
+    }
+
+    #[test]
+    fn test_rejects_invalid_hosts() {
+        assert!("0.0.0.0:50051".parse::<ListenHost>().unwrap_err().contains("port"));
+        assert!("[::1]:50051".parse::<ListenHost>().is_err());
+        assert!("[127.0.0.1]".parse::<ListenHost>().is_err());
+        assert!("auth.internal".parse::<ListenHost>().is_err());
+        assert!("".parse::<ListenHost>().is_err());
+    }
+
+    #[test]
+    fn test_dual_stack_addresses() {
+        let addrs = host("::").socket_addrs(8080, true).unwrap();
+        assert_eq!(
+            addrs,
+            vec![
+                "0.0.0.0:8080".parse::<SocketAddr>().unwrap(),
+                "[::]:8080".parse::<SocketAddr>().unwrap()
+            ]
+        );
+        assert_eq!(host("localhost").socket_addrs(8080, true).unwrap().len(), 2);
+        assert!(host("10.0.0.1").socket_addrs(8080, true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_binds_and_accepts_on_each_listener() {
+        let listeners = bind(&["127.0.0.1:0".parse().unwrap(), "127.0.0.2:0".parse().unwrap()]);
+        let Ok(listeners) = listeners else {
+            // 127.0.0.2 is not routable on every platform
+            return;
+        };
+        let addrs: Vec<SocketAddr> = listeners.iter().map(|l| l.local_addr().unwrap()).collect();
+        let mut incoming = Box::pin(incoming(listeners));
+
+        for addr in addrs {
+            let _client = TcpStream::connect(addr).await.unwrap();
+            let accepted = incoming.next().await.unwrap().unwrap();
+            assert_eq!(accepted.local_addr().unwrap(), addr);
+        }
+    }
+}
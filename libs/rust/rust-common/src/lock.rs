@@ -0,0 +1,625 @@
+//! Distributed locks for leader election.
+//!
+//! A [`DistributedLock`] grants at most one replica a [`Lease`] on a named
+//! key, so schedulers such as key rotation, reapers and migrations run once
+//! per deployment. Each grant carries a fencing token that increases with
+//! every acquisition; pass it to the resources a job writes so they can
+//! reject a stale holder whose lease expired while it was paused.
+//!
+//! Leases renew themselves in the background. A lease is lost when another
+//! holder has taken the key or renewals fail until the TTL has elapsed; the
+//! lock's loss callbacks then run and [`Lease::lost`] resolves.
+//!
+//! [`RedisLeaseStore`] keeps leases in a single Redis instance; the
+//! in-memory store suits tests and single-replica deployments.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use redis::aio::ConnectionManager;
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::PlatformError;
+
+/// Sets the lease only if absent and returns the next fencing token.
+const ACQUIRE_SCRIPT: &str = r"
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    return redis.call('INCR', KEYS[2])
+end
+return false
+";
+
+/// Extends the lease if still held by the caller.
+const RENEW_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+";
+
+/// Deletes the lease if still held by the caller.
+const RELEASE_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+";
+
+/// Storage of leases, shared by every replica competing for them.
+pub trait LeaseStore: Send + Sync + 'static {
+    /// Grants `key` to `holder` for `ttl` unless another holder has it.
+    ///
+    /// Returns the fencing token of the grant, or `None` when the key is
+    /// held.
+    fn acquire(
+        &self,
+        key: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<Option<u64>, PlatformError>> + Send;
+
+    /// Extends `holder`'s lease on `key` to `ttl` from now.
+    ///
+    /// Returns `false` when `holder` no longer has the key.
+    fn renew(
+        &self,
+        key: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<bool, PlatformError>> + Send;
+
+    /// Gives up `holder`'s lease on `key`, if it still has it.
+    fn release(
+        &self,
+        key: &str,
+        holder: &str,
+    ) -> impl Future<Output = Result<bool, PlatformError>> + Send;
+}
+
+/// Leases in a single Redis instance.
+///
+/// The lease lives under the key itself and its fencing counter under
+/// `<key>:fencing`, which never expires so tokens keep increasing across
+/// holders.
+#[derive(Clone)]
+pub struct RedisLeaseStore {
+    conn: ConnectionManager,
+}
+
+impl RedisLeaseStore {
+    /// Connect to the Redis instance at `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is invalid or Redis is unreachable.
+    pub async fn connect(url: &str) -> Result<Self, PlatformError> {
+        let client = redis::Client::open(url).map_err(|e| redis_error(&e))?;
+        let conn = ConnectionManager::new(client).await.map_err(|e| redis_error(&e))?;
+        Ok(Self { conn })
+    }
+
+    /// Use an existing connection.
+    #[must_use]
+    pub const fn new(conn: ConnectionManager) -> Self {
+        Self { conn }
+    }
+}
+
+impl LeaseStore for RedisLeaseStore {
+    async fn acquire(
+        &self,
+        key: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Option<u64>, PlatformError> {
+        redis::Script::new(ACQUIRE_SCRIPT)
+            .key(key)
+            .key(format!("{key}:fencing"))
+            .arg(holder)
+            .arg(millis(ttl))
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| redis_error(&e))
+    }
+
+    async fn renew(&self, key: &str, holder: &str, ttl: Duration) -> Result<bool, PlatformError> {
+        let renewed: i64 = redis::Script::new(RENEW_SCRIPT)
+            .key(key)
+            .arg(holder)
+            .arg(millis(ttl))
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| redis_error(&e))?;
+        Ok(renewed == 1)
+    }
+
+    async fn release(&self, key: &str, holder: &str) -> Result<bool, PlatformError> {
+        let released: i64 = redis::Script::new(RELEASE_SCRIPT)
+            .key(key)
+            .arg(holder)
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| redis_error(&e))?;
+        Ok(released == 1)
+    }
+}
+
+fn redis_error(e: &redis::RedisError) -> PlatformError {
+    PlatformError::Unavailable(format!("Redis: {e}"))
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Leases held in process memory.
+#[derive(Debug, Default)]
+pub struct InMemoryLeaseStore {
+    state: Mutex<InMemoryLeases>,
+}
+
+#[derive(Debug, Default)]
+struct InMemoryLeases {
+    /// Holder and expiry of each held key
+    held: HashMap<String, (String, Instant)>,
+    /// Last fencing token granted per key
+    fencing: HashMap<String, u64>,
+}
+
+impl InMemoryLeaseStore {
+    /// Create an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, InMemoryLeases> {
+        self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl LeaseStore for InMemoryLeaseStore {
+    async fn acquire(
+        &self,
+        key: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Option<u64>, PlatformError> {
+        let mut state = self.state();
+        let now = Instant::now();
+        if state.held.get(key).is_some_and(|(_, expires)| *expires > now) {
+            return Ok(None);
+        }
+        state.held.insert(key.to_string(), (holder.to_string(), now + ttl));
+        let token = state.fencing.entry(key.to_string()).or_default();
+        *token += 1;
+        let token = *token;
+        drop(state);
+        Ok(Some(token))
+    }
+
+    async fn renew(&self, key: &str, holder: &str, ttl: Duration) -> Result<bool, PlatformError> {
+        let mut state = self.state();
+        let now = Instant::now();
+        let Some((current, expires)) = state.held.get_mut(key) else {
+            return Ok(false);
+        };
+        if current != holder || *expires <= now {
+            return Ok(false);
+        }
+        *expires = now + ttl;
+        drop(state);
+        Ok(true)
+    }
+
+    async fn release(&self, key: &str, holder: &str) -> Result<bool, PlatformError> {
+        let mut state = self.state();
+        let held = state.held.get(key).is_some_and(|(current, _)| current == holder);
+        if held {
+            state.held.remove(key);
+        }
+        drop(state);
+        Ok(held)
+    }
+}
+
+/// Lease timing.
+#[derive(Debug, Clone)]
+pub struct LeaseConfig {
+    /// How long a grant or renewal lasts
+    pub ttl: Duration,
+    /// Time between renewals, well below `ttl`
+    pub renew_interval: Duration,
+    /// Time between attempts while waiting to acquire
+    pub retry_interval: Duration,
+}
+
+impl Default for LeaseConfig {
+    fn default() -> Self {
+        Self::with_ttl(Duration::from_secs(30))
+    }
+}
+
+impl LeaseConfig {
+    /// Create a config renewing and retrying every third of `ttl`.
+    #[must_use]
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            renew_interval: ttl / 3,
+            retry_interval: ttl / 3,
+        }
+    }
+}
+
+/// Why a lease was lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossReason {
+    /// Another holder has the key
+    Taken,
+    /// Renewals failed until the TTL elapsed
+    Expired,
+}
+
+/// A lease that was lost, passed to loss callbacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaseLost {
+    /// Key of the lease
+    pub key: String,
+    /// Fencing token of the lost grant
+    pub fencing_token: u64,
+    /// Why it was lost
+    pub reason: LossReason,
+}
+
+type LossCallback = Arc<dyn Fn(&LeaseLost) + Send + Sync>;
+
+/// Named lock granting leases from a [`LeaseStore`].
+pub struct DistributedLock<S: LeaseStore> {
+    store: Arc<S>,
+    key: String,
+    holder: String,
+    config: LeaseConfig,
+    on_lost: Vec<LossCallback>,
+}
+
+impl<S: LeaseStore> fmt::Debug for DistributedLock<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DistributedLock")
+            .field("key", &self.key)
+            .field("holder", &self.holder)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: LeaseStore> DistributedLock<S> {
+    /// Create a lock on `key`, held under a random holder ID.
+    pub fn new(store: Arc<S>, key: impl Into<String>) -> Self {
+        Self {
+            store,
+            key: key.into(),
+            holder: uuid::Uuid::new_v4().to_string(),
+            config: LeaseConfig::default(),
+            on_lost: Vec::new(),
+        }
+    }
+
+    /// Set the lease timing.
+    #[must_use]
+    pub const fn with_config(mut self, config: LeaseConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Hold leases under `holder`, e.g. a pod name, instead of a random ID.
+    #[must_use]
+    pub fn with_holder(mut self, holder: impl Into<String>) -> Self {
+        self.holder = holder.into();
+        self
+    }
+
+    /// Run `callback` whenever a lease of this lock is lost.
+    #[must_use]
+    pub fn on_lost(mut self, callback: impl Fn(&LeaseLost) + Send + Sync + 'static) -> Self {
+        self.on_lost.push(Arc::new(callback));
+        self
+    }
+
+    /// Key the lock guards.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Take a lease if no one holds one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be reached.
+    pub async fn try_acquire(&self) -> Result<Option<Lease>, PlatformError> {
+        let Some(fencing_token) = self
+            .store
+            .acquire(&self.key, &self.holder, self.config.ttl)
+            .await?
+        else {
+            return Ok(None);
+        };
+        debug!(key = %self.key, fencing_token, "Lease acquired");
+        Ok(Some(self.start_renewal(fencing_token)))
+    }
+
+    /// Take a lease, waiting up to `wait` for the current holder to let go.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be reached.
+    pub async fn acquire(&self, wait: Duration) -> Result<Option<Lease>, PlatformError> {
+        let deadline = Instant::now() + wait;
+        loop {
+            if let Some(lease) = self.try_acquire().await? {
+                return Ok(Some(lease));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(self.config.retry_interval.min(deadline - now)).await;
+        }
+    }
+
+    /// Run `job` if a lease can be taken, releasing it afterwards.
+    ///
+    /// Returns `None` when another holder has the lease. The job is dropped
+    /// as soon as the lease is lost.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be reached or the lease is lost
+    /// before the job completes.
+    pub async fn run_exclusive<F: Future>(&self, job: F) -> Result<Option<F::Output>, PlatformError> {
+        let Some(mut lease) = self.try_acquire().await? else {
+            return Ok(None);
+        };
+        tokio::select! {
+            output = job => {
+                lease.release().await?;
+                Ok(Some(output))
+            }
+            () = lease.lost() => Err(PlatformError::Unavailable(format!(
+                "lease on {} lost while running",
+                self.key
+            ))),
+        }
+    }
+
+    fn start_renewal(&self, fencing_token: u64) -> Lease {
+        let (lost_tx, lost_rx) = watch::channel(false);
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let renewal = Renewal {
+            store: self.store.clone(),
+            key: self.key.clone(),
+            holder: self.holder.clone(),
+            config: self.config.clone(),
+            fencing_token,
+            on_lost: self.on_lost.clone(),
+            lost: lost_tx,
+        };
+        Lease {
+            key: self.key.clone(),
+            fencing_token,
+            lost: lost_rx,
+            stop: Some(stop_tx),
+            task: Some(tokio::spawn(renewal.run(stop_rx))),
+        }
+    }
+}
+
+/// Background renewal of one grant.
+struct Renewal<S: LeaseStore> {
+    store: Arc<S>,
+    key: String,
+    holder: String,
+    config: LeaseConfig,
+    fencing_token: u64,
+    on_lost: Vec<LossCallback>,
+    lost: watch::Sender<bool>,
+}
+
+impl<S: LeaseStore> Renewal<S> {
+    /// Renews until stopped, releasing the key, or until the lease is lost.
+    async fn run(self, mut stop: oneshot::Receiver<()>) -> Result<bool, PlatformError> {
+        let mut expires = Instant::now() + self.config.ttl;
+        loop {
+            tokio::select! {
+                _ = &mut stop => return self.store.release(&self.key, &self.holder).await,
+                () = tokio::time::sleep(self.config.renew_interval) => {}
+            }
+
+            let attempted = Instant::now();
+            match self.store.renew(&self.key, &self.holder, self.config.ttl).await {
+                Ok(true) => expires = attempted + self.config.ttl,
+                Ok(false) => {
+                    self.lose(LossReason::Taken);
+                    return Ok(false);
+                }
+                Err(e) if Instant::now() >= expires => {
+                    warn!(key = %self.key, error = %e, "Lease renewal failed past its TTL");
+                    self.lose(LossReason::Expired);
+                    return Ok(false);
+                }
+                Err(e) => warn!(key = %self.key, error = %e, "Lease renewal failed, retrying"),
+            }
+        }
+    }
+
+    fn lose(&self, reason: LossReason) {
+        warn!(key = %self.key, fencing_token = self.fencing_token, ?reason, "Lease lost");
+        self.lost.send_replace(true);
+        let lost = LeaseLost {
+            key: self.key.clone(),
+            fencing_token: self.fencing_token,
+            reason,
+        };
+        for callback in &self.on_lost {
+            callback(&lost);
+        }
+    }
+}
+
+/// A held lease, renewed in the background until released or dropped.
+///
+/// Dropping the lease releases it in the background; call
+/// [`Lease::release`] to wait for the release instead.
+#[derive(Debug)]
+pub struct Lease {
+    key: String,
+    fencing_token: u64,
+    lost: watch::Receiver<bool>,
+    stop: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<Result<bool, PlatformError>>>,
+}
+
+impl Lease {
+    /// Key the lease is on.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Fencing token of this grant, greater than that of every earlier one.
+    #[must_use]
+    pub const fn fencing_token(&self) -> u64 {
+        self.fencing_token
+    }
+
+    /// Whether the lease is still held.
+    #[must_use]
+    pub fn is_held(&self) -> bool {
+        !*self.lost.borrow()
+    }
+
+    /// Resolve once the lease is lost; pending forever while it is held.
+    pub async fn lost(&mut self) {
+        if self.lost.wait_for(|lost| *lost).await.is_err() {
+            // Renewal stopped without losing the lease
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Stop renewing and give up the lease.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be reached; the lease then
+    /// expires after its TTL.
+    pub async fn release(mut self) -> Result<(), PlatformError> {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        match self.task.take() {
+            Some(task) => task
+                .await
+                .map_err(|e| PlatformError::Internal(format!("lease renewal panicked: {e}")))?
+                .map(drop),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn config() -> LeaseConfig {
+        LeaseConfig {
+            ttl: Duration::from_millis(200),
+            renew_interval: Duration::from_millis(20),
+            retry_interval: Duration::from_millis(10),
+        }
+    }
+
+    fn lock(store: &Arc<InMemoryLeaseStore>) -> DistributedLock<InMemoryLeaseStore> {
+        DistributedLock::new(store.clone(), "jobs:rotation").with_config(config())
+    }
+
+    #[tokio::test]
+    async fn test_one_holder_at_a_time_with_increasing_tokens() {
+        let store = Arc::new(InMemoryLeaseStore::new());
+        let (first, second) = (lock(&store), lock(&store));
+
+        let lease = first.try_acquire().await.unwrap().unwrap();
+        assert_eq!(lease.fencing_token(), 1);
+        assert!(second.try_acquire().await.unwrap().is_none());
+
+        // Renewal keeps the lease past its TTL
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(lease.is_held());
+        assert!(second.try_acquire().await.unwrap().is_none());
+
+        lease.release().await.unwrap();
+        let lease = second.try_acquire().await.unwrap().unwrap();
+        assert_eq!(lease.fencing_token(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_dropped_lease() {
+        let store = Arc::new(InMemoryLeaseStore::new());
+        let lease = lock(&store).try_acquire().await.unwrap().unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(lease);
+        });
+
+        let waiting = lock(&store);
+        assert!(waiting.acquire(Duration::from_secs(1)).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_loss_runs_callbacks() {
+        let store = Arc::new(InMemoryLeaseStore::new());
+        let losses = Arc::new(AtomicU32::new(0));
+        let counted = losses.clone();
+        let lock = lock(&store).with_holder("replica-a").on_lost(move |lost| {
+            assert_eq!(lost.reason, LossReason::Taken);
+            counted.fetch_add(1, Ordering::SeqCst);
+        });
+        let mut lease = lock.try_acquire().await.unwrap().unwrap();
+
+        // Another holder takes over once the key is gone
+        store.release("jobs:rotation", "replica-a").await.unwrap();
+        store
+            .acquire("jobs:rotation", "replica-b", Duration::from_secs(1))
+            .await
+            .unwrap()
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(1), lease.lost()).await.unwrap();
+        assert!(!lease.is_held());
+        assert_eq!(losses.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_exclusive() {
+        let store = Arc::new(InMemoryLeaseStore::new());
+        assert_eq!(lock(&store).run_exclusive(async { 7 }).await.unwrap(), Some(7));
+
+        let _held = lock(&store).try_acquire().await.unwrap().unwrap();
+        assert_eq!(lock(&store).run_exclusive(async { 7 }).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,261 @@
+//! Runtime log level control.
+//!
+//! [`LogLevelControl`] swaps the `EnvFilter` of a running subscriber through
+//! a [`reload`] handle, e.g. to turn on `debug` for one module while
+//! investigating an incident. Every override expires: once its duration has
+//! passed the filter reverts to the directives the service started with, so
+//! verbose logging cannot be left on by accident.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::{info, warn};
+use tracing_subscriber::{EnvFilter, reload};
+
+use crate::error::PlatformError;
+
+/// Longest an override lasts unless configured otherwise.
+pub const DEFAULT_MAX_DURATION: Duration = Duration::from_secs(3600);
+
+type Reload = dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync;
+
+/// Current filter of a [`LogLevelControl`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogLevelStatus {
+    /// Directives in effect, e.g. `info,auth_edge::jwt=debug`
+    pub directives: String,
+    /// Directives restored when the override expires
+    pub default: String,
+    /// Seconds until the override expires, `None` without one
+    pub reverts_in_secs: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct Override {
+    directives: Option<String>,
+    until: Option<Instant>,
+    /// Bumped on every change so a stale reversion does nothing
+    generation: u64,
+}
+
+struct Inner {
+    reload: Box<Reload>,
+    default: String,
+    current: Mutex<Override>,
+}
+
+/// Changes the log filter at runtime and reverts it after a while.
+#[derive(Clone)]
+pub struct LogLevelControl {
+    inner: Arc<Inner>,
+    max_duration: Duration,
+}
+
+impl std::fmt::Debug for LogLevelControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogLevelControl")
+            .field("default", &self.inner.default)
+            .field("max_duration", &self.max_duration)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LogLevelControl {
+    /// Control the filter behind `handle`, which was built from `default`.
+    pub fn new<S: 'static>(
+        handle: reload::Handle<EnvFilter, S>,
+        default: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                reload: Box::new(move |filter| handle.reload(filter)),
+                default: default.into(),
+                current: Mutex::new(Override::default()),
+            }),
+            max_duration: DEFAULT_MAX_DURATION,
+        }
+    }
+
+    /// Cap overrides at `max_duration`.
+    #[must_use]
+    pub const fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+
+    /// Longest an override lasts.
+    #[must_use]
+    pub const fn max_duration(&self) -> Duration {
+        self.max_duration
+    }
+
+    /// The filter in effect.
+    #[must_use]
+    pub fn status(&self) -> LogLevelStatus {
+        let current = self.lock();
+        LogLevelStatus {
+            directives: current
+                .directives
+                .clone()
+                .unwrap_or_else(|| self.inner.default.clone()),
+            default: self.inner.default.clone(),
+            reverts_in_secs: current
+                .until
+                .map(|until| until.saturating_duration_since(Instant::now()).as_secs()),
+        }
+    }
+
+    /// Apply `directives` for `duration`, capped at the maximum duration.
+    ///
+    /// A zero `duration` lasts the maximum duration. Must be called within a
+    /// Tokio runtime, which runs the reversion.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `directives` do not parse and `Unavailable`
+    /// if the subscriber is gone.
+    pub fn set(
+        &self,
+        directives: &str,
+        duration: Duration,
+    ) -> Result<LogLevelStatus, PlatformError> {
+        let filter = EnvFilter::try_new(directives)
+            .map_err(|e| PlatformError::invalid_input(format!("invalid log directives: {e}")))?;
+        let duration = if duration.is_zero() {
+            self.max_duration
+        } else {
+            duration.min(self.max_duration)
+        };
+
+        let generation = {
+            let mut current = self.lock();
+            (self.inner.reload)(filter).map_err(|e| PlatformError::unavailable(e.to_string()))?;
+            current.generation += 1;
+            current.directives = Some(directives.to_string());
+            current.until = Some(Instant::now() + duration);
+            current.generation
+        };
+        info!(
+            directives,
+            duration_secs = duration.as_secs(),
+            "Log level overridden"
+        );
+
+        let control = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            control.revert(Some(generation));
+        });
+        Ok(self.status())
+    }
+
+    /// Restore the default directives now.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unavailable` if the subscriber is gone.
+    pub fn reset(&self) -> Result<LogLevelStatus, PlatformError> {
+        if self.revert(None) {
+            Ok(self.status())
+        } else {
+            Err(PlatformError::unavailable("log subscriber is gone"))
+        }
+    }
+
+    /// Restore the default unless another change followed `generation`.
+    fn revert(&self, generation: Option<u64>) -> bool {
+        let mut current = self.lock();
+        if generation.is_some_and(|generation| generation != current.generation) {
+            return true;
+        }
+        if current.directives.is_none() {
+            return true;
+        }
+        // The default parsed when the subscriber was built
+        let filter = EnvFilter::new(&self.inner.default);
+        if let Err(e) = (self.inner.reload)(filter) {
+            warn!(error = %e, "Failed to restore the default log level");
+            return false;
+        }
+        current.generation += 1;
+        current.directives = None;
+        current.until = None;
+        drop(current);
+        info!(directives = %self.inner.default, "Log level restored");
+        true
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Override> {
+        self.inner
+            .current
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Level;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn control() -> (LogLevelControl, impl tracing::Subscriber + Send + Sync) {
+        let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let subscriber = tracing_subscriber::registry().with(filter);
+        (LogLevelControl::new(handle, "info"), subscriber)
+    }
+
+    #[tokio::test]
+    async fn test_override_reverts_after_duration() {
+        let (control, subscriber) = control();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        assert!(!tracing::enabled!(Level::DEBUG));
+
+        let status = control.set("debug", Duration::from_millis(20)).unwrap();
+        assert_eq!(status.directives, "debug");
+        assert_eq!(status.default, "info");
+        assert!(status.reverts_in_secs.is_some());
+        assert!(tracing::enabled!(Level::DEBUG));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let status = control.status();
+        assert_eq!(status.directives, "info");
+        assert_eq!(status.reverts_in_secs, None);
+        assert!(!tracing::enabled!(Level::DEBUG));
+    }
+
+    #[tokio::test]
+    async fn test_later_override_is_not_reverted_early() {
+        let (control, _subscriber) = control();
+        control.set("debug", Duration::from_millis(20)).unwrap();
+        control.set("trace", Duration::from_secs(60)).unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(control.status().directives, "trace");
+
+        let status = control.reset().unwrap();
+        assert_eq!(status.directives, "info");
+    }
+
+    #[tokio::test]
+    async fn test_duration_is_capped() {
+        let (control, _subscriber) = control();
+        let control = control.with_max_duration(Duration::from_secs(60));
+
+        let status = control.set("debug", Duration::from_secs(86_400)).unwrap();
+        assert!(status.reverts_in_secs.unwrap() <= 60);
+        let status = control.set("debug", Duration::ZERO).unwrap();
+        assert!(status.reverts_in_secs.unwrap() <= 60);
+    }
+
+    #[test]
+    fn test_invalid_directives_are_rejected() {
+        let (control, _subscriber) = control();
+        let err = control
+            .set("auth_edge=loud", Duration::from_secs(1))
+            .unwrap_err();
+        assert!(matches!(err, PlatformError::InvalidInput(_)));
+        assert_eq!(control.status().directives, "info");
+    }
+}
@@ -241,6 +241,33 @@ impl LoggingClient {
         }
     }
 
+    /// Send a batch of entries to `Logging_Service` immediately, bypassing the
+    /// buffer.
+    ///
+    /// Unlike [`flush`](Self::flush), an undelivered batch is not logged
+    /// locally; the caller keeps it and chooses its own fallback.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlatformError::CircuitOpen` if `Logging_Service` is unavailable.
+    pub async fn send_batch(&self, entries: Vec<LogEntry>) -> Result<(), PlatformError> {
+        if !self.circuit_breaker.allow_request().await {
+            return Err(PlatformError::circuit_open("logging_service"));
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        // In production, this would send via gRPC
+        self.circuit_breaker.record_success().await;
+        debug!(
+            service = %self.config.service_id,
+            entries = entries.len(),
+            "Sent log batch to Logging_Service"
+        );
+        Ok(())
+    }
+
     /// Fall back to local tracing when Logging_Service is unavailable.
     async fn fallback_to_local(&self) {
         let buffer = self.buffer.read().await;
@@ -350,6 +377,15 @@ mod tests {
         assert_eq!(client.buffer_size().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_send_batch_bypasses_buffer() {
+        let client = LoggingClient::new(LoggingClientConfig::default()).await.unwrap();
+        let entries = vec![LogEntry::new(LogLevel::Info, "audit", "test-service")];
+
+        assert!(client.send_batch(entries).await.is_ok());
+        assert_eq!(client.buffer_size().await, 0);
+    }
+
     #[test]
     fn test_log_level_as_str() {
         assert_eq!(LogLevel::Debug.as_str(), "DEBUG");
@@ -0,0 +1,367 @@
+//! Prometheus metrics pushed over OTLP.
+//!
+//! Services record metrics in Prometheus registries and serve them for
+//! scraping. When an OTLP endpoint is configured, [`init_telemetry`] also
+//! pushes the default registry to the collector on an interval, so metrics
+//! reach the same backend as spans with the same resource attributes.
+//! Counters become monotonic cumulative sums, gauges and untyped metrics
+//! gauges, histograms explicit-bucket histograms and summaries summaries.
+//! Histogram points of a tenant bucket carry the latest exemplar recorded by
+//! [`TenantMetrics`].
+//!
+//! [`init_telemetry`]: crate::tracing_config::init_telemetry
+//! [`TenantMetrics`]: crate::tenant_metrics::TenantMetrics
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_client::MetricsServiceClient;
+use opentelemetry_proto::tonic::common::v1::{AnyValue, InstrumentationScope, KeyValue, any_value};
+use opentelemetry_proto::tonic::metrics::v1::summary_data_point::ValueAtQuantile;
+use opentelemetry_proto::tonic::metrics::v1::{
+    AggregationTemporality, Exemplar, Gauge, Histogram, HistogramDataPoint, Metric,
+    NumberDataPoint, ResourceMetrics, ScopeMetrics, Sum, Summary, SummaryDataPoint, exemplar,
+    metric, number_data_point,
+};
+use opentelemetry_proto::tonic::resource::v1::Resource;
+use prometheus::Registry;
+use prometheus::proto::{MetricFamily, MetricType};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tonic::metadata::MetadataMap;
+use tonic::transport::Channel;
+
+use crate::error::PlatformError;
+use crate::tenant_metrics;
+
+/// Bound on one export call
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pushes a Prometheus registry to an OTLP/gRPC collector.
+pub struct OtlpMetricsExporter {
+    client: MetricsServiceClient<Channel>,
+    registry: Registry,
+    resource: Resource,
+    metadata: MetadataMap,
+    start_time_unix_nano: u64,
+}
+
+impl OtlpMetricsExporter {
+    /// Connects lazily to the collector at `endpoint`; must be called within
+    /// a Tokio runtime.
+    pub fn new(
+        endpoint: &str,
+        metadata: MetadataMap,
+        resource_attributes: &[(String, String)],
+        registry: Registry,
+    ) -> Result<Self, PlatformError> {
+        let channel = Channel::from_shared(endpoint.to_string())
+            .map_err(|e| PlatformError::invalid_input(format!("OTLP metrics endpoint: {e}")))?
+            .connect_lazy();
+        Ok(Self {
+            client: MetricsServiceClient::new(channel),
+            registry,
+            resource: Resource {
+                attributes: resource_attributes
+                    .iter()
+                    .map(|(key, value)| key_value(key, value))
+                    .collect(),
+                dropped_attributes_count: 0,
+            },
+            metadata,
+            start_time_unix_nano: unix_nanos(),
+        })
+    }
+
+    /// Pushes the registry every `interval` until the task is aborted.
+    pub fn spawn(mut self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(status) = self.export().await {
+                    tracing::warn!(error = %status, "Failed to export metrics over OTLP");
+                }
+            }
+        })
+    }
+
+    async fn export(&mut self) -> Result<(), tonic::Status> {
+        let mut request = tonic::Request::new(export_request(
+            &self.registry.gather(),
+            self.resource.clone(),
+            self.start_time_unix_nano,
+            unix_nanos(),
+        ));
+        *request.metadata_mut() = self.metadata.clone();
+        request.set_timeout(EXPORT_TIMEOUT);
+        self.client.export(request).await?;
+        Ok(())
+    }
+}
+
+/// Converts gathered metric families into one OTLP export request.
+fn export_request(
+    families: &[MetricFamily],
+    resource: Resource,
+    start_time_unix_nano: u64,
+    time_unix_nano: u64,
+) -> ExportMetricsServiceRequest {
+    let metrics = families
+        .iter()
+        .map(|family| Metric {
+            name: family.get_name().to_string(),
+            description: family.get_help().to_string(),
+            data: Some(data(family, start_time_unix_nano, time_unix_nano)),
+            ..Metric::default()
+        })
+        .collect();
+
+    ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: Some(resource),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Some(InstrumentationScope {
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    ..InstrumentationScope::default()
+                }),
+                metrics,
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }],
+    }
+}
+
+fn data(family: &MetricFamily, start: u64, now: u64) -> metric::Data {
+    let cumulative = AggregationTemporality::Cumulative as i32;
+    let points = |value: fn(&prometheus::proto::Metric) -> f64| {
+        family
+            .get_metric()
+            .iter()
+            .map(|m| NumberDataPoint {
+                attributes: attributes(m),
+                start_time_unix_nano: start,
+                time_unix_nano: now,
+                value: Some(number_data_point::Value::AsDouble(value(m))),
+                ..NumberDataPoint::default()
+            })
+            .collect()
+    };
+
+    match family.get_field_type() {
+        MetricType::COUNTER => metric::Data::Sum(Sum {
+            data_points: points(|m| m.get_counter().get_value()),
+            aggregation_temporality: cumulative,
+            is_monotonic: true,
+        }),
+        MetricType::GAUGE => metric::Data::Gauge(Gauge {
+            data_points: points(|m| m.get_gauge().get_value()),
+        }),
+        MetricType::UNTYPED => metric::Data::Gauge(Gauge {
+            data_points: points(|m| m.get_untyped().get_value()),
+        }),
+        MetricType::HISTOGRAM => metric::Data::Histogram(Histogram {
+            data_points: family
+                .get_metric()
+                .iter()
+                .map(|m| histogram_point(family.get_name(), m, start, now))
+                .collect(),
+            aggregation_temporality: cumulative,
+        }),
+        MetricType::SUMMARY => metric::Data::Summary(Summary {
+            data_points: family
+                .get_metric()
+                .iter()
+                .map(|m| {
+                    let summary = m.get_summary();
+                    SummaryDataPoint {
+                        attributes: attributes(m),
+                        start_time_unix_nano: start,
+                        time_unix_nano: now,
+                        count: summary.get_sample_count(),
+                        sum: summary.get_sample_sum(),
+                        quantile_values: summary
+                            .get_quantile()
+                            .iter()
+                            .map(|q| ValueAtQuantile {
+                                quantile: q.get_quantile(),
+                                value: q.get_value(),
+                            })
+                            .collect(),
+                        flags: 0,
+                    }
+                })
+                .collect(),
+        }),
+    }
+}
+
+/// Prometheus buckets count every observation up to their bound; OTLP
+/// buckets only those above the previous bound, plus an overflow bucket.
+fn histogram_point(
+    name: &str,
+    m: &prometheus::proto::Metric,
+    start: u64,
+    now: u64,
+) -> HistogramDataPoint {
+    let histogram = m.get_histogram();
+    let mut explicit_bounds = Vec::new();
+    let mut bucket_counts = Vec::new();
+    let mut below = 0;
+    for bucket in histogram
+        .get_bucket()
+        .iter()
+        .filter(|bucket| bucket.get_upper_bound().is_finite())
+    {
+        explicit_bounds.push(bucket.get_upper_bound());
+        bucket_counts.push(bucket.get_cumulative_count().saturating_sub(below));
+        below = bucket.get_cumulative_count();
+    }
+    bucket_counts.push(histogram.get_sample_count().saturating_sub(below));
+
+    HistogramDataPoint {
+        attributes: attributes(m),
+        start_time_unix_nano: start,
+        time_unix_nano: now,
+        count: histogram.get_sample_count(),
+        sum: Some(histogram.get_sample_sum()),
+        bucket_counts,
+        explicit_bounds,
+        exemplars: tenant_exemplar(name, m).into_iter().collect(),
+        ..HistogramDataPoint::default()
+    }
+}
+
+/// Exemplar of the point's tenant bucket, naming the actual tenant.
+fn tenant_exemplar(name: &str, m: &prometheus::proto::Metric) -> Option<Exemplar> {
+    let bucket = m.get_label().iter().find(|label| label.get_name() == "tenant")?;
+    let recorded = tenant_metrics::exemplar(name, bucket.get_value())?;
+    Some(Exemplar {
+        filtered_attributes: vec![key_value("tenant", &recorded.tenant)],
+        time_unix_nano: recorded.time_unix_nano,
+        span_id: recorded.span_id.map(|id| id.to_vec()).unwrap_or_default(),
+        trace_id: recorded.trace_id.map(|id| id.to_vec()).unwrap_or_default(),
+        value: Some(exemplar::Value::AsDouble(recorded.value)),
+    })
+}
+
+fn attributes(m: &prometheus::proto::Metric) -> Vec<KeyValue> {
+    m.get_label()
+        .iter()
+        .map(|label| key_value(label.get_name(), label.get_value()))
+        .collect()
+}
+
+fn key_value(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts};
+
+    fn registry() -> Registry {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(Opts::new("requests_total", "Requests"), &["method"]).unwrap();
+        let in_flight = IntGauge::new("in_flight", "In-flight requests").unwrap();
+        let latency = HistogramVec::new(
+            HistogramOpts::new("latency_seconds", "Latency").buckets(vec![0.1, 1.0]),
+            &["method"],
+        )
+        .unwrap();
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry.register(Box::new(in_flight.clone())).unwrap();
+        registry.register(Box::new(latency.clone())).unwrap();
+
+        requests.with_label_values(&["Issue"]).inc_by(3);
+        in_flight.set(2);
+        for seconds in [0.05, 0.5, 0.7, 5.0] {
+            latency.with_label_values(&["Issue"]).observe(seconds);
+        }
+        registry
+    }
+
+    fn metric<'a>(request: &'a ExportMetricsServiceRequest, name: &str) -> &'a metric::Data {
+        request.resource_metrics[0].scope_metrics[0]
+            .metrics
+            .iter()
+            .find(|m| m.name == name)
+            .and_then(|m| m.data.as_ref())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_export_request_converts_families() {
+        let resource = Resource {
+            attributes: vec![key_value("service.name", "token-service")],
+            dropped_attributes_count: 0,
+        };
+        let request = export_request(&registry().gather(), resource.clone(), 1, 2);
+        assert_eq!(request.resource_metrics[0].resource, Some(resource));
+
+        let metric::Data::Sum(sum) = metric(&request, "requests_total") else {
+            panic!("counter should be a sum");
+        };
+        assert!(sum.is_monotonic);
+        assert_eq!(sum.aggregation_temporality, AggregationTemporality::Cumulative as i32);
+        assert_eq!(sum.data_points[0].attributes, vec![key_value("method", "Issue")]);
+        assert_eq!(sum.data_points[0].value, Some(number_data_point::Value::AsDouble(3.0)));
+        assert_eq!(
+            (sum.data_points[0].start_time_unix_nano, sum.data_points[0].time_unix_nano),
+            (1, 2)
+        );
+
+        let metric::Data::Gauge(gauge) = metric(&request, "in_flight") else {
+            panic!("gauge should be a gauge");
+        };
+        assert_eq!(gauge.data_points[0].value, Some(number_data_point::Value::AsDouble(2.0)));
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_not_cumulative() {
+        let request = export_request(&registry().gather(), Resource::default(), 1, 2);
+        let metric::Data::Histogram(histogram) = metric(&request, "latency_seconds") else {
+            panic!("histogram should be a histogram");
+        };
+        let point = &histogram.data_points[0];
+        assert_eq!(point.explicit_bounds, vec![0.1, 1.0]);
+        assert_eq!(point.bucket_counts, vec![1, 2, 1]);
+        assert_eq!(point.count, 4);
+        assert_eq!(point.sum, Some(6.25));
+        assert!(point.exemplars.is_empty());
+    }
+
+    #[test]
+    fn test_tenant_histograms_carry_exemplars() {
+        let registry = Registry::new();
+        let buckets = std::sync::Arc::new(tenant_metrics::TenantBuckets::new(0));
+        let metrics = tenant_metrics::TenantMetrics::new("otlp", &registry, buckets).unwrap();
+        metrics.record("acme", true, 0.2);
+
+        let request = export_request(&registry.gather(), Resource::default(), 1, 2);
+        let metric::Data::Histogram(histogram) =
+            metric(&request, "otlp_tenant_request_duration_seconds")
+        else {
+            panic!("histogram should be a histogram");
+        };
+        let exemplar = &histogram.data_points[0].exemplars[0];
+        assert_eq!(exemplar.filtered_attributes, vec![key_value("tenant", "acme")]);
+        assert_eq!(exemplar.value, Some(exemplar::Value::AsDouble(0.2)));
+    }
+}
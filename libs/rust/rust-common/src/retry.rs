@@ -135,6 +135,40 @@ impl RetryPolicy {
         }
     }
 
+    /// Execute an async operation, retrying errors accepted by `retryable`.
+    ///
+    /// For callers with their own error type. `retryable` is consulted only
+    /// while attempts remain, so it runs exactly once per retry.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last error if it is not retryable or retries are exhausted.
+    pub async fn execute_when<F, Fut, T, E, R>(
+        &self,
+        mut operation: F,
+        mut retryable: R,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, E>>,
+        R: FnMut(&E) -> bool,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Ok(result) => return Ok(result),
+                Err(error) => {
+                    if attempt >= self.config.max_retries || !retryable(&error) {
+                        return Err(error);
+                    }
+                    let delay = self.delay_for_attempt(attempt);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
     /// Get the maximum number of retries.
     #[must_use]
     pub const fn max_retries(&self) -> u32 {
@@ -206,6 +240,34 @@ mod tests {
         assert_eq!(result.unwrap(), 42);
     }
 
+    #[tokio::test]
+    async fn test_execute_when_custom_predicate() {
+        let policy = RetryPolicy::new(
+            RetryConfig::default()
+                .with_max_retries(2)
+                .with_initial_delay(Duration::from_millis(1))
+                .without_jitter(),
+        );
+        let mut calls = 0;
+        let mut retries = 0;
+        let result: Result<i32, &str> = policy
+            .execute_when(
+                || {
+                    calls += 1;
+                    async { Err("timeout") }
+                },
+                |_| {
+                    retries += 1;
+                    true
+                },
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+        assert_eq!(retries, 2);
+    }
+
     #[tokio::test]
     async fn test_execute_non_retryable_error() {
         let policy = RetryPolicy::with_defaults();
@@ -0,0 +1,166 @@
+//! Bloom filter of revoked token IDs.
+//!
+//! The token service builds a [`RevocationFilter`] over the `jti`s on its
+//! revocation list and serves it to the auth edge, which checks validated
+//! tokens against the filter before the revocation list itself. A filter
+//! has no false negatives: a `jti` it does not contain was not revoked when
+//! the filter was built, so only hits need an exact lookup. Both sides use
+//! this type, so the wire form produced by [`RevocationFilter::to_bytes`]
+//! is read back with the same hashing by [`RevocationFilter::from_parts`].
+
+use sha2::{Digest, Sha256};
+
+use crate::PlatformError;
+
+/// Bloom filter over `jti`s, hashed with SHA-256 and double hashing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevocationFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+    len: usize,
+}
+
+impl RevocationFilter {
+    /// Creates a filter sized for `capacity` entries at the given
+    /// false-positive rate.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn with_capacity(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-capacity * false_positive_rate.ln() / (ln2 * ln2)).ceil().max(64.0);
+        let hashes = (bits / capacity * ln2).round().max(1.0) as u32;
+        Self {
+            bits: vec![0; (bits as usize).div_ceil(64)],
+            hashes,
+            len: 0,
+        }
+    }
+
+    /// Rebuilds a filter from its [`to_bytes`](Self::to_bytes) form.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bits` is not a whole, non-empty number of
+    /// 64-bit words or `hashes` is zero.
+    pub fn from_parts(bits: &[u8], hashes: u32, len: usize) -> Result<Self, PlatformError> {
+        if bits.is_empty() || bits.len() % 8 != 0 || hashes == 0 {
+            return Err(PlatformError::invalid_input("malformed revocation filter"));
+        }
+        let bits = bits
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap_or_default()))
+            .collect();
+        Ok(Self { bits, hashes, len })
+    }
+
+    /// Bit array as little-endian 64-bit words.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bits.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    /// Number of hash functions.
+    #[must_use]
+    pub const fn hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    /// Number of entries inserted.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no entry was inserted.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds a `jti`.
+    pub fn insert(&mut self, jti: &str) {
+        for pos in positions(self.bit_len(), self.hashes, jti) {
+            self.bits[pos / 64] |= 1 << (pos % 64);
+        }
+        self.len += 1;
+    }
+
+    /// Whether the `jti` may have been inserted; `false` is definite.
+    #[must_use]
+    pub fn contains(&self, jti: &str) -> bool {
+        positions(self.bit_len(), self.hashes, jti)
+            .all(|pos| self.bits[pos / 64] & (1 << (pos % 64)) != 0)
+    }
+
+    /// Removes every entry, keeping the size.
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.len = 0;
+    }
+
+    fn bit_len(&self) -> u64 {
+        self.bits.len() as u64 * 64
+    }
+}
+
+/// Bit positions of a `jti` in a filter of `bit_len` bits.
+#[allow(clippy::cast_possible_truncation)]
+fn positions(bit_len: u64, hashes: u32, jti: &str) -> impl Iterator<Item = usize> {
+    let digest = Sha256::digest(jti.as_bytes());
+    let mut h1 = [0; 8];
+    let mut h2 = [0; 8];
+    h1.copy_from_slice(&digest[..8]);
+    h2.copy_from_slice(&digest[8..16]);
+    let (h1, h2) = (u64::from_le_bytes(h1), u64::from_le_bytes(h2) | 1);
+    (0..u64::from(hashes)).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_len) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives_and_few_false_positives() {
+        let mut filter = RevocationFilter::with_capacity(1_000, 1e-3);
+        for i in 0..1_000 {
+            filter.insert(&format!("jti-{i}"));
+        }
+        assert_eq!(filter.len(), 1_000);
+        assert!((0..1_000).all(|i| filter.contains(&format!("jti-{i}"))));
+        let false_positives = (1_000..11_000)
+            .filter(|i| filter.contains(&format!("jti-{i}")))
+            .count();
+        assert!(false_positives < 50, "{false_positives} false positives");
+    }
+
+    #[test]
+    fn test_round_trips_through_bytes() {
+        let mut filter = RevocationFilter::with_capacity(10, 1e-6);
+        filter.insert("revoked");
+
+        let decoded =
+            RevocationFilter::from_parts(&filter.to_bytes(), filter.hashes(), filter.len())
+                .unwrap();
+        assert_eq!(decoded, filter);
+        assert!(decoded.contains("revoked"));
+        assert!(!decoded.contains("clean"));
+
+        assert!(RevocationFilter::from_parts(&[], 3, 0).is_err());
+        assert!(RevocationFilter::from_parts(&[0; 7], 3, 0).is_err());
+        assert!(RevocationFilter::from_parts(&[0; 8], 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_clear_empties_filter() {
+        let mut filter = RevocationFilter::with_capacity(10, 1e-3);
+        filter.insert("revoked");
+        filter.clear();
+        assert!(filter.is_empty());
+        assert!(!filter.contains("revoked"));
+    }
+}
@@ -0,0 +1,152 @@
+//! OAuth `scope` claim formatting.
+//!
+//! Access tokens carry their scopes in the `scope` claim as one
+//! space-delimited string (RFC 8693 §4.2, RFC 9068 §2.2.3). Services keep
+//! scopes as a list and convert at the token boundary with the helpers here.
+//! When reading tokens, the legacy `scopes` JSON array is accepted as well.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Name of the standard scope claim
+pub const SCOPE_CLAIM: &str = "scope";
+
+/// Name of the legacy array-valued scope claim, accepted when reading tokens
+pub const LEGACY_SCOPES_CLAIM: &str = "scopes";
+
+/// Formats scopes as a `scope` claim value.
+pub fn format_scope<S: AsRef<str>>(scopes: &[S]) -> String {
+    ScopeClaim(scopes).to_string()
+}
+
+/// Splits a `scope` claim value into scopes, ignoring repeated whitespace.
+pub fn parse_scope(scope: &str) -> Vec<String> {
+    scope.split_whitespace().map(str::to_string).collect()
+}
+
+/// Borrowed scopes that display and serialize as a `scope` claim value.
+#[derive(Debug, Clone, Copy)]
+pub struct ScopeClaim<'a, S>(pub &'a [S]);
+
+impl<S: AsRef<str>> fmt::Display for ScopeClaim<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, scope) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(scope.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: AsRef<str>> Serialize for ScopeClaim<'_, S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Serializes optional scopes as a `scope` string, for
+/// `#[serde(serialize_with = "rust_common::scope::serialize")]`.
+///
+/// # Errors
+///
+/// Returns the serializer's error.
+pub fn serialize<S: Serializer>(
+    scopes: &Option<Vec<String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match scopes {
+        Some(scopes) => ScopeClaim(scopes).serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserializes a space-delimited `scope` string or a legacy array of
+/// scopes, for `#[serde(deserialize_with = "rust_common::scope::deserialize")]`.
+///
+/// `null` and empty values read as no scopes.
+///
+/// # Errors
+///
+/// Returns an error for values that are neither a string nor a string array.
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ScopeValue {
+        Delimited(String),
+        List(Vec<String>),
+    }
+
+    let scopes = match Option::<ScopeValue>::deserialize(deserializer)? {
+        Some(ScopeValue::Delimited(scope)) => parse_scope(&scope),
+        Some(ScopeValue::List(scopes)) => scopes,
+        None => Vec::new(),
+    };
+    Ok((!scopes.is_empty()).then_some(scopes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Claims shaped like the services' token claims, with flattened extras
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Token {
+        #[serde(
+            rename = "scope",
+            alias = "scopes",
+            default,
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize",
+            deserialize_with = "deserialize"
+        )]
+        scopes: Option<Vec<String>>,
+        #[serde(flatten)]
+        custom: HashMap<String, serde_json::Value>,
+    }
+
+    #[test]
+    fn test_format_and_parse_round_trip() {
+        let scopes = ["openid", "orders:read"];
+        assert_eq!(format_scope(&scopes), "openid orders:read");
+        assert_eq!(parse_scope("openid orders:read"), scopes);
+        assert_eq!(parse_scope("  openid \t orders:read "), scopes);
+        assert_eq!(format_scope::<&str>(&[]), "");
+        assert!(parse_scope("").is_empty());
+    }
+
+    #[test]
+    fn test_serializes_space_delimited_scope_claim() {
+        let token = Token {
+            scopes: Some(vec!["openid".to_string(), "profile".to_string()]),
+            ..Token::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&token).unwrap(),
+            r#"{"scope":"openid profile"}"#
+        );
+        assert_eq!(serde_json::to_string(&Token::default()).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_deserializes_both_claim_forms() {
+        let expected = Token {
+            scopes: Some(vec!["openid".to_string(), "profile".to_string()]),
+            ..Token::default()
+        };
+        let standard: Token = serde_json::from_str(r#"{"scope":"openid profile"}"#).unwrap();
+        let legacy: Token = serde_json::from_str(r#"{"scopes":["openid","profile"]}"#).unwrap();
+        assert_eq!(standard, expected);
+        assert_eq!(legacy, expected);
+
+        for empty in [r#"{}"#, r#"{"scope":""}"#, r#"{"scope":null}"#, r#"{"scopes":[]}"#] {
+            assert_eq!(serde_json::from_str::<Token>(empty).unwrap(), Token::default());
+        }
+        assert!(serde_json::from_str::<Token>(r#"{"scope":42}"#).is_err());
+    }
+}
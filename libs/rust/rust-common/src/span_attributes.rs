@@ -0,0 +1,236 @@
+//! Standard span attributes of the auth services.
+//!
+//! [`AuthSpanLayer`] runs every RPC in one server span declaring the
+//! attributes below, initially empty. Handlers fill them in with
+//! [`record_auth!`](crate::record_auth), which only accepts these attributes,
+//! so dashboards and trace queries filter on the same names in every service.
+//! Error statuses returned by a handler are recorded by the layer itself.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tonic::codegen::http;
+use tonic::{Code, Status};
+use tower::{Layer, Service};
+use tracing::{Instrument, Span};
+
+/// Client calling the service, e.g. an API key or OAuth client ID.
+pub const CLIENT_ID: &str = "auth.client_id";
+/// Tenant the request targets.
+pub const TENANT: &str = "auth.tenant";
+/// Outcome of the request, [`ALLOW`] or [`DENY`].
+pub const DECISION: &str = "auth.decision";
+/// Error code of a denied request, e.g. `TOKEN_EXPIRED`.
+pub const ERROR_CODE: &str = "auth.error_code";
+/// SPIFFE ID of the calling workload.
+pub const PEER_SPIFFE_ID: &str = "peer.spiffe_id";
+
+/// Every standard attribute.
+pub const ALL: [&str; 5] = [CLIENT_ID, TENANT, DECISION, ERROR_CODE, PEER_SPIFFE_ID];
+
+/// [`DECISION`] of a request that was granted.
+pub const ALLOW: &str = "allow";
+/// [`DECISION`] of a request that was refused or failed.
+pub const DENY: &str = "deny";
+
+/// Open an info span declaring every standard attribute, initially empty.
+///
+/// Extra fields follow the name as in [`tracing::info_span!`].
+#[macro_export]
+macro_rules! auth_span {
+    ($name:expr $(, $($fields:tt)+)?) => {
+        ::tracing::info_span!(
+            $name,
+            auth.client_id = ::tracing::field::Empty,
+            auth.tenant = ::tracing::field::Empty,
+            auth.decision = ::tracing::field::Empty,
+            auth.error_code = ::tracing::field::Empty,
+            peer.spiffe_id = ::tracing::field::Empty,
+            $($($fields)+)?
+        )
+    };
+}
+
+/// Record standard attributes on a span opened by [`auth_span!`] or an
+/// [`AuthSpan`].
+///
+/// Keys are `client_id`, `tenant`, `decision`, `error_code` and
+/// `peer_spiffe_id`; any other key fails to compile.
+///
+/// ```
+/// use rust_common::span_attributes::ALLOW;
+/// use rust_common::{auth_span, record_auth};
+///
+/// let span = auth_span!("ValidateToken");
+/// record_auth!(span, client_id = "api-key:ci", decision = ALLOW);
+/// ```
+#[macro_export]
+macro_rules! record_auth {
+    (@name client_id) => { $crate::span_attributes::CLIENT_ID };
+    (@name tenant) => { $crate::span_attributes::TENANT };
+    (@name decision) => { $crate::span_attributes::DECISION };
+    (@name error_code) => { $crate::span_attributes::ERROR_CODE };
+    (@name peer_spiffe_id) => { $crate::span_attributes::PEER_SPIFFE_ID };
+    ($span:expr, $($key:ident = $value:expr),+ $(,)?) => {{
+        let span = &$span;
+        $( span.record($crate::record_auth!(@name $key), $value); )+
+    }};
+}
+
+/// Server span of the current RPC, found in its request extensions.
+#[derive(Debug, Clone)]
+pub struct AuthSpan(Span);
+
+impl AuthSpan {
+    /// The span of `request`, or a disabled span outside [`AuthSpanLayer`].
+    #[must_use]
+    pub fn of<T>(request: &tonic::Request<T>) -> Self {
+        request
+            .extensions()
+            .get::<Self>()
+            .cloned()
+            .unwrap_or_else(|| Self(Span::none()))
+    }
+
+    /// Record `value` under the standard attribute `field`.
+    pub fn record(&self, field: &str, value: &str) {
+        self.0.record(field, value);
+    }
+
+    /// The underlying span.
+    #[must_use]
+    pub const fn span(&self) -> &Span {
+        &self.0
+    }
+}
+
+/// Canonical name of a gRPC status code, e.g. `PERMISSION_DENIED`.
+#[must_use]
+pub const fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "OK",
+        Code::Cancelled => "CANCELLED",
+        Code::Unknown => "UNKNOWN",
+        Code::InvalidArgument => "INVALID_ARGUMENT",
+        Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+        Code::NotFound => "NOT_FOUND",
+        Code::AlreadyExists => "ALREADY_EXISTS",
+        Code::PermissionDenied => "PERMISSION_DENIED",
+        Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+        Code::FailedPrecondition => "FAILED_PRECONDITION",
+        Code::Aborted => "ABORTED",
+        Code::OutOfRange => "OUT_OF_RANGE",
+        Code::Unimplemented => "UNIMPLEMENTED",
+        Code::Internal => "INTERNAL",
+        Code::Unavailable => "UNAVAILABLE",
+        Code::DataLoss => "DATA_LOSS",
+        Code::Unauthenticated => "UNAUTHENTICATED",
+    }
+}
+
+/// Server layer running each RPC in an [`AuthSpan`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuthSpanLayer;
+
+impl<S> Layer<S> for AuthSpanLayer {
+    type Service = AuthSpanService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthSpanService { inner }
+    }
+}
+
+/// Service wrapper created by [`AuthSpanLayer`].
+#[derive(Debug, Clone)]
+pub struct AuthSpanService<S> {
+    inner: S,
+}
+
+impl<S, B, R> Service<http::Request<B>> for AuthSpanService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<R>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        let span = auth_span!(
+            "rpc",
+            otel.kind = "server",
+            otel.name = %req.uri().path(),
+            rpc.method = %req.uri().path()
+        );
+        req.extensions_mut().insert(AuthSpan(span.clone()));
+        let response = span.in_scope(|| self.inner.call(req));
+
+        let record = span.clone();
+        Box::pin(
+            async move {
+                let response = response.await?;
+                // Errors returned by handlers travel in the headers
+                if let Some(status) = Status::from_header_map(response.headers()) {
+                    if status.code() != Code::Ok {
+                        record.record(DECISION, DENY);
+                        record.record(ERROR_CODE, code_name(status.code()));
+                    }
+                }
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_auth_span_declares_standard_attributes() {
+        tracing::subscriber::with_default(tracing_subscriber::registry(), || {
+            let span = auth_span!("test", rpc.method = "Check");
+            for name in ALL {
+                assert!(span.field(name).is_some(), "{name} is not declared");
+            }
+            assert!(span.field("rpc.method").is_some());
+            record_auth!(span, client_id = "api-key:ci", decision = ALLOW);
+        });
+    }
+
+    #[test]
+    fn test_span_outside_layer_is_disabled() {
+        let span = AuthSpan::of(&tonic::Request::new(()));
+        assert!(span.span().is_none());
+        record_auth!(span, tenant = "acme");
+    }
+
+    #[test]
+    fn test_code_names() {
+        assert_eq!(code_name(Code::PermissionDenied), "PERMISSION_DENIED");
+        assert_eq!(code_name(Code::Unauthenticated), "UNAUTHENTICATED");
+    }
+
+    #[tokio::test]
+    async fn test_layer_exposes_span_to_handlers() {
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let handler_seen = seen.clone();
+        let service = AuthSpanLayer.layer(tower::service_fn(move |req: http::Request<()>| {
+            *handler_seen.lock().unwrap() = req.extensions().get::<AuthSpan>().cloned();
+            async { Ok::<_, std::convert::Infallible>(Status::permission_denied("denied").into_http()) }
+        }));
+
+        let response = service.oneshot(http::Request::new(())).await.unwrap();
+        assert_eq!(response.headers()["grpc-status"], "7");
+        let span = seen.lock().unwrap().take().unwrap();
+        assert!(span.span().field(DECISION).is_some());
+    }
+}
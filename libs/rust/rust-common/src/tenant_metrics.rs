@@ -0,0 +1,468 @@
+//! Per-tenant rate, errors and duration with bounded cardinality.
+//!
+//! A tenant label gives one series per tenant, which does not scale to the
+//! long tail. [`TenantBuckets`] labels only the busiest tenants by name and
+//! aggregates the rest into `other`: it counts requests per tenant over a
+//! window, and each [`recompute`](TenantBuckets::recompute) makes the top N
+//! of that window the labelled tenants of the next one.
+//!
+//! [`TenantMetrics`] records, per tenant bucket:
+//!
+//! - `<namespace>_tenant_requests_total` by `outcome` (`ok` or `error`)
+//! - `<namespace>_tenant_request_duration_seconds`
+//!
+//! Handlers that know the tenant call [`TenantMetrics::record`];
+//! [`TenantMetricsLayer`] records every RPC of a server by the tenant in its
+//! `x-tenant-id` metadata instead. Series of tenants that drop out of the top
+//! N are removed on recompute.
+//! Each bucket also keeps its latest duration exemplar carrying the actual
+//! tenant ID and the current trace; the OTLP metrics exporter attaches them
+//! to the histogram, so a spike in `other` can be traced to its tenant.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use opentelemetry::trace::TraceContextExt;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use tonic::codegen::http;
+use tonic::{Code, Status};
+use tower::{Layer, Service};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Metadata naming the tenant of a request, read by [`TenantMetricsLayer`]
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Label of the tenants outside the top N
+pub const OTHER_TENANT: &str = "other";
+
+/// Label of requests without a tenant
+pub const NO_TENANT: &str = "none";
+
+/// Distinct tenants counted per window; later ones only count as `other`
+const DEFAULT_MAX_TRACKED: usize = 10_000;
+
+const BUCKETS: [f64; 11] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Latest exemplar by histogram name and tenant bucket
+static EXEMPLARS: LazyLock<Mutex<HashMap<(String, String), Exemplar>>> =
+    LazyLock::new(Mutex::default);
+
+/// One observation kept as an exemplar of its tenant bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exemplar {
+    /// Actual tenant ID of the observation
+    pub tenant: String,
+    /// Observed value
+    pub value: f64,
+    /// Time of the observation
+    pub time_unix_nano: u64,
+    /// Trace the observation belongs to, if it was recorded within one
+    pub trace_id: Option<[u8; 16]>,
+    /// Span the observation was recorded in
+    pub span_id: Option<[u8; 8]>,
+}
+
+/// Latest exemplar recorded for `bucket` of the histogram `name`.
+#[must_use]
+pub fn exemplar(name: &str, bucket: &str) -> Option<Exemplar> {
+    lock(&EXEMPLARS)
+        .get(&(name.to_string(), bucket.to_string()))
+        .cloned()
+}
+
+/// Chooses which tenants are labelled by name.
+#[derive(Debug)]
+pub struct TenantBuckets {
+    top_n: usize,
+    max_tracked: usize,
+    window: Mutex<HashMap<String, u64>>,
+    labelled: RwLock<HashSet<String>>,
+}
+
+impl TenantBuckets {
+    /// Labels the `top_n` busiest tenants of each window; until the first
+    /// recompute every tenant is `other`.
+    #[must_use]
+    pub fn new(top_n: usize) -> Self {
+        Self {
+            top_n,
+            max_tracked: DEFAULT_MAX_TRACKED,
+            window: Mutex::default(),
+            labelled: RwLock::default(),
+        }
+    }
+
+    /// Bound the distinct tenants counted per window.
+    #[must_use]
+    pub const fn with_max_tracked(mut self, max_tracked: usize) -> Self {
+        self.max_tracked = max_tracked;
+        self
+    }
+
+    /// Count a request of `tenant` and return its label.
+    pub fn bucket<'a>(&self, tenant: &'a str) -> &'a str {
+        if tenant.is_empty() {
+            return NO_TENANT;
+        }
+        {
+            let mut window = lock(&self.window);
+            if let Some(count) = window.get_mut(tenant) {
+                *count += 1;
+            } else if window.len() < self.max_tracked {
+                window.insert(tenant.to_string(), 1);
+            }
+        }
+        if self.is_labelled(tenant) {
+            tenant
+        } else {
+            OTHER_TENANT
+        }
+    }
+
+    /// Whether `tenant` is currently labelled by name.
+    #[must_use]
+    pub fn is_labelled(&self, tenant: &str) -> bool {
+        self.labelled
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(tenant)
+    }
+
+    /// Tenants currently labelled by name.
+    #[must_use]
+    pub fn labelled(&self) -> Vec<String> {
+        let mut labelled: Vec<String> = self
+            .labelled
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect();
+        labelled.sort_unstable();
+        labelled
+    }
+
+    /// Label the busiest tenants of the window that just ended and start a
+    /// new one.
+    ///
+    /// Returns the tenants that are no longer labelled.
+    pub fn recompute(&self) -> Vec<String> {
+        let window = std::mem::take(&mut *lock(&self.window));
+        let mut counts: Vec<(String, u64)> = window.into_iter().collect();
+        // Busiest first; ties broken by name so the choice is stable
+        counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let top: HashSet<String> = counts
+            .into_iter()
+            .take(self.top_n)
+            .map(|(tenant, _)| tenant)
+            .collect();
+
+        let mut labelled = self.labelled.write().unwrap_or_else(PoisonError::into_inner);
+        let demoted = labelled.difference(&top).cloned().collect();
+        *labelled = top;
+        demoted
+    }
+}
+
+/// Rate, errors and duration per tenant bucket.
+#[derive(Debug, Clone)]
+pub struct TenantMetrics {
+    buckets: Arc<TenantBuckets>,
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    duration_name: String,
+}
+
+impl TenantMetrics {
+    /// Create the metrics under `namespace` and register them in `registry`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metrics are already registered.
+    pub fn new(
+        namespace: &str,
+        registry: &Registry,
+        buckets: Arc<TenantBuckets>,
+    ) -> Result<Self, prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new("tenant_requests_total", "Total requests per tenant bucket and outcome")
+                .namespace(namespace),
+            &["tenant", "outcome"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "tenant_request_duration_seconds",
+                "Request latency per tenant bucket in seconds",
+            )
+            .namespace(namespace)
+            .buckets(BUCKETS.to_vec()),
+            &["tenant"],
+        )?;
+        registry.register(Box::new(duration.clone()))?;
+
+        Ok(Self {
+            buckets,
+            requests,
+            duration,
+            duration_name: format!("{namespace}_tenant_request_duration_seconds"),
+        })
+    }
+
+    /// Record one request of `tenant`.
+    pub fn record(&self, tenant: &str, ok: bool, duration_secs: f64) {
+        let bucket = self.buckets.bucket(tenant);
+        let outcome = if ok { "ok" } else { "error" };
+        self.requests.with_label_values(&[bucket, outcome]).inc();
+        self.duration.with_label_values(&[bucket]).observe(duration_secs);
+
+        let (trace_id, span_id) = current_trace();
+        let exemplar = Exemplar {
+            tenant: tenant.to_string(),
+            value: duration_secs,
+            time_unix_nano: unix_nanos(),
+            trace_id,
+            span_id,
+        };
+        lock(&EXEMPLARS).insert((self.duration_name.clone(), bucket.to_string()), exemplar);
+    }
+
+    /// Requests recorded for `bucket` with `outcome`.
+    #[must_use]
+    pub fn requests(&self, bucket: &str, outcome: &str) -> u64 {
+        self.requests.with_label_values(&[bucket, outcome]).get()
+    }
+
+    /// Recompute the labelled tenants and remove the series of those that
+    /// dropped out.
+    pub fn recompute(&self) {
+        let demoted = self.buckets.recompute();
+        for tenant in &demoted {
+            for outcome in ["ok", "error"] {
+                let _ = self.requests.remove_label_values(&[tenant, outcome]);
+            }
+            let _ = self.duration.remove_label_values(&[tenant]);
+        }
+        if !demoted.is_empty() {
+            lock(&EXEMPLARS).retain(|(name, bucket), _| {
+                name != &self.duration_name || !demoted.contains(bucket)
+            });
+            tracing::debug!(
+                labelled = self.buckets.labelled().len(),
+                demoted = demoted.len(),
+                "Tenant metric buckets recomputed"
+            );
+        }
+    }
+
+    /// Recompute the labelled tenants every `interval`.
+    pub async fn run(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            self.recompute();
+        }
+    }
+}
+
+/// Server layer recording [`TenantMetrics`] for every RPC.
+#[derive(Debug, Clone)]
+pub struct TenantMetricsLayer {
+    metrics: TenantMetrics,
+}
+
+impl TenantMetricsLayer {
+    /// Create a layer recording into `metrics`.
+    #[must_use]
+    pub const fn new(metrics: TenantMetrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for TenantMetricsLayer {
+    type Service = TenantMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TenantMetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Service wrapper created by [`TenantMetricsLayer`].
+#[derive(Debug, Clone)]
+pub struct TenantMetricsService<S> {
+    inner: S,
+    metrics: TenantMetrics,
+}
+
+impl<S, B, R> Service<http::Request<B>> for TenantMetricsService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<R>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let tenant = req
+            .headers()
+            .get(TENANT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+        let metrics = self.metrics.clone();
+        let started = Instant::now();
+        let response = self.inner.call(req);
+
+        Box::pin(async move {
+            let response = response.await;
+            let ok = response.as_ref().is_ok_and(|response| {
+                Status::from_header_map(response.headers()).is_none_or(|s| s.code() == Code::Ok)
+            });
+            metrics.record(&tenant, ok, started.elapsed().as_secs_f64());
+            response
+        })
+    }
+}
+
+/// Trace and span IDs of the current tracing span, when it is part of a trace.
+fn current_trace() -> (Option<[u8; 16]>, Option<[u8; 8]>) {
+    let cx = tracing::Span::current().context();
+    let span = cx.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() {
+        return (None, None);
+    }
+    (
+        Some(span_context.trace_id().to_bytes()),
+        Some(span_context.span_id().to_bytes()),
+    )
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn traffic(buckets: &TenantBuckets, tenant: &str, requests: usize) {
+        for _ in 0..requests {
+            buckets.bucket(tenant);
+        }
+    }
+
+    #[test]
+    fn test_top_tenants_are_labelled() {
+        let buckets = TenantBuckets::new(2);
+        assert_eq!(buckets.bucket("acme"), OTHER_TENANT);
+        assert_eq!(buckets.bucket(""), NO_TENANT);
+
+        traffic(&buckets, "acme", 5);
+        traffic(&buckets, "globex", 3);
+        traffic(&buckets, "initech", 1);
+        assert!(buckets.recompute().is_empty());
+        assert_eq!(buckets.labelled(), ["acme", "globex"]);
+        assert_eq!(buckets.bucket("acme"), "acme");
+        assert_eq!(buckets.bucket("initech"), OTHER_TENANT);
+
+        // The next window only saw initech and acme
+        traffic(&buckets, "initech", 4);
+        assert_eq!(buckets.recompute(), ["globex"]);
+        assert_eq!(buckets.labelled(), ["acme", "initech"]);
+    }
+
+    #[test]
+    fn test_tracked_tenants_are_bounded() {
+        let buckets = TenantBuckets::new(5).with_max_tracked(1);
+        traffic(&buckets, "acme", 1);
+        traffic(&buckets, "globex", 10);
+        buckets.recompute();
+        assert_eq!(buckets.labelled(), ["acme"]);
+    }
+
+    #[tokio::test]
+    async fn test_layer_records_metadata_tenant() {
+        use tower::ServiceExt;
+
+        let buckets = Arc::new(TenantBuckets::new(1));
+        let metrics = TenantMetrics::new("layer", &Registry::new(), buckets.clone()).unwrap();
+        let service = TenantMetricsLayer::new(metrics.clone()).layer(tower::service_fn(
+            |req: http::Request<()>| async move {
+                let response = if req.uri().path().ends_with("Deny") {
+                    Status::permission_denied("denied").into_http()
+                } else {
+                    http::Response::new(tonic::body::empty_body())
+                };
+                Ok::<_, std::convert::Infallible>(response)
+            },
+        ));
+        let request = |path: &str, tenant: &str| {
+            http::Request::builder()
+                .uri(path)
+                .header(TENANT_HEADER, tenant)
+                .body(())
+                .unwrap()
+        };
+
+        service.clone().oneshot(request("/pkg.Svc/Allow", "acme")).await.unwrap();
+        metrics.recompute();
+        service.clone().oneshot(request("/pkg.Svc/Deny", "acme")).await.unwrap();
+        service.oneshot(request("/pkg.Svc/Allow", "globex")).await.unwrap();
+
+        assert_eq!(metrics.requests(OTHER_TENANT, "ok"), 2);
+        assert_eq!(metrics.requests("acme", "error"), 1);
+    }
+
+    #[test]
+    fn test_metrics_drop_demoted_tenants() {
+        let registry = Registry::new();
+        let buckets = Arc::new(TenantBuckets::new(1));
+        let metrics = TenantMetrics::new("test", &registry, buckets.clone()).unwrap();
+
+        metrics.record("acme", true, 0.01);
+        metrics.recompute();
+        metrics.record("acme", false, 0.02);
+        metrics.record("globex", true, 0.03);
+        assert_eq!(metrics.requests("acme", "error"), 1);
+        assert_eq!(metrics.requests(OTHER_TENANT, "ok"), 2);
+
+        let other = exemplar("test_tenant_request_duration_seconds", OTHER_TENANT).unwrap();
+        assert_eq!((other.tenant.as_str(), other.value), ("globex", 0.03));
+        assert!(other.trace_id.is_none());
+
+        // globex took over the window
+        metrics.record("globex", true, 0.01);
+        metrics.recompute();
+        let families = registry.gather();
+        let tenants: HashSet<&str> = families
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .flat_map(|metric| metric.get_label())
+            .filter(|label| label.get_name() == "tenant")
+            .map(|label| label.get_value())
+            .collect();
+        assert!(!tenants.contains("acme"));
+        assert!(exemplar("test_tenant_request_duration_seconds", "acme").is_none());
+    }
+}
@@ -0,0 +1,155 @@
+//! W3C trace context propagation for gRPC servers.
+//!
+//! Callers send their trace as `traceparent`/`tracestate` metadata.
+//! [`TraceContextLayer`] extracts it from every incoming RPC and makes it the
+//! current OpenTelemetry context while the RPC is handled, so spans the
+//! handler opens (e.g. through `#[instrument]` with a `tracing-opentelemetry`
+//! subscriber) continue the caller's trace instead of starting a new one.
+//! The extracted context is also left in the request extensions.
+
+use std::task::{Context as TaskContext, Poll};
+
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::{FutureExt, WithContext};
+use opentelemetry::Context;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tonic::codegen::http;
+use tonic::metadata::{KeyRef, MetadataMap};
+use tower::{Layer, Service};
+
+/// Reads propagation fields from gRPC metadata.
+pub struct MetadataExtractor<'a>(pub &'a MetadataMap);
+
+impl Extractor for MetadataExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .keys()
+            .filter_map(|key| match key {
+                KeyRef::Ascii(key) => Some(key.as_str()),
+                KeyRef::Binary(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Reads propagation fields from the HTTP/2 headers carrying gRPC metadata.
+struct HeaderExtractor<'a>(&'a http::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(http::HeaderName::as_str).collect()
+    }
+}
+
+/// Extract the caller's trace context from `traceparent`/`tracestate`.
+///
+/// Returns an empty context when the metadata carries no valid trace.
+#[must_use]
+pub fn extract_context(metadata: &MetadataMap) -> Context {
+    TraceContextPropagator::new().extract(&MetadataExtractor(metadata))
+}
+
+/// Server layer continuing the caller's trace for each RPC.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceContextLayer;
+
+impl<S> Layer<S> for TraceContextLayer {
+    type Service = TraceContextService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceContextService { inner }
+    }
+}
+
+/// Service wrapper created by [`TraceContextLayer`].
+#[derive(Debug, Clone)]
+pub struct TraceContextService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for TraceContextService<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = WithContext<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        let otel_cx = TraceContextPropagator::new().extract(&HeaderExtractor(req.headers()));
+        req.extensions_mut().insert(otel_cx.clone());
+        let response = {
+            let _guard = otel_cx.clone().attach();
+            self.inner.call(req)
+        };
+        response.with_context(otel_cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+    use tower::ServiceExt;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    fn trace_id(cx: &Context) -> String {
+        cx.span().span_context().trace_id().to_string()
+    }
+
+    #[test]
+    fn test_extract_context_from_metadata() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("traceparent", TRACEPARENT.parse().unwrap());
+        metadata.insert("tracestate", "vendor=value".parse().unwrap());
+
+        let cx = extract_context(&metadata);
+        let span = cx.span();
+        let span_context = span.span_context();
+        assert!(span_context.is_remote());
+        assert_eq!(trace_id(&cx), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(span_context.span_id().to_string(), "00f067aa0ba902b7");
+        assert_eq!(span_context.trace_state().get("vendor"), Some("value"));
+    }
+
+    #[test]
+    fn test_missing_or_invalid_traceparent_yields_empty_context() {
+        assert!(!extract_context(&MetadataMap::new()).has_active_span());
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert("traceparent", "00-not-a-trace-01".parse().unwrap());
+        assert!(!extract_context(&metadata).has_active_span());
+    }
+
+    #[tokio::test]
+    async fn test_layer_makes_caller_trace_current() {
+        let service = TraceContextLayer.layer(tower::service_fn(
+            |req: http::Request<()>| async move {
+                let current = trace_id(&Context::current());
+                let stored = req.extensions().get::<Context>().map(trace_id);
+                Ok::<_, std::convert::Infallible>((current, stored))
+            },
+        ));
+
+        let req = http::Request::builder()
+            .header("traceparent", TRACEPARENT)
+            .body(())
+            .unwrap();
+        let (current, stored) = service.oneshot(req).await.unwrap();
+        assert_eq!(current, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(stored.as_deref(), Some("4bf92f3577b34da6a3ce929d0e0e4736"));
+    }
+}
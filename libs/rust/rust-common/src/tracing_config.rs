@@ -1,26 +1,75 @@
 //! OpenTelemetry tracing integration.
 //!
-//! This module provides configuration for distributed tracing using OpenTelemetry.
+//! [`init_telemetry`] installs the process-wide subscriber every service
+//! starts with: log lines (JSON in production) filtered by `RUST_LOG`, spans
+//! and Prometheus metrics exported over OTLP when an endpoint is configured,
+//! W3C trace context propagation, and a panic hook that records panics as
+//! error events. The filter can be changed at runtime through the returned
+//! [`Telemetry`].
+//!
+//! Spans and metrics share one resource: `service.name`, `service.version`,
+//! the workload's SPIFFE ID as `service.spiffe_id` when known, and any extra
+//! attributes, e.g. from `OTLP_RESOURCE_ATTRIBUTES`.
+
+use std::panic;
+use std::time::Duration;
+
+use opentelemetry::KeyValue;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{WithExportConfig, WithTonicConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, TracerProvider};
+use opentelemetry_sdk::{Resource, runtime};
+use tokio::task::JoinHandle;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, reload, util::SubscriberInitExt};
 
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use crate::error::PlatformError;
+use crate::log_level::LogLevelControl;
+use crate::otlp_metrics::OtlpMetricsExporter;
+
+/// Resource attribute carrying the workload's SPIFFE ID
+pub const SERVICE_SPIFFE_ID: &str = "service.spiffe_id";
 
 /// Tracing configuration.
 #[derive(Debug, Clone)]
 pub struct TracingConfig {
     /// Service name for tracing
     pub service_name: String,
+    /// Service version reported as `service.version`
+    pub service_version: String,
+    /// SPIFFE ID of the workload, reported as `service.spiffe_id`
+    pub spiffe_id: Option<String>,
+    /// Extra resource attributes of exported spans and metrics
+    pub resource_attributes: Vec<(String, String)>,
     /// Log level filter
     pub log_level: String,
     /// Whether to output JSON format
     pub json_output: bool,
+    /// OTLP gRPC collector spans and metrics are exported to; `None`
+    /// disables export
+    pub otlp_endpoint: Option<String>,
+    /// gRPC metadata sent with every export, e.g. collector credentials
+    pub otlp_headers: Vec<(String, String)>,
+    /// Interval between metric exports
+    pub metrics_interval: Duration,
+    /// Share of traces sampled (0.0 to 1.0)
+    pub sampling_ratio: f64,
 }
 
 impl Default for TracingConfig {
     fn default() -> Self {
         Self {
             service_name: "rust-service".to_string(),
+            service_version: env!("CARGO_PKG_VERSION").to_string(),
+            spiffe_id: None,
+            resource_attributes: Vec::new(),
             log_level: "info".to_string(),
             json_output: false,
+            otlp_endpoint: None,
+            otlp_headers: Vec::new(),
+            metrics_interval: Duration::from_secs(60),
+            sampling_ratio: 1.0,
         }
     }
 }
@@ -33,6 +82,30 @@ impl TracingConfig {
         self
     }
 
+    /// Report `version` as `service.version`.
+    #[must_use]
+    pub fn with_service_version(mut self, version: impl Into<String>) -> Self {
+        self.service_version = version.into();
+        self
+    }
+
+    /// Report the workload's SPIFFE ID as `service.spiffe_id`.
+    #[must_use]
+    pub fn with_spiffe_id(mut self, spiffe_id: impl Into<String>) -> Self {
+        self.spiffe_id = Some(spiffe_id.into());
+        self
+    }
+
+    /// Add resource attributes to exported spans and metrics.
+    #[must_use]
+    pub fn with_resource_attributes(
+        mut self,
+        attributes: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        self.resource_attributes.extend(attributes);
+        self
+    }
+
     /// Create config with custom log level.
     #[must_use]
     pub fn with_log_level(mut self, level: impl Into<String>) -> Self {
@@ -46,27 +119,242 @@ impl TracingConfig {
         self.json_output = true;
         self
     }
+
+    /// Export spans to the OTLP collector at `endpoint`.
+    #[must_use]
+    pub fn with_otlp_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.otlp_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Send `headers` as gRPC metadata with every OTLP export.
+    #[must_use]
+    pub fn with_otlp_headers(mut self, headers: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.otlp_headers.extend(headers);
+        self
+    }
+
+    /// Export metrics every `interval`.
+    #[must_use]
+    pub const fn with_metrics_interval(mut self, interval: Duration) -> Self {
+        self.metrics_interval = interval;
+        self
+    }
+
+    /// Resource attributes of exported spans and metrics. Extra attributes
+    /// never override the service name, version or SPIFFE ID.
+    #[must_use]
+    pub fn resource(&self) -> Vec<(String, String)> {
+        let mut resource = vec![
+            ("service.name".to_string(), self.service_name.clone()),
+            ("service.version".to_string(), self.service_version.clone()),
+        ];
+        if let Some(spiffe_id) = &self.spiffe_id {
+            resource.push((SERVICE_SPIFFE_ID.to_string(), spiffe_id.clone()));
+        }
+        for (key, value) in &self.resource_attributes {
+            if !resource.iter().any(|(existing, _)| existing == key) {
+                resource.push((key.clone(), value.clone()));
+            }
+        }
+        resource
+    }
+
+    /// Sample `ratio` of traces.
+    #[must_use]
+    pub const fn with_sampling_ratio(mut self, ratio: f64) -> Self {
+        self.sampling_ratio = ratio;
+        self
+    }
 }
 
-/// Initialize tracing with the given configuration.
+/// Telemetry installed by [`init_telemetry`].
+#[derive(Debug)]
+pub struct Telemetry {
+    log_level: LogLevelControl,
+    tracer_provider: Option<TracerProvider>,
+    metrics_export: Option<JoinHandle<()>>,
+}
+
+impl Telemetry {
+    /// Changes the log filter at runtime.
+    #[must_use]
+    pub const fn log_level(&self) -> &LogLevelControl {
+        &self.log_level
+    }
+
+    /// Flush spans not exported yet and stop exporting. Metrics recorded
+    /// since the last export are not pushed.
+    pub fn shutdown(self) {
+        if let Some(metrics_export) = self.metrics_export {
+            metrics_export.abort();
+        }
+        if let Some(provider) = self.tracer_provider {
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!(error = %e, "Failed to flush spans on shutdown");
+            }
+        }
+    }
+}
+
+/// Install the global subscriber, OTLP exporters and panic hook.
 ///
-/// This sets up the global tracing subscriber with the specified configuration.
-/// Should be called once at application startup.
-pub fn init_tracing(config: &TracingConfig) {
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new(&config.log_level));
-
-    if config.json_output {
-        tracing_subscriber::registry()
-            .with(filter)
-            .with(tracing_subscriber::fmt::layer().json())
-            .init();
+/// `RUST_LOG`, when valid, replaces the configured log level as the default
+/// directives. With an OTLP endpoint, the default Prometheus registry is
+/// pushed every `metrics_interval`. Must be called once, within a Tokio
+/// runtime when spans and metrics are exported.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if an OTLP exporter cannot be built or a header
+/// is not valid gRPC metadata, and `Unavailable` if a global subscriber is
+/// already installed.
+pub fn init_telemetry(config: &TracingConfig) -> Result<Telemetry, PlatformError> {
+    let (directives, filter) = std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .and_then(|directives| {
+            let filter = EnvFilter::try_new(&directives).ok()?;
+            Some((directives, filter))
+        })
+        .unwrap_or_else(|| (config.log_level.clone(), EnvFilter::new(&config.log_level)));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let metadata = otlp_metadata(&config.otlp_headers)?;
+    let resource = config.resource();
+    let tracer_provider = config
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| tracer_provider(config, endpoint, metadata.clone(), &resource))
+        .transpose()?;
+    let metrics_exporter = config
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| {
+            OtlpMetricsExporter::new(
+                endpoint,
+                metadata,
+                &resource,
+                prometheus::default_registry().clone(),
+            )
+        })
+        .transpose()?;
+    let otel_layer = tracer_provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(config.service_name.clone()))
+    });
+    let (json, plain) = if config.json_output {
+        (Some(tracing_subscriber::fmt::layer().json()), None)
     } else {
-        tracing_subscriber::registry()
-            .with(filter)
-            .with(tracing_subscriber::fmt::layer())
-            .init();
+        (None, Some(tracing_subscriber::fmt::layer()))
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(otel_layer)
+        .with(json)
+        .with(plain)
+        .try_init()
+        .map_err(|e| PlatformError::unavailable(format!("tracing already initialized: {e}")))?;
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    if let Some(provider) = &tracer_provider {
+        opentelemetry::global::set_tracer_provider(provider.clone());
     }
+    install_panic_hook();
+
+    Ok(Telemetry {
+        log_level: LogLevelControl::new(handle, directives),
+        tracer_provider,
+        metrics_export: metrics_exporter
+            .map(|exporter| exporter.spawn(config.metrics_interval)),
+    })
+}
+
+/// Parse a `key=value,key=value` list, the format of `OTLP_HEADERS` and
+/// `OTLP_RESOURCE_ATTRIBUTES`. Blank entries are skipped.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` for an entry without `=` or with an empty key.
+pub fn parse_key_values(list: &str) -> Result<Vec<(String, String)>, PlatformError> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(PlatformError::invalid_input(format!(
+                "{entry:?} is not a key=value pair"
+            ))),
+        })
+        .collect()
+}
+
+/// gRPC metadata carrying the OTLP headers.
+fn otlp_metadata(headers: &[(String, String)]) -> Result<MetadataMap, PlatformError> {
+    let mut metadata = MetadataMap::new();
+    for (key, value) in headers {
+        let name = MetadataKey::from_bytes(key.to_ascii_lowercase().as_bytes())
+            .map_err(|_| PlatformError::invalid_input(format!("OTLP header name {key:?}")))?;
+        let value = MetadataValue::try_from(value.as_str())
+            .map_err(|_| PlatformError::invalid_input(format!("OTLP header {key:?} value")))?;
+        metadata.insert(name, value);
+    }
+    Ok(metadata)
+}
+
+fn tracer_provider(
+    config: &TracingConfig,
+    endpoint: &str,
+    metadata: MetadataMap,
+    resource: &[(String, String)],
+) -> Result<TracerProvider, PlatformError> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .with_metadata(metadata)
+        .build()
+        .map_err(|e| PlatformError::invalid_input(format!("OTLP exporter: {e}")))?;
+    let sampler = if config.sampling_ratio >= 1.0 {
+        Sampler::AlwaysOn
+    } else if config.sampling_ratio <= 0.0 {
+        Sampler::AlwaysOff
+    } else {
+        Sampler::TraceIdRatioBased(config.sampling_ratio)
+    };
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_sampler(Sampler::ParentBased(Box::new(sampler)))
+        .with_resource(Resource::new(
+            resource
+                .iter()
+                .map(|(key, value)| KeyValue::new(key.clone(), value.clone())),
+        ))
+        .build())
+}
+
+/// Record panics as error events before the default hook runs.
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        let location = info
+            .location()
+            .map(|location| format!("{}:{}", location.file(), location.line()))
+            .unwrap_or_default();
+        tracing::error!(
+            panic.message = %message,
+            panic.location = %location,
+            thread = std::thread::current().name().unwrap_or("unnamed"),
+            "Panic"
+        );
+        default_hook(info);
+    }));
 }
 
 #[cfg(test)]
@@ -79,6 +367,7 @@ mod tests {
         assert_eq!(config.service_name, "rust-service");
         assert_eq!(config.log_level, "info");
         assert!(!config.json_output);
+        assert_eq!(config.otlp_endpoint, None);
     }
 
     #[test]
@@ -86,10 +375,55 @@ mod tests {
         let config = TracingConfig::default()
             .with_service_name("my-service")
             .with_log_level("debug")
-            .with_json_output();
+            .with_json_output()
+            .with_otlp_endpoint("http://collector:4317")
+            .with_sampling_ratio(0.25);
 
         assert_eq!(config.service_name, "my-service");
         assert_eq!(config.log_level, "debug");
         assert!(config.json_output);
+        assert_eq!(
+            config.otlp_endpoint.as_deref(),
+            Some("http://collector:4317")
+        );
+        assert!((config.sampling_ratio - 0.25).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_resource_attributes() {
+        let config = TracingConfig::default()
+            .with_service_name("token-service")
+            .with_service_version("1.2.0")
+            .with_spiffe_id("spiffe://example.org/ns/auth/sa/token")
+            .with_resource_attributes(parse_key_values("deployment.environment=prod, service.name=other").unwrap());
+
+        assert_eq!(
+            config.resource(),
+            vec![
+                ("service.name".to_string(), "token-service".to_string()),
+                ("service.version".to_string(), "1.2.0".to_string()),
+                (SERVICE_SPIFFE_ID.to_string(), "spiffe://example.org/ns/auth/sa/token".to_string()),
+                ("deployment.environment".to_string(), "prod".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_otlp_headers() {
+        let headers = parse_key_values("Authorization=Bearer abc=, x-tenant = acme,").unwrap();
+        assert_eq!(
+            headers,
+            vec![
+                ("Authorization".to_string(), "Bearer abc=".to_string()),
+                ("x-tenant".to_string(), "acme".to_string()),
+            ]
+        );
+        let metadata = otlp_metadata(&headers).unwrap();
+        assert_eq!(metadata.get("authorization").unwrap(), "Bearer abc=");
+
+        assert!(parse_key_values("no-equals").is_err());
+        assert!(parse_key_values("=value").is_err());
+        assert!(otlp_metadata(&[("bad header".to_string(), "v".to_string())]).is_err());
+        assert!(parse_key_values("").unwrap().is_empty());
     }
 }
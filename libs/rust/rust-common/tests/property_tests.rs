@@ -5,7 +5,7 @@
 use proptest::prelude::*;
 use rust_common::{
     CircuitBreaker, CircuitBreakerConfig, CircuitState,
-    PlatformError,
+    PlatformError, TripPolicy,
 };
 
 // **Feature: rust-libs-modernization-2025, Property 14: Input Validation Rejection**
@@ -143,6 +143,7 @@ proptest! {
                 success_threshold,
                 timeout: std::time::Duration::from_millis(100),
                 half_open_max_requests: 3,
+                trip: TripPolicy::ConsecutiveFailures,
             };
             let cb = CircuitBreaker::new(config);
 
@@ -175,6 +176,7 @@ proptest! {
                 success_threshold,
                 timeout: std::time::Duration::from_millis(1), // Very short timeout
                 half_open_max_requests: 10,
+                trip: TripPolicy::ConsecutiveFailures,
             };
             let cb = CircuitBreaker::new(config);
 
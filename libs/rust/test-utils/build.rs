@@ -0,0 +1,18 @@
+//! Compiles the public auth API protos for golden-file snapshots.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Messages only, with serde so responses can be rendered as JSON
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(false)
+        .type_attribute(".", "#[derive(serde::Serialize)]")
+        .compile_protos(
+            &[
+                "../../../api/proto/auth/auth_edge.proto",
+                "../../../api/proto/auth/token_service.proto",
+            ],
+            &["../../../api/proto/auth"],
+        )?;
+
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! Golden-file snapshots of gRPC response shapes.
+//!
+//! Responses are rendered as canonical JSON (sorted keys, pretty-printed)
+//! with volatile fields such as tokens and timestamps redacted, and
+//! compared against files checked into the repository. Set
+//! `UPDATE_GOLDEN=1` to (re)write the golden files after an intended
+//! proto change.
+//!
+//! [`proto`] holds the public auth API messages (`ValidateTokenResponse`,
+//! `IntrospectResponse`, `TokenPairResponse`, ...) with `serde::Serialize`
+//! derived, so their shapes can be snapshotted directly.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Public auth API messages from `api/proto/auth`.
+#[allow(missing_docs, clippy::all, clippy::pedantic, clippy::nursery)]
+pub mod proto {
+    /// `auth.common` package
+    pub mod common {
+        include!(concat!(env!("OUT_DIR"), "/auth.common.rs"));
+    }
+    /// `auth.edge` package
+    pub mod edge {
+        include!(concat!(env!("OUT_DIR"), "/auth.edge.rs"));
+    }
+    /// `auth.token` package
+    pub mod token {
+        include!(concat!(env!("OUT_DIR"), "/auth.token.rs"));
+    }
+}
+
+/// Environment variable that switches [`Golden`] to update mode.
+pub const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+/// Replacement for redacted values.
+pub const REDACTED: &str = "[redacted]";
+
+/// Fields redacted by default: tokens, timestamps and per-request IDs.
+pub const VOLATILE_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "expires_at",
+    "issued_at",
+    "not_before",
+    "exp",
+    "iat",
+    "nbf",
+    "jti",
+    "jwt_id",
+    "correlation_id",
+];
+
+/// Golden-file comparison failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GoldenError {
+    /// Value could not be serialized to JSON
+    Serialize(String),
+    /// Golden file could not be read or written
+    Io {
+        /// Golden file path
+        path: PathBuf,
+        /// Underlying error
+        reason: String,
+    },
+    /// No golden file exists yet
+    Missing {
+        /// Expected golden file path
+        path: PathBuf,
+    },
+    /// Rendered value differs from the golden file
+    Mismatch {
+        /// Golden file path
+        path: PathBuf,
+        /// Golden file contents
+        expected: String,
+        /// Rendered value
+        actual: String,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize(reason) => write!(f, "serialization failed: {reason}"),
+            Self::Io { path, reason } => write!(f, "{}: {reason}", path.display()),
+            Self::Missing { path } => write!(
+                f,
+                "{} does not exist; run with {UPDATE_ENV}=1 to create it",
+                path.display()
+            ),
+            Self::Mismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} is out of date; run with {UPDATE_ENV}=1 if the change is intended\n\
+                 --- expected\n{expected}\n+++ actual\n{actual}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+/// Golden-file directory with its redaction rules.
+#[derive(Debug, Clone)]
+pub struct Golden {
+    dir: PathBuf,
+    redact: BTreeSet<String>,
+    update: bool,
+}
+
+impl Golden {
+    /// Golden files in `dir`, redacting [`VOLATILE_FIELDS`].
+    ///
+    /// Update mode is enabled when `UPDATE_GOLDEN` is set to anything but
+    /// `0` or an empty string.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let update = std::env::var(UPDATE_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+        Self {
+            dir: dir.into(),
+            redact: VOLATILE_FIELDS.iter().map(|f| (*f).to_string()).collect(),
+            update,
+        }
+    }
+
+    /// Also redact `field` wherever it appears.
+    #[must_use]
+    pub fn redact(mut self, field: impl Into<String>) -> Self {
+        self.redact.insert(field.into());
+        self
+    }
+
+    /// Stop redacting `field`.
+    #[must_use]
+    pub fn keep(mut self, field: &str) -> Self {
+        self.redact.remove(field);
+        self
+    }
+
+    /// Force update mode on or off regardless of the environment.
+    #[must_use]
+    pub const fn updating(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Path of the golden file for `name`.
+    #[must_use]
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.json"))
+    }
+
+    /// Render `value` as canonical, redacted JSON.
+    ///
+    /// Object keys are sorted at every level. A redacted field keeps its
+    /// default value (`null`, `""`, `0`, `false`, empty list or map) so
+    /// that presence still shows up in the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GoldenError::Serialize`] if `value` cannot be serialized.
+    pub fn render<T: Serialize + ?Sized>(&self, value: &T) -> Result<String, GoldenError> {
+        let value =
+            serde_json::to_value(value).map_err(|e| GoldenError::Serialize(e.to_string()))?;
+        let canonical = self.canonicalize(value);
+        let mut rendered = serde_json::to_string_pretty(&canonical)
+            .map_err(|e| GoldenError::Serialize(e.to_string()))?;
+        rendered.push('\n');
+        Ok(rendered)
+    }
+
+    /// Compare `value` with the golden file `name`, or write it in update
+    /// mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the golden file is missing or differs, or if
+    /// it cannot be read or written.
+    pub fn check<T: Serialize + ?Sized>(&self, name: &str, value: &T) -> Result<(), GoldenError> {
+        let path = self.path(name);
+        let actual = self.render(value)?;
+
+        if self.update {
+            return write(&path, &actual);
+        }
+
+        let expected = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents.replace("\r\n", "\n"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(GoldenError::Missing { path });
+            }
+            Err(e) => {
+                return Err(GoldenError::Io {
+                    path,
+                    reason: e.to_string(),
+                });
+            }
+        };
+
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(GoldenError::Mismatch {
+                path,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    /// Assert that `value` matches the golden file `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the expected and actual JSON if [`Golden::check`] fails.
+    #[track_caller]
+    pub fn assert<T: Serialize + ?Sized>(&self, name: &str, value: &T) {
+        if let Err(e) = self.check(name, value) {
+            panic!("golden file mismatch: {e}");
+        }
+    }
+
+    fn canonicalize(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                let mut sorted = Map::new();
+                for (key, value) in entries {
+                    let value = if self.redact.contains(&key) && !is_default(&value) {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        self.canonicalize(value)
+                    };
+                    sorted.insert(key, value);
+                }
+                Value::Object(sorted)
+            }
+            Value::Array(values) => {
+                Value::Array(values.into_iter().map(|v| self.canonicalize(v)).collect())
+            }
+            other => other,
+        }
+    }
+}
+
+fn is_default(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(b) => !b,
+        Value::Number(n) => n.as_f64() == Some(0.0),
+        Value::String(s) => s.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        Value::Object(map) => map.is_empty(),
+    }
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), GoldenError> {
+    let io_error = |e: std::io::Error| GoldenError::Io {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    std::fs::write(path, contents).map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proto::edge::{IntrospectResponse, ValidateTokenResponse};
+    use super::proto::token::TokenPairResponse;
+    use super::*;
+    use std::collections::HashMap;
+
+    fn api_golden() -> Golden {
+        Golden::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden"))
+    }
+
+    fn scratch_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("golden-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn claims() -> HashMap<String, String> {
+        HashMap::from([
+            ("iss".to_string(), "https://auth.example.com".to_string()),
+            ("jti".to_string(), uuid::Uuid::new_v4().to_string()),
+            ("exp".to_string(), "1767225600".to_string()),
+            ("scope".to_string(), "openid profile".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_validate_token_response_shape() {
+        let response = ValidateTokenResponse {
+            valid: true,
+            subject: "user-123".to_string(),
+            claims: claims(),
+            error_code: String::new(),
+            error_message: String::new(),
+        };
+        api_golden().assert("validate_token_response", &response);
+    }
+
+    #[test]
+    fn test_introspect_response_shape() {
+        let response = IntrospectResponse {
+            active: true,
+            subject: "user-123".to_string(),
+            client_id: "client-abc".to_string(),
+            scopes: vec!["openid".to_string(), "profile".to_string()],
+            expires_at: chrono::Utc::now().timestamp() + 900,
+            issued_at: chrono::Utc::now().timestamp(),
+            token_type: "Bearer".to_string(),
+            claims: claims(),
+        };
+        api_golden().assert("introspect_response", &response);
+    }
+
+    #[test]
+    fn test_token_pair_response_shape() {
+        let response = TokenPairResponse {
+            access_token: "eyJhbGciOiJFUzI1NiJ9.payload.signature".to_string(),
+            refresh_token: uuid::Uuid::new_v4().to_string(),
+            id_token: String::new(),
+            expires_at: chrono::Utc::now().timestamp() + 900,
+            token_type: "Bearer".to_string(),
+        };
+        api_golden().assert("token_pair_response", &response);
+    }
+
+    #[test]
+    fn test_render_sorts_keys_and_keeps_default_volatile_fields() -> Result<(), GoldenError> {
+        let rendered = Golden::new(scratch_dir()).render(&serde_json::json!({
+            "b": {"jti": "abc", "exp": 0},
+            "a": [{"access_token": ""}],
+        }))?;
+
+        let expected = "{\n  \"a\": [\n    {\n      \"access_token\": \"\"\n    }\n  ],\n  \
+                        \"b\": {\n    \"exp\": 0,\n    \"jti\": \"[redacted]\"\n  }\n}\n";
+        assert_eq!(rendered, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_redact_and_keep() -> Result<(), GoldenError> {
+        let golden = Golden::new(scratch_dir()).redact("session_id").keep("jti");
+        let rendered = golden.render(&serde_json::json!({"session_id": "s-1", "jti": "j-1"}))?;
+
+        assert!(rendered.contains("\"session_id\": \"[redacted]\""));
+        assert!(rendered.contains("\"jti\": \"j-1\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_reports_missing_and_mismatch() -> Result<(), GoldenError> {
+        let dir = scratch_dir();
+        let golden = Golden::new(&dir).updating(false);
+
+        assert!(matches!(
+            golden.check("sample", &serde_json::json!({"valid": true})),
+            Err(GoldenError::Missing { .. })
+        ));
+
+        golden
+            .clone()
+            .updating(true)
+            .check("sample", &serde_json::json!({"valid": true}))?;
+        golden.check("sample", &serde_json::json!({"valid": true}))?;
+        assert!(matches!(
+            golden.check("sample", &serde_json::json!({"valid": false})),
+            Err(GoldenError::Mismatch { .. })
+        ));
+
+        let _ = std::fs::remove_dir_all(dir);
+        Ok(())
+    }
+}
@@ -4,6 +4,8 @@
 //! - Proptest generators for all domain types
 //! - Mock implementations for service clients
 //! - Test fixtures with sample data
+//! - End-to-end token lifecycle scenarios
+//! - Golden-file snapshots of gRPC response shapes
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
@@ -11,5 +13,7 @@
 pub mod generators;
 pub mod mocks;
 pub mod fixtures;
+pub mod golden;
+pub mod scenarios;
 
 pub use generators::*;
@@ -0,0 +1,398 @@
+//! End-to-end token lifecycle scenarios.
+//!
+//! A service test suite implements [`TokenLifecycle`] over its real
+//! components (KMS, storage, refresh rotator, validator) and runs a
+//! [`LifecycleScenario`], which drives
+//! issue → validate → refresh → replay-detect → revoke → validate-fails
+//! and checks the invariants between steps.
+
+use std::fmt;
+use std::future::Future;
+
+/// Tokens returned by issuing or refreshing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuedTokens {
+    /// Signed access token
+    pub access_token: String,
+    /// Opaque refresh token
+    pub refresh_token: String,
+    /// `jti` of the access token
+    pub jti: String,
+    /// Refresh token family
+    pub family_id: String,
+}
+
+/// Claims of an access token that passed validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedToken {
+    /// `sub` claim
+    pub subject: String,
+    /// `jti` claim
+    pub jti: String,
+}
+
+/// How a lifecycle operation was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// A rotated-out refresh token was presented again
+    Replay,
+    /// The token or its family has been revoked
+    Revoked,
+    /// The token is malformed, expired or unknown
+    Invalid,
+    /// Any other failure (storage, signing, ...)
+    Other,
+}
+
+/// Token lifecycle operations of a service under test.
+///
+/// Uses native async traits (Rust 2024).
+pub trait TokenLifecycle: Send + Sync {
+    /// Error returned by the service
+    type Error: fmt::Debug + Send;
+
+    /// Issue an access/refresh token pair for a new session.
+    fn issue(
+        &self,
+        subject: &str,
+        session_id: &str,
+    ) -> impl Future<Output = Result<IssuedTokens, Self::Error>> + Send;
+
+    /// Validate an access token.
+    fn validate(
+        &self,
+        access_token: &str,
+    ) -> impl Future<Output = Result<ValidatedToken, Self::Error>> + Send;
+
+    /// Exchange a refresh token for a new token pair.
+    fn refresh(
+        &self,
+        refresh_token: &str,
+    ) -> impl Future<Output = Result<IssuedTokens, Self::Error>> + Send;
+
+    /// Revoke the access token and its refresh token family.
+    fn revoke(&self, tokens: &IssuedTokens)
+    -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Classify a service error.
+    fn rejection(&self, error: &Self::Error) -> Rejection;
+}
+
+/// Step of a lifecycle scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleStep {
+    /// Issue the initial token pair
+    Issue,
+    /// Validate the issued access token
+    Validate,
+    /// Rotate the refresh token and validate the new access token
+    Refresh,
+    /// Replay the rotated-out refresh token
+    ReplayDetect,
+    /// Revoke the current tokens
+    Revoke,
+    /// Validate the revoked access token
+    ValidateRevoked,
+}
+
+/// A violated lifecycle invariant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioError {
+    /// Step that failed
+    pub step: LifecycleStep,
+    /// What went wrong
+    pub reason: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.step, self.reason)
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+/// Tokens seen while running a scenario.
+#[derive(Debug, Clone)]
+pub struct LifecycleReport {
+    /// Initial token pair
+    pub issued: IssuedTokens,
+    /// Token pair after the refresh
+    pub refreshed: IssuedTokens,
+}
+
+/// Full token lifecycle for one subject and session.
+#[derive(Debug, Clone)]
+pub struct LifecycleScenario {
+    subject: String,
+    session_id: String,
+}
+
+impl Default for LifecycleScenario {
+    fn default() -> Self {
+        Self::new(
+            format!("user-{}", uuid::Uuid::new_v4()),
+            uuid::Uuid::new_v4().simple().to_string(),
+        )
+    }
+}
+
+impl LifecycleScenario {
+    /// Create a scenario for the given subject and session.
+    #[must_use]
+    pub fn new(subject: impl Into<String>, session_id: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            session_id: session_id.into(),
+        }
+    }
+
+    /// Run every step against `lifecycle`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first step whose invariant does not hold.
+    pub async fn run<L: TokenLifecycle>(
+        &self,
+        lifecycle: &L,
+    ) -> Result<LifecycleReport, ScenarioError> {
+        use LifecycleStep::{Issue, Refresh, ReplayDetect, Revoke, Validate, ValidateRevoked};
+
+        let issued = lifecycle
+            .issue(&self.subject, &self.session_id)
+            .await
+            .map_err(|e| fail(Issue, format!("issue failed: {e:?}")))?;
+        if issued.access_token.is_empty()
+            || issued.refresh_token.is_empty()
+            || issued.jti.is_empty()
+        {
+            return Err(fail(Issue, "empty token or jti".to_string()));
+        }
+
+        self.expect_valid(lifecycle, Validate, &issued).await?;
+
+        let refreshed = lifecycle
+            .refresh(&issued.refresh_token)
+            .await
+            .map_err(|e| fail(Refresh, format!("refresh failed: {e:?}")))?;
+        if refreshed.refresh_token == issued.refresh_token {
+            return Err(fail(Refresh, "refresh token was not rotated".to_string()));
+        }
+        if refreshed.jti == issued.jti {
+            return Err(fail(Refresh, "access token jti was reused".to_string()));
+        }
+        if refreshed.family_id != issued.family_id {
+            return Err(fail(Refresh, "refresh left the token family".to_string()));
+        }
+        self.expect_valid(lifecycle, Refresh, &refreshed).await?;
+
+        expect_rejected(
+            lifecycle,
+            ReplayDetect,
+            lifecycle.refresh(&issued.refresh_token).await,
+            Rejection::Replay,
+        )?;
+        expect_rejected(
+            lifecycle,
+            ReplayDetect,
+            lifecycle.refresh(&refreshed.refresh_token).await,
+            Rejection::Revoked,
+        )?;
+
+        lifecycle
+            .revoke(&refreshed)
+            .await
+            .map_err(|e| fail(Revoke, format!("revoke failed: {e:?}")))?;
+        expect_rejected(
+            lifecycle,
+            ValidateRevoked,
+            lifecycle.validate(&refreshed.access_token).await,
+            Rejection::Revoked,
+        )?;
+
+        Ok(LifecycleReport { issued, refreshed })
+    }
+
+    async fn expect_valid<L: TokenLifecycle>(
+        &self,
+        lifecycle: &L,
+        step: LifecycleStep,
+        tokens: &IssuedTokens,
+    ) -> Result<(), ScenarioError> {
+        let validated = lifecycle
+            .validate(&tokens.access_token)
+            .await
+            .map_err(|e| fail(step, format!("access token rejected: {e:?}")))?;
+        if validated.subject != self.subject {
+            return Err(fail(
+                step,
+                format!("subject {} != {}", validated.subject, self.subject),
+            ));
+        }
+        if validated.jti != tokens.jti {
+            return Err(fail(
+                step,
+                format!("jti {} != {}", validated.jti, tokens.jti),
+            ));
+        }
+        Ok(())
+    }
+}
+
+const fn fail(step: LifecycleStep, reason: String) -> ScenarioError {
+    ScenarioError { step, reason }
+}
+
+fn expect_rejected<L: TokenLifecycle, T: fmt::Debug>(
+    lifecycle: &L,
+    step: LifecycleStep,
+    result: Result<T, L::Error>,
+    expected: Rejection,
+) -> Result<(), ScenarioError> {
+    match result {
+        Err(e) if lifecycle.rejection(&e) == expected => Ok(()),
+        Err(e) => Err(fail(
+            step,
+            format!("expected {expected:?} rejection, got {e:?}"),
+        )),
+        Ok(value) => Err(fail(
+            step,
+            format!("expected {expected:?} rejection, got {value:?}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    /// Minimal lifecycle with opaque tokens, honouring every invariant
+    /// unless `skip_replay_revocation` is set.
+    #[derive(Default)]
+    struct FakeLifecycle {
+        skip_replay_revocation: bool,
+        state: Mutex<FakeState>,
+    }
+
+    #[derive(Default)]
+    struct FakeState {
+        next: u32,
+        // refresh token -> (subject, family, current)
+        refresh: HashMap<String, (String, String, bool)>,
+        revoked_families: HashSet<String>,
+        access: HashMap<String, (String, String)>,
+        revoked_jtis: HashSet<String>,
+    }
+
+    impl FakeState {
+        fn mint(&mut self, subject: &str, family_id: &str) -> IssuedTokens {
+            self.next += 1;
+            let jti = format!("jti-{}", self.next);
+            let tokens = IssuedTokens {
+                access_token: format!("at-{}", self.next),
+                refresh_token: format!("rt-{}", self.next),
+                jti: jti.clone(),
+                family_id: family_id.to_string(),
+            };
+            self.refresh.insert(
+                tokens.refresh_token.clone(),
+                (subject.to_string(), family_id.to_string(), true),
+            );
+            self.access
+                .insert(tokens.access_token.clone(), (subject.to_string(), jti));
+            tokens
+        }
+    }
+
+    impl FakeLifecycle {
+        fn state(&self) -> MutexGuard<'_, FakeState> {
+            self.state.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    impl TokenLifecycle for FakeLifecycle {
+        type Error = Rejection;
+
+        async fn issue(&self, subject: &str, session_id: &str) -> Result<IssuedTokens, Rejection> {
+            let tokens = self.state().mint(subject, session_id);
+            Ok(tokens)
+        }
+
+        async fn validate(&self, access_token: &str) -> Result<ValidatedToken, Rejection> {
+            let state = self.state();
+            let (subject, jti) = state
+                .access
+                .get(access_token)
+                .cloned()
+                .ok_or(Rejection::Invalid)?;
+            let revoked = state.revoked_jtis.contains(&jti);
+            drop(state);
+            if revoked {
+                return Err(Rejection::Revoked);
+            }
+            Ok(ValidatedToken { subject, jti })
+        }
+
+        async fn refresh(&self, refresh_token: &str) -> Result<IssuedTokens, Rejection> {
+            let mut state = self.state();
+            let (subject, family_id, current) = state
+                .refresh
+                .get(refresh_token)
+                .cloned()
+                .ok_or(Rejection::Invalid)?;
+            if state.revoked_families.contains(&family_id) {
+                return Err(Rejection::Revoked);
+            }
+            if !current {
+                if !self.skip_replay_revocation {
+                    state.revoked_families.insert(family_id);
+                }
+                return Err(Rejection::Replay);
+            }
+            state.refresh.insert(
+                refresh_token.to_string(),
+                (subject.clone(), family_id.clone(), false),
+            );
+            Ok(state.mint(&subject, &family_id))
+        }
+
+        async fn revoke(&self, tokens: &IssuedTokens) -> Result<(), Rejection> {
+            let mut state = self.state();
+            state.revoked_jtis.insert(tokens.jti.clone());
+            state.revoked_families.insert(tokens.family_id.clone());
+            drop(state);
+            Ok(())
+        }
+
+        fn rejection(&self, error: &Rejection) -> Rejection {
+            *error
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scenario_passes_for_conforming_lifecycle() -> Result<(), ScenarioError> {
+        let report = LifecycleScenario::new("user-1", "session-1")
+            .run(&FakeLifecycle::default())
+            .await?;
+        assert_ne!(report.issued.refresh_token, report.refreshed.refresh_token);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scenario_reports_failed_invariant() {
+        let lifecycle = FakeLifecycle {
+            skip_replay_revocation: true,
+            ..FakeLifecycle::default()
+        };
+        let result = LifecycleScenario::default().run(&lifecycle).await;
+        assert!(matches!(
+            result,
+            Err(ScenarioError {
+                step: LifecycleStep::ReplayDetect,
+                ..
+            })
+        ));
+    }
+}
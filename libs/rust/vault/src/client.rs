@@ -7,7 +7,7 @@ use crate::{
     secrets::{AuthResponse, DatabaseCredsResponse, KvResponse},
 };
 use reqwest::Client;
-use rust_common::{CircuitBreaker, CircuitBreakerConfig};
+use rust_common::{CircuitBreaker, CircuitBreakerConfig, TripPolicy};
 use secrecy::SecretString;
 use serde::de::DeserializeOwned;
 use std::{sync::Arc, time::Duration};
@@ -37,6 +37,7 @@ impl VaultClient {
             success_threshold: 2,
             timeout: config.circuit_breaker_timeout,
             half_open_max_requests: 3,
+            trip: TripPolicy::ConsecutiveFailures,
         };
 
         Ok(Self {
@@ -0,0 +1,83 @@
+//! Rate limiter throughput benchmarks.
+//!
+//! Compares the sharded client table against a single shard, which
+//! serializes every check behind one lock like the former global
+//! `RwLock<HashMap>`, with checks for distinct clients issued concurrently.
+//!
+//! Run with `cargo test --release --bench rate_limiter_bench -- --nocapture`;
+//! throughput is only asserted in release builds with at least four cores.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use auth_edge::rate_limiter::{AdaptiveRateLimiter, RateLimitConfig, DEFAULT_SHARDS};
+
+const TASKS: usize = 8;
+const CHECKS_PER_TASK: usize = 50_000;
+const CLIENTS_PER_TASK: usize = 1_000;
+
+/// Time `TASKS * CHECKS_PER_TASK` concurrent checks
+fn measure(limiter: AdaptiveRateLimiter) -> Duration {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(TASKS)
+        .build()
+        .unwrap();
+    let limiter = Arc::new(limiter);
+
+    runtime.block_on(async {
+        let clients: Vec<Vec<String>> = (0..TASKS)
+            .map(|task| {
+                (0..CLIENTS_PER_TASK)
+                    .map(|i| format!("client-{task}-{i}"))
+                    .collect()
+            })
+            .collect();
+
+        let start = Instant::now();
+        let tasks: Vec<_> = clients
+            .into_iter()
+            .map(|clients| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move {
+                    for i in 0..CHECKS_PER_TASK {
+                        let client = &clients[i % CLIENTS_PER_TASK];
+                        std::hint::black_box(limiter.check(client).await);
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        start.elapsed()
+    })
+}
+
+fn checks_per_sec(elapsed: Duration) -> f64 {
+    (TASKS * CHECKS_PER_TASK) as f64 / elapsed.as_secs_f64()
+}
+
+#[test]
+fn test_sharded_check_throughput() {
+    let single = measure(AdaptiveRateLimiter::with_shards(RateLimitConfig::default(), 1));
+    let sharded = measure(AdaptiveRateLimiter::new(RateLimitConfig::default()));
+
+    println!("\nRate Limiter Check Benchmark ({TASKS} concurrent tasks):");
+    println!("  Checks: {}", TASKS * CHECKS_PER_TASK);
+    println!("  Single lock:        {:.0} checks/s", checks_per_sec(single));
+    println!(
+        "  {DEFAULT_SHARDS} shards:          {:.0} checks/s",
+        checks_per_sec(sharded)
+    );
+    println!("  Speedup: {:.2}x", single.as_secs_f64() / sharded.as_secs_f64());
+
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if !cfg!(debug_assertions) && cores >= 4 {
+        assert!(
+            sharded < single,
+            "sharded limiter ({:?}) should beat a single lock ({:?})",
+            sharded,
+            single
+        );
+    }
+}
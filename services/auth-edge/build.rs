@@ -1,4 +1,8 @@
+use std::path::PathBuf;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+
     // Compile crypto-service proto for client
     tonic_build::configure()
         .build_server(false)
@@ -9,14 +13,35 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         )?;
 
     // Compile auth-edge proto for server implementation
-    // Using simplified version without buf/validate and google/api imports
+    // Using simplified version without buf/validate and google/api imports;
+    // its descriptors back server reflection and the client is used by the
+    // perf smoke test
     tonic_build::configure()
         .build_server(true)
-        .build_client(false)
+        .build_client(true)
+        .file_descriptor_set_path(out_dir.join("auth_edge_descriptor.bin"))
         .compile_protos(
             &["proto/auth_edge.proto"],
             &["proto"],
         )?;
 
+    // Compile token-service proto for the federation token exchange client
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(true)
+        .compile_protos(
+            &["../../api/proto/auth/token_service.proto"],
+            &["../../api/proto/auth"],
+        )?;
+
+    // Compile the SPIFFE Workload API; the server is used by tests
+    tonic_build::configure()
+        .build_server(true)
+        .build_client(true)
+        .compile_protos(
+            &["proto/workload.proto"],
+            &["proto"],
+        )?;
+
     Ok(())
 }
@@ -0,0 +1,10 @@
+//! Header and claims decoding without signature verification.
+
+#![no_main]
+
+use auth_edge::jwt::harness;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = harness::parse_unverified(data);
+});
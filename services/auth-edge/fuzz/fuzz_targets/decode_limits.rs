@@ -0,0 +1,16 @@
+//! Payload decode limits: size, claim count and nesting depth checks.
+
+#![no_main]
+
+use auth_edge::jwt::{harness, DecodeLimits};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Small limits so the fuzzer reaches every rejection path
+    let limits = DecodeLimits {
+        max_payload_bytes: 256,
+        max_claims: 4,
+        max_depth: 3,
+    };
+    let _ = harness::parse_with_limits(data, &limits);
+});
@@ -0,0 +1,12 @@
+//! Full validation pipeline: header parse, signature check, claims checks.
+
+#![no_main]
+
+use auth_edge::jwt::harness;
+use jsonwebtoken::DecodingKey;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let key = DecodingKey::from_secret(b"fuzz-secret");
+    let _ = harness::validate_with_key(data, &key, &["sub", "scopes"]);
+});
@@ -0,0 +1,369 @@
+//! API Key Caller Authentication
+//!
+//! Alternative to mTLS for low-trust internal callers. Callers send
+//! `x-api-key: <key_id>.<secret>`; only the SHA-256 of the secret is stored,
+//! either in a JSON file rendered from Vault or in Cache_Service. Each key is
+//! scoped to specific RPCs and carries its own per-minute rate limit.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use rust_common::{CacheClient, CacheClientConfig};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tonic::Status;
+
+use crate::config::Config;
+
+/// Metadata header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Window for per-key rate limits
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// API key authentication failures
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ApiKeyError {
+    /// Key is malformed, unknown or has the wrong secret
+    #[error("Invalid API key")]
+    Invalid,
+
+    /// Key is valid but not scoped to the requested RPC
+    #[error("API key {key_id} is not allowed to call {rpc}")]
+    NotAllowed {
+        /// Key that was used
+        key_id: String,
+        /// RPC that was requested
+        rpc: String,
+    },
+
+    /// Key exceeded its rate limit
+    #[error("API key {key_id} rate limited")]
+    RateLimited {
+        /// Key that was used
+        key_id: String,
+        /// Seconds until the window resets
+        retry_after: u64,
+    },
+
+    /// Key store could not be read
+    #[error("API key store unavailable: {0}")]
+    Store(String),
+}
+
+impl ApiKeyError {
+    /// Converts to a gRPC status without leaking which part of the key failed
+    pub fn to_status(&self) -> Status {
+        match self {
+            Self::Invalid => Status::unauthenticated("Invalid API key"),
+            Self::NotAllowed { rpc, .. } => {
+                Status::permission_denied(format!("API key not allowed to call {rpc}"))
+            }
+            Self::RateLimited { retry_after, .. } => Status::resource_exhausted(format!(
+                "API key rate limit exceeded, retry after {retry_after}s"
+            )),
+            Self::Store(_) => Status::unavailable("API key verification temporarily unavailable"),
+        }
+    }
+}
+
+/// Stored API key; never contains the secret itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    /// Public key identifier (the part before the `.`)
+    pub key_id: String,
+    /// Hex-encoded SHA-256 of the secret
+    pub secret_sha256: String,
+    /// RPC method names this key may call, e.g. `ValidateToken`
+    pub allowed_rpcs: Vec<String>,
+    /// Requests per minute for this key
+    pub rate_limit_per_minute: u32,
+}
+
+impl ApiKeyRecord {
+    /// Creates a record for a secret, hashing it
+    pub fn new(
+        key_id: impl Into<String>,
+        secret: &str,
+        allowed_rpcs: Vec<String>,
+        rate_limit_per_minute: u32,
+    ) -> Self {
+        Self {
+            key_id: key_id.into(),
+            secret_sha256: to_hex(&Sha256::digest(secret.as_bytes())),
+            allowed_rpcs,
+            rate_limit_per_minute,
+        }
+    }
+
+    /// Compares a presented secret against the stored hash in constant time
+    fn secret_matches(&self, secret: &str) -> bool {
+        let Some(expected) = from_hex(&self.secret_sha256) else {
+            return false;
+        };
+        let presented = Sha256::digest(secret.as_bytes());
+        presented.as_slice().ct_eq(&expected).into()
+    }
+
+    fn allows(&self, rpc: &str) -> bool {
+        self.allowed_rpcs.iter().any(|allowed| allowed == rpc)
+    }
+}
+
+/// Identity of an authenticated API key caller, added to request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyIdentity {
+    /// Key identifier
+    pub key_id: String,
+}
+
+/// Source of hashed API key records
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    /// Looks up a key record by ID
+    async fn lookup(&self, key_id: &str) -> Result<Option<ApiKeyRecord>, ApiKeyError>;
+}
+
+/// Key records loaded once from a JSON file (e.g. rendered by Vault Agent)
+#[derive(Debug, Default)]
+pub struct StaticKeyStore {
+    records: HashMap<String, ApiKeyRecord>,
+}
+
+impl StaticKeyStore {
+    /// Creates a store from records
+    pub fn new(records: impl IntoIterator<Item = ApiKeyRecord>) -> Self {
+        Self {
+            records: records
+                .into_iter()
+                .map(|record| (record.key_id.clone(), record))
+                .collect(),
+        }
+    }
+
+    /// Loads a JSON array of records from a file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ApiKeyError> {
+        let path = path.as_ref();
+        let contents = std::fs::read(path)
+            .map_err(|e| ApiKeyError::Store(format!("{}: {e}", path.display())))?;
+        let records: Vec<ApiKeyRecord> = serde_json::from_slice(&contents)
+            .map_err(|e| ApiKeyError::Store(format!("{}: {e}", path.display())))?;
+        Ok(Self::new(records))
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for StaticKeyStore {
+    async fn lookup(&self, key_id: &str) -> Result<Option<ApiKeyRecord>, ApiKeyError> {
+        Ok(self.records.get(key_id).cloned())
+    }
+}
+
+/// Key records stored as JSON in Cache_Service under `auth-edge:apikey`
+pub struct CacheKeyStore {
+    cache: CacheClient,
+}
+
+impl CacheKeyStore {
+    /// Connects to Cache_Service
+    pub async fn new(config: &Config) -> Result<Self, ApiKeyError> {
+        let cache_config = CacheClientConfig::default()
+            .with_address(config.cache_service_url_str())
+            .with_namespace("auth-edge:apikey");
+        let cache_config = match config.cache_encryption_key {
+            Some(key) => cache_config.with_encryption_key(key),
+            None => cache_config,
+        };
+        let cache = CacheClient::new(cache_config)
+            .await
+            .map_err(|e| ApiKeyError::Store(e.to_string()))?;
+        Ok(Self { cache })
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for CacheKeyStore {
+    async fn lookup(&self, key_id: &str) -> Result<Option<ApiKeyRecord>, ApiKeyError> {
+        let Some(bytes) = self
+            .cache
+            .get(key_id)
+            .await
+            .map_err(|e| ApiKeyError::Store(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| ApiKeyError::Store(format!("corrupt record for {key_id}: {e}")))
+    }
+}
+
+/// Verifies API keys, their RPC scope and per-key rate limits
+pub struct ApiKeyAuthenticator {
+    store: Arc<dyn ApiKeyStore>,
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl ApiKeyAuthenticator {
+    /// Creates an authenticator over a key store
+    pub fn new(store: Arc<dyn ApiKeyStore>) -> Self {
+        Self {
+            store,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Builds the authenticator from configuration, or `None` when disabled
+    ///
+    /// Keys come from `API_KEYS_PATH` when set, otherwise from Cache_Service.
+    pub async fn from_config(config: &Config) -> Result<Option<Self>, ApiKeyError> {
+        if !config.api_key_auth_enabled {
+            return Ok(None);
+        }
+        let store: Arc<dyn ApiKeyStore> = match &config.api_keys_path {
+            Some(path) => Arc::new(StaticKeyStore::from_file(path)?),
+            None => Arc::new(CacheKeyStore::new(config).await?),
+        };
+        Ok(Some(Self::new(store)))
+    }
+
+    /// Authenticates a presented `<key_id>.<secret>` for an RPC method name
+    pub async fn authenticate(&self, presented: &str, rpc: &str) -> Result<ApiKeyIdentity, ApiKeyError> {
+        self.authenticate_at(presented, rpc, Instant::now()).await
+    }
+
+    async fn authenticate_at(
+        &self,
+        presented: &str,
+        rpc: &str,
+        now: Instant,
+    ) -> Result<ApiKeyIdentity, ApiKeyError> {
+        let (key_id, secret) = presented.split_once('.').ok_or(ApiKeyError::Invalid)?;
+        if key_id.is_empty() || secret.is_empty() {
+            return Err(ApiKeyError::Invalid);
+        }
+
+        let record = self.store.lookup(key_id).await?.ok_or(ApiKeyError::Invalid)?;
+        if !record.secret_matches(secret) {
+            return Err(ApiKeyError::Invalid);
+        }
+        if !record.allows(rpc) {
+            return Err(ApiKeyError::NotAllowed {
+                key_id: record.key_id,
+                rpc: rpc.to_string(),
+            });
+        }
+
+        self.check_rate(&record, now).await?;
+        Ok(ApiKeyIdentity {
+            key_id: record.key_id,
+        })
+    }
+
+    /// Fixed-window per-key request counter
+    async fn check_rate(&self, record: &ApiKeyRecord, now: Instant) -> Result<(), ApiKeyError> {
+        let mut windows = self.windows.lock().await;
+        let (start, count) = windows.entry(record.key_id.clone()).or_insert((now, 0));
+        if now.duration_since(*start) >= RATE_WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= record.rate_limit_per_minute {
+            let retry_after = RATE_WINDOW
+                .saturating_sub(now.duration_since(*start))
+                .as_secs()
+                .max(1);
+            return Err(ApiKeyError::RateLimited {
+                key_id: record.key_id.clone(),
+                retry_after,
+            });
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authenticator(rate_limit: u32) -> ApiKeyAuthenticator {
+        let record = ApiKeyRecord::new("legacy-batch", "s3cr3t", vec!["ValidateToken".to_string()], rate_limit);
+        ApiKeyAuthenticator::new(Arc::new(StaticKeyStore::new([record])))
+    }
+
+    #[tokio::test]
+    async fn test_valid_key_authenticates() {
+        let identity = authenticator(10)
+            .authenticate("legacy-batch.s3cr3t", "ValidateToken")
+            .await
+            .unwrap();
+        assert_eq!(identity.key_id, "legacy-batch");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_secret_and_unknown_key_rejected() {
+        let auth = authenticator(10);
+        for presented in ["legacy-batch.wrong", "other.s3cr3t", "no-separator", ".s3cr3t"] {
+            assert_eq!(
+                auth.authenticate(presented, "ValidateToken").await,
+                Err(ApiKeyError::Invalid),
+                "{presented}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_key_scoped_to_rpcs() {
+        let result = authenticator(10)
+            .authenticate("legacy-batch.s3cr3t", "IntrospectToken")
+            .await;
+        assert!(matches!(result, Err(ApiKeyError::NotAllowed { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_per_key_rate_limit() {
+        let auth = authenticator(2);
+        let start = Instant::now();
+        for _ in 0..2 {
+            assert!(auth.authenticate_at("legacy-batch.s3cr3t", "ValidateToken", start).await.is_ok());
+        }
+        assert!(matches!(
+            auth.authenticate_at("legacy-batch.s3cr3t", "ValidateToken", start).await,
+            Err(ApiKeyError::RateLimited { .. })
+        ));
+        assert!(
+            auth.authenticate_at("legacy-batch.s3cr3t", "ValidateToken", start + RATE_WINDOW)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_record_never_serializes_secret() {
+        let record = ApiKeyRecord::new("k", "plaintext-secret", vec![], 1);
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("plaintext-secret"));
+        assert_eq!(record.secret_sha256.len(), 64);
+    }
+}
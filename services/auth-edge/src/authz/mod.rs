@@ -0,0 +1,250 @@
+//! Route Authorization
+//!
+//! After a token's signature and claims are validated, an optional
+//! [`PolicyEvaluator`] decides whether its holder may call the route the
+//! consumer is guarding (`ValidateTokenRequest.route`), based on scopes, roles
+//! and tenant. Policies come either from the built-in rule engine
+//! (`AUTHZ_RULES`) or from an OPA sidecar (`OPA_URL`). Evaluation fails
+//! closed: a policy that cannot be evaluated denies the request.
+
+pub mod opa;
+pub mod rules;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+
+pub use opa::OpaEvaluator;
+pub use rules::{RouteRule, RuleEngine};
+
+/// Everything a policy may base its decision on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PolicyInput {
+    /// HTTP method of the route, when the consumer sent one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Route path, e.g. `/v1/orders/42`
+    pub path: String,
+    /// Tenant the request targets, when the consumer sent one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Calling client (API key ID or peer IP)
+    pub caller: String,
+    /// Token subject
+    pub subject: String,
+    /// Granted scopes
+    pub scopes: Vec<String>,
+    /// Roles from the `roles` claim
+    pub roles: Vec<String>,
+    /// Tenant the token belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_tenant: Option<String>,
+    /// All validated claims
+    pub claims: serde_json::Value,
+}
+
+impl PolicyInput {
+    /// Builds the input for a route such as `GET /v1/orders/42`
+    ///
+    /// The method prefix is optional; `tenant_claim` names the claim holding
+    /// the token's tenant.
+    pub fn new(
+        route: &str,
+        tenant: &str,
+        caller: &str,
+        claims: &Claims,
+        tenant_claim: &str,
+    ) -> Self {
+        let route = route.trim();
+        let (method, path) = match route.split_once(' ') {
+            Some((method, path)) => (Some(method.to_ascii_uppercase()), path.trim()),
+            None => (None, route),
+        };
+
+        Self {
+            method,
+            path: path.to_string(),
+            tenant: (!tenant.is_empty()).then(|| tenant.to_string()),
+            caller: caller.to_string(),
+            subject: claims.sub.clone(),
+            scopes: claims.scopes.clone().unwrap_or_default(),
+            roles: string_list(claims.custom.get("roles")),
+            token_tenant: claims
+                .custom
+                .get(tenant_claim)
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            claims: serde_json::to_value(claims).unwrap_or_default(),
+        }
+    }
+}
+
+/// Reads a claim that is either an array of strings or a space-separated string
+fn string_list(value: Option<&serde_json::Value>) -> Vec<String> {
+    match value {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_json::Value::String(s)) => s.split_whitespace().map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Outcome of a policy evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDecision {
+    /// Whether the request is allowed
+    pub allowed: bool,
+    /// Why the request was denied; empty when allowed
+    pub reason: String,
+}
+
+impl PolicyDecision {
+    /// An allow decision
+    pub fn allow() -> Self {
+        Self {
+            allowed: true,
+            reason: String::new(),
+        }
+    }
+
+    /// A deny decision
+    pub fn deny(reason: impl Into<String>) -> Self {
+        Self {
+            allowed: false,
+            reason: reason.into(),
+        }
+    }
+
+    /// Converts a deny decision into an `AccessDenied` error
+    pub fn into_result(self) -> Result<(), AuthEdgeError> {
+        if self.allowed {
+            Ok(())
+        } else {
+            Err(AuthEdgeError::AccessDenied {
+                reason: self.reason,
+            })
+        }
+    }
+}
+
+/// Decides whether a validated token may access a route
+#[async_trait]
+pub trait PolicyEvaluator: Send + Sync {
+    /// Evaluates the policy; errors mean the policy could not be evaluated
+    async fn evaluate(&self, input: &PolicyInput) -> Result<PolicyDecision, AuthEdgeError>;
+}
+
+/// Configured policy evaluator together with the tenant claim name
+pub struct Authorizer {
+    evaluator: Arc<dyn PolicyEvaluator>,
+    tenant_claim: String,
+}
+
+impl Authorizer {
+    /// Creates an authorizer reading the token's tenant from `tenant_claim`
+    pub fn new(evaluator: Arc<dyn PolicyEvaluator>, tenant_claim: impl Into<String>) -> Self {
+        Self {
+            evaluator,
+            tenant_claim: tenant_claim.into(),
+        }
+    }
+
+    /// Builds the configured authorizer, or `None` when authorization is disabled
+    pub fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        let evaluator: Arc<dyn PolicyEvaluator> = if let Some(url) = &config.opa_url {
+            Arc::new(OpaEvaluator::new(url, config.opa_timeout_ms)?)
+        } else if !config.authz_rules.is_empty() {
+            Arc::new(RuleEngine::from_config(&config.authz_rules))
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(Self::new(evaluator, config.authz_tenant_claim.clone())))
+    }
+
+    /// Checks that `claims` may access `route`; errors when denied or when
+    /// the policy cannot be evaluated
+    pub async fn authorize(
+        &self,
+        route: &str,
+        tenant: &str,
+        caller: &str,
+        claims: &Claims,
+    ) -> Result<(), AuthEdgeError> {
+        let input = PolicyInput::new(route, tenant, caller, claims, &self.tenant_claim);
+        self.evaluator.evaluate(&input).await?.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    pub(crate) fn claims(scopes: &[&str], custom: &[(&str, serde_json::Value)]) -> Claims {
+        Claims {
+            iss: "https://auth.platform".to_string(),
+            sub: "user-1".to_string(),
+            aud: vec!["api".to_string()],
+            exp: 2_000,
+            iat: 1_000,
+            nbf: None,
+            jti: "jti-1".to_string(),
+            session_id: None,
+            scopes: Some(scopes.iter().map(|s| s.to_string()).collect()),
+            custom: custom
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authorizer_denies_with_access_denied() {
+        let rules = serde_json::from_str::<Vec<crate::config::AuthzRuleConfig>>(
+            r#"[{"route":"/v1/orders/**","scopes":["orders:read"]}]"#,
+        )
+        .unwrap();
+        let authorizer = Authorizer::new(Arc::new(RuleEngine::from_config(&rules)), "tenant_id");
+
+        let reader = claims(&["orders:read"], &[]);
+        assert!(authorizer.authorize("GET /v1/orders/1", "", "c", &reader).await.is_ok());
+
+        let err = authorizer
+            .authorize("GET /v1/orders/1", "", "c", &claims(&[], &[]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthEdgeError::AccessDenied { .. }));
+        assert_eq!(err.code(), crate::error::ErrorCode::AccessDenied);
+    }
+
+    #[test]
+    fn test_policy_input_from_route_and_claims() {
+        let claims = claims(
+            &["orders:read"],
+            &[
+                ("roles", serde_json::json!("admin auditor")),
+                ("tenant_id", serde_json::json!("acme")),
+            ],
+        );
+        let input = PolicyInput::new("get /v1/orders/42", "", "10.0.0.1", &claims, "tenant_id");
+
+        assert_eq!(input.method.as_deref(), Some("GET"));
+        assert_eq!(input.path, "/v1/orders/42");
+        assert_eq!(input.tenant, None);
+        assert_eq!(input.roles, vec!["admin", "auditor"]);
+        assert_eq!(input.token_tenant.as_deref(), Some("acme"));
+        assert_eq!(input.claims["sub"], "user-1");
+
+        let input = PolicyInput::new("/v1/orders", "acme", "10.0.0.1", &claims, "org");
+        assert_eq!(input.method, None);
+        assert_eq!(input.tenant.as_deref(), Some("acme"));
+        assert_eq!(input.token_tenant, None);
+    }
+}
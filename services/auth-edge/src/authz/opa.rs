@@ -0,0 +1,127 @@
+//! OPA Policy Evaluator
+//!
+//! Queries an Open Policy Agent sidecar through its Data API: the policy
+//! input is POSTed to `OPA_URL` (e.g. `http://localhost:8181/v1/data/authz`)
+//! and the document at that path decides. The document is either a boolean
+//! or an object with a boolean `allow` and an optional `reason`. An undefined
+//! document denies; an unreachable or failing OPA is reported as unavailable.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rust_common::PlatformError;
+use serde::Deserialize;
+
+use super::{PolicyDecision, PolicyEvaluator, PolicyInput};
+use crate::error::AuthEdgeError;
+
+/// Data API response body
+#[derive(Debug, Deserialize)]
+struct DataResponse {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+}
+
+/// Policy evaluator backed by an OPA sidecar
+pub struct OpaEvaluator {
+    http_client: reqwest::Client,
+    url: String,
+}
+
+impl OpaEvaluator {
+    /// Creates an evaluator querying the decision document at `url`
+    pub fn new(url: &str, timeout_ms: u64) -> Result<Self, AuthEdgeError> {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(timeout_ms))
+            .build()
+            .map_err(|e| {
+                AuthEdgeError::Platform(PlatformError::Internal(format!(
+                    "Failed to create OPA client: {e}"
+                )))
+            })?;
+        Ok(Self {
+            http_client,
+            url: url.to_string(),
+        })
+    }
+
+    async fn query(&self, input: &PolicyInput) -> Result<DataResponse, reqwest::Error> {
+        self.http_client
+            .post(&self.url)
+            .json(&serde_json::json!({ "input": input }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+/// Interprets the decision document
+fn decision(result: Option<serde_json::Value>) -> PolicyDecision {
+    match result {
+        Some(serde_json::Value::Bool(true)) => PolicyDecision::allow(),
+        Some(serde_json::Value::Object(document)) => {
+            let reason = document
+                .get("reason")
+                .and_then(|r| r.as_str())
+                .unwrap_or("denied by policy");
+            match document.get("allow") {
+                Some(serde_json::Value::Bool(true)) => PolicyDecision::allow(),
+                _ => PolicyDecision::deny(reason),
+            }
+        }
+        Some(_) => PolicyDecision::deny("denied by policy"),
+        None => PolicyDecision::deny("policy decision undefined"),
+    }
+}
+
+#[async_trait]
+impl PolicyEvaluator for OpaEvaluator {
+    async fn evaluate(&self, input: &PolicyInput) -> Result<PolicyDecision, AuthEdgeError> {
+        match self.query(input).await {
+            Ok(response) => Ok(decision(response.result)),
+            Err(e) if e.is_timeout() => Err(AuthEdgeError::Platform(PlatformError::Timeout(
+                "OPA query timed out".to_string(),
+            ))),
+            Err(e) => {
+                tracing::warn!(error = %e, "OPA policy query failed");
+                Err(AuthEdgeError::Platform(PlatformError::Unavailable(
+                    "policy engine unavailable".to_string(),
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decision_documents() {
+        assert!(decision(Some(json!(true))).allowed);
+        assert!(decision(Some(json!({"allow": true}))).allowed);
+        assert_eq!(
+            decision(Some(json!({"allow": false, "reason": "tenant mismatch"}))),
+            PolicyDecision::deny("tenant mismatch")
+        );
+        assert!(!decision(Some(json!(false))).allowed);
+        assert!(!decision(Some(json!({"allow": "yes"}))).allowed);
+        assert_eq!(
+            decision(serde_json::from_str::<DataResponse>("{}").unwrap().result),
+            PolicyDecision::deny("policy decision undefined")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_opa_fails_closed() {
+        let opa = OpaEvaluator::new("http://127.0.0.1:9/v1/data/authz", 250).unwrap();
+        let claims = crate::authz::tests::claims(&[], &[]);
+        let input = PolicyInput::new("GET /v1/orders", "", "10.0.0.1", &claims, "tenant_id");
+
+        let err = opa.evaluate(&input).await.unwrap_err();
+        assert!(matches!(err, AuthEdgeError::Platform(_)));
+    }
+}
@@ -0,0 +1,215 @@
+//! Built-in Rule Engine
+//!
+//! Evaluates `AUTHZ_RULES` in order; the first rule whose route matches the
+//! request decides. Requests matching no rule are denied.
+
+use async_trait::async_trait;
+
+use super::{PolicyDecision, PolicyEvaluator, PolicyInput};
+use crate::config::AuthzRuleConfig;
+use crate::error::AuthEdgeError;
+
+/// Route pattern with an optional method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteRule {
+    method: Option<String>,
+    segments: Vec<String>,
+    rest: bool,
+}
+
+impl RouteRule {
+    /// Parses `METHOD /path` or `/path`
+    ///
+    /// A `*` segment matches any single segment; a trailing `**` matches any
+    /// number of remaining segments, including none.
+    pub fn parse(route: &str) -> Result<Self, String> {
+        let route = route.trim();
+        let (method, path) = match route.split_once(' ') {
+            Some((method, path)) => (Some(method.to_ascii_uppercase()), path.trim()),
+            None => (None, route),
+        };
+        if !path.starts_with('/') {
+            return Err("path must start with /".to_string());
+        }
+
+        let mut segments: Vec<String> = split_path(path).map(str::to_string).collect();
+        let rest = segments.last().is_some_and(|s| s == "**");
+        if rest {
+            segments.pop();
+        }
+        if segments.iter().any(|s| s == "**") {
+            return Err("** is only allowed as the last segment".to_string());
+        }
+
+        Ok(Self {
+            method,
+            segments,
+            rest,
+        })
+    }
+
+    /// Whether the rule covers a route given as `METHOD /path` or `/path`
+    pub fn matches_route(&self, route: &str) -> bool {
+        let route = route.trim();
+        match route.split_once(' ') {
+            Some((method, path)) => {
+                self.matches(Some(method.to_ascii_uppercase().as_str()), path.trim())
+            }
+            None => self.matches(None, route),
+        }
+    }
+
+    /// Whether the rule covers the request's method and path
+    pub fn matches(&self, method: Option<&str>, path: &str) -> bool {
+        if let Some(expected) = &self.method {
+            if method != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        let segments: Vec<&str> = split_path(path).collect();
+        if segments.len() < self.segments.len()
+            || (!self.rest && segments.len() != self.segments.len())
+        {
+            return false;
+        }
+        self.segments
+            .iter()
+            .zip(&segments)
+            .all(|(pattern, segment)| pattern == "*" || pattern == segment)
+    }
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|s| !s.is_empty())
+}
+
+struct CompiledRule {
+    route: RouteRule,
+    config: AuthzRuleConfig,
+}
+
+/// First-match rule engine over `AUTHZ_RULES`
+pub struct RuleEngine {
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleEngine {
+    /// Compiles the configured rules, skipping invalid routes
+    ///
+    /// Routes are checked during config validation, so none are skipped in
+    /// practice.
+    pub fn from_config(rules: &[AuthzRuleConfig]) -> Self {
+        let rules = rules
+            .iter()
+            .filter_map(|config| {
+                RouteRule::parse(&config.route).ok().map(|route| CompiledRule {
+                    route,
+                    config: config.clone(),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Applies the first matching rule
+    pub fn decide(&self, input: &PolicyInput) -> PolicyDecision {
+        let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| rule.route.matches(input.method.as_deref(), &input.path))
+        else {
+            return PolicyDecision::deny("no authorization rule matches the route");
+        };
+        let config = &rule.config;
+
+        if let Some(missing) = config.scopes.iter().find(|s| !input.scopes.contains(s)) {
+            return PolicyDecision::deny(format!("missing scope {missing}"));
+        }
+        if !config.roles.is_empty() && !config.roles.iter().any(|r| input.roles.contains(r)) {
+            return PolicyDecision::deny("missing required role");
+        }
+        if config.same_tenant {
+            match (&input.tenant, &input.token_tenant) {
+                (Some(tenant), Some(token_tenant)) if tenant == token_tenant => {}
+                (None, _) => return PolicyDecision::deny("request does not name a tenant"),
+                _ => return PolicyDecision::deny("token belongs to another tenant"),
+            }
+        }
+        PolicyDecision::allow()
+    }
+}
+
+#[async_trait]
+impl PolicyEvaluator for RuleEngine {
+    async fn evaluate(&self, input: &PolicyInput) -> Result<PolicyDecision, AuthEdgeError> {
+        Ok(self.decide(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authz::tests::claims;
+
+    #[test]
+    fn test_route_matching() {
+        let rule = RouteRule::parse("GET /v1/orders/*").unwrap();
+        assert!(rule.matches(Some("GET"), "/v1/orders/42"));
+        assert!(rule.matches(Some("GET"), "/v1/orders/42?expand=items"));
+        assert!(!rule.matches(Some("POST"), "/v1/orders/42"));
+        assert!(!rule.matches(None, "/v1/orders/42"));
+        assert!(!rule.matches(Some("GET"), "/v1/orders"));
+        assert!(!rule.matches(Some("GET"), "/v1/orders/42/items"));
+
+        let rule = RouteRule::parse("/admin/**").unwrap();
+        assert!(rule.matches(None, "/admin"));
+        assert!(rule.matches(Some("DELETE"), "/admin/users/7"));
+        assert!(!rule.matches(Some("GET"), "/v1/admin"));
+
+        assert!(RouteRule::parse("orders").is_err());
+        assert!(RouteRule::parse("/v1/**/orders").is_err());
+    }
+
+    #[test]
+    fn test_rule_engine_decisions() {
+        let engine = RuleEngine::from_config(
+            &serde_json::from_str::<Vec<AuthzRuleConfig>>(
+                r#"[
+                    {"route":"GET /v1/tenants/*/orders/**","scopes":["orders:read"],"same_tenant":true},
+                    {"route":"/admin/**","roles":["admin","operator"]}
+                ]"#,
+            )
+            .unwrap(),
+        );
+        let tenant = ("tenant_id", serde_json::json!("acme"));
+        let reader = claims(&["orders:read"], std::slice::from_ref(&tenant));
+        let input = |route: &str, tenant: &str, claims: &_| {
+            PolicyInput::new(route, tenant, "10.0.0.1", claims, "tenant_id")
+        };
+
+        let allowed = engine.decide(&input("GET /v1/tenants/acme/orders/1", "acme", &reader));
+        assert!(allowed.allowed);
+
+        let other_tenant = engine.decide(&input("GET /v1/tenants/beta/orders", "beta", &reader));
+        assert_eq!(other_tenant, PolicyDecision::deny("token belongs to another tenant"));
+
+        let no_scope = engine.decide(&input(
+            "GET /v1/tenants/acme/orders",
+            "acme",
+            &claims(&[], &[tenant]),
+        ));
+        assert_eq!(no_scope, PolicyDecision::deny("missing scope orders:read"));
+
+        let operator = claims(&[], &[("roles", serde_json::json!(["operator"]))]);
+        assert!(engine.decide(&input("POST /admin/users", "", &operator)).allowed);
+        assert!(!engine.decide(&input("POST /admin/users", "", &reader)).allowed);
+
+        let unmatched = engine.decide(&input("GET /v1/health", "", &reader));
+        assert!(!unmatched.allowed);
+    }
+}
@@ -0,0 +1,279 @@
+//! CAEP Event Subscriber
+//!
+//! Receives CAEP Security Event Tokens pushed by the transmitter and enforces
+//! `session-revoked` and `credential-change` events at the edge: matching
+//! validated-token cache entries are dropped and the subject (or session) is
+//! denied for every token issued at or before the event, until the denial
+//! expires. Tokens issued after the event, i.e. after re-authentication,
+//! are accepted again.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use auth_caep::receiver::{DefaultCaepReceiver, DynEventCallback, ProcessResult};
+use auth_caep::{
+    CaepError, CaepEvent, CaepEventType, CaepReceiver, CaepResult, SubjectIdentifier,
+};
+use parking_lot::Mutex;
+use tracing::info;
+
+use crate::config::Config;
+use crate::jwt::{Claims, JwtValidator};
+
+/// Subject or session targeted by a CAEP event
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DenyKey {
+    /// Every session of a subject (`sub`, regardless of issuer)
+    Subject(String),
+    /// A single session (`session_id` claim)
+    Session(String),
+}
+
+impl DenyKey {
+    /// Maps a CAEP subject identifier onto the token claim it denies
+    ///
+    /// Email subjects cannot be matched against token claims.
+    pub fn from_subject(subject: &SubjectIdentifier) -> Option<Self> {
+        match subject {
+            SubjectIdentifier::IssSub { sub, .. } => Some(Self::Subject(sub.clone())),
+            SubjectIdentifier::Opaque { id } => Some(Self::Subject(id.clone())),
+            SubjectIdentifier::SessionId { session_id } => {
+                Some(Self::Session(session_id.clone()))
+            }
+            SubjectIdentifier::Email { .. } => None,
+        }
+    }
+
+    /// Whether the token belongs to this subject or session
+    pub fn matches(&self, claims: &Claims) -> bool {
+        match self {
+            Self::Subject(sub) => claims.sub == *sub,
+            Self::Session(session_id) => claims.session_id.as_deref() == Some(session_id),
+        }
+    }
+}
+
+struct Denial {
+    issued_before: i64,
+    expires_at: Instant,
+}
+
+/// Subjects and sessions whose earlier tokens are denied
+pub struct SubjectDenylist {
+    entries: Mutex<HashMap<DenyKey, Denial>>,
+    ttl: Duration,
+}
+
+impl SubjectDenylist {
+    /// Creates a denylist whose entries expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Denies tokens matching `key` issued at or before `at` (Unix seconds)
+    pub fn deny(&self, key: DenyKey, at: i64) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+        entries.retain(|_, denial| denial.expires_at > now);
+
+        let denial = entries.entry(key).or_insert(Denial {
+            issued_before: at,
+            expires_at: now + self.ttl,
+        });
+        denial.issued_before = denial.issued_before.max(at);
+        denial.expires_at = now + self.ttl;
+    }
+
+    /// Records the denial a CAEP event calls for and returns its target
+    pub fn apply(&self, event: &CaepEvent) -> Option<DenyKey> {
+        let key = DenyKey::from_subject(&event.subject)?;
+        self.deny(key.clone(), event.event_timestamp.timestamp());
+        Some(key)
+    }
+
+    /// Whether the token was issued before a denial of its subject or session
+    pub fn is_denied(&self, claims: &Claims) -> bool {
+        let now = Instant::now();
+        let entries = self.entries.lock();
+        let denied = |key: DenyKey| {
+            entries
+                .get(&key)
+                .is_some_and(|denial| denial.expires_at > now && claims.iat <= denial.issued_before)
+        };
+
+        denied(DenyKey::Subject(claims.sub.clone()))
+            || claims
+                .session_id
+                .as_ref()
+                .is_some_and(|session_id| denied(DenyKey::Session(session_id.clone())))
+    }
+
+    /// Number of recorded denials, including expired ones not yet purged
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether no denials are recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Applies revocation events to the denylist and the validated-token cache
+struct RevocationCallback {
+    denylist: Arc<SubjectDenylist>,
+    validator: Arc<JwtValidator>,
+}
+
+impl DynEventCallback for RevocationCallback {
+    fn on_event_dyn(
+        &self,
+        event: &CaepEvent,
+    ) -> Pin<Box<dyn Future<Output = CaepResult<()>> + Send + '_>> {
+        let result = match self.denylist.apply(event) {
+            Some(key) => {
+                let issued_before = event.event_timestamp.timestamp();
+                let removed = self.validator.invalidate_cached_matching(|claims| {
+                    key.matches(claims) && claims.iat <= issued_before
+                });
+                info!(
+                    event_type = event.event_type.name(),
+                    removed,
+                    "Applied CAEP revocation event"
+                );
+                Ok(())
+            }
+            None => Err(CaepError::processing(
+                "email subjects cannot be matched against token claims",
+            )),
+        };
+        Box::pin(std::future::ready(result))
+    }
+}
+
+/// Verifies pushed SETs and enforces revocation events
+pub struct CaepSubscriber {
+    receiver: DefaultCaepReceiver,
+}
+
+impl CaepSubscriber {
+    /// Registers revocation handling on a receiver
+    pub fn new(
+        mut receiver: DefaultCaepReceiver,
+        denylist: Arc<SubjectDenylist>,
+        validator: Arc<JwtValidator>,
+    ) -> Self {
+        for event_type in [CaepEventType::SessionRevoked, CaepEventType::CredentialChange] {
+            receiver.register_handler(
+                event_type,
+                Box::new(RevocationCallback {
+                    denylist: denylist.clone(),
+                    validator: validator.clone(),
+                }),
+            );
+        }
+        Self { receiver }
+    }
+
+    /// Builds the subscriber from configuration, or `None` when disabled
+    pub fn from_config(
+        config: &Config,
+        denylist: Arc<SubjectDenylist>,
+        validator: Arc<JwtValidator>,
+    ) -> Option<Self> {
+        let jwks_url = config.caep_jwks_url.as_ref()?;
+        let receiver = DefaultCaepReceiver::new(
+            jwks_url.as_str(),
+            config.caep_issuer.as_str(),
+            config.caep_audience.as_str(),
+        );
+        Some(Self::new(receiver, denylist, validator))
+    }
+
+    /// Verifies a SET and applies its event
+    pub async fn process(&self, set_jwt: &str) -> CaepResult<ProcessResult> {
+        self.receiver.process_set(set_jwt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(sub: &str, session_id: Option<&str>, iat: i64) -> Claims {
+        Claims {
+            iss: "https://idp.example".to_string(),
+            sub: sub.to_string(),
+            aud: vec![],
+            exp: iat + 600,
+            iat,
+            nbf: None,
+            jti: format!("{sub}-{iat}"),
+            session_id: session_id.map(str::to_string),
+            scopes: None,
+            custom: HashMap::new(),
+        }
+    }
+
+    fn event(event_type: CaepEventType, subject: SubjectIdentifier, at: i64) -> CaepEvent {
+        let mut event = CaepEvent::new(event_type, subject);
+        event.event_timestamp = chrono::DateTime::from_timestamp(at, 0).unwrap();
+        event
+    }
+
+    #[test]
+    fn test_credential_change_denies_earlier_tokens() {
+        let denylist = SubjectDenylist::new(Duration::from_secs(60));
+        let key = denylist
+            .apply(&event(
+                CaepEventType::CredentialChange,
+                SubjectIdentifier::iss_sub("https://idp.example", "user-1"),
+                1_000,
+            ))
+            .unwrap();
+
+        assert_eq!(key, DenyKey::Subject("user-1".to_string()));
+        assert!(denylist.is_denied(&claims("user-1", None, 900)));
+        assert!(denylist.is_denied(&claims("user-1", None, 1_000)));
+        assert!(!denylist.is_denied(&claims("user-1", None, 1_001)));
+        assert!(!denylist.is_denied(&claims("user-2", None, 900)));
+    }
+
+    #[test]
+    fn test_session_revoked_denies_only_that_session() {
+        let denylist = SubjectDenylist::new(Duration::from_secs(60));
+        denylist
+            .apply(&event(
+                CaepEventType::SessionRevoked,
+                SubjectIdentifier::SessionId {
+                    session_id: "s-1".to_string(),
+                },
+                1_000,
+            ))
+            .unwrap();
+
+        assert!(denylist.is_denied(&claims("user-1", Some("s-1"), 900)));
+        assert!(!denylist.is_denied(&claims("user-1", Some("s-2"), 900)));
+        assert!(!denylist.is_denied(&claims("user-1", None, 900)));
+    }
+
+    #[test]
+    fn test_expired_denial_and_unsupported_subject() {
+        let denylist = SubjectDenylist::new(Duration::ZERO);
+        denylist.deny(DenyKey::Subject("user-1".to_string()), 1_000);
+        assert!(!denylist.is_denied(&claims("user-1", None, 900)));
+
+        let email = event(
+            CaepEventType::CredentialChange,
+            SubjectIdentifier::email("user@example.com"),
+            1_000,
+        );
+        assert!(denylist.apply(&email).is_none());
+    }
+}
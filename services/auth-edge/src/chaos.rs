@@ -0,0 +1,250 @@
+//! Chaos Injection
+//!
+//! Game days exercise how auth-edge behaves when its dependencies slow down
+//! or fail. With `CHAOS_ENABLED=true`, admins can set a fault on the JWKS
+//! fetch, crypto-service calls or cache-service lookups through the admin
+//! API: every call to the component first waits the fault's latency, then
+//! fails with its error rate. Each fault is removed by a timer after its
+//! duration, at most `CHAOS_MAX_DURATION`, so a forgotten game day cannot
+//! outlive its window. Without the flag no fault can be set and each
+//! injection point only checks that no injector is installed.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Injector installed at startup when `CHAOS_ENABLED` is set
+static INJECTOR: OnceLock<ChaosInjector> = OnceLock::new();
+
+/// Internal component a fault can be injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChaosTarget {
+    /// JWKS endpoint fetches
+    Jwks,
+    /// Crypto-service encrypt and decrypt calls
+    Crypto,
+    /// Cache-service lookups of JWKs and revoked JTIs
+    Cache,
+}
+
+impl ChaosTarget {
+    /// Name used by the admin API
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Jwks => "jwks",
+            Self::Crypto => "crypto",
+            Self::Cache => "cache",
+        }
+    }
+}
+
+impl fmt::Display for ChaosTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ChaosTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jwks" => Ok(Self::Jwks),
+            "crypto" => Ok(Self::Crypto),
+            "cache" => Ok(Self::Cache),
+            other => Err(format!("unknown chaos target {other:?} (jwks, crypto or cache)")),
+        }
+    }
+}
+
+/// Latency and failures injected into one component
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaosFault {
+    /// Delay added to every call
+    pub latency: Duration,
+    /// Share of calls failed, from 0.0 to 1.0
+    pub error_rate: f64,
+    /// When the fault is removed
+    pub expires_at: Instant,
+}
+
+/// Failure injected into a component call
+#[derive(Debug, Clone, Copy, Error)]
+#[error("Injected {0} failure")]
+pub struct InjectedFailure(pub ChaosTarget);
+
+/// Active faults, set through the admin API
+pub struct ChaosInjector {
+    faults: Arc<Mutex<HashMap<ChaosTarget, ChaosFault>>>,
+    max_duration: Duration,
+}
+
+impl ChaosInjector {
+    /// Creates an injector whose faults last at most `max_duration`
+    pub fn new(max_duration: Duration) -> Self {
+        Self {
+            faults: Arc::default(),
+            max_duration,
+        }
+    }
+
+    /// Sets the fault of `target`, replacing any active one
+    ///
+    /// The duration is capped at `CHAOS_MAX_DURATION`; a zero duration
+    /// clears the fault instead. Must be called within a Tokio runtime.
+    pub fn set(
+        &self,
+        target: ChaosTarget,
+        latency: Duration,
+        error_rate: f64,
+        duration: Duration,
+    ) -> Result<Option<ChaosFault>, String> {
+        if !(0.0..=1.0).contains(&error_rate) {
+            return Err(format!("error_rate must be between 0.0 and 1.0, got {error_rate}"));
+        }
+        if duration.is_zero() {
+            self.faults.lock().remove(&target);
+            return Ok(None);
+        }
+
+        let duration = duration.min(self.max_duration);
+        let fault = ChaosFault {
+            latency,
+            error_rate,
+            expires_at: Instant::now() + duration,
+        };
+        self.faults.lock().insert(target, fault);
+
+        let faults = Arc::downgrade(&self.faults);
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            let Some(faults) = faults.upgrade() else {
+                return;
+            };
+            let mut faults = faults.lock();
+            // A replaced fault has its own timer
+            if faults.get(&target).is_some_and(|current| current.expires_at == fault.expires_at) {
+                faults.remove(&target);
+                info!(target = %target, "Chaos fault expired");
+            }
+        });
+        Ok(Some(fault))
+    }
+
+    /// Active faults by target
+    pub fn active(&self) -> Vec<(ChaosTarget, ChaosFault)> {
+        let now = Instant::now();
+        let mut faults = self.faults.lock();
+        faults.retain(|_, fault| fault.expires_at > now);
+        faults.iter().map(|(target, fault)| (*target, *fault)).collect()
+    }
+
+    /// Applies the active fault of `target` to one call
+    pub async fn inject(&self, target: ChaosTarget) -> Result<(), InjectedFailure> {
+        let fault = self.faults.lock().get(&target).copied();
+        let Some(fault) = fault.filter(|fault| fault.expires_at > Instant::now()) else {
+            return Ok(());
+        };
+        if !fault.latency.is_zero() {
+            tokio::time::sleep(fault.latency).await;
+        }
+        if fault.error_rate > 0.0 && rand::random::<f64>() < fault.error_rate {
+            return Err(InjectedFailure(target));
+        }
+        Ok(())
+    }
+}
+
+/// Installs the process-wide injector when `CHAOS_ENABLED` is set
+pub fn install(config: &Config) -> Option<&'static ChaosInjector> {
+    if !config.chaos_enabled {
+        return None;
+    }
+    warn!(
+        max_duration_secs = config.chaos_max_duration_secs,
+        "Chaos injection enabled; admins can slow down or fail internal components"
+    );
+    Some(INJECTOR.get_or_init(|| {
+        ChaosInjector::new(Duration::from_secs(config.chaos_max_duration_secs))
+    }))
+}
+
+/// Process-wide injector, if installed
+pub fn injector() -> Option<&'static ChaosInjector> {
+    INJECTOR.get()
+}
+
+/// Applies the active fault of `target`, if chaos injection is enabled
+pub async fn inject(target: ChaosTarget) -> Result<(), InjectedFailure> {
+    match INJECTOR.get() {
+        Some(injector) => injector.inject(target).await,
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_faults_inject_latency_and_errors() {
+        let chaos = ChaosInjector::new(Duration::from_secs(60));
+        assert!(chaos.inject(ChaosTarget::Jwks).await.is_ok());
+
+        chaos
+            .set(ChaosTarget::Jwks, Duration::ZERO, 1.0, Duration::from_secs(30))
+            .unwrap();
+        assert!(matches!(
+            chaos.inject(ChaosTarget::Jwks).await,
+            Err(InjectedFailure(ChaosTarget::Jwks))
+        ));
+        assert!(chaos.inject(ChaosTarget::Cache).await.is_ok());
+
+        chaos
+            .set(ChaosTarget::Jwks, Duration::from_millis(20), 0.0, Duration::from_secs(30))
+            .unwrap();
+        let started = Instant::now();
+        assert!(chaos.inject(ChaosTarget::Jwks).await.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_faults_expire() {
+        let chaos = ChaosInjector::new(Duration::from_millis(30));
+        let fault = chaos
+            .set(ChaosTarget::Crypto, Duration::ZERO, 1.0, Duration::from_secs(3600))
+            .unwrap()
+            .unwrap();
+        assert!(fault.expires_at <= Instant::now() + Duration::from_millis(30));
+        assert_eq!(chaos.active().len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(chaos.active().is_empty());
+        assert!(chaos.faults.lock().is_empty());
+        assert!(chaos.inject(ChaosTarget::Crypto).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_validates_and_clears() {
+        let chaos = ChaosInjector::new(Duration::from_secs(60));
+        assert!(chaos
+            .set(ChaosTarget::Cache, Duration::ZERO, 1.5, Duration::from_secs(1))
+            .is_err());
+
+        chaos
+            .set(ChaosTarget::Cache, Duration::ZERO, 1.0, Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(chaos.set(ChaosTarget::Cache, Duration::ZERO, 0.0, Duration::ZERO), Ok(None));
+        assert!(chaos.active().is_empty());
+        assert_eq!("cache".parse(), Ok(ChaosTarget::Cache));
+        assert!("database".parse::<ChaosTarget>().is_err());
+    }
+}
@@ -2,8 +2,13 @@
 //!
 //! Provides type-safe configuration with URL validation and environment variable support.
 
+use rust_common::SlidingWindow;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
 use thiserror::Error;
 use url::Url;
 
@@ -33,15 +38,249 @@ pub enum ConfigError {
     /// Environment variable parse error
     #[error("Failed to parse environment variable {name}: {reason}")]
     ParseError { name: String, reason: String },
+
+    /// Settings that are unsafe for the selected deployment profile
+    #[error("Unsafe configuration for {profile} profile: {reason}")]
+    UnsafeProfile {
+        /// Profile whose guarantees would be weakened
+        profile: DeploymentProfile,
+        /// Which setting conflicts with the profile
+        reason: String,
+    },
+}
+
+/// Named deployment profile selecting a bundle of security defaults.
+///
+/// `internal` is for mesh-internal validation where every caller presents a
+/// workload identity; `public-edge` is for internet-facing traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeploymentProfile {
+    /// Mesh-internal validator behind mTLS
+    #[default]
+    Internal,
+    /// Internet-facing edge
+    PublicEdge,
+}
+
+impl DeploymentProfile {
+    /// Returns the canonical profile name.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Internal => "internal",
+            Self::PublicEdge => "public-edge",
+        }
+    }
+
+    /// Returns the default settings bundle for this profile.
+    #[must_use]
+    pub const fn defaults(&self) -> ProfileDefaults {
+        match self {
+            Self::Internal => ProfileDefaults {
+                rate_limit_base: 10_000,
+                require_mtls: true,
+                error_verbosity: ErrorVerbosity::Detailed,
+                introspection_enabled: true,
+            },
+            Self::PublicEdge => ProfileDefaults {
+                rate_limit_base: 100,
+                require_mtls: false,
+                error_verbosity: ErrorVerbosity::Minimal,
+                introspection_enabled: false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for DeploymentProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DeploymentProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "internal" => Ok(Self::Internal),
+            "public-edge" | "public_edge" | "public" => Ok(Self::PublicEdge),
+            other => Err(format!(
+                "unknown deployment profile '{other}' (expected internal or public-edge)"
+            )),
+        }
+    }
+}
+
+/// How much detail error responses carry back to callers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorVerbosity {
+    /// Sanitized reason included in the message
+    Detailed,
+    /// Error code only
+    Minimal,
+}
+
+impl FromStr for ErrorVerbosity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "detailed" => Ok(Self::Detailed),
+            "minimal" => Ok(Self::Minimal),
+            other => Err(format!(
+                "unknown error verbosity '{other}' (expected detailed or minimal)"
+            )),
+        }
+    }
+}
+
+/// Settings bundle applied by a [`DeploymentProfile`].
+///
+/// Each value can still be overridden individually through the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileDefaults {
+    /// Base requests per rate-limit window per client
+    pub rate_limit_base: u32,
+    /// Whether callers must present a client certificate
+    pub require_mtls: bool,
+    /// Error response verbosity
+    pub error_verbosity: ErrorVerbosity,
+    /// Whether the IntrospectToken RPC is exposed
+    pub introspection_enabled: bool,
+}
+
+/// Claims disclosed to callers matching a SPIFFE ID pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ClaimDisclosureConfig {
+    /// SPIFFE ID, or prefix ending in `/*`
+    pub caller: String,
+    /// Claim names returned to the caller; `*` returns all claims
+    pub claims: Vec<String>,
+}
+
+/// Resource server allowed to call `IntrospectToken`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IntrospectionClientConfig {
+    /// Resource server name
+    pub name: String,
+    /// SPIFFE ID, or prefix ending in `/*`, of the resource server workload
+    #[serde(default)]
+    pub spiffe_id: Option<String>,
+    /// API key ID the resource server authenticates with
+    #[serde(default)]
+    pub api_key_id: Option<String>,
+    /// Audience of tokens meant for this resource server; others are reported inactive
+    #[serde(default)]
+    pub audience: Option<String>,
+    /// Scopes returned to this resource server; a trailing `*` matches by prefix
+    pub scopes: Vec<String>,
+}
+
+/// Trusted JWT issuer with its own key set and validation rules.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TrustedIssuerConfig {
+    /// Expected `iss` claim value
+    pub issuer: String,
+    /// JWKS endpoint publishing this issuer's signing keys
+    pub jwks_url: String,
+    /// Signing algorithms accepted from this issuer (asymmetric only)
+    pub algorithms: Vec<jsonwebtoken::Algorithm>,
+    /// Audiences accepted from this issuer; empty accepts any audience
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    /// Public key (JWK) signed JWK Sets from this issuer must verify against
+    #[serde(default)]
+    pub jwks_signing_key: Option<crate::jwt::jwk_cache::Jwk>,
+    /// Reject JWK Sets that are not signed by `jwks_signing_key`
+    #[serde(default)]
+    pub signed_jwks_only: bool,
+}
+
+/// External identity provider whose tokens are accepted for federation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FederatedIdpConfig {
+    /// Short IdP name, e.g. `okta` or `entra`
+    pub name: String,
+    /// Expected `iss` claim value
+    pub issuer: String,
+    /// OpenID Connect discovery document publishing the IdP's `jwks_uri`
+    pub discovery_url: String,
+    /// Audiences accepted from this IdP; empty accepts any audience
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    /// Signing algorithms accepted from this IdP (asymmetric only)
+    #[serde(default = "default_federation_algorithms")]
+    pub algorithms: Vec<jsonwebtoken::Algorithm>,
+    /// External claim name to platform claim name; unmapped claims are dropped
+    #[serde(default)]
+    pub claim_mapping: HashMap<String, String>,
+    /// Re-issue a platform token through the token service
+    #[serde(default)]
+    pub exchange: bool,
+    /// Lifetime of re-issued access tokens; 0 uses the token service default
+    #[serde(default)]
+    pub exchange_ttl_secs: u32,
+}
+
+/// Authorization rule for routes matching a pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AuthzRuleConfig {
+    /// `METHOD /path` or `/path`; `*` matches one segment, a trailing `**` the rest
+    pub route: String,
+    /// Scopes the token must all hold
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Roles of which the token must hold at least one; empty accepts any
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Require the token's tenant to match the requested tenant
+    #[serde(default)]
+    pub same_tenant: bool,
+}
+
+/// Maximum token age for routes matching a pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TokenAgeRuleConfig {
+    /// `METHOD /path` or `/path`, matched like [`AuthzRuleConfig::route`]
+    pub route: String,
+    /// Maximum seconds since `iat`; 0 lifts the limit for the route
+    pub max_age_secs: u64,
+}
+
+fn default_federation_algorithms() -> Vec<jsonwebtoken::Algorithm> {
+    vec![jsonwebtoken::Algorithm::RS256]
+}
+
+/// Every asymmetric algorithm the JWK cache can verify
+fn default_jwt_algorithms() -> Vec<jsonwebtoken::Algorithm> {
+    use jsonwebtoken::Algorithm::*;
+    vec![RS256, RS384, RS512, PS256, PS384, PS512, ES256, ES384, EdDSA]
+}
+
+/// HMAC keys are never published in a JWKS
+fn is_hmac(algorithm: &jsonwebtoken::Algorithm) -> bool {
+    matches!(
+        algorithm,
+        jsonwebtoken::Algorithm::HS256
+            | jsonwebtoken::Algorithm::HS384
+            | jsonwebtoken::Algorithm::HS512
+    )
 }
 
 /// Service configuration with validation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Config {
-    /// Server host address
-    pub host: String,
+    /// Server host: an IP address (IPv6 with or without brackets) or `localhost`
+    pub host: rust_common::ListenHost,
+    /// Also listen on the other address family's wildcard or loopback address
+    pub dual_stack: bool,
     /// Server port (1-65535)
     pub port: u16,
+    /// HTTP/REST gateway port (gateway disabled when unset)
+    pub http_gateway_port: Option<u16>,
+    /// Longest a log level override lasts before the default is restored
+    pub log_level_override_max_secs: u64,
     /// Token service URL
     pub token_service_url: Url,
     /// Session service URL
@@ -50,22 +289,76 @@ pub struct Config {
     pub iam_service_url: Url,
     /// JWKS endpoint URL
     pub jwks_url: Url,
+    /// Signing algorithms accepted for tokens verified against `jwks_url`
+    pub jwt_algorithms: Vec<jsonwebtoken::Algorithm>,
+    /// Issuer whose discovery document replaces `jwks_url` when set
+    ///
+    /// Kept verbatim: the discovery document must name exactly this issuer.
+    pub oidc_issuer: Option<String>,
+    /// Discovery document cache TTL in seconds (must be > 0)
+    pub oidc_discovery_ttl_seconds: u64,
+    /// Trusted issuers keyed by `iss`; when empty every token uses `jwks_url`
+    pub trusted_issuers: Vec<TrustedIssuerConfig>,
+    /// External IdPs accepted by the federation exchange
+    pub federated_idps: Vec<FederatedIdpConfig>,
     /// Cache service URL
     pub cache_service_url: Url,
     /// Logging service URL
     pub logging_service_url: Url,
     /// OTLP endpoint URL
     pub otlp_endpoint: Url,
+    /// Export spans and metrics to `otlp_endpoint`
+    pub otlp_enabled: bool,
+    /// gRPC metadata sent with every OTLP export, e.g. collector credentials
+    pub otlp_headers: Vec<(String, String)>,
+    /// Share of traces sampled (0.0 to 1.0)
+    pub otlp_sampling_ratio: f64,
+    /// Extra resource attributes of exported spans and metrics
+    pub otlp_resource_attributes: Vec<(String, String)>,
+    /// SPIFFE ID of this workload, reported as the `service.spiffe_id`
+    /// resource attribute
+    pub workload_spiffe_id: Option<String>,
     /// JWKS cache TTL in seconds (must be > 0)
     pub jwks_cache_ttl_seconds: u64,
+    /// Minimum seconds between JWKS refetches triggered by unknown `kid`s
+    pub jwks_refetch_interval_seconds: u64,
+    /// Seconds a `kid` absent after a refetch is rejected without refetching
+    pub jwks_negative_cache_ttl_seconds: u64,
+    /// Fewest keys a refetched JWKS may have to replace the cached one
+    pub jwks_min_keys: usize,
+    /// Seconds a `kid` dropped from the JWKS while still in use is kept
+    /// (0 drops it at once)
+    pub jwks_kid_grace_period_seconds: u64,
     /// Circuit breaker failure threshold (must be > 0)
     pub circuit_breaker_failure_threshold: u32,
     /// Circuit breaker timeout in seconds
     pub circuit_breaker_timeout_seconds: u64,
+    /// Failure rate (0.0-1.0) over the sliding window that opens a circuit;
+    /// 0 opens on `circuit_breaker_failure_threshold` consecutive failures
+    pub circuit_breaker_failure_rate: f64,
+    /// Calls in the failure rate window
+    pub circuit_breaker_window_size: u32,
+    /// Seconds in the failure rate window; 0 counts calls instead
+    pub circuit_breaker_window_seconds: u64,
+    /// Calls needed in the window before its failure rate can open a circuit
+    pub circuit_breaker_minimum_calls: u32,
+    /// Probe calls let through while a circuit is half-open (must be > 0)
+    pub circuit_breaker_half_open_max_requests: u32,
     /// Request timeout in seconds
     pub request_timeout_secs: u64,
     /// Allowed SPIFFE domains
     pub allowed_spiffe_domains: Vec<String>,
+    /// SPIFFE ID patterns workloads of their trust domain must match
+    pub spiffe_allowed_ids: Vec<crate::mtls::SpiffeIdPattern>,
+    /// Reject SPIFFE IDs of trust domains without allowed ID patterns
+    pub spiffe_deny_by_default: bool,
+    /// SPIFFE ID patterns of callers allowed to use the admin gRPC API;
+    /// empty leaves the admin API unserved
+    pub admin_spiffe_ids: Vec<crate::mtls::SpiffeIdPattern>,
+    /// Let admins inject latency and errors into internal components
+    pub chaos_enabled: bool,
+    /// Max seconds an injected fault stays active
+    pub chaos_max_duration_secs: u64,
     /// Graceful shutdown timeout in seconds
     pub shutdown_timeout_seconds: u64,
     /// Cache encryption key (32 bytes for AES-256) - deprecated, use crypto_service
@@ -78,35 +371,423 @@ pub struct Config {
     pub crypto_fallback_enabled: bool,
     /// Crypto service timeout in seconds
     pub crypto_timeout_secs: u64,
+    /// Extra key namespace prefixes this service may use
+    pub crypto_allowed_namespaces: Vec<String>,
+    /// SPIFFE ID of this workload for namespace mapping
+    pub crypto_spiffe_id: Option<String>,
+    /// SPIFFE ID patterns mapped to the key namespace prefixes they grant
+    pub crypto_spiffe_namespaces: HashMap<String, Vec<String>>,
+    /// Deployment profile the defaults below were derived from
+    pub deployment_profile: DeploymentProfile,
+    /// Base requests per rate-limit window per client (must be > 0)
+    pub rate_limit_base: u32,
+    /// Extra requests a client may burst above its limit
+    pub rate_limit_burst: u32,
+    /// Seconds of good behavior before a new client reaches the full limit
+    pub rate_limit_warmup_secs: u64,
+    /// Extra windows enforced alongside the base limit (most restrictive wins)
+    pub rate_limit_windows: Vec<crate::rate_limiter::RateWindow>,
+    /// Seconds without requests before a client's rate limit state is
+    /// forgotten (0 keeps it; must cover the longest window)
+    pub rate_limit_client_ttl_secs: u64,
+    /// Most clients whose rate limit state is tracked (0 is unbounded)
+    pub rate_limit_max_clients: usize,
+    /// Consecutive denials or failed requests that ban a client (0 disables bans)
+    pub rate_limit_ban_threshold: u32,
+    /// Seconds of a client's first ban; each further ban doubles it
+    pub rate_limit_ban_secs: u64,
+    /// Seconds of the longest ban
+    pub rate_limit_max_ban_secs: u64,
+    /// Limits keyed by SPIFFE ID, subject, source IP and method, including
+    /// the method quotas of `RATE_LIMIT_METHOD_QUOTAS`
+    pub rate_limit_policy: crate::rate_limiter::RateLimitPolicy,
+    /// Utilization percentages (1-100) at which responses carry an
+    /// `x-quota-warning` before the caller is rate limited
+    pub quota_warning_thresholds: Vec<u8>,
+    /// Milliseconds between system load samples fed to the rate limiter
+    /// (0 disables load reduction)
+    pub load_sample_interval_ms: u64,
+    /// Requests in flight counted as full load (0 ignores in-flight requests)
+    pub load_max_in_flight: usize,
+    /// Tasks queued on the runtime counted as full load (0 ignores the queue)
+    pub load_max_queue_depth: usize,
+    /// Requests handled at once by the service stack (0 is unlimited)
+    pub concurrency_limit: usize,
+    /// Requests waiting for a free slot before new ones are shed
+    pub concurrency_max_queue: usize,
+    /// Seconds shed callers are told to wait before retrying
+    pub load_shed_retry_after_secs: u64,
+    /// Require callers to present a client certificate
+    pub require_mtls: bool,
+    /// PEM certificate chain of the gRPC server; enables in-process TLS
+    pub tls_cert_path: Option<String>,
+    /// PEM private key of the gRPC server certificate
+    pub tls_key_path: Option<String>,
+    /// PEM trust bundle verifying client certificates
+    pub tls_client_ca_path: Option<String>,
+    /// Seconds between checks of the `TLS_*` files for changes (0 disables)
+    pub tls_reload_interval_secs: u64,
+    /// SPIRE agent Workload API socket (`unix:///path`); serves TLS with the
+    /// workload's X.509-SVID instead of `TLS_*` files
+    pub spiffe_endpoint_socket: Option<String>,
+    /// PEM trust bundle enabling full chain validation of caller certificates
+    pub mtls_trust_bundle_path: Option<String>,
+    /// Extended key usages caller certificates must carry (names or OIDs)
+    pub mtls_required_ekus: Vec<String>,
+    /// Caller certificate revocation checking via CRL or stapled OCSP
+    pub cert_revocation_mode: crate::mtls::RevocationMode,
+    /// Seconds revocation results are cached per certificate serial
+    pub cert_revocation_cache_ttl_secs: u64,
+    /// Accept `x-api-key` caller authentication as an alternative to mTLS
+    pub api_key_auth_enabled: bool,
+    /// JSON file of hashed API key records (read from Cache_Service when unset)
+    pub api_keys_path: Option<String>,
+    /// Fraction of validation decisions written to the access log (0 disables)
+    pub access_log_sample_rate: f64,
+    /// Vault KV path of the access log subject-hashing salt
+    pub access_log_salt_path: String,
+    /// Record every validation decision in the audit log
+    pub audit_log_enabled: bool,
+    /// Audit events sent to Logging_Service per batch
+    pub audit_log_batch_size: usize,
+    /// Seconds between flushes of a partial audit batch
+    pub audit_log_flush_interval_secs: u64,
+    /// File undelivered audit batches are appended to, as JSON lines
+    pub audit_log_fallback_path: String,
+    /// Error response verbosity for callers without a workload identity
+    pub error_verbosity: ErrorVerbosity,
+    /// Error response verbosity for mesh-internal callers with a SPIFFE ID
+    pub mesh_error_verbosity: ErrorVerbosity,
+    /// Expose the IntrospectToken RPC
+    pub introspection_enabled: bool,
+    /// Resource servers allowed to introspect; empty admits any authenticated caller
+    pub introspection_clients: Vec<IntrospectionClientConfig>,
+    /// Max tokens per IntrospectBatch request
+    pub introspect_batch_max_tokens: usize,
+    /// Tokens of one IntrospectBatch request introspected concurrently
+    pub introspect_batch_concurrency: usize,
+    /// RFC 7662 endpoint opaque (non-JWS) tokens are introspected at
+    pub upstream_introspection_url: Option<String>,
+    /// Client ID authenticating to the upstream introspection endpoint
+    pub upstream_introspection_client_id: Option<String>,
+    /// Client secret authenticating to the upstream introspection endpoint
+    pub upstream_introspection_client_secret: Option<String>,
+    /// Timeout of upstream introspection requests in milliseconds
+    pub upstream_introspection_timeout_ms: u64,
+    /// Max seconds an upstream introspection result is cached (0 disables)
+    pub upstream_introspection_cache_ttl_secs: u64,
+    /// Max opaque tokens with a cached upstream introspection result
+    pub upstream_introspection_cache_size: usize,
+    /// Max tokens in the validated-token cache (0 disables)
+    pub validation_cache_size: usize,
+    /// Max seconds a validated token is cached (capped by its `exp`)
+    pub validation_cache_max_ttl_secs: u64,
+    /// Share validations among identical concurrent requests
+    pub validation_coalescing_enabled: bool,
+    /// Busiest tenants labelled by name in the tenant metrics
+    pub tenant_metrics_top_n: usize,
+    /// Seconds of traffic the busiest tenants are chosen from
+    pub tenant_metrics_window_secs: u64,
+    /// Check `jti` against the revocation list after signature validation
+    pub revocation_check_enabled: bool,
+    /// Accept tokens when the revocation list is unreachable
+    pub revocation_fail_open: bool,
+    /// Cache-service namespace holding `revoked:{jti}` entries
+    pub revocation_namespace: String,
+    /// Revoked JTIs remembered in memory, least recently seen evicted first
+    pub revocation_cache_size: usize,
+    /// Fetch the token service's filter of revoked JTIs and skip lookups of
+    /// JTIs it does not contain
+    pub revocation_filter_sync: bool,
+    /// Seconds between revoked-JTI filter fetches
+    pub revocation_filter_interval_secs: u64,
+    /// Seconds after its last fetch the filter is no longer trusted
+    pub revocation_filter_max_age_secs: u64,
+    /// Restore claims the token service moved into `claims_ref` entries
+    pub claims_ref_enabled: bool,
+    /// Cache-service namespace holding `claims_ref:{id}` entries
+    pub claims_ref_namespace: String,
+    /// AES key the token service encrypts cache entries with
+    pub claims_ref_encryption_key: Option<[u8; 32]>,
+    /// SPIFFE ID patterns of callers receiving private claims decrypted;
+    /// empty leaves them encrypted for every caller
+    pub private_claim_readers: Vec<crate::mtls::SpiffeIdPattern>,
+    /// SPIFFE ID patterns of proxies allowed to forward an end client's
+    /// certificate in `client_certificate_pem`
+    pub certificate_forwarders: Vec<crate::mtls::SpiffeIdPattern>,
+    /// JWKS of the CAEP transmitter (CAEP receiver disabled when unset)
+    pub caep_jwks_url: Option<String>,
+    /// Expected `iss` of CAEP Security Event Tokens
+    pub caep_issuer: String,
+    /// Expected `aud` of CAEP Security Event Tokens
+    pub caep_audience: String,
+    /// Seconds a revoked subject or session stays denied
+    pub caep_deny_ttl_secs: u64,
+    /// Per-caller claim disclosure policies, first match wins
+    pub claim_disclosure: Vec<ClaimDisclosureConfig>,
+    /// Claims disclosed to callers without a matching policy
+    pub claim_disclosure_default: Vec<String>,
+    /// Named CEL claim expressions, required as `cel:<name>`
+    pub claim_expressions: HashMap<String, String>,
+    /// Expose the admin ExplainToken RPC to API keys scoped to it
+    pub explain_token_enabled: bool,
+    /// Serve gRPC server reflection so tools can discover the API
+    pub grpc_reflection_enabled: bool,
+    /// PEM public key for emergency bypass tokens (bypass disabled when unset)
+    pub emergency_bypass_key_path: Option<String>,
+    /// Signing algorithm of the emergency bypass key
+    pub emergency_bypass_algorithm: jsonwebtoken::Algorithm,
+    /// Maximum bypass token lifetime in seconds (1-3600)
+    pub emergency_bypass_max_lifetime_secs: u64,
+    /// Dotenv-format file layered over the environment and watched for changes
+    pub config_file: Option<String>,
+    /// Seconds between config file change checks (0 = reload on SIGHUP only)
+    pub config_watch_interval_secs: u64,
+    /// Per-route authorization rules, first match wins (disabled when empty)
+    pub authz_rules: Vec<AuthzRuleConfig>,
+    /// Claim holding the token's tenant
+    pub authz_tenant_claim: String,
+    /// OPA decision endpoint, e.g. `http://localhost:8181/v1/data/authz`
+    pub opa_url: Option<String>,
+    /// Timeout of OPA policy queries in milliseconds
+    pub opa_timeout_ms: u64,
+    /// Maximum seconds since a token's `iat`, whatever its `exp` (0 disables)
+    pub max_token_age_secs: u64,
+    /// Per-route maximum token age overrides, first match wins
+    pub max_token_age_routes: Vec<TokenAgeRuleConfig>,
+    /// Key IDs allowed to sign each restricted audience
+    pub audience_key_ids: HashMap<String, Vec<String>>,
+    /// Maximum decoded JWT payload size in bytes
+    pub jwt_max_payload_bytes: usize,
+    /// Maximum number of top-level claims in a JWT payload
+    pub jwt_max_claims: usize,
+    /// Maximum nesting depth of JSON values in a JWT payload
+    pub jwt_max_nesting: usize,
 }
 
 impl Config {
     /// Loads configuration from environment variables with validation.
+    ///
+    /// When `CONFIG_FILE` names a dotenv-format file, its entries override the
+    /// process environment; the file is re-read on every call, which is how
+    /// the reloader picks up changes.
     pub fn from_env() -> Result<Self, ConfigError> {
         dotenvy::dotenv().ok();
+        Self::from_source(&EnvSource::from_config_file()?)
+    }
+
+    /// Loads configuration from a variable source with validation.
+    pub fn from_source(src: &EnvSource) -> Result<Self, ConfigError> {
+        let deployment_profile: DeploymentProfile =
+            parse_env(src, "DEPLOYMENT_PROFILE", DeploymentProfile::default())?;
+        let profile_defaults = deployment_profile.defaults();
 
         let config = Self {
-            host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
-            port: parse_env("PORT", 50052)?,
-            token_service_url: parse_url_env("TOKEN_SERVICE_URL", "http://localhost:50051")?,
-            session_service_url: parse_url_env("SESSION_SERVICE_URL", "http://localhost:50053")?,
-            iam_service_url: parse_url_env("IAM_SERVICE_URL", "http://localhost:50054")?,
-            jwks_url: parse_url_env("JWKS_URL", "http://localhost:50051/.well-known/jwks.json")?,
-            cache_service_url: parse_url_env("CACHE_SERVICE_URL", "http://localhost:50060")?,
-            logging_service_url: parse_url_env("LOGGING_SERVICE_URL", "http://localhost:50061")?,
-            otlp_endpoint: parse_url_env("OTLP_ENDPOINT", "http://localhost:4317")?,
-            jwks_cache_ttl_seconds: parse_env("JWKS_CACHE_TTL", 3600)?,
-            circuit_breaker_failure_threshold: parse_env("CB_FAILURE_THRESHOLD", 5)?,
-            circuit_breaker_timeout_seconds: parse_env("CB_TIMEOUT", 30)?,
-            request_timeout_secs: parse_env("REQUEST_TIMEOUT", 30)?,
-            allowed_spiffe_domains: parse_list_env("ALLOWED_SPIFFE_DOMAINS"),
-            shutdown_timeout_seconds: parse_env("SHUTDOWN_TIMEOUT", 30)?,
-            cache_encryption_key: parse_encryption_key_env("CACHE_ENCRYPTION_KEY"),
-            crypto_service_url: parse_url_env("CRYPTO_SERVICE_URL", "http://localhost:50051")?,
-            crypto_key_namespace: env::var("CRYPTO_KEY_NAMESPACE")
-                .unwrap_or_else(|_| "auth-edge".to_string()),
-            crypto_fallback_enabled: parse_env("CRYPTO_FALLBACK_ENABLED", true)?,
-            crypto_timeout_secs: parse_env("CRYPTO_TIMEOUT", 5)?,
+            host: parse_env(
+                src,
+                "HOST",
+                rust_common::ListenHost::Ip(std::net::Ipv4Addr::UNSPECIFIED.into()),
+            )?,
+            dual_stack: parse_env(src, "DUAL_STACK", false)?,
+            port: parse_env(src, "PORT", 50052)?,
+            http_gateway_port: parse_optional_env(src, "HTTP_GATEWAY_PORT")?,
+            log_level_override_max_secs: parse_env(src, "LOG_LEVEL_OVERRIDE_MAX", 3600)?,
+            token_service_url: parse_url_env(src, "TOKEN_SERVICE_URL", "http://localhost:50051")?,
+            session_service_url: parse_url_env(src, "SESSION_SERVICE_URL", "http://localhost:50053")?,
+            iam_service_url: parse_url_env(src, "IAM_SERVICE_URL", "http://localhost:50054")?,
+            jwks_url: parse_url_env(src, "JWKS_URL", "http://localhost:50051/.well-known/jwks.json")?,
+            jwt_algorithms: parse_algorithms_env(src, "JWT_ALGORITHMS")?,
+            oidc_issuer: src.var("OIDC_ISSUER"),
+            oidc_discovery_ttl_seconds: parse_env(src, "OIDC_DISCOVERY_TTL", 3600)?,
+            trusted_issuers: parse_json_env(src, "TRUSTED_ISSUERS")?,
+            federated_idps: parse_json_env(src, "FEDERATED_IDPS")?,
+            cache_service_url: parse_url_env(src, "CACHE_SERVICE_URL", "http://localhost:50060")?,
+            logging_service_url: parse_url_env(src, "LOGGING_SERVICE_URL", "http://localhost:50061")?,
+            otlp_endpoint: parse_url_env(src, "OTLP_ENDPOINT", "http://localhost:4317")?,
+            otlp_enabled: parse_env(src, "OTLP_ENABLED", false)?,
+            otlp_headers: parse_key_values_env(src, "OTLP_HEADERS")?,
+            otlp_sampling_ratio: parse_env(src, "OTLP_SAMPLING_RATIO", 1.0)?,
+            otlp_resource_attributes: parse_key_values_env(src, "OTLP_RESOURCE_ATTRIBUTES")?,
+            workload_spiffe_id: src.var("SPIFFE_ID"),
+            jwks_cache_ttl_seconds: parse_env(src, "JWKS_CACHE_TTL", 3600)?,
+            jwks_refetch_interval_seconds: parse_env(src, "JWKS_REFETCH_INTERVAL", 10)?,
+            jwks_negative_cache_ttl_seconds: parse_env(src, "JWKS_NEGATIVE_CACHE_TTL", 60)?,
+            jwks_min_keys: parse_env(src, "JWKS_MIN_KEYS", 1)?,
+            jwks_kid_grace_period_seconds: parse_env(src, "JWKS_KID_GRACE_PERIOD", 900)?,
+            circuit_breaker_failure_threshold: parse_env(src, "CB_FAILURE_THRESHOLD", 5)?,
+            circuit_breaker_timeout_seconds: parse_env(src, "CB_TIMEOUT", 30)?,
+            circuit_breaker_failure_rate: parse_env(src, "CB_FAILURE_RATE", 0.0)?,
+            circuit_breaker_window_size: parse_env(src, "CB_WINDOW_SIZE", 100)?,
+            circuit_breaker_window_seconds: parse_env(src, "CB_WINDOW_SECONDS", 0)?,
+            circuit_breaker_minimum_calls: parse_env(src, "CB_MINIMUM_CALLS", 20)?,
+            circuit_breaker_half_open_max_requests: parse_env(src, "CB_HALF_OPEN_MAX_REQUESTS", 3)?,
+            request_timeout_secs: parse_env(src, "REQUEST_TIMEOUT", 30)?,
+            allowed_spiffe_domains: parse_list_env(src, "ALLOWED_SPIFFE_DOMAINS"),
+            spiffe_allowed_ids: parse_list_env(src, "SPIFFE_ALLOWED_IDS")
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    p.parse().map_err(|reason| ConfigError::ParseError {
+                        name: "SPIFFE_ALLOWED_IDS".to_string(),
+                        reason,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            spiffe_deny_by_default: parse_env(src, "SPIFFE_DENY_BY_DEFAULT", false)?,
+            admin_spiffe_ids: parse_list_env(src, "ADMIN_SPIFFE_IDS")
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    p.parse().map_err(|reason| ConfigError::ParseError {
+                        name: "ADMIN_SPIFFE_IDS".to_string(),
+                        reason,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            chaos_enabled: parse_env(src, "CHAOS_ENABLED", false)?,
+            chaos_max_duration_secs: parse_env(src, "CHAOS_MAX_DURATION", 3600)?,
+            shutdown_timeout_seconds: parse_env(src, "SHUTDOWN_TIMEOUT", 30)?,
+            cache_encryption_key: parse_encryption_key_env(src, "CACHE_ENCRYPTION_KEY"),
+            crypto_service_url: parse_url_env(src, "CRYPTO_SERVICE_URL", "http://localhost:50051")?,
+            crypto_key_namespace: src.var("CRYPTO_KEY_NAMESPACE")
+                .unwrap_or_else(|| "auth-edge".to_string()),
+            crypto_fallback_enabled: parse_env(src, "CRYPTO_FALLBACK_ENABLED", true)?,
+            crypto_timeout_secs: parse_env(src, "CRYPTO_TIMEOUT", 5)?,
+            crypto_allowed_namespaces: parse_list_env(src, "CRYPTO_ALLOWED_NAMESPACES"),
+            crypto_spiffe_id: src.var("CRYPTO_SPIFFE_ID"),
+            crypto_spiffe_namespaces: parse_namespace_map_env(src, "CRYPTO_SPIFFE_NAMESPACES"),
+            deployment_profile,
+            rate_limit_base: parse_env(src, "RATE_LIMIT_BASE", profile_defaults.rate_limit_base)?,
+            rate_limit_burst: parse_env(src, "RATE_LIMIT_BURST", 10)?,
+            rate_limit_warmup_secs: parse_env(src, "RATE_LIMIT_WARMUP", 300)?,
+            rate_limit_windows: parse_list_env(src, "RATE_LIMIT_WINDOWS")
+                .iter()
+                .filter(|w| !w.is_empty())
+                .map(|w| {
+                    w.parse().map_err(|reason| ConfigError::ParseError {
+                        name: "RATE_LIMIT_WINDOWS".to_string(),
+                        reason,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            rate_limit_client_ttl_secs: parse_env(src, "RATE_LIMIT_CLIENT_TTL", 3600)?,
+            rate_limit_max_clients: parse_env(src, "RATE_LIMIT_MAX_CLIENTS", 100_000)?,
+            rate_limit_ban_threshold: parse_env(src, "RATE_LIMIT_BAN_THRESHOLD", 0)?,
+            rate_limit_ban_secs: parse_env(src, "RATE_LIMIT_BAN", 60)?,
+            rate_limit_max_ban_secs: parse_env(src, "RATE_LIMIT_MAX_BAN", 3600)?,
+            rate_limit_policy: parse_rate_limit_policy_env(src)?,
+            quota_warning_thresholds: parse_percentages_env(src, "QUOTA_WARNING_THRESHOLDS")?,
+            load_sample_interval_ms: parse_env(src, "LOAD_SAMPLE_INTERVAL_MS", 1000)?,
+            load_max_in_flight: parse_env(src, "LOAD_MAX_IN_FLIGHT", 1000)?,
+            load_max_queue_depth: parse_env(src, "LOAD_MAX_QUEUE_DEPTH", 256)?,
+            concurrency_limit: parse_env(src, "CONCURRENCY_LIMIT", 0)?,
+            concurrency_max_queue: parse_env(src, "CONCURRENCY_MAX_QUEUE", 64)?,
+            load_shed_retry_after_secs: parse_env(src, "LOAD_SHED_RETRY_AFTER", 1)?,
+            require_mtls: parse_env(src, "REQUIRE_MTLS", profile_defaults.require_mtls)?,
+            tls_cert_path: src.var("TLS_CERT_PATH"),
+            tls_key_path: src.var("TLS_KEY_PATH"),
+            tls_client_ca_path: src.var("TLS_CLIENT_CA_PATH"),
+            tls_reload_interval_secs: parse_env(src, "TLS_RELOAD_INTERVAL", 30)?,
+            spiffe_endpoint_socket: src.var("SPIFFE_ENDPOINT_SOCKET"),
+            mtls_trust_bundle_path: src.var("MTLS_TRUST_BUNDLE_PATH"),
+            mtls_required_ekus: parse_list_env(src, "MTLS_REQUIRED_EKUS"),
+            cert_revocation_mode: parse_env(src, "CERT_REVOCATION_MODE", Default::default())?,
+            cert_revocation_cache_ttl_secs: parse_env(src, "CERT_REVOCATION_CACHE_TTL", 300)?,
+            api_key_auth_enabled: parse_env(src, "API_KEY_AUTH_ENABLED", false)?,
+            api_keys_path: src.var("API_KEYS_PATH"),
+            access_log_sample_rate: parse_env(src, "ACCESS_LOG_SAMPLE_RATE", 0.0)?,
+            access_log_salt_path: src.var("ACCESS_LOG_SALT_PATH")
+                .unwrap_or_else(|| "auth-edge/access-log".to_string()),
+            audit_log_enabled: parse_env(src, "AUDIT_LOG_ENABLED", true)?,
+            audit_log_batch_size: parse_env(src, "AUDIT_LOG_BATCH_SIZE", 100)?,
+            audit_log_flush_interval_secs: parse_env(src, "AUDIT_LOG_FLUSH_INTERVAL", 5)?,
+            audit_log_fallback_path: src.var("AUDIT_LOG_FALLBACK_PATH")
+                .unwrap_or_else(|| "/var/log/auth-edge/audit.jsonl".to_string()),
+            error_verbosity: parse_env(src, "ERROR_VERBOSITY", profile_defaults.error_verbosity)?,
+            mesh_error_verbosity: parse_env(src, "MESH_ERROR_VERBOSITY", ErrorVerbosity::Detailed)?,
+            introspection_enabled: parse_env(
+                src,
+                "INTROSPECTION_ENABLED",
+                profile_defaults.introspection_enabled,
+            )?,
+            introspection_clients: parse_json_env(src, "INTROSPECTION_CLIENTS")?,
+            introspect_batch_max_tokens: parse_env(src, "INTROSPECT_BATCH_MAX_TOKENS", 100)?,
+            introspect_batch_concurrency: parse_env(src, "INTROSPECT_BATCH_CONCURRENCY", 16)?,
+            upstream_introspection_url: src.var("UPSTREAM_INTROSPECTION_URL"),
+            upstream_introspection_client_id: src.var("UPSTREAM_INTROSPECTION_CLIENT_ID"),
+            upstream_introspection_client_secret: src.var("UPSTREAM_INTROSPECTION_CLIENT_SECRET"),
+            upstream_introspection_timeout_ms: parse_env(src, "UPSTREAM_INTROSPECTION_TIMEOUT_MS", 1000)?,
+            upstream_introspection_cache_ttl_secs: parse_env(src, "UPSTREAM_INTROSPECTION_CACHE_TTL", 60)?,
+            upstream_introspection_cache_size: parse_env(src, "UPSTREAM_INTROSPECTION_CACHE_SIZE", 10_000)?,
+            validation_cache_size: parse_env(src, "VALIDATION_CACHE_SIZE", 10_000)?,
+            validation_cache_max_ttl_secs: parse_env(src, "VALIDATION_CACHE_MAX_TTL", 300)?,
+            validation_coalescing_enabled: parse_env(src, "VALIDATION_COALESCING_ENABLED", true)?,
+            tenant_metrics_top_n: parse_env(src, "TENANT_METRICS_TOP_N", 20)?,
+            tenant_metrics_window_secs: parse_env(src, "TENANT_METRICS_WINDOW", 300)?,
+            revocation_check_enabled: parse_env(src, "REVOCATION_CHECK_ENABLED", true)?,
+            revocation_fail_open: parse_env(src, "REVOCATION_FAIL_OPEN", false)?,
+            revocation_namespace: src.var("REVOCATION_NAMESPACE")
+                .unwrap_or_else(|| "token".to_string()),
+            revocation_cache_size: parse_env(src, "REVOCATION_CACHE_SIZE", 100_000)?,
+            revocation_filter_sync: parse_env(src, "REVOCATION_FILTER_SYNC", false)?,
+            revocation_filter_interval_secs: parse_env(src, "REVOCATION_FILTER_INTERVAL", 30)?,
+            revocation_filter_max_age_secs: parse_env(src, "REVOCATION_FILTER_MAX_AGE", 120)?,
+            claims_ref_enabled: parse_env(src, "CLAIMS_REF_ENABLED", false)?,
+            claims_ref_namespace: src.var("CLAIMS_REF_NAMESPACE")
+                .unwrap_or_else(|| "token".to_string()),
+            claims_ref_encryption_key: parse_encryption_key_env(src, "CLAIMS_REF_ENCRYPTION_KEY"),
+            private_claim_readers: parse_list_env(src, "PRIVATE_CLAIM_READERS")
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    p.parse().map_err(|reason| ConfigError::ParseError {
+                        name: "PRIVATE_CLAIM_READERS".to_string(),
+                        reason,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            certificate_forwarders: parse_list_env(src, "CERTIFICATE_FORWARDERS")
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    p.parse().map_err(|reason| ConfigError::ParseError {
+                        name: "CERTIFICATE_FORWARDERS".to_string(),
+                        reason,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            caep_jwks_url: src.var("CAEP_JWKS_URL"),
+            caep_issuer: src.var("CAEP_ISSUER").unwrap_or_default(),
+            caep_audience: src.var("CAEP_AUDIENCE").unwrap_or_else(|| "auth-edge".to_string()),
+            caep_deny_ttl_secs: parse_env(src, "CAEP_DENY_TTL", 3600)?,
+            claim_disclosure: parse_json_env(src, "CLAIM_DISCLOSURE_POLICIES")?,
+            claim_disclosure_default: match parse_list_env(src, "CLAIM_DISCLOSURE_DEFAULT") {
+                claims if claims.is_empty() => vec!["*".to_string()],
+                claims => claims,
+            },
+            claim_expressions: parse_json_env(src, "CLAIM_EXPRESSIONS")?,
+            explain_token_enabled: parse_env(src, "EXPLAIN_TOKEN_ENABLED", false)?,
+            grpc_reflection_enabled: parse_env(src, "GRPC_REFLECTION_ENABLED", false)?,
+            emergency_bypass_key_path: src.var("EMERGENCY_BYPASS_KEY_PATH"),
+            emergency_bypass_algorithm: parse_env(
+                src,
+                "EMERGENCY_BYPASS_ALGORITHM",
+                jsonwebtoken::Algorithm::ES256,
+            )?,
+            emergency_bypass_max_lifetime_secs: parse_env(src, "EMERGENCY_BYPASS_MAX_LIFETIME", 900)?,
+            config_file: src.file().map(str::to_string),
+            config_watch_interval_secs: parse_env(src, "CONFIG_WATCH_INTERVAL", 5)?,
+            authz_rules: parse_json_env(src, "AUTHZ_RULES")?,
+            authz_tenant_claim: src.var("AUTHZ_TENANT_CLAIM")
+                .unwrap_or_else(|| "tenant_id".to_string()),
+            opa_url: src.var("OPA_URL"),
+            opa_timeout_ms: parse_env(src, "OPA_TIMEOUT_MS", 250)?,
+            max_token_age_secs: parse_env(src, "MAX_TOKEN_AGE", 0)?,
+            max_token_age_routes: parse_json_env(src, "MAX_TOKEN_AGE_ROUTES")?,
+            audience_key_ids: parse_json_env(src, "AUDIENCE_KEY_IDS")?,
+            jwt_max_payload_bytes: parse_env(src, "JWT_MAX_PAYLOAD_BYTES", 16 * 1024)?,
+            jwt_max_claims: parse_env(src, "JWT_MAX_CLAIMS", 128)?,
+            jwt_max_nesting: parse_env(src, "JWT_MAX_NESTING", 16)?,
         };
 
         config.validate()?;
@@ -118,12 +799,123 @@ impl Config {
         if self.port == 0 {
             return Err(ConfigError::InvalidPort);
         }
+        if matches!(self.http_gateway_port, Some(port) if port == 0 || port == self.port) {
+            return Err(ConfigError::InvalidPort);
+        }
+        self.listen_addrs(self.port)?;
         if self.jwks_cache_ttl_seconds == 0 {
             return Err(ConfigError::InvalidTtl);
         }
         if self.circuit_breaker_failure_threshold == 0 {
             return Err(ConfigError::InvalidThreshold);
         }
+        if self.circuit_breaker_half_open_max_requests == 0 {
+            return Err(ConfigError::ParseError {
+                name: "CB_HALF_OPEN_MAX_REQUESTS".to_string(),
+                reason: "at least one probe must be allowed".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.circuit_breaker_failure_rate) {
+            return Err(ConfigError::ParseError {
+                name: "CB_FAILURE_RATE".to_string(),
+                reason: "failure rate must be between 0.0 and 1.0".to_string(),
+            });
+        }
+        if self.circuit_breaker_failure_rate > 0.0
+            && self.circuit_breaker_window_seconds == 0
+            && self.circuit_breaker_window_size == 0
+        {
+            return Err(ConfigError::ParseError {
+                name: "CB_WINDOW_SIZE".to_string(),
+                reason: "window size must be greater than 0".to_string(),
+            });
+        }
+        if self.jwt_algorithms.is_empty() || self.jwt_algorithms.iter().any(is_hmac) {
+            return Err(ConfigError::ParseError {
+                name: "JWT_ALGORITHMS".to_string(),
+                reason: "at least one algorithm is required and HMAC cannot be verified through JWKS"
+                    .to_string(),
+            });
+        }
+        if let Some(issuer) = &self.oidc_issuer {
+            Url::parse(issuer).map_err(|e| ConfigError::InvalidUrl {
+                field: "OIDC_ISSUER".to_string(),
+                reason: e.to_string(),
+            })?;
+            if self.oidc_discovery_ttl_seconds == 0 {
+                return Err(ConfigError::ParseError {
+                    name: "OIDC_DISCOVERY_TTL".to_string(),
+                    reason: "TTL must be greater than 0".to_string(),
+                });
+            }
+        }
+        self.validate_trusted_issuers()?;
+        self.validate_federated_idps()?;
+        self.validate_claim_disclosure()?;
+        self.validate_introspection_clients()?;
+        self.validate_upstream_introspection()?;
+        self.validate_server_tls()?;
+        self.validate_spiffe_policy()?;
+        self.validate_admin_api()?;
+        self.validate_chaos()?;
+        if !self.private_claim_readers.is_empty()
+            && self.tls_client_ca_path.is_none()
+            && self.spiffe_endpoint_socket.is_none()
+        {
+            return Err(ConfigError::ParseError {
+                name: "PRIVATE_CLAIM_READERS".to_string(),
+                reason: "requires client certificates (TLS_CLIENT_CA_PATH or SPIFFE_ENDPOINT_SOCKET)"
+                    .to_string(),
+            });
+        }
+        if !self.certificate_forwarders.is_empty()
+            && self.tls_client_ca_path.is_none()
+            && self.spiffe_endpoint_socket.is_none()
+        {
+            return Err(ConfigError::ParseError {
+                name: "CERTIFICATE_FORWARDERS".to_string(),
+                reason: "requires client certificates (TLS_CLIENT_CA_PATH or SPIFFE_ENDPOINT_SOCKET)"
+                    .to_string(),
+            });
+        }
+        self.validate_chain_validation()?;
+        crate::jwt::ClaimExpressions::compile(&self.claim_expressions).map_err(|reason| {
+            ConfigError::ParseError {
+                name: "CLAIM_EXPRESSIONS".to_string(),
+                reason,
+            }
+        })?;
+        self.validate_authz()?;
+        if let Some(audience) = self
+            .audience_key_ids
+            .iter()
+            .find_map(|(audience, kids)| kids.is_empty().then_some(audience))
+        {
+            return Err(ConfigError::ParseError {
+                name: "AUDIENCE_KEY_IDS".to_string(),
+                reason: format!("audience {audience} lists no key IDs"),
+            });
+        }
+        for (name, limit) in [
+            ("JWT_MAX_PAYLOAD_BYTES", self.jwt_max_payload_bytes),
+            ("JWT_MAX_CLAIMS", self.jwt_max_claims),
+            ("JWT_MAX_NESTING", self.jwt_max_nesting),
+        ] {
+            if limit == 0 {
+                return Err(ConfigError::ParseError {
+                    name: name.to_string(),
+                    reason: "limit must be greater than 0".to_string(),
+                });
+            }
+        }
+        for rule in &self.max_token_age_routes {
+            crate::authz::RouteRule::parse(&rule.route).map_err(|reason| {
+                ConfigError::ParseError {
+                    name: "MAX_TOKEN_AGE_ROUTES".to_string(),
+                    reason: format!("route {}: {reason}", rule.route),
+                }
+            })?;
+        }
         if self.crypto_key_namespace.is_empty() {
             return Err(ConfigError::MissingRequired(
                 "crypto_key_namespace".to_string(),
@@ -135,9 +927,638 @@ impl Config {
                 reason: "timeout must be greater than 0".to_string(),
             });
         }
+        if let Some(spiffe_id) = &self.crypto_spiffe_id {
+            crate::mtls::OwnedSpiffeId::parse(spiffe_id).map_err(|e| ConfigError::ParseError {
+                name: "CRYPTO_SPIFFE_ID".to_string(),
+                reason: e.to_string(),
+            })?;
+        }
+        if let Some(spiffe_id) = &self.workload_spiffe_id {
+            crate::mtls::OwnedSpiffeId::parse(spiffe_id).map_err(|e| ConfigError::ParseError {
+                name: "SPIFFE_ID".to_string(),
+                reason: e.to_string(),
+            })?;
+        }
+        if !(0.0..=1.0).contains(&self.otlp_sampling_ratio) {
+            return Err(ConfigError::ParseError {
+                name: "OTLP_SAMPLING_RATIO".to_string(),
+                reason: "sampling ratio must be between 0.0 and 1.0".to_string(),
+            });
+        }
+        if self.rate_limit_base == 0 {
+            return Err(ConfigError::InvalidThreshold);
+        }
+        // Forgetting a client resets its counters, so none may outlive a window
+        let limits = self.rate_limit_config();
+        let longest_window = limits
+            .extra_windows
+            .iter()
+            .chain(self.rate_limit_policy.rules.iter().map(|rule| &rule.limit))
+            .map(|w| w.window)
+            .fold(limits.window, Duration::max);
+        if self.rate_limit_client_ttl_secs > 0
+            && Duration::from_secs(self.rate_limit_client_ttl_secs) < longest_window
+        {
+            return Err(ConfigError::ParseError {
+                name: "RATE_LIMIT_CLIENT_TTL".to_string(),
+                reason: format!(
+                    "TTL must be at least the longest window ({}s)",
+                    longest_window.as_secs()
+                ),
+            });
+        }
+        if self.rate_limit_ban_threshold > 0
+            && (self.rate_limit_ban_secs == 0 || self.rate_limit_max_ban_secs < self.rate_limit_ban_secs)
+        {
+            return Err(ConfigError::ParseError {
+                name: "RATE_LIMIT_BAN".to_string(),
+                reason: "ban must be greater than 0 and at most RATE_LIMIT_MAX_BAN".to_string(),
+            });
+        }
+        self.rate_limit_policy
+            .validate()
+            .map_err(|reason| ConfigError::ParseError {
+                name: "RATE_LIMIT_POLICY".to_string(),
+                reason,
+            })?;
+        if self.revocation_check_enabled && self.revocation_cache_size == 0 {
+            return Err(ConfigError::ParseError {
+                name: "REVOCATION_CACHE_SIZE".to_string(),
+                reason: "size must be greater than 0".to_string(),
+            });
+        }
+        if self.revocation_filter_sync && self.revocation_filter_interval_secs == 0 {
+            return Err(ConfigError::ParseError {
+                name: "REVOCATION_FILTER_INTERVAL".to_string(),
+                reason: "interval must be greater than 0".to_string(),
+            });
+        }
+        if self.revocation_filter_sync
+            && self.revocation_filter_max_age_secs < self.revocation_filter_interval_secs
+        {
+            return Err(ConfigError::ParseError {
+                name: "REVOCATION_FILTER_MAX_AGE".to_string(),
+                reason: "must be at least REVOCATION_FILTER_INTERVAL".to_string(),
+            });
+        }
+        self.validate_caep()?;
+        if !(0.0..=1.0).contains(&self.access_log_sample_rate) {
+            return Err(ConfigError::ParseError {
+                name: "ACCESS_LOG_SAMPLE_RATE".to_string(),
+                reason: "sample rate must be between 0.0 and 1.0".to_string(),
+            });
+        }
+        if self.tenant_metrics_window_secs == 0 {
+            return Err(ConfigError::ParseError {
+                name: "TENANT_METRICS_WINDOW".to_string(),
+                reason: "window must be greater than 0".to_string(),
+            });
+        }
+        if self.introspect_batch_max_tokens == 0 {
+            return Err(ConfigError::ParseError {
+                name: "INTROSPECT_BATCH_MAX_TOKENS".to_string(),
+                reason: "batch size must be greater than 0".to_string(),
+            });
+        }
+        if self.introspect_batch_concurrency == 0 {
+            return Err(ConfigError::ParseError {
+                name: "INTROSPECT_BATCH_CONCURRENCY".to_string(),
+                reason: "concurrency must be greater than 0".to_string(),
+            });
+        }
+        if self.audit_log_enabled && self.audit_log_batch_size == 0 {
+            return Err(ConfigError::ParseError {
+                name: "AUDIT_LOG_BATCH_SIZE".to_string(),
+                reason: "batch size must be greater than 0".to_string(),
+            });
+        }
+        if self.audit_log_enabled && self.audit_log_flush_interval_secs == 0 {
+            return Err(ConfigError::ParseError {
+                name: "AUDIT_LOG_FLUSH_INTERVAL".to_string(),
+                reason: "flush interval must be greater than 0".to_string(),
+            });
+        }
+        if self.emergency_bypass_max_lifetime_secs == 0
+            || self.emergency_bypass_max_lifetime_secs
+                > crate::jwt::bypass::MAX_BYPASS_LIFETIME_SECS
+        {
+            return Err(ConfigError::ParseError {
+                name: "EMERGENCY_BYPASS_MAX_LIFETIME".to_string(),
+                reason: format!(
+                    "lifetime must be between 1 and {} seconds",
+                    crate::jwt::bypass::MAX_BYPASS_LIFETIME_SECS
+                ),
+            });
+        }
+        self.validate_profile()
+    }
+
+    /// Validates the CAEP receiver settings.
+    fn validate_caep(&self) -> Result<(), ConfigError> {
+        if self.caep_jwks_url.is_none() {
+            return Ok(());
+        }
+        if self.caep_issuer.is_empty() {
+            return Err(ConfigError::MissingRequired("caep_issuer".to_string()));
+        }
+        if self.http_gateway_port.is_none() {
+            return Err(ConfigError::MissingRequired("http_gateway_port".to_string()));
+        }
+        if self.caep_deny_ttl_secs == 0 {
+            return Err(ConfigError::ParseError {
+                name: "CAEP_DENY_TTL".to_string(),
+                reason: "TTL must be greater than 0".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validates the trusted issuer registry.
+    fn validate_trusted_issuers(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::ParseError {
+            name: "TRUSTED_ISSUERS".to_string(),
+            reason,
+        };
+        let mut seen = std::collections::HashSet::new();
+
+        for trusted in &self.trusted_issuers {
+            if trusted.issuer.is_empty() {
+                return Err(invalid("issuer must not be empty".to_string()));
+            }
+            if !seen.insert(trusted.issuer.as_str()) {
+                return Err(invalid(format!("duplicate issuer {}", trusted.issuer)));
+            }
+            Url::parse(&trusted.jwks_url).map_err(|e| ConfigError::InvalidUrl {
+                field: format!("TRUSTED_ISSUERS[{}].jwks_url", trusted.issuer),
+                reason: e.to_string(),
+            })?;
+            if trusted.algorithms.is_empty() {
+                return Err(invalid(format!("no algorithms for issuer {}", trusted.issuer)));
+            }
+            if trusted.algorithms.iter().any(is_hmac) {
+                return Err(invalid(format!(
+                    "HMAC algorithms cannot be verified through JWKS (issuer {})",
+                    trusted.issuer
+                )));
+            }
+            if trusted.signed_jwks_only && trusted.jwks_signing_key.is_none() {
+                return Err(invalid(format!(
+                    "signed_jwks_only requires a jwks_signing_key (issuer {})",
+                    trusted.issuer
+                )));
+            }
+            crate::jwt::JwksTrustAnchor::from_config(trusted).map_err(|e| {
+                invalid(format!("{e} (issuer {})", trusted.issuer))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Validates the federated IdPs.
+    fn validate_federated_idps(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::ParseError {
+            name: "FEDERATED_IDPS".to_string(),
+            reason,
+        };
+        let mut names = std::collections::HashSet::new();
+        let mut issuers = std::collections::HashSet::new();
+
+        for idp in &self.federated_idps {
+            if idp.name.is_empty() || idp.issuer.is_empty() {
+                return Err(invalid("name and issuer must not be empty".to_string()));
+            }
+            if !names.insert(idp.name.as_str()) {
+                return Err(invalid(format!("duplicate IdP {}", idp.name)));
+            }
+            if !issuers.insert(idp.issuer.as_str())
+                || self.trusted_issuers.iter().any(|t| t.issuer == idp.issuer)
+            {
+                return Err(invalid(format!("issuer of IdP {} is already trusted", idp.name)));
+            }
+            Url::parse(&idp.discovery_url).map_err(|e| ConfigError::InvalidUrl {
+                field: format!("FEDERATED_IDPS[{}].discovery_url", idp.name),
+                reason: e.to_string(),
+            })?;
+            if idp.algorithms.is_empty() || idp.algorithms.iter().any(is_hmac) {
+                return Err(invalid(format!(
+                    "IdP {} needs at least one asymmetric algorithm",
+                    idp.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates claim disclosure caller patterns and claim lists.
+    fn validate_claim_disclosure(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::ParseError {
+            name: "CLAIM_DISCLOSURE_POLICIES".to_string(),
+            reason,
+        };
+
+        for policy in &self.claim_disclosure {
+            let id = policy.caller.strip_suffix("/*").unwrap_or(&policy.caller);
+            crate::mtls::OwnedSpiffeId::parse(id)
+                .map_err(|e| invalid(format!("caller {}: {e}", policy.caller)))?;
+            if policy.claims.is_empty() {
+                return Err(invalid(format!("no claims for caller {}", policy.caller)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates introspection client identities and scope lists.
+    fn validate_introspection_clients(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::ParseError {
+            name: "INTROSPECTION_CLIENTS".to_string(),
+            reason,
+        };
+
+        for client in &self.introspection_clients {
+            if client.spiffe_id.is_none() && client.api_key_id.is_none() {
+                return Err(invalid(format!(
+                    "client {} needs a spiffe_id or api_key_id",
+                    client.name
+                )));
+            }
+            if let Some(pattern) = &client.spiffe_id {
+                let id = pattern.strip_suffix("/*").unwrap_or(pattern);
+                crate::mtls::OwnedSpiffeId::parse(id)
+                    .map_err(|e| invalid(format!("client {}: {e}", client.name)))?;
+            }
+            if client.scopes.is_empty() {
+                return Err(invalid(format!("no scopes for client {}", client.name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the upstream introspection endpoint and its credentials.
+    fn validate_upstream_introspection(&self) -> Result<(), ConfigError> {
+        let Some(url) = &self.upstream_introspection_url else {
+            return Ok(());
+        };
+        Url::parse(url).map_err(|e| ConfigError::InvalidUrl {
+            field: "UPSTREAM_INTROSPECTION_URL".to_string(),
+            reason: e.to_string(),
+        })?;
+        if self.upstream_introspection_client_id.is_some()
+            != self.upstream_introspection_client_secret.is_some()
+        {
+            return Err(ConfigError::ParseError {
+                name: "UPSTREAM_INTROSPECTION_CLIENT_SECRET".to_string(),
+                reason: "client ID and secret must be set together".to_string(),
+            });
+        }
+        if self.upstream_introspection_timeout_ms == 0 {
+            return Err(ConfigError::ParseError {
+                name: "UPSTREAM_INTROSPECTION_TIMEOUT_MS".to_string(),
+                reason: "timeout must be greater than 0".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validates the in-process TLS settings of the gRPC server.
+    fn validate_server_tls(&self) -> Result<(), ConfigError> {
+        let invalid = |name: &str, reason: &str| ConfigError::ParseError {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        };
+
+        match (&self.tls_cert_path, &self.tls_key_path) {
+            (Some(_), None) => return Err(invalid("TLS_KEY_PATH", "required with TLS_CERT_PATH")),
+            (None, Some(_)) => return Err(invalid("TLS_CERT_PATH", "required with TLS_KEY_PATH")),
+            (None, None) if self.tls_client_ca_path.is_some() => {
+                return Err(invalid("TLS_CLIENT_CA_PATH", "requires TLS_CERT_PATH and TLS_KEY_PATH"));
+            }
+            (Some(_), Some(_)) if self.require_mtls && self.tls_client_ca_path.is_none() => {
+                return Err(invalid(
+                    "TLS_CLIENT_CA_PATH",
+                    "required to verify client certificates when REQUIRE_MTLS is set",
+                ));
+            }
+            _ => {}
+        }
+
+        if let Some(socket) = &self.spiffe_endpoint_socket {
+            if self.tls_cert_path.is_some() {
+                return Err(invalid(
+                    "SPIFFE_ENDPOINT_SOCKET",
+                    "mutually exclusive with TLS_CERT_PATH",
+                ));
+            }
+            if !socket.starts_with("unix:") {
+                return Err(invalid("SPIFFE_ENDPOINT_SOCKET", "must be a unix: socket address"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the caller certificate chain validation settings.
+    fn validate_chain_validation(&self) -> Result<(), ConfigError> {
+        let invalid = |name: &str, reason: String| ConfigError::ParseError {
+            name: name.to_string(),
+            reason,
+        };
+
+        if let Some(eku) = self.mtls_required_ekus.iter().find(|eku| !crate::mtls::verifier::is_valid_eku(eku)) {
+            return Err(invalid("MTLS_REQUIRED_EKUS", format!("{eku:?} is not a key usage name or OID")));
+        }
+        if self.mtls_trust_bundle_path.is_none() {
+            if !self.mtls_required_ekus.is_empty() {
+                return Err(invalid("MTLS_REQUIRED_EKUS", "requires MTLS_TRUST_BUNDLE_PATH".to_string()));
+            }
+            if self.cert_revocation_mode != crate::mtls::RevocationMode::Off {
+                return Err(invalid("CERT_REVOCATION_MODE", "requires MTLS_TRUST_BUNDLE_PATH".to_string()));
+            }
+        }
+        if self.cert_revocation_cache_ttl_secs == 0 {
+            return Err(invalid("CERT_REVOCATION_CACHE_TTL", "must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Validates that SPIFFE ID patterns name allowed trust domains.
+    fn validate_spiffe_policy(&self) -> Result<(), ConfigError> {
+        match self
+            .spiffe_allowed_ids
+            .iter()
+            .find(|p| !self.allowed_spiffe_domains.iter().any(|d| d == p.trust_domain()))
+        {
+            Some(pattern) => Err(ConfigError::ParseError {
+                name: "SPIFFE_ALLOWED_IDS".to_string(),
+                reason: format!("{pattern} is outside ALLOWED_SPIFFE_DOMAINS"),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates that admin callers can present a SPIFFE ID this service accepts.
+    fn validate_admin_api(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::ParseError {
+            name: "ADMIN_SPIFFE_IDS".to_string(),
+            reason,
+        };
+
+        if self.admin_spiffe_ids.is_empty() {
+            return Ok(());
+        }
+        if self.tls_client_ca_path.is_none() && self.spiffe_endpoint_socket.is_none() {
+            return Err(invalid(
+                "requires client certificates (TLS_CLIENT_CA_PATH or SPIFFE_ENDPOINT_SOCKET)"
+                    .to_string(),
+            ));
+        }
+        match self
+            .admin_spiffe_ids
+            .iter()
+            .find(|p| !self.allowed_spiffe_domains.iter().any(|d| d == p.trust_domain()))
+        {
+            Some(pattern) => Err(invalid(format!("{pattern} is outside ALLOWED_SPIFFE_DOMAINS"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates that injected faults can be set and expire.
+    fn validate_chaos(&self) -> Result<(), ConfigError> {
+        if !self.chaos_enabled {
+            return Ok(());
+        }
+        if self.admin_spiffe_ids.is_empty() {
+            return Err(ConfigError::ParseError {
+                name: "CHAOS_ENABLED".to_string(),
+                reason: "requires ADMIN_SPIFFE_IDS to set faults".to_string(),
+            });
+        }
+        if self.chaos_max_duration_secs == 0 {
+            return Err(ConfigError::ParseError {
+                name: "CHAOS_MAX_DURATION".to_string(),
+                reason: "must be positive".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validates the authorization rules and OPA settings.
+    fn validate_authz(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::ParseError {
+            name: "AUTHZ_RULES".to_string(),
+            reason,
+        };
+
+        if let Some(opa_url) = &self.opa_url {
+            if !self.authz_rules.is_empty() {
+                return Err(invalid("AUTHZ_RULES and OPA_URL are mutually exclusive".to_string()));
+            }
+            Url::parse(opa_url).map_err(|e| ConfigError::InvalidUrl {
+                field: "OPA_URL".to_string(),
+                reason: e.to_string(),
+            })?;
+            if self.opa_timeout_ms == 0 {
+                return Err(ConfigError::ParseError {
+                    name: "OPA_TIMEOUT_MS".to_string(),
+                    reason: "timeout must be greater than 0".to_string(),
+                });
+            }
+        }
+        for rule in &self.authz_rules {
+            crate::authz::RouteRule::parse(&rule.route)
+                .map_err(|reason| invalid(format!("route {}: {reason}", rule.route)))?;
+        }
+        if self.authz_tenant_claim.is_empty() {
+            return Err(ConfigError::MissingRequired("authz_tenant_claim".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Rejects overrides that weaken the selected deployment profile.
+    fn validate_profile(&self) -> Result<(), ConfigError> {
+        let unsafe_profile = |reason: &str| ConfigError::UnsafeProfile {
+            profile: self.deployment_profile,
+            reason: reason.to_string(),
+        };
+
+        match self.deployment_profile {
+            DeploymentProfile::Internal => {
+                if !self.require_mtls {
+                    return Err(unsafe_profile(
+                        "internal mode trusts workload identity and requires mTLS",
+                    ));
+                }
+            }
+            DeploymentProfile::PublicEdge => {
+                if self.error_verbosity == ErrorVerbosity::Detailed {
+                    return Err(unsafe_profile(
+                        "detailed error responses must not be exposed to public clients",
+                    ));
+                }
+                if self.introspection_enabled {
+                    return Err(unsafe_profile(
+                        "token introspection must not be exposed on the public edge",
+                    ));
+                }
+                if self.explain_token_enabled {
+                    return Err(unsafe_profile(
+                        "token explanation must not be exposed on the public edge",
+                    ));
+                }
+                if self.grpc_reflection_enabled {
+                    return Err(unsafe_profile(
+                        "server reflection must not be exposed on the public edge",
+                    ));
+                }
+                if self.revocation_check_enabled && self.revocation_fail_open {
+                    return Err(unsafe_profile(
+                        "revocation checks must fail closed on the public edge",
+                    ));
+                }
+                if self.chaos_enabled {
+                    return Err(unsafe_profile(
+                        "chaos injection is for staging, not the public edge",
+                    ));
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Configuration values reported by ExplainToken.
+    ///
+    /// URLs are reduced to their origin; no paths, credentials or key material.
+    #[must_use]
+    pub fn explain_summary(&self) -> HashMap<String, String> {
+        let mut summary = HashMap::new();
+        summary.insert("deployment_profile".to_string(), self.deployment_profile.to_string());
+        summary.insert("introspection_enabled".to_string(), self.introspection_enabled.to_string());
+        summary.insert("jwks_origin".to_string(), self.jwks_url.origin().ascii_serialization());
+        summary.insert(
+            "jwks_cache_ttl_seconds".to_string(),
+            self.jwks_cache_ttl_seconds.to_string(),
+        );
+        summary.insert(
+            "trusted_issuers".to_string(),
+            self.trusted_issuers
+                .iter()
+                .map(|trusted| trusted.issuer.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        summary.insert(
+            "emergency_bypass_enabled".to_string(),
+            self.emergency_bypass_key_path.is_some().to_string(),
+        );
+        summary.insert("now".to_string(), chrono::Utc::now().timestamp().to_string());
+        summary
+    }
+
+    /// Whether `other` changes settings that only take effect on restart.
+    ///
+    /// Rate limits, circuit breaker thresholds, the JWKS URL, cache TTL and
+    /// refetch limits and the watch interval are applied by a reload;
+    /// everything else is not.
+    pub fn requires_restart(&self, other: &Config) -> bool {
+        let mut other = other.clone();
+        other.rate_limit_base = self.rate_limit_base;
+        other.rate_limit_burst = self.rate_limit_burst;
+        other.rate_limit_warmup_secs = self.rate_limit_warmup_secs;
+        other.rate_limit_windows = self.rate_limit_windows.clone();
+        other.rate_limit_client_ttl_secs = self.rate_limit_client_ttl_secs;
+        other.rate_limit_max_clients = self.rate_limit_max_clients;
+        other.rate_limit_ban_threshold = self.rate_limit_ban_threshold;
+        other.rate_limit_ban_secs = self.rate_limit_ban_secs;
+        other.rate_limit_max_ban_secs = self.rate_limit_max_ban_secs;
+        other.rate_limit_policy = self.rate_limit_policy.clone();
+        other.circuit_breaker_failure_threshold = self.circuit_breaker_failure_threshold;
+        other.circuit_breaker_timeout_seconds = self.circuit_breaker_timeout_seconds;
+        other.circuit_breaker_failure_rate = self.circuit_breaker_failure_rate;
+        other.circuit_breaker_window_size = self.circuit_breaker_window_size;
+        other.circuit_breaker_window_seconds = self.circuit_breaker_window_seconds;
+        other.circuit_breaker_minimum_calls = self.circuit_breaker_minimum_calls;
+        other.circuit_breaker_half_open_max_requests = self.circuit_breaker_half_open_max_requests;
+        other.jwks_url = self.jwks_url.clone();
+        other.jwks_cache_ttl_seconds = self.jwks_cache_ttl_seconds;
+        other.jwks_refetch_interval_seconds = self.jwks_refetch_interval_seconds;
+        other.jwks_negative_cache_ttl_seconds = self.jwks_negative_cache_ttl_seconds;
+        other.config_watch_interval_secs = self.config_watch_interval_secs;
+        other != *self
+    }
+
+    /// Creates the circuit breaker configuration for downstream services.
+    #[must_use]
+    pub fn circuit_breaker_config(&self) -> rust_common::CircuitBreakerConfig {
+        let config = rust_common::CircuitBreakerConfig::default()
+            .with_failure_threshold(self.circuit_breaker_failure_threshold)
+            .with_timeout(Duration::from_secs(self.circuit_breaker_timeout_seconds))
+            .with_half_open_max_requests(self.circuit_breaker_half_open_max_requests);
+        if self.circuit_breaker_failure_rate == 0.0 {
+            return config;
+        }
+        let window = match self.circuit_breaker_window_seconds {
+            0 => SlidingWindow::Count(self.circuit_breaker_window_size),
+            secs => SlidingWindow::Time(Duration::from_secs(secs)),
+        };
+        config.with_failure_rate(
+            self.circuit_breaker_failure_rate,
+            window,
+            self.circuit_breaker_minimum_calls,
+        )
+    }
+
+    /// Creates the rate limiter configuration for this deployment.
+    #[must_use]
+    pub fn rate_limit_config(&self) -> crate::rate_limiter::RateLimitConfig {
+        crate::rate_limiter::RateLimitConfig {
+            base_limit: self.rate_limit_base,
+            burst_capacity: self.rate_limit_burst,
+            warmup_period: Duration::from_secs(self.rate_limit_warmup_secs),
+            extra_windows: self.rate_limit_windows.clone(),
+            client_idle_ttl: Duration::from_secs(self.rate_limit_client_ttl_secs),
+            max_clients: self.rate_limit_max_clients,
+            ban_threshold: self.rate_limit_ban_threshold,
+            ban_duration: Duration::from_secs(self.rate_limit_ban_secs),
+            max_ban_duration: Duration::from_secs(self.rate_limit_max_ban_secs),
+            ..Default::default()
+        }
+    }
+
+    /// Socket addresses to listen on for `port`, one per address family when
+    /// dual-stack.
+    pub fn listen_addrs(&self, port: u16) -> Result<Vec<std::net::SocketAddr>, ConfigError> {
+        self.host
+            .socket_addrs(port, self.dual_stack)
+            .map_err(|reason| ConfigError::ParseError {
+                name: "DUAL_STACK".to_string(),
+                reason,
+            })
+    }
+
+    /// Creates the SPIFFE workload allowlist policy.
+    #[must_use]
+    pub fn spiffe_policy(&self) -> crate::mtls::SpiffePolicy {
+        crate::mtls::SpiffePolicy::new(self.spiffe_allowed_ids.clone(), self.spiffe_deny_by_default)
+    }
+
+    /// Copy of the configuration without secret key material, for display.
+    #[must_use]
+    pub fn redacted(&self) -> Config {
+        Config {
+            cache_encryption_key: None,
+            claims_ref_encryption_key: None,
+            upstream_introspection_client_secret: self
+                .upstream_introspection_client_secret
+                .as_ref()
+                .map(|_| "<redacted>".to_string()),
+            private_claim_readers: vec![],
+            otlp_headers: self
+                .otlp_headers
+                .iter()
+                .map(|(name, _)| (name.clone(), "<redacted>".to_string()))
+                .collect(),
+            ..self.clone()
+        }
+    }
+
     /// Gets the crypto service URL as a string.
     #[must_use]
     pub fn crypto_service_url_str(&self) -> &str {
@@ -152,6 +1573,11 @@ impl Config {
             .with_key_namespace(&self.crypto_key_namespace)
             .with_fallback_enabled(self.crypto_fallback_enabled)
             .with_timeout(std::time::Duration::from_secs(self.crypto_timeout_secs))
+            .with_allowed_namespace_prefixes(self.crypto_allowed_namespaces.clone())
+            .with_spiffe_namespaces(
+                self.crypto_spiffe_id.clone(),
+                self.crypto_spiffe_namespaces.clone(),
+            )
     }
 
     /// Gets the cache service URL as a string.
@@ -179,39 +1605,253 @@ impl Config {
     }
 }
 
-/// Parse an environment variable with a default value.
-fn parse_env<T: std::str::FromStr>(name: &str, default: T) -> Result<T, ConfigError>
-where
-    T::Err: std::fmt::Display,
-{
-    match env::var(name) {
-        Ok(val) => val.parse().map_err(|e: T::Err| ConfigError::ParseError {
-            name: name.to_string(),
-            reason: e.to_string(),
-        }),
-        Err(_) => Ok(default),
-    }
+/// Configuration variables: an optional config file layered over the process
+/// environment.
+#[derive(Debug, Clone, Default)]
+pub struct EnvSource {
+    file: Option<String>,
+    overrides: HashMap<String, String>,
 }
 
-/// Parse a URL environment variable with a default value.
-fn parse_url_env(name: &str, default: &str) -> Result<Url, ConfigError> {
-    let url_str = env::var(name).unwrap_or_else(|_| default.to_string());
-    Url::parse(&url_str).map_err(|e| ConfigError::InvalidUrl {
-        field: name.to_string(),
-        reason: e.to_string(),
-    })
-}
+impl EnvSource {
+    /// Reads the process environment only.
+    pub fn process() -> Self {
+        Self::default()
+    }
 
-/// Parse a comma-separated list environment variable.
-fn parse_list_env(name: &str) -> Vec<String> {
-    env::var(name)
+    /// Layers the dotenv-format file named by `CONFIG_FILE`, if any, over the
+    /// process environment.
+    pub fn from_config_file() -> Result<Self, ConfigError> {
+        match env::var("CONFIG_FILE") {
+            Ok(path) if !path.trim().is_empty() => Self::with_file(path),
+            _ => Ok(Self::process()),
+        }
+    }
+
+    /// Layers a dotenv-format file over the process environment.
+    pub fn with_file(path: impl Into<String>) -> Result<Self, ConfigError> {
+        let path = path.into();
+        let parse_error = |reason: String| ConfigError::ParseError {
+            name: "CONFIG_FILE".to_string(),
+            reason,
+        };
+        let overrides = dotenvy::from_path_iter(&path)
+            .map_err(|e| parse_error(format!("{path}: {e}")))?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| parse_error(format!("{path}: {e}")))?;
+        Ok(Self {
+            file: Some(path),
+            overrides,
+        })
+    }
+
+    /// Layers explicit values over the process environment.
+    pub fn with_overrides(overrides: HashMap<String, String>) -> Self {
+        Self {
+            file: None,
+            overrides,
+        }
+    }
+
+    /// Path of the layered config file.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Looks up a variable, preferring the config file.
+    pub fn var(&self, name: &str) -> Option<String> {
+        self.overrides
+            .get(name)
+            .cloned()
+            .or_else(|| env::var(name).ok())
+    }
+}
+
+/// Parse an environment variable with a default value.
+fn parse_env<T: std::str::FromStr>(src: &EnvSource, name: &str, default: T) -> Result<T, ConfigError>
+where
+    T::Err: std::fmt::Display,
+{
+    match src.var(name) {
+        Some(val) => val.parse().map_err(|e: T::Err| ConfigError::ParseError {
+            name: name.to_string(),
+            reason: e.to_string(),
+        }),
+        None => Ok(default),
+    }
+}
+
+/// Parse a comma-separated algorithm list; unset yields every supported one.
+fn parse_algorithms_env(
+    src: &EnvSource,
+    name: &str,
+) -> Result<Vec<jsonwebtoken::Algorithm>, ConfigError> {
+    if src.var(name).is_none() {
+        return Ok(default_jwt_algorithms());
+    }
+    parse_list_env(src, name)
+        .iter()
+        .filter(|alg| !alg.is_empty())
+        .map(|alg| {
+            alg.parse().map_err(|e: jsonwebtoken::errors::Error| ConfigError::ParseError {
+                name: name.to_string(),
+                reason: format!("{alg}: {e}"),
+            })
+        })
+        .collect()
+}
+
+/// Parse an optional environment variable; unset yields `None`.
+fn parse_optional_env<T: std::str::FromStr>(
+    src: &EnvSource,
+    name: &str,
+) -> Result<Option<T>, ConfigError>
+where
+    T::Err: std::fmt::Display,
+{
+    src.var(name)
+        .map(|val| {
+            val.parse().map_err(|e: T::Err| ConfigError::ParseError {
+                name: name.to_string(),
+                reason: e.to_string(),
+            })
+        })
+        .transpose()
+}
+
+/// Parse a URL environment variable with a default value.
+fn parse_url_env(src: &EnvSource, name: &str, default: &str) -> Result<Url, ConfigError> {
+    let url_str = src.var(name).unwrap_or_else(|| default.to_string());
+    Url::parse(&url_str).map_err(|e| ConfigError::InvalidUrl {
+        field: name.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Parse a comma-separated list environment variable.
+fn parse_list_env(src: &EnvSource, name: &str) -> Vec<String> {
+    src.var(name)
         .map(|v| v.split(',').map(|s| s.trim().to_string()).collect())
         .unwrap_or_default()
 }
 
+/// Parse a `key=value,...` list, e.g. `OTLP_HEADERS`.
+fn parse_key_values_env(src: &EnvSource, name: &str) -> Result<Vec<(String, String)>, ConfigError> {
+    src.var(name)
+        .map(|list| {
+            rust_common::tracing_config::parse_key_values(&list).map_err(|e| {
+                ConfigError::ParseError {
+                    name: name.to_string(),
+                    reason: e.to_string(),
+                }
+            })
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+/// Parse a comma-separated list of percentages from 1 to 100, e.g. `80,90%`.
+fn parse_percentages_env(src: &EnvSource, name: &str) -> Result<Vec<u8>, ConfigError> {
+    parse_list_env(src, name)
+        .iter()
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.trim_end_matches('%').parse::<u8>() {
+            Ok(percent) if (1..=100).contains(&percent) => Ok(percent),
+            _ => Err(ConfigError::ParseError {
+                name: name.to_string(),
+                reason: format!("{p:?} is not a percentage from 1 to 100"),
+            }),
+        })
+        .collect()
+}
+
+/// Parse `RATE_LIMIT_POLICY` with the `RATE_LIMIT_METHOD_QUOTAS` appended as
+/// rules.
+fn parse_rate_limit_policy_env(
+    src: &EnvSource,
+) -> Result<crate::rate_limiter::RateLimitPolicy, ConfigError> {
+    let mut policy: crate::rate_limiter::RateLimitPolicy =
+        parse_json_env(src, "RATE_LIMIT_POLICY")?;
+    policy
+        .rules
+        .extend(parse_method_quotas_env(src, "RATE_LIMIT_METHOD_QUOTAS")?);
+    Ok(policy)
+}
+
+/// Parse a `Method=limit/duration,...` quota list, e.g.
+/// `IntrospectToken=500/1m,ValidateToken=100/1m`, into rules keyed by caller
+/// and method, so each caller has its own budget per method. Methods may also
+/// be given as full gRPC paths.
+fn parse_method_quotas_env(
+    src: &EnvSource,
+    name: &str,
+) -> Result<Vec<crate::rate_limiter::RateLimitRule>, ConfigError> {
+    let invalid = |reason: String| ConfigError::ParseError {
+        name: name.to_string(),
+        reason,
+    };
+    parse_list_env(src, name)
+        .iter()
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (method, quota) = entry
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected method=limit/duration, got {entry:?}")))?;
+            let quota: crate::rate_limiter::RateWindow = quota.parse().map_err(invalid)?;
+            let method = method.trim().rsplit('/').next().unwrap_or_default();
+            if method.is_empty() || quota.limit == 0 {
+                return Err(invalid(format!("{entry:?} needs a method and a limit above 0")));
+            }
+            Ok(crate::rate_limiter::RateLimitRule {
+                methods: vec![method.to_string()],
+                dimensions: vec![
+                    crate::rate_limiter::KeyDimension::Caller,
+                    crate::rate_limiter::KeyDimension::Method,
+                ],
+                limit: quota,
+            })
+        })
+        .collect()
+}
+
+/// Parse a `pattern=ns1|ns2,pattern2=ns3` mapping environment variable.
+fn parse_namespace_map_env(src: &EnvSource, name: &str) -> HashMap<String, Vec<String>> {
+    src.var(name)
+        .map(|v| {
+            v.split(',')
+                .filter_map(|entry| entry.split_once('='))
+                .map(|(pattern, namespaces)| {
+                    let namespaces = namespaces
+                        .split('|')
+                        .map(|ns| ns.trim().to_string())
+                        .filter(|ns| !ns.is_empty())
+                        .collect();
+                    (pattern.trim().to_string(), namespaces)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse a JSON environment variable, defaulting when unset.
+fn parse_json_env<T: serde::de::DeserializeOwned + Default>(
+    src: &EnvSource,
+    name: &str,
+) -> Result<T, ConfigError> {
+    match src.var(name) {
+        Some(val) if !val.trim().is_empty() => {
+            serde_json::from_str(&val).map_err(|e| ConfigError::ParseError {
+                name: name.to_string(),
+                reason: e.to_string(),
+            })
+        }
+        _ => Ok(T::default()),
+    }
+}
+
 /// Parse an encryption key from hex-encoded environment variable.
-fn parse_encryption_key_env(name: &str) -> Option<[u8; 32]> {
-    env::var(name).ok().and_then(|hex| {
+fn parse_encryption_key_env(src: &EnvSource, name: &str) -> Option<[u8; 32]> {
+    src.var(name).and_then(|hex| {
         let bytes: Vec<u8> = (0..hex.len())
             .step_by(2)
             .filter_map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
@@ -229,29 +1869,161 @@ fn parse_encryption_key_env(name: &str) -> Option<[u8; 32]> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_common::TripPolicy;
 
     fn test_config_base() -> Config {
         Config {
-            host: "localhost".to_string(),
+            host: rust_common::ListenHost::Localhost,
+            dual_stack: false,
             port: 8080,
+            http_gateway_port: None,
+            log_level_override_max_secs: 3600,
             token_service_url: Url::parse("http://localhost:50051").unwrap(),
             session_service_url: Url::parse("http://localhost:50053").unwrap(),
             iam_service_url: Url::parse("http://localhost:50054").unwrap(),
             jwks_url: Url::parse("http://localhost:50051/.well-known/jwks.json").unwrap(),
+            jwt_algorithms: default_jwt_algorithms(),
+            oidc_issuer: None,
+            oidc_discovery_ttl_seconds: 3600,
+            trusted_issuers: vec![],
+            federated_idps: vec![],
             cache_service_url: Url::parse("http://localhost:50060").unwrap(),
             logging_service_url: Url::parse("http://localhost:50061").unwrap(),
             otlp_endpoint: Url::parse("http://localhost:4317").unwrap(),
+            otlp_enabled: false,
+            otlp_headers: vec![],
+            otlp_sampling_ratio: 1.0,
+            otlp_resource_attributes: vec![],
+            workload_spiffe_id: None,
             jwks_cache_ttl_seconds: 3600,
+            jwks_refetch_interval_seconds: 10,
+            jwks_negative_cache_ttl_seconds: 60,
+            jwks_min_keys: 1,
+            jwks_kid_grace_period_seconds: 900,
             circuit_breaker_failure_threshold: 5,
             circuit_breaker_timeout_seconds: 30,
+            circuit_breaker_failure_rate: 0.0,
+            circuit_breaker_window_size: 100,
+            circuit_breaker_window_seconds: 0,
+            circuit_breaker_minimum_calls: 20,
+            circuit_breaker_half_open_max_requests: 3,
             request_timeout_secs: 30,
             allowed_spiffe_domains: vec![],
+            spiffe_allowed_ids: vec![],
+            spiffe_deny_by_default: false,
+            admin_spiffe_ids: vec![],
+            chaos_enabled: false,
+            chaos_max_duration_secs: 3600,
             shutdown_timeout_seconds: 30,
             cache_encryption_key: None,
             crypto_service_url: Url::parse("http://localhost:50051").unwrap(),
             crypto_key_namespace: "auth-edge".to_string(),
             crypto_fallback_enabled: true,
             crypto_timeout_secs: 5,
+            crypto_allowed_namespaces: vec![],
+            crypto_spiffe_id: None,
+            crypto_spiffe_namespaces: HashMap::new(),
+            deployment_profile: DeploymentProfile::Internal,
+            rate_limit_base: 10_000,
+            rate_limit_burst: 10,
+            rate_limit_warmup_secs: 300,
+            rate_limit_windows: vec![],
+            rate_limit_client_ttl_secs: 3600,
+            rate_limit_max_clients: 100_000,
+            rate_limit_ban_threshold: 0,
+            rate_limit_ban_secs: 60,
+            rate_limit_max_ban_secs: 3600,
+            rate_limit_policy: Default::default(),
+            quota_warning_thresholds: vec![],
+            load_sample_interval_ms: 1000,
+            load_max_in_flight: 1000,
+            load_max_queue_depth: 256,
+            concurrency_limit: 0,
+            concurrency_max_queue: 64,
+            load_shed_retry_after_secs: 1,
+            require_mtls: true,
+            tls_cert_path: None,
+            tls_key_path: None,
+            tls_client_ca_path: None,
+            tls_reload_interval_secs: 30,
+            spiffe_endpoint_socket: None,
+            mtls_trust_bundle_path: None,
+            mtls_required_ekus: vec![],
+            cert_revocation_mode: crate::mtls::RevocationMode::Off,
+            cert_revocation_cache_ttl_secs: 300,
+            api_key_auth_enabled: false,
+            api_keys_path: None,
+            access_log_sample_rate: 0.0,
+            access_log_salt_path: "auth-edge/access-log".to_string(),
+            audit_log_enabled: false,
+            audit_log_batch_size: 100,
+            audit_log_flush_interval_secs: 5,
+            audit_log_fallback_path: "/var/log/auth-edge/audit.jsonl".to_string(),
+            error_verbosity: ErrorVerbosity::Detailed,
+            mesh_error_verbosity: ErrorVerbosity::Detailed,
+            introspection_enabled: true,
+            introspection_clients: vec![],
+            introspect_batch_max_tokens: 100,
+            introspect_batch_concurrency: 16,
+            upstream_introspection_url: None,
+            upstream_introspection_client_id: None,
+            upstream_introspection_client_secret: None,
+            upstream_introspection_timeout_ms: 1000,
+            upstream_introspection_cache_ttl_secs: 60,
+            upstream_introspection_cache_size: 10_000,
+            validation_cache_size: 10_000,
+            validation_cache_max_ttl_secs: 300,
+            validation_coalescing_enabled: true,
+            tenant_metrics_top_n: 20,
+            tenant_metrics_window_secs: 300,
+            revocation_check_enabled: true,
+            revocation_fail_open: false,
+            revocation_namespace: "token".to_string(),
+            revocation_cache_size: 100_000,
+            revocation_filter_sync: false,
+            revocation_filter_interval_secs: 30,
+            revocation_filter_max_age_secs: 120,
+            claims_ref_enabled: false,
+            claims_ref_namespace: "token".to_string(),
+            claims_ref_encryption_key: None,
+            private_claim_readers: vec![],
+            certificate_forwarders: vec![],
+            caep_jwks_url: None,
+            caep_issuer: String::new(),
+            caep_audience: "auth-edge".to_string(),
+            caep_deny_ttl_secs: 3600,
+            claim_disclosure: vec![],
+            claim_disclosure_default: vec!["*".to_string()],
+            claim_expressions: HashMap::new(),
+            explain_token_enabled: false,
+            grpc_reflection_enabled: false,
+            emergency_bypass_key_path: None,
+            emergency_bypass_algorithm: jsonwebtoken::Algorithm::ES256,
+            emergency_bypass_max_lifetime_secs: 900,
+            config_file: None,
+            config_watch_interval_secs: 5,
+            authz_rules: vec![],
+            authz_tenant_claim: "tenant_id".to_string(),
+            opa_url: None,
+            opa_timeout_ms: 250,
+            max_token_age_secs: 0,
+            max_token_age_routes: vec![],
+            audience_key_ids: HashMap::new(),
+            jwt_max_payload_bytes: 16 * 1024,
+            jwt_max_claims: 128,
+            jwt_max_nesting: 16,
+        }
+    }
+
+    fn public_edge_config() -> Config {
+        let defaults = DeploymentProfile::PublicEdge.defaults();
+        Config {
+            deployment_profile: DeploymentProfile::PublicEdge,
+            rate_limit_base: defaults.rate_limit_base,
+            require_mtls: defaults.require_mtls,
+            error_verbosity: defaults.error_verbosity,
+            introspection_enabled: defaults.introspection_enabled,
+            ..test_config_base()
         }
     }
 
@@ -262,6 +2034,15 @@ mod tests {
         assert!(matches!(config.validate(), Err(ConfigError::InvalidPort)));
     }
 
+    #[test]
+    fn test_config_validation_gateway_port_conflict() {
+        let mut config = test_config_base();
+        config.http_gateway_port = Some(config.port);
+        assert!(matches!(config.validate(), Err(ConfigError::InvalidPort)));
+        config.http_gateway_port = Some(8081);
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_config_validation_invalid_ttl() {
         let mut config = test_config_base();
@@ -279,6 +2060,66 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_env_source_file_overrides_environment() {
+        let path = std::env::temp_dir().join(format!("auth-edge-{}.env", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "RATE_LIMIT_BASE=42\nCB_FAILURE_THRESHOLD=9\n").unwrap();
+        let src = EnvSource::with_file(path.to_string_lossy()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(src.var("RATE_LIMIT_BASE").as_deref(), Some("42"));
+        assert_eq!(parse_env(&src, "CB_FAILURE_THRESHOLD", 5u32).unwrap(), 9);
+        assert!(matches!(
+            EnvSource::with_file(path.to_string_lossy()),
+            Err(ConfigError::ParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_requires_restart_ignores_reloadable_settings() {
+        let config = test_config_base();
+
+        let mut reloadable = config.clone();
+        reloadable.rate_limit_base = 5;
+        reloadable.circuit_breaker_timeout_seconds = 60;
+        reloadable.jwks_url = Url::parse("http://keys.internal/jwks.json").unwrap();
+        assert!(!config.requires_restart(&reloadable));
+
+        let mut restart = config.clone();
+        restart.port = 9090;
+        assert!(config.requires_restart(&restart));
+    }
+
+    #[test]
+    fn test_circuit_breaker_failure_rate_mode() {
+        let mut config = test_config_base();
+        assert_eq!(config.circuit_breaker_config().trip, TripPolicy::ConsecutiveFailures);
+
+        config.circuit_breaker_failure_rate = 0.5;
+        config.circuit_breaker_window_seconds = 60;
+        assert_eq!(
+            config.circuit_breaker_config().trip,
+            TripPolicy::FailureRate {
+                threshold: 0.5,
+                window: SlidingWindow::Time(Duration::from_secs(60)),
+                minimum_calls: 20,
+            }
+        );
+
+        config.circuit_breaker_failure_rate = 1.5;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_budget() {
+        let mut config = test_config_base();
+        config.circuit_breaker_half_open_max_requests = 1;
+        assert_eq!(config.circuit_breaker_config().half_open_max_requests, 1);
+
+        config.circuit_breaker_half_open_max_requests = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
     #[test]
     fn test_config_validation_zero_crypto_timeout() {
         let mut config = test_config_base();
@@ -286,9 +2127,25 @@ mod tests {
         assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
     }
 
+    #[test]
+    fn test_config_validation_invalid_crypto_spiffe_id() {
+        let mut config = test_config_base();
+        config.crypto_spiffe_id = Some("not-a-spiffe-id".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_crypto_client_config_carries_namespace_policy() {
+        let mut config = test_config_base();
+        config.crypto_allowed_namespaces = vec!["shared".to_string()];
+        let policy = config.crypto_client_config().namespace_policy().unwrap();
+        assert!(policy.is_allowed("shared/tenant-a"));
+        assert!(!policy.is_allowed("token"));
+    }
+
     #[test]
     fn test_parse_url_env_invalid() {
-        let result = parse_url_env("NONEXISTENT_VAR", "not-a-valid-url");
+        let result = parse_url_env(&EnvSource::process(), "NONEXISTENT_VAR", "not-a-valid-url");
         assert!(result.is_err());
     }
 
@@ -299,4 +2156,677 @@ mod tests {
         assert_eq!(crypto_config.key_namespace, "auth-edge");
         assert!(crypto_config.fallback_enabled);
     }
+
+    #[test]
+    fn test_profile_defaults_are_valid() {
+        assert!(test_config_base().validate().is_ok());
+        assert!(public_edge_config().validate().is_ok());
+    }
+
+    #[test]
+    fn test_profile_from_str() {
+        assert_eq!(
+            "internal".parse::<DeploymentProfile>(),
+            Ok(DeploymentProfile::Internal)
+        );
+        assert_eq!(
+            "Public-Edge".parse::<DeploymentProfile>(),
+            Ok(DeploymentProfile::PublicEdge)
+        );
+        assert!("dmz".parse::<DeploymentProfile>().is_err());
+    }
+
+    #[test]
+    fn test_internal_profile_rejects_disabled_mtls() {
+        let mut config = test_config_base();
+        config.require_mtls = false;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::UnsafeProfile {
+                profile: DeploymentProfile::Internal,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_public_edge_rejects_detailed_errors() {
+        let mut config = public_edge_config();
+        config.error_verbosity = ErrorVerbosity::Detailed;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::UnsafeProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_introspection_clients() {
+        let mut config = test_config_base();
+        config.introspection_clients = vec![IntrospectionClientConfig {
+            name: "orders".to_string(),
+            spiffe_id: Some("spiffe://example.org/ns/orders/*".to_string()),
+            api_key_id: None,
+            audience: Some("https://orders.example.com".to_string()),
+            scopes: vec!["orders:*".to_string()],
+        }];
+        assert!(config.validate().is_ok());
+
+        config.introspection_clients[0].spiffe_id = None;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.introspection_clients[0].api_key_id = Some("orders-rs".to_string());
+        config.introspection_clients[0].scopes.clear();
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_listen_addrs() {
+        let mut config = test_config_base();
+        config.host = "::".parse().unwrap();
+        assert_eq!(config.listen_addrs(8080).unwrap()[0].to_string(), "[::]:8080");
+
+        config.dual_stack = true;
+        assert!(config.validate().is_ok());
+        assert_eq!(config.listen_addrs(8080).unwrap().len(), 2);
+
+        config.host = "fd00::10".parse().unwrap();
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_server_tls_settings() {
+        let mut config = test_config_base();
+        config.tls_cert_path = Some("/etc/auth-edge/tls.crt".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        // REQUIRE_MTLS needs a trust bundle to verify client certificates
+        config.tls_key_path = Some("/etc/auth-edge/tls.key".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.tls_client_ca_path = Some("/etc/auth-edge/ca.crt".to_string());
+        assert!(config.validate().is_ok());
+
+        config.tls_cert_path = None;
+        config.tls_key_path = None;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_spiffe_endpoint_socket_settings() {
+        let mut config = test_config_base();
+        config.spiffe_endpoint_socket = Some("unix:///run/spire/agent.sock".to_string());
+        assert!(config.validate().is_ok());
+
+        config.spiffe_endpoint_socket = Some("tcp://127.0.0.1:8081".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.spiffe_endpoint_socket = Some("unix:///run/spire/agent.sock".to_string());
+        config.tls_cert_path = Some("/etc/auth-edge/tls.crt".to_string());
+        config.tls_key_path = Some("/etc/auth-edge/tls.key".to_string());
+        config.tls_client_ca_path = Some("/etc/auth-edge/ca.crt".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_spiffe_allowed_ids_settings() {
+        let mut config = test_config_base();
+        config.allowed_spiffe_domains = vec!["example.org".to_string()];
+        config.spiffe_allowed_ids = vec!["spiffe://example.org/ns/payments/**".parse().unwrap()];
+        config.spiffe_deny_by_default = true;
+        assert!(config.validate().is_ok());
+        assert!(config.spiffe_policy().is_restrictive());
+
+        config.spiffe_allowed_ids.push("spiffe://partner.org/**".parse().unwrap());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+        config.allowed_spiffe_domains.push("partner.org".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_admin_spiffe_ids_settings() {
+        let mut config = test_config_base();
+        config.allowed_spiffe_domains = vec!["example.org".to_string()];
+        config.admin_spiffe_ids = vec!["spiffe://example.org/ns/ops/sa/*".parse().unwrap()];
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.spiffe_endpoint_socket = Some("unix:///run/spire/agent.sock".to_string());
+        assert!(config.validate().is_ok());
+
+        config.admin_spiffe_ids.push("spiffe://partner.org/**".parse().unwrap());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_audit_log_settings() {
+        let mut config = test_config_base();
+        config.audit_log_enabled = true;
+        assert!(config.validate().is_ok());
+
+        config.audit_log_batch_size = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.audit_log_batch_size = 100;
+        config.audit_log_flush_interval_secs = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.audit_log_enabled = false;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_introspect_batch_settings() {
+        let mut config = test_config_base();
+        assert!(config.validate().is_ok());
+
+        config.introspect_batch_max_tokens = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.introspect_batch_max_tokens = 100;
+        config.introspect_batch_concurrency = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_upstream_introspection_settings() {
+        let mut config = test_config_base();
+        config.upstream_introspection_url = Some("https://as.example.com/introspect".to_string());
+        assert!(config.validate().is_ok());
+
+        config.upstream_introspection_client_id = Some("auth-edge".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.upstream_introspection_client_secret = Some("s3cret".to_string());
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.redacted().upstream_introspection_client_secret.as_deref(),
+            Some("<redacted>")
+        );
+
+        config.upstream_introspection_url = Some("not a url".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::InvalidUrl { .. })));
+    }
+
+    #[test]
+    fn test_revocation_filter_settings() {
+        let mut config = test_config_base();
+        config.revocation_filter_sync = true;
+        assert!(config.validate().is_ok());
+
+        config.revocation_filter_max_age_secs = 10;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::ParseError { name, .. }) if name == "REVOCATION_FILTER_MAX_AGE"
+        ));
+
+        config.revocation_filter_interval_secs = 0;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::ParseError { name, .. }) if name == "REVOCATION_FILTER_INTERVAL"
+        ));
+    }
+
+    #[test]
+    fn test_chaos_settings() {
+        let with_chaos = |mut config: Config| {
+            config.chaos_enabled = true;
+            config.allowed_spiffe_domains = vec!["example.org".to_string()];
+            config.admin_spiffe_ids = vec!["spiffe://example.org/ns/ops/sa/*".parse().unwrap()];
+            config.spiffe_endpoint_socket = Some("unix:///run/spire/agent.sock".to_string());
+            config
+        };
+        let mut config = with_chaos(test_config_base());
+        assert!(config.validate().is_ok());
+
+        config.chaos_max_duration_secs = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        let mut config = with_chaos(test_config_base());
+        config.admin_spiffe_ids.clear();
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        assert!(matches!(
+            with_chaos(public_edge_config()).validate(),
+            Err(ConfigError::UnsafeProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_private_claim_readers_settings() {
+        let mut config = test_config_base();
+        config.private_claim_readers = vec!["spiffe://example.org/ns/kyc/sa/*".parse().unwrap()];
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.spiffe_endpoint_socket = Some("unix:///run/spire/agent.sock".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_certificate_forwarders_settings() {
+        let mut config = test_config_base();
+        config.certificate_forwarders = vec!["spiffe://example.org/ns/edge/sa/envoy".parse().unwrap()];
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.spiffe_endpoint_socket = Some("unix:///run/spire/agent.sock".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_chain_validation_settings() {
+        let mut config = test_config_base();
+        config.mtls_required_ekus = vec!["clientAuth".to_string(), "1.3.6.1.4.1.99999.1".to_string()];
+        config.cert_revocation_mode = crate::mtls::RevocationMode::HardFail;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.mtls_trust_bundle_path = Some("/etc/auth-edge/clients.pem".to_string());
+        assert!(config.validate().is_ok());
+
+        config.mtls_required_ekus.push("client-auth".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_public_edge_rejects_introspection() {
+        let mut config = public_edge_config();
+        config.introspection_enabled = true;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::UnsafeProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_public_edge_rejects_explain_token() {
+        let mut config = public_edge_config();
+        config.explain_token_enabled = true;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::UnsafeProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_public_edge_rejects_server_reflection() {
+        let mut config = public_edge_config();
+        config.grpc_reflection_enabled = true;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::UnsafeProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_caep_receiver_requires_issuer_and_gateway() {
+        let mut config = test_config_base();
+        config.caep_jwks_url = Some("https://ssf.example/jwks".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::MissingRequired(_))));
+
+        config.caep_issuer = "https://ssf.example".to_string();
+        assert!(matches!(config.validate(), Err(ConfigError::MissingRequired(_))));
+
+        config.http_gateway_port = Some(8081);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_public_edge_rejects_fail_open_revocation() {
+        let mut config = public_edge_config();
+        config.revocation_fail_open = true;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::UnsafeProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_zero_rate_limit_rejected() {
+        let mut config = public_edge_config();
+        config.rate_limit_base = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::InvalidThreshold)));
+    }
+
+    #[test]
+    fn test_rate_limit_config_uses_profile_limit() {
+        let config = public_edge_config();
+        assert_eq!(config.rate_limit_config().base_limit, 100);
+    }
+
+    fn trusted_issuer(issuer: &str) -> TrustedIssuerConfig {
+        TrustedIssuerConfig {
+            issuer: issuer.to_string(),
+            jwks_url: format!("{issuer}/.well-known/jwks.json"),
+            algorithms: vec![jsonwebtoken::Algorithm::RS256],
+            audiences: vec![],
+            jwks_signing_key: None,
+            signed_jwks_only: false,
+        }
+    }
+
+    #[test]
+    fn test_trusted_issuers_parse_from_json() {
+        let issuers: Vec<TrustedIssuerConfig> = serde_json::from_str(
+            r#"[{"issuer":"https://idp.example","jwks_url":"https://idp.example/jwks","algorithms":["RS256","ES256"],"audiences":["api"]}]"#,
+        )
+        .unwrap();
+        assert_eq!(issuers[0].algorithms.len(), 2);
+        assert_eq!(issuers[0].audiences, vec!["api".to_string()]);
+    }
+
+    fn federated_idp(name: &str, issuer: &str) -> FederatedIdpConfig {
+        FederatedIdpConfig {
+            name: name.to_string(),
+            issuer: issuer.to_string(),
+            discovery_url: format!("{issuer}/.well-known/openid-configuration"),
+            audiences: vec![],
+            algorithms: default_federation_algorithms(),
+            claim_mapping: HashMap::new(),
+            exchange: false,
+            exchange_ttl_secs: 0,
+        }
+    }
+
+    #[test]
+    fn test_federated_idps_validation() {
+        let mut config = test_config_base();
+        config.federated_idps = vec![
+            federated_idp("okta", "https://acme.okta.com"),
+            federated_idp("entra", "https://login.microsoftonline.com/tenant/v2.0"),
+        ];
+        assert!(config.validate().is_ok());
+
+        config.trusted_issuers = vec![trusted_issuer("https://acme.okta.com")];
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.trusted_issuers.clear();
+        config.federated_idps[1].algorithms = vec![jsonwebtoken::Algorithm::HS256];
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_trusted_issuers_validation() {
+        let mut config = test_config_base();
+        config.trusted_issuers = vec![trusted_issuer("https://a.example"), trusted_issuer("https://b.example")];
+        assert!(config.validate().is_ok());
+
+        config.trusted_issuers.push(trusted_issuer("https://a.example"));
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        let mut hmac = trusted_issuer("https://c.example");
+        hmac.algorithms = vec![jsonwebtoken::Algorithm::HS256];
+        config.trusted_issuers = vec![hmac];
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_signed_jwks_settings() {
+        let issuers: Vec<TrustedIssuerConfig> = serde_json::from_str(
+            r#"[{"issuer":"https://partner.example","jwks_url":"https://partner.example/jwks","algorithms":["ES256"],
+                 "jwks_signing_key":{"kty":"EC","kid":"anchor-1","crv":"P-256","x":"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU","y":"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"},
+                 "signed_jwks_only":true}]"#,
+        )
+        .unwrap();
+        let mut config = test_config_base();
+        config.trusted_issuers = issuers;
+        assert!(config.validate().is_ok());
+
+        config.trusted_issuers[0].jwks_signing_key.as_mut().unwrap().crv = Some("P-521".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.trusted_issuers[0].jwks_signing_key = None;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_rate_limit_config_burst_and_warmup() {
+        let mut config = test_config_base();
+        config.rate_limit_burst = 0;
+        config.rate_limit_warmup_secs = 0;
+        let limits = config.rate_limit_config();
+        assert_eq!(limits.burst_capacity, 0);
+        assert!(limits.warmup_period.is_zero());
+        assert!(limits.extra_windows.is_empty());
+    }
+
+    #[test]
+    fn test_rate_limit_client_ttl_covers_windows() {
+        let mut config = test_config_base();
+        config.rate_limit_client_ttl_secs = 30;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.rate_limit_client_ttl_secs = 600;
+        config.rate_limit_windows = vec!["50000/1h".parse().unwrap()];
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.rate_limit_client_ttl_secs = 3600;
+        config.rate_limit_windows = vec![];
+        config.rate_limit_policy =
+            serde_json::from_str(r#"[{"dimensions": ["subject"], "limit": "1000/1h"}]"#).unwrap();
+        assert!(config.validate().is_ok());
+        config.rate_limit_policy =
+            serde_json::from_str(r#"[{"dimensions": ["subject"], "limit": "1000/2h"}]"#).unwrap();
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.rate_limit_client_ttl_secs = 0;
+        assert!(config.validate().is_ok());
+        let limits = config.rate_limit_config();
+        assert!(limits.client_idle_ttl.is_zero());
+        assert_eq!(limits.max_clients, 100_000);
+    }
+
+    #[test]
+    fn test_rate_limit_ban_bounds() {
+        let mut config = test_config_base();
+        config.rate_limit_ban_secs = 0;
+        assert!(config.validate().is_ok());
+
+        config.rate_limit_ban_threshold = 20;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+        config.rate_limit_ban_secs = 7200;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+        config.rate_limit_ban_secs = 60;
+        assert!(config.validate().is_ok());
+
+        let limits = config.rate_limit_config();
+        assert_eq!(limits.ban_threshold, 20);
+        assert_eq!(limits.max_ban_duration, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_bypass_lifetime_bounds() {
+        let mut config = test_config_base();
+        config.emergency_bypass_max_lifetime_secs = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.emergency_bypass_max_lifetime_secs = 7200;
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_authz_validation() {
+        let mut config = test_config_base();
+        config.authz_rules = serde_json::from_str(
+            r#"[{"route":"GET /v1/orders/*","scopes":["orders:read"],"same_tenant":true}]"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        config.opa_url = Some("http://localhost:8181/v1/data/authz".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+
+        config.authz_rules.clear();
+        assert!(config.validate().is_ok());
+        config.opa_url = Some("not a url".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::InvalidUrl { .. })));
+
+        config.opa_url = None;
+        config.authz_rules = serde_json::from_str(r#"[{"route":"/v1/**/orders"}]"#).unwrap();
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_max_token_age_routes_validation() {
+        let mut config = test_config_base();
+        config.max_token_age_secs = 3600;
+        config.max_token_age_routes =
+            serde_json::from_str(r#"[{"route":"POST /v1/payments/**","max_age_secs":300}]"#)
+                .unwrap();
+        assert!(config.validate().is_ok());
+
+        config.max_token_age_routes[0].route = "v1/payments".to_string();
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_claim_expressions_compiled_at_load() {
+        let mut config = test_config_base();
+        config.claim_expressions = HashMap::from([(
+            "acme_admin".to_string(),
+            "claims.tenant == 'acme' && 'admin' in claims.roles".to_string(),
+        )]);
+        assert!(config.validate().is_ok());
+
+        config
+            .claim_expressions
+            .insert("broken".to_string(), "claims.tenant ==".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_quota_warning_thresholds() {
+        let src = |value: &str| {
+            EnvSource::with_overrides(HashMap::from([(
+                "QUOTA_WARNING_THRESHOLDS".to_string(),
+                value.to_string(),
+            )]))
+        };
+        assert_eq!(
+            parse_percentages_env(&src("80, 90%"), "QUOTA_WARNING_THRESHOLDS").unwrap(),
+            vec![80, 90]
+        );
+        assert!(parse_percentages_env(&src(""), "QUOTA_WARNING_THRESHOLDS").unwrap().is_empty());
+        assert!(parse_percentages_env(&src("0"), "QUOTA_WARNING_THRESHOLDS").is_err());
+        assert!(parse_percentages_env(&src("150"), "QUOTA_WARNING_THRESHOLDS").is_err());
+    }
+
+    #[test]
+    fn test_otlp_export_settings() {
+        let src = |value: &str| {
+            EnvSource::with_overrides(HashMap::from([(
+                "OTLP_HEADERS".to_string(),
+                value.to_string(),
+            )]))
+        };
+        assert_eq!(
+            parse_key_values_env(&src("authorization=Bearer abc, x-tenant=acme"), "OTLP_HEADERS")
+                .unwrap(),
+            vec![
+                ("authorization".to_string(), "Bearer abc".to_string()),
+                ("x-tenant".to_string(), "acme".to_string()),
+            ]
+        );
+        assert!(parse_key_values_env(&src("authorization"), "OTLP_HEADERS").is_err());
+
+        let mut config = test_config_base();
+        config.otlp_headers = vec![("authorization".to_string(), "Bearer abc".to_string())];
+        assert_eq!(config.redacted().otlp_headers[0].1, "<redacted>");
+
+        config.otlp_sampling_ratio = 1.5;
+        assert!(config.validate().is_err());
+        config.otlp_sampling_ratio = 0.1;
+        config.workload_spiffe_id = Some("not-a-spiffe-id".to_string());
+        assert!(config.validate().is_err());
+        config.workload_spiffe_id = Some("spiffe://example.org/ns/auth/sa/auth-edge".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_method_quotas() {
+        let src = |value: &str| {
+            EnvSource::with_overrides(HashMap::from([(
+                "RATE_LIMIT_METHOD_QUOTAS".to_string(),
+                value.to_string(),
+            )]))
+        };
+        let rules = parse_method_quotas_env(
+            &src("IntrospectToken=500/1m, /auth.v1.AuthEdgeService/ValidateToken=100/1m"),
+            "RATE_LIMIT_METHOD_QUOTAS",
+        )
+        .unwrap();
+        assert_eq!(
+            rules[0],
+            crate::rate_limiter::RateLimitRule {
+                methods: vec!["IntrospectToken".to_string()],
+                dimensions: vec![
+                    crate::rate_limiter::KeyDimension::Caller,
+                    crate::rate_limiter::KeyDimension::Method,
+                ],
+                limit: crate::rate_limiter::RateWindow::new(500, Duration::from_secs(60)),
+            }
+        );
+        assert_eq!(rules[1].methods, ["ValidateToken"]);
+        assert_eq!(rules[1].limit.limit, 100);
+        assert!(parse_method_quotas_env(&src(""), "RATE_LIMIT_METHOD_QUOTAS").unwrap().is_empty());
+        assert!(parse_method_quotas_env(&src("IntrospectToken"), "RATE_LIMIT_METHOD_QUOTAS").is_err());
+        assert!(parse_method_quotas_env(&src("IntrospectToken=0/1m"), "RATE_LIMIT_METHOD_QUOTAS").is_err());
+
+        // Quotas join the keyed policy after its own rules
+        let config = Config::from_source(&EnvSource::with_overrides(HashMap::from([
+            (
+                "RATE_LIMIT_POLICY".to_string(),
+                r#"[{"dimensions": ["source_ip"], "limit": "100/1m"}]"#.to_string(),
+            ),
+            ("RATE_LIMIT_METHOD_QUOTAS".to_string(), "IntrospectToken=10/1m".to_string()),
+        ])))
+        .unwrap();
+        let rules = &config.rate_limit_policy.rules;
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].methods, ["IntrospectToken"]);
+
+        // Quota windows count towards the client TTL bound
+        let mut config = test_config_base();
+        config.rate_limit_client_ttl_secs = 3600;
+        config.rate_limit_policy.rules =
+            parse_method_quotas_env(&src("IntrospectToken=10/2h"), "RATE_LIMIT_METHOD_QUOTAS")
+                .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_jwt_algorithms() {
+        let src = |value: &str| {
+            EnvSource::with_overrides(HashMap::from([(
+                "JWT_ALGORITHMS".to_string(),
+                value.to_string(),
+            )]))
+        };
+        assert_eq!(
+            parse_algorithms_env(&src("EdDSA, PS256,ES384"), "JWT_ALGORITHMS").unwrap(),
+            vec![
+                jsonwebtoken::Algorithm::EdDSA,
+                jsonwebtoken::Algorithm::PS256,
+                jsonwebtoken::Algorithm::ES384,
+            ]
+        );
+        assert!(parse_algorithms_env(&src("ES512"), "JWT_ALGORITHMS").is_err());
+
+        let mut config = test_config_base();
+        config.jwt_algorithms.push(jsonwebtoken::Algorithm::HS256);
+        assert!(config.validate().is_err());
+        config.jwt_algorithms.clear();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_oidc_issuer() {
+        let mut config = test_config_base();
+        config.oidc_issuer = Some("https://idp.example".to_string());
+        assert!(config.validate().is_ok());
+
+        config.oidc_discovery_ttl_seconds = 0;
+        assert!(config.validate().is_err());
+        config.oidc_issuer = None;
+        assert!(config.validate().is_ok());
+
+        config.oidc_issuer = Some("not a url".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::InvalidUrl { .. })));
+    }
 }
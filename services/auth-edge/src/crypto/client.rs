@@ -5,10 +5,11 @@
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use rust_common::CircuitBreaker;
+use rust_common::CircuitBreakerRegistry;
 use tonic::transport::Channel;
 use tracing::{info, instrument, warn};
 
+use crate::chaos::{self, ChaosTarget};
 use crate::crypto::config::CryptoClientConfig;
 use crate::crypto::error::CryptoError;
 use crate::crypto::fallback::{EncryptedData, FallbackHandler, PendingOperation};
@@ -18,13 +19,14 @@ use crate::crypto::proto::{
     crypto_service_client::CryptoServiceClient, DecryptRequest, EncryptRequest,
     GetKeyMetadataRequest, RotateKeyRequest,
 };
+use crate::middleware::trace_context::traced;
 
 /// CryptoClient for centralized cryptographic operations
 pub struct CryptoClient {
     /// gRPC client for crypto-service
     grpc_client: CryptoServiceClient<Channel>,
-    /// Circuit breaker for resilience
-    circuit_breaker: Arc<CircuitBreaker>,
+    /// Circuit breakers for resilience, one per crypto-service method
+    circuit_breakers: Arc<CircuitBreakerRegistry>,
     /// Key manager for KEK/DEK handling
     key_manager: Arc<KeyManager>,
     /// Fallback handler for degraded mode
@@ -52,16 +54,22 @@ impl CryptoClient {
             .connect_lazy();
 
         let grpc_client = CryptoServiceClient::new(channel);
-        let circuit_breaker = Arc::new(CircuitBreaker::new(config.circuit_breaker.clone()));
-        let key_manager = Arc::new(KeyManager::new(
-            &config.key_namespace,
-            Duration::from_secs(3600), // 1 hour rotation window
+        let circuit_breakers = Arc::new(CircuitBreakerRegistry::new(
+            "crypto-service",
+            config.circuit_breaker.clone(),
         ));
+        let key_manager = Arc::new(
+            KeyManager::new(
+                &config.key_namespace,
+                Duration::from_secs(3600), // 1 hour rotation window
+            )
+            .with_policy(config.namespace_policy()?),
+        );
         let metrics = Arc::new(CryptoMetrics::new());
 
         Ok(Self {
             grpc_client,
-            circuit_breaker,
+            circuit_breakers,
             key_manager,
             fallback: None,
             metrics,
@@ -109,9 +117,10 @@ impl CryptoClient {
         correlation_id: &str,
     ) -> Result<EncryptedData, CryptoError> {
         let start = Instant::now();
+        let circuit_breaker = self.circuit_breakers.get("Encrypt");
 
         // Check circuit breaker
-        if !self.circuit_breaker.allow_request().await {
+        if !circuit_breaker.allow_request().await {
             return self.encrypt_fallback(plaintext, aad, start);
         }
 
@@ -124,9 +133,13 @@ impl CryptoClient {
         };
 
         let mut client = self.grpc_client.clone();
-        match client.encrypt(request).await {
+        let result = match chaos::inject(ChaosTarget::Crypto).await {
+            Ok(()) => client.encrypt(traced(request)).await,
+            Err(e) => Err(tonic::Status::unavailable(e.to_string())),
+        };
+        match result {
             Ok(response) => {
-                self.circuit_breaker.record_success().await;
+                circuit_breaker.record_success().await;
                 let inner = response.into_inner();
 
                 let encrypted = EncryptedData {
@@ -146,7 +159,7 @@ impl CryptoClient {
                 Ok(encrypted)
             }
             Err(status) => {
-                self.circuit_breaker.record_failure().await;
+                circuit_breaker.record_failure().await;
                 let error = CryptoError::from(status);
 
                 if error.is_retryable() {
@@ -195,8 +208,14 @@ impl CryptoClient {
             return self.decrypt_fallback(encrypted, aad, start);
         }
 
+        if let Err(error) = self.key_manager.authorize(&encrypted.key_id) {
+            self.metrics.record_failure("decrypt", "namespace_denied", start.elapsed());
+            return Err(error);
+        }
+
         // Check circuit breaker
-        if !self.circuit_breaker.allow_request().await {
+        let circuit_breaker = self.circuit_breakers.get("Decrypt");
+        if !circuit_breaker.allow_request().await {
             // Can't decrypt remote-encrypted data locally
             self.metrics.record_failure("decrypt", "circuit_open", start.elapsed());
             return Err(CryptoError::CircuitOpen);
@@ -212,16 +231,20 @@ impl CryptoClient {
         };
 
         let mut client = self.grpc_client.clone();
-        match client.decrypt(request).await {
+        let result = match chaos::inject(ChaosTarget::Crypto).await {
+            Ok(()) => client.decrypt(traced(request)).await,
+            Err(e) => Err(tonic::Status::unavailable(e.to_string())),
+        };
+        match result {
             Ok(response) => {
-                self.circuit_breaker.record_success().await;
+                circuit_breaker.record_success().await;
                 self.metrics.record_success("decrypt", start.elapsed());
                 self.metrics.set_fallback_active(false);
 
                 Ok(response.into_inner().plaintext)
             }
             Err(status) => {
-                self.circuit_breaker.record_failure().await;
+                circuit_breaker.record_failure().await;
                 let error = CryptoError::from(status);
                 self.metrics.record_failure("decrypt", "service_error", start.elapsed());
                 Err(error)
@@ -252,8 +275,9 @@ impl CryptoClient {
     #[instrument(skip(self), fields(correlation_id = %correlation_id))]
     pub async fn rotate_key(&self, correlation_id: &str) -> Result<KeyId, CryptoError> {
         let start = Instant::now();
+        let circuit_breaker = self.circuit_breakers.get("RotateKey");
 
-        if !self.circuit_breaker.allow_request().await {
+        if !circuit_breaker.allow_request().await {
             // Queue for later
             if let Some(ref fallback) = self.fallback {
                 fallback
@@ -273,9 +297,9 @@ impl CryptoClient {
         };
 
         let mut client = self.grpc_client.clone();
-        match client.rotate_key(request).await {
+        match client.rotate_key(traced(request)).await {
             Ok(response) => {
-                self.circuit_breaker.record_success().await;
+                circuit_breaker.record_success().await;
                 let inner = response.into_inner();
 
                 let new_key = inner
@@ -291,7 +315,7 @@ impl CryptoClient {
                 Ok(new_key)
             }
             Err(status) => {
-                self.circuit_breaker.record_failure().await;
+                circuit_breaker.record_failure().await;
                 let error = CryptoError::from(status);
                 self.metrics.record_failure("rotate_key", "service_error", start.elapsed());
                 Err(error)
@@ -312,7 +336,7 @@ impl CryptoClient {
         };
 
         let mut client = self.grpc_client.clone();
-        let response = client.get_key_metadata(request).await?;
+        let response = client.get_key_metadata(traced(request)).await?;
         let inner = response.into_inner();
 
         let metadata = inner
@@ -337,7 +361,17 @@ impl CryptoClient {
     /// Checks if operating in fallback mode
     #[must_use]
     pub fn is_fallback_active(&self) -> bool {
-        !self.circuit_breaker.is_closed()
+        let threshold = self.circuit_breakers.config().failure_threshold;
+        self.circuit_breakers
+            .breakers()
+            .iter()
+            .any(|(_, breaker)| breaker.failure_count() >= threshold)
+    }
+
+    /// Gets the circuit breakers, keyed by crypto-service method
+    #[must_use]
+    pub fn circuit_breakers(&self) -> &CircuitBreakerRegistry {
+        &self.circuit_breakers
     }
 
     /// Gets the key manager
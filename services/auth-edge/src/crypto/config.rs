@@ -2,10 +2,13 @@
 //!
 //! Type-safe configuration for the crypto-service client with validation.
 
+use std::collections::HashMap;
 use std::time::Duration;
 use url::Url;
 
 use crate::crypto::error::CryptoError;
+use crate::crypto::namespace::NamespacePolicy;
+use crate::mtls::spiffe::OwnedSpiffeId;
 use rust_common::CircuitBreakerConfig;
 
 /// Configuration for CryptoClient
@@ -21,6 +24,12 @@ pub struct CryptoClientConfig {
     pub timeout: Duration,
     /// Circuit breaker configuration
     pub circuit_breaker: CircuitBreakerConfig,
+    /// Additional key namespace prefixes this service may use
+    pub allowed_namespace_prefixes: Vec<String>,
+    /// SPIFFE ID of this workload, used to look up `spiffe_namespaces`
+    pub spiffe_id: Option<String>,
+    /// SPIFFE ID patterns mapped to the namespace prefixes they grant
+    pub spiffe_namespaces: HashMap<String, Vec<String>>,
 }
 
 impl Default for CryptoClientConfig {
@@ -31,6 +40,9 @@ impl Default for CryptoClientConfig {
             fallback_enabled: true,
             timeout: Duration::from_secs(5),
             circuit_breaker: CircuitBreakerConfig::default(),
+            allowed_namespace_prefixes: Vec::new(),
+            spiffe_id: None,
+            spiffe_namespaces: HashMap::new(),
         }
     }
 }
@@ -71,6 +83,44 @@ impl CryptoClientConfig {
         self
     }
 
+    /// Creates a new config with additional allowed namespace prefixes
+    #[must_use]
+    pub fn with_allowed_namespace_prefixes(mut self, prefixes: Vec<String>) -> Self {
+        self.allowed_namespace_prefixes = prefixes;
+        self
+    }
+
+    /// Creates a new config with the workload SPIFFE ID and its namespace mapping
+    #[must_use]
+    pub fn with_spiffe_namespaces(
+        mut self,
+        spiffe_id: Option<String>,
+        mapping: HashMap<String, Vec<String>>,
+    ) -> Self {
+        self.spiffe_id = spiffe_id;
+        self.spiffe_namespaces = mapping;
+        self
+    }
+
+    /// Builds the key namespace policy for this service
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::InvalidConfig` if the SPIFFE ID cannot be parsed
+    pub fn namespace_policy(&self) -> Result<NamespacePolicy, CryptoError> {
+        let mut policy = NamespacePolicy::own(&self.key_namespace)
+            .with_prefixes(self.allowed_namespace_prefixes.iter().cloned());
+
+        if let Some(spiffe_id) = &self.spiffe_id {
+            let identity = OwnedSpiffeId::parse(spiffe_id).map_err(|e| {
+                CryptoError::invalid_config(format!("Invalid SPIFFE ID '{spiffe_id}': {e}"))
+            })?;
+            policy = policy.with_spiffe_identity(&identity, &self.spiffe_namespaces);
+        }
+
+        Ok(policy)
+    }
+
     /// Validates the configuration
     ///
     /// # Errors
@@ -108,6 +158,8 @@ impl CryptoClientConfig {
             });
         }
 
+        self.namespace_policy()?;
+
         Ok(())
     }
 
@@ -142,6 +194,24 @@ mod tests {
         assert!(matches!(result, Err(CryptoError::InvalidConfig { .. })));
     }
 
+    #[test]
+    fn test_invalid_spiffe_id_rejected() {
+        let config = CryptoClientConfig::default()
+            .with_spiffe_namespaces(Some("https://not-spiffe".to_string()), HashMap::new());
+        assert!(matches!(config.validate(), Err(CryptoError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_namespace_policy_includes_configured_prefixes() {
+        let config = CryptoClientConfig::default()
+            .with_allowed_namespace_prefixes(vec!["shared".to_string()]);
+        let policy = config.namespace_policy().unwrap();
+
+        assert!(policy.is_allowed("auth-edge"));
+        assert!(policy.is_allowed("shared/tenant-a"));
+        assert!(!policy.is_allowed("token"));
+    }
+
     #[test]
     fn test_builder_pattern() {
         let url = Url::parse("https://crypto.example.com:50051").unwrap();
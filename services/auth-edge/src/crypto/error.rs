@@ -64,6 +64,13 @@ pub enum CryptoError {
     /// Circuit breaker is open
     #[error("Circuit breaker open for crypto-service")]
     CircuitOpen,
+
+    /// Key namespace is outside this service's namespace policy
+    #[error("Key namespace not authorized: {namespace}")]
+    NamespaceDenied {
+        /// Namespace that was requested
+        namespace: String,
+    },
 }
 
 impl CryptoError {
@@ -252,6 +259,15 @@ mod tests {
         .is_retryable());
     }
 
+    #[test]
+    fn test_namespace_denied_not_retryable() {
+        let error = CryptoError::NamespaceDenied {
+            namespace: "token".to_string(),
+        };
+        assert!(!error.is_retryable());
+        assert!(!error.should_trip_circuit());
+    }
+
     #[test]
     fn test_error_should_trip_circuit() {
         assert!(CryptoError::ServiceUnavailable {
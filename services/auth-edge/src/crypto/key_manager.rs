@@ -10,6 +10,7 @@ use tokio::sync::RwLock;
 use tracing::{info, warn};
 
 use crate::crypto::error::CryptoError;
+use crate::crypto::namespace::NamespacePolicy;
 use crate::crypto::proto::{
     crypto_service_client::CryptoServiceClient, GenerateKeyRequest, GetKeyMetadataRequest,
     KeyAlgorithm,
@@ -105,6 +106,8 @@ pub struct KeyManager {
     rotation_window: Duration,
     /// Key namespace
     namespace: String,
+    /// Namespaces this manager may generate or rotate keys in
+    policy: NamespacePolicy,
 }
 
 impl KeyManager {
@@ -117,10 +120,33 @@ impl KeyManager {
             previous_keys: Arc::new(RwLock::new(Vec::new())),
             cached_dek: Arc::new(RwLock::new(None)),
             rotation_window,
+            policy: NamespacePolicy::own(&ns),
             namespace: ns,
         }
     }
 
+    /// Replaces the namespace policy (defaults to the manager's own namespace)
+    #[must_use]
+    pub fn with_policy(mut self, policy: NamespacePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Rejects keys outside the namespace policy
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::NamespaceDenied` for foreign namespaces
+    pub fn authorize(&self, key_id: &KeyId) -> Result<(), CryptoError> {
+        self.policy.authorize_key(key_id).inspect_err(|_| {
+            warn!(
+                namespace = %self.namespace,
+                requested = %key_id.namespace,
+                "Key namespace denied by policy"
+            );
+        })
+    }
+
     /// Initializes the KeyManager by requesting/creating a KEK from crypto-service
     ///
     /// # Errors
@@ -146,6 +172,7 @@ impl KeyManager {
 
         // Try to get existing key metadata first
         let key_id = KeyId::new(&self.namespace, "cache-kek", 1);
+        self.authorize(&key_id)?;
         let get_request = GetKeyMetadataRequest {
             key_id: Some(key_id.to_proto()),
             correlation_id: correlation_id.to_string(),
@@ -156,6 +183,7 @@ impl KeyManager {
                 if let Some(metadata) = response.into_inner().metadata {
                     if let Some(proto_id) = metadata.id {
                         let existing_key = KeyId::from_proto(&proto_id);
+                        self.authorize(&existing_key)?;
                         info!(key_id = %existing_key, "Using existing KEK");
                         self.active_key.store(Arc::new(existing_key.clone()));
                         return Ok(existing_key);
@@ -192,6 +220,7 @@ impl KeyManager {
             .key_id
             .map(|k| KeyId::from_proto(&k))
             .ok_or_else(|| CryptoError::encryption_failed("No key ID in response"))?;
+        self.authorize(&new_key)?;
 
         info!(key_id = %new_key, "Created new KEK");
         self.active_key.store(Arc::new(new_key.clone()));
@@ -211,6 +240,7 @@ impl KeyManager {
     ///
     /// Returns error if rotation fails
     pub async fn rotate(&self, new_key: KeyId) -> Result<(), CryptoError> {
+        self.authorize(&new_key)?;
         let old_key = self.active_key();
 
         // Add old key to previous keys
@@ -239,6 +269,10 @@ impl KeyManager {
 
     /// Checks if a key ID is valid (current or within rotation window)
     pub async fn is_valid_key(&self, key_id: &KeyId) -> bool {
+        if !self.policy.is_allowed(&key_id.namespace) {
+            return false;
+        }
+
         // Check if it's the active key
         if *key_id == self.active_key() {
             return true;
@@ -329,6 +363,28 @@ mod tests {
         assert!(manager.is_valid_key(&key2).await);
     }
 
+    #[tokio::test]
+    async fn test_rotate_rejects_foreign_namespace() {
+        let manager = KeyManager::new("test", Duration::from_secs(3600));
+        let key1 = KeyId::new("test", "key", 1);
+        manager.active_key.store(Arc::new(key1.clone()));
+
+        let result = manager.rotate(KeyId::new("other-service", "key", 2)).await;
+
+        assert!(matches!(result, Err(CryptoError::NamespaceDenied { .. })));
+        assert_eq!(manager.active_key(), key1);
+    }
+
+    #[tokio::test]
+    async fn test_policy_allows_tenant_namespace() {
+        let manager = KeyManager::new("test", Duration::from_secs(3600))
+            .with_policy(NamespacePolicy::own("test").with_prefixes(["shared"]));
+
+        manager.rotate(KeyId::new("shared/tenant-a", "key", 1)).await.unwrap();
+        assert!(manager.is_valid_key(&KeyId::new("shared/tenant-a", "key", 1)).await);
+        assert!(!manager.is_valid_key(&KeyId::new("billing", "key", 1)).await);
+    }
+
     #[tokio::test]
     async fn test_dek_caching() {
         let manager = KeyManager::new("test", Duration::from_secs(3600));
@@ -60,6 +60,7 @@ pub fn log_crypto_error(
         CryptoError::FallbackUnavailable => "fallback_unavailable",
         CryptoError::TransportError { .. } => "transport_error",
         CryptoError::CircuitOpen => "circuit_open",
+        CryptoError::NamespaceDenied { .. } => "namespace_denied",
     };
 
     error!(
@@ -11,6 +11,7 @@ pub mod fallback;
 pub mod key_manager;
 pub mod logging;
 pub mod metrics;
+pub mod namespace;
 
 #[cfg(test)]
 mod tests;
@@ -24,6 +25,7 @@ pub use fallback::FallbackHandler;
 pub use key_manager::{KeyId, KeyManager, KeyMetadata};
 pub use logging::{log_crypto_error, log_crypto_fallback, log_crypto_operation, log_key_rotation};
 pub use metrics::CryptoMetrics;
+pub use namespace::NamespacePolicy;
 
 /// Generated gRPC client code from crypto_service.proto
 pub mod proto {
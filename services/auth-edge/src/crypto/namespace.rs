@@ -0,0 +1,144 @@
+//! Key Namespace Policy
+//!
+//! Restricts which crypto-service key namespaces this workload may use.
+//! A service always owns its configured namespace; additional prefixes come
+//! from explicit configuration or from the workload's SPIFFE identity.
+//!
+//! Prefixes match whole `/`-separated segments: `auth-edge` allows
+//! `auth-edge` and `auth-edge/tenant-a`, but not `auth-edge-admin`.
+
+use std::collections::HashMap;
+
+use crate::crypto::error::CryptoError;
+use crate::crypto::key_manager::KeyId;
+use crate::mtls::spiffe::OwnedSpiffeId;
+
+/// Set of key namespace prefixes a workload is authorized for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespacePolicy {
+    allowed_prefixes: Vec<String>,
+}
+
+impl NamespacePolicy {
+    /// Creates a policy that only allows the service's own namespace
+    #[must_use]
+    pub fn own(namespace: impl Into<String>) -> Self {
+        Self {
+            allowed_prefixes: vec![namespace.into()],
+        }
+    }
+
+    /// Adds explicitly allowed namespace prefixes
+    #[must_use]
+    pub fn with_prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for prefix in prefixes {
+            let prefix = prefix.into();
+            if !prefix.is_empty() && !self.allowed_prefixes.contains(&prefix) {
+                self.allowed_prefixes.push(prefix);
+            }
+        }
+        self
+    }
+
+    /// Adds the prefixes mapped to every SPIFFE pattern the identity matches
+    ///
+    /// Patterns follow [`OwnedSpiffeId::matches`]: exact URIs or `/*` wildcards.
+    #[must_use]
+    pub fn with_spiffe_identity(
+        self,
+        identity: &OwnedSpiffeId,
+        mapping: &HashMap<String, Vec<String>>,
+    ) -> Self {
+        let mapped: Vec<String> = mapping
+            .iter()
+            .filter(|(pattern, _)| identity.matches(pattern))
+            .flat_map(|(_, prefixes)| prefixes.iter().cloned())
+            .collect();
+        self.with_prefixes(mapped)
+    }
+
+    /// Checks whether a namespace falls under an allowed prefix
+    #[must_use]
+    pub fn is_allowed(&self, namespace: &str) -> bool {
+        self.allowed_prefixes.iter().any(|prefix| {
+            namespace == prefix
+                || namespace
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// Authorizes a namespace
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::NamespaceDenied` if no prefix covers the namespace
+    pub fn authorize(&self, namespace: &str) -> Result<(), CryptoError> {
+        if self.is_allowed(namespace) {
+            Ok(())
+        } else {
+            Err(CryptoError::NamespaceDenied {
+                namespace: namespace.to_string(),
+            })
+        }
+    }
+
+    /// Authorizes the namespace of a key
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::NamespaceDenied` if the key is in a foreign namespace
+    pub fn authorize_key(&self, key_id: &KeyId) -> Result<(), CryptoError> {
+        self.authorize(&key_id.namespace)
+    }
+
+    /// Returns the allowed prefixes
+    #[must_use]
+    pub fn allowed_prefixes(&self) -> &[String] {
+        &self.allowed_prefixes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_own_namespace_and_tenants_allowed() {
+        let policy = NamespacePolicy::own("auth-edge");
+        assert!(policy.is_allowed("auth-edge"));
+        assert!(policy.is_allowed("auth-edge/tenant-a"));
+        assert!(!policy.is_allowed("auth-edge-admin"));
+        assert!(!policy.is_allowed("token"));
+    }
+
+    #[test]
+    fn test_authorize_key_rejects_foreign_namespace() {
+        let policy = NamespacePolicy::own("auth-edge");
+        let result = policy.authorize_key(&KeyId::new("token", "signing", 1));
+        assert!(matches!(result, Err(CryptoError::NamespaceDenied { .. })));
+    }
+
+    #[test]
+    fn test_spiffe_mapping_adds_prefixes() {
+        let identity = OwnedSpiffeId::parse("spiffe://prod.example/ns/auth/sa/auth-edge").unwrap();
+        let mapping = HashMap::from([
+            (
+                "spiffe://prod.example/ns/auth/*".to_string(),
+                vec!["shared".to_string()],
+            ),
+            (
+                "spiffe://prod.example/ns/billing/*".to_string(),
+                vec!["billing".to_string()],
+            ),
+        ]);
+
+        let policy = NamespacePolicy::own("auth-edge").with_spiffe_identity(&identity, &mapping);
+        assert!(policy.is_allowed("shared/sessions"));
+        assert!(!policy.is_allowed("billing"));
+    }
+}
@@ -3,11 +3,15 @@
 //! This module provides a unified error handling approach extending PlatformError
 //! from rust-common with domain-specific AuthEdgeError variants.
 
+use crate::config::{Config, ErrorVerbosity};
+use crate::mtls::OwnedSpiffeId;
 use chrono::{DateTime, Utc};
 use rust_common::PlatformError;
+use std::collections::HashMap;
 use std::time::Duration;
 use thiserror::Error;
 use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, StatusExt};
 use uuid::Uuid;
 
 /// Sensitive patterns that should be sanitized from error messages.
@@ -24,6 +28,9 @@ pub const SENSITIVE_PATTERNS: &[&str] = &[
     "private",
 ];
 
+/// Domain of the `google.rpc.ErrorInfo` attached to every error status.
+pub const ERROR_DOMAIN: &str = "auth-edge.auth-platform";
+
 /// Non-exhaustive error enum for forward compatibility.
 /// Extends PlatformError with domain-specific variants.
 #[non_exhaustive]
@@ -51,6 +58,15 @@ pub enum AuthEdgeError {
         valid_from: DateTime<Utc>,
     },
 
+    /// Token was issued longer ago than the maximum token age allows
+    #[error("Token issued at {issued_at} exceeds maximum age of {max_age:?}")]
+    TokenTooOld {
+        /// When the token was issued (`iat`)
+        issued_at: DateTime<Utc>,
+        /// Maximum age applied to the request
+        max_age: Duration,
+    },
+
     /// Token structure is malformed
     #[error("Token malformed: {reason}")]
     TokenMalformed {
@@ -65,6 +81,14 @@ pub enum AuthEdgeError {
         claims: Vec<String>,
     },
 
+    /// Token was revoked before it expired
+    #[error("Token revoked")]
+    TokenRevoked,
+
+    /// Token is bound to a client certificate other than the one presented
+    #[error("Token certificate binding mismatch")]
+    TokenBindingMismatch,
+
     /// SPIFFE ID extraction or validation failed
     #[error("SPIFFE ID error: {reason}")]
     SpiffeError {
@@ -86,6 +110,22 @@ pub enum AuthEdgeError {
         reason: String,
     },
 
+    /// Authorization policy denied access to the route
+    #[error("Access denied: {reason}")]
+    AccessDenied {
+        /// Why the policy denied access
+        reason: String,
+    },
+
+    /// Risk evaluation requires a stronger authentication than the token's
+    #[error("Step-up authentication required: {acr_values:?}")]
+    StepUpRequired {
+        /// Acceptable `acr` values, in order of preference
+        acr_values: Vec<String>,
+        /// Maximum age of the new authentication, if restricted
+        max_age: Option<Duration>,
+    },
+
     /// Request was rate limited
     #[error("Rate limit exceeded, retry after {retry_after:?}")]
     RateLimited {
@@ -114,14 +154,24 @@ pub enum ErrorCode {
     TokenInvalid,
     /// Token expired
     TokenExpired,
+    /// Token older than the maximum token age
+    TokenTooOld,
     /// Token malformed
     TokenMalformed,
     /// Claims invalid
     ClaimsInvalid,
+    /// Token revoked
+    TokenRevoked,
+    /// Token certificate binding mismatch
+    TokenBindingMismatch,
     /// SPIFFE error
     SpiffeError,
     /// Certificate error
     CertificateError,
+    /// Access denied by authorization policy
+    AccessDenied,
+    /// Step-up authentication required by risk evaluation
+    StepUpRequired,
     /// Service unavailable
     ServiceUnavailable,
     /// Rate limited
@@ -142,10 +192,15 @@ impl ErrorCode {
             Self::TokenMissing => "AUTH_TOKEN_MISSING",
             Self::TokenInvalid => "AUTH_TOKEN_INVALID",
             Self::TokenExpired => "AUTH_TOKEN_EXPIRED",
+            Self::TokenTooOld => "AUTH_TOKEN_TOO_OLD",
             Self::TokenMalformed => "AUTH_TOKEN_MALFORMED",
             Self::ClaimsInvalid => "AUTH_CLAIMS_INVALID",
+            Self::TokenRevoked => "AUTH_TOKEN_REVOKED",
+            Self::TokenBindingMismatch => "AUTH_TOKEN_BINDING_MISMATCH",
             Self::SpiffeError => "AUTH_SPIFFE_ERROR",
             Self::CertificateError => "AUTH_CERTIFICATE_ERROR",
+            Self::AccessDenied => "AUTH_ACCESS_DENIED",
+            Self::StepUpRequired => "AUTH_STEP_UP_REQUIRED",
             Self::ServiceUnavailable => "SERVICE_UNAVAILABLE",
             Self::RateLimited => "RATE_LIMITED",
             Self::Timeout => "TIMEOUT",
@@ -158,9 +213,15 @@ impl ErrorCode {
     #[must_use]
     pub const fn grpc_code(&self) -> Code {
         match self {
-            Self::TokenMissing | Self::TokenInvalid | Self::TokenExpired => Code::Unauthenticated,
+            Self::TokenMissing
+            | Self::TokenInvalid
+            | Self::TokenExpired
+            | Self::TokenTooOld
+            | Self::TokenRevoked
+            | Self::TokenBindingMismatch
+            | Self::StepUpRequired => Code::Unauthenticated,
             Self::TokenMalformed => Code::InvalidArgument,
-            Self::ClaimsInvalid => Code::PermissionDenied,
+            Self::ClaimsInvalid | Self::AccessDenied => Code::PermissionDenied,
             Self::SpiffeError | Self::CertificateError => Code::Unauthenticated,
             Self::ServiceUnavailable | Self::CircuitOpen => Code::Unavailable,
             Self::RateLimited => Code::ResourceExhausted,
@@ -181,6 +242,12 @@ pub struct ErrorResponse {
     pub correlation_id: Uuid,
     /// Optional retry-after duration
     pub retry_after: Option<Duration>,
+    /// Required claims the token lacks
+    pub missing_claims: Vec<String>,
+    /// `acr` values a step-up must satisfy
+    pub acr_values: Vec<String>,
+    /// Maximum age of the step-up authentication
+    pub max_age: Option<Duration>,
 }
 
 impl ErrorResponse {
@@ -200,12 +267,23 @@ impl ErrorResponse {
             AuthEdgeError::TokenNotYetValid { .. } => {
                 (ErrorCode::TokenMalformed, "Token is not yet valid".to_string(), None)
             }
+            AuthEdgeError::TokenTooOld { .. } => {
+                (ErrorCode::TokenTooOld, "Token exceeds the maximum token age".to_string(), None)
+            }
             AuthEdgeError::TokenMalformed { reason } => {
                 (ErrorCode::TokenMalformed, sanitize_message(reason), None)
             }
             AuthEdgeError::ClaimsInvalid { claims } => {
                 (ErrorCode::ClaimsInvalid, format!("Missing required claims: {claims:?}"), None)
             }
+            AuthEdgeError::TokenRevoked => {
+                (ErrorCode::TokenRevoked, "Token has been revoked".to_string(), None)
+            }
+            AuthEdgeError::TokenBindingMismatch => (
+                ErrorCode::TokenBindingMismatch,
+                "Token is not bound to the presented client certificate".to_string(),
+                None,
+            ),
             AuthEdgeError::SpiffeError { .. } => {
                 (ErrorCode::SpiffeError, "SPIFFE ID validation failed".to_string(), None)
             }
@@ -215,6 +293,12 @@ impl ErrorResponse {
             AuthEdgeError::JwkCacheError { .. } => {
                 (ErrorCode::Internal, "Key validation temporarily unavailable".to_string(), None)
             }
+            AuthEdgeError::AccessDenied { reason } => {
+                (ErrorCode::AccessDenied, format!("Access denied: {}", sanitize_message(reason)), None)
+            }
+            AuthEdgeError::StepUpRequired { .. } => {
+                (ErrorCode::StepUpRequired, "Step-up authentication required".to_string(), None)
+            }
             AuthEdgeError::RateLimited { retry_after } => {
                 (ErrorCode::RateLimited, "Rate limit exceeded".to_string(), Some(Duration::from_secs(*retry_after)))
             }
@@ -231,14 +315,105 @@ impl ErrorResponse {
             message,
             correlation_id,
             retry_after,
+            missing_claims: match error {
+                AuthEdgeError::ClaimsInvalid { claims } => claims.clone(),
+                _ => Vec::new(),
+            },
+            acr_values: match error {
+                AuthEdgeError::StepUpRequired { acr_values, .. } => acr_values.clone(),
+                _ => Vec::new(),
+            },
+            max_age: match error {
+                AuthEdgeError::StepUpRequired { max_age, .. } => *max_age,
+                _ => None,
+            },
         }
     }
 
-    /// Convert to gRPC Status.
+    /// Create an error response carrying as much detail as `verbosity` allows.
+    ///
+    /// `Minimal` responses carry only the error code and the step-up the
+    /// caller must complete; `Detailed` responses add the cause unless it may
+    /// contain secrets.
+    #[must_use]
+    pub fn with_verbosity(error: &AuthEdgeError, correlation_id: Uuid, verbosity: ErrorVerbosity) -> Self {
+        let mut response = Self::from_error(error, correlation_id);
+        match verbosity {
+            ErrorVerbosity::Minimal => {
+                response.message = response.code.as_str().to_string();
+                response.missing_claims.clear();
+            }
+            ErrorVerbosity::Detailed => {
+                if let Some(detail) = error.detail().filter(|detail| !contains_sensitive_info(detail)) {
+                    response.message = format!("{}: {detail}", response.message);
+                }
+            }
+        }
+        response
+    }
+
+    /// Convert to gRPC Status with `google.rpc` error details.
+    ///
+    /// Every status carries an `ErrorInfo` whose reason is the error code and
+    /// whose metadata holds the correlation ID. Retryable errors add a
+    /// `RetryInfo` with the retry-after delay, and missing claims are listed
+    /// as `BadRequest` field violations. A required step-up adds its
+    /// space-separated `acr_values` and `max_age` to the `ErrorInfo`.
     #[must_use]
     pub fn to_status(&self) -> Status {
-        let message = format!("{} [correlation_id: {}]", self.message, self.correlation_id);
-        Status::new(self.code.grpc_code(), message)
+        let mut metadata =
+            HashMap::from([("correlation_id".to_string(), self.correlation_id.to_string())]);
+        if !self.acr_values.is_empty() {
+            metadata.insert("acr_values".to_string(), self.acr_values.join(" "));
+        }
+        if let Some(max_age) = self.max_age {
+            metadata.insert("max_age".to_string(), max_age.as_secs().to_string());
+        }
+        let mut details = ErrorDetails::new();
+        details.set_error_info(self.code.as_str(), ERROR_DOMAIN, metadata);
+        if let Some(retry_after) = self.retry_after {
+            details.set_retry_info(Some(retry_after));
+        }
+        for claim in &self.missing_claims {
+            details.add_bad_request_violation(
+                format!("claims.{claim}"),
+                "required claim is missing or invalid",
+            );
+        }
+        Status::with_error_details(self.code.grpc_code(), self.message.clone(), details)
+    }
+}
+
+/// Error verbosity chosen by the caller's identity.
+///
+/// Mesh-internal callers, identified by a SPIFFE ID, get
+/// `MESH_ERROR_VERBOSITY`; every other caller gets `ERROR_VERBOSITY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorVerbosityPolicy {
+    /// Verbosity for callers without a workload identity
+    pub external: ErrorVerbosity,
+    /// Verbosity for callers presenting a SPIFFE ID
+    pub mesh: ErrorVerbosity,
+}
+
+impl ErrorVerbosityPolicy {
+    /// Creates the policy from `ERROR_VERBOSITY` and `MESH_ERROR_VERBOSITY`.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            external: config.error_verbosity,
+            mesh: config.mesh_error_verbosity,
+        }
+    }
+
+    /// Verbosity for a caller with the given SPIFFE ID, if any.
+    #[must_use]
+    pub fn for_caller(&self, spiffe_id: Option<&OwnedSpiffeId>) -> ErrorVerbosity {
+        if spiffe_id.is_some() {
+            self.mesh
+        } else {
+            self.external
+        }
     }
 }
 
@@ -272,11 +447,16 @@ impl AuthEdgeError {
             Self::TokenInvalid => ErrorCode::TokenInvalid,
             Self::TokenExpired { .. } => ErrorCode::TokenExpired,
             Self::TokenNotYetValid { .. } => ErrorCode::TokenMalformed,
+            Self::TokenTooOld { .. } => ErrorCode::TokenTooOld,
             Self::TokenMalformed { .. } => ErrorCode::TokenMalformed,
             Self::ClaimsInvalid { .. } => ErrorCode::ClaimsInvalid,
+            Self::TokenRevoked => ErrorCode::TokenRevoked,
+            Self::TokenBindingMismatch => ErrorCode::TokenBindingMismatch,
             Self::SpiffeError { .. } => ErrorCode::SpiffeError,
             Self::CertificateError { .. } => ErrorCode::CertificateError,
             Self::JwkCacheError { .. } => ErrorCode::Internal,
+            Self::AccessDenied { .. } => ErrorCode::AccessDenied,
+            Self::StepUpRequired { .. } => ErrorCode::StepUpRequired,
             Self::RateLimited { .. } => ErrorCode::RateLimited,
             Self::Timeout { .. } => ErrorCode::Timeout,
             Self::Platform(e) => match e {
@@ -295,6 +475,29 @@ impl AuthEdgeError {
         ErrorResponse::from_error(self, correlation_id).to_status()
     }
 
+    /// Convert to gRPC Status with correlation ID at the given verbosity.
+    #[must_use]
+    pub fn to_status_with(&self, correlation_id: Uuid, verbosity: ErrorVerbosity) -> Status {
+        ErrorResponse::with_verbosity(self, correlation_id, verbosity).to_status()
+    }
+
+    /// Cause of the error beyond its standard message, for detailed responses.
+    fn detail(&self) -> Option<String> {
+        match self {
+            Self::TokenExpired { expired_at } => Some(format!("expired at {expired_at}")),
+            Self::TokenNotYetValid { valid_from } => Some(format!("valid from {valid_from}")),
+            Self::TokenTooOld { issued_at, max_age } => {
+                Some(format!("issued at {issued_at}, maximum age {}s", max_age.as_secs()))
+            }
+            Self::SpiffeError { reason }
+            | Self::CertificateError { reason }
+            | Self::JwkCacheError { reason } => Some(reason.clone()),
+            Self::Timeout { duration } => Some(format!("after {}ms", duration.as_millis())),
+            Self::Platform(e) => Some(e.to_string()),
+            _ => None,
+        }
+    }
+
     /// Check if this error is retryable.
     /// Delegates to PlatformError for infrastructure errors.
     #[must_use]
@@ -418,6 +621,14 @@ impl From<std::io::Error> for AuthEdgeError {
     }
 }
 
+/// Lets layers producing `AuthEdgeError` wrap infallible services such as
+/// the gRPC router.
+impl From<std::convert::Infallible> for AuthEdgeError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -469,7 +680,89 @@ mod tests {
         let response = ErrorResponse::from_error(&error, correlation_id);
         
         assert_eq!(response.correlation_id, correlation_id);
-        let status = response.to_status();
-        assert!(status.message().contains(&correlation_id.to_string()));
+        let details = response.to_status().get_error_details();
+        let info = details.error_info().unwrap();
+        assert_eq!(info.reason, "AUTH_TOKEN_MISSING");
+        assert_eq!(info.domain, ERROR_DOMAIN);
+        assert_eq!(info.metadata["correlation_id"], correlation_id.to_string());
+        assert!(details.retry_info().is_none());
+        assert!(details.bad_request().is_none());
+    }
+
+    #[test]
+    fn test_status_details_for_retries_and_claims() {
+        let correlation_id = Uuid::new_v4();
+        let status = AuthEdgeError::RateLimited { retry_after: 12 }.to_status(correlation_id);
+        let retry = status.get_error_details().retry_info().cloned().unwrap();
+        assert_eq!(retry.retry_delay, Some(Duration::from_secs(12)));
+
+        let error = AuthEdgeError::ClaimsInvalid {
+            claims: vec!["tenant_id".to_string(), "scope".to_string()],
+        };
+        let status = error.to_status(correlation_id);
+        assert_eq!(status.code(), Code::PermissionDenied);
+        let violations = status.get_error_details().bad_request().cloned().unwrap().field_violations;
+        let fields: Vec<_> = violations.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["claims.tenant_id", "claims.scope"]);
+
+        // Minimal responses do not name the claims
+        let status = error.to_status_with(correlation_id, ErrorVerbosity::Minimal);
+        assert!(status.get_error_details().bad_request().is_none());
+        assert!(status.get_error_details().error_info().is_some());
+    }
+
+    #[test]
+    fn test_status_details_for_step_up() {
+        let error = AuthEdgeError::StepUpRequired {
+            acr_values: vec!["urn:acr:mfa".to_string(), "urn:acr:hwk".to_string()],
+            max_age: Some(Duration::from_secs(300)),
+        };
+        // The step-up is kept even in minimal responses
+        let status = error.to_status_with(Uuid::new_v4(), ErrorVerbosity::Minimal);
+        assert_eq!(status.code(), Code::Unauthenticated);
+        let info = status.get_error_details().error_info().cloned().unwrap();
+        assert_eq!(info.reason, "AUTH_STEP_UP_REQUIRED");
+        assert_eq!(info.metadata["acr_values"], "urn:acr:mfa urn:acr:hwk");
+        assert_eq!(info.metadata["max_age"], "300");
+    }
+
+    #[test]
+    fn test_error_verbosity_levels() {
+        let correlation_id = Uuid::new_v4();
+        let error = AuthEdgeError::SpiffeError {
+            reason: "trust domain evil.example is not allowed".to_string(),
+        };
+
+        let minimal = ErrorResponse::with_verbosity(&error, correlation_id, ErrorVerbosity::Minimal);
+        assert_eq!(minimal.message, "AUTH_SPIFFE_ERROR");
+        assert_eq!(minimal.code, ErrorCode::SpiffeError);
+
+        let detailed = ErrorResponse::with_verbosity(&error, correlation_id, ErrorVerbosity::Detailed);
+        assert_eq!(
+            detailed.message,
+            "SPIFFE ID validation failed: trust domain evil.example is not allowed"
+        );
+
+        // Causes that may carry secrets are never added
+        let leaky = AuthEdgeError::JwkCacheError {
+            reason: "fetch failed with api_key=abc123".to_string(),
+        };
+        let detailed = ErrorResponse::with_verbosity(&leaky, correlation_id, ErrorVerbosity::Detailed);
+        assert_eq!(detailed.message, "Key validation temporarily unavailable");
+        assert!(!detailed.to_status().message().contains("abc123"));
+    }
+
+    #[test]
+    fn test_error_verbosity_policy_by_caller() {
+        let policy = ErrorVerbosityPolicy {
+            external: ErrorVerbosity::Minimal,
+            mesh: ErrorVerbosity::Detailed,
+        };
+        let spiffe_id = OwnedSpiffeId {
+            trust_domain: "example.org".to_string(),
+            path: vec!["ns".to_string(), "prod".to_string(), "sa".to_string(), "orders".to_string()],
+        };
+        assert_eq!(policy.for_caller(Some(&spiffe_id)), ErrorVerbosity::Detailed);
+        assert_eq!(policy.for_caller(None), ErrorVerbosity::Minimal);
     }
 }
@@ -0,0 +1,155 @@
+//! Token Exchange
+//!
+//! Re-issues a verified federated identity as a platform access token through
+//! the token service, so downstream services only ever see platform tokens.
+//! The token service also returns a refresh token; it is discarded, since the
+//! external IdP stays the authority for the session.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_common::{CircuitBreaker, CircuitBreakerLayer, CircuitBreakerService, PlatformError};
+use tonic::transport::{Channel, Endpoint};
+use tower::Layer;
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::federation::{FederatedIdentity, FederatedIdp};
+use crate::proto::auth::token::token_service_client::TokenServiceClient;
+use crate::proto::auth::token::IssueTokenRequest;
+
+/// Platform access token issued for a federated identity
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IssuedToken {
+    /// Access token
+    pub access_token: String,
+    /// Token type, e.g. `Bearer`
+    pub token_type: String,
+    /// Expiration (Unix seconds)
+    pub expires_at: i64,
+}
+
+/// Token service client guarded by the token service circuit breaker
+pub struct TokenExchanger {
+    client: TokenServiceClient<CircuitBreakerService<Channel>>,
+}
+
+impl TokenExchanger {
+    /// Creates a lazily connected client for the configured token service
+    pub fn new(
+        config: &Config,
+        circuit_breaker: Arc<CircuitBreaker>,
+    ) -> Result<Self, AuthEdgeError> {
+        let channel = Endpoint::from_shared(config.token_service_url.to_string())
+            .map_err(|e| AuthEdgeError::Platform(PlatformError::InvalidInput(e.to_string())))?
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .connect_lazy();
+        let channel = CircuitBreakerLayer::new("token-service", circuit_breaker).layer(channel);
+        Ok(Self {
+            client: TokenServiceClient::new(channel),
+        })
+    }
+
+    /// Issues a platform access token for the identity
+    pub async fn exchange(
+        &self,
+        idp: &FederatedIdp,
+        identity: &FederatedIdentity,
+        scopes: Vec<String>,
+    ) -> Result<IssuedToken, AuthEdgeError> {
+        let request = IssueTokenRequest {
+            user_id: identity.subject.clone(),
+            session_id: String::new(),
+            scopes,
+            custom_claims: custom_claims(identity),
+            access_token_ttl_seconds: i32::try_from(idp.exchange_ttl_secs).unwrap_or(i32::MAX),
+            refresh_token_ttl_seconds: 0,
+            issuer: String::new(),
+            audience: String::new(),
+            client_id: String::new(),
+            audiences: Vec::new(),
+        };
+
+        let response = self
+            .client
+            .clone()
+            .issue_token_pair(request)
+            .await
+            .map_err(exchange_error)?
+            .into_inner();
+        Ok(IssuedToken {
+            access_token: response.access_token,
+            token_type: response.token_type,
+            expires_at: response.expires_at,
+        })
+    }
+}
+
+/// Error of a failed exchange; an open circuit keeps its own error
+fn exchange_error(status: tonic::Status) -> AuthEdgeError {
+    use std::error::Error;
+
+    match status.source().and_then(|err| err.downcast_ref::<PlatformError>()) {
+        Some(PlatformError::CircuitOpen { service }) => {
+            AuthEdgeError::Platform(PlatformError::circuit_open(service.clone()))
+        }
+        _ => AuthEdgeError::from(status),
+    }
+}
+
+/// Mapped claims as token service custom claims, tagged with the source IdP
+fn custom_claims(identity: &FederatedIdentity) -> HashMap<String, String> {
+    let mut claims: HashMap<String, String> = identity
+        .claims
+        .iter()
+        .filter(|(name, _)| name.as_str() != "sub")
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (name.clone(), value)
+        })
+        .collect();
+    claims.insert("idp".to_string(), identity.idp.clone());
+    claims
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_custom_claims_tag_idp_and_skip_subject() {
+        let identity = FederatedIdentity {
+            idp: "okta".to_string(),
+            subject: "u1".to_string(),
+            claims: BTreeMap::from([
+                ("sub".to_string(), serde_json::json!("u1")),
+                ("email".to_string(), serde_json::json!("u1@example.com")),
+                ("roles".to_string(), serde_json::json!(["admin"])),
+            ]),
+        };
+        let claims = custom_claims(&identity);
+
+        assert_eq!(claims["idp"], "okta");
+        assert_eq!(claims["email"], "u1@example.com");
+        assert_eq!(claims["roles"], r#"["admin"]"#);
+        assert!(!claims.contains_key("sub"));
+    }
+
+    #[test]
+    fn test_exchange_error_keeps_open_circuit() {
+        let status = tonic::Status::from_error(Box::new(PlatformError::circuit_open("token-service")));
+        assert!(matches!(
+            exchange_error(status),
+            AuthEdgeError::Platform(PlatformError::CircuitOpen { service }) if service == "token-service"
+        ));
+        assert!(!matches!(
+            exchange_error(tonic::Status::unavailable("down")),
+            AuthEdgeError::Platform(PlatformError::CircuitOpen { .. })
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+//! Upstream IdP Federation
+//!
+//! Accepts JWTs issued by external IdPs (Okta, Entra ID, ...) on the
+//! `ExchangeFederatedToken` route only; `ValidateToken` keeps accepting
+//! platform tokens alone. Each IdP's signing keys are located through its
+//! OpenID Connect discovery document at startup, tokens are verified against
+//! the IdP's own algorithms and audiences, and their claims are rewritten into
+//! platform claim names before optional re-issuance by the token service.
+
+pub mod exchange;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_common::scope::format_scope;
+use tracing::info;
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+use crate::jwt::discovery::OidcDiscovery;
+use crate::jwt::issuer::IssuerPolicy;
+use crate::jwt::jwk_cache::JwkCache;
+use crate::jwt::token::{Token, Unvalidated};
+
+pub use exchange::{IssuedToken, TokenExchanger};
+
+/// Rewrites external claim names into platform claim names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClaimMapping {
+    rules: HashMap<String, String>,
+}
+
+impl ClaimMapping {
+    /// Creates a mapping from external to platform claim names
+    pub fn new(rules: HashMap<String, String>) -> Self {
+        Self { rules }
+    }
+
+    /// Returns the mapped claims; claims without a rule are dropped
+    pub fn apply(&self, claims: &Claims) -> BTreeMap<String, serde_json::Value> {
+        self.rules
+            .iter()
+            .filter_map(|(external, platform)| {
+                claim_value(claims, external).map(|value| (platform.clone(), value))
+            })
+            .collect()
+    }
+}
+
+/// Reads a registered or custom claim as JSON
+fn claim_value(claims: &Claims, name: &str) -> Option<serde_json::Value> {
+    use serde_json::Value;
+
+    match name {
+        "iss" => Some(Value::from(claims.iss.as_str())),
+        "sub" => Some(Value::from(claims.sub.as_str())),
+        "aud" => Some(Value::from(claims.aud.clone())),
+        "exp" => Some(Value::from(claims.exp)),
+        "iat" => Some(Value::from(claims.iat)),
+        "jti" if !claims.jti.is_empty() => Some(Value::from(claims.jti.as_str())),
+        "session_id" => claims.session_id.as_deref().map(Value::from),
+        "scope" => claims.scopes.as_deref().map(|scopes| Value::from(format_scope(scopes))),
+        "scopes" => claims.scopes.clone().map(Value::from),
+        _ => claims.custom.get(name).cloned(),
+    }
+}
+
+/// Identity asserted by a verified federated token
+#[derive(Debug, Clone, PartialEq)]
+pub struct FederatedIdentity {
+    /// IdP name
+    pub idp: String,
+    /// Platform subject: the claim mapped to `sub`, else the external `sub`
+    pub subject: String,
+    /// Claims after mapping
+    pub claims: BTreeMap<String, serde_json::Value>,
+}
+
+/// Key set, policy and claim mapping for one external IdP
+pub struct FederatedIdp {
+    /// IdP name
+    pub name: String,
+    /// Issuer, algorithm and audience rules
+    pub policy: IssuerPolicy,
+    /// IdP's JWK cache
+    pub keys: Arc<JwkCache>,
+    /// Claim mapping into platform names
+    pub mapping: ClaimMapping,
+    /// Whether verified tokens are re-issued as platform tokens
+    pub exchange: bool,
+    /// Lifetime of re-issued access tokens; 0 uses the token service default
+    pub exchange_ttl_secs: u32,
+}
+
+impl FederatedIdp {
+    /// Maps verified external claims onto a platform identity
+    pub fn identity(&self, claims: &Claims) -> FederatedIdentity {
+        let claims_out = self.mapping.apply(claims);
+        let subject = match claims_out.get("sub") {
+            Some(serde_json::Value::String(sub)) => sub.clone(),
+            _ => claims.sub.clone(),
+        };
+        FederatedIdentity {
+            idp: self.name.clone(),
+            subject,
+            claims: claims_out,
+        }
+    }
+}
+
+/// Federated IdPs keyed by `iss` claim
+#[derive(Default)]
+pub struct FederationRegistry {
+    idps: HashMap<String, FederatedIdp>,
+}
+
+impl FederationRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discovers every configured IdP, or returns `None` when none are configured
+    ///
+    /// Fails when a discovery document cannot be fetched or names another issuer.
+    pub async fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        if config.federated_idps.is_empty() {
+            return Ok(None);
+        }
+
+        let mut registry = Self::new();
+        for idp in &config.federated_idps {
+            let discovery = OidcDiscovery::new(
+                idp.issuer.clone(),
+                idp.discovery_url.clone(),
+                Duration::from_secs(config.oidc_discovery_ttl_seconds),
+            )?;
+            let keys = JwkCache::for_discovered_issuer(config, Arc::new(discovery)).await?;
+            registry.register(FederatedIdp {
+                name: idp.name.clone(),
+                policy: IssuerPolicy::new(
+                    idp.issuer.clone(),
+                    idp.algorithms.clone(),
+                    idp.audiences.clone(),
+                ),
+                keys: Arc::new(keys),
+                mapping: ClaimMapping::new(idp.claim_mapping.clone()),
+                exchange: idp.exchange,
+                exchange_ttl_secs: idp.exchange_ttl_secs,
+            });
+        }
+        info!(idps = registry.len(), "Federated IdPs loaded");
+        Ok(Some(registry))
+    }
+
+    /// Registers an IdP, replacing any previous entry with the same issuer
+    pub fn register(&mut self, idp: FederatedIdp) {
+        self.idps.insert(idp.policy.issuer().to_string(), idp);
+    }
+
+    /// Verifies a federated token and returns its IdP and claims
+    pub async fn verify(
+        &self,
+        raw_token: &str,
+    ) -> Result<(&FederatedIdp, Claims), AuthEdgeError> {
+        let unvalidated = Token::<Unvalidated>::parse(raw_token)?;
+        let idp = self
+            .idps
+            .get(&unvalidated.unverified_issuer()?)
+            .ok_or_else(|| AuthEdgeError::ClaimsInvalid {
+                claims: vec!["iss".to_string()],
+            })?;
+
+        idp.policy.check_algorithm(unvalidated.algorithm())?;
+        let signature_validated = unvalidated.validate_signature(&idp.keys).await?;
+        if let Some(claims) = signature_validated.peek_claims() {
+            idp.policy.check_claims(claims)?;
+        }
+        let validated = signature_validated.validate_claims(&[])?;
+        Ok((idp, validated.claims().clone()))
+    }
+
+    /// Number of registered IdPs
+    pub fn len(&self) -> usize {
+        self.idps.len()
+    }
+
+    /// Whether no IdPs are registered
+    pub fn is_empty(&self) -> bool {
+        self.idps.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::discovery::DiscoveryDocument;
+
+    fn entra_claims() -> Claims {
+        let custom = [
+            ("oid", serde_json::json!("00000000-aaaa")),
+            ("groups", serde_json::json!(["admins"])),
+            ("ipaddr", serde_json::json!("203.0.113.7")),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+        Claims {
+            iss: "https://login.microsoftonline.com/tenant/v2.0".to_string(),
+            sub: "pairwise-sub".to_string(),
+            aud: vec!["api://edge".to_string()],
+            exp: 2_000,
+            iat: 1_000,
+            nbf: None,
+            jti: String::new(),
+            session_id: None,
+            scopes: None,
+            custom,
+        }
+    }
+
+    #[test]
+    fn test_mapping_renames_and_drops_claims() {
+        let mapping = ClaimMapping::new(
+            [("oid", "sub"), ("groups", "roles"), ("jti", "external_jti")]
+                .into_iter()
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+        );
+        let mapped = mapping.apply(&entra_claims());
+
+        assert_eq!(mapped["sub"], "00000000-aaaa");
+        assert_eq!(mapped["roles"], serde_json::json!(["admins"]));
+        assert!(!mapped.contains_key("ipaddr"));
+        assert!(!mapped.contains_key("external_jti"));
+    }
+
+    #[test]
+    fn test_discovery_document_and_audience_string() {
+        let document: DiscoveryDocument = serde_json::from_str(
+            r#"{"issuer":"https://acme.okta.com","jwks_uri":"https://acme.okta.com/v1/keys","response_types_supported":["code"]}"#,
+        )
+        .unwrap();
+        assert_eq!(document.jwks_uri, "https://acme.okta.com/v1/keys");
+
+        let claims: Claims = serde_json::from_str(
+            r#"{"iss":"https://acme.okta.com","sub":"u1","aud":"api://default","exp":2000,"iat":1000,"scp":["openid"]}"#,
+        )
+        .unwrap();
+        assert_eq!(claims.aud, vec!["api://default"]);
+        assert!(claims.jti.is_empty());
+    }
+}
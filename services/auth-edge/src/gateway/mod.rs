@@ -0,0 +1,997 @@
+//! HTTP/REST Gateway
+//!
+//! Serves `/validate`, `/introspect` and `/healthz` as JSON for consumers
+//! that cannot speak gRPC. Requests are handled by the same
+//! `AuthEdgeServiceImpl` and API key authenticator as the tonic server, so
+//! validation, logging and the `RATE_LIMIT_POLICY` limits checked by the
+//! service behave identically on both listeners. On top of those, the gateway
+//! alone limits each caller under `RATE_LIMIT_*` with its adaptive rate
+//! limiter, which the tonic server does not consult. When a CAEP transmitter
+//! is configured, pushed Security Event Tokens are accepted on `/caep/events`
+//! (RFC 8935); they are authenticated by their signature rather than an API
+//! key. Tokens from federated IdPs are accepted on `/federation/token` only.
+//! Other services holding an API key scoped to `DowngradeClientTrust` report
+//! misbehaving clients on `/rate-limit/trust`, which marks them suspicious in
+//! the adaptive rate limiter. Keys scoped to `ClearRateLimitBans` lift bans
+//! on `/rate-limit/bans/clear`, and keys scoped to `SetLogLevel` override the
+//! log filter for a while on `/admin/log-level`. Keys scoped to
+//! `RateLimitSnapshot` export and import the adaptive rate limiter's client
+//! state on `/rate-limit/snapshot` for blue/green deploys. Every rate limited
+//! response, allowed or not, carries the `RateLimit-Limit`,
+//! `RateLimit-Remaining`, `RateLimit-Reset` and `RateLimit-Policy` fields of
+//! the client's limit.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use auth_caep::CaepError;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Request as HttpRequest, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Extension, Json, Router};
+use prost_types::value::Kind;
+use prost_types::{Struct as ProtoStruct, Value as ProtoValue};
+use rust_common::{LogLevelControl, PlatformError};
+use serde::{Deserialize, Serialize};
+use tonic::transport::server::TcpConnectInfo;
+use tonic::{Code, Status};
+use tracing::{info, warn};
+
+use crate::api_key::{ApiKeyAuthenticator, ApiKeyIdentity, API_KEY_HEADER};
+use crate::error::ErrorCode;
+use crate::grpc::AuthEdgeServiceImpl;
+use crate::middleware::{
+    ConcurrencyLimit, ConcurrencyLimitLayer, LoadShedLayer, RateLimitHeaders, ShedResponse,
+};
+use crate::proto::auth::v1::auth_edge_service_server::AuthEdgeService;
+use crate::proto::auth::v1::{
+    ExchangeFederatedTokenRequest, ExchangeFederatedTokenResponse, IntrospectTokenRequest,
+    IntrospectTokenResponse, TokenErrorCode, ValidateTokenRequest, ValidateTokenResponse,
+};
+use crate::rate_limiter::{
+    AdaptiveRateLimiter, InFlightRequests, RateLimitDecision, RateLimiterSnapshot,
+    QUOTA_WARNING_HEADER,
+};
+
+/// Shared state of the HTTP gateway
+#[derive(Clone)]
+pub struct GatewayState {
+    service: Arc<AuthEdgeServiceImpl>,
+    api_keys: Option<Arc<ApiKeyAuthenticator>>,
+    limiter: Arc<AdaptiveRateLimiter>,
+    in_flight: InFlightRequests,
+    concurrency: ConcurrencyLimit,
+    log_level: Option<LogLevelControl>,
+}
+
+impl GatewayState {
+    /// Creates gateway state over the gRPC service implementation
+    pub fn new(
+        service: Arc<AuthEdgeServiceImpl>,
+        api_keys: Option<Arc<ApiKeyAuthenticator>>,
+        limiter: Arc<AdaptiveRateLimiter>,
+    ) -> Self {
+        Self {
+            service,
+            api_keys,
+            limiter,
+            in_flight: InFlightRequests::default(),
+            concurrency: ConcurrencyLimit::new(0, 0),
+            log_level: None,
+        }
+    }
+
+    /// Counts gateway requests among the requests in flight
+    pub fn with_in_flight(mut self, in_flight: InFlightRequests) -> Self {
+        self.in_flight = in_flight;
+        self
+    }
+
+    /// Takes request slots from the gRPC server's `limit`
+    pub fn with_concurrency(mut self, limit: ConcurrencyLimit) -> Self {
+        self.concurrency = limit;
+        self
+    }
+
+    /// Serves `/admin/log-level` over the process log filter
+    pub fn with_log_level(mut self, log_level: LogLevelControl) -> Self {
+        self.log_level = Some(log_level);
+        self
+    }
+}
+
+/// Builds the gateway router
+pub fn router(state: GatewayState) -> Router {
+    Router::new()
+        .route("/validate", post(validate))
+        .route("/introspect", post(introspect))
+        .route("/federation/token", post(federation_token))
+        .route("/rate-limit/trust", post(downgrade_trust))
+        .route("/rate-limit/bans/clear", post(clear_bans))
+        .route(
+            "/rate-limit/snapshot",
+            get(export_snapshot)
+                .put(import_snapshot)
+                .layer(DefaultBodyLimit::max(MAX_SNAPSHOT_BYTES)),
+        )
+        .route(
+            "/admin/log-level",
+            put(set_log_level).get(log_level).delete(reset_log_level),
+        )
+        .layer(middleware::from_fn_with_state(state.clone(), guard))
+        .layer(ConcurrencyLimitLayer::new(state.concurrency.clone()))
+        .layer(LoadShedLayer::new(state.concurrency.clone()))
+        .route("/healthz", get(healthz))
+        .route("/caep/events", post(caep_events))
+        .with_state(state)
+}
+
+/// Serves the gateway on every address until the future is dropped
+pub async fn serve(addrs: &[SocketAddr], router: Router) -> std::io::Result<()> {
+    let listeners = rust_common::listen::bind(addrs)?;
+    info!(addrs = ?addrs, "HTTP gateway listening");
+    futures::future::try_join_all(listeners.into_iter().map(|listener| {
+        axum::serve(
+            listener,
+            router.clone().into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .into_future()
+    }))
+    .await
+    .map(|_| ())
+}
+
+/// JSON body of `POST /validate`
+#[derive(Debug, Default, Deserialize)]
+pub struct ValidateBody {
+    /// JWT to validate; falls back to the `Authorization: Bearer` header
+    #[serde(default)]
+    pub token: String,
+    /// Claims that must be present
+    #[serde(default)]
+    pub required_claims: Vec<String>,
+    /// Route to authorize, as `METHOD /path`
+    #[serde(default)]
+    pub route: String,
+    /// Tenant the request targets
+    #[serde(default)]
+    pub tenant: String,
+    /// End user's IP address, for risk evaluation
+    #[serde(default)]
+    pub client_ip: String,
+    /// Further risk signals, such as device posture
+    #[serde(default)]
+    pub risk_context: HashMap<String, String>,
+}
+
+/// JSON body of `POST /introspect`
+#[derive(Debug, Default, Deserialize)]
+pub struct IntrospectBody {
+    /// Token to introspect; falls back to the `Authorization: Bearer` header
+    #[serde(default)]
+    pub token: String,
+    /// `access_token` or `refresh_token`
+    #[serde(default)]
+    pub token_type_hint: String,
+}
+
+/// JSON body of `POST /federation/token`
+#[derive(Debug, Default, Deserialize)]
+pub struct FederationBody {
+    /// External JWT; falls back to the `Authorization: Bearer` header
+    #[serde(default)]
+    pub subject_token: String,
+    /// Scopes requested for the platform token
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// JSON body of `POST /rate-limit/trust`
+#[derive(Debug, Deserialize)]
+pub struct TrustDowngradeBody {
+    /// Rate limiter key of the client (`api-key:<id>` or peer IP)
+    pub client_id: String,
+    /// Why the reporting service distrusts the client
+    #[serde(default)]
+    pub reason: String,
+    /// Seconds the client stays suspicious, capped at a day
+    #[serde(default = "default_trust_hold_seconds")]
+    pub hold_seconds: u64,
+}
+
+/// JSON body of `POST /rate-limit/bans/clear`
+#[derive(Debug, Default, Deserialize)]
+pub struct ClearBansBody {
+    /// Client whose bans to lift (`api-key:<id>`, peer IP or SPIFFE ID);
+    /// empty lifts every ban
+    #[serde(default)]
+    pub client_id: String,
+}
+
+/// JSON result of `POST /rate-limit/bans/clear`
+#[derive(Debug, Serialize)]
+pub struct ClearBansResult {
+    /// Number of bans lifted
+    pub cleared: usize,
+}
+
+/// JSON result of `PUT /rate-limit/snapshot`
+#[derive(Debug, Serialize)]
+pub struct ImportSnapshotResult {
+    /// Number of clients imported
+    pub imported: usize,
+}
+
+/// JSON body of `PUT /admin/log-level`
+#[derive(Debug, Deserialize)]
+pub struct SetLogLevelBody {
+    /// `EnvFilter` directives, e.g. `info,auth_edge::jwt=debug`
+    pub directives: String,
+    /// Seconds before the default filter is restored, capped at
+    /// `LOG_LEVEL_OVERRIDE_MAX`; 0 or absent uses the cap
+    #[serde(default)]
+    pub duration_seconds: u64,
+}
+
+fn default_trust_hold_seconds() -> u64 {
+    900
+}
+
+/// Longest downgrade another service can request
+const MAX_TRUST_HOLD: Duration = Duration::from_secs(86_400);
+
+/// Largest rate limiter snapshot accepted by `PUT /rate-limit/snapshot`
+const MAX_SNAPSHOT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Error returned by the gateway
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ErrorBody {
+    /// Stable error code
+    pub code: String,
+    /// Human-readable message
+    pub message: String,
+}
+
+/// JSON result of `POST /validate`
+#[derive(Debug, Serialize)]
+pub struct ValidateResult {
+    /// Whether the token is valid
+    pub valid: bool,
+    /// Token subject
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub subject: String,
+    /// Token issuer
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub issuer: String,
+    /// Token audiences
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub audiences: Vec<String>,
+    /// Token scopes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+    /// JWT ID
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub jwt_id: String,
+    /// All claims
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claims: Option<serde_json::Value>,
+    /// Why validation failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorBody>,
+    /// Step-up the holder must complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_up_required: Option<StepUpBody>,
+}
+
+/// Step-up authentication required by risk evaluation
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct StepUpBody {
+    /// Acceptable `acr` values, in order of preference
+    pub acr_values: Vec<String>,
+    /// Maximum age in seconds of the new authentication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<i64>,
+}
+
+impl From<ValidateTokenResponse> for ValidateResult {
+    fn from(response: ValidateTokenResponse) -> Self {
+        Self {
+            valid: response.valid,
+            subject: response.subject,
+            issuer: response.issuer,
+            audiences: response.audiences,
+            scopes: response.scopes,
+            jwt_id: response.jwt_id,
+            claims: response.claims.as_ref().map(struct_to_json),
+            error: response.error.map(|error| ErrorBody {
+                code: TokenErrorCode::try_from(error.code)
+                    .unwrap_or(TokenErrorCode::Unspecified)
+                    .as_str_name()
+                    .to_string(),
+                message: error.message,
+            }),
+            step_up_required: response.step_up_required.map(|step_up| StepUpBody {
+                acr_values: step_up.acr_values,
+                max_age: (step_up.max_age > 0).then_some(step_up.max_age),
+            }),
+        }
+    }
+}
+
+impl ValidateResult {
+    /// 200 when valid, 403 when the route was denied, else 401
+    fn status(&self) -> StatusCode {
+        let access_denied = TokenErrorCode::AccessDenied.as_str_name();
+        match &self.error {
+            _ if self.valid => StatusCode::OK,
+            Some(error) if error.code == access_denied => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// `WWW-Authenticate` challenge of a required step-up (RFC 9470)
+    fn step_up_challenge(&self) -> Option<HeaderValue> {
+        let step_up = self.step_up_required.as_ref()?;
+        let mut challenge = format!(
+            "Bearer error=\"insufficient_user_authentication\", acr_values=\"{}\"",
+            step_up.acr_values.join(" ")
+        );
+        if let Some(max_age) = step_up.max_age {
+            challenge.push_str(&format!(", max_age={max_age}"));
+        }
+        HeaderValue::from_str(&challenge).ok()
+    }
+}
+
+/// JSON result of `POST /federation/token`
+#[derive(Debug, Serialize)]
+pub struct FederationResult {
+    /// IdP that issued the subject token
+    pub idp: String,
+    /// Platform subject
+    pub subject: String,
+    /// Claims after mapping
+    pub claims: serde_json::Value,
+    /// Re-issued platform access token
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub access_token: String,
+    /// Token type of the platform token
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub token_type: String,
+    /// Expiration of the platform token (Unix timestamp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+impl From<ExchangeFederatedTokenResponse> for FederationResult {
+    fn from(response: ExchangeFederatedTokenResponse) -> Self {
+        Self {
+            idp: response.idp,
+            subject: response.subject,
+            claims: response
+                .claims
+                .as_ref()
+                .map(struct_to_json)
+                .unwrap_or_else(|| serde_json::json!({})),
+            access_token: response.access_token,
+            token_type: response.token_type,
+            expires_at: (response.expires_at != 0).then_some(response.expires_at),
+        }
+    }
+}
+
+/// JSON result of `POST /introspect` (RFC 7662 field names)
+#[derive(Debug, Serialize)]
+pub struct IntrospectResult {
+    /// Whether the token is active
+    pub active: bool,
+    /// Space-separated scopes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Client identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Human-readable username
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Token type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// Expiration (Unix timestamp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    /// Issued at (Unix timestamp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    /// Not before (Unix timestamp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    /// Subject
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Audience
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Issuer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// JWT ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+impl From<IntrospectTokenResponse> for IntrospectResult {
+    fn from(response: IntrospectTokenResponse) -> Self {
+        Self {
+            active: response.active,
+            scope: response.scope,
+            client_id: response.client_id,
+            username: response.username,
+            token_type: response.token_type,
+            exp: response.exp,
+            iat: response.iat,
+            nbf: response.nbf,
+            sub: response.sub,
+            aud: response.aud,
+            iss: response.iss,
+            jti: response.jti,
+        }
+    }
+}
+
+/// Body of `/healthz`, served as is without serializing per request
+const HEALTHZ_BODY: &str = r#"{"status":"ok"}"#;
+
+async fn healthz() -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("application/json")),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+        ],
+        HEALTHZ_BODY,
+    )
+}
+
+async fn validate(
+    State(state): State<GatewayState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+    headers: HeaderMap,
+    Json(body): Json<ValidateBody>,
+) -> Response {
+    let message = ValidateTokenRequest {
+        token: token_or_bearer(body.token, &headers),
+        required_claims: body.required_claims,
+        route: body.route,
+        tenant: body.tenant,
+        client_ip: body.client_ip,
+        risk_context: body.risk_context,
+        ..Default::default()
+    };
+    let request = grpc_request(message, peer, identity.map(|Extension(id)| id));
+
+    match state.service.validate_token(request).await {
+        Ok(response) => {
+            let result = ValidateResult::from(response.into_inner());
+            let mut response = (result.status(), Json(&result)).into_response();
+            if let Some(challenge) = result.step_up_challenge() {
+                response.headers_mut().insert(header::WWW_AUTHENTICATE, challenge);
+            }
+            response
+        }
+        Err(status) => status_response(&status),
+    }
+}
+
+async fn introspect(
+    State(state): State<GatewayState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+    headers: HeaderMap,
+    Json(body): Json<IntrospectBody>,
+) -> Response {
+    let message = IntrospectTokenRequest {
+        token: token_or_bearer(body.token, &headers),
+        token_type_hint: body.token_type_hint,
+    };
+    let request = grpc_request(message, peer, identity.map(|Extension(id)| id));
+
+    match state.service.introspect_token(request).await {
+        Ok(response) => Json(IntrospectResult::from(response.into_inner())).into_response(),
+        Err(status) => status_response(&status),
+    }
+}
+
+async fn federation_token(
+    State(state): State<GatewayState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+    headers: HeaderMap,
+    Json(body): Json<FederationBody>,
+) -> Response {
+    let message = ExchangeFederatedTokenRequest {
+        subject_token: token_or_bearer(body.subject_token, &headers),
+        scopes: body.scopes,
+    };
+    let request = grpc_request(message, peer, identity.map(|Extension(id)| id));
+
+    match state.service.exchange_federated_token(request).await {
+        Ok(response) => Json(FederationResult::from(response.into_inner())).into_response(),
+        Err(status) => status_response(&status),
+    }
+}
+
+/// Marks a client reported by another service as suspicious
+async fn downgrade_trust(
+    State(state): State<GatewayState>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+    Json(body): Json<TrustDowngradeBody>,
+) -> Response {
+    let Some(Extension(identity)) = identity else {
+        return status_response(&Status::unauthenticated("API key required"));
+    };
+    if body.client_id.is_empty() {
+        return status_response(&Status::invalid_argument("client_id is required"));
+    }
+
+    let hold = Duration::from_secs(body.hold_seconds).min(MAX_TRUST_HOLD);
+    state.limiter.downgrade_trust(&body.client_id, hold).await;
+    warn!(
+        client_id = %body.client_id,
+        reason = %body.reason,
+        reported_by = %identity.key_id,
+        hold_secs = hold.as_secs(),
+        "Client trust downgraded"
+    );
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Lifts rate limiter bans of one client, or of every client
+///
+/// Bans under keyed rate limits are lifted for every key containing the
+/// client ID as a dimension value.
+async fn clear_bans(
+    State(state): State<GatewayState>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+    Json(body): Json<ClearBansBody>,
+) -> Response {
+    let Some(Extension(identity)) = identity else {
+        return status_response(&Status::unauthenticated("API key required"));
+    };
+
+    let client = (!body.client_id.is_empty()).then_some(body.client_id.as_str());
+    let mut cleared = match client {
+        Some(client) => usize::from(state.limiter.clear_ban(client).await),
+        None => state.limiter.clear_bans(|_| true).await,
+    };
+    cleared += state.service.keyed_rate_limits().clear_bans(client).await;
+    warn!(
+        client_id = %body.client_id,
+        cleared_by = %identity.key_id,
+        cleared,
+        "Rate limit bans cleared"
+    );
+    Json(ClearBansResult { cleared }).into_response()
+}
+
+/// Exports the adaptive rate limiter's client state
+async fn export_snapshot(
+    State(state): State<GatewayState>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+) -> Response {
+    let Some(Extension(identity)) = identity else {
+        return status_response(&Status::unauthenticated("API key required"));
+    };
+
+    let snapshot = state.limiter.export_snapshot();
+    info!(
+        clients = snapshot.clients.len(),
+        exported_by = %identity.key_id,
+        "Rate limiter state exported"
+    );
+    Json(snapshot).into_response()
+}
+
+/// Imports client state exported by another replica
+async fn import_snapshot(
+    State(state): State<GatewayState>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+    Json(snapshot): Json<RateLimiterSnapshot>,
+) -> Response {
+    let Some(Extension(identity)) = identity else {
+        return status_response(&Status::unauthenticated("API key required"));
+    };
+
+    match state.limiter.import_snapshot(&snapshot) {
+        Ok(imported) => {
+            warn!(imported, imported_by = %identity.key_id, "Rate limiter state imported");
+            Json(ImportSnapshotResult { imported }).into_response()
+        }
+        Err(reason) => status_response(&Status::invalid_argument(reason)),
+    }
+}
+
+/// Returns the log filter in effect
+async fn log_level(
+    State(state): State<GatewayState>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+) -> Response {
+    if identity.is_none() {
+        return status_response(&Status::unauthenticated("API key required"));
+    }
+    match &state.log_level {
+        Some(control) => Json(control.status()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Overrides the log filter until the requested duration passes
+async fn set_log_level(
+    State(state): State<GatewayState>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+    Json(body): Json<SetLogLevelBody>,
+) -> Response {
+    let Some(Extension(identity)) = identity else {
+        return status_response(&Status::unauthenticated("API key required"));
+    };
+    let Some(control) = &state.log_level else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match control.set(&body.directives, Duration::from_secs(body.duration_seconds)) {
+        Ok(status) => {
+            warn!(
+                directives = %status.directives,
+                reverts_in_secs = ?status.reverts_in_secs,
+                set_by = %identity.key_id,
+                "Log level overridden"
+            );
+            Json(status).into_response()
+        }
+        Err(err) => status_response(&log_level_status(&err)),
+    }
+}
+
+/// Restores the default log filter
+async fn reset_log_level(
+    State(state): State<GatewayState>,
+    identity: Option<Extension<ApiKeyIdentity>>,
+) -> Response {
+    let Some(Extension(identity)) = identity else {
+        return status_response(&Status::unauthenticated("API key required"));
+    };
+    let Some(control) = &state.log_level else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match control.reset() {
+        Ok(status) => {
+            warn!(reset_by = %identity.key_id, "Log level reset");
+            Json(status).into_response()
+        }
+        Err(err) => status_response(&log_level_status(&err)),
+    }
+}
+
+fn log_level_status(err: &PlatformError) -> Status {
+    match err {
+        PlatformError::InvalidInput(message) => Status::invalid_argument(message.clone()),
+        _ => Status::unavailable(err.to_string()),
+    }
+}
+
+/// Receives a pushed CAEP Security Event Token
+async fn caep_events(State(state): State<GatewayState>, body: String) -> Response {
+    let Some(subscriber) = state.service.caep_subscriber() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match subscriber.process(body.trim()).await {
+        Ok(result) => {
+            info!(event_id = %result.event_id, "CAEP event accepted");
+            StatusCode::ACCEPTED.into_response()
+        }
+        Err(err) => {
+            warn!(error = %err, "CAEP event rejected");
+            let code = match err {
+                CaepError::VerificationError(_) | CaepError::JwksFetchError(_) => "invalid_key",
+                _ => "invalid_request",
+            };
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "err": code, "description": err.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Authenticates API key callers and applies the adaptive rate limit
+async fn guard(
+    State(state): State<GatewayState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    mut req: HttpRequest,
+    next: Next,
+) -> Response {
+    let _in_flight = state.in_flight.start();
+    let rpc = rpc_for_path(req.uri().path());
+    let presented = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .map(|value| value.to_str().unwrap_or_default().to_string());
+
+    let mut client = peer.ip().to_string();
+    if let (Some(authenticator), Some(presented)) = (&state.api_keys, presented) {
+        match authenticator.authenticate(&presented, rpc).await {
+            Ok(identity) => {
+                client = format!("api-key:{}", identity.key_id);
+                req.extensions_mut().insert(identity);
+            }
+            Err(err) => {
+                warn!(rpc = %rpc, error = %err, "API key authentication failed");
+                return status_response(&err.to_status());
+            }
+        }
+    }
+
+    if let RateLimitDecision::Denied { retry_after } = state.limiter.check(&client).await {
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ErrorBody {
+                code: ErrorCode::RateLimited.as_str().to_string(),
+                message: "Rate limit exceeded".to_string(),
+            }),
+        )
+            .into_response();
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(retry_after.as_secs().max(1)),
+        );
+        let info = state.limiter.get_limit_info(&client).await;
+        RateLimitHeaders::from_info(&info).insert_headers(response.headers_mut());
+        return response;
+    }
+
+    let mut response = next.run(req).await;
+    state
+        .limiter
+        .record_outcome(&client, response.status().is_success())
+        .await;
+    let info = state.limiter.get_limit_info(&client).await;
+    RateLimitHeaders::from_info(&info).insert_headers(response.headers_mut());
+    let warning = state
+        .service
+        .quota_warnings()
+        .level(info.utilization)
+        .map(|level| format!("{level}%"));
+    if let Some(value) = warning.and_then(|w| HeaderValue::try_from(w).ok()) {
+        response.headers_mut().insert(QUOTA_WARNING_HEADER, value);
+    }
+    response
+}
+
+/// gRPC method name used for API key scopes on each gateway route
+fn rpc_for_path(path: &str) -> &'static str {
+    match path {
+        "/introspect" => "IntrospectToken",
+        "/federation/token" => "ExchangeFederatedToken",
+        "/rate-limit/trust" => "DowngradeClientTrust",
+        "/rate-limit/bans/clear" => "ClearRateLimitBans",
+        "/admin/log-level" => "SetLogLevel",
+        "/rate-limit/snapshot" => "RateLimitSnapshot",
+        _ => "ValidateToken",
+    }
+}
+
+/// Wraps a message as a tonic request carrying the caller's identity
+fn grpc_request<T>(
+    message: T,
+    peer: SocketAddr,
+    identity: Option<ApiKeyIdentity>,
+) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request.extensions_mut().insert(TcpConnectInfo {
+        local_addr: None,
+        remote_addr: Some(peer),
+    });
+    if let Some(identity) = identity {
+        request.extensions_mut().insert(identity);
+    }
+    request
+}
+
+fn token_or_bearer(token: String, headers: &HeaderMap) -> String {
+    if !token.is_empty() {
+        return token;
+    }
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Maps a gRPC status onto the equivalent HTTP response
+impl ShedResponse for Response {
+    fn shed(retry_after: u64) -> Self {
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ErrorBody {
+                code: ErrorCode::RateLimited.as_str().to_string(),
+                message: "Too many requests in flight".to_string(),
+            }),
+        )
+            .into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+        response
+    }
+}
+
+fn status_response(status: &Status) -> Response {
+    (
+        http_status(status.code()),
+        Json(ErrorBody {
+            code: format!("{:?}", status.code()),
+            message: status.message().to_string(),
+        }),
+    )
+        .into_response()
+}
+
+fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Cancelled | Code::Unknown | Code::Internal | Code::DataLoss => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn struct_to_json(value: &ProtoStruct) -> serde_json::Value {
+    serde_json::Value::Object(
+        value
+            .fields
+            .iter()
+            .map(|(key, value)| (key.clone(), value_to_json(value)))
+            .collect(),
+    )
+}
+
+fn value_to_json(value: &ProtoValue) -> serde_json::Value {
+    match &value.kind {
+        None | Some(Kind::NullValue(_)) => serde_json::Value::Null,
+        Some(Kind::BoolValue(b)) => serde_json::Value::Bool(*b),
+        Some(Kind::NumberValue(n)) => serde_json::Number::from_f64(*n)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Some(Kind::StringValue(s)) => serde_json::Value::String(s.clone()),
+        Some(Kind::ListValue(list)) => {
+            serde_json::Value::Array(list.values.iter().map(value_to_json).collect())
+        }
+        Some(Kind::StructValue(s)) => struct_to_json(s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::auth::v1::{StepUpRequired, TokenValidationError};
+    use prost_types::ListValue;
+
+    #[test]
+    fn test_http_status_mapping() {
+        assert_eq!(http_status(Code::Unauthenticated), StatusCode::UNAUTHORIZED);
+        assert_eq!(http_status(Code::PermissionDenied), StatusCode::FORBIDDEN);
+        assert_eq!(http_status(Code::ResourceExhausted), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(http_status(Code::Unavailable), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_rpc_for_path_matches_api_key_scopes() {
+        assert_eq!(rpc_for_path("/validate"), "ValidateToken");
+        assert_eq!(rpc_for_path("/introspect"), "IntrospectToken");
+        assert_eq!(rpc_for_path("/federation/token"), "ExchangeFederatedToken");
+        assert_eq!(rpc_for_path("/rate-limit/trust"), "DowngradeClientTrust");
+        assert_eq!(rpc_for_path("/rate-limit/bans/clear"), "ClearRateLimitBans");
+        assert_eq!(rpc_for_path("/admin/log-level"), "SetLogLevel");
+        assert_eq!(rpc_for_path("/rate-limit/snapshot"), "RateLimitSnapshot");
+    }
+
+    #[test]
+    fn test_token_falls_back_to_bearer_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer abc.def.ghi"));
+        assert_eq!(token_or_bearer(String::new(), &headers), "abc.def.ghi");
+        assert_eq!(token_or_bearer("body".to_string(), &headers), "body");
+        assert_eq!(token_or_bearer(String::new(), &HeaderMap::new()), "");
+    }
+
+    #[test]
+    fn test_validate_result_from_response() {
+        let mut fields = std::collections::BTreeMap::new();
+        fields.insert(
+            "roles".to_string(),
+            ProtoValue {
+                kind: Some(Kind::ListValue(ListValue {
+                    values: vec![ProtoValue {
+                        kind: Some(Kind::StringValue("admin".to_string())),
+                    }],
+                })),
+            },
+        );
+        let valid = ValidateResult::from(ValidateTokenResponse {
+            valid: true,
+            subject: "user-1".to_string(),
+            claims: Some(ProtoStruct { fields }),
+            ..Default::default()
+        });
+        assert_eq!(valid.claims, Some(serde_json::json!({ "roles": ["admin"] })));
+        assert!(valid.error.is_none());
+
+        let invalid = ValidateResult::from(ValidateTokenResponse {
+            error: Some(TokenValidationError {
+                code: TokenErrorCode::Expired as i32,
+                message: "Token expired".to_string(),
+                details: Default::default(),
+            }),
+            ..Default::default()
+        });
+        assert!(!invalid.valid);
+        assert_eq!(
+            invalid.error,
+            Some(ErrorBody {
+                code: "TOKEN_ERROR_CODE_EXPIRED".to_string(),
+                message: "Token expired".to_string(),
+            })
+        );
+        assert_eq!(valid.status(), StatusCode::OK);
+        assert_eq!(invalid.status(), StatusCode::UNAUTHORIZED);
+
+        let denied = ValidateResult::from(ValidateTokenResponse {
+            error: Some(TokenValidationError {
+                code: TokenErrorCode::AccessDenied as i32,
+                message: "Access denied".to_string(),
+                details: Default::default(),
+            }),
+            ..Default::default()
+        });
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+        assert!(denied.step_up_challenge().is_none());
+
+        let step_up = ValidateResult::from(ValidateTokenResponse {
+            error: Some(TokenValidationError {
+                code: TokenErrorCode::StepUpRequired as i32,
+                message: "Step-up authentication required".to_string(),
+                details: Default::default(),
+            }),
+            step_up_required: Some(StepUpRequired {
+                acr_values: vec!["urn:acr:mfa".to_string()],
+                max_age: 300,
+            }),
+            ..Default::default()
+        });
+        assert_eq!(step_up.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            step_up.step_up_challenge().unwrap(),
+            "Bearer error=\"insufficient_user_authentication\", acr_values=\"urn:acr:mfa\", max_age=300"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Admin API
+//!
+//! `AuthEdgeAdminService` lets operators inspect and control a running
+//! instance: circuit breakers, rate limiter clients, the cached JWK set, the
+//! configuration in effect and, with `CHAOS_ENABLED`, injected faults. Every
+//! call must come over mTLS from a workload whose SPIFFE ID matches
+//! `ADMIN_SPIFFE_IDS`; API keys are not accepted.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rust_common::CircuitBreaker;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::chaos::{self, ChaosInjector, ChaosTarget};
+use crate::config::Config;
+use crate::grpc::AuthEdgeServiceImpl;
+use crate::mtls::{OwnedSpiffeId, SpiffeIdPattern};
+use crate::proto::auth::v1::auth_edge_admin_service_server::AuthEdgeAdminService;
+use crate::proto::auth::v1::*;
+use crate::rate_limiter::AdaptiveRateLimiter;
+use crate::reload::ConfigReloader;
+
+/// Workloads allowed to call the admin API
+#[derive(Debug, Clone, Default)]
+pub struct AdminCallers {
+    patterns: Vec<SpiffeIdPattern>,
+}
+
+impl AdminCallers {
+    /// Creates the allowlist; empty admits nobody
+    pub fn new(patterns: Vec<SpiffeIdPattern>) -> Self {
+        Self { patterns }
+    }
+
+    /// Whether no caller is allowed, so the admin API is not served
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Authenticates an admin caller by its mTLS SPIFFE ID
+    pub fn authorize(&self, spiffe_id: Option<&OwnedSpiffeId>) -> Result<(), Status> {
+        let Some(spiffe_id) = spiffe_id else {
+            return Err(Status::unauthenticated(
+                "Admin API requires a client certificate with a SPIFFE ID",
+            ));
+        };
+        if self.patterns.iter().any(|pattern| pattern.matches(spiffe_id)) {
+            Ok(())
+        } else {
+            Err(Status::permission_denied("Caller is not an admin workload"))
+        }
+    }
+}
+
+/// Admin API over the running service's components
+pub struct AdminServiceImpl {
+    service: Arc<AuthEdgeServiceImpl>,
+    limiter: Arc<AdaptiveRateLimiter>,
+    reloader: Option<Arc<ConfigReloader>>,
+    callers: AdminCallers,
+}
+
+impl AdminServiceImpl {
+    /// Creates the admin API, or `None` when `ADMIN_SPIFFE_IDS` is empty
+    pub fn from_config(
+        config: &Config,
+        service: Arc<AuthEdgeServiceImpl>,
+        limiter: Arc<AdaptiveRateLimiter>,
+    ) -> Option<Self> {
+        let callers = AdminCallers::new(config.admin_spiffe_ids.clone());
+        (!callers.is_empty()).then(|| Self {
+            service,
+            limiter,
+            reloader: None,
+            callers,
+        })
+    }
+
+    /// Reports the configuration of the reloader instead of the startup one
+    pub fn with_reloader(mut self, reloader: Arc<ConfigReloader>) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// Authenticates the caller, returning its SPIFFE ID for the audit log
+    fn authorize<T>(&self, request: &Request<T>, rpc: &str) -> Result<String, Status> {
+        let spiffe_id = self.service.caller_spiffe_id(request);
+        if let Err(status) = self.callers.authorize(spiffe_id.as_ref()) {
+            warn!(
+                rpc = rpc,
+                spiffe_id = spiffe_id.as_ref().map(OwnedSpiffeId::to_uri).unwrap_or_default(),
+                "Admin API call rejected"
+            );
+            return Err(status);
+        }
+        Ok(spiffe_id.map(|id| id.to_uri()).unwrap_or_default())
+    }
+
+    fn circuit_breaker(&self, name: &str) -> Result<&Arc<CircuitBreaker>, Status> {
+        self.service
+            .named_circuit_breakers()
+            .into_iter()
+            .find(|(circuit, _)| *circuit == name)
+            .map(|(_, breaker)| breaker)
+            .ok_or_else(|| Status::not_found(format!("Unknown circuit breaker {name:?}")))
+    }
+
+    /// Chaos injector, or `FailedPrecondition` without `CHAOS_ENABLED`
+    fn chaos(&self) -> Result<&'static ChaosInjector, Status> {
+        chaos::injector()
+            .ok_or_else(|| Status::failed_precondition("Chaos injection is not enabled"))
+    }
+
+    fn faults(chaos: &ChaosInjector) -> ListChaosFaultsResponse {
+        let now = Instant::now();
+        ListChaosFaultsResponse {
+            faults: chaos
+                .active()
+                .into_iter()
+                .map(|(target, fault)| ChaosFaultStatus {
+                    target: target.to_string(),
+                    latency_ms: u64::try_from(fault.latency.as_millis()).unwrap_or(u64::MAX),
+                    error_rate: fault.error_rate,
+                    expires_in_seconds: fault.expires_at.saturating_duration_since(now).as_secs(),
+                })
+                .collect(),
+        }
+    }
+
+    fn kids(&self) -> ListJwksResponse {
+        ListJwksResponse {
+            kids: self.service.jwt_validator().default_keys().local_kids(),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl AuthEdgeAdminService for AdminServiceImpl {
+    async fn get_circuit_breakers(
+        &self,
+        request: Request<GetCircuitBreakersRequest>,
+    ) -> Result<Response<GetCircuitBreakersResponse>, Status> {
+        self.authorize(&request, "GetCircuitBreakers")?;
+        let mut circuit_breakers = Vec::new();
+        for (name, breaker) in self.service.named_circuit_breakers() {
+            circuit_breakers.push(CircuitBreakerStatus {
+                name: name.to_string(),
+                state: breaker.state().await.as_str().to_string(),
+                failures: breaker.failure_count(),
+            });
+        }
+        Ok(Response::new(GetCircuitBreakersResponse { circuit_breakers }))
+    }
+
+    async fn get_rate_limiter_stats(
+        &self,
+        request: Request<GetRateLimiterStatsRequest>,
+    ) -> Result<Response<GetRateLimiterStatsResponse>, Status> {
+        self.authorize(&request, "GetRateLimiterStats")?;
+        let stats = self.limiter.stats();
+        Ok(Response::new(GetRateLimiterStatsResponse {
+            tracked_clients: stats.tracked_clients as u64,
+            banned_clients: stats.banned_clients as u64,
+            system_load: stats.system_load,
+        }))
+    }
+
+    async fn list_jwks(
+        &self,
+        request: Request<ListJwksRequest>,
+    ) -> Result<Response<ListJwksResponse>, Status> {
+        self.authorize(&request, "ListJwks")?;
+        Ok(Response::new(self.kids()))
+    }
+
+    async fn get_config(
+        &self,
+        request: Request<GetConfigRequest>,
+    ) -> Result<Response<GetConfigResponse>, Status> {
+        self.authorize(&request, "GetConfig")?;
+        let config = match &self.reloader {
+            Some(reloader) => reloader.current().redacted(),
+            None => self.service.config.redacted(),
+        };
+        Ok(Response::new(GetConfigResponse {
+            config: format!("{config:#?}"),
+        }))
+    }
+
+    async fn reset_circuit_breaker(
+        &self,
+        request: Request<ResetCircuitBreakerRequest>,
+    ) -> Result<Response<ResetCircuitBreakerResponse>, Status> {
+        let caller = self.authorize(&request, "ResetCircuitBreaker")?;
+        let name = request.into_inner().name;
+        let breaker = self.circuit_breaker(&name)?;
+        let previous_state = breaker.state().await;
+        breaker.reset().await;
+        info!(
+            caller = %caller,
+            circuit = %name,
+            previous_state = previous_state.as_str(),
+            "Circuit breaker reset"
+        );
+        Ok(Response::new(ResetCircuitBreakerResponse {
+            previous_state: previous_state.as_str().to_string(),
+        }))
+    }
+
+    async fn clear_ban(
+        &self,
+        request: Request<ClearBanRequest>,
+    ) -> Result<Response<ClearBanResponse>, Status> {
+        let caller = self.authorize(&request, "ClearBan")?;
+        let client_id = request.into_inner().client_id;
+        if client_id.is_empty() {
+            return Err(Status::invalid_argument("client_id is required"));
+        }
+        let cleared = self.limiter.clear_ban(&client_id).await;
+        info!(caller = %caller, client_id = %client_id, cleared, "Rate limit ban cleared");
+        Ok(Response::new(ClearBanResponse { cleared }))
+    }
+
+    async fn refresh_jwks(
+        &self,
+        request: Request<RefreshJwksRequest>,
+    ) -> Result<Response<ListJwksResponse>, Status> {
+        let caller = self.authorize(&request, "RefreshJwks")?;
+        let keys = self.service.jwt_validator().default_keys();
+        if request.get_ref().accept_shrink {
+            warn!(caller = %caller, "Accepting the next JWKS without shrink checks");
+            keys.accept_next_keyset();
+        }
+        keys.force_refresh()
+            .await
+            .map_err(|e| Status::unavailable(format!("JWKS refresh failed: {e}")))?;
+        let response = self.kids();
+        info!(caller = %caller, keys = response.kids.len(), "JWKS refreshed");
+        Ok(Response::new(response))
+    }
+
+    async fn set_chaos_fault(
+        &self,
+        request: Request<SetChaosFaultRequest>,
+    ) -> Result<Response<ListChaosFaultsResponse>, Status> {
+        let caller = self.authorize(&request, "SetChaosFault")?;
+        let chaos = self.chaos()?;
+        let req = request.into_inner();
+        let target: ChaosTarget = req.target.parse().map_err(Status::invalid_argument)?;
+        let fault = chaos
+            .set(
+                target,
+                Duration::from_millis(req.latency_ms),
+                req.error_rate,
+                Duration::from_secs(req.duration_seconds),
+            )
+            .map_err(Status::invalid_argument)?;
+        match fault {
+            Some(fault) => {
+                let expires_in = fault.expires_at.saturating_duration_since(Instant::now());
+                warn!(
+                    caller = %caller,
+                    target = %target,
+                    latency_ms = req.latency_ms,
+                    error_rate = fault.error_rate,
+                    expires_in_secs = expires_in.as_secs(),
+                    "Chaos fault set"
+                );
+            }
+            None => info!(caller = %caller, target = %target, "Chaos fault cleared"),
+        }
+        Ok(Response::new(Self::faults(chaos)))
+    }
+
+    async fn list_chaos_faults(
+        &self,
+        request: Request<ListChaosFaultsRequest>,
+    ) -> Result<Response<ListChaosFaultsResponse>, Status> {
+        self.authorize(&request, "ListChaosFaults")?;
+        Ok(Response::new(Self::faults(self.chaos()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_callers_matched_by_spiffe_id() {
+        let callers = AdminCallers::new(vec!["spiffe://example.org/ns/ops/sa/*".parse().unwrap()]);
+        let operator = OwnedSpiffeId::parse("spiffe://example.org/ns/ops/sa/oncall").unwrap();
+        let other = OwnedSpiffeId::parse("spiffe://example.org/ns/orders/sa/api").unwrap();
+
+        assert!(callers.authorize(Some(&operator)).is_ok());
+        assert_eq!(
+            callers.authorize(Some(&other)).unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(callers.authorize(None).unwrap_err().code(), tonic::Code::Unauthenticated);
+        assert!(AdminCallers::default().authorize(Some(&operator)).is_err());
+    }
+}
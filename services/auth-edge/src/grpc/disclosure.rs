@@ -0,0 +1,175 @@
+//! Claim Disclosure Policies
+//!
+//! Limits which claims a `ValidateTokenResponse` carries, per caller SPIFFE
+//! identity, so each consumer only receives the claims it needs. Policies are
+//! matched in configuration order; callers without a matching policy (or
+//! without a client certificate) get the default claim set.
+
+use std::collections::HashSet;
+
+use crate::config::{ClaimDisclosureConfig, Config};
+use crate::mtls::OwnedSpiffeId;
+use crate::proto::auth::v1::ValidateTokenResponse;
+
+/// Wildcard claim name disclosing every claim
+const ALL_CLAIMS: &str = "*";
+
+/// Set of claims a caller may receive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimSet {
+    /// Every claim
+    All,
+    /// Only the named claims
+    Only(HashSet<String>),
+}
+
+impl ClaimSet {
+    /// Builds a claim set from configured names; `*` selects all claims
+    pub fn from_names(names: &[String]) -> Self {
+        if names.iter().any(|name| name == ALL_CLAIMS) {
+            Self::All
+        } else {
+            Self::Only(names.iter().cloned().collect())
+        }
+    }
+
+    /// Whether the claim may be disclosed
+    pub fn allows(&self, claim: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(claims) => claims.contains(claim),
+        }
+    }
+
+    /// Removes undisclosed claims from a validation response
+    pub fn apply(&self, response: &mut ValidateTokenResponse) {
+        if matches!(self, Self::All) {
+            return;
+        }
+        if !self.allows("sub") {
+            response.subject.clear();
+        }
+        if !self.allows("iss") {
+            response.issuer.clear();
+        }
+        if !self.allows("aud") {
+            response.audiences.clear();
+        }
+        if !self.allows("scopes") {
+            response.scopes.clear();
+        }
+        if !self.allows("jti") {
+            response.jwt_id.clear();
+        }
+        if let Some(claims) = response.claims.as_mut() {
+            claims.fields.retain(|name, _| self.allows(name));
+        }
+    }
+}
+
+/// Claim disclosure policies keyed by caller SPIFFE ID pattern
+#[derive(Debug, Clone)]
+pub struct DisclosurePolicies {
+    policies: Vec<(String, ClaimSet)>,
+    default: ClaimSet,
+}
+
+impl DisclosurePolicies {
+    /// Creates policies matched in order, falling back to `default`
+    pub fn new(policies: &[ClaimDisclosureConfig], default: &[String]) -> Self {
+        Self {
+            policies: policies
+                .iter()
+                .map(|policy| (policy.caller.clone(), ClaimSet::from_names(&policy.claims)))
+                .collect(),
+            default: ClaimSet::from_names(default),
+        }
+    }
+
+    /// Builds the policies from configuration
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(&config.claim_disclosure, &config.claim_disclosure_default)
+    }
+
+    /// Returns the claim set for a caller
+    pub fn for_caller(&self, caller: Option<&OwnedSpiffeId>) -> &ClaimSet {
+        caller
+            .and_then(|id| {
+                self.policies
+                    .iter()
+                    .find(|(pattern, _)| id.matches(pattern))
+                    .map(|(_, claims)| claims)
+            })
+            .unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{value::Kind, Struct, Value};
+
+    fn policies() -> DisclosurePolicies {
+        let policies = [
+            ClaimDisclosureConfig {
+                caller: "spiffe://example.org/ns/billing/sa/gateway".to_string(),
+                claims: vec!["sub".to_string(), "plan".to_string()],
+            },
+            ClaimDisclosureConfig {
+                caller: "spiffe://example.org/ns/iam/*".to_string(),
+                claims: vec!["*".to_string()],
+            },
+        ];
+        DisclosurePolicies::new(&policies, &["sub".to_string()])
+    }
+
+    fn response() -> ValidateTokenResponse {
+        let fields = ["sub", "iss", "plan", "email"]
+            .into_iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    Value {
+                        kind: Some(Kind::StringValue(format!("{name}-value"))),
+                    },
+                )
+            })
+            .collect();
+        ValidateTokenResponse {
+            valid: true,
+            subject: "user-1".to_string(),
+            issuer: "https://idp.example".to_string(),
+            audiences: vec!["api".to_string()],
+            jwt_id: "jti-1".to_string(),
+            claims: Some(Struct { fields }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_billing_gateway_gets_minimal_claims() {
+        let policies = policies();
+        let billing = OwnedSpiffeId::parse("spiffe://example.org/ns/billing/sa/gateway").unwrap();
+
+        let mut response = response();
+        policies.for_caller(Some(&billing)).apply(&mut response);
+
+        assert_eq!(response.subject, "user-1");
+        assert!(response.issuer.is_empty());
+        assert!(response.audiences.is_empty());
+        assert!(response.jwt_id.is_empty());
+        let names: Vec<&String> = response.claims.as_ref().unwrap().fields.keys().collect();
+        assert_eq!(names, vec!["plan", "sub"]);
+    }
+
+    #[test]
+    fn test_wildcard_and_default_policies() {
+        let policies = policies();
+        let iam = OwnedSpiffeId::parse("spiffe://example.org/ns/iam/sa/admin").unwrap();
+        let other = OwnedSpiffeId::parse("spiffe://example.org/ns/web/sa/app").unwrap();
+
+        assert_eq!(policies.for_caller(Some(&iam)), &ClaimSet::All);
+        assert!(!policies.for_caller(Some(&other)).allows("plan"));
+        assert!(policies.for_caller(None).allows("sub"));
+    }
+}
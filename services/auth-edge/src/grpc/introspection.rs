@@ -0,0 +1,198 @@
+//! Introspection Callers
+//!
+//! `IntrospectToken` answers only authenticated resource servers (RFC 7662
+//! section 4). A caller authenticates with its mTLS SPIFFE ID or with an API
+//! key (client credentials). When resource servers are configured, each one
+//! only learns about tokens issued for its audience and only sees the scopes
+//! relevant to it; unlisted callers are rejected.
+
+use tonic::Status;
+
+use crate::api_key::ApiKeyIdentity;
+use crate::config::{Config, IntrospectionClientConfig};
+use crate::mtls::OwnedSpiffeId;
+use crate::proto::auth::v1::IntrospectTokenResponse;
+
+/// Wildcard scope pattern returning every scope
+const ALL_SCOPES: &str = "*";
+
+/// Resource server allowed to introspect tokens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceServer {
+    config: IntrospectionClientConfig,
+}
+
+impl ResourceServer {
+    /// Creates a resource server from its configuration
+    pub fn new(config: IntrospectionClientConfig) -> Self {
+        Self { config }
+    }
+
+    /// Resource server name
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Whether the authenticated caller is this resource server
+    fn matches(&self, spiffe_id: Option<&OwnedSpiffeId>, api_key: Option<&ApiKeyIdentity>) -> bool {
+        let by_spiffe = match (&self.config.spiffe_id, spiffe_id) {
+            (Some(pattern), Some(id)) => id.matches(pattern),
+            _ => false,
+        };
+        let by_api_key = match (&self.config.api_key_id, api_key) {
+            (Some(key_id), Some(identity)) => *key_id == identity.key_id,
+            _ => false,
+        };
+        by_spiffe || by_api_key
+    }
+
+    /// Whether a scope is relevant to this resource server; patterns ending
+    /// in `*` match by prefix
+    pub fn allows_scope(&self, scope: &str) -> bool {
+        self.config.scopes.iter().any(|pattern| match pattern.strip_suffix(ALL_SCOPES) {
+            Some(prefix) => scope.starts_with(prefix),
+            None => scope == pattern,
+        })
+    }
+
+    /// Limits an introspection response to what this resource server may see
+    ///
+    /// Tokens whose audiences do not include the resource server's audience
+    /// are reported inactive, so a resource server cannot probe tokens meant
+    /// for others.
+    pub fn shape(&self, response: &mut IntrospectTokenResponse, audiences: &[String]) {
+        if !response.active {
+            return;
+        }
+        if let Some(audience) = &self.config.audience {
+            if !audiences.contains(audience) {
+                *response = IntrospectTokenResponse {
+                    active: false,
+                    ..Default::default()
+                };
+                return;
+            }
+            response.aud = Some(audience.clone());
+        }
+        if let Some(scope) = response.scope.take() {
+            let scopes: Vec<&str> = scope
+                .split_whitespace()
+                .filter(|scope| self.allows_scope(scope))
+                .collect();
+            response.scope = (!scopes.is_empty()).then(|| scopes.join(" "));
+        }
+    }
+}
+
+/// Resource servers allowed to call `IntrospectToken`
+#[derive(Debug, Clone, Default)]
+pub struct IntrospectionClients {
+    clients: Vec<ResourceServer>,
+}
+
+impl IntrospectionClients {
+    /// Creates the allowlist; empty admits any authenticated caller
+    pub fn new(clients: &[IntrospectionClientConfig]) -> Self {
+        Self {
+            clients: clients.iter().cloned().map(ResourceServer::new).collect(),
+        }
+    }
+
+    /// Builds the allowlist from configuration
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(&config.introspection_clients)
+    }
+
+    /// Authenticates an introspection caller
+    ///
+    /// Returns the caller's resource server, or `None` when no resource
+    /// servers are configured and the caller gets the full response.
+    pub fn authorize(
+        &self,
+        spiffe_id: Option<&OwnedSpiffeId>,
+        api_key: Option<&ApiKeyIdentity>,
+    ) -> Result<Option<&ResourceServer>, Status> {
+        if spiffe_id.is_none() && api_key.is_none() {
+            return Err(Status::unauthenticated(
+                "Token introspection requires a client certificate or API key",
+            ));
+        }
+        if self.clients.is_empty() {
+            return Ok(None);
+        }
+        self.clients
+            .iter()
+            .find(|client| client.matches(spiffe_id, api_key))
+            .map(Some)
+            .ok_or_else(|| Status::permission_denied("Caller is not an introspection client"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orders() -> IntrospectionClientConfig {
+        IntrospectionClientConfig {
+            name: "orders".to_string(),
+            spiffe_id: Some("spiffe://example.org/ns/orders/*".to_string()),
+            api_key_id: Some("orders-rs".to_string()),
+            audience: Some("https://orders.example.com".to_string()),
+            scopes: vec!["orders:*".to_string(), "openid".to_string()],
+        }
+    }
+
+    fn active(scope: &str) -> IntrospectTokenResponse {
+        IntrospectTokenResponse {
+            active: true,
+            sub: Some("user-1".to_string()),
+            scope: Some(scope.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_anonymous_callers_rejected() {
+        let open = IntrospectionClients::default();
+        let err = open.authorize(None, None).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+        let key = ApiKeyIdentity { key_id: "any".to_string() };
+        assert!(open.authorize(None, Some(&key)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_callers_matched_by_spiffe_id_or_api_key() {
+        let clients = IntrospectionClients::new(&[orders()]);
+        let workload = OwnedSpiffeId::parse("spiffe://example.org/ns/orders/sa/api").unwrap();
+        let other = OwnedSpiffeId::parse("spiffe://example.org/ns/billing/sa/api").unwrap();
+        let key = ApiKeyIdentity { key_id: "orders-rs".to_string() };
+
+        assert_eq!(clients.authorize(Some(&workload), None).unwrap().unwrap().name(), "orders");
+        assert_eq!(clients.authorize(None, Some(&key)).unwrap().unwrap().name(), "orders");
+        assert_eq!(
+            clients.authorize(Some(&other), None).unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_shape_filters_scopes_and_audience() {
+        let orders = ResourceServer::new(orders());
+        let audiences = vec!["https://orders.example.com".to_string()];
+
+        let mut response = active("openid orders:read billing:write orders:write");
+        orders.shape(&mut response, &audiences);
+        assert!(response.active);
+        assert_eq!(response.scope.as_deref(), Some("openid orders:read orders:write"));
+        assert_eq!(response.aud.as_deref(), Some("https://orders.example.com"));
+
+        let mut response = active("billing:write");
+        orders.shape(&mut response, &audiences);
+        assert!(response.scope.is_none());
+
+        let mut response = active("orders:read");
+        orders.shape(&mut response, &["https://billing.example.com".to_string()]);
+        assert_eq!(response, IntrospectTokenResponse::default());
+    }
+}
@@ -3,32 +3,122 @@
 //! Implements the AuthEdgeService with type-state JWT validation,
 //! Tower middleware stack, and proper error handling with correlation IDs.
 
-use crate::config::Config;
-use crate::error::{AuthEdgeError, ErrorResponse, ErrorCode as AuthErrorCode};
-use crate::jwt::{JwkCache, JwtValidator};
-use crate::mtls::SpiffeValidator;
-use crate::observability::AuthEdgeLogger;
+pub mod admin;
+pub mod disclosure;
+pub mod introspection;
+pub mod private_claims;
+pub mod upstream_introspection;
+
+use crate::api_key::ApiKeyIdentity;
+use crate::authz::Authorizer;
+use crate::caep::{CaepSubscriber, SubjectDenylist};
+use crate::config::{Config, ErrorVerbosity};
+use crate::grpc::disclosure::{ClaimSet, DisclosurePolicies};
+use crate::grpc::introspection::{IntrospectionClients, ResourceServer};
+use crate::grpc::private_claims::PrivateClaims;
+use crate::grpc::upstream_introspection::UpstreamIntrospector;
+use crate::error::{AuthEdgeError, ErrorResponse, ErrorCode as AuthErrorCode, ErrorVerbosityPolicy};
+use crate::federation::{FederationRegistry, TokenExchanger};
+use crate::jwt::{
+    AudienceKeyPolicy, BypassValidator, CheckStatus as JwtCheckStatus, ClaimExpressions, Claims, ClaimsRefResolver, DecodeLimits, IssuerRegistry, JwkCache,
+    JwtValidator, RevocationChecker, TokenAgePolicy, ValidationCache, ValidationCoalescer,
+};
+use crate::mtls::binding::{self, MTLS_BINDING};
+use crate::mtls::{
+    ChainVerifier, OwnedSpiffeId, PeerCertificates, SpiffeError, SpiffeValidator, WorkloadIdentity,
+};
+use crate::observability::{
+    AccessDecision, AccessEvent, AccessLog, AuditEvent, AuditLog, AuthEdgeLogger, BypassMetrics,
+    CircuitBreakerMetrics, CircuitBreakerReporter, RevocationMetrics, ValidationCacheMetrics,
+    ValidationCoalescerMetrics,
+};
 use crate::proto::auth::v1::auth_edge_service_server::AuthEdgeService;
 use crate::proto::auth::v1::*;
+use crate::middleware::trace_context;
+use crate::middleware::{CorrelationId, RateLimitHeaders, RateLimitSlot};
+use crate::rate_limiter::{KeyedRateLimiter, QuotaWarnings, RateLimitDecision, RateLimitKey};
+use crate::risk::{NoopRiskEvaluator, RiskEvaluator, RiskInput};
 use prost_types::Struct as ProtoStruct;
 use prost_types::value::Kind;
 use prost_types::Value as ProtoValue;
-use rust_common::{CircuitBreaker, CircuitBreakerConfig};
+use auth_linkerd::TraceContext;
+use futures::{Stream, StreamExt};
+use rust_common::scope::format_scope;
+use rust_common::span_attributes::{ALLOW, DENY};
+use rust_common::{record_auth, AuthSpan, CircuitBreaker, TenantBuckets, TenantMetrics};
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
-use tonic::{Request, Response, Status};
+use std::time::{Duration, Instant};
+use tonic::{Request, Response, Status, Streaming};
 use tracing::{error, info, instrument};
 use uuid::Uuid;
 
+/// Maximum validations processed concurrently per ValidateTokenStream call.
+const STREAM_MAX_IN_FLIGHT: usize = 64;
+
+/// Metadata carrying a base64 OCSP response stapled for the client certificate.
+const STAPLED_OCSP_HEADER: &str = "x-client-cert-ocsp";
+
+/// Response stream returned by ValidateTokenStream.
+type ValidateTokenResponseStream =
+    Pin<Box<dyn Stream<Item = Result<ValidateTokenStreamResponse, Status>> + Send + 'static>>;
+
 /// Auth Edge Service implementation with modern patterns.
 pub struct AuthEdgeServiceImpl {
     config: Config,
-    jwt_validator: JwtValidator,
+    jwt_validator: Arc<JwtValidator>,
     token_service_cb: Arc<CircuitBreaker>,
     iam_service_cb: Arc<CircuitBreaker>,
     spiffe_validator: SpiffeValidator,
+    chain_verifier: Option<Arc<ChainVerifier>>,
     logger: Arc<AuthEdgeLogger>,
+    access_log: Option<Arc<AccessLog>>,
+    audit_log: Option<Arc<AuditLog>>,
+    disclosure: Arc<DisclosurePolicies>,
+    private_claims: Option<Arc<PrivateClaims>>,
+    introspection_clients: IntrospectionClients,
+    upstream_introspector: Option<Arc<UpstreamIntrospector>>,
+    caep: Option<Arc<CaepSubscriber>>,
+    federation: Option<Arc<FederationRegistry>>,
+    token_exchanger: Option<TokenExchanger>,
+    authorizer: Option<Arc<Authorizer>>,
+    risk_evaluator: Arc<dyn RiskEvaluator>,
+    rate_limits: Arc<KeyedRateLimiter>,
+    quota_warnings: QuotaWarnings,
+    tenant_metrics: TenantMetrics,
+    error_verbosity: ErrorVerbosityPolicy,
+    workload_identity: Option<WorkloadIdentity>,
+}
+
+/// Caller details used for access and audit logging, claim disclosure,
+/// certificate binding, route authorization and risk evaluation.
+struct Caller<'a> {
+    client: &'a str,
+    /// Address of the connection, the end user's unless the request names one
+    peer_ip: Option<IpAddr>,
+    rpc: &'a str,
+    /// SPIFFE ID of the calling workload, for the audit log
+    spiffe_id: Option<&'a str>,
+    claims: &'a ClaimSet,
+    /// Decryption of private claims, if the caller may read them
+    private_claims: Option<&'a PrivateClaims>,
+    certificate: Option<&'a [u8]>,
+    /// Whether the caller is a proxy trusted to forward `client_certificate_pem`
+    forwards_certificates: bool,
+    authorizer: Option<&'a Authorizer>,
+    risk_evaluator: &'a dyn RiskEvaluator,
+    /// Keyed rate limits and the request's arrival key
+    rate_limits: Option<(&'a KeyedRateLimiter, &'a RateLimitKey)>,
+    /// Warning thresholds and the slot of the response's rate limit metadata
+    rate_limit_slot: Option<(&'a QuotaWarnings, &'a RateLimitSlot)>,
+    /// Detail of error messages returned to the caller
+    verbosity: ErrorVerbosity,
+    /// Span of the RPC, recording the decision; `None` for streamed tokens
+    span: Option<&'a AuthSpan>,
+    /// Latency and errors per tenant
+    tenant_metrics: &'a TenantMetrics,
 }
 
 impl AuthEdgeServiceImpl {
@@ -38,99 +128,565 @@ impl AuthEdgeServiceImpl {
             JwkCache::new(&config).await?
         );
 
-        let jwt_validator = JwtValidator::new(jwk_cache);
+        let algorithms = match jwk_cache.discovery() {
+            Some(discovery) => {
+                let algorithms = discovery
+                    .document()
+                    .await?
+                    .accepted_algorithms(&config.jwt_algorithms);
+                if algorithms.is_empty() {
+                    return Err(AuthEdgeError::JwkCacheError {
+                        reason: format!(
+                            "issuer {} advertises none of the configured JWT algorithms",
+                            discovery.issuer()
+                        ),
+                    });
+                }
+                algorithms
+            }
+            None => config.jwt_algorithms.clone(),
+        };
 
-        let cb_config = CircuitBreakerConfig::default()
-            .with_failure_threshold(config.circuit_breaker_failure_threshold)
-            .with_timeout(Duration::from_secs(config.circuit_breaker_timeout_seconds));
+        let mut jwt_validator = JwtValidator::new(jwk_cache)
+            .with_algorithms(algorithms)
+            .with_decode_limits(DecodeLimits::from_config(&config));
+        if !config.claim_expressions.is_empty() {
+            let expressions = ClaimExpressions::compile(&config.claim_expressions).map_err(|e| {
+                AuthEdgeError::Platform(rust_common::PlatformError::InvalidInput(e))
+            })?;
+            jwt_validator = jwt_validator.with_claim_expressions(expressions);
+        }
+        if !config.audience_key_ids.is_empty() {
+            jwt_validator = jwt_validator
+                .with_audience_keys(AudienceKeyPolicy::new(config.audience_key_ids.clone()));
+        }
+        if let Some(max_age) = TokenAgePolicy::from_config(&config) {
+            jwt_validator = jwt_validator.with_max_token_age(max_age);
+        }
+        if let Some(issuers) = IssuerRegistry::from_config(&config).await? {
+            jwt_validator = jwt_validator.with_issuers(issuers);
+        }
+        let bypass_metrics = BypassMetrics::new(prometheus::default_registry()).map_err(|e| {
+            AuthEdgeError::Platform(rust_common::PlatformError::Internal(e.to_string()))
+        })?;
+        if let Some(bypass) = BypassValidator::from_config(&config, bypass_metrics)? {
+            info!("Emergency bypass tokens enabled");
+            jwt_validator = jwt_validator.with_bypass(bypass);
+        }
+        let cache_metrics = ValidationCacheMetrics::new(prometheus::default_registry())
+            .map_err(|e| {
+                AuthEdgeError::Platform(rust_common::PlatformError::Internal(e.to_string()))
+            })?;
+        if let Some(cache) = ValidationCache::from_config(&config, cache_metrics) {
+            jwt_validator = jwt_validator.with_cache(cache);
+        }
+        if config.validation_coalescing_enabled {
+            let coalescer_metrics =
+                ValidationCoalescerMetrics::new(prometheus::default_registry()).map_err(|e| {
+                    AuthEdgeError::Platform(rust_common::PlatformError::Internal(e.to_string()))
+                })?;
+            jwt_validator =
+                jwt_validator.with_coalescer(ValidationCoalescer::new(coalescer_metrics));
+        }
+        let revocation_metrics = RevocationMetrics::new(prometheus::default_registry())
+            .map_err(|e| {
+                AuthEdgeError::Platform(rust_common::PlatformError::Internal(e.to_string()))
+            })?;
+        if let Some(revocation) = RevocationChecker::from_config(&config, revocation_metrics).await? {
+            info!(
+                fail_open = config.revocation_fail_open,
+                "Token revocation checks enabled"
+            );
+            jwt_validator = jwt_validator.with_revocation(revocation);
+        }
+        if let Some(claims_refs) = ClaimsRefResolver::from_config(&config).await? {
+            info!(namespace = %config.claims_ref_namespace, "Claims by reference enabled");
+            jwt_validator = jwt_validator.with_claims_refs(claims_refs);
+        }
+        let denylist = config.caep_jwks_url.is_some().then(|| {
+            Arc::new(SubjectDenylist::new(Duration::from_secs(config.caep_deny_ttl_secs)))
+        });
+        if let Some(denylist) = &denylist {
+            jwt_validator = jwt_validator.with_denylist(denylist.clone());
+        }
+        let jwt_validator = Arc::new(jwt_validator);
+        let caep = denylist
+            .and_then(|denylist| {
+                CaepSubscriber::from_config(&config, denylist, jwt_validator.clone())
+            })
+            .map(Arc::new);
+        if caep.is_some() {
+            info!("CAEP revocation events enabled");
+        }
 
+        let cb_config = config.circuit_breaker_config();
         let token_service_cb = Arc::new(CircuitBreaker::new(cb_config.clone()));
         let iam_service_cb = Arc::new(CircuitBreaker::new(cb_config));
 
-        let spiffe_validator = SpiffeValidator::new(config.allowed_spiffe_domains.clone());
+        let federation = FederationRegistry::from_config(&config).await?.map(Arc::new);
+        let token_exchanger = if config.federated_idps.iter().any(|idp| idp.exchange) {
+            Some(TokenExchanger::new(&config, token_service_cb.clone())?)
+        } else {
+            None
+        };
+
+        let spiffe_validator = SpiffeValidator::new(config.allowed_spiffe_domains.clone())
+            .with_policy(config.spiffe_policy());
+        let chain_verifier = ChainVerifier::from_config(&config)?.map(Arc::new);
+        if chain_verifier.is_some() {
+            info!(
+                revocation = ?config.cert_revocation_mode,
+                "Client certificate chain validation enabled"
+            );
+        }
         let logger = Arc::new(AuthEdgeLogger::new(&config).await?);
+        let cb_metrics = CircuitBreakerMetrics::new(prometheus::default_registry())
+            .map(Arc::new)
+            .map_err(|e| {
+                AuthEdgeError::Platform(rust_common::PlatformError::Internal(e.to_string()))
+            })?;
+        let circuits = [("token_service", &token_service_cb), ("iam_service", &iam_service_cb)];
+        for (circuit, breaker) in circuits {
+            breaker.add_observer(Arc::new(CircuitBreakerReporter::new(
+                circuit,
+                cb_metrics.clone(),
+                logger.clone(),
+            )));
+        }
+        let access_log = AccessLog::from_config(&config, logger.clone())
+            .await?
+            .map(Arc::new);
+        let audit_log = AuditLog::from_config(&config).await?.map(Arc::new);
+        let disclosure = Arc::new(DisclosurePolicies::from_config(&config));
+        let private_claims = PrivateClaims::from_config(&config).await?.map(Arc::new);
+        if private_claims.is_some() {
+            info!(
+                readers = config.private_claim_readers.len(),
+                "Private claim decryption enabled"
+            );
+        }
+        let introspection_clients = IntrospectionClients::from_config(&config);
+        let upstream_introspector = UpstreamIntrospector::from_config(&config)?.map(Arc::new);
+        if upstream_introspector.is_some() {
+            info!(
+                cache_ttl_secs = config.upstream_introspection_cache_ttl_secs,
+                "Upstream introspection of opaque tokens enabled"
+            );
+        }
+        let authorizer = Authorizer::from_config(&config)?.map(Arc::new);
+        if authorizer.is_some() {
+            info!(opa = config.opa_url.is_some(), "Route authorization enabled");
+        }
+        let tenant_metrics = TenantMetrics::new(
+            "auth_edge",
+            prometheus::default_registry(),
+            Arc::new(TenantBuckets::new(config.tenant_metrics_top_n)),
+        )
+        .map_err(|e| {
+            AuthEdgeError::Platform(rust_common::PlatformError::Internal(e.to_string()))
+        })?;
+        let rate_limits = Arc::new(KeyedRateLimiter::from_config(&config));
+        if !rate_limits.is_empty() {
+            info!(
+                rules = config.rate_limit_policy.rules.len(),
+                "Keyed rate limits enabled"
+            );
+        }
 
         Ok(Self {
-            config,
             jwt_validator,
             token_service_cb,
             iam_service_cb,
             spiffe_validator,
+            chain_verifier,
             logger,
+            access_log,
+            audit_log,
+            disclosure,
+            private_claims,
+            introspection_clients,
+            upstream_introspector,
+            caep,
+            federation,
+            token_exchanger,
+            authorizer,
+            risk_evaluator: Arc::new(NoopRiskEvaluator),
+            rate_limits,
+            quota_warnings: QuotaWarnings::from_config(&config),
+            tenant_metrics,
+            error_verbosity: ErrorVerbosityPolicy::from_config(&config),
+            config,
+            workload_identity: None,
         })
     }
 
+    /// Uses the X.509-SVID fetched from the SPIRE agent as this service's
+    /// identity.
+    pub fn with_workload_identity(mut self, identity: WorkloadIdentity) -> Self {
+        self.workload_identity = Some(identity);
+        self
+    }
+
+    /// Vetoes or steps up validated tokens with `evaluator` instead of
+    /// allowing them all.
+    pub fn with_risk_evaluator(mut self, evaluator: Arc<dyn RiskEvaluator>) -> Self {
+        self.risk_evaluator = evaluator;
+        self
+    }
+
+    /// This service's own SPIFFE identity, when served from SPIRE.
+    pub fn workload_identity(&self) -> Option<&WorkloadIdentity> {
+        self.workload_identity.as_ref()
+    }
+
+    /// CAEP event subscriber, if a transmitter is configured.
+    pub fn caep_subscriber(&self) -> Option<&Arc<CaepSubscriber>> {
+        self.caep.as_ref()
+    }
+
+    /// Limiter of `RATE_LIMIT_POLICY`
+    pub fn keyed_rate_limits(&self) -> &Arc<KeyedRateLimiter> {
+        &self.rate_limits
+    }
+
+    /// Latency and errors per tenant, whose busiest tenants are recomputed
+    /// by `TenantMetrics::run`.
+    pub fn tenant_metrics(&self) -> &TenantMetrics {
+        &self.tenant_metrics
+    }
+
+    /// JWT validator shared by every RPC.
+    pub fn jwt_validator(&self) -> &Arc<JwtValidator> {
+        &self.jwt_validator
+    }
+
+    /// Logging service client of the RPC handlers.
+    pub fn logger(&self) -> &Arc<AuthEdgeLogger> {
+        &self.logger
+    }
+
+    /// Audit log of validation decisions, if enabled.
+    pub fn audit_log(&self) -> Option<&Arc<AuditLog>> {
+        self.audit_log.as_ref()
+    }
+
+    /// Circuit breakers guarding the token and IAM services.
+    pub fn circuit_breakers(&self) -> [&Arc<CircuitBreaker>; 2] {
+        [&self.token_service_cb, &self.iam_service_cb]
+    }
+
+    /// Circuit breakers with the names they are reported under.
+    fn named_circuit_breakers(&self) -> [(&'static str, &Arc<CircuitBreaker>); 2] {
+        [("token_service", &self.token_service_cb), ("iam_service", &self.iam_service_cb)]
+    }
+
     /// Generates a new correlation ID for request tracing.
     fn generate_correlation_id() -> Uuid {
         Uuid::new_v4()
     }
 
-    /// Converts ErrorCode to proto TokenErrorCode
-    fn error_code_to_proto(code: AuthErrorCode) -> i32 {
-        match code {
-            AuthErrorCode::TokenMissing => 6,       // MISSING_CLAIMS
-            AuthErrorCode::TokenInvalid => 3,       // INVALID_SIGNATURE
-            AuthErrorCode::TokenExpired => 1,       // EXPIRED
-            AuthErrorCode::TokenMalformed => 9,     // MALFORMED
-            AuthErrorCode::ClaimsInvalid => 6,      // MISSING_CLAIMS
-            AuthErrorCode::SpiffeError => 4,        // INVALID_ISSUER
-            AuthErrorCode::CertificateError => 3,   // INVALID_SIGNATURE
-            _ => 0,                                 // UNSPECIFIED
+    /// Correlation ID the tracing layer assigned to the request.
+    fn correlation_id<T>(request: &Request<T>) -> Uuid {
+        request
+            .extensions()
+            .get::<CorrelationId>()
+            .map_or_else(Self::generate_correlation_id, |id| id.0)
+    }
+
+    /// Identifies the caller for the access log: API key ID, else peer IP.
+    fn client_id<T>(request: &Request<T>) -> String {
+        if let Some(identity) = request.extensions().get::<ApiKeyIdentity>() {
+            return format!("api-key:{}", identity.key_id);
         }
+        Self::peer_ip(request)
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
     }
 
-    /// Converts HashMap to proto Struct
-    fn hashmap_to_proto_struct(map: HashMap<String, String>) -> Option<ProtoStruct> {
-        let mut fields = HashMap::new();
-        for (key, value) in map {
-            fields.insert(key, ProtoValue {
-                kind: Some(Kind::StringValue(value)),
-            });
+    /// IP address of the connection a request arrived on.
+    fn peer_ip<T>(request: &Request<T>) -> Option<IpAddr> {
+        request
+            .remote_addr()
+            .or_else(|| PeerCertificates::of(request)?.remote_addr())
+            .map(|addr| addr.ip())
+    }
+
+    /// Applies the keyed rate limits checked on arrival at `rpc`.
+    ///
+    /// Returns the request's rate limit key for the checks made once its
+    /// token is validated, or `None` when `RATE_LIMIT_POLICY` has no rules.
+    async fn admit<T>(
+        &self,
+        request: &Request<T>,
+        rpc: &str,
+    ) -> Result<Option<RateLimitKey>, Status> {
+        self.admit_requests(request, rpc, 1).await
+    }
+
+    /// Like [`Self::admit`], counting the call as `requests` requests, e.g.
+    /// one per token of a batch.
+    async fn admit_requests<T>(
+        &self,
+        request: &Request<T>,
+        rpc: &str,
+        requests: usize,
+    ) -> Result<Option<RateLimitKey>, Status> {
+        let rate_limits = &*self.rate_limits;
+        if rate_limits.is_empty() {
+            return Ok(None);
         }
-        Some(ProtoStruct { fields })
+        let key = self.rate_limit_key(request, rpc);
+        if let RateLimitDecision::Denied { retry_after } =
+            rate_limits.check_requests(&key, requests).await
+        {
+            let mut status = Self::rate_limited(retry_after).to_status_with(
+                Self::correlation_id(request),
+                self.verbosity_for(request),
+            );
+            if let Some(info) = rate_limits.limit_info(&key).await {
+                RateLimitHeaders::from_info(&info).insert_metadata(status.metadata_mut());
+            }
+            return Err(status);
+        }
+        if let Some(slot) = request.extensions().get::<RateLimitSlot>() {
+            Self::report_limits(&self.quota_warnings, rate_limits, &key, slot).await;
+        }
+        Ok(Some(key))
     }
 
-    /// Converts an AuthEdgeError to a ValidateTokenResponse with proper sanitization.
-    fn error_to_response(err: &AuthEdgeError, correlation_id: Uuid) -> ValidateTokenResponse {
-        let response = ErrorResponse::from_error(err, correlation_id);
+    /// Records the limits checked under `key` in the response's metadata
+    /// slot, raising its quota warning if the caller nears one.
+    async fn report_limits(
+        warnings: &QuotaWarnings,
+        rate_limits: &KeyedRateLimiter,
+        key: &RateLimitKey,
+        slot: &RateLimitSlot,
+    ) {
+        let Some(info) = rate_limits.limit_info(key).await else {
+            return;
+        };
+        slot.set_limits(RateLimitHeaders::from_info(&info));
+        if !warnings.is_enabled() {
+            return;
+        }
+        if let Some(level) = warnings.level(rate_limits.utilization(key).await) {
+            slot.raise(level);
+        }
+    }
 
-        ValidateTokenResponse {
-            valid: false,
-            subject: String::new(),
-            issuer: String::new(),
-            audiences: vec![],
-            scopes: vec![],
-            expires_at: None,
-            issued_at: None,
-            not_before: None,
-            jwt_id: String::new(),
-            claims: None,
-            error: Some(TokenValidationError {
-                code: Self::error_code_to_proto(response.code),
-                message: format!("{} [correlation_id: {}]", response.message, correlation_id),
-                details: HashMap::new(),
-            }),
-            binding: None,
-            acr: String::new(),
-            amr: vec![],
-            authorized_party: String::new(),
+    /// Warning thresholds for callers nearing their rate limits.
+    pub fn quota_warnings(&self) -> &QuotaWarnings {
+        &self.quota_warnings
+    }
+
+    /// Rate limit key of a request to `rpc`, before its token is validated.
+    fn rate_limit_key<T>(&self, request: &Request<T>, rpc: &str) -> RateLimitKey {
+        RateLimitKey::new(rpc)
+            .with_spiffe_id(self.caller_spiffe_id(request).map(|id| id.to_uri()))
+            .with_source_ip(Self::peer_ip(request))
+            .with_api_key(
+                request
+                    .extensions()
+                    .get::<ApiKeyIdentity>()
+                    .map(|identity| identity.key_id.clone()),
+            )
+    }
+
+    fn rate_limited(retry_after: Duration) -> AuthEdgeError {
+        AuthEdgeError::RateLimited {
+            retry_after: retry_after.as_secs().max(1),
         }
     }
-}
 
-#[tonic::async_trait]
-impl AuthEdgeService for AuthEdgeServiceImpl {
-    #[instrument(
-        skip(self, request),
-        fields(correlation_id = %Self::generate_correlation_id())
-    )]
-    async fn validate_token(
+    /// Detail of error messages returned to the caller of `request`.
+    fn verbosity_for<T>(&self, request: &Request<T>) -> ErrorVerbosity {
+        self.error_verbosity
+            .for_caller(self.caller_spiffe_id(request).as_ref())
+    }
+
+    /// Identifies the caller's workload from its mTLS client certificate.
+    fn caller_spiffe_id<T>(&self, request: &Request<T>) -> Option<OwnedSpiffeId> {
+        let peer = PeerCertificates::of(request)?;
+        self.spiffe_validator.extract_from_connection(peer).ok()
+    }
+
+    /// Span of the RPC, with the caller's client and SPIFFE ID recorded.
+    fn auth_span<T>(&self, request: &Request<T>) -> AuthSpan {
+        let span = AuthSpan::of(request);
+        record_auth!(span, client_id = &Self::client_id(request));
+        if let Some(spiffe_id) = self.caller_spiffe_id(request) {
+            record_auth!(span, peer_spiffe_id = &spiffe_id.to_uri());
+        }
+        span
+    }
+
+    /// Records a validation decision on the RPC's span.
+    fn record_decision(span: &AuthSpan, decision: AccessDecision) {
+        match decision {
+            AccessDecision::Allow => record_auth!(span, decision = ALLOW),
+            AccessDecision::Deny(code) => {
+                record_auth!(span, decision = DENY, error_code = code.as_str())
+            }
+        }
+    }
+
+    /// Extracts the caller's SPIFFE ID once its certificate chain validates.
+    ///
+    /// An OCSP response for the leaf may be stapled base64-encoded in the
+    /// `x-client-cert-ocsp` metadata.
+    async fn verify_peer_identity(
         &self,
-        request: Request<ValidateTokenRequest>,
-    ) -> Result<Response<ValidateTokenResponse>, Status> {
-        let correlation_id = Self::generate_correlation_id();
-        let req = request.into_inner();
+        peer: &PeerCertificates,
+        metadata: &tonic::metadata::MetadataMap,
+    ) -> Result<OwnedSpiffeId, String> {
+        let spiffe_id = self
+            .spiffe_validator
+            .extract_from_connection(peer)
+            .map_err(|e| e.to_string())?;
+        if let Some(verifier) = &self.chain_verifier {
+            use base64::Engine;
+            let stapled_ocsp = metadata
+                .get(STAPLED_OCSP_HEADER)
+                .map(|value| {
+                    let value = value.to_str().map_err(|e| e.to_string())?;
+                    base64::engine::general_purpose::STANDARD
+                        .decode(value)
+                        .map_err(|e| format!("invalid {STAPLED_OCSP_HEADER}: {e}"))
+                })
+                .transpose()?;
+            verifier
+                .verify(peer.chain(), stapled_ocsp.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(spiffe_id)
+    }
+
+    /// DER of the caller's mTLS client certificate.
+    fn peer_certificate<T>(request: &Request<T>) -> Option<Arc<[u8]>> {
+        PeerCertificates::of(request)?.leaf().map(Arc::from)
+    }
 
+    /// Whether the caller may forward an end client's certificate, i.e. its
+    /// SPIFFE ID matches `CERTIFICATE_FORWARDERS`.
+    fn forwards_certificates(&self, spiffe_id: Option<&OwnedSpiffeId>) -> bool {
+        spiffe_id.is_some_and(|id| {
+            self.config
+                .certificate_forwarders
+                .iter()
+                .any(|pattern| pattern.matches(id))
+        })
+    }
+
+    /// Verifies an RFC 8705 certificate binding against the caller's own
+    /// mTLS certificate, or the `client_certificate_pem` forwarded by a
+    /// trusted proxy.
+    ///
+    /// A certificate is public, so one forwarded by any other caller would
+    /// prove nothing and is rejected.
+    fn certificate_binding(
+        caller: &Caller<'_>,
+        req: &ValidateTokenRequest,
+        claims: &Claims,
+    ) -> Result<Option<TokenBinding>, AuthEdgeError> {
+        let forwarded = req
+            .client_certificate_pem
+            .as_deref()
+            .filter(|pem| !pem.trim().is_empty());
+        if forwarded.is_some() && !caller.forwards_certificates {
+            return Err(AuthEdgeError::AccessDenied {
+                reason: "caller may not forward client certificates".to_string(),
+            });
+        }
+        if binding::bound_thumbprint(claims)?.is_none() {
+            return Ok(None);
+        }
+        let forwarded = forwarded.map(binding::certificate_der_from_pem).transpose()?;
+        let certificate = forwarded.as_deref().or(caller.certificate);
+
+        Ok(binding::verify_certificate_binding(claims, certificate)?.map(|thumbprint| {
+            TokenBinding {
+                r#type: MTLS_BINDING.to_string(),
+                jwk_thumbprint: String::new(),
+                certificate_thumbprint: thumbprint,
+                verified: true,
+            }
+        }))
+    }
+
+    /// Validates one token request, applies the caller's claim disclosure
+    /// policy and records the decision in the access and audit logs.
+    async fn validate_request(
+        validator: &JwtValidator,
+        logger: &AuthEdgeLogger,
+        access_log: Option<&AccessLog>,
+        audit_log: Option<&AuditLog>,
+        caller: &Caller<'_>,
+        req: ValidateTokenRequest,
+        correlation_id: Uuid,
+    ) -> ValidateTokenResponse {
+        let started = Instant::now();
+        let tenant = req.tenant.clone();
+        let (mut response, subject, decision) =
+            Self::decide(validator, logger, caller, req, correlation_id).await;
+        caller
+            .tenant_metrics
+            .record(&tenant, response.valid, started.elapsed().as_secs_f64());
+        if let Some(span) = caller.span {
+            Self::record_decision(span, decision);
+        }
+        if response.valid {
+            if let Some(private_claims) = caller.private_claims {
+                private_claims
+                    .reveal(&mut response, &correlation_id.to_string())
+                    .await;
+            }
+            caller.claims.apply(&mut response);
+        }
+        if let Some((rate_limits, key)) = caller.rate_limits {
+            rate_limits.record_outcome(key, response.valid).await;
+            if let Some(subject) = &subject {
+                let key = key.clone().with_subject(subject.clone());
+                rate_limits.record_outcome(&key, response.valid).await;
+            }
+        }
+
+        if let Some(access_log) = access_log {
+            access_log
+                .record(&AccessEvent {
+                    subject: subject.as_deref(),
+                    client: caller.client,
+                    rpc: caller.rpc,
+                    decision,
+                    latency: started.elapsed(),
+                    trust_level: None,
+                    correlation_id: &correlation_id.to_string(),
+                })
+                .await;
+        }
+        if let Some(audit_log) = audit_log {
+            let correlation_id = correlation_id.to_string();
+            audit_log
+                .record(
+                    AuditEvent::new(decision, caller.rpc, caller.client, &correlation_id)
+                        .with_subject(subject.as_deref())
+                        .with_caller_spiffe_id(caller.spiffe_id),
+                )
+                .await;
+        }
+
+        response
+    }
+
+    /// Validates one token request and authorizes its route; shared by the
+    /// unary and streaming RPCs.
+    async fn decide(
+        validator: &JwtValidator,
+        logger: &AuthEdgeLogger,
+        caller: &Caller<'_>,
+        req: ValidateTokenRequest,
+        correlation_id: Uuid,
+    ) -> (ValidateTokenResponse, Option<String>, AccessDecision) {
         // Check for missing token
         if req.token.is_empty() {
             let err = AuthEdgeError::TokenMissing;
@@ -139,33 +695,112 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
                 error_type = "TokenMissing",
                 "Token validation failed: token missing"
             );
-            self.logger
-                .log_validation_failure(&err, &correlation_id.to_string())
-                .await;
-            return Ok(Response::new(Self::error_to_response(&err, correlation_id)));
+            logger.log_validation_failure(&err, &correlation_id.to_string()).await;
+            return (
+                Self::error_to_response(&err, correlation_id, caller.verbosity),
+                None,
+                AccessDecision::Deny(err.code()),
+            );
         }
 
         // Use type-state JWT validation
         let required_refs: Vec<&str> = req.required_claims.iter().map(|s| s.as_str()).collect();
 
-        match self
-            .jwt_validator
-            .validate_token(&req.token, &required_refs)
+        let validation = validator
+            .validate_token_for_route(&req.token, &required_refs, &req.route)
             .await
-        {
-            Ok(validated_token) => {
+            .and_then(|validated_token| {
+                let binding = Self::certificate_binding(caller, &req, validated_token.claims())?;
+                Ok((validated_token, binding))
+            });
+
+        match validation {
+            Ok((validated_token, binding)) => {
                 let claims = validated_token.claims();
 
+                // Limits keyed by subject apply once the token is trusted
+                if let Some((rate_limits, key)) = caller.rate_limits {
+                    let key = key.clone().with_subject(claims.sub.clone());
+                    let decision = rate_limits.check(&key).await;
+                    if let Some((warnings, slot)) = caller.rate_limit_slot {
+                        Self::report_limits(warnings, rate_limits, &key, slot).await;
+                    }
+                    if let RateLimitDecision::Denied { retry_after } = decision {
+                        let err = Self::rate_limited(retry_after);
+                        info!(
+                            subject = %claims.sub,
+                            correlation_id = %correlation_id,
+                            "Subject rate limit exceeded"
+                        );
+                        return (
+                            Self::error_to_response(&err, correlation_id, caller.verbosity),
+                            Some(claims.sub.clone()),
+                            AccessDecision::Deny(err.code()),
+                        );
+                    }
+                }
+
                 info!(
                     subject = %claims.sub,
                     correlation_id = %correlation_id,
                     "Token validated successfully"
                 );
-                self.logger
+                logger
                     .log_validation_success(&claims.sub, &correlation_id.to_string())
                     .await;
+                if validated_token.is_emergency_bypass() {
+                    logger
+                        .log_emergency_bypass(
+                            &claims.sub,
+                            &claims.jti,
+                            claims.exp,
+                            &correlation_id.to_string(),
+                        )
+                        .await;
+                }
 
-                Ok(Response::new(ValidateTokenResponse {
+                if let Some(authorizer) = caller.authorizer.filter(|_| !req.route.is_empty()) {
+                    if let Err(err) = authorizer
+                        .authorize(&req.route, &req.tenant, caller.client, claims)
+                        .await
+                    {
+                        info!(
+                            subject = %claims.sub,
+                            route = %req.route,
+                            error = %err,
+                            correlation_id = %correlation_id,
+                            "Route authorization denied"
+                        );
+                        logger
+                            .log_validation_failure(&err, &correlation_id.to_string())
+                            .await;
+                        return (
+                            Self::error_to_response(&err, correlation_id, caller.verbosity),
+                            Some(claims.sub.clone()),
+                            AccessDecision::Deny(err.code()),
+                        );
+                    }
+                }
+
+                if let Err(err) = Self::evaluate_risk(caller, &req, claims).await {
+                    info!(
+                        subject = %claims.sub,
+                        route = %req.route,
+                        error = %err,
+                        correlation_id = %correlation_id,
+                        "Risk evaluation refused token"
+                    );
+                    logger
+                        .log_validation_failure(&err, &correlation_id.to_string())
+                        .await;
+                    return (
+                        Self::error_to_response(&err, correlation_id, caller.verbosity),
+                        Some(claims.sub.clone()),
+                        AccessDecision::Deny(err.code()),
+                    );
+                }
+
+                let response = ValidateTokenResponse {
                     valid: true,
                     subject: claims.sub.clone(),
                     issuer: claims.iss.clone(),
@@ -177,11 +812,13 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
                     jwt_id: claims.jti.clone(),
                     claims: Self::hashmap_to_proto_struct(claims.to_map()),
                     error: None,
-                    binding: None,
+                    binding,
                     acr: String::new(),
                     amr: vec![],
                     authorized_party: String::new(),
-                }))
+                    step_up_required: None,
+                };
+                (response, Some(claims.sub.clone()), AccessDecision::Allow)
             }
             Err(err) => {
                 error!(
@@ -190,29 +827,87 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
                     error_type = ?err.code(),
                     "Token validation failed"
                 );
-                self.logger
+                logger
                     .log_validation_failure(&err, &correlation_id.to_string())
                     .await;
 
-                Ok(Response::new(Self::error_to_response(&err, correlation_id)))
+                (
+                    Self::error_to_response(&err, correlation_id, caller.verbosity),
+                    None,
+                    AccessDecision::Deny(err.code()),
+                )
             }
         }
     }
 
-    #[instrument(skip(self, request))]
-    async fn introspect_token(
+    /// Runs the risk evaluator on a validated, authorized token
+    ///
+    /// The end user's address is the request's `client_ip`, else the peer's.
+    async fn evaluate_risk(
+        caller: &Caller<'_>,
+        req: &ValidateTokenRequest,
+        claims: &Claims,
+    ) -> Result<(), AuthEdgeError> {
+        let input = RiskInput {
+            claims,
+            route: &req.route,
+            tenant: &req.tenant,
+            client: caller.client,
+            client_ip: req.client_ip.parse().ok().or(caller.peer_ip),
+            context: &req.risk_context,
+        };
+        caller.risk_evaluator.evaluate(&input).await?.into_result(&input)
+    }
+
+    /// Introspects one token for an authorized caller, shaping the response
+    /// for its resource server and recording the decision in the audit log.
+    async fn introspect(
         &self,
-        request: Request<IntrospectTokenRequest>,
-    ) -> Result<Response<IntrospectTokenResponse>, Status> {
-        let correlation_id = Self::generate_correlation_id();
-        let req = request.into_inner();
+        token: &str,
+        rpc: &str,
+        client: &str,
+        spiffe_uri: Option<&str>,
+        resource_server: Option<&ResourceServer>,
+        correlation_id: Uuid,
+    ) -> (IntrospectTokenResponse, AccessDecision) {
+        let upstream = self
+            .upstream_introspector
+            .as_ref()
+            .filter(|_| UpstreamIntrospector::is_opaque(token));
+        let (response, subject, decision) = match upstream {
+            Some(upstream) => {
+                self.introspect_upstream(upstream, token, resource_server, correlation_id)
+                    .await
+            }
+            None => self.introspect_jws(token, resource_server, correlation_id).await,
+        };
+
+        if let Some(audit_log) = &self.audit_log {
+            let correlation_id = correlation_id.to_string();
+            audit_log
+                .record(
+                    AuditEvent::new(decision, rpc, client, &correlation_id)
+                        .with_subject(subject.as_deref())
+                        .with_caller_spiffe_id(spiffe_uri),
+                )
+                .await;
+        }
+        (response, decision)
+    }
 
+    /// Introspects a JWS by validating it locally.
+    async fn introspect_jws(
+        &self,
+        token: &str,
+        resource_server: Option<&ResourceServer>,
+        correlation_id: Uuid,
+    ) -> (IntrospectTokenResponse, Option<String>, AccessDecision) {
         // For introspection, we validate without required claims
-        match self.jwt_validator.validate_token(&req.token, &[]).await {
+        match self.jwt_validator.validate_token(token, &[]).await {
             Ok(validated_token) => {
                 let claims = validated_token.claims();
 
-                Ok(Response::new(IntrospectTokenResponse {
+                let mut response = IntrospectTokenResponse {
                     active: !claims.is_expired(),
                     sub: Some(claims.sub.clone()),
                     client_id: claims
@@ -220,40 +915,354 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
                         .get("client_id")
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string()),
-                    scope: claims.scopes.as_ref().map(|scopes| scopes.join(" ")),
-                    exp: Some(claims.exp as i64),
-                    iat: Some(claims.iat as i64),
+                    scope: claims.scopes.as_deref().map(format_scope),
+                    exp: Some(claims.exp),
+                    iat: Some(claims.iat),
                     token_type: Some("Bearer".to_string()),
                     ..Default::default()
-                }))
+                };
+                let decision = if response.active {
+                    AccessDecision::Allow
+                } else {
+                    AccessDecision::Deny(AuthErrorCode::TokenExpired)
+                };
+                if let Some(resource_server) = resource_server {
+                    resource_server.shape(&mut response, &claims.aud);
+                    info!(
+                        resource_server = %resource_server.name(),
+                        active = response.active,
+                        correlation_id = %correlation_id,
+                        "Token introspection shaped for resource server"
+                    );
+                }
+                (response, Some(claims.sub.clone()), decision)
             }
-            Err(_err) => {
+            Err(err) => {
                 info!(
                     correlation_id = %correlation_id,
                     "Token introspection: token inactive"
                 );
+                let response = IntrospectTokenResponse {
+                    active: false,
+                    ..Default::default()
+                };
+                (response, None, AccessDecision::Deny(err.code()))
+            }
+        }
+    }
 
-                Ok(Response::new(IntrospectTokenResponse {
+    /// Introspects an opaque token at the upstream introspection endpoint.
+    async fn introspect_upstream(
+        &self,
+        upstream: &UpstreamIntrospector,
+        token: &str,
+        resource_server: Option<&ResourceServer>,
+        correlation_id: Uuid,
+    ) -> (IntrospectTokenResponse, Option<String>, AccessDecision) {
+        match upstream.introspect(token).await {
+            Ok(result) => {
+                let audiences = result.audiences();
+                let subject = result.sub.clone().filter(|_| result.active);
+                let mut response = result.into_response();
+                let decision = if response.active {
+                    AccessDecision::Allow
+                } else {
+                    AccessDecision::Deny(AuthErrorCode::TokenInvalid)
+                };
+                if let Some(resource_server) = resource_server {
+                    resource_server.shape(&mut response, &audiences);
+                }
+                info!(
+                    active = response.active,
+                    correlation_id = %correlation_id,
+                    "Opaque token introspected upstream"
+                );
+                (response, subject, decision)
+            }
+            Err(err) => {
+                let response = IntrospectTokenResponse {
                     active: false,
                     ..Default::default()
-                }))
+                };
+                (response, None, AccessDecision::Deny(err.code()))
             }
         }
     }
 
+    /// Uses the caller's correlation ID for logging when it is a UUID.
+    fn stream_correlation_id(requested: &str) -> Uuid {
+        Uuid::parse_str(requested).unwrap_or_else(|_| Self::generate_correlation_id())
+    }
+
+    /// Converts ErrorCode to proto TokenErrorCode
+    fn error_code_to_proto(code: AuthErrorCode) -> i32 {
+        match code {
+            AuthErrorCode::TokenMissing => 6,       // MISSING_CLAIMS
+            AuthErrorCode::TokenInvalid => 3,       // INVALID_SIGNATURE
+            AuthErrorCode::TokenExpired => 1,       // EXPIRED
+            AuthErrorCode::TokenTooOld => 15,       // TOO_OLD
+            AuthErrorCode::TokenMalformed => 9,     // MALFORMED
+            AuthErrorCode::ClaimsInvalid => 6,      // MISSING_CLAIMS
+            AuthErrorCode::TokenRevoked => 8,       // REVOKED
+            AuthErrorCode::SpiffeError => 4,        // INVALID_ISSUER
+            AuthErrorCode::CertificateError => 3,   // INVALID_SIGNATURE
+            AuthErrorCode::AccessDenied => 14,      // ACCESS_DENIED
+            AuthErrorCode::TokenBindingMismatch => 11, // BINDING_MISMATCH
+            AuthErrorCode::StepUpRequired => 16,    // STEP_UP_REQUIRED
+            _ => 0,                                 // UNSPECIFIED
+        }
+    }
+
+    /// Converts HashMap to proto Struct
+    fn hashmap_to_proto_struct(map: HashMap<String, String>) -> Option<ProtoStruct> {
+        let mut fields = HashMap::new();
+        for (key, value) in map {
+            fields.insert(key, ProtoValue {
+                kind: Some(Kind::StringValue(value)),
+            });
+        }
+        Some(ProtoStruct { fields })
+    }
+
+    /// Converts a JSON value to a proto Value
+    fn json_to_proto_value(value: serde_json::Value) -> ProtoValue {
+        let kind = match value {
+            serde_json::Value::Null => Kind::NullValue(0),
+            serde_json::Value::Bool(b) => Kind::BoolValue(b),
+            serde_json::Value::Number(n) => Kind::NumberValue(n.as_f64().unwrap_or_default()),
+            serde_json::Value::String(s) => Kind::StringValue(s),
+            serde_json::Value::Array(values) => Kind::ListValue(prost_types::ListValue {
+                values: values.into_iter().map(Self::json_to_proto_value).collect(),
+            }),
+            serde_json::Value::Object(map) => Kind::StructValue(ProtoStruct {
+                fields: map
+                    .into_iter()
+                    .map(|(key, value)| (key, Self::json_to_proto_value(value)))
+                    .collect(),
+            }),
+        };
+        ProtoValue { kind: Some(kind) }
+    }
+
+    /// Converts an AuthEdgeError to a ValidateTokenResponse with proper sanitization.
+    fn error_to_response(
+        err: &AuthEdgeError,
+        correlation_id: Uuid,
+        verbosity: ErrorVerbosity,
+    ) -> ValidateTokenResponse {
+        let response = ErrorResponse::with_verbosity(err, correlation_id, verbosity);
+
+        ValidateTokenResponse {
+            valid: false,
+            subject: String::new(),
+            issuer: String::new(),
+            audiences: vec![],
+            scopes: vec![],
+            expires_at: None,
+            issued_at: None,
+            not_before: None,
+            jwt_id: String::new(),
+            claims: None,
+            error: Some(TokenValidationError {
+                code: Self::error_code_to_proto(response.code),
+                message: format!("{} [correlation_id: {}]", response.message, correlation_id),
+                details: HashMap::new(),
+            }),
+            binding: None,
+            acr: String::new(),
+            amr: vec![],
+            authorized_party: String::new(),
+            step_up_required: (response.code == AuthErrorCode::StepUpRequired).then(|| {
+                StepUpRequired {
+                    acr_values: response.acr_values,
+                    max_age: response.max_age.map_or(0, |max_age| max_age.as_secs() as i64),
+                }
+            }),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl AuthEdgeService for AuthEdgeServiceImpl {
+    #[instrument(skip(self, request))]
+    async fn validate_token(
+        &self,
+        request: Request<ValidateTokenRequest>,
+    ) -> Result<Response<ValidateTokenResponse>, Status> {
+        let span = self.auth_span(&request);
+        let rate_limit_key = self.admit(&request, "ValidateToken").await?;
+        let correlation_id = Self::correlation_id(&request);
+        let client = Self::client_id(&request);
+        let spiffe_id = self.caller_spiffe_id(&request);
+        let certificate = Self::peer_certificate(&request);
+        let rate_limit_slot = request.extensions().get::<RateLimitSlot>().cloned();
+        let trace = request.extensions().get::<TraceContext>().cloned();
+        let spiffe_uri = spiffe_id.as_ref().map(OwnedSpiffeId::to_uri);
+        let caller = Caller {
+            client: &client,
+            peer_ip: Self::peer_ip(&request),
+            rpc: "ValidateToken",
+            spiffe_id: spiffe_uri.as_deref(),
+            claims: self.disclosure.for_caller(spiffe_id.as_ref()),
+            private_claims: self
+                .private_claims
+                .as_deref()
+                .filter(|private_claims| private_claims.may_read(spiffe_id.as_ref())),
+            certificate: certificate.as_deref(),
+            forwards_certificates: self.forwards_certificates(spiffe_id.as_ref()),
+            authorizer: self.authorizer.as_deref(),
+            risk_evaluator: self.risk_evaluator.as_ref(),
+            rate_limits: rate_limit_key.as_ref().map(|key| (&*self.rate_limits, key)),
+            rate_limit_slot: rate_limit_slot.as_ref().map(|slot| (&self.quota_warnings, slot)),
+            verbosity: self.error_verbosity.for_caller(spiffe_id.as_ref()),
+            span: Some(&span),
+            tenant_metrics: &self.tenant_metrics,
+        };
+        let req = request.into_inner();
+        if !req.tenant.is_empty() {
+            record_auth!(span, tenant = &req.tenant);
+        }
+
+        Ok(Response::new(
+            trace_context::scope(
+                trace,
+                Self::validate_request(
+                    &self.jwt_validator,
+                    &self.logger,
+                    self.access_log.as_deref(),
+                    self.audit_log.as_deref(),
+                    &caller,
+                    req,
+                    correlation_id,
+                ),
+            )
+            .await,
+        ))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn introspect_token(
+        &self,
+        request: Request<IntrospectTokenRequest>,
+    ) -> Result<Response<IntrospectTokenResponse>, Status> {
+        let span = self.auth_span(&request);
+        self.admit(&request, "IntrospectToken").await?;
+        if !self.config.introspection_enabled {
+            return Err(Status::permission_denied(format!(
+                "Token introspection is not exposed in the {} profile",
+                self.config.deployment_profile
+            )));
+        }
+
+        let correlation_id = Self::correlation_id(&request);
+        let client = Self::client_id(&request);
+        let spiffe_id = self.caller_spiffe_id(&request);
+        let spiffe_uri = spiffe_id.as_ref().map(OwnedSpiffeId::to_uri);
+        let resource_server = self
+            .introspection_clients
+            .authorize(spiffe_id.as_ref(), request.extensions().get::<ApiKeyIdentity>())?;
+        let trace = request.extensions().get::<TraceContext>().cloned();
+        let req = request.into_inner();
+
+        let (response, decision) = trace_context::scope(
+            trace,
+            self.introspect(
+                &req.token,
+                "IntrospectToken",
+                &client,
+                spiffe_uri.as_deref(),
+                resource_server,
+                correlation_id,
+            ),
+        )
+        .await;
+        Self::record_decision(&span, decision);
+        Ok(Response::new(response))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn introspect_batch(
+        &self,
+        request: Request<IntrospectBatchRequest>,
+    ) -> Result<Response<IntrospectBatchResponse>, Status> {
+        self.auth_span(&request);
+        // Each token counts as one request; oversized batches are rejected below
+        let requests = request
+            .get_ref()
+            .tokens
+            .len()
+            .clamp(1, self.config.introspect_batch_max_tokens.max(1));
+        self.admit_requests(&request, "IntrospectBatch", requests).await?;
+        if !self.config.introspection_enabled {
+            return Err(Status::permission_denied(format!(
+                "Token introspection is not exposed in the {} profile",
+                self.config.deployment_profile
+            )));
+        }
+
+        let correlation_id = Self::correlation_id(&request);
+        let client = Self::client_id(&request);
+        let spiffe_id = self.caller_spiffe_id(&request);
+        let spiffe_uri = spiffe_id.as_ref().map(OwnedSpiffeId::to_uri);
+        let resource_server = self
+            .introspection_clients
+            .authorize(spiffe_id.as_ref(), request.extensions().get::<ApiKeyIdentity>())?;
+        let trace = request.extensions().get::<TraceContext>().cloned();
+        let req = request.into_inner();
+        if req.tokens.len() > self.config.introspect_batch_max_tokens {
+            return Err(Status::invalid_argument(format!(
+                "IntrospectBatch accepts at most {} tokens, got {}",
+                self.config.introspect_batch_max_tokens,
+                req.tokens.len()
+            )));
+        }
+
+        let tokens = req.tokens.len();
+        let client = client.as_str();
+        let spiffe_uri = spiffe_uri.as_deref();
+        let introspections = futures::stream::iter(req.tokens)
+            .map(|item| async move {
+                self.introspect(
+                    &item.token,
+                    "IntrospectBatch",
+                    client,
+                    spiffe_uri,
+                    resource_server,
+                    correlation_id,
+                )
+                .await
+            })
+            .buffered(self.config.introspect_batch_concurrency)
+            .map(|(response, _)| response)
+            .collect::<Vec<_>>();
+        let results = trace_context::scope(trace, introspections).await;
+        info!(
+            tokens,
+            correlation_id = %correlation_id,
+            "Token batch introspected"
+        );
+        Ok(Response::new(IntrospectBatchResponse { results }))
+    }
+
     #[instrument(skip(self, request))]
     async fn get_service_identity(
         &self,
         request: Request<GetServiceIdentityRequest>,
     ) -> Result<Response<GetServiceIdentityResponse>, Status> {
-        let correlation_id = Self::generate_correlation_id();
-        let req = request.into_inner();
+        let span = self.auth_span(&request);
+        self.admit(&request, "GetServiceIdentity").await?;
+        let correlation_id = Self::correlation_id(&request);
 
-        match self
-            .spiffe_validator
-            .extract_from_certificate(&req.certificate_pem)
-        {
+        // The identity comes from the connection's client certificate, never
+        // from the request body
+        let identity = match PeerCertificates::of(&request) {
+            Some(peer) => self.verify_peer_identity(peer, request.metadata()).await,
+            None => Err(SpiffeError::MissingCertificate.to_string()),
+        };
+
+        match identity {
             Ok(spiffe_id) => {
+                record_auth!(span, decision = ALLOW);
                 let service_name =
                     SpiffeValidator::extract_service_name(&spiffe_id).unwrap_or_default();
 
@@ -273,6 +1282,11 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
                 }))
             }
             Err(err) => {
+                record_auth!(
+                    span,
+                    decision = DENY,
+                    error_code = AuthErrorCode::SpiffeError.as_str()
+                );
                 error!(
                     error = %err,
                     correlation_id = %correlation_id,
@@ -283,7 +1297,13 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
                     valid: false,
                     spiffe_id: String::new(),
                     service_name: String::new(),
-                    error_message: format!("{err} [correlation_id: {correlation_id}]"),
+                    error_message: match self.error_verbosity.external {
+                        ErrorVerbosity::Detailed => format!("{err} [correlation_id: {correlation_id}]"),
+                        ErrorVerbosity::Minimal => format!(
+                            "{} [correlation_id: {correlation_id}]",
+                            AuthErrorCode::SpiffeError.as_str()
+                        ),
+                    },
                     ..Default::default()
                 }))
             }
@@ -295,7 +1315,9 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
         &self,
         request: Request<ValidateDPoPRequest>,
     ) -> Result<Response<ValidateDPoPResponse>, Status> {
-        let correlation_id = Self::generate_correlation_id();
+        self.auth_span(&request);
+        self.admit(&request, "ValidateDPoP").await?;
+        let correlation_id = Self::correlation_id(&request);
         let _req = request.into_inner();
 
         // TODO: Implement DPoP validation logic
@@ -322,19 +1344,457 @@ impl AuthEdgeService for AuthEdgeServiceImpl {
         &self,
         request: Request<CheckRevocationRequest>,
     ) -> Result<Response<CheckRevocationResponse>, Status> {
-        let correlation_id = Self::generate_correlation_id();
-        let _req = request.into_inner();
+        self.auth_span(&request);
+        self.admit(&request, "CheckRevocation").await?;
+        let correlation_id = Self::correlation_id(&request);
+        let verbosity = self.verbosity_for(&request);
+        let req = request.into_inner();
 
-        // TODO: Implement revocation checking logic
-        info!(
-            correlation_id = %correlation_id,
-            "Revocation check requested - not yet implemented"
-        );
+        let Some(revocation) = self.jwt_validator.revocation() else {
+            return Err(Status::failed_precondition("Revocation checking is disabled"));
+        };
+        if !matches!(req.id_type.as_str(), "" | "jti") {
+            return Err(Status::invalid_argument("Only jti revocation lookups are supported"));
+        }
+        if req.token_id.is_empty() {
+            return Err(Status::invalid_argument("token_id is required"));
+        }
+
+        let revoked = revocation
+            .is_revoked(&req.token_id)
+            .await
+            .map_err(|e| AuthEdgeError::Platform(e).to_status_with(correlation_id, verbosity))?;
+        if revoked {
+            self.jwt_validator.invalidate_cached(&req.token_id);
+        }
 
         Ok(Response::new(CheckRevocationResponse {
-            revoked: false,
+            revoked,
             revoked_at: None,
             reason: String::new(),
         }))
     }
+
+    #[instrument(skip(self, request))]
+    async fn explain_token(
+        &self,
+        request: Request<ExplainTokenRequest>,
+    ) -> Result<Response<ExplainTokenResponse>, Status> {
+        self.auth_span(&request);
+        self.admit(&request, "ExplainToken").await?;
+        if !self.config.explain_token_enabled {
+            return Err(Status::permission_denied("Token explanation is disabled"));
+        }
+        // The API key layer has already checked the key is scoped to ExplainToken
+        let Some(identity) = request.extensions().get::<ApiKeyIdentity>().cloned() else {
+            return Err(Status::permission_denied(
+                "ExplainToken requires an API key scoped to ExplainToken",
+            ));
+        };
+
+        let correlation_id = Self::correlation_id(&request);
+        let req = request.into_inner();
+        let required_claims: Vec<&str> = req.required_claims.iter().map(String::as_str).collect();
+        let required_scopes: Vec<&str> = req.required_scopes.iter().map(String::as_str).collect();
+
+        // IntrospectToken validates without required claims; ValidateToken then
+        // additionally requires the requested claims
+        let introspection = self.jwt_validator.validate_token(&req.token, &[]).await;
+        let validation = match &introspection {
+            Ok(token) => {
+                let unmet = token
+                    .claims()
+                    .unmet(&required_claims, self.jwt_validator.claim_expressions());
+                if unmet.is_empty() {
+                    Ok(())
+                } else {
+                    Err(AuthEdgeError::ClaimsInvalid { claims: unmet })
+                }
+            }
+            Err(err) => Err(err.clone()),
+        };
+
+        let outcomes = self
+            .jwt_validator
+            .explain(&req.token, &required_claims, &required_scopes)
+            .await;
+
+        info!(
+            key_id = %identity.key_id,
+            correlation_id = %correlation_id,
+            validation_passed = validation.is_ok(),
+            introspection_active = introspection.is_ok(),
+            "Token explained"
+        );
+
+        Ok(Response::new(ExplainTokenResponse {
+            validation_passed: validation.is_ok(),
+            validation_error_code: validation
+                .err()
+                .map(|err| err.code().as_str().to_string())
+                .unwrap_or_default(),
+            introspection_active: introspection.is_ok(),
+            divergent_checks: outcomes
+                .iter()
+                .filter(|outcome| outcome.diverges())
+                .map(|outcome| outcome.check.as_str().to_string())
+                .collect(),
+            checks: outcomes
+                .into_iter()
+                .map(|outcome| TokenCheck {
+                    name: outcome.check.as_str().to_string(),
+                    status: match outcome.status {
+                        JwtCheckStatus::Passed => CheckStatus::Passed,
+                        JwtCheckStatus::Failed => CheckStatus::Failed,
+                        JwtCheckStatus::Skipped => CheckStatus::Skipped,
+                    } as i32,
+                    applied_by_validation: outcome.check.applied_by_validation(),
+                    applied_by_introspection: outcome.check.applied_by_introspection(),
+                    detail: outcome.detail,
+                })
+                .collect(),
+            config: self.config.explain_summary(),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn exchange_federated_token(
+        &self,
+        request: Request<ExchangeFederatedTokenRequest>,
+    ) -> Result<Response<ExchangeFederatedTokenResponse>, Status> {
+        let span = self.auth_span(&request);
+        self.admit(&request, "ExchangeFederatedToken").await?;
+        let Some(federation) = &self.federation else {
+            return Err(Status::failed_precondition("No federated IdPs are configured"));
+        };
+
+        let correlation_id = Self::correlation_id(&request);
+        let verbosity = self.verbosity_for(&request);
+        let req = request.into_inner();
+
+        let (idp, claims) = federation
+            .verify(&req.subject_token)
+            .await
+            .map_err(|e| e.to_status_with(correlation_id, verbosity))?;
+        let identity = idp.identity(&claims);
+
+        let issued = match &self.token_exchanger {
+            Some(exchanger) if idp.exchange => Some(
+                exchanger
+                    .exchange(idp, &identity, req.scopes)
+                    .await
+                    .map_err(|e| e.to_status_with(correlation_id, verbosity))?,
+            ),
+            _ => None,
+        };
+
+        record_auth!(span, decision = ALLOW);
+        info!(
+            correlation_id = %correlation_id,
+            idp = %identity.idp,
+            exchanged = issued.is_some(),
+            "Federated token accepted"
+        );
+
+        let issued = issued.unwrap_or_default();
+        Ok(Response::new(ExchangeFederatedTokenResponse {
+            idp: identity.idp,
+            subject: identity.subject,
+            claims: Some(ProtoStruct {
+                fields: identity
+                    .claims
+                    .into_iter()
+                    .map(|(key, value)| (key, Self::json_to_proto_value(value)))
+                    .collect(),
+            }),
+            access_token: issued.access_token,
+            token_type: issued.token_type,
+            expires_at: issued.expires_at,
+        }))
+    }
+
+    type ValidateTokenStreamStream = ValidateTokenResponseStream;
+
+    #[instrument(skip(self, request))]
+    async fn validate_token_stream(
+        &self,
+        request: Request<Streaming<ValidateTokenStreamRequest>>,
+    ) -> Result<Response<Self::ValidateTokenStreamStream>, Status> {
+        self.auth_span(&request);
+        let validator = self.jwt_validator.clone();
+        let logger = self.logger.clone();
+        let access_log = self.access_log.clone();
+        let audit_log = self.audit_log.clone();
+        let client: Arc<str> = Self::client_id(&request).into();
+        let peer_ip = Self::peer_ip(&request);
+        let spiffe_id = self.caller_spiffe_id(&request);
+        let spiffe_uri: Option<Arc<str>> = spiffe_id.as_ref().map(|id| id.to_uri().into());
+        let claims = Arc::new(self.disclosure.for_caller(spiffe_id.as_ref()).clone());
+        let private_claims = self
+            .private_claims
+            .clone()
+            .filter(|private_claims| private_claims.may_read(spiffe_id.as_ref()));
+        let verbosity = self.error_verbosity.for_caller(spiffe_id.as_ref());
+        let certificate = Self::peer_certificate(&request);
+        let forwards_certificates = self.forwards_certificates(spiffe_id.as_ref());
+        let authorizer = self.authorizer.clone();
+        let risk_evaluator = self.risk_evaluator.clone();
+        let rate_limits = self.rate_limits.clone();
+        let rate_limit_key = Arc::new(self.rate_limit_key(&request, "ValidateTokenStream"));
+        let tenant_metrics = self.tenant_metrics.clone();
+
+        let responses = request
+            .into_inner()
+            .map(move |item| {
+                let validator = validator.clone();
+                let logger = logger.clone();
+                let access_log = access_log.clone();
+                let audit_log = audit_log.clone();
+                let client = client.clone();
+                let spiffe_uri = spiffe_uri.clone();
+                let claims = claims.clone();
+                let private_claims = private_claims.clone();
+                let certificate = certificate.clone();
+                let authorizer = authorizer.clone();
+                let risk_evaluator = risk_evaluator.clone();
+                let rate_limits = rate_limits.clone();
+                let rate_limit_key = rate_limit_key.clone();
+                let tenant_metrics = tenant_metrics.clone();
+                async move {
+                    let item = item?;
+                    let caller = Caller {
+                        client: &client,
+                        peer_ip,
+                        rpc: "ValidateTokenStream",
+                        spiffe_id: spiffe_uri.as_deref(),
+                        claims: &claims,
+                        private_claims: private_claims.as_deref(),
+                        certificate: certificate.as_deref(),
+                        forwards_certificates,
+                        authorizer: authorizer.as_deref(),
+                        risk_evaluator: risk_evaluator.as_ref(),
+                        rate_limits: (!rate_limits.is_empty()).then(|| (&*rate_limits, &*rate_limit_key)),
+                        rate_limit_slot: None,
+                        verbosity,
+                        span: None,
+                        tenant_metrics: &tenant_metrics,
+                    };
+                    let correlation_id = Self::stream_correlation_id(&item.correlation_id);
+
+                    // Each streamed token counts as one request
+                    if let Some((rate_limits, key)) = caller.rate_limits {
+                        if let RateLimitDecision::Denied { retry_after } = rate_limits.check(key).await {
+                            let err = Self::rate_limited(retry_after);
+                            if let Some(audit_log) = &audit_log {
+                                let decision = AccessDecision::Deny(err.code());
+                                let id = correlation_id.to_string();
+                                let event = AuditEvent::new(decision, caller.rpc, &client, &id)
+                                    .with_caller_spiffe_id(caller.spiffe_id);
+                                audit_log.record(event).await;
+                            }
+                            return Ok(ValidateTokenStreamResponse {
+                                correlation_id: item.correlation_id,
+                                response: Some(Self::error_to_response(
+                                    &err,
+                                    correlation_id,
+                                    caller.verbosity,
+                                )),
+                            });
+                        }
+                    }
+                    let response = Self::validate_request(
+                        &validator,
+                        &logger,
+                        access_log.as_deref(),
+                        audit_log.as_deref(),
+                        &caller,
+                        item.request.unwrap_or_default(),
+                        correlation_id,
+                    )
+                    .await;
+
+                    Ok(ValidateTokenStreamResponse {
+                        correlation_id: item.correlation_id,
+                        response: Some(response),
+                    })
+                }
+            })
+            .buffer_unordered(STREAM_MAX_IN_FLIGHT);
+
+        Ok(Response::new(Box::pin(responses)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_correlation_id_reuses_uuid() {
+        let id = Uuid::new_v4();
+        assert_eq!(AuthEdgeServiceImpl::stream_correlation_id(&id.to_string()), id);
+        assert_ne!(AuthEdgeServiceImpl::stream_correlation_id("req-42"), Uuid::nil());
+    }
+
+    #[tokio::test]
+    async fn test_report_limits_fills_slot() {
+        let policy: crate::rate_limiter::RateLimitPolicy =
+            serde_json::from_str(r#"[{"dimensions": ["method"], "limit": "10/1m"}]"#).unwrap();
+        let rate_limits = KeyedRateLimiter::new(
+            &policy,
+            &crate::rate_limiter::RateLimitConfig {
+                burst_capacity: 0,
+                warmup_period: std::time::Duration::ZERO,
+                ..Default::default()
+            },
+        );
+        let key = RateLimitKey::new("ValidateToken");
+        let slot = RateLimitSlot::default();
+        for _ in 0..7 {
+            rate_limits.check(&key).await;
+        }
+
+        AuthEdgeServiceImpl::report_limits(&QuotaWarnings::new(vec![90]), &rate_limits, &key, &slot)
+            .await;
+        let limits = slot.limits().unwrap();
+        assert_eq!((limits.limit, limits.remaining), (7, 0));
+        assert_eq!(slot.header_value().as_deref(), Some("90%"));
+    }
+
+    #[test]
+    fn test_certificate_binding_trusts_only_forwarding_proxies() {
+        use base64::Engine;
+
+        let holder: &[u8] = b"token holder certificate";
+        let gateway: &[u8] = b"gateway certificate";
+        let claims: Claims = serde_json::from_value(serde_json::json!({
+            "iss": "https://auth.example.com",
+            "sub": "client-1",
+            "aud": "api",
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "iat": chrono::Utc::now().timestamp(),
+            "cnf": {"x5t#S256": crate::mtls::certificate_thumbprint(holder)},
+        }))
+        .unwrap();
+        let claim_set = ClaimSet::All;
+        let tenant_metrics = TenantMetrics::new(
+            "test",
+            &prometheus::Registry::new(),
+            Arc::new(TenantBuckets::new(1)),
+        )
+        .unwrap();
+        let caller = |certificate, forwards_certificates| Caller {
+            client: "gateway",
+            peer_ip: None,
+            rpc: "ValidateToken",
+            spiffe_id: None,
+            claims: &claim_set,
+            private_claims: None,
+            certificate: Some(certificate),
+            forwards_certificates,
+            authorizer: None,
+            risk_evaluator: &NoopRiskEvaluator,
+            rate_limits: None,
+            rate_limit_slot: None,
+            verbosity: ErrorVerbosity::Detailed,
+            span: None,
+            tenant_metrics: &tenant_metrics,
+        };
+        let direct = ValidateTokenRequest::default();
+        let forwarded = ValidateTokenRequest {
+            client_certificate_pem: Some(format!(
+                "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+                base64::engine::general_purpose::STANDARD.encode(holder)
+            )),
+            ..Default::default()
+        };
+
+        // The holder's own connection proves possession
+        let binding =
+            AuthEdgeServiceImpl::certificate_binding(&caller(holder, false), &direct, &claims)
+                .unwrap()
+                .unwrap();
+        assert_eq!(binding.r#type, "mtls");
+        assert!(binding.verified);
+
+        // Anyone else pasting the holder's public certificate is rejected
+        assert!(matches!(
+            AuthEdgeServiceImpl::certificate_binding(&caller(gateway, false), &direct, &claims),
+            Err(AuthEdgeError::TokenBindingMismatch)
+        ));
+        assert!(matches!(
+            AuthEdgeServiceImpl::certificate_binding(&caller(gateway, false), &forwarded, &claims),
+            Err(AuthEdgeError::AccessDenied { .. })
+        ));
+
+        // A trusted proxy forwards the end client's certificate
+        let binding =
+            AuthEdgeServiceImpl::certificate_binding(&caller(gateway, true), &forwarded, &claims)
+                .unwrap()
+                .unwrap();
+        assert!(binding.verified);
+    }
+
+    #[tokio::test]
+    async fn test_introspect_batch_answers_each_token_in_order() {
+        // 8/1m admits 6 requests of an unknown caller
+        let config = Config::from_source(&crate::config::EnvSource::with_overrides(
+            HashMap::from([
+                ("REVOCATION_CHECK_ENABLED".to_string(), "false".to_string()),
+                ("INTROSPECT_BATCH_MAX_TOKENS".to_string(), "2".to_string()),
+                ("RATE_LIMIT_BURST".to_string(), "0".to_string()),
+                ("RATE_LIMIT_WARMUP".to_string(), "0".to_string()),
+                (
+                    "RATE_LIMIT_POLICY".to_string(),
+                    r#"[{"dimensions": ["method"], "limit": "8/1m"}]"#.to_string(),
+                ),
+            ]),
+        ))
+        .unwrap();
+        let service = AuthEdgeServiceImpl::new(config).await.unwrap();
+        let batch = |tokens: &[&str]| {
+            let mut request = Request::new(IntrospectBatchRequest {
+                tokens: tokens
+                    .iter()
+                    .map(|token| IntrospectTokenRequest {
+                        token: token.to_string(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            });
+            request.extensions_mut().insert(ApiKeyIdentity {
+                key_id: "orders-rs".to_string(),
+            });
+            request
+        };
+
+        let results = service
+            .introspect_batch(batch(&["not-a-jwt", "also.not.a-jwt"]))
+            .await
+            .unwrap()
+            .into_inner()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| !result.active));
+
+        let status = service
+            .introspect_batch(batch(&["a", "b", "c"]))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = service
+            .introspect_batch(Request::new(IntrospectBatchRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        // Each token was charged, so 5 of 6 requests are spent and a third
+        // batch of two exceeds the limit
+        let status = service
+            .introspect_batch(batch(&["d", "e"]))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    }
 }
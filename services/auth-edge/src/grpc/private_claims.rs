@@ -0,0 +1,228 @@
+//! Private Claims
+//!
+//! The token service encrypts the custom claims named in its `PRIVATE_CLAIMS`
+//! into `enc.` envelopes bound to the token's `jti` and the claim name (see
+//! `rust_common::claim_envelope`). Callers matching `PRIVATE_CLAIM_READERS`
+//! receive them decrypted through the crypto-service in `ValidateToken`
+//! responses; every other caller receives the envelope as issued. A claim
+//! that fails to decrypt stays encrypted and the token stays valid.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use prost_types::value::Kind;
+use rust_common::claim_envelope::{aad, ClaimEnvelope};
+use rust_common::PlatformError;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::crypto::fallback::EncryptedData;
+use crate::crypto::{CryptoClient, CryptoError, KeyId};
+use crate::error::AuthEdgeError;
+use crate::mtls::{OwnedSpiffeId, SpiffeIdPattern};
+use crate::proto::auth::v1::ValidateTokenResponse;
+
+/// Algorithm of every claim envelope
+const ENVELOPE_ALGORITHM: &str = "AES-256-GCM";
+
+/// Decrypts claim envelopes
+#[async_trait]
+pub trait ClaimDecryptor: Send + Sync {
+    /// JSON plaintext of an envelope sealed with `aad`
+    async fn decrypt(
+        &self,
+        envelope: &ClaimEnvelope,
+        aad: &[u8],
+        correlation_id: &str,
+    ) -> Result<Vec<u8>, CryptoError>;
+}
+
+#[async_trait]
+impl ClaimDecryptor for CryptoClient {
+    async fn decrypt(
+        &self,
+        envelope: &ClaimEnvelope,
+        aad: &[u8],
+        correlation_id: &str,
+    ) -> Result<Vec<u8>, CryptoError> {
+        let encrypted = EncryptedData {
+            ciphertext: envelope.ciphertext.clone(),
+            iv: envelope.iv.clone(),
+            tag: envelope.tag.clone(),
+            key_id: KeyId::new(
+                envelope.key_namespace.clone(),
+                envelope.key_id.clone(),
+                envelope.key_version,
+            ),
+            algorithm: ENVELOPE_ALGORITHM.to_string(),
+        };
+        CryptoClient::decrypt(self, &encrypted, Some(aad), correlation_id).await
+    }
+}
+
+/// Decrypts private claims for authorized callers
+pub struct PrivateClaims {
+    decryptor: Arc<dyn ClaimDecryptor>,
+    readers: Vec<SpiffeIdPattern>,
+}
+
+impl PrivateClaims {
+    /// Creates private claim decryption for callers matching `readers`
+    pub fn new(decryptor: Arc<dyn ClaimDecryptor>, readers: Vec<SpiffeIdPattern>) -> Self {
+        Self { decryptor, readers }
+    }
+
+    /// Builds decryption from configuration, or `None` without readers
+    pub async fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        if config.private_claim_readers.is_empty() {
+            return Ok(None);
+        }
+        let client = CryptoClient::new(config.crypto_client_config())
+            .await
+            .map_err(|e| PlatformError::invalid_input(format!("private claims: {e}")))?;
+        Ok(Some(Self::new(
+            Arc::new(client),
+            config.private_claim_readers.clone(),
+        )))
+    }
+
+    /// Whether a caller may read private claims
+    pub fn may_read(&self, caller: Option<&OwnedSpiffeId>) -> bool {
+        caller.is_some_and(|id| self.readers.iter().any(|pattern| pattern.matches(id)))
+    }
+
+    /// Replaces the envelopes in a valid response with their claim values;
+    /// strings verbatim, other values as JSON
+    pub async fn reveal(&self, response: &mut ValidateTokenResponse, correlation_id: &str) {
+        let Some(claims) = response.claims.as_mut() else {
+            return;
+        };
+        for (name, value) in &mut claims.fields {
+            let Some(Kind::StringValue(text)) = &value.kind else {
+                continue;
+            };
+            let Some(envelope) = ClaimEnvelope::parse(text) else {
+                continue;
+            };
+
+            let plaintext = match self
+                .decryptor
+                .decrypt(&envelope, &aad(&response.jwt_id, name), correlation_id)
+                .await
+            {
+                Ok(plaintext) => plaintext,
+                Err(err) => {
+                    warn!(claim = %name, error = %err, "Private claim not decrypted");
+                    continue;
+                }
+            };
+            match serde_json::from_slice(&plaintext) {
+                Ok(serde_json::Value::String(s)) => value.kind = Some(Kind::StringValue(s)),
+                Ok(other) => value.kind = Some(Kind::StringValue(other.to_string())),
+                Err(err) => warn!(claim = %name, error = %err, "Private claim is not JSON"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{Struct, Value};
+    use std::collections::BTreeMap;
+
+    /// "Decrypts" by reversing the ciphertext when the AAD matches
+    struct ReversingDecryptor {
+        aad: Vec<u8>,
+    }
+
+    #[async_trait]
+    impl ClaimDecryptor for ReversingDecryptor {
+        async fn decrypt(
+            &self,
+            envelope: &ClaimEnvelope,
+            aad: &[u8],
+            _correlation_id: &str,
+        ) -> Result<Vec<u8>, CryptoError> {
+            if aad != self.aad {
+                return Err(CryptoError::decryption_failed("tag mismatch"));
+            }
+            Ok(envelope.ciphertext.iter().rev().copied().collect())
+        }
+    }
+
+    fn envelope(plaintext: &[u8]) -> String {
+        ClaimEnvelope {
+            key_namespace: "token".to_string(),
+            key_id: "private-claims".to_string(),
+            key_version: 1,
+            iv: vec![0; 12],
+            ciphertext: plaintext.iter().rev().copied().collect(),
+            tag: vec![1; 16],
+        }
+        .to_compact()
+    }
+
+    fn string(value: &str) -> Value {
+        Value {
+            kind: Some(Kind::StringValue(value.to_string())),
+        }
+    }
+
+    fn private_claims() -> PrivateClaims {
+        PrivateClaims::new(
+            Arc::new(ReversingDecryptor {
+                aad: aad("jti-1", "national_id"),
+            }),
+            vec!["spiffe://example.org/ns/kyc/sa/*".parse().unwrap()],
+        )
+    }
+
+    fn response(national_id: &str) -> ValidateTokenResponse {
+        ValidateTokenResponse {
+            valid: true,
+            jwt_id: "jti-1".to_string(),
+            claims: Some(Struct {
+                fields: BTreeMap::from([
+                    ("national_id".to_string(), string(national_id)),
+                    ("tenant".to_string(), string("acme")),
+                ]),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn field(response: &ValidateTokenResponse, name: &str) -> Option<Kind> {
+        response.claims.as_ref().unwrap().fields[name].kind.clone()
+    }
+
+    #[test]
+    fn test_only_matching_callers_read_private_claims() {
+        let private_claims = private_claims();
+        let reader = OwnedSpiffeId::parse("spiffe://example.org/ns/kyc/sa/verifier").unwrap();
+        let other = OwnedSpiffeId::parse("spiffe://example.org/ns/web/sa/frontend").unwrap();
+        assert!(private_claims.may_read(Some(&reader)));
+        assert!(!private_claims.may_read(Some(&other)));
+        assert!(!private_claims.may_read(None));
+    }
+
+    #[tokio::test]
+    async fn test_reveals_envelopes() {
+        let mut response = response(&envelope(br#""123-45-6789""#));
+        private_claims().reveal(&mut response, "corr").await;
+        assert_eq!(
+            field(&response, "national_id"),
+            Some(Kind::StringValue("123-45-6789".to_string()))
+        );
+        assert_eq!(field(&response, "tenant"), Some(Kind::StringValue("acme".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_undecryptable_envelopes_stay_encrypted() {
+        let sealed = envelope(br#""123-45-6789""#);
+        let mut response = response(&sealed);
+        response.jwt_id = "jti-2".to_string();
+        private_claims().reveal(&mut response, "corr").await;
+        assert_eq!(field(&response, "national_id"), Some(Kind::StringValue(sealed)));
+    }
+}
@@ -0,0 +1,322 @@
+//! Upstream Introspection
+//!
+//! Opaque (reference) tokens carry no claims auth-edge could verify locally.
+//! With `UPSTREAM_INTROSPECTION_URL` set, `IntrospectToken` and
+//! `IntrospectBatch` send any token that is not a JWS to that RFC 7662
+//! endpoint, the token service's or an external authorization server's,
+//! authenticating with HTTP Basic client credentials when configured. Results
+//! are cached by the SHA-256 of the token for `UPSTREAM_INTROSPECTION_CACHE_TTL`
+//! seconds, never past the token's `exp`, and mapped into the usual
+//! `IntrospectTokenResponse`. An unreachable or failing endpoint leaves the
+//! token inactive.
+
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use rust_common::PlatformError;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::proto::auth::v1::IntrospectTokenResponse;
+
+type TokenHash = [u8; 32];
+
+/// RFC 7662 introspection response
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct UpstreamResponse {
+    /// Whether the token is active
+    pub active: bool,
+    /// Space-separated scopes
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Client the token was issued to
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Human-readable resource owner
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Token type, e.g. `Bearer`
+    #[serde(default)]
+    pub token_type: Option<String>,
+    /// Expiration (Unix seconds)
+    #[serde(default)]
+    pub exp: Option<i64>,
+    /// Issued at (Unix seconds)
+    #[serde(default)]
+    pub iat: Option<i64>,
+    /// Not before (Unix seconds)
+    #[serde(default)]
+    pub nbf: Option<i64>,
+    /// Subject
+    #[serde(default)]
+    pub sub: Option<String>,
+    /// Audience, a string or an array of strings
+    #[serde(default)]
+    pub aud: Option<serde_json::Value>,
+    /// Issuer
+    #[serde(default)]
+    pub iss: Option<String>,
+    /// Token ID
+    #[serde(default)]
+    pub jti: Option<String>,
+}
+
+impl UpstreamResponse {
+    /// Audiences of the token
+    pub fn audiences(&self) -> Vec<String> {
+        match &self.aud {
+            Some(serde_json::Value::String(aud)) => vec![aud.clone()],
+            Some(serde_json::Value::Array(auds)) => auds
+                .iter()
+                .filter_map(|aud| aud.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Maps the response into an `IntrospectTokenResponse`
+    ///
+    /// Inactive tokens reveal nothing but `active: false`.
+    pub fn into_response(self) -> IntrospectTokenResponse {
+        if !self.active {
+            return IntrospectTokenResponse::default();
+        }
+        let audiences = self.audiences();
+        IntrospectTokenResponse {
+            active: true,
+            scope: self.scope,
+            client_id: self.client_id,
+            username: self.username,
+            token_type: self.token_type.or_else(|| Some("Bearer".to_string())),
+            exp: self.exp,
+            iat: self.iat,
+            nbf: self.nbf,
+            sub: self.sub,
+            aud: (audiences.len() == 1).then(|| audiences[0].clone()),
+            iss: self.iss,
+            jti: self.jti,
+            ..Default::default()
+        }
+    }
+
+    /// How long the response may be reused, at most `max_ttl`
+    fn ttl(&self, max_ttl: Duration, now_unix: i64) -> Duration {
+        match self.exp.filter(|_| self.active) {
+            Some(exp) => {
+                max_ttl.min(Duration::from_secs(u64::try_from(exp - now_unix).unwrap_or(0)))
+            }
+            None => max_ttl,
+        }
+    }
+}
+
+struct CachedResponse {
+    response: UpstreamResponse,
+    expires_at: Instant,
+}
+
+/// Client of an RFC 7662 introspection endpoint for opaque tokens
+pub struct UpstreamIntrospector {
+    http: reqwest::Client,
+    url: String,
+    credentials: Option<(String, String)>,
+    cache: Mutex<LruCache<TokenHash, CachedResponse>>,
+    cache_ttl: Duration,
+}
+
+impl UpstreamIntrospector {
+    /// Creates a client of the endpoint at `url`, caching up to `cache_size`
+    /// results for at most `cache_ttl`
+    pub fn new(
+        url: &str,
+        credentials: Option<(String, String)>,
+        timeout: Duration,
+        cache_ttl: Duration,
+        cache_size: NonZeroUsize,
+    ) -> Result<Self, AuthEdgeError> {
+        let http = reqwest::Client::builder().timeout(timeout).build().map_err(|e| {
+            AuthEdgeError::Platform(PlatformError::Internal(format!(
+                "Failed to create introspection client: {e}"
+            )))
+        })?;
+        Ok(Self {
+            http,
+            url: url.to_string(),
+            credentials,
+            cache: Mutex::new(LruCache::new(cache_size)),
+            cache_ttl,
+        })
+    }
+
+    /// Builds the client from configuration, or `None` when no endpoint is set
+    pub fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        let Some(url) = &config.upstream_introspection_url else {
+            return Ok(None);
+        };
+        let credentials = config
+            .upstream_introspection_client_id
+            .clone()
+            .zip(config.upstream_introspection_client_secret.clone());
+        Self::new(
+            url,
+            credentials,
+            Duration::from_millis(config.upstream_introspection_timeout_ms),
+            Duration::from_secs(config.upstream_introspection_cache_ttl_secs),
+            NonZeroUsize::new(config.upstream_introspection_cache_size)
+                .unwrap_or(NonZeroUsize::MIN),
+        )
+        .map(Some)
+    }
+
+    /// Whether the token is opaque rather than a JWS
+    pub fn is_opaque(token: &str) -> bool {
+        jsonwebtoken::decode_header(token).is_err()
+    }
+
+    /// Introspects an opaque token, from the cache when possible
+    pub async fn introspect(&self, token: &str) -> Result<UpstreamResponse, AuthEdgeError> {
+        let key: TokenHash = Sha256::digest(token.as_bytes()).into();
+        if let Some(response) = self.cached(&key, Instant::now()) {
+            return Ok(response);
+        }
+
+        let response = self.fetch(token).await.map_err(|e| {
+            tracing::warn!(error = %e, "Upstream token introspection failed");
+            if e.is_timeout() {
+                AuthEdgeError::Platform(PlatformError::Timeout(
+                    "upstream introspection timed out".to_string(),
+                ))
+            } else {
+                AuthEdgeError::Platform(PlatformError::Unavailable(
+                    "upstream introspection unavailable".to_string(),
+                ))
+            }
+        })?;
+        let ttl = response.ttl(self.cache_ttl, chrono::Utc::now().timestamp());
+        if !ttl.is_zero() {
+            self.cache.lock().put(
+                key,
+                CachedResponse {
+                    response: response.clone(),
+                    expires_at: Instant::now() + ttl,
+                },
+            );
+        }
+        Ok(response)
+    }
+
+    fn cached(&self, key: &TokenHash, now: Instant) -> Option<UpstreamResponse> {
+        let mut cache = self.cache.lock();
+        match cache.get(key) {
+            Some(cached) if cached.expires_at > now => Some(cached.response.clone()),
+            Some(_) => {
+                cache.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn fetch(&self, token: &str) -> Result<UpstreamResponse, reqwest::Error> {
+        let mut request = self
+            .http
+            .post(&self.url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[("token", token), ("token_type_hint", "access_token")]);
+        if let Some((client_id, client_secret)) = &self.credentials {
+            request = request.basic_auth(client_id, Some(client_secret));
+        }
+        request.send().await?.error_for_status()?.json().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, header_exists, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn introspector(url: &str) -> UpstreamIntrospector {
+        UpstreamIntrospector::new(
+            url,
+            Some(("auth-edge".to_string(), "s3cret".to_string())),
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+            NonZeroUsize::new(16).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_non_jws_tokens_are_opaque() {
+        assert!(UpstreamIntrospector::is_opaque("2YotnFZFEjr1zCsicMWpAA"));
+        assert!(!UpstreamIntrospector::is_opaque(
+            "eyJhbGciOiJSUzI1NiIsInR5cCI6IkpXVCJ9.e30.c2ln"
+        ));
+    }
+
+    #[test]
+    fn test_response_mapping() {
+        let response: UpstreamResponse = serde_json::from_value(serde_json::json!({
+            "active": true,
+            "scope": "orders:read",
+            "client_id": "web",
+            "sub": "user-1",
+            "aud": ["https://orders.example.com"],
+            "exp": 2_000,
+        }))
+        .unwrap();
+        assert_eq!(response.audiences(), ["https://orders.example.com"]);
+        assert_eq!(response.ttl(Duration::from_secs(60), 1_990), Duration::from_secs(10));
+
+        let mapped = response.into_response();
+        assert!(mapped.active);
+        assert_eq!(mapped.aud.as_deref(), Some("https://orders.example.com"));
+        assert_eq!(mapped.token_type.as_deref(), Some("Bearer"));
+
+        let inactive = UpstreamResponse {
+            sub: Some("user-1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(inactive.into_response(), IntrospectTokenResponse::default());
+    }
+
+    #[tokio::test]
+    async fn test_results_are_cached_by_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header_exists("authorization"))
+            .and(body_string_contains("token=opaque-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "active": true,
+                "sub": "user-1",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let introspector = introspector(&server.uri());
+
+        for _ in 0..2 {
+            let response = introspector.introspect("opaque-1").await.unwrap();
+            assert_eq!(response.sub.as_deref(), Some("user-1"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_endpoint_is_unavailable() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        assert!(matches!(
+            introspector(&server.uri()).introspect("opaque-1").await,
+            Err(AuthEdgeError::Platform(PlatformError::Unavailable(_)))
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+//! Audience-Restricted Signing Keys
+//!
+//! The token service can sign each audience with a dedicated key
+//! (`AUDIENCE_SIGNING_KEYS`), so a leaked key only mints tokens for one
+//! audience. `AUDIENCE_KEY_IDS` tells the edge which key IDs belong to which
+//! audience; [`AudienceKeyPolicy`] then rejects tokens for a restricted
+//! audience signed with any other key, and tokens signed with an audience's
+//! key that are not addressed to that audience.
+
+use std::collections::HashMap;
+
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+
+/// Key IDs allowed to sign each restricted audience
+#[derive(Debug, Clone, Default)]
+pub struct AudienceKeyPolicy {
+    keys: HashMap<String, Vec<String>>,
+}
+
+impl AudienceKeyPolicy {
+    /// Creates a policy from an audience to key IDs mapping
+    pub fn new(keys: HashMap<String, Vec<String>>) -> Self {
+        Self { keys }
+    }
+
+    /// Checks the signing key ID against the token's audiences
+    pub fn check(&self, kid: Option<&str>, claims: &Claims) -> Result<(), AuthEdgeError> {
+        for (audience, kids) in &self.keys {
+            let addressed = claims.aud.contains(audience);
+            let signed_with_audience_key = kid.is_some_and(|kid| kids.iter().any(|k| k == kid));
+            if addressed != signed_with_audience_key {
+                return Err(AuthEdgeError::TokenInvalid);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(aud: &[&str]) -> Claims {
+        Claims {
+            iss: "https://auth.example.com".to_string(),
+            sub: "user".to_string(),
+            aud: aud.iter().map(|a| a.to_string()).collect(),
+            exp: chrono::Utc::now().timestamp() + 300,
+            iat: chrono::Utc::now().timestamp(),
+            nbf: None,
+            jti: "jti".to_string(),
+            session_id: None,
+            scopes: None,
+            custom: HashMap::new(),
+        }
+    }
+
+    fn policy() -> AudienceKeyPolicy {
+        AudienceKeyPolicy::new(HashMap::from([
+            ("internal-api".to_string(), vec!["internal-key".to_string()]),
+            (
+                "partner-api".to_string(),
+                vec!["partner-key".to_string(), "partner-key-next".to_string()],
+            ),
+        ]))
+    }
+
+    #[test]
+    fn test_restricted_audience_requires_its_key() {
+        let policy = policy();
+        assert!(policy.check(Some("internal-key"), &claims(&["internal-api"])).is_ok());
+        assert!(policy.check(Some("partner-key-next"), &claims(&["partner-api"])).is_ok());
+        assert!(matches!(
+            policy.check(Some("partner-key"), &claims(&["internal-api"])),
+            Err(AuthEdgeError::TokenInvalid)
+        ));
+        assert!(policy.check(Some("issuer-key"), &claims(&["internal-api"])).is_err());
+        assert!(policy.check(None, &claims(&["internal-api"])).is_err());
+    }
+
+    #[test]
+    fn test_audience_key_only_signs_its_audience() {
+        let policy = policy();
+        assert!(policy.check(Some("issuer-key"), &claims(&["api"])).is_ok());
+        assert!(policy.check(Some("partner-key"), &claims(&["api"])).is_err());
+        assert!(policy
+            .check(Some("partner-key"), &claims(&["partner-api", "internal-api"]))
+            .is_err());
+        assert!(AudienceKeyPolicy::default().check(None, &claims(&["internal-api"])).is_ok());
+    }
+}
@@ -0,0 +1,261 @@
+//! Emergency Bypass Tokens
+//!
+//! Break-glass path for when the IdP (and therefore the JWKS endpoint) is down.
+//! Bypass tokens carry the `emergency+jwt` type header, are signed by an
+//! offline emergency key that is never published through JWKS, and must be
+//! short-lived. Every use is audited and counted.
+
+use std::time::Duration;
+
+use jsonwebtoken::{Algorithm, DecodingKey};
+use rust_common::PlatformError;
+use tracing::{error, warn};
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::jwt::token::{Token, Unvalidated, Validated};
+use crate::observability::metrics::BypassMetrics;
+
+/// JWT `typ` header value marking an emergency bypass token.
+pub const EMERGENCY_BYPASS_TYP: &str = "emergency+jwt";
+
+/// Hard upper bound on the configurable bypass token lifetime (1 hour).
+pub const MAX_BYPASS_LIFETIME_SECS: u64 = 3600;
+
+/// Allowed clock skew for the `iat` claim.
+const IAT_LEEWAY_SECS: i64 = 60;
+
+/// Validates emergency bypass tokens against the dedicated offline key.
+pub struct BypassValidator {
+    key: DecodingKey,
+    algorithm: Algorithm,
+    max_lifetime: Duration,
+    metrics: BypassMetrics,
+}
+
+impl BypassValidator {
+    /// Creates a validator from an already-loaded decoding key.
+    pub fn new(
+        key: DecodingKey,
+        algorithm: Algorithm,
+        max_lifetime: Duration,
+        metrics: BypassMetrics,
+    ) -> Self {
+        Self {
+            key,
+            algorithm,
+            max_lifetime,
+            metrics,
+        }
+    }
+
+    /// Creates a validator from a PEM-encoded public key.
+    ///
+    /// Only asymmetric algorithms are accepted: the private half of the
+    /// emergency key must stay offline.
+    pub fn from_pem(
+        pem: &[u8],
+        algorithm: Algorithm,
+        max_lifetime: Duration,
+        metrics: BypassMetrics,
+    ) -> Result<Self, AuthEdgeError> {
+        let key = match algorithm {
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512 => DecodingKey::from_rsa_pem(pem),
+            Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem),
+            Algorithm::EdDSA => DecodingKey::from_ed_pem(pem),
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                return Err(invalid_key("symmetric algorithms are not allowed"));
+            }
+        }
+        .map_err(|e| invalid_key(&e.to_string()))?;
+
+        Ok(Self::new(key, algorithm, max_lifetime, metrics))
+    }
+
+    /// Loads the validator described by the configuration, if one is configured.
+    pub fn from_config(
+        config: &Config,
+        metrics: BypassMetrics,
+    ) -> Result<Option<Self>, AuthEdgeError> {
+        let Some(path) = config.emergency_bypass_key_path.as_ref() else {
+            return Ok(None);
+        };
+
+        let pem = std::fs::read(path)?;
+        Self::from_pem(
+            &pem,
+            config.emergency_bypass_algorithm,
+            Duration::from_secs(config.emergency_bypass_max_lifetime_secs),
+            metrics,
+        )
+        .map(Some)
+    }
+
+    /// Validates a bypass token: signature, lifetime bound, then standard claims.
+    pub fn validate(
+        &self,
+        token: Token<Unvalidated>,
+        required_claims: &[&str],
+    ) -> Result<Token<Validated>, AuthEdgeError> {
+        match self.validate_inner(token, required_claims) {
+            Ok(validated) => {
+                self.metrics.record_accepted();
+                let claims = validated.claims();
+                error!(
+                    target: "audit",
+                    event_type = "emergency_bypass_used",
+                    subject = %claims.sub,
+                    issuer = %claims.iss,
+                    jti = %claims.jti,
+                    issued_at = claims.iat,
+                    expires_at = claims.exp,
+                    "Emergency bypass token accepted"
+                );
+                Ok(validated)
+            }
+            Err(err) => {
+                self.metrics.record_rejected(err.code().as_str());
+                warn!(
+                    target: "audit",
+                    event_type = "emergency_bypass_rejected",
+                    error_code = err.code().as_str(),
+                    "Emergency bypass token rejected"
+                );
+                Err(err)
+            }
+        }
+    }
+
+    fn validate_inner(
+        &self,
+        token: Token<Unvalidated>,
+        required_claims: &[&str],
+    ) -> Result<Token<Validated>, AuthEdgeError> {
+        if token.algorithm() != self.algorithm {
+            return Err(AuthEdgeError::TokenInvalid);
+        }
+
+        let signature_validated = token.validate_signature_with_key(&self.key)?;
+        let claims = signature_validated
+            .peek_claims()
+            .ok_or_else(|| AuthEdgeError::TokenMalformed {
+                reason: "Claims not available".to_string(),
+            })?;
+
+        let now = chrono::Utc::now().timestamp();
+        if claims.iat > now + IAT_LEEWAY_SECS {
+            return Err(AuthEdgeError::TokenNotYetValid {
+                valid_from: chrono::DateTime::from_timestamp(claims.iat, 0)
+                    .unwrap_or_else(chrono::Utc::now),
+            });
+        }
+
+        let max_lifetime = i64::try_from(self.max_lifetime.as_secs()).unwrap_or(i64::MAX);
+        if claims.exp.saturating_sub(claims.iat) > max_lifetime {
+            return Err(AuthEdgeError::ClaimsInvalid {
+                claims: vec!["exp".to_string()],
+            });
+        }
+
+        signature_validated.validate_claims(required_claims)
+    }
+
+    /// Maximum accepted token lifetime.
+    pub fn max_lifetime(&self) -> Duration {
+        self.max_lifetime
+    }
+}
+
+fn invalid_key(reason: &str) -> AuthEdgeError {
+    AuthEdgeError::Platform(PlatformError::InvalidInput(format!(
+        "emergency bypass key: {reason}"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use prometheus::Registry;
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"emergency-test-secret";
+
+    fn validator(max_lifetime: Duration) -> BypassValidator {
+        let metrics = BypassMetrics::new(&Registry::new()).unwrap();
+        BypassValidator::new(
+            DecodingKey::from_secret(SECRET),
+            Algorithm::HS256,
+            max_lifetime,
+            metrics,
+        )
+    }
+
+    fn bypass_token(lifetime: i64, secret: &[u8]) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let mut header = Header::new(Algorithm::HS256);
+        header.typ = Some(EMERGENCY_BYPASS_TYP.to_string());
+        let claims = json!({
+            "iss": "break-glass",
+            "sub": "oncall@example.com",
+            "aud": ["auth-edge"],
+            "exp": now + lifetime,
+            "iat": now,
+            "jti": "bypass-1",
+        });
+        encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    #[test]
+    fn test_accepts_short_lived_token() {
+        let validator = validator(Duration::from_secs(900));
+        let token = Token::<Unvalidated>::parse(&bypass_token(600, SECRET)).unwrap();
+        assert!(token.is_emergency_bypass());
+
+        let validated = validator.validate(token, &["sub"]).unwrap();
+        assert_eq!(validated.subject(), "oncall@example.com");
+        assert_eq!(validator.metrics.accepted.get(), 1.0);
+    }
+
+    #[test]
+    fn test_rejects_lifetime_over_max() {
+        let validator = validator(Duration::from_secs(900));
+        let token = Token::<Unvalidated>::parse(&bypass_token(7200, SECRET)).unwrap();
+
+        let result = validator.validate(token, &[]);
+        assert!(matches!(result, Err(AuthEdgeError::ClaimsInvalid { .. })));
+        assert_eq!(
+            validator
+                .metrics
+                .rejected
+                .with_label_values(&["AUTH_CLAIMS_INVALID"])
+                .get(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_rejects_wrong_key() {
+        let validator = validator(Duration::from_secs(900));
+        let token = Token::<Unvalidated>::parse(&bypass_token(600, b"other-secret")).unwrap();
+
+        assert!(validator.validate(token, &[]).is_err());
+    }
+
+    #[test]
+    fn test_from_pem_rejects_symmetric_algorithm() {
+        let metrics = BypassMetrics::new(&Registry::new()).unwrap();
+        let result = BypassValidator::from_pem(
+            b"not-a-key",
+            Algorithm::HS256,
+            Duration::from_secs(900),
+            metrics,
+        );
+        assert!(result.is_err());
+    }
+}
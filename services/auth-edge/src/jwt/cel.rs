@@ -0,0 +1,546 @@
+//! CEL Claim Expressions
+//!
+//! A subset of the Common Expression Language for asserting claim values,
+//! e.g. `claims.tenant == 'acme' && 'admin' in claims.roles`. Expressions are
+//! compiled once and evaluated against the token's claims as JSON.
+//!
+//! Supported: `claims.a.b` and `claims['a'][0]` paths, string, integer,
+//! float, boolean, `null` and list literals, `== != < <= > >=`, `in`, `!`,
+//! `&&`, `||`, parentheses, `has(claims.x)`, `size(x)` / `x.size()`, and the
+//! string methods `startsWith`, `endsWith` and `contains`. As in CEL, `&&`
+//! and `||` absorb errors when the other side decides the result; any other
+//! error (e.g. a missing claim) makes the expression false.
+
+use serde_json::Value;
+
+/// Compiled claim expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimExpression {
+    source: String,
+    root: Expr,
+}
+
+impl ClaimExpression {
+    /// Parses an expression
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.expr()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected {token:?}"));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Expression source text
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the expression evaluates to `true` for `claims`
+    pub fn matches(&self, claims: &Value) -> bool {
+        matches!(eval(&self.root, claims), Ok(Value::Bool(true)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 16] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")", "[", "]", ",", ".", "-",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '\'' || c == '"' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err("unterminated string".to_string()),
+                    Some(&q) if q == c => break,
+                    Some('\\') => {
+                        let escaped = chars.get(i + 1).ok_or("unterminated string")?;
+                        value.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            other => *other,
+                        });
+                        i += 2;
+                    }
+                    Some(&other) => {
+                        value.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push(Token::Str(value));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(if text.contains('.') {
+                Token::Float(text.parse().map_err(|_| format!("invalid number {text}"))?)
+            } else {
+                Token::Int(text.parse().map_err(|_| format!("invalid number {text}"))?)
+            });
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("unexpected character '{c}'"))?;
+            i += op.len();
+            tokens.push(Token::Op(op));
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compare {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    StartsWith,
+    EndsWith,
+    Contains,
+    Size,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    List(Vec<Expr>),
+    Path(Vec<Segment>),
+    Has(Vec<Segment>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Compare, Box<Expr>, Box<Expr>),
+    In(Box<Expr>, Box<Expr>),
+    Call(Method, Box<Expr>, Vec<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(format!("expected '{op}'"))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.relation()?;
+        while self.eat("&&") {
+            left = Expr::And(Box::new(left), Box::new(self.relation()?));
+        }
+        Ok(left)
+    }
+
+    fn relation(&mut self) -> Result<Expr, String> {
+        let left = self.unary()?;
+        let compare = match self.peek() {
+            Some(Token::Op("==")) => Compare::Eq,
+            Some(Token::Op("!=")) => Compare::Ne,
+            Some(Token::Op("<")) => Compare::Lt,
+            Some(Token::Op("<=")) => Compare::Le,
+            Some(Token::Op(">")) => Compare::Gt,
+            Some(Token::Op(">=")) => Compare::Ge,
+            Some(Token::Ident(word)) if word == "in" => {
+                self.pos += 1;
+                return Ok(Expr::In(Box::new(left), Box::new(self.unary()?)));
+            }
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        Ok(Expr::Compare(compare, Box::new(left), Box::new(self.unary()?)))
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return match self.next() {
+                Some(Token::Int(n)) => Ok(Expr::Literal(Value::from(-n))),
+                Some(Token::Float(f)) => Ok(Expr::Literal(Value::from(-f))),
+                _ => Err("expected a number after '-'".to_string()),
+            };
+        }
+        let mut expr = self.primary()?;
+        while self.eat(".") {
+            let method = match self.next() {
+                Some(Token::Ident(name)) => match name.as_str() {
+                    "startsWith" => Method::StartsWith,
+                    "endsWith" => Method::EndsWith,
+                    "contains" => Method::Contains,
+                    "size" => Method::Size,
+                    other => return Err(format!("unknown method {other}")),
+                },
+                _ => return Err("expected a method name".to_string()),
+            };
+            let args = self.args()?;
+            let arity = if method == Method::Size { 0 } else { 1 };
+            if args.len() != arity {
+                return Err(format!("{method:?} takes {arity} argument(s)"));
+            }
+            expr = Expr::Call(method, Box::new(expr), args);
+        }
+        Ok(expr)
+    }
+
+    fn args(&mut self) -> Result<Vec<Expr>, String> {
+        self.expect("(")?;
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.expr()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        Ok(args)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Int(n)) => Ok(Expr::Literal(Value::from(n))),
+            Some(Token::Float(f)) => Ok(Expr::Literal(Value::from(f))),
+            Some(Token::Op("(")) => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Op("[")) => {
+                let mut items = Vec::new();
+                if !self.eat("]") {
+                    loop {
+                        items.push(self.expr()?);
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            Some(Token::Ident(word)) => match word.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "claims" => Ok(Expr::Path(self.path()?)),
+                "has" => {
+                    self.expect("(")?;
+                    if !matches!(self.next(), Some(Token::Ident(root)) if root == "claims") {
+                        return Err("has() takes a claims path".to_string());
+                    }
+                    let path = self.path()?;
+                    self.expect(")")?;
+                    if path.is_empty() {
+                        return Err("has() takes a claims path".to_string());
+                    }
+                    Ok(Expr::Has(path))
+                }
+                "size" => {
+                    let mut args = self.args()?;
+                    if args.len() != 1 {
+                        return Err("size() takes 1 argument".to_string());
+                    }
+                    Ok(Expr::Call(Method::Size, Box::new(args.remove(0)), Vec::new()))
+                }
+                other => Err(format!("unknown identifier {other}")),
+            },
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    /// Path segments after `claims`; stops before a method call
+    fn path(&mut self) -> Result<Vec<Segment>, String> {
+        let mut segments = Vec::new();
+        loop {
+            if matches!(self.peek(), Some(Token::Op(".")))
+                && matches!(self.tokens.get(self.pos + 2), Some(Token::Op("(")))
+            {
+                break;
+            }
+            if self.eat(".") {
+                match self.next() {
+                    Some(Token::Ident(name)) => segments.push(Segment::Key(name)),
+                    _ => return Err("expected a claim name after '.'".to_string()),
+                }
+            } else if self.eat("[") {
+                segments.push(match self.next() {
+                    Some(Token::Str(key)) => Segment::Key(key),
+                    Some(Token::Int(index)) if index >= 0 => Segment::Index(index as usize),
+                    _ => return Err("expected a string key or index".to_string()),
+                });
+                self.expect("]")?;
+            } else {
+                break;
+            }
+        }
+        Ok(segments)
+    }
+}
+
+/// Evaluation error; its message is only used for debugging
+type Eval = Result<Value, &'static str>;
+
+fn lookup<'a>(claims: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter().try_fold(claims, |value, segment| match segment {
+        Segment::Key(key) => value.get(key),
+        Segment::Index(index) => value.get(*index),
+    })
+}
+
+fn eval(expr: &Expr, claims: &Value) -> Eval {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::List(items) => items
+            .iter()
+            .map(|item| eval(item, claims))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Expr::Path(path) => lookup(claims, path).cloned().ok_or("no such claim"),
+        Expr::Has(path) => Ok(Value::Bool(lookup(claims, path).is_some())),
+        Expr::Not(inner) => Ok(Value::Bool(!as_bool(eval(inner, claims)?)?)),
+        Expr::And(left, right) => {
+            match eval(left, claims).and_then(as_bool) {
+                Ok(false) => Ok(Value::Bool(false)),
+                left => match (left, eval(right, claims).and_then(as_bool)) {
+                    (_, Ok(false)) => Ok(Value::Bool(false)),
+                    (Ok(true), Ok(true)) => Ok(Value::Bool(true)),
+                    (Err(e), _) | (_, Err(e)) => Err(e),
+                    (Ok(false), _) => unreachable!("handled above"),
+                },
+            }
+        }
+        Expr::Or(left, right) => {
+            match eval(left, claims).and_then(as_bool) {
+                Ok(true) => Ok(Value::Bool(true)),
+                left => match (left, eval(right, claims).and_then(as_bool)) {
+                    (_, Ok(true)) => Ok(Value::Bool(true)),
+                    (Ok(false), Ok(false)) => Ok(Value::Bool(false)),
+                    (Err(e), _) | (_, Err(e)) => Err(e),
+                    (Ok(true), _) => unreachable!("handled above"),
+                },
+            }
+        }
+        Expr::Compare(op, left, right) => {
+            compare(*op, &eval(left, claims)?, &eval(right, claims)?).map(Value::Bool)
+        }
+        Expr::In(item, container) => {
+            let item = eval(item, claims)?;
+            match eval(container, claims)? {
+                Value::Array(items) => Ok(Value::Bool(items.iter().any(|v| equal(v, &item)))),
+                Value::Object(map) => match item {
+                    Value::String(key) => Ok(Value::Bool(map.contains_key(&key))),
+                    _ => Err("map keys are strings"),
+                },
+                _ => Err("'in' needs a list or map"),
+            }
+        }
+        Expr::Call(method, target, args) => {
+            let target = eval(target, claims)?;
+            if *method == Method::Size {
+                return match target {
+                    Value::String(s) => Ok(Value::from(s.chars().count())),
+                    Value::Array(items) => Ok(Value::from(items.len())),
+                    Value::Object(map) => Ok(Value::from(map.len())),
+                    _ => Err("size() needs a string, list or map"),
+                };
+            }
+            let (Value::String(target), Value::String(arg)) = (target, eval(&args[0], claims)?)
+            else {
+                return Err("string method needs string operands");
+            };
+            Ok(Value::Bool(match method {
+                Method::StartsWith => target.starts_with(&arg),
+                Method::EndsWith => target.ends_with(&arg),
+                Method::Contains => target.contains(&arg),
+                Method::Size => unreachable!("handled above"),
+            }))
+        }
+    }
+}
+
+fn as_bool(value: Value) -> Result<bool, &'static str> {
+    value.as_bool().ok_or("expected a boolean")
+}
+
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(l), Some(r)) => l == r,
+        _ => left == right,
+    }
+}
+
+fn compare(op: Compare, left: &Value, right: &Value) -> Result<bool, &'static str> {
+    match op {
+        Compare::Eq => return Ok(equal(left, right)),
+        Compare::Ne => return Ok(!equal(left, right)),
+        _ => {}
+    }
+    let ordering = match (left, right) {
+        (Value::String(l), Value::String(r)) => l.cmp(r),
+        _ => {
+            let (Some(l), Some(r)) = (left.as_f64(), right.as_f64()) else {
+                return Err("ordering needs two numbers or two strings");
+            };
+            l.partial_cmp(&r).ok_or("numbers are not comparable")?
+        }
+    };
+    Ok(match op {
+        Compare::Lt => ordering.is_lt(),
+        Compare::Le => ordering.is_le(),
+        Compare::Gt => ordering.is_gt(),
+        Compare::Ge => ordering.is_ge(),
+        Compare::Eq | Compare::Ne => unreachable!("handled above"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn matches(source: &str, claims: &Value) -> bool {
+        ClaimExpression::compile(source).unwrap().matches(claims)
+    }
+
+    #[test]
+    fn test_tenant_and_role_assertion() {
+        let admin = json!({"tenant": "acme", "roles": ["admin", "billing"], "amr": ["pwd"]});
+        let source = "claims.tenant == 'acme' && 'admin' in claims.roles";
+        assert!(matches(source, &admin));
+        assert!(!matches(source, &json!({"tenant": "acme", "roles": ["billing"]})));
+        assert!(!matches(source, &json!({"tenant": "beta", "roles": ["admin"]})));
+        assert!(!matches(source, &json!({"roles": ["admin"]})));
+    }
+
+    #[test]
+    fn test_operators_and_functions() {
+        let claims = json!({
+            "sub": "user-42",
+            "acr": 2,
+            "org": {"id": "o-1", "plan": "pro"},
+            "scopes": ["orders:read", "orders:write"],
+            "email": "a@example.com"
+        });
+
+        assert!(matches("claims.acr >= 2 && claims.acr < 3.5", &claims));
+        assert!(matches("claims.org.plan in ['pro', 'enterprise']", &claims));
+        assert!(matches("claims['org']['id'] == \"o-1\"", &claims));
+        assert!(matches("claims.scopes[1] == 'orders:write'", &claims));
+        assert!(matches("size(claims.scopes) == 2 && claims.scopes.size() > 1", &claims));
+        assert!(matches("claims.email.endsWith('@example.com')", &claims));
+        assert!(matches("claims.sub.startsWith('user-') && !claims.sub.contains('admin')", &claims));
+        assert!(matches("has(claims.org.plan) && !has(claims.tenant)", &claims));
+        assert!(matches("'id' in claims.org", &claims));
+        assert!(matches("(claims.acr == 1 || claims.acr == 2) && claims.acr != -1", &claims));
+        assert!(!matches("claims.sub", &claims));
+    }
+
+    #[test]
+    fn test_errors_absorbed_by_logical_operators() {
+        let claims = json!({"tenant": "acme"});
+        assert!(matches("claims.missing == 'x' || claims.tenant == 'acme'", &claims));
+        assert!(!matches("claims.missing == 'x' || claims.tenant == 'beta'", &claims));
+        assert!(!matches("claims.tenant == 'beta' && claims.missing == 'x'", &claims));
+        assert!(!matches("claims.missing == 'x' && true", &claims));
+        assert!(!matches("!(claims.missing == 'x')", &claims));
+        assert!(!matches("claims.tenant < 3", &claims));
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        for source in [
+            "",
+            "claims.tenant ==",
+            "tenant == 'acme'",
+            "claims.tenant == 'acme",
+            "claims.roles.exists(r, r == 'admin')",
+            "has(claims)",
+            "claims.tenant == 'acme' 'x'",
+            "claims.a # b",
+        ] {
+            assert!(ClaimExpression::compile(source).is_err(), "{source}");
+        }
+    }
+}
@@ -1,22 +1,71 @@
 //! JWT Claims structure with validation helpers.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
+use crate::jwt::cel::ClaimExpression;
+
+/// Prefix of `required_claims` entries naming a configured claim expression
+pub const EXPRESSION_PREFIX: &str = "cel:";
+
+/// Named claim expressions from `CLAIM_EXPRESSIONS`, compiled at config load
+#[derive(Debug, Clone, Default)]
+pub struct ClaimExpressions {
+    expressions: HashMap<String, ClaimExpression>,
+}
+
+impl ClaimExpressions {
+    /// Compiles every expression, failing on the first invalid one
+    pub fn compile(sources: &HashMap<String, String>) -> Result<Self, String> {
+        let expressions = sources
+            .iter()
+            .map(|(name, source)| {
+                ClaimExpression::compile(source)
+                    .map(|expression| (name.clone(), expression))
+                    .map_err(|e| format!("expression {name}: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { expressions })
+    }
+
+    /// Expression registered under `name`
+    pub fn get(&self, name: &str) -> Option<&ClaimExpression> {
+        self.expressions.get(name)
+    }
+
+    /// Whether no expressions are configured
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+}
+
 /// JWT Claims structure following RFC 7519.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub iss: String,
     pub sub: String,
+    /// Accepts a single audience string as well as an array (RFC 7519)
+    #[serde(deserialize_with = "string_or_seq")]
     pub aud: Vec<String>,
     pub exp: i64,
     pub iat: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nbf: Option<i64>,
+    /// Empty when the issuer does not assign token IDs
+    #[serde(default)]
     pub jti: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Space-delimited `scope` claim (RFC 8693); a legacy `scopes` array is
+    /// accepted too
+    #[serde(
+        rename = "scope",
+        alias = "scopes",
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "rust_common::scope::serialize",
+        deserialize_with = "rust_common::scope::deserialize"
+    )]
     pub scopes: Option<Vec<String>>,
     #[serde(flatten)]
     pub custom: HashMap<String, serde_json::Value>,
@@ -47,11 +96,38 @@ impl Claims {
             "iat" => true,
             "jti" => !self.jti.is_empty(),
             "session_id" => self.session_id.is_some(),
-            "scopes" => self.scopes.is_some(),
+            "scope" | "scopes" => self.scopes.is_some(),
             _ => self.custom.contains_key(claim_name),
         }
     }
 
+    /// Whether the claims satisfy a compiled expression.
+    pub fn satisfies(&self, expression: &ClaimExpression) -> bool {
+        serde_json::to_value(self).is_ok_and(|claims| expression.matches(&claims))
+    }
+
+    /// Returns the required claims that are missing or, for `cel:<name>`
+    /// entries, whose expression is unknown or does not hold.
+    pub fn unmet(&self, required: &[&str], expressions: &ClaimExpressions) -> Vec<String> {
+        let mut json = None;
+        required
+            .iter()
+            .filter(|required| match required.strip_prefix(EXPRESSION_PREFIX) {
+                Some(name) => match expressions.get(name) {
+                    Some(expression) => {
+                        let claims = json.get_or_insert_with(|| {
+                            serde_json::to_value(self).unwrap_or_default()
+                        });
+                        !expression.matches(claims)
+                    }
+                    None => true,
+                },
+                None => !self.has_claim(required),
+            })
+            .map(|required| required.to_string())
+            .collect()
+    }
+
     /// Converts claims to a string map for gRPC response.
     pub fn to_map(&self) -> HashMap<String, String> {
         let mut map = HashMap::new();
@@ -69,7 +145,101 @@ impl Claims {
         if let Some(ref scopes) = self.scopes {
             map.insert("scopes".to_string(), scopes.join(" "));
         }
-        
+
+        // Custom claims; strings verbatim, other values as JSON
+        for (name, value) in &self.custom {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            map.entry(name.clone()).or_insert(value);
+        }
+
         map
     }
 }
+
+fn string_or_seq<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(aud) => vec![aud],
+        OneOrMany::Many(auds) => auds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unmet_checks_presence_and_expressions() {
+        let claims: Claims = serde_json::from_value(serde_json::json!({
+            "iss": "https://auth.platform",
+            "sub": "user-1",
+            "aud": "api",
+            "exp": 2_000,
+            "iat": 1_000,
+            "tenant": "acme",
+            "roles": ["admin"]
+        }))
+        .unwrap();
+        let expressions = ClaimExpressions::compile(&HashMap::from([(
+            "acme_admin".to_string(),
+            "claims.tenant == 'acme' && 'admin' in claims.roles".to_string(),
+        ), (
+            "beta".to_string(),
+            "claims.tenant == 'beta'".to_string(),
+        )]))
+        .unwrap();
+
+        assert!(claims.unmet(&["sub", "tenant", "cel:acme_admin"], &expressions).is_empty());
+        assert_eq!(
+            claims.unmet(&["session_id", "cel:beta", "cel:unknown"], &expressions),
+            vec!["session_id", "cel:beta", "cel:unknown"]
+        );
+        assert!(ClaimExpressions::compile(&HashMap::from([(
+            "broken".to_string(),
+            "claims.tenant ==".to_string(),
+        )]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_scope_claim_accepts_string_and_array() {
+        let claims = |scope: serde_json::Value, key: &str| -> Claims {
+            let mut json = serde_json::json!({
+                "iss": "https://auth.platform",
+                "sub": "user-1",
+                "aud": "api",
+                "exp": 2_000,
+                "iat": 1_000
+            });
+            json[key] = scope;
+            serde_json::from_value(json).unwrap()
+        };
+
+        let standard = claims(serde_json::json!("openid orders:read"), "scope");
+        let legacy = claims(serde_json::json!(["openid", "orders:read"]), "scopes");
+        for claims in [&standard, &legacy] {
+            assert!(claims.has_scope("orders:read"));
+            assert!(claims.has_claim("scope"));
+            assert!(!claims.custom.contains_key("scope"));
+            assert!(!claims.custom.contains_key("scopes"));
+        }
+        assert_eq!(standard.scopes, legacy.scopes);
+
+        // Re-serialized claims, as seen by claim expressions, use `scope`
+        let json = serde_json::to_value(&legacy).unwrap();
+        assert_eq!(json["scope"], "openid orders:read");
+        assert!(json.get("scopes").is_none());
+    }
+}
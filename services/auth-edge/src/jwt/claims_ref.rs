@@ -0,0 +1,210 @@
+//! Claims by Reference
+//!
+//! With `CLAIMS_BY_REFERENCE`, the token service moves the largest custom
+//! claims of access tokens over its size budget into a Cache_Service entry
+//! (`claims_ref:{id}`), bound to the token's `jti`, and the token carries a
+//! `claims_ref` claim. [`ClaimsRefResolver`] merges those claims back after
+//! the token is verified, so required claims and claim expressions see the
+//! claims as issued.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rust_common::{CacheClient, CacheClientConfig, PlatformError};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+
+/// Claim naming the entry that holds a token's overflow claims
+pub const CLAIMS_REF_CLAIM: &str = "claims_ref";
+
+/// Backing store of overflow claim entries
+#[async_trait]
+pub trait ClaimsRefStore: Send + Sync {
+    /// Raw entry for a `claims_ref` ID, if it exists
+    async fn get(&self, id: &str) -> Result<Option<Vec<u8>>, PlatformError>;
+}
+
+/// Overflow claims shared with the token service through the cache-service
+pub struct CacheClaimsRefStore {
+    cache: CacheClient,
+}
+
+impl CacheClaimsRefStore {
+    /// Connects to the cache-service namespace holding overflow claims
+    pub async fn new(config: &Config) -> Result<Self, PlatformError> {
+        let mut cache_config = CacheClientConfig::default()
+            .with_address(config.cache_service_url_str())
+            .with_namespace(&config.claims_ref_namespace);
+        if let Some(key) = config.claims_ref_encryption_key {
+            cache_config = cache_config.with_encryption_key(key);
+        }
+        Ok(Self {
+            cache: CacheClient::new(cache_config).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl ClaimsRefStore for CacheClaimsRefStore {
+    async fn get(&self, id: &str) -> Result<Option<Vec<u8>>, PlatformError> {
+        self.cache.get(&format!("{CLAIMS_REF_CLAIM}:{id}")).await
+    }
+}
+
+/// Overflow entry as written by the token service
+#[derive(Deserialize)]
+struct OverflowEntry {
+    jti: String,
+    claims: HashMap<String, serde_json::Value>,
+}
+
+/// Restores claims moved out of oversized tokens
+pub struct ClaimsRefResolver {
+    store: Arc<dyn ClaimsRefStore>,
+}
+
+impl ClaimsRefResolver {
+    /// Creates a resolver reading overflow entries from `store`
+    pub fn new(store: Arc<dyn ClaimsRefStore>) -> Self {
+        Self { store }
+    }
+
+    /// Builds the resolver from configuration, or `None` when disabled
+    pub async fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        if !config.claims_ref_enabled {
+            return Ok(None);
+        }
+        let store = CacheClaimsRefStore::new(config).await?;
+        Ok(Some(Self::new(Arc::new(store))))
+    }
+
+    /// Merges the claims referenced by `claims_ref` into `claims`
+    ///
+    /// Tokens without `claims_ref` are left as they are. A missing entry, or
+    /// one written for another `jti`, invalidates the token; claims already
+    /// in the token are never overridden.
+    pub async fn resolve(&self, claims: &mut Claims) -> Result<(), AuthEdgeError> {
+        let Some(reference) = claims.custom.get(CLAIMS_REF_CLAIM) else {
+            return Ok(());
+        };
+        let id = reference.as_str().ok_or(AuthEdgeError::TokenInvalid)?;
+
+        let raw = match self.store.get(id).await {
+            Ok(Some(raw)) => raw,
+            Ok(None) => return Err(AuthEdgeError::TokenInvalid),
+            Err(err) => {
+                warn!(error = %err, "Claims reference store unavailable, rejecting token");
+                return Err(AuthEdgeError::Platform(PlatformError::Unavailable(
+                    "claims reference store".to_string(),
+                )));
+            }
+        };
+        let entry: OverflowEntry =
+            serde_json::from_slice(&raw).map_err(|_| AuthEdgeError::TokenInvalid)?;
+        if entry.jti != claims.jti {
+            return Err(AuthEdgeError::TokenInvalid);
+        }
+
+        for (name, value) in entry.claims {
+            claims.custom.entry(name).or_insert(value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        entries: Mutex<HashMap<String, Vec<u8>>>,
+        unavailable: bool,
+    }
+
+    #[async_trait]
+    impl ClaimsRefStore for MemoryStore {
+        async fn get(&self, id: &str) -> Result<Option<Vec<u8>>, PlatformError> {
+            if self.unavailable {
+                return Err(PlatformError::Unavailable("cache".to_string()));
+            }
+            Ok(self.entries.lock().get(id).cloned())
+        }
+    }
+
+    fn claims(reference: Option<&str>) -> Claims {
+        let mut custom = HashMap::from([("tenant".to_string(), serde_json::json!("acme"))]);
+        if let Some(reference) = reference {
+            custom.insert(CLAIMS_REF_CLAIM.to_string(), serde_json::json!(reference));
+        }
+        Claims {
+            iss: "https://auth.example.com".to_string(),
+            sub: "user".to_string(),
+            aud: vec!["api".to_string()],
+            exp: chrono::Utc::now().timestamp() + 300,
+            iat: chrono::Utc::now().timestamp(),
+            nbf: None,
+            jti: "jti-1".to_string(),
+            session_id: None,
+            scopes: None,
+            custom,
+        }
+    }
+
+    fn resolver() -> ClaimsRefResolver {
+        let store = MemoryStore::default();
+        store.entries.lock().insert(
+            "ref-1".to_string(),
+            serde_json::to_vec(&serde_json::json!({
+                "jti": "jti-1",
+                "claims": {"groups": ["admins", "billing"], "tenant": "other"}
+            }))
+            .unwrap(),
+        );
+        store.entries.lock().insert(
+            "ref-2".to_string(),
+            br#"{"jti": "jti-2", "claims": {"groups": ["admins"]}}"#.to_vec(),
+        );
+        ClaimsRefResolver::new(Arc::new(store))
+    }
+
+    #[tokio::test]
+    async fn test_merges_referenced_claims() {
+        let resolver = resolver();
+        let mut resolved = claims(Some("ref-1"));
+        resolver.resolve(&mut resolved).await.unwrap();
+        assert_eq!(resolved.custom["groups"], serde_json::json!(["admins", "billing"]));
+        assert_eq!(resolved.custom["tenant"], serde_json::json!("acme"));
+        assert!(resolved.unmet(&["groups"], &Default::default()).is_empty());
+
+        let mut plain = claims(None);
+        resolver.resolve(&mut plain).await.unwrap();
+        assert_eq!(plain.custom, claims(None).custom);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_missing_or_foreign_entries() {
+        let resolver = resolver();
+        for reference in ["ref-2", "unknown"] {
+            assert!(matches!(
+                resolver.resolve(&mut claims(Some(reference))).await,
+                Err(AuthEdgeError::TokenInvalid)
+            ));
+        }
+
+        let unavailable = ClaimsRefResolver::new(Arc::new(MemoryStore {
+            unavailable: true,
+            ..Default::default()
+        }));
+        assert!(matches!(
+            unavailable.resolve(&mut claims(Some("ref-1"))).await,
+            Err(AuthEdgeError::Platform(PlatformError::Unavailable(_)))
+        ));
+    }
+}
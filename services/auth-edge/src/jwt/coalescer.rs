@@ -0,0 +1,165 @@
+//! In-Flight Validation Coalescing
+//!
+//! Gateways retry aggressively, so the same token often arrives again before
+//! its first validation has finished. Concurrent validations of one token
+//! with the same required claims and route share a single validation: the
+//! first caller runs it and the others wait for its result. Results are only
+//! shared while in flight; a later request validates again (usually against
+//! the validated-token cache).
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+
+use crate::error::AuthEdgeError;
+use crate::jwt::token::{Token, Validated};
+use crate::observability::ValidationCoalescerMetrics;
+
+type CoalesceKey = [u8; 32];
+
+/// Result shared by the callers of one in-flight validation
+type Slot = Arc<OnceCell<Result<Token<Validated>, AuthEdgeError>>>;
+
+/// Shares in-flight validations among identical requests
+pub struct ValidationCoalescer {
+    in_flight: Mutex<HashMap<CoalesceKey, Slot>>,
+    metrics: ValidationCoalescerMetrics,
+}
+
+impl ValidationCoalescer {
+    /// Creates a coalescer with no validations in flight
+    pub fn new(metrics: ValidationCoalescerMetrics) -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+            metrics,
+        }
+    }
+
+    /// Runs `validate`, or waits for the identical validation already in
+    /// flight
+    ///
+    /// When the caller running a validation is cancelled, one of the
+    /// waiting callers runs its own `validate` instead.
+    pub async fn coalesce<F, Fut>(
+        &self,
+        raw_token: &str,
+        required_claims: &[&str],
+        route: &str,
+        validate: F,
+    ) -> Result<Token<Validated>, AuthEdgeError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Token<Validated>, AuthEdgeError>>,
+    {
+        let key = coalesce_key(raw_token, required_claims, route);
+        let slot = {
+            let mut in_flight = self.in_flight.lock();
+            match in_flight.get(&key) {
+                Some(slot) => {
+                    self.metrics.record_coalesced();
+                    slot.clone()
+                }
+                None => in_flight.entry(key).or_default().clone(),
+            }
+        };
+
+        let result = slot.get_or_init(validate).await.clone();
+
+        // The first caller to finish retires the slot
+        let mut in_flight = self.in_flight.lock();
+        if in_flight.get(&key).is_some_and(|current| Arc::ptr_eq(current, &slot)) {
+            in_flight.remove(&key);
+        }
+        result
+    }
+
+    /// Number of validations in flight
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().len()
+    }
+}
+
+/// Hash of the token, its required claims in any order, and the route
+fn coalesce_key(raw_token: &str, required_claims: &[&str], route: &str) -> CoalesceKey {
+    let mut claims = required_claims.to_vec();
+    claims.sort_unstable();
+    claims.dedup();
+
+    let mut hasher = Sha256::new();
+    hasher.update(raw_token.as_bytes());
+    for claim in claims {
+        hasher.update([0]);
+        hasher.update(claim.as_bytes());
+    }
+    hasher.update([1]);
+    hasher.update(route.as_bytes());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn coalescer() -> ValidationCoalescer {
+        ValidationCoalescer::new(
+            ValidationCoalescerMetrics::new(&prometheus::Registry::new()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_key_ignores_required_claim_order() {
+        assert_eq!(
+            coalesce_key("t", &["sub", "scope"], ""),
+            coalesce_key("t", &["scope", "sub", "sub"], "")
+        );
+        assert_ne!(coalesce_key("t", &["sub"], ""), coalesce_key("t", &[], ""));
+        assert_ne!(coalesce_key("t", &[], "GET /a"), coalesce_key("t", &[], ""));
+        assert_ne!(coalesce_key("t", &["a"], ""), coalesce_key("t", &[], "a"));
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_share_one_validation() {
+        let coalescer = coalescer();
+        let runs = &AtomicUsize::new(0);
+        let validate = move || async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(AuthEdgeError::TokenInvalid)
+        };
+
+        let (a, b, c) = tokio::join!(
+            coalescer.coalesce("t", &["sub"], "", validate),
+            coalescer.coalesce("t", &["sub"], "", validate),
+            coalescer.coalesce("t", &["sub"], "", validate),
+        );
+        assert!([a, b, c].iter().all(|r| matches!(r, Err(AuthEdgeError::TokenInvalid))));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(coalescer.metrics.coalesced.get(), 2.0);
+        assert_eq!(coalescer.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_different_requests_validate_separately() {
+        let coalescer = coalescer();
+        let runs = &AtomicUsize::new(0);
+        let validate = move || async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(AuthEdgeError::TokenExpired { expired_at: chrono::Utc::now() })
+        };
+
+        let _ = tokio::join!(
+            coalescer.coalesce("t", &["sub"], "", validate),
+            coalescer.coalesce("t", &["scope"], "", validate),
+        );
+        let _ = coalescer.coalesce("t", &["sub"], "", validate).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(coalescer.metrics.coalesced.get(), 0.0);
+    }
+}
@@ -0,0 +1,288 @@
+//! OpenID Connect Discovery
+//!
+//! Resolves an issuer's `/.well-known/openid-configuration` document to
+//! locate its `jwks_uri`, signing algorithms and token endpoint. The document
+//! is cached for a TTL and revalidated when a token names an unknown `kid`,
+//! so an issuer moving its key set is picked up without a restart.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::Algorithm;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::error::AuthEdgeError;
+
+/// Path of the discovery document below the issuer URL
+pub const WELL_KNOWN_PATH: &str = "/.well-known/openid-configuration";
+
+/// Subset of an OpenID Connect discovery document
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DiscoveryDocument {
+    /// Issuer identifier; must equal the configured issuer
+    pub issuer: String,
+    /// JWKS endpoint publishing the issuer's signing keys
+    pub jwks_uri: String,
+    /// OAuth 2.0 token endpoint
+    #[serde(default)]
+    pub token_endpoint: Option<String>,
+    /// Signing algorithms the issuer uses for ID tokens
+    #[serde(default)]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+impl DiscoveryDocument {
+    /// Advertised asymmetric algorithms; `none`, HMAC and unknown values are skipped
+    pub fn algorithms(&self) -> Vec<Algorithm> {
+        self.id_token_signing_alg_values_supported
+            .iter()
+            .filter_map(|alg| alg.parse::<Algorithm>().ok())
+            .filter(|alg| !matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))
+            .collect()
+    }
+
+    /// Narrows `configured` to the algorithms the issuer advertises
+    ///
+    /// An issuer that advertises no usable algorithm leaves `configured`
+    /// unchanged.
+    pub fn accepted_algorithms(&self, configured: &[Algorithm]) -> Vec<Algorithm> {
+        let advertised = self.algorithms();
+        if advertised.is_empty() {
+            return configured.to_vec();
+        }
+        configured
+            .iter()
+            .copied()
+            .filter(|alg| advertised.contains(alg))
+            .collect()
+    }
+}
+
+/// Discovery document with the time it was fetched
+struct CachedDocument {
+    document: Arc<DiscoveryDocument>,
+    fetched_at: Instant,
+}
+
+/// Cached discovery document of one issuer
+pub struct OidcDiscovery {
+    issuer: String,
+    url: String,
+    ttl: Duration,
+    http: reqwest::Client,
+    cached: Mutex<Option<CachedDocument>>,
+}
+
+impl OidcDiscovery {
+    /// Creates a discovery client for an explicit document URL
+    pub fn new(
+        issuer: impl Into<String>,
+        discovery_url: impl Into<String>,
+        ttl: Duration,
+    ) -> Result<Self, AuthEdgeError> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| AuthEdgeError::JwkCacheError {
+                reason: format!("Failed to create HTTP client: {e}"),
+            })?;
+
+        Ok(Self {
+            issuer: issuer.into(),
+            url: discovery_url.into(),
+            ttl,
+            http,
+            cached: Mutex::new(None),
+        })
+    }
+
+    /// Creates a discovery client for the issuer's well-known document
+    pub fn for_issuer(issuer: &str, ttl: Duration) -> Result<Self, AuthEdgeError> {
+        let url = format!("{}{WELL_KNOWN_PATH}", issuer.trim_end_matches('/'));
+        Self::new(issuer, url, ttl)
+    }
+
+    /// Returns the expected issuer
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Returns the discovery document URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the cached document, fetching it once the TTL has expired
+    pub async fn document(&self) -> Result<Arc<DiscoveryDocument>, AuthEdgeError> {
+        let mut cached = self.cached.lock().await;
+        if let Some(entry) = cached.as_ref() {
+            if entry.fetched_at.elapsed() < self.ttl {
+                return Ok(entry.document.clone());
+            }
+        }
+        self.refresh(&mut cached).await
+    }
+
+    /// Fetches the document regardless of its age
+    ///
+    /// Used on `kid` misses. When the fetch fails the previously cached
+    /// document, if any, is kept and returned.
+    pub async fn revalidate(&self) -> Result<Arc<DiscoveryDocument>, AuthEdgeError> {
+        let mut cached = self.cached.lock().await;
+        self.refresh(&mut cached).await
+    }
+
+    async fn refresh(
+        &self,
+        cached: &mut Option<CachedDocument>,
+    ) -> Result<Arc<DiscoveryDocument>, AuthEdgeError> {
+        match self.fetch().await {
+            Ok(document) => {
+                let document = Arc::new(document);
+                if cached.as_ref().map(|entry| &entry.document.jwks_uri) != Some(&document.jwks_uri) {
+                    info!(
+                        issuer = %self.issuer,
+                        jwks_uri = %document.jwks_uri,
+                        token_endpoint = document.token_endpoint.as_deref().unwrap_or_default(),
+                        "OIDC discovery document resolved"
+                    );
+                }
+                *cached = Some(CachedDocument {
+                    document: document.clone(),
+                    fetched_at: Instant::now(),
+                });
+                Ok(document)
+            }
+            Err(e) => match cached.as_ref() {
+                Some(entry) => {
+                    warn!(
+                        issuer = %self.issuer,
+                        error = %e,
+                        "OIDC discovery failed, keeping cached document"
+                    );
+                    Ok(entry.document.clone())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn fetch(&self) -> Result<DiscoveryDocument, AuthEdgeError> {
+        let document: DiscoveryDocument = self
+            .http
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if document.issuer != self.issuer {
+            return Err(AuthEdgeError::JwkCacheError {
+                reason: format!(
+                    "discovery document at {} names issuer {} instead of {}",
+                    self.url, document.issuer, self.issuer
+                ),
+            });
+        }
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn document_json(issuer: &str, jwks_uri: &str) -> serde_json::Value {
+        serde_json::json!({
+            "issuer": issuer,
+            "jwks_uri": jwks_uri,
+            "token_endpoint": format!("{issuer}/oauth2/token"),
+            "id_token_signing_alg_values_supported": ["RS256", "ES256", "HS256", "none"],
+        })
+    }
+
+    #[test]
+    fn test_algorithms_skip_hmac_and_none() {
+        let document: DiscoveryDocument =
+            serde_json::from_value(document_json("https://idp.example", "https://idp.example/keys"))
+                .unwrap();
+
+        assert_eq!(document.algorithms(), vec![Algorithm::RS256, Algorithm::ES256]);
+        assert_eq!(
+            document.accepted_algorithms(&[Algorithm::ES256, Algorithm::EdDSA]),
+            vec![Algorithm::ES256]
+        );
+        assert_eq!(document.token_endpoint.as_deref(), Some("https://idp.example/oauth2/token"));
+    }
+
+    #[tokio::test]
+    async fn test_document_cached_until_revalidated() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(WELL_KNOWN_PATH))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(document_json(&server.uri(), "https://keys.example/jwks")),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let discovery = OidcDiscovery::for_issuer(&server.uri(), Duration::from_secs(300)).unwrap();
+        let first = discovery.document().await.unwrap();
+        let cached = discovery.document().await.unwrap();
+        let revalidated = discovery.revalidate().await.unwrap();
+
+        assert!(Arc::ptr_eq(&first, &cached));
+        assert_eq!(revalidated.jwks_uri, "https://keys.example/jwks");
+    }
+
+    #[tokio::test]
+    async fn test_issuer_mismatch_rejected() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(WELL_KNOWN_PATH))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(document_json("https://other.example", "https://keys.example")),
+            )
+            .mount(&server)
+            .await;
+
+        let discovery = OidcDiscovery::for_issuer(&server.uri(), Duration::from_secs(300)).unwrap();
+        assert!(matches!(
+            discovery.document().await,
+            Err(AuthEdgeError::JwkCacheError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_revalidate_keeps_cached_document_on_failure() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(WELL_KNOWN_PATH))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(document_json(&server.uri(), "https://keys.example/jwks")),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(WELL_KNOWN_PATH))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let discovery = OidcDiscovery::for_issuer(&server.uri(), Duration::from_secs(300)).unwrap();
+        discovery.document().await.unwrap();
+
+        let document = discovery.revalidate().await.unwrap();
+        assert_eq!(document.jwks_uri, "https://keys.example/jwks");
+    }
+}
@@ -0,0 +1,348 @@
+//! Token Explanation
+//!
+//! Runs every validation check independently, instead of stopping at the
+//! first failure, so support engineers can see why `ValidateToken` and
+//! `IntrospectToken` disagree about a token. Details carry only check names,
+//! claim names, timestamps and error codes; never token contents.
+
+use crate::jwt::claims::{ClaimExpressions, Claims};
+use crate::jwt::harness::parse_unverified;
+use crate::jwt::issuer::IssuerPolicy;
+use crate::jwt::token::{Token, Unvalidated};
+use crate::jwt::validator::JwtValidator;
+use std::sync::Arc;
+
+/// A single check performed on a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// Cryptographic signature against the issuer's keys
+    Signature,
+    /// `iss` is a trusted issuer allowed to use the header algorithm
+    Issuer,
+    /// `exp` is in the future
+    Expiry,
+    /// `nbf` is in the past
+    NotBefore,
+    /// `aud` satisfies the issuer policy
+    Audience,
+    /// Caller-requested claims are present
+    RequiredClaims,
+    /// Caller-requested scopes are granted
+    Scopes,
+    /// Token has not been revoked
+    Revocation,
+}
+
+impl Check {
+    /// Stable check name
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Signature => "signature",
+            Self::Issuer => "issuer",
+            Self::Expiry => "exp",
+            Self::NotBefore => "nbf",
+            Self::Audience => "aud",
+            Self::RequiredClaims => "required_claims",
+            Self::Scopes => "scopes",
+            Self::Revocation => "revocation",
+        }
+    }
+
+    /// Whether `ValidateToken` enforces this check
+    pub const fn applied_by_validation(&self) -> bool {
+        !matches!(self, Self::Scopes)
+    }
+
+    /// Whether `IntrospectToken` enforces this check
+    pub const fn applied_by_introspection(&self) -> bool {
+        !matches!(self, Self::RequiredClaims | Self::Scopes)
+    }
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Check passed
+    Passed,
+    /// Check failed
+    Failed,
+    /// Check could not run or does not apply in this configuration
+    Skipped,
+}
+
+/// Result of one check with a sanitized explanation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckOutcome {
+    /// Check performed
+    pub check: Check,
+    /// Outcome
+    pub status: CheckStatus,
+    /// Sanitized detail
+    pub detail: String,
+}
+
+impl CheckOutcome {
+    fn passed(check: Check, detail: impl Into<String>) -> Self {
+        Self { check, status: CheckStatus::Passed, detail: detail.into() }
+    }
+
+    fn failed(check: Check, detail: impl Into<String>) -> Self {
+        Self { check, status: CheckStatus::Failed, detail: detail.into() }
+    }
+
+    fn skipped(check: Check, detail: impl Into<String>) -> Self {
+        Self { check, status: CheckStatus::Skipped, detail: detail.into() }
+    }
+
+    /// Whether this failure affects exactly one of the two paths
+    pub fn diverges(&self) -> bool {
+        self.status == CheckStatus::Failed
+            && self.check.applied_by_validation() != self.check.applied_by_introspection()
+    }
+}
+
+impl JwtValidator {
+    /// Evaluates every check on a token without short-circuiting
+    pub async fn explain(
+        &self,
+        raw_token: &str,
+        required_claims: &[&str],
+        required_scopes: &[&str],
+    ) -> Vec<CheckOutcome> {
+        let claims = parse_unverified(raw_token.as_bytes()).ok();
+        let token = match Token::<Unvalidated>::parse(raw_token) {
+            Ok(token) => token,
+            Err(err) => {
+                return vec![CheckOutcome::failed(
+                    Check::Signature,
+                    format!("token could not be parsed ({})", err.code().as_str()),
+                )];
+            }
+        };
+
+        let mut outcomes = Vec::new();
+        let mut policy = None;
+        if token.is_emergency_bypass() {
+            outcomes.push(CheckOutcome::skipped(
+                Check::Signature,
+                "emergency bypass token; see the bypass audit log",
+            ));
+            outcomes.push(CheckOutcome::skipped(Check::Issuer, "emergency bypass token"));
+        } else {
+            let keys = match self.issuers() {
+                Some(issuers) => {
+                    let resolved = token
+                        .unverified_issuer()
+                        .and_then(|iss| issuers.resolve(&iss))
+                        .and_then(|trusted| {
+                            trusted.policy.check_algorithm(token.algorithm())?;
+                            Ok(trusted)
+                        });
+                    match resolved {
+                        Ok(trusted) => {
+                            outcomes.push(CheckOutcome::passed(
+                                Check::Issuer,
+                                format!("trusted issuer, algorithm {:?}", token.algorithm()),
+                            ));
+                            policy = Some(&trusted.policy);
+                            Some(Arc::clone(&trusted.keys))
+                        }
+                        Err(err) => {
+                            outcomes.push(CheckOutcome::failed(Check::Issuer, err.code().as_str()));
+                            None
+                        }
+                    }
+                }
+                None => {
+                    outcomes.push(CheckOutcome::skipped(
+                        Check::Issuer,
+                        "no trusted issuer registry; default JWKS used",
+                    ));
+                    Some(self.default_keys())
+                }
+            };
+
+            outcomes.push(match keys {
+                Some(keys) => match token.validate_signature(&keys).await {
+                    Ok(_) => CheckOutcome::passed(Check::Signature, "verified"),
+                    Err(err) => CheckOutcome::failed(Check::Signature, err.code().as_str()),
+                },
+                None => CheckOutcome::skipped(Check::Signature, "no key set for issuer"),
+            });
+        }
+
+        outcomes.extend(explain_claims(
+            claims.as_ref(),
+            policy,
+            self.claim_expressions(),
+            required_claims,
+            required_scopes,
+            chrono::Utc::now().timestamp(),
+        ));
+        outcomes.push(self.explain_revocation(claims.as_ref()).await);
+        outcomes
+    }
+
+    async fn explain_revocation(&self, claims: Option<&Claims>) -> CheckOutcome {
+        let Some(revocation) = self.revocation() else {
+            return CheckOutcome::skipped(Check::Revocation, "revocation checking is disabled");
+        };
+        let Some(claims) = claims.filter(|claims| !claims.jti.is_empty()) else {
+            return CheckOutcome::skipped(Check::Revocation, "no jti to look up");
+        };
+        match revocation.is_revoked(&claims.jti).await {
+            Ok(true) => CheckOutcome::failed(Check::Revocation, "jti is revoked"),
+            Ok(false) => CheckOutcome::passed(Check::Revocation, "jti is not revoked"),
+            Err(_) if revocation.fail_open() => CheckOutcome::skipped(
+                Check::Revocation,
+                "revocation list unavailable; failing open",
+            ),
+            Err(_) => CheckOutcome::failed(
+                Check::Revocation,
+                "revocation list unavailable; failing closed",
+            ),
+        }
+    }
+}
+
+/// Evaluates the claim checks on (unverified) claims at time `now`
+pub fn explain_claims(
+    claims: Option<&Claims>,
+    policy: Option<&IssuerPolicy>,
+    expressions: &ClaimExpressions,
+    required_claims: &[&str],
+    required_scopes: &[&str],
+    now: i64,
+) -> Vec<CheckOutcome> {
+    let checks = [
+        Check::Expiry,
+        Check::NotBefore,
+        Check::Audience,
+        Check::RequiredClaims,
+        Check::Scopes,
+    ];
+    let Some(claims) = claims else {
+        return checks
+            .into_iter()
+            .map(|check| CheckOutcome::skipped(check, "claims could not be decoded"))
+            .collect();
+    };
+
+    let expiry = if claims.exp < now {
+        CheckOutcome::failed(Check::Expiry, format!("exp {} < now {}", claims.exp, now))
+    } else {
+        CheckOutcome::passed(Check::Expiry, format!("exp {} >= now {}", claims.exp, now))
+    };
+
+    let not_before = match claims.nbf {
+        Some(nbf) if nbf > now => {
+            CheckOutcome::failed(Check::NotBefore, format!("nbf {nbf} > now {now}"))
+        }
+        Some(nbf) => CheckOutcome::passed(Check::NotBefore, format!("nbf {nbf} <= now {now}")),
+        None => CheckOutcome::passed(Check::NotBefore, "nbf absent"),
+    };
+
+    let audience = match policy {
+        Some(policy) => match policy.check_claims(claims) {
+            Ok(()) => CheckOutcome::passed(Check::Audience, "matches issuer policy"),
+            Err(err) => CheckOutcome::failed(Check::Audience, err.code().as_str()),
+        },
+        None => CheckOutcome::skipped(Check::Audience, "no issuer policy applies"),
+    };
+
+    let unmet = claims.unmet(required_claims, expressions);
+    let required = if unmet.is_empty() {
+        CheckOutcome::passed(Check::RequiredClaims, format!("{} present", required_claims.len()))
+    } else {
+        CheckOutcome::failed(Check::RequiredClaims, format!("missing: {}", unmet.join(", ")))
+    };
+
+    let missing: Vec<&str> = required_scopes
+        .iter()
+        .copied()
+        .filter(|scope| !claims.has_scope(scope))
+        .collect();
+    let scopes = if missing.is_empty() {
+        CheckOutcome::passed(Check::Scopes, format!("{} granted", required_scopes.len()))
+    } else {
+        CheckOutcome::failed(Check::Scopes, format!("missing: {}", missing.join(", ")))
+    };
+
+    vec![expiry, not_before, audience, required, scopes]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::Algorithm;
+    use std::collections::HashMap;
+
+    fn claims() -> Claims {
+        Claims {
+            iss: "https://idp.example".to_string(),
+            sub: "user".to_string(),
+            aud: vec!["web".to_string()],
+            exp: 2_000,
+            iat: 1_000,
+            nbf: Some(1_000),
+            jti: "jti".to_string(),
+            session_id: None,
+            scopes: Some(vec!["read".to_string()]),
+            custom: HashMap::new(),
+        }
+    }
+
+    fn status(outcomes: &[CheckOutcome], check: Check) -> CheckStatus {
+        outcomes.iter().find(|o| o.check == check).unwrap().status
+    }
+
+    #[test]
+    fn test_explain_claims_reports_every_failure() {
+        let policy = IssuerPolicy::new(
+            "https://idp.example",
+            vec![Algorithm::RS256],
+            vec!["api".to_string()],
+        );
+        let outcomes = explain_claims(
+            Some(&claims()),
+            Some(&policy),
+            &ClaimExpressions::default(),
+            &["session_id"],
+            &["read", "write"],
+            2_500,
+        );
+
+        assert_eq!(status(&outcomes, Check::Expiry), CheckStatus::Failed);
+        assert_eq!(status(&outcomes, Check::NotBefore), CheckStatus::Passed);
+        assert_eq!(status(&outcomes, Check::Audience), CheckStatus::Failed);
+        assert_eq!(status(&outcomes, Check::RequiredClaims), CheckStatus::Failed);
+        assert_eq!(status(&outcomes, Check::Scopes), CheckStatus::Failed);
+        assert!(outcomes.iter().all(|o| !o.detail.contains("user")));
+    }
+
+    #[test]
+    fn test_required_claims_failure_diverges() {
+        let outcomes = explain_claims(
+            Some(&claims()),
+            None,
+            &ClaimExpressions::default(),
+            &["session_id"],
+            &[],
+            1_500,
+        );
+        let diverging: Vec<Check> = outcomes
+            .iter()
+            .filter(|o| o.diverges())
+            .map(|o| o.check)
+            .collect();
+
+        assert_eq!(diverging, vec![Check::RequiredClaims]);
+        assert_eq!(status(&outcomes, Check::Audience), CheckStatus::Skipped);
+    }
+
+    #[test]
+    fn test_undecodable_claims_are_skipped() {
+        let outcomes = explain_claims(None, None, &ClaimExpressions::default(), &[], &[], 0);
+        assert!(outcomes.iter().all(|o| o.status == CheckStatus::Skipped));
+    }
+}
@@ -0,0 +1,128 @@
+//! Network-free validation entry points
+//!
+//! Drives the same parse/verify/claims pipeline as [`JwtValidator`] without a
+//! JWK cache or gRPC server, so fuzzers and offline tooling can feed arbitrary
+//! bytes straight into token parsing.
+//!
+//! [`JwtValidator`]: crate::jwt::JwtValidator
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::DecodingKey;
+
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+use crate::jwt::token::{DecodeLimits, Token, Unvalidated, Validated};
+use crate::jwt::validator::JwtValidator;
+
+/// Parses untrusted bytes as a JWT and decodes its claims without verifying the signature.
+///
+/// Never use the result for authorization decisions.
+pub fn parse_unverified(raw: &[u8]) -> Result<Claims, AuthEdgeError> {
+    let raw = std::str::from_utf8(raw).map_err(|_| AuthEdgeError::TokenMalformed {
+        reason: "Token is not valid UTF-8".to_string(),
+    })?;
+
+    let token = Token::<Unvalidated>::parse(raw)?;
+    let payload = raw
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| AuthEdgeError::TokenMalformed {
+            reason: "Missing payload segment".to_string(),
+        })?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| AuthEdgeError::TokenMalformed {
+            reason: "Invalid payload encoding".to_string(),
+        })?;
+
+    let claims: Claims =
+        serde_json::from_slice(&bytes).map_err(|_| AuthEdgeError::TokenMalformed {
+            reason: "Invalid claims JSON".to_string(),
+        })?;
+
+    // Touch the accessors the gRPC layer relies on
+    let _ = token.kid();
+    let _ = claims.to_map();
+    Ok(claims)
+}
+
+/// Parses untrusted bytes as a JWT, enforcing `limits` on the payload.
+pub fn parse_with_limits(
+    raw: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Token<Unvalidated>, AuthEdgeError> {
+    let raw = std::str::from_utf8(raw).map_err(|_| AuthEdgeError::TokenMalformed {
+        reason: "Token is not valid UTF-8".to_string(),
+    })?;
+    Token::<Unvalidated>::parse_with_limits(raw, limits)
+}
+
+/// Runs the full validation pipeline on untrusted bytes with a fixed key.
+pub fn validate_with_key(
+    raw: &[u8],
+    key: &DecodingKey,
+    required_claims: &[&str],
+) -> Result<Token<Validated>, AuthEdgeError> {
+    let raw = std::str::from_utf8(raw).map_err(|_| AuthEdgeError::TokenMalformed {
+        reason: "Token is not valid UTF-8".to_string(),
+    })?;
+    JwtValidator::validate_token_with_key(raw, key, required_claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// Replays the committed fuzz corpus; every entry must be rejected without panicking.
+    #[test]
+    fn test_malformed_corpus_is_rejected() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/jwt_validator");
+        let key = DecodingKey::from_secret(b"fuzz-secret");
+        let mut replayed = 0;
+
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let bytes = std::fs::read(entry.unwrap().path()).unwrap();
+            let _ = parse_unverified(&bytes);
+            assert!(validate_with_key(&bytes, &key, &["sub"]).is_err());
+            replayed += 1;
+        }
+
+        assert!(replayed > 0);
+    }
+
+    fn token(payload: &str) -> Vec<u8> {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT","kid":"k1"}"#);
+        format!("{header}.{}.sig", URL_SAFE_NO_PAD.encode(payload)).into_bytes()
+    }
+
+    #[test]
+    fn test_decode_limits_reject_oversized_payloads() {
+        let limits = DecodeLimits {
+            max_payload_bytes: 64,
+            max_claims: 3,
+            max_depth: 2,
+        };
+        let within = r#"{"sub":"s","x":[1],"s":"{[:]}\""}"#;
+        assert!(parse_with_limits(&token(within), &limits).is_ok());
+
+        let oversized = format!(r#"{{"sub":"{}"}}"#, "a".repeat(64));
+        let too_deep = r#"{"sub":"s","x":[[1]]}"#;
+        let too_many = r#"{"a":1,"b":2,"c":3,"d":4}"#;
+        for payload in [oversized.as_str(), too_deep, too_many] {
+            assert!(matches!(
+                parse_with_limits(&token(payload), &limits),
+                Err(AuthEdgeError::TokenMalformed { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rejects_non_utf8() {
+        assert!(matches!(
+            parse_unverified(&[0xff, 0xfe, b'.', b'.']),
+            Err(AuthEdgeError::TokenMalformed { .. })
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+//! Trusted Issuer Registry
+//!
+//! Maps the `iss` claim of incoming tokens to the issuer's JWK set and
+//! validation policy, so tokens from the internal token-service and from
+//! external IdPs are each verified against their own keys, algorithms and
+//! audience rules.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use jsonwebtoken::Algorithm;
+use tracing::info;
+
+use crate::config::{Config, TrustedIssuerConfig};
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+use crate::jwt::jwk_cache::JwkCache;
+use crate::jwt::signed_jwks::JwksTrustAnchor;
+
+/// Validation rules for tokens from one issuer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuerPolicy {
+    issuer: String,
+    algorithms: Vec<Algorithm>,
+    audiences: Vec<String>,
+}
+
+impl IssuerPolicy {
+    /// Creates a policy for the given issuer
+    pub fn new(issuer: impl Into<String>, algorithms: Vec<Algorithm>, audiences: Vec<String>) -> Self {
+        Self {
+            issuer: issuer.into(),
+            algorithms,
+            audiences,
+        }
+    }
+
+    /// Returns the expected `iss` claim
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Rejects header algorithms the issuer is not allowed to use
+    pub fn check_algorithm(&self, algorithm: Algorithm) -> Result<(), AuthEdgeError> {
+        if self.algorithms.contains(&algorithm) {
+            Ok(())
+        } else {
+            Err(AuthEdgeError::TokenInvalid)
+        }
+    }
+
+    /// Checks the verified issuer and audience claims
+    pub fn check_claims(&self, claims: &Claims) -> Result<(), AuthEdgeError> {
+        if claims.iss != self.issuer {
+            return Err(AuthEdgeError::ClaimsInvalid {
+                claims: vec!["iss".to_string()],
+            });
+        }
+        if !self.audiences.is_empty() && !claims.aud.iter().any(|aud| self.audiences.contains(aud)) {
+            return Err(AuthEdgeError::ClaimsInvalid {
+                claims: vec!["aud".to_string()],
+            });
+        }
+        Ok(())
+    }
+}
+
+impl From<&TrustedIssuerConfig> for IssuerPolicy {
+    fn from(config: &TrustedIssuerConfig) -> Self {
+        Self::new(
+            config.issuer.clone(),
+            config.algorithms.clone(),
+            config.audiences.clone(),
+        )
+    }
+}
+
+/// Key set and policy for one trusted issuer
+pub struct TrustedIssuer {
+    /// Validation rules
+    pub policy: IssuerPolicy,
+    /// Issuer's JWK cache
+    pub keys: Arc<JwkCache>,
+}
+
+/// Trusted issuers keyed by `iss` claim
+#[derive(Default)]
+pub struct IssuerRegistry {
+    issuers: HashMap<String, TrustedIssuer>,
+}
+
+impl IssuerRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the registry from configuration, or `None` when no trusted
+    /// issuers are configured
+    pub async fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        if config.trusted_issuers.is_empty() {
+            return Ok(None);
+        }
+
+        let mut registry = Self::new();
+        for trusted in &config.trusted_issuers {
+            let mut keys = JwkCache::for_issuer(config, &trusted.issuer, &trusted.jwks_url).await?;
+            if let Some(anchor) = JwksTrustAnchor::from_config(trusted)? {
+                info!(
+                    issuer = %trusted.issuer,
+                    signed_only = anchor.signed_only(),
+                    "Signed JWK Set verification enabled"
+                );
+                keys = keys.with_trust_anchor(anchor);
+            }
+            registry.register(IssuerPolicy::from(trusted), Arc::new(keys));
+        }
+        info!(issuers = registry.len(), "Trusted issuer registry loaded");
+        Ok(Some(registry))
+    }
+
+    /// Registers an issuer, replacing any previous entry with the same `iss`
+    pub fn register(&mut self, policy: IssuerPolicy, keys: Arc<JwkCache>) {
+        self.issuers
+            .insert(policy.issuer.clone(), TrustedIssuer { policy, keys });
+    }
+
+    /// Looks up the issuer for an `iss` claim
+    pub fn resolve(&self, issuer: &str) -> Result<&TrustedIssuer, AuthEdgeError> {
+        self.issuers
+            .get(issuer)
+            .ok_or_else(|| AuthEdgeError::ClaimsInvalid {
+                claims: vec!["iss".to_string()],
+            })
+    }
+
+    /// Number of registered issuers
+    pub fn len(&self) -> usize {
+        self.issuers.len()
+    }
+
+    /// Whether no issuers are registered
+    pub fn is_empty(&self) -> bool {
+        self.issuers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::token::{Token, Unvalidated};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn claims(iss: &str, aud: &[&str]) -> Claims {
+        Claims {
+            iss: iss.to_string(),
+            sub: "user".to_string(),
+            aud: aud.iter().map(|a| a.to_string()).collect(),
+            exp: chrono::Utc::now().timestamp() + 300,
+            iat: chrono::Utc::now().timestamp(),
+            nbf: None,
+            jti: "jti".to_string(),
+            session_id: None,
+            scopes: None,
+            custom: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_policy_rejects_disallowed_algorithm() {
+        let policy = IssuerPolicy::new("https://idp.example", vec![Algorithm::RS256], vec![]);
+        assert!(policy.check_algorithm(Algorithm::RS256).is_ok());
+        assert!(matches!(
+            policy.check_algorithm(Algorithm::ES256),
+            Err(AuthEdgeError::TokenInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_policy_audience_rules() {
+        let policy = IssuerPolicy::new(
+            "https://idp.example",
+            vec![Algorithm::RS256],
+            vec!["api".to_string()],
+        );
+        assert!(policy.check_claims(&claims("https://idp.example", &["api", "web"])).is_ok());
+        assert!(matches!(
+            policy.check_claims(&claims("https://idp.example", &["web"])),
+            Err(AuthEdgeError::ClaimsInvalid { claims }) if claims == vec!["aud".to_string()]
+        ));
+        assert!(matches!(
+            policy.check_claims(&claims("https://other.example", &["api"])),
+            Err(AuthEdgeError::ClaimsInvalid { claims }) if claims == vec!["iss".to_string()]
+        ));
+    }
+
+    #[test]
+    fn test_unverified_issuer_selects_registry_entry() {
+        let raw = encode(
+            &Header::default(),
+            &claims("https://idp.example", &["api"]),
+            &EncodingKey::from_secret(b"unrelated"),
+        )
+        .unwrap();
+        let token = Token::<Unvalidated>::parse(&raw).unwrap();
+        assert_eq!(token.unverified_issuer().unwrap(), "https://idp.example");
+
+        let registry = IssuerRegistry::new();
+        assert!(registry.is_empty());
+        assert!(matches!(
+            registry.resolve("https://idp.example"),
+            Err(AuthEdgeError::ClaimsInvalid { .. })
+        ));
+    }
+}
@@ -4,25 +4,38 @@
 //! - Uses CacheClient from rust-common for distributed caching
 //! - Maintains local fallback when Cache_Service is unavailable
 //! - Prevents thundering herd on cache refresh using single-flight pattern
-
+//! - Refetches on unknown `kid`s, rate limited and with a per-kid negative
+//!   cache so random key IDs cannot force a JWKS fetch per request
+//! - Binds each key to the algorithms its type and curve (or `alg`) allow
+//! - Optionally locates the JWKS endpoint through OpenID Connect discovery,
+//!   revalidating the discovery document when a `kid` is unknown
+//! - Verifies signed JWK Sets against a pinned trust anchor
+//! - Refuses key sets that shrink below `JWKS_MIN_KEYS`, and keeps a `kid`
+//!   that disappeared while still verifying traffic for a grace period
+
+use crate::chaos::{self, ChaosTarget};
 use crate::config::Config;
 use crate::error::AuthEdgeError;
+use crate::jwt::discovery::OidcDiscovery;
+use crate::jwt::signed_jwks::{self, JwksTrustAnchor};
+use crate::middleware::{trace_context, CorrelationId, CORRELATION_ID_HEADER};
 use arc_swap::ArcSwap;
 use futures::future::{BoxFuture, Shared};
 use futures::FutureExt;
-use jsonwebtoken::DecodingKey;
+use jsonwebtoken::{Algorithm, DecodingKey};
 use rust_common::{CacheClient, CacheClientConfig, PlatformError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
-use tracing::{info, warn, instrument};
+use tracing::{debug, info, warn, instrument};
 
 /// JSON Web Key structure.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Jwk {
-    /// Key type (RSA, EC, oct)
+    /// Key type (RSA, EC, OKP)
     pub kty: String,
     /// Key ID
     pub kid: String,
@@ -35,14 +48,70 @@ pub struct Jwk {
     pub n: Option<String>,
     /// RSA exponent
     pub e: Option<String>,
-    /// EC x coordinate
+    /// EC x coordinate or OKP public key
     pub x: Option<String>,
     /// EC y coordinate
     pub y: Option<String>,
-    /// EC curve
+    /// EC or OKP curve
     pub crv: Option<String>,
 }
 
+/// RSA algorithms, PKCS#1 v1.5 and RSASSA-PSS
+const RSA_ALGORITHMS: [Algorithm; 6] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+];
+
+impl Jwk {
+    /// Algorithms the key may verify
+    ///
+    /// RSA keys cover RS* and PS*, EC keys the ES algorithm of their curve
+    /// and OKP keys EdDSA over Ed25519. A published `alg` narrows this to
+    /// one algorithm. Empty for keys that cannot be used: P-521 (ES512 is
+    /// not supported by the JWT library), Ed448 and unknown types.
+    pub fn algorithms(&self) -> Vec<Algorithm> {
+        let supported = match (self.kty.as_str(), self.crv.as_deref()) {
+            ("RSA", _) => RSA_ALGORITHMS.to_vec(),
+            ("EC", None | Some("P-256")) => vec![Algorithm::ES256],
+            ("EC", Some("P-384")) => vec![Algorithm::ES384],
+            ("OKP", Some("Ed25519")) => vec![Algorithm::EdDSA],
+            _ => Vec::new(),
+        };
+        match self.alg.as_deref() {
+            None => supported,
+            Some(alg) => alg
+                .parse::<Algorithm>()
+                .ok()
+                .filter(|alg| supported.contains(alg))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Decoding key with the algorithms it may verify
+#[derive(Clone)]
+pub struct VerificationKey {
+    key: DecodingKey,
+    algorithms: Vec<Algorithm>,
+}
+
+impl VerificationKey {
+    /// Returns the decoding key
+    pub fn key(&self) -> &DecodingKey {
+        &self.key
+    }
+
+    /// Whether a token signed with `algorithm` may be verified by this key
+    pub fn allows(&self, algorithm: Algorithm) -> bool {
+        self.algorithms.contains(&algorithm)
+    }
+}
+
 /// JSON Web Key Set structure.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Jwks {
@@ -52,35 +121,242 @@ pub struct Jwks {
 
 /// Local cache entry with keys and metadata.
 struct LocalCacheEntry {
-    keys: HashMap<String, Arc<DecodingKey>>,
+    keys: HashMap<String, Arc<VerificationKey>>,
     fetched_at: Instant,
 }
 
+/// Upper bound on negatively cached key IDs
+const MAX_NEGATIVE_KIDS: usize = 1024;
+
+/// Rate limit and negative cache for refetches triggered by unknown `kid`s.
+struct KidMissGuard {
+    /// Minimum time between miss-triggered refetches
+    min_interval: Duration,
+    /// How long a kid absent after a refetch is not looked up again
+    negative_ttl: Duration,
+    /// Time of the last miss-triggered refetch
+    last_refetch: parking_lot::Mutex<Option<Instant>>,
+    /// Key IDs absent from the last refetch, with the time they were recorded
+    negative: parking_lot::Mutex<HashMap<String, Instant>>,
+}
+
+impl KidMissGuard {
+    fn new(min_interval: Duration, negative_ttl: Duration) -> Self {
+        Self {
+            min_interval,
+            negative_ttl,
+            last_refetch: parking_lot::Mutex::new(None),
+            negative: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `kid` was absent from a recent refetch
+    fn is_known_missing(&self, kid: &str) -> bool {
+        self.negative
+            .lock()
+            .get(kid)
+            .is_some_and(|recorded| recorded.elapsed() < self.negative_ttl)
+    }
+
+    /// Takes the refetch slot unless a miss refetched within `min_interval`
+    fn try_acquire(&self) -> bool {
+        let mut last = self.last_refetch.lock();
+        if last.is_some_and(|at| at.elapsed() < self.min_interval) {
+            return false;
+        }
+        *last = Some(Instant::now());
+        true
+    }
+
+    /// Records `kid` as absent from the key set
+    fn record_missing(&self, kid: &str) {
+        let mut negative = self.negative.lock();
+        if negative.len() >= MAX_NEGATIVE_KIDS {
+            negative.retain(|_, recorded| recorded.elapsed() < self.negative_ttl);
+        }
+        if negative.len() < MAX_NEGATIVE_KIDS {
+            negative.insert(kid.to_string(), Instant::now());
+        }
+    }
+}
+
+/// Sanity checks applied before a fetched key set replaces the cached one.
+///
+/// A misconfigured issuer publishing an empty or much smaller set would
+/// otherwise reject all traffic at once.
+struct KeysetGuard {
+    /// Fewest keys a replacing set may have
+    min_keys: usize,
+    /// How long a dropped kid that still verifies traffic is kept
+    grace_period: Duration,
+    /// When each kid last returned a key
+    last_used: parking_lot::Mutex<HashMap<String, Instant>>,
+    /// Kids kept after disappearing, with the time they first went missing
+    retiring: parking_lot::Mutex<HashMap<String, Instant>>,
+    /// Accept the next fetched set unchecked (admin override)
+    accept_next: AtomicBool,
+}
+
+impl KeysetGuard {
+    fn new(min_keys: usize, grace_period: Duration) -> Self {
+        Self {
+            min_keys,
+            grace_period,
+            last_used: parking_lot::Mutex::new(HashMap::new()),
+            retiring: parking_lot::Mutex::new(HashMap::new()),
+            accept_next: AtomicBool::new(false),
+        }
+    }
+
+    /// Records that `kid` returned a key
+    fn record_use(&self, kid: &str) {
+        let now = Instant::now();
+        let mut last_used = self.last_used.lock();
+        match last_used.get_mut(kid) {
+            Some(used_at) => *used_at = now,
+            None => {
+                last_used.insert(kid.to_string(), now);
+            }
+        }
+    }
+
+    /// Key set to cache in place of `previous` after fetching `fetched`
+    ///
+    /// A set with fewer than `min_keys` keys leaves `previous` in place. Kids
+    /// of `previous` missing from `fetched` are kept while they verified a
+    /// token within the grace period, for at most the grace period since they
+    /// first went missing.
+    fn apply(
+        &self,
+        previous: Option<&HashMap<String, Arc<VerificationKey>>>,
+        mut fetched: HashMap<String, Arc<VerificationKey>>,
+        now: Instant,
+    ) -> HashMap<String, Arc<VerificationKey>> {
+        if self.accept_next.swap(false, Ordering::SeqCst) {
+            self.retiring.lock().clear();
+            return fetched;
+        }
+        let Some(previous) = previous.filter(|previous| !previous.is_empty()) else {
+            return fetched;
+        };
+        if fetched.len() < self.min_keys {
+            warn!(
+                published = fetched.len(),
+                min_keys = self.min_keys,
+                "JWKS has fewer keys than JWKS_MIN_KEYS, keeping the previous key set"
+            );
+            return previous.clone();
+        }
+
+        let mut retiring = self.retiring.lock();
+        retiring.retain(|kid, _| !fetched.contains_key(kid));
+        let mut last_used = self.last_used.lock();
+        last_used.retain(|_, used_at| now.duration_since(*used_at) < self.grace_period);
+        for (kid, key) in previous {
+            if fetched.contains_key(kid) {
+                continue;
+            }
+            let missing_since = *retiring.entry(kid.clone()).or_insert(now);
+            let in_grace = now.duration_since(missing_since) < self.grace_period;
+            if in_grace && last_used.contains_key(kid) {
+                warn!(kid = %kid, "Key dropped from JWKS is still in use, keeping it for the grace period");
+                fetched.insert(kid.clone(), key.clone());
+            } else {
+                retiring.remove(kid);
+            }
+        }
+        fetched
+    }
+}
+
 /// Type alias for the inflight future.
 type InflightFuture = Shared<BoxFuture<'static, Result<Arc<LocalCacheEntry>, AuthEdgeError>>>;
 
 /// JWK Cache with Cache_Service integration and single-flight refresh pattern.
 pub struct JwkCache {
     /// Remote cache client (Cache_Service)
-    cache_client: CacheClient,
+    cache_client: Arc<CacheClient>,
     /// Local fallback cache
-    local_cache: ArcSwap<Option<LocalCacheEntry>>,
+    local_cache: Arc<ArcSwap<Option<LocalCacheEntry>>>,
     /// JWKS endpoint URL
-    jwks_url: String,
+    jwks_url: Arc<ArcSwap<String>>,
+    /// Discovery document the JWKS endpoint is taken from, if any
+    discovery: Option<Arc<OidcDiscovery>>,
     /// Cache TTL
     ttl: Duration,
     /// Single-flight coordinator
     inflight: Arc<Mutex<Option<InflightFuture>>>,
     /// HTTP client for fetching JWKS
     http_client: reqwest::Client,
+    /// Guards refetches triggered by unknown key IDs
+    miss_guard: KidMissGuard,
+    /// Key the JWK Set must be signed with, if it is signed
+    trust_anchor: Option<Arc<JwksTrustAnchor>>,
+    /// Shrink checks on fetched key sets
+    keyset_guard: Arc<KeysetGuard>,
 }
 
 impl JwkCache {
     /// Creates a new JWK cache with Cache_Service integration.
+    ///
+    /// With `OIDC_ISSUER` set the JWKS endpoint is discovered from the
+    /// issuer's discovery document instead of `JWKS_URL`.
     pub async fn new(config: &Config) -> Result<Self, AuthEdgeError> {
+        match &config.oidc_issuer {
+            Some(issuer) => {
+                let discovery = OidcDiscovery::for_issuer(
+                    issuer,
+                    Duration::from_secs(config.oidc_discovery_ttl_seconds),
+                )?;
+                Self::with_discovery(config, "auth-edge:jwk", Arc::new(discovery)).await
+            }
+            None => Self::with_source(config, "auth-edge:jwk", config.jwks_url_str()).await,
+        }
+    }
+
+    /// Creates a JWK cache for one trusted issuer.
+    ///
+    /// Each issuer gets its own remote cache namespace so key IDs published
+    /// by different issuers cannot collide.
+    pub async fn for_issuer(
+        config: &Config,
+        issuer: &str,
+        jwks_url: &str,
+    ) -> Result<Self, AuthEdgeError> {
+        Self::with_source(config, &format!("auth-edge:jwk:{issuer}"), jwks_url).await
+    }
+
+    /// Creates a JWK cache for an issuer located through OIDC discovery.
+    ///
+    /// Fetches the discovery document once so a misconfigured issuer fails
+    /// at startup.
+    pub async fn for_discovered_issuer(
+        config: &Config,
+        discovery: Arc<OidcDiscovery>,
+    ) -> Result<Self, AuthEdgeError> {
+        let namespace = format!("auth-edge:jwk:{}", discovery.issuer());
+        Self::with_discovery(config, &namespace, discovery).await
+    }
+
+    async fn with_discovery(
+        config: &Config,
+        namespace: &str,
+        discovery: Arc<OidcDiscovery>,
+    ) -> Result<Self, AuthEdgeError> {
+        let document = discovery.document().await?;
+        let mut cache = Self::with_source(config, namespace, &document.jwks_uri).await?;
+        cache.discovery = Some(discovery);
+        Ok(cache)
+    }
+
+    async fn with_source(
+        config: &Config,
+        namespace: &str,
+        jwks_url: &str,
+    ) -> Result<Self, AuthEdgeError> {
         let cache_config = CacheClientConfig::default()
             .with_address(config.cache_service_url_str())
-            .with_namespace("auth-edge:jwk")
+            .with_namespace(namespace)
             .with_default_ttl(Duration::from_secs(config.jwks_cache_ttl_seconds));
 
         let cache_config = if let Some(key) = config.cache_encryption_key {
@@ -101,43 +377,101 @@ impl JwkCache {
             })?;
 
         Ok(Self {
-            cache_client,
-            local_cache: ArcSwap::new(Arc::new(None)),
-            jwks_url: config.jwks_url_str().to_string(),
+            cache_client: Arc::new(cache_client),
+            local_cache: Arc::new(ArcSwap::new(Arc::new(None))),
+            jwks_url: Arc::new(ArcSwap::from_pointee(jwks_url.to_string())),
+            discovery: None,
             ttl: Duration::from_secs(config.jwks_cache_ttl_seconds),
             inflight: Arc::new(Mutex::new(None)),
             http_client,
+            miss_guard: KidMissGuard::new(
+                Duration::from_secs(config.jwks_refetch_interval_seconds),
+                Duration::from_secs(config.jwks_negative_cache_ttl_seconds),
+            ),
+            trust_anchor: None,
+            keyset_guard: Arc::new(KeysetGuard::new(
+                config.jwks_min_keys,
+                Duration::from_secs(config.jwks_kid_grace_period_seconds),
+            )),
         })
     }
 
+    /// Verifies fetched JWK Sets against `anchor` before accepting keys
+    pub fn with_trust_anchor(mut self, anchor: JwksTrustAnchor) -> Self {
+        self.trust_anchor = Some(Arc::new(anchor));
+        self
+    }
+
     /// Gets a decoding key by key ID with distributed cache and local fallback.
     #[instrument(skip(self), fields(kid = %kid))]
-    pub async fn get_key(&self, kid: &str) -> Result<DecodingKey, AuthEdgeError> {
+    pub async fn get_key(&self, kid: &str) -> Result<VerificationKey, AuthEdgeError> {
         // 1. Try remote cache first
-        if let Ok(Some(key_bytes)) = self.cache_client.get(&format!("key:{kid}")).await {
+        let cached = match chaos::inject(ChaosTarget::Cache).await {
+            Ok(()) => self.cache_client.get(&format!("key:{kid}")).await.ok().flatten(),
+            Err(_) => None,
+        };
+        if let Some(key_bytes) = cached {
             if let Ok(key) = self.deserialize_key(&key_bytes) {
+                self.keyset_guard.record_use(kid);
                 return Ok(key);
             }
         }
 
         // 2. Try local cache
         if let Some(key) = self.try_get_local(kid) {
+            self.keyset_guard.record_use(kid);
             return Ok((*key).clone());
         }
 
-        // 3. Refresh with single-flight
-        self.refresh_single_flight().await?;
+        // 3. Refresh with single-flight: an expired key set is always
+        // refetched, a kid missing from a fresh one only within the miss limits
+        if self.is_stale() {
+            self.refresh_single_flight(false).await?;
+        } else {
+            self.refresh_on_miss(kid).await?;
+        }
 
         // 4. Try local cache again after refresh
-        self.try_get_local(kid)
-            .map(|k| (*k).clone())
-            .ok_or_else(|| AuthEdgeError::JwkCacheError {
-                reason: format!("Key {kid} not found after refresh"),
-            })
+        match self.try_get_local(kid) {
+            Some(key) => {
+                self.keyset_guard.record_use(kid);
+                Ok((*key).clone())
+            }
+            None => {
+                self.miss_guard.record_missing(kid);
+                Err(AuthEdgeError::JwkCacheError {
+                    reason: format!("Key {kid} not found after refresh"),
+                })
+            }
+        }
+    }
+
+    /// Refetches the key set for a kid missing from a fresh cache.
+    ///
+    /// Kids absent from a recent refetch fail without a fetch, and at most
+    /// one miss-triggered refetch starts per refetch interval; callers over
+    /// the limit only wait for a refetch already in flight. A kid miss also
+    /// revalidates the discovery document.
+    async fn refresh_on_miss(&self, kid: &str) -> Result<(), AuthEdgeError> {
+        if self.miss_guard.is_known_missing(kid) {
+            return Err(AuthEdgeError::JwkCacheError {
+                reason: format!("Key {kid} not found"),
+            });
+        }
+        if self.miss_guard.try_acquire() {
+            return self.refresh_single_flight(true).await;
+        }
+
+        debug!(kid = %kid, "JWKS refetch rate limited");
+        let inflight = self.inflight.lock().await.clone();
+        match inflight {
+            Some(fut) => fut.await.map(|_| ()),
+            None => Ok(()),
+        }
     }
 
     /// Tries to get a key from local cache if valid.
-    fn try_get_local(&self, kid: &str) -> Option<Arc<DecodingKey>> {
+    fn try_get_local(&self, kid: &str) -> Option<Arc<VerificationKey>> {
         let cache = self.local_cache.load();
         if let Some(ref entry) = **cache {
             if entry.fetched_at.elapsed() < self.ttl {
@@ -157,11 +491,18 @@ impl JwkCache {
         }
     }
 
+    /// Returns the discovery client, if the JWKS endpoint is discovered.
+    pub fn discovery(&self) -> Option<&Arc<OidcDiscovery>> {
+        self.discovery.as_ref()
+    }
+
     /// Refreshes the cache using single-flight pattern.
     ///
     /// Only one HTTP request will be made even if multiple concurrent
-    /// callers request a refresh simultaneously.
-    async fn refresh_single_flight(&self) -> Result<(), AuthEdgeError> {
+    /// callers request a refresh simultaneously. With discovery enabled the
+    /// JWKS endpoint is re-read from the discovery document first, forcing a
+    /// fetch of the document when `revalidate` is set.
+    async fn refresh_single_flight(&self, revalidate: bool) -> Result<(), AuthEdgeError> {
         let mut inflight_guard = self.inflight.lock().await;
 
         if let Some(ref fut) = *inflight_guard {
@@ -171,17 +512,45 @@ impl JwkCache {
             return Ok(());
         }
 
-        let url = self.jwks_url.clone();
+        let jwks_url = self.jwks_url.clone();
+        let discovery = self.discovery.clone();
         let client = self.http_client.clone();
         let local_cache = self.local_cache.clone();
         let cache_client = self.cache_client.clone();
         let ttl = self.ttl;
+        let trust_anchor = self.trust_anchor.clone();
+        let keyset_guard = self.keyset_guard.clone();
+        // Captured here: the shared fetch may be polled by another waiter
+        let trace = trace_context::outgoing();
+        let correlation_id = CorrelationId::current();
 
         let fut: BoxFuture<'static, Result<Arc<LocalCacheEntry>, AuthEdgeError>> =
             Box::pin(async move {
+                if let Some(discovery) = discovery {
+                    let document = if revalidate {
+                        discovery.revalidate().await?
+                    } else {
+                        discovery.document().await?
+                    };
+                    if **jwks_url.load() != document.jwks_uri {
+                        info!(jwks_uri = %document.jwks_uri, "Discovered JWKS endpoint changed");
+                        jwks_url.store(Arc::new(document.jwks_uri.clone()));
+                    }
+                }
+                let url = jwks_url.load_full();
                 info!(url = %url, "Fetching JWKS");
 
-                let response = client.get(&url).send().await.map_err(|e| {
+                let mut request =
+                    trace_context::inject_headers(client.get(url.as_str()), trace.as_ref());
+                if let Some(id) = correlation_id {
+                    request = request.header(CORRELATION_ID_HEADER, id.to_string());
+                }
+                chaos::inject(ChaosTarget::Jwks).await.map_err(|e| {
+                    AuthEdgeError::JwkCacheError {
+                        reason: format!("Failed to fetch JWKS: {e}"),
+                    }
+                })?;
+                let response = request.send().await.map_err(|e| {
                     AuthEdgeError::JwkCacheError {
                         reason: format!("Failed to fetch JWKS: {e}"),
                     }
@@ -193,17 +562,15 @@ impl JwkCache {
                     });
                 }
 
-                let jwks: Jwks =
-                    response
-                        .json()
-                        .await
-                        .map_err(|e| AuthEdgeError::JwkCacheError {
-                            reason: format!("Failed to parse JWKS: {e}"),
-                        })?;
+                let body = response.text().await.map_err(|e| AuthEdgeError::JwkCacheError {
+                    reason: format!("Failed to read JWKS: {e}"),
+                })?;
+                let jwks = signed_jwks::parse_jwks(&body, trust_anchor.as_deref())
+                    .inspect_err(|e| warn!(url = %url, error = %e, "JWKS rejected"))?;
 
                 let mut keys = HashMap::new();
                 for jwk in &jwks.keys {
-                    if let Some(key) = Self::jwk_to_decoding_key(jwk) {
+                    if let Some(key) = Self::jwk_to_verification_key(jwk) {
                         keys.insert(jwk.kid.clone(), Arc::new(key));
 
                         // Store in remote cache (best effort)
@@ -215,6 +582,13 @@ impl JwkCache {
                     }
                 }
 
+                let previous = local_cache.load();
+                let keys = keyset_guard.apply(
+                    (**previous).as_ref().map(|entry| &entry.keys),
+                    keys,
+                    Instant::now(),
+                );
+
                 let entry = Arc::new(LocalCacheEntry {
                     keys: keys.clone(),
                     fetched_at: Instant::now(),
@@ -240,39 +614,39 @@ impl JwkCache {
         result.map(|_| ())
     }
 
-    /// Converts a JWK to a DecodingKey.
-    fn jwk_to_decoding_key(jwk: &Jwk) -> Option<DecodingKey> {
-        match jwk.kty.as_str() {
+    /// Converts a JWK to a verification key.
+    pub(crate) fn jwk_to_verification_key(jwk: &Jwk) -> Option<VerificationKey> {
+        let algorithms = jwk.algorithms();
+        if algorithms.is_empty() {
+            warn!(
+                kid = %jwk.kid,
+                kty = %jwk.kty,
+                crv = jwk.crv.as_deref().unwrap_or_default(),
+                alg = jwk.alg.as_deref().unwrap_or_default(),
+                "Unsupported key type, curve or algorithm"
+            );
+            return None;
+        }
+
+        let key = match jwk.kty.as_str() {
             "RSA" => {
                 let n = jwk.n.as_ref()?;
                 let e = jwk.e.as_ref()?;
-                
+
                 // Check minimum key size (2048 bits = 256 bytes base64)
                 if n.len() < 340 {
                     warn!(kid = %jwk.kid, "RSA key too small, rejecting");
                     return None;
                 }
-                
-                DecodingKey::from_rsa_components(n, e).ok()
-            }
-            "EC" => {
-                let x = jwk.x.as_ref()?;
-                let y = jwk.y.as_ref()?;
-                let crv = jwk.crv.as_deref().unwrap_or("P-256");
-                
-                // Only allow P-256 or stronger curves
-                if !matches!(crv, "P-256" | "P-384" | "P-521") {
-                    warn!(kid = %jwk.kid, crv = %crv, "Weak EC curve, rejecting");
-                    return None;
-                }
-                
-                DecodingKey::from_ec_components(x, y).ok()
-            }
-            _ => {
-                warn!(kty = %jwk.kty, "Unsupported key type");
-                None
+
+                DecodingKey::from_rsa_components(n, e).ok()?
             }
-        }
+            "EC" => DecodingKey::from_ec_components(jwk.x.as_ref()?, jwk.y.as_ref()?).ok()?,
+            "OKP" => DecodingKey::from_ed_components(jwk.x.as_ref()?).ok()?,
+            _ => return None,
+        };
+
+        Some(VerificationKey { key, algorithms })
     }
 
     /// Serializes a JWK for cache storage.
@@ -283,19 +657,26 @@ impl JwkCache {
     }
 
     /// Deserializes a key from cache bytes.
-    fn deserialize_key(&self, bytes: &[u8]) -> Result<DecodingKey, AuthEdgeError> {
+    fn deserialize_key(&self, bytes: &[u8]) -> Result<VerificationKey, AuthEdgeError> {
         let jwk: Jwk = serde_json::from_slice(bytes).map_err(|e| AuthEdgeError::JwkCacheError {
             reason: format!("Failed to deserialize JWK: {e}"),
         })?;
-        Self::jwk_to_decoding_key(&jwk).ok_or_else(|| AuthEdgeError::JwkCacheError {
-            reason: "Failed to convert JWK to DecodingKey".to_string(),
+        Self::jwk_to_verification_key(&jwk).ok_or_else(|| AuthEdgeError::JwkCacheError {
+            reason: "Failed to convert JWK to VerificationKey".to_string(),
         })
     }
 
-    /// Forces a cache refresh (for testing).
+    /// Refetches the key set now.
+    ///
+    /// The current keys stay in use if the fetch fails.
     pub async fn force_refresh(&self) -> Result<(), AuthEdgeError> {
-        self.local_cache.store(Arc::new(None));
-        self.refresh_single_flight().await
+        self.refresh_single_flight(false).await
+    }
+
+    /// Accepts the next fetched key set without the shrink checks, dropping
+    /// kids kept for their grace period.
+    pub fn accept_next_keyset(&self) {
+        self.keyset_guard.accept_next.store(true, Ordering::SeqCst);
     }
 
     /// Gets the number of locally cached keys.
@@ -307,13 +688,215 @@ impl JwkCache {
             None => 0,
         }
     }
+
+    /// Key IDs of the locally cached keys, sorted.
+    #[must_use]
+    pub fn local_kids(&self) -> Vec<String> {
+        let cache = self.local_cache.load();
+        let mut kids: Vec<String> = match **cache {
+            Some(ref entry) => entry.keys.keys().cloned().collect(),
+            None => Vec::new(),
+        };
+        kids.sort();
+        kids
+    }
 }
 
-// Clone implementation for cache_client sharing
-impl Clone for CacheClient {
-    fn clone(&self) -> Self {
-        // CacheClient uses Arc internally, so this is cheap
-        // This is a workaround - in production, CacheClient should implement Clone
-        unimplemented!("CacheClient clone not available - use Arc<CacheClient>")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwk(kty: &str, crv: Option<&str>, alg: Option<&str>) -> Jwk {
+        Jwk {
+            kty: kty.to_string(),
+            kid: "key-1".to_string(),
+            key_use: Some("sig".to_string()),
+            alg: alg.map(str::to_string),
+            n: None,
+            e: None,
+            x: None,
+            y: None,
+            crv: crv.map(str::to_string),
+        }
+    }
+
+    fn keys(kids: &[&str]) -> HashMap<String, Arc<VerificationKey>> {
+        kids.iter()
+            .map(|kid| {
+                let key = VerificationKey {
+                    key: DecodingKey::from_secret(kid.as_bytes()),
+                    algorithms: vec![Algorithm::ES256],
+                };
+                (kid.to_string(), Arc::new(key))
+            })
+            .collect()
+    }
+
+    fn sorted_kids(keys: &HashMap<String, Arc<VerificationKey>>) -> Vec<&str> {
+        let mut kids: Vec<_> = keys.keys().map(String::as_str).collect();
+        kids.sort_unstable();
+        kids
+    }
+
+    #[test]
+    fn test_keyset_below_minimum_is_refused() {
+        let guard = KeysetGuard::new(2, Duration::from_secs(600));
+        let previous = keys(&["a", "b"]);
+        let now = Instant::now();
+
+        assert_eq!(sorted_kids(&guard.apply(Some(&previous), keys(&[]), now)), ["a", "b"]);
+        assert_eq!(sorted_kids(&guard.apply(Some(&previous), keys(&["c"]), now)), ["a", "b"]);
+        assert_eq!(sorted_kids(&guard.apply(None, keys(&["c"]), now)), ["c"]);
+
+        guard.accept_next.store(true, Ordering::SeqCst);
+        assert!(guard.apply(Some(&previous), keys(&[]), now).is_empty());
+        assert_eq!(sorted_kids(&guard.apply(Some(&previous), keys(&[]), now)), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_dropped_kid_in_use_is_kept_for_grace_period() {
+        let guard = KeysetGuard::new(1, Duration::from_secs(600));
+        let previous = keys(&["old", "idle", "new"]);
+        guard.record_use("old");
+        let now = Instant::now();
+
+        let kept = guard.apply(Some(&previous), keys(&["new"]), now);
+        assert_eq!(sorted_kids(&kept), ["new", "old"]);
+
+        // Still kept while in use, until the grace period since it went missing
+        let later = now + Duration::from_secs(300);
+        assert_eq!(sorted_kids(&guard.apply(Some(&kept), keys(&["new"]), later)), ["new", "old"]);
+        let expired = now + Duration::from_secs(601);
+        assert_eq!(sorted_kids(&guard.apply(Some(&kept), keys(&["new"]), expired)), ["new"]);
+    }
+
+    #[test]
+    fn test_key_algorithms() {
+        assert_eq!(jwk("RSA", None, None).algorithms(), RSA_ALGORITHMS.to_vec());
+        assert_eq!(jwk("RSA", None, Some("PS256")).algorithms(), vec![Algorithm::PS256]);
+        assert_eq!(jwk("EC", Some("P-256"), None).algorithms(), vec![Algorithm::ES256]);
+        assert_eq!(jwk("EC", Some("P-384"), None).algorithms(), vec![Algorithm::ES384]);
+        assert_eq!(jwk("OKP", Some("Ed25519"), None).algorithms(), vec![Algorithm::EdDSA]);
+
+        // `alg` must fit the key type and curve
+        assert!(jwk("EC", Some("P-256"), Some("ES384")).algorithms().is_empty());
+        assert!(jwk("RSA", None, Some("HS256")).algorithms().is_empty());
+        assert!(jwk("EC", Some("P-521"), None).algorithms().is_empty());
+        assert!(jwk("OKP", Some("Ed448"), None).algorithms().is_empty());
+        assert!(jwk("oct", None, None).algorithms().is_empty());
+    }
+
+    #[test]
+    fn test_okp_key_conversion() {
+        let mut ed25519 = jwk("OKP", Some("Ed25519"), Some("EdDSA"));
+        ed25519.x = Some("11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo".to_string());
+        let key = JwkCache::jwk_to_verification_key(&ed25519).unwrap();
+        assert!(key.allows(Algorithm::EdDSA));
+        assert!(!key.allows(Algorithm::ES256));
+
+        ed25519.x = None;
+        assert!(JwkCache::jwk_to_verification_key(&ed25519).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_kid_miss_refetch_is_limited() {
+        use crate::config::EnvSource;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "keys": [{
+                    "kty": "OKP",
+                    "kid": "ed-1",
+                    "crv": "Ed25519",
+                    "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo",
+                }],
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let config = Config::from_source(&EnvSource::with_overrides(HashMap::from([
+            ("JWKS_URL".to_string(), format!("{}/jwks", server.uri())),
+            ("JWKS_REFETCH_INTERVAL".to_string(), "300".to_string()),
+        ])))
+        .unwrap();
+        let cache = JwkCache::new(&config).await.unwrap();
+
+        // Initial load, then one miss-triggered refetch for an unknown kid
+        assert!(cache.get_key("ed-1").await.is_ok());
+        assert!(cache.get_key("unknown-1").await.is_err());
+
+        // Negatively cached, then rate limited: neither fetches again
+        assert!(cache.get_key("unknown-1").await.is_err());
+        assert!(cache.get_key("unknown-2").await.is_err());
+        assert!(cache.get_key("ed-1").await.is_ok());
+    }
+
+    #[test]
+    fn test_miss_guard_negative_cache_expires() {
+        let guard = KidMissGuard::new(Duration::ZERO, Duration::ZERO);
+        guard.record_missing("kid-1");
+        assert!(!guard.is_known_missing("kid-1"));
+        assert!(guard.try_acquire() && guard.try_acquire());
+
+        let guard = KidMissGuard::new(Duration::from_secs(60), Duration::from_secs(60));
+        guard.record_missing("kid-1");
+        assert!(guard.is_known_missing("kid-1"));
+        assert!(guard.try_acquire());
+        assert!(!guard.try_acquire());
+    }
+
+    #[tokio::test]
+    async fn test_kid_miss_revalidates_discovery() {
+        use crate::config::EnvSource;
+        use crate::jwt::discovery::WELL_KNOWN_PATH;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        async fn publish(server: &MockServer, jwks_path: &str, keys: serde_json::Value) {
+            server.reset().await;
+            Mock::given(method("GET"))
+                .and(path(WELL_KNOWN_PATH))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "issuer": server.uri(),
+                    "jwks_uri": format!("{}{jwks_path}", server.uri()),
+                })))
+                .mount(server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(jwks_path))
+                .respond_with(ResponseTemplate::new(200).set_body_json(keys))
+                .mount(server)
+                .await;
+        }
+
+        let server = MockServer::start().await;
+        publish(&server, "/keys-a", serde_json::json!({"keys": []})).await;
+        let config = Config::from_source(&EnvSource::with_overrides(HashMap::from([(
+            "OIDC_ISSUER".to_string(),
+            server.uri(),
+        )])))
+        .unwrap();
+        let cache = JwkCache::new(&config).await.unwrap();
+        assert!(cache.get_key("ed-0").await.is_err());
+
+        // The issuer moves its key set; the unknown kid revalidates discovery
+        publish(
+            &server,
+            "/keys-b",
+            serde_json::json!({"keys": [{
+                "kty": "OKP",
+                "kid": "ed-1",
+                "crv": "Ed25519",
+                "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo",
+            }]}),
+        )
+        .await;
+        let key = cache.get_key("ed-1").await.unwrap();
+        assert!(key.allows(Algorithm::EdDSA));
     }
 }
@@ -0,0 +1,153 @@
+//! Maximum Token Age
+//!
+//! Some compliance regimes reject tokens issued more than a set time ago,
+//! whatever their `exp`. [`TokenAgePolicy`] enforces `MAX_TOKEN_AGE` against
+//! the `iat` claim, with per-route overrides from `MAX_TOKEN_AGE_ROUTES`
+//! matched like `AUTHZ_RULES`.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::authz::RouteRule;
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+
+/// Maximum token age with per-route overrides
+#[derive(Debug, Clone, Default)]
+pub struct TokenAgePolicy {
+    default: Option<Duration>,
+    routes: Vec<(RouteRule, Option<Duration>)>,
+}
+
+impl TokenAgePolicy {
+    /// Policy applying `default` to every route; `None` sets no limit
+    pub fn new(default: Option<Duration>) -> Self {
+        Self {
+            default,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Overrides the maximum age for routes matching `route`
+    ///
+    /// Overrides are matched in the order they were added; `None` lifts the
+    /// limit for the route.
+    pub fn with_route(mut self, route: RouteRule, max_age: Option<Duration>) -> Self {
+        self.routes.push((route, max_age));
+        self
+    }
+
+    /// Builds the policy from `MAX_TOKEN_AGE` and `MAX_TOKEN_AGE_ROUTES`
+    ///
+    /// Returns `None` when neither is configured. Routes are checked during
+    /// config validation, so none are skipped in practice.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if config.max_token_age_secs == 0 && config.max_token_age_routes.is_empty() {
+            return None;
+        }
+        let policy = Self::new(non_zero_secs(config.max_token_age_secs));
+        Some(
+            config
+                .max_token_age_routes
+                .iter()
+                .filter_map(|rule| {
+                    RouteRule::parse(&rule.route)
+                        .ok()
+                        .map(|route| (route, non_zero_secs(rule.max_age_secs)))
+                })
+                .fold(policy, |policy, (route, max_age)| policy.with_route(route, max_age)),
+        )
+    }
+
+    /// Maximum age applying to `route`; an empty route gets the default
+    pub fn max_age(&self, route: &str) -> Option<Duration> {
+        if route.is_empty() {
+            return self.default;
+        }
+        self.routes
+            .iter()
+            .find(|(rule, _)| rule.matches_route(route))
+            .map_or(self.default, |(_, max_age)| *max_age)
+    }
+
+    /// Rejects tokens issued longer ago than the route allows
+    pub fn check(&self, claims: &Claims, route: &str) -> Result<(), AuthEdgeError> {
+        self.check_at(claims, route, Utc::now())
+    }
+
+    fn check_at(&self, claims: &Claims, route: &str, now: DateTime<Utc>) -> Result<(), AuthEdgeError> {
+        let Some(max_age) = self.max_age(route) else {
+            return Ok(());
+        };
+        let age = now.timestamp().saturating_sub(claims.iat);
+        if age > i64::try_from(max_age.as_secs()).unwrap_or(i64::MAX) {
+            return Err(AuthEdgeError::TokenTooOld {
+                issued_at: DateTime::from_timestamp(claims.iat, 0).unwrap_or(now),
+                max_age,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn non_zero_secs(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn issued_ago(secs: i64) -> Claims {
+        let now = Utc::now().timestamp();
+        Claims {
+            iss: "https://idp.example".to_string(),
+            sub: "user".to_string(),
+            aud: vec!["api".to_string()],
+            exp: now + 86_400,
+            iat: now - secs,
+            nbf: None,
+            jti: "jti".to_string(),
+            session_id: None,
+            scopes: None,
+            custom: HashMap::new(),
+        }
+    }
+
+    fn policy() -> TokenAgePolicy {
+        TokenAgePolicy::new(Some(Duration::from_secs(3600)))
+            .with_route(RouteRule::parse("POST /v1/payments/**").unwrap(), Some(Duration::from_secs(300)))
+            .with_route(RouteRule::parse("/v1/reports/**").unwrap(), None)
+    }
+
+    #[test]
+    fn test_rejects_tokens_older_than_default() {
+        let policy = policy();
+        assert!(policy.check(&issued_ago(600), "").is_ok());
+        assert!(matches!(
+            policy.check(&issued_ago(7200), "GET /v1/orders"),
+            Err(AuthEdgeError::TokenTooOld { max_age, .. }) if max_age == Duration::from_secs(3600)
+        ));
+        assert_eq!(
+            AuthEdgeError::TokenTooOld { issued_at: Utc::now(), max_age: Duration::from_secs(1) }
+                .code()
+                .as_str(),
+            "AUTH_TOKEN_TOO_OLD"
+        );
+    }
+
+    #[test]
+    fn test_route_overrides() {
+        let policy = policy();
+        assert!(matches!(
+            policy.check(&issued_ago(600), "post /v1/payments/42"),
+            Err(AuthEdgeError::TokenTooOld { .. })
+        ));
+        assert!(policy.check(&issued_ago(600), "GET /v1/payments/42").is_ok());
+        assert!(policy.check(&issued_ago(86_000), "GET /v1/reports/daily").is_ok());
+        assert!(TokenAgePolicy::default().check(&issued_ago(86_000), "").is_ok());
+    }
+}
@@ -1,9 +1,34 @@
 pub mod validator;
+pub mod audience_keys;
+pub mod bypass;
+pub mod cel;
+pub mod coalescer;
+pub mod explain;
+pub mod harness;
+pub mod issuer;
 pub mod claims;
+pub mod claims_ref;
+pub mod discovery;
 pub mod jwk_cache;
+pub mod max_age;
+pub mod revocation;
+pub mod signed_jwks;
 pub mod token;
+pub mod validation_cache;
 
 pub use validator::JwtValidator;
-pub use claims::Claims;
-pub use jwk_cache::JwkCache;
-pub use token::{Token, TokenState, Unvalidated, SignatureValidated, Validated};
+pub use audience_keys::AudienceKeyPolicy;
+pub use bypass::BypassValidator;
+pub use cel::ClaimExpression;
+pub use coalescer::ValidationCoalescer;
+pub use claims::{ClaimExpressions, Claims};
+pub use claims_ref::{ClaimsRefResolver, ClaimsRefStore};
+pub use discovery::{DiscoveryDocument, OidcDiscovery};
+pub use explain::{Check, CheckOutcome, CheckStatus};
+pub use issuer::{IssuerPolicy, IssuerRegistry, TrustedIssuer};
+pub use jwk_cache::{JwkCache, VerificationKey};
+pub use max_age::TokenAgePolicy;
+pub use revocation::{RevocationChecker, RevocationStore};
+pub use signed_jwks::JwksTrustAnchor;
+pub use token::{DecodeLimits, Token, TokenState, Unvalidated, SignatureValidated, Validated};
+pub use validation_cache::ValidationCache;
@@ -0,0 +1,413 @@
+//! Token Revocation
+//!
+//! Consults the revocation list kept in the cache-service by the token
+//! service (`revoked:{jti}` entries) after a token's signature and claims
+//! have been verified. A bounded LRU set of JTIs already confirmed revoked
+//! sits in front of the cache-service, so replayed revoked tokens are
+//! rejected without a round trip and stay rejected while the cache-service is
+//! unreachable. The set is exact, so it never rejects a token the store has
+//! not revoked; a JTI missing from it always falls through to the
+//! cache-service.
+//!
+//! With `REVOCATION_FILTER_SYNC`, a filter of every revoked JTI is also
+//! fetched from the token service every `REVOCATION_FILTER_INTERVAL`. A JTI
+//! missing from it was not revoked when the filter was built, so the
+//! cache-service lookup is skipped; only filter hits are looked up. A
+//! filter older than `REVOCATION_FILTER_MAX_AGE` is ignored.
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwapOption;
+use async_trait::async_trait;
+use lru::LruCache;
+use parking_lot::Mutex;
+use rust_common::{CacheClient, CacheClientConfig, PlatformError, RevocationFilter};
+use tonic::transport::{Channel, Endpoint};
+use tracing::{debug, warn};
+
+use crate::chaos::{self, ChaosTarget};
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::observability::RevocationMetrics;
+use crate::proto::auth::token::token_service_client::TokenServiceClient;
+use crate::proto::auth::token::RevocationFilterRequest;
+
+/// Backing store of revoked JTIs
+#[async_trait]
+pub trait RevocationStore: Send + Sync {
+    /// Whether the JTI is on the revocation list
+    async fn is_revoked(&self, jti: &str) -> Result<bool, PlatformError>;
+
+    /// Adds a JTI to the revocation list until `ttl` elapses
+    async fn revoke(&self, jti: &str, ttl: Duration) -> Result<(), PlatformError>;
+}
+
+/// Revocation list shared with the token service through the cache-service
+pub struct CacheRevocationStore {
+    cache: CacheClient,
+}
+
+impl CacheRevocationStore {
+    /// Connects to the cache-service namespace holding revoked JTIs
+    pub async fn new(config: &Config) -> Result<Self, PlatformError> {
+        let cache_config = CacheClientConfig::default()
+            .with_address(config.cache_service_url_str())
+            .with_namespace(&config.revocation_namespace);
+        Ok(Self {
+            cache: CacheClient::new(cache_config).await?,
+        })
+    }
+
+    fn key(jti: &str) -> String {
+        format!("revoked:{jti}")
+    }
+}
+
+#[async_trait]
+impl RevocationStore for CacheRevocationStore {
+    async fn is_revoked(&self, jti: &str) -> Result<bool, PlatformError> {
+        chaos::inject(ChaosTarget::Cache)
+            .await
+            .map_err(|e| PlatformError::unavailable(e.to_string()))?;
+        self.cache.exists(&Self::key(jti)).await
+    }
+
+    async fn revoke(&self, jti: &str, ttl: Duration) -> Result<(), PlatformError> {
+        self.cache.set(&Self::key(jti), b"1", Some(ttl)).await
+    }
+}
+
+/// Revoked-JTI filter fetched from the token service
+struct SyncedFilter {
+    filter: RevocationFilter,
+    fetched_at: Instant,
+}
+
+/// Checks validated tokens against the revocation list
+pub struct RevocationChecker {
+    store: Arc<dyn RevocationStore>,
+    known_revoked: Mutex<LruCache<String, ()>>,
+    synced: Arc<ArcSwapOption<SyncedFilter>>,
+    filter_max_age: Duration,
+    fail_open: bool,
+    metrics: RevocationMetrics,
+}
+
+impl RevocationChecker {
+    /// Creates a checker remembering up to `cache_size` revoked JTIs
+    pub fn new(
+        store: Arc<dyn RevocationStore>,
+        cache_size: usize,
+        fail_open: bool,
+        metrics: RevocationMetrics,
+    ) -> Self {
+        Self {
+            store,
+            known_revoked: Mutex::new(LruCache::new(
+                NonZeroUsize::new(cache_size).unwrap_or(NonZeroUsize::MIN),
+            )),
+            synced: Arc::new(ArcSwapOption::empty()),
+            filter_max_age: Duration::ZERO,
+            fail_open,
+            metrics,
+        }
+    }
+
+    /// Trusts misses of a synced filter until it is `max_age` old
+    pub fn with_filter_max_age(mut self, max_age: Duration) -> Self {
+        self.filter_max_age = max_age;
+        self
+    }
+
+    /// Builds the checker from configuration, or `None` when disabled
+    pub async fn from_config(
+        config: &Config,
+        metrics: RevocationMetrics,
+    ) -> Result<Option<Self>, AuthEdgeError> {
+        if !config.revocation_check_enabled {
+            return Ok(None);
+        }
+        let store = CacheRevocationStore::new(config).await?;
+        let checker = Self::new(
+            Arc::new(store),
+            config.revocation_cache_size,
+            config.revocation_fail_open,
+            metrics,
+        );
+        if !config.revocation_filter_sync {
+            return Ok(Some(checker));
+        }
+
+        let checker = checker
+            .with_filter_max_age(Duration::from_secs(config.revocation_filter_max_age_secs));
+        let channel = Endpoint::from_shared(config.token_service_url.to_string())
+            .map_err(|e| AuthEdgeError::Platform(PlatformError::InvalidInput(e.to_string())))?
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .connect_lazy();
+        tokio::spawn(sync_filter(
+            TokenServiceClient::new(channel),
+            checker.synced.clone(),
+            Duration::from_secs(config.revocation_filter_interval_secs),
+        ));
+        Ok(Some(checker))
+    }
+
+    /// Replaces the synced filter of revoked JTIs
+    pub fn set_filter(&self, filter: RevocationFilter) {
+        store_filter(&self.synced, filter);
+    }
+
+    /// Whether a fresh synced filter rules out that the JTI was revoked
+    fn filter_clears(&self, jti: &str) -> bool {
+        self.synced.load().as_ref().is_some_and(|synced| {
+            synced.fetched_at.elapsed() < self.filter_max_age && !synced.filter.contains(jti)
+        })
+    }
+
+    /// Adds a JTI to the known-revoked set, forgetting the least recently
+    /// seen one when full
+    fn remember(&self, jti: &str) {
+        self.known_revoked.lock().put(jti.to_string(), ());
+    }
+
+    /// Whether the JTI was already confirmed revoked
+    fn known_revoked(&self, jti: &str) -> bool {
+        self.known_revoked.lock().get(jti).is_some()
+    }
+
+    /// Whether tokens are accepted when the revocation list is unreachable
+    pub fn fail_open(&self) -> bool {
+        self.fail_open
+    }
+
+    /// Looks up a JTI, consulting the known-revoked set before the store
+    pub async fn is_revoked(&self, jti: &str) -> Result<bool, PlatformError> {
+        if self.known_revoked(jti) {
+            return Ok(true);
+        }
+        let revoked = self.store.is_revoked(jti).await?;
+        if revoked {
+            self.remember(jti);
+        }
+        Ok(revoked)
+    }
+
+    /// Rejects revoked tokens, applying the fail-open policy to store errors
+    ///
+    /// Tokens without a `jti` cannot be revoked individually and pass.
+    pub async fn check(&self, jti: &str) -> Result<(), AuthEdgeError> {
+        if jti.is_empty() {
+            return Ok(());
+        }
+        if self.known_revoked(jti) {
+            self.metrics.record("cached_revoked");
+            return Err(AuthEdgeError::TokenRevoked);
+        }
+        if self.filter_clears(jti) {
+            self.metrics.record("filter_clean");
+            return Ok(());
+        }
+        match self.store.is_revoked(jti).await {
+            Ok(true) => {
+                self.remember(jti);
+                self.metrics.record("revoked");
+                Err(AuthEdgeError::TokenRevoked)
+            }
+            Ok(false) => {
+                self.metrics.record("clean");
+                Ok(())
+            }
+            Err(err) if self.fail_open => {
+                warn!(error = %err, "Revocation list unavailable, accepting token (fail-open)");
+                self.metrics.record("fail_open");
+                Ok(())
+            }
+            Err(err) => {
+                warn!(error = %err, "Revocation list unavailable, rejecting token (fail-closed)");
+                self.metrics.record("fail_closed");
+                Err(AuthEdgeError::Platform(PlatformError::Unavailable(
+                    "revocation list".to_string(),
+                )))
+            }
+        }
+    }
+
+    /// Revokes a JTI until `ttl` elapses
+    pub async fn revoke(&self, jti: &str, ttl: Duration) -> Result<(), AuthEdgeError> {
+        self.store.revoke(jti, ttl).await?;
+        self.remember(jti);
+        Ok(())
+    }
+}
+
+fn store_filter(slot: &ArcSwapOption<SyncedFilter>, filter: RevocationFilter) {
+    slot.store(Some(Arc::new(SyncedFilter {
+        filter,
+        fetched_at: Instant::now(),
+    })));
+}
+
+/// Fetches the revoked-JTI filter from the token service every `interval`
+///
+/// A failed fetch keeps the previous filter, which is ignored once it is
+/// older than its maximum age.
+async fn sync_filter(
+    mut client: TokenServiceClient<Channel>,
+    slot: Arc<ArcSwapOption<SyncedFilter>>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let fetched = client
+            .get_revocation_filter(RevocationFilterRequest {})
+            .await
+            .map_err(|status| PlatformError::unavailable(status.message()))
+            .and_then(|response| {
+                let response = response.into_inner();
+                RevocationFilter::from_parts(
+                    &response.bits,
+                    response.hashes,
+                    usize::try_from(response.entries).unwrap_or(usize::MAX),
+                )
+            });
+        match fetched {
+            Ok(filter) => {
+                debug!(entries = filter.len(), "Revoked-JTI filter synced");
+                store_filter(&slot, filter);
+            }
+            Err(e) => warn!(error = %e, "Revoked-JTI filter sync failed, keeping the previous filter"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::Registry;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct MemoryStore {
+        revoked: Mutex<HashSet<String>>,
+        lookups: AtomicUsize,
+        unavailable: bool,
+    }
+
+    #[async_trait]
+    impl RevocationStore for MemoryStore {
+        async fn is_revoked(&self, jti: &str) -> Result<bool, PlatformError> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            if self.unavailable {
+                return Err(PlatformError::Unavailable("cache".to_string()));
+            }
+            Ok(self.revoked.lock().contains(jti))
+        }
+
+        async fn revoke(&self, jti: &str, _ttl: Duration) -> Result<(), PlatformError> {
+            self.revoked.lock().insert(jti.to_string());
+            Ok(())
+        }
+    }
+
+    fn checker(store: Arc<MemoryStore>, fail_open: bool) -> RevocationChecker {
+        RevocationChecker::new(
+            store,
+            16,
+            fail_open,
+            RevocationMetrics::new(&Registry::new()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_revoked_jti_rejected_and_remembered() {
+        let store = Arc::new(MemoryStore::default());
+        store.revoked.lock().insert("revoked".to_string());
+        let checker = checker(store.clone(), false);
+
+        assert!(checker.check("clean").await.is_ok());
+        assert!(matches!(checker.check("revoked").await, Err(AuthEdgeError::TokenRevoked)));
+        assert!(matches!(checker.check("revoked").await, Err(AuthEdgeError::TokenRevoked)));
+        assert_eq!(store.lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_store_fail_open_and_closed() {
+        let store = Arc::new(MemoryStore {
+            unavailable: true,
+            ..Default::default()
+        });
+
+        assert!(checker(store.clone(), true).check("jti").await.is_ok());
+        assert!(matches!(
+            checker(store, false).check("jti").await,
+            Err(AuthEdgeError::Platform(PlatformError::Unavailable(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_synced_filter_skips_lookups_of_misses() {
+        let store = Arc::new(MemoryStore::default());
+        store.revoked.lock().insert("revoked".to_string());
+        let checker = checker(store.clone(), false).with_filter_max_age(Duration::from_secs(60));
+        let mut filter = RevocationFilter::with_capacity(16, 1e-6);
+        filter.insert("revoked");
+        checker.set_filter(filter);
+
+        assert!(checker.check("clean").await.is_ok());
+        assert_eq!(store.lookups.load(Ordering::SeqCst), 0);
+        assert!(matches!(checker.check("revoked").await, Err(AuthEdgeError::TokenRevoked)));
+        assert_eq!(store.lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_synced_filter_is_ignored() {
+        let store = Arc::new(MemoryStore::default());
+        let checker = checker(store.clone(), false);
+        checker.set_filter(RevocationFilter::with_capacity(16, 1e-6));
+
+        assert!(checker.check("clean").await.is_ok());
+        assert_eq!(store.lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_local_revocation_survives_outage() {
+        let store = Arc::new(MemoryStore {
+            unavailable: true,
+            ..Default::default()
+        });
+        let checker = checker(store, true);
+
+        checker.revoke("jti", Duration::from_secs(60)).await.unwrap();
+        assert!(matches!(checker.check("jti").await, Err(AuthEdgeError::TokenRevoked)));
+        assert!(checker.check("").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_known_revoked_set_is_exact_and_bounded() {
+        let store = Arc::new(MemoryStore::default());
+        let checker = RevocationChecker::new(
+            store.clone(),
+            2,
+            false,
+            RevocationMetrics::new(&Registry::new()).unwrap(),
+        );
+        for jti in ["a", "b", "c"] {
+            checker.revoke(jti, Duration::from_secs(60)).await.unwrap();
+        }
+
+        // The least recently revoked JTI is looked up again
+        assert!(matches!(checker.check("c").await, Err(AuthEdgeError::TokenRevoked)));
+        assert_eq!(store.lookups.load(Ordering::SeqCst), 0);
+        assert!(matches!(checker.check("a").await, Err(AuthEdgeError::TokenRevoked)));
+        assert_eq!(store.lookups.load(Ordering::SeqCst), 1);
+
+        // A JTI never revoked always reaches the store
+        store.revoked.lock().clear();
+        assert!(checker.check("d").await.is_ok());
+        assert_eq!(store.lookups.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,168 @@
+//! Signed JWK Sets
+//!
+//! Some IdPs publish their JWK Set as a JWS whose payload is the set, so its
+//! keys are authenticated independently of the TLS connection they were
+//! fetched over. A trusted issuer configured with a `jwks_signing_key` has
+//! signed sets verified against that pinned key before any of their keys are
+//! accepted; with `signed_jwks_only`, unsigned sets are rejected as well.
+//! A rejected set leaves the previously accepted keys in place.
+
+use jsonwebtoken::{decode, decode_header, Validation};
+
+use crate::config::TrustedIssuerConfig;
+use crate::error::AuthEdgeError;
+use crate::jwt::jwk_cache::{Jwk, JwkCache, Jwks, VerificationKey};
+
+/// Pinned key a JWK Set must be signed with
+pub struct JwksTrustAnchor {
+    kid: String,
+    key: VerificationKey,
+    signed_only: bool,
+}
+
+impl JwksTrustAnchor {
+    /// Creates an anchor verifying sets signed by `jwk`
+    ///
+    /// With `signed_only`, unsigned sets are rejected.
+    pub fn new(jwk: &Jwk, signed_only: bool) -> Result<Self, AuthEdgeError> {
+        let key = JwkCache::jwk_to_verification_key(jwk).ok_or_else(|| {
+            AuthEdgeError::JwkCacheError {
+                reason: format!("JWK Set signing key {} is not a supported public key", jwk.kid),
+            }
+        })?;
+        Ok(Self {
+            kid: jwk.kid.clone(),
+            key,
+            signed_only,
+        })
+    }
+
+    /// Builds the anchor of a trusted issuer, or `None` when its JWK Set
+    /// is not signed
+    pub fn from_config(trusted: &TrustedIssuerConfig) -> Result<Option<Self>, AuthEdgeError> {
+        trusted
+            .jwks_signing_key
+            .as_ref()
+            .map(|jwk| Self::new(jwk, trusted.signed_jwks_only))
+            .transpose()
+    }
+
+    /// Whether unsigned JWK Sets are rejected
+    pub fn signed_only(&self) -> bool {
+        self.signed_only
+    }
+
+    /// Verifies a JWS-wrapped JWK Set and returns its keys
+    fn verify(&self, jws: &str) -> Result<Jwks, AuthEdgeError> {
+        let rejected = |reason: String| AuthEdgeError::JwkCacheError {
+            reason: format!("Signed JWK Set rejected: {reason}"),
+        };
+
+        let header = decode_header(jws).map_err(|e| rejected(e.to_string()))?;
+        if !self.key.allows(header.alg) {
+            return Err(rejected(format!("{:?} is not allowed for key {}", header.alg, self.kid)));
+        }
+        if let Some(kid) = header.kid.filter(|kid| *kid != self.kid) {
+            return Err(rejected(format!("signed by unknown key {kid}")));
+        }
+
+        // The set carries no audience; `exp` is only checked when present
+        let mut validation = Validation::new(header.alg);
+        validation.validate_aud = false;
+        validation.required_spec_claims.clear();
+        decode::<Jwks>(jws, self.key.key(), &validation)
+            .map(|data| data.claims)
+            .map_err(|e| rejected(e.to_string()))
+    }
+}
+
+/// Parses a fetched JWK Set, verifying it when it is a JWS
+///
+/// Sets that are JSON objects are unsigned; anything else is taken as a
+/// compact JWS. Signed sets need an anchor, and an anchor that is
+/// `signed_only` rejects unsigned sets.
+pub fn parse_jwks(body: &str, anchor: Option<&JwksTrustAnchor>) -> Result<Jwks, AuthEdgeError> {
+    let body = body.trim();
+    if body.starts_with('{') {
+        if anchor.is_some_and(JwksTrustAnchor::signed_only) {
+            return Err(AuthEdgeError::JwkCacheError {
+                reason: "Unsigned JWK Set rejected: issuer publishes signed JWK Sets only"
+                    .to_string(),
+            });
+        }
+        return serde_json::from_str(body).map_err(|e| AuthEdgeError::JwkCacheError {
+            reason: format!("Failed to parse JWKS: {e}"),
+        });
+    }
+
+    match anchor {
+        Some(anchor) => anchor.verify(body),
+        None => Err(AuthEdgeError::JwkCacheError {
+            reason: "Signed JWK Set received but no jwks_signing_key is configured".to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+
+    const JWKS: &str = r#"{"keys":[{"kty":"EC","kid":"token-1","crv":"P-256","x":"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU","y":"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"}]}"#;
+
+    /// Signing key pair and its public JWK
+    fn signing_key(kid: &str) -> (EncodingKey, Jwk) {
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let point = key_pair.public_key_raw();
+        let jwk = Jwk {
+            kty: "EC".to_string(),
+            kid: kid.to_string(),
+            key_use: Some("sig".to_string()),
+            alg: Some("ES256".to_string()),
+            n: None,
+            e: None,
+            x: Some(URL_SAFE_NO_PAD.encode(&point[1..33])),
+            y: Some(URL_SAFE_NO_PAD.encode(&point[33..65])),
+            crv: Some("P-256".to_string()),
+        };
+        (EncodingKey::from_ec_der(&key_pair.serialize_der()), jwk)
+    }
+
+    fn sign(key: &EncodingKey, kid: &str) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(kid.to_string());
+        header.typ = Some("jwk-set+jwt".to_string());
+        let jwks: serde_json::Value = serde_json::from_str(JWKS).unwrap();
+        encode(&header, &jwks, key).unwrap()
+    }
+
+    #[test]
+    fn test_signed_set_is_verified() {
+        let (key, jwk) = signing_key("anchor-1");
+        let anchor = JwksTrustAnchor::new(&jwk, true).unwrap();
+
+        let jwks = parse_jwks(&sign(&key, "anchor-1"), Some(&anchor)).unwrap();
+        assert_eq!(jwks.keys[0].kid, "token-1");
+
+        let (other, _) = signing_key("anchor-1");
+        assert!(parse_jwks(&sign(&other, "anchor-1"), Some(&anchor)).is_err());
+        assert!(parse_jwks(&sign(&key, "anchor-2"), Some(&anchor)).is_err());
+        assert!(parse_jwks(&sign(&key, "anchor-1"), None).is_err());
+    }
+
+    #[test]
+    fn test_signed_only_rejects_unsigned_sets() {
+        let (_, jwk) = signing_key("anchor-1");
+        let strict = JwksTrustAnchor::new(&jwk, true).unwrap();
+        assert!(matches!(
+            parse_jwks(JWKS, Some(&strict)),
+            Err(AuthEdgeError::JwkCacheError { reason }) if reason.starts_with("Unsigned")
+        ));
+
+        let lenient = JwksTrustAnchor::new(&jwk, false).unwrap();
+        assert_eq!(parse_jwks(JWKS, Some(&lenient)).unwrap().keys.len(), 1);
+        assert_eq!(parse_jwks(JWKS, None).unwrap().keys.len(), 1);
+    }
+}
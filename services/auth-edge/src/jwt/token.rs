@@ -6,6 +6,8 @@
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 
@@ -56,6 +58,114 @@ impl TokenState for Validated {
 }
 
 
+// ============================================================================
+// Decode Limits
+// ============================================================================
+
+/// Bounds on the claims payload checked before it is deserialized
+///
+/// Keeps oversized or deeply nested payloads from pinning a worker; tokens
+/// over any limit are rejected as malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum decoded payload size in bytes
+    pub max_payload_bytes: usize,
+    /// Maximum number of top-level claims
+    pub max_claims: usize,
+    /// Maximum nesting depth of objects and arrays
+    pub max_depth: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_payload_bytes: 16 * 1024,
+            max_claims: 128,
+            max_depth: 16,
+        }
+    }
+}
+
+impl DecodeLimits {
+    /// Builds the limits from `JWT_MAX_PAYLOAD_BYTES`, `JWT_MAX_CLAIMS` and
+    /// `JWT_MAX_NESTING`
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        DecodeLimits {
+            max_payload_bytes: config.jwt_max_payload_bytes,
+            max_claims: config.jwt_max_claims,
+            max_depth: config.jwt_max_nesting,
+        }
+    }
+
+    /// Checks the base64url payload segment of `raw` against the limits
+    ///
+    /// Scans the JSON once without building it, so the cost is linear in
+    /// the payload size whatever its shape.
+    fn check(&self, raw: &str) -> Result<(), AuthEdgeError> {
+        let malformed = |reason: String| AuthEdgeError::TokenMalformed { reason };
+        let payload = raw
+            .split('.')
+            .nth(1)
+            .ok_or_else(|| malformed("Missing payload segment".to_string()))?;
+        if payload.len() > self.max_payload_bytes.div_ceil(3) * 4 {
+            return Err(malformed(format!(
+                "Payload exceeds {} bytes",
+                self.max_payload_bytes
+            )));
+        }
+        let bytes = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| malformed("Invalid payload encoding".to_string()))?;
+        if bytes.len() > self.max_payload_bytes {
+            return Err(malformed(format!(
+                "Payload exceeds {} bytes",
+                self.max_payload_bytes
+            )));
+        }
+
+        let mut depth = 0usize;
+        let mut claims = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        for &byte in &bytes {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => in_string = true,
+                b'{' | b'[' => {
+                    depth += 1;
+                    if depth > self.max_depth {
+                        return Err(malformed(format!(
+                            "Claims nested deeper than {}",
+                            self.max_depth
+                        )));
+                    }
+                }
+                b'}' | b']' => depth = depth.saturating_sub(1),
+                // Each top-level member has exactly one unquoted colon
+                b':' if depth == 1 => {
+                    claims += 1;
+                    if claims > self.max_claims {
+                        return Err(malformed(format!(
+                            "More than {} claims",
+                            self.max_claims
+                        )));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
 // ============================================================================
 // Type-State Token Wrapper
 // ============================================================================
@@ -78,8 +188,15 @@ pub struct Token<State: TokenState> {
 impl Token<Unvalidated> {
     /// Parse a raw JWT string into an unvalidated token
     /// 
-    /// This performs zero-copy header parsing where possible.
+    /// This performs zero-copy header parsing where possible. The payload
+    /// must fit the default [`DecodeLimits`].
     pub fn parse(raw: &str) -> Result<Self, AuthEdgeError> {
+        Self::parse_with_limits(raw, &DecodeLimits::default())
+    }
+
+    /// Parse a raw JWT string, rejecting payloads over `limits`
+    pub fn parse_with_limits(raw: &str, limits: &DecodeLimits) -> Result<Self, AuthEdgeError> {
+        limits.check(raw)?;
         let header = decode_header(raw).map_err(|e| AuthEdgeError::TokenMalformed {
             reason: format!("Invalid header: {}", e),
         })?;
@@ -105,6 +222,30 @@ impl Token<Unvalidated> {
         self.header.alg
     }
 
+    /// Read the `iss` claim without verifying the signature
+    ///
+    /// Only used to pick the issuer's key set; the claim is re-checked once
+    /// the signature has been verified.
+    pub fn unverified_issuer(&self) -> Result<String, AuthEdgeError> {
+        #[derive(Deserialize)]
+        struct IssuerOnly {
+            iss: String,
+        }
+
+        let mut validation = Validation::new(self.header.alg);
+        validation.insecure_disable_signature_validation();
+        validation.validate_exp = false;
+        validation.validate_nbf = false;
+        validation.validate_aud = false;
+        validation.required_spec_claims.clear();
+
+        decode::<IssuerOnly>(&self.raw, &DecodingKey::from_secret(&[]), &validation)
+            .map(|data| data.claims.iss)
+            .map_err(|e| AuthEdgeError::TokenMalformed {
+                reason: format!("Unreadable issuer: {}", e),
+            })
+    }
+
     /// Validate the token signature using the JWK cache
     pub async fn validate_signature(
         self,
@@ -114,7 +255,10 @@ impl Token<Unvalidated> {
             reason: "Missing kid in header".to_string(),
         })?;
 
-        let decoding_key = cache.get_key(kid).await?;
+        let key = cache.get_key(kid).await?;
+        if !key.allows(self.header.alg) {
+            return Err(AuthEdgeError::TokenInvalid);
+        }
 
         // Set up validation (signature only, no claims validation yet)
         let mut validation = Validation::new(self.header.alg);
@@ -123,7 +267,7 @@ impl Token<Unvalidated> {
         validation.validate_aud = false;
         validation.required_spec_claims.clear();
 
-        let token_data = decode::<Claims>(&self.raw, &decoding_key, &validation)
+        let token_data = decode::<Claims>(&self.raw, key.key(), &validation)
             .map_err(|e| {
                 if e.to_string().contains("InvalidSignature") {
                     AuthEdgeError::TokenInvalid
@@ -229,6 +373,31 @@ impl Token<SignatureValidated> {
     pub fn peek_claims(&self) -> Option<&Claims> {
         self.claims.as_ref()
     }
+
+    /// Rebuilds a token whose signature was verified earlier, e.g. from the
+    /// validated-token cache; claims must still be validated again
+    pub(crate) fn from_verified(raw: &str, header: Header, claims: Claims) -> Self {
+        Token {
+            raw: raw.to_string(),
+            kid: header.kid.clone(),
+            header,
+            claims: Some(claims),
+            _state: PhantomData,
+        }
+    }
+}
+
+/// Validated tokens are shared among coalesced validations
+impl Clone for Token<Validated> {
+    fn clone(&self) -> Self {
+        Token {
+            raw: self.raw.clone(),
+            header: self.header.clone(),
+            claims: self.claims.clone(),
+            kid: self.kid.clone(),
+            _state: PhantomData,
+        }
+    }
 }
 
 impl Token<Validated> {
@@ -237,6 +406,11 @@ impl Token<Validated> {
         self.claims.as_ref().expect("Validated token must have claims")
     }
 
+    /// Mutable claims, for restoring claims carried by reference
+    pub(crate) fn claims_mut(&mut self) -> &mut Claims {
+        self.claims.as_mut().expect("Validated token must have claims")
+    }
+
     /// Get the subject claim
     pub fn subject(&self) -> &str {
         &self.claims().sub
@@ -294,4 +468,9 @@ impl<S: TokenState> Token<S> {
     pub fn state_name(&self) -> &'static str {
         S::state_name()
     }
+
+    /// Check whether the header marks this as an emergency bypass token
+    pub fn is_emergency_bypass(&self) -> bool {
+        self.header.typ.as_deref() == Some(crate::jwt::bypass::EMERGENCY_BYPASS_TYP)
+    }
 }
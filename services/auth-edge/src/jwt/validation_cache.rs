@@ -0,0 +1,335 @@
+//! Validated-Token Cache
+//!
+//! Bounded LRU of tokens whose signature and issuer policy have already been
+//! verified, keyed by the SHA-256 of the raw token. Entries live until the
+//! token's `exp` or the configured max TTL, whichever comes first. Time-based
+//! claims and required claims are still checked on every hit.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::Header;
+use lru::LruCache;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::jwt::claims::Claims;
+use crate::observability::ValidationCacheMetrics;
+
+type TokenHash = [u8; 32];
+
+struct CachedToken {
+    header: Header,
+    claims: Claims,
+    expires_at: Instant,
+}
+
+struct Inner {
+    entries: LruCache<TokenHash, CachedToken>,
+    by_jti: HashMap<String, TokenHash>,
+}
+
+impl Inner {
+    /// Drops the `jti` index entry of a removed token, unless a newer token
+    /// with the same `jti` has taken it over
+    fn forget_jti(&mut self, key: &TokenHash, token: &CachedToken) {
+        if self.by_jti.get(&token.claims.jti) == Some(key) {
+            self.by_jti.remove(&token.claims.jti);
+        }
+    }
+}
+
+/// LRU cache of signature-verified tokens
+pub struct ValidationCache {
+    inner: Mutex<Inner>,
+    max_ttl: Duration,
+    metrics: ValidationCacheMetrics,
+}
+
+impl ValidationCache {
+    /// Creates a cache holding up to `capacity` tokens for at most `max_ttl`
+    pub fn new(capacity: NonZeroUsize, max_ttl: Duration, metrics: ValidationCacheMetrics) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                entries: LruCache::new(capacity),
+                by_jti: HashMap::new(),
+            }),
+            max_ttl,
+            metrics,
+        }
+    }
+
+    /// Builds the cache from configuration, or `None` when disabled
+    pub fn from_config(config: &Config, metrics: ValidationCacheMetrics) -> Option<Self> {
+        let capacity = NonZeroUsize::new(config.validation_cache_size)?;
+        if config.validation_cache_max_ttl_secs == 0 {
+            return None;
+        }
+        Some(Self::new(
+            capacity,
+            Duration::from_secs(config.validation_cache_max_ttl_secs),
+            metrics,
+        ))
+    }
+
+    /// Returns the cached header and claims for a token, if still fresh
+    pub fn get(&self, raw_token: &str) -> Option<(Header, Claims)> {
+        self.get_at(raw_token, Instant::now())
+    }
+
+    fn get_at(&self, raw_token: &str, now: Instant) -> Option<(Header, Claims)> {
+        let key = hash(raw_token);
+        let mut inner = self.inner.lock();
+
+        let expired = match inner.entries.get(&key) {
+            Some(entry) if entry.expires_at > now => {
+                self.metrics.record_hit();
+                return Some((entry.header.clone(), entry.claims.clone()));
+            }
+            Some(_) => true,
+            None => false,
+        };
+        if expired {
+            if let Some(entry) = inner.entries.pop(&key) {
+                inner.forget_jti(&key, &entry);
+                self.metrics.record_removal("expired");
+            }
+        }
+        self.metrics.record_miss();
+        None
+    }
+
+    /// Caches a verified token until its `exp` or the max TTL
+    pub fn insert(&self, raw_token: &str, header: &Header, claims: &Claims) {
+        let remaining = claims.exp - chrono::Utc::now().timestamp();
+        let Ok(remaining) = u64::try_from(remaining) else {
+            return;
+        };
+        self.insert_at(
+            raw_token,
+            header,
+            claims,
+            Instant::now() + Duration::from_secs(remaining).min(self.max_ttl),
+        );
+    }
+
+    fn insert_at(&self, raw_token: &str, header: &Header, claims: &Claims, expires_at: Instant) {
+        let key = hash(raw_token);
+        let mut inner = self.inner.lock();
+
+        let entry = CachedToken {
+            header: header.clone(),
+            claims: claims.clone(),
+            expires_at,
+        };
+        if let Some((evicted_key, evicted)) = inner.entries.push(key, entry) {
+            if evicted_key != key {
+                inner.forget_jti(&evicted_key, &evicted);
+                self.metrics.record_removal("evicted");
+            }
+        }
+        if !claims.jti.is_empty() {
+            inner.by_jti.insert(claims.jti.clone(), key);
+        }
+    }
+
+    /// Drops the cached token with the given `jti`, e.g. on a revocation event
+    pub fn invalidate_jti(&self, jti: &str) -> bool {
+        let mut inner = self.inner.lock();
+        let Some(key) = inner.by_jti.remove(jti) else {
+            return false;
+        };
+        let removed = inner.entries.pop(&key).is_some();
+        if removed {
+            self.metrics.record_removal("revoked");
+        }
+        removed
+    }
+
+    /// Drops a cached token by its raw value
+    pub fn invalidate_token(&self, raw_token: &str) -> bool {
+        let mut inner = self.inner.lock();
+        let key = hash(raw_token);
+        match inner.entries.pop(&key) {
+            Some(entry) => {
+                inner.forget_jti(&key, &entry);
+                self.metrics.record_removal("revoked");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drops every cached token whose claims match, e.g. on a CAEP event
+    ///
+    /// Returns the number of entries removed.
+    pub fn invalidate_matching(&self, matches: impl Fn(&Claims) -> bool) -> usize {
+        let mut inner = self.inner.lock();
+        let keys: Vec<TokenHash> = inner
+            .entries
+            .iter()
+            .filter(|(_, entry)| matches(&entry.claims))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in &keys {
+            if let Some(entry) = inner.entries.pop(key) {
+                inner.forget_jti(key, &entry);
+                self.metrics.record_removal("revoked");
+            }
+        }
+        keys.len()
+    }
+
+    /// Drops every cached token, e.g. after the key set changed
+    ///
+    /// Returns the number of entries removed.
+    pub fn clear(&self) -> usize {
+        let mut inner = self.inner.lock();
+        let removed = inner.entries.len();
+        inner.entries.clear();
+        inner.by_jti.clear();
+        self.metrics
+            .removals
+            .with_label_values(&["flushed"])
+            .inc_by(removed as f64);
+        removed
+    }
+
+    /// Number of cached tokens
+    pub fn len(&self) -> usize {
+        self.inner.lock().entries.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn hash(raw_token: &str) -> TokenHash {
+    Sha256::digest(raw_token.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::Registry;
+
+    fn cache(capacity: usize) -> ValidationCache {
+        ValidationCache::new(
+            NonZeroUsize::new(capacity).unwrap(),
+            Duration::from_secs(60),
+            ValidationCacheMetrics::new(&Registry::new()).unwrap(),
+        )
+    }
+
+    fn claims(jti: &str) -> Claims {
+        Claims {
+            iss: "https://idp.example".to_string(),
+            sub: "user".to_string(),
+            aud: vec![],
+            exp: chrono::Utc::now().timestamp() + 600,
+            iat: chrono::Utc::now().timestamp(),
+            nbf: None,
+            jti: jti.to_string(),
+            session_id: None,
+            scopes: None,
+            custom: HashMap::new(),
+        }
+    }
+
+    fn hits(cache: &ValidationCache) -> f64 {
+        cache.metrics.lookups.with_label_values(&["hit"]).get()
+    }
+
+    #[test]
+    fn test_hit_until_expiry() {
+        let cache = cache(4);
+        let now = Instant::now();
+        cache.insert_at("t1", &Header::default(), &claims("a"), now + Duration::from_secs(10));
+
+        assert_eq!(cache.get_at("t1", now).unwrap().1.jti, "a");
+        assert!(cache.get_at("t2", now).is_none());
+        assert!(cache.get_at("t1", now + Duration::from_secs(11)).is_none());
+        assert!(cache.is_empty());
+        assert_eq!(hits(&cache), 1.0);
+    }
+
+    #[test]
+    fn test_insert_caps_ttl() {
+        let cache = cache(4);
+        cache.insert("t1", &Header::default(), &claims("a"));
+        let later = Instant::now() + Duration::from_secs(61);
+        assert!(cache.get_at("t1", later).is_none());
+    }
+
+    #[test]
+    fn test_lru_eviction_cleans_jti_index() {
+        let cache = cache(1);
+        let expires = Instant::now() + Duration::from_secs(10);
+        cache.insert_at("t1", &Header::default(), &claims("a"), expires);
+        cache.insert_at("t2", &Header::default(), &claims("b"), expires);
+
+        assert_eq!(cache.len(), 1);
+        assert!(!cache.invalidate_jti("a"));
+        assert!(cache.invalidate_jti("b"));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_eviction_keeps_jti_of_newer_token() {
+        let cache = cache(2);
+        let expires = Instant::now() + Duration::from_secs(10);
+        cache.insert_at("t1", &Header::default(), &claims("a"), expires);
+        cache.insert_at("t2", &Header::default(), &claims("a"), expires);
+        cache.insert_at("t3", &Header::default(), &claims("b"), expires);
+
+        // Evicting t1 leaves the index pointing at t2
+        assert!(cache.get_at("t1", Instant::now()).is_none());
+        assert!(cache.invalidate_jti("a"));
+        assert!(cache.get_at("t2", Instant::now()).is_none());
+    }
+
+    #[test]
+    fn test_invalidate_on_revocation() {
+        let cache = cache(4);
+        cache.insert("t1", &Header::default(), &claims("a"));
+        cache.insert("t2", &Header::default(), &claims("b"));
+
+        assert!(cache.invalidate_jti("a"));
+        assert!(cache.get("t1").is_none());
+        assert!(cache.invalidate_token("t2"));
+        assert!(cache.get("t2").is_none());
+    }
+
+    #[test]
+    fn test_invalidate_matching_subject() {
+        let cache = cache(4);
+        let mut other = claims("b");
+        other.sub = "other".to_string();
+        cache.insert("t1", &Header::default(), &claims("a"));
+        cache.insert("t2", &Header::default(), &other);
+
+        assert_eq!(cache.invalidate_matching(|claims| claims.sub == "user"), 1);
+        assert!(cache.get("t1").is_none());
+        assert!(!cache.invalidate_jti("a"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_clear_drops_entries_and_jti_index() {
+        let cache = cache(4);
+        cache.insert("t1", &Header::default(), &claims("a"));
+        cache.insert("t2", &Header::default(), &claims("b"));
+
+        assert_eq!(cache.clear(), 2);
+        assert!(cache.is_empty());
+        assert!(!cache.invalidate_jti("a"));
+        assert_eq!(
+            cache.metrics.removals.with_label_values(&["flushed"]).get(),
+            2.0
+        );
+    }
+}
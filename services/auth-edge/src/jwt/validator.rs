@@ -3,43 +3,347 @@
 //! Provides both legacy validation API and new type-state based validation.
 
 use crate::error::AuthEdgeError;
-use crate::jwt::claims::Claims;
+use crate::jwt::audience_keys::AudienceKeyPolicy;
+use crate::jwt::bypass::BypassValidator;
+use crate::caep::SubjectDenylist;
+use crate::jwt::claims::{ClaimExpressions, Claims, EXPRESSION_PREFIX};
+use crate::jwt::claims_ref::ClaimsRefResolver;
+use crate::jwt::coalescer::ValidationCoalescer;
+use crate::jwt::issuer::IssuerRegistry;
 use crate::jwt::jwk_cache::JwkCache;
-use crate::jwt::token::{Token, Unvalidated, SignatureValidated, Validated};
+use crate::jwt::max_age::TokenAgePolicy;
+use crate::jwt::revocation::RevocationChecker;
+use crate::jwt::token::{DecodeLimits, Token, Unvalidated, SignatureValidated, Validated};
+use crate::jwt::validation_cache::ValidationCache;
+use arc_swap::ArcSwap;
+use jsonwebtoken::{Algorithm, DecodingKey};
 use std::sync::Arc;
 
 /// JWT Validator with JWK cache integration
 pub struct JwtValidator {
-    jwk_cache: Arc<JwkCache>,
+    jwk_cache: ArcSwap<JwkCache>,
+    issuers: Option<IssuerRegistry>,
+    bypass: Option<BypassValidator>,
+    cache: Option<ValidationCache>,
+    coalescer: Option<ValidationCoalescer>,
+    revocation: Option<RevocationChecker>,
+    denylist: Option<Arc<SubjectDenylist>>,
+    expressions: ClaimExpressions,
+    algorithms: Option<Vec<Algorithm>>,
+    max_age: Option<TokenAgePolicy>,
+    audience_keys: Option<AudienceKeyPolicy>,
+    claims_refs: Option<ClaimsRefResolver>,
+    decode_limits: DecodeLimits,
 }
 
 impl JwtValidator {
     /// Creates a new JWT validator with the given JWK cache
     pub fn new(jwk_cache: Arc<JwkCache>) -> Self {
-        JwtValidator { jwk_cache }
+        JwtValidator {
+            jwk_cache: ArcSwap::new(jwk_cache),
+            issuers: None,
+            bypass: None,
+            cache: None,
+            coalescer: None,
+            revocation: None,
+            denylist: None,
+            expressions: ClaimExpressions::default(),
+            algorithms: None,
+            max_age: None,
+            audience_keys: None,
+            claims_refs: None,
+            decode_limits: DecodeLimits::default(),
+        }
+    }
+
+    /// Routes tokens to per-issuer key sets and policies by `iss` claim
+    ///
+    /// Tokens from issuers missing from the registry are rejected.
+    pub fn with_issuers(mut self, issuers: IssuerRegistry) -> Self {
+        self.issuers = Some(issuers);
+        self
+    }
+
+    /// Enables emergency bypass tokens verified by the given validator
+    pub fn with_bypass(mut self, bypass: BypassValidator) -> Self {
+        self.bypass = Some(bypass);
+        self
+    }
+
+    /// Reuses earlier signature verifications for repeated tokens
+    pub fn with_cache(mut self, cache: ValidationCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Shares validations among identical concurrent requests
+    pub fn with_coalescer(mut self, coalescer: ValidationCoalescer) -> Self {
+        self.coalescer = Some(coalescer);
+        self
+    }
+
+    /// Rejects tokens whose `jti` is on the revocation list
+    pub fn with_revocation(mut self, revocation: RevocationChecker) -> Self {
+        self.revocation = Some(revocation);
+        self
+    }
+
+    /// Rejects tokens of subjects and sessions revoked by CAEP events
+    pub fn with_denylist(mut self, denylist: Arc<SubjectDenylist>) -> Self {
+        self.denylist = Some(denylist);
+        self
+    }
+
+    /// Limits tokens verified against the default JWK cache to `algorithms`
+    ///
+    /// Tokens from registered issuers follow their issuer's allowlist.
+    pub fn with_algorithms(mut self, algorithms: Vec<Algorithm>) -> Self {
+        self.algorithms = Some(algorithms);
+        self
+    }
+
+    /// Resolves `cel:<name>` required claims to these expressions
+    pub fn with_claim_expressions(mut self, expressions: ClaimExpressions) -> Self {
+        self.expressions = expressions;
+        self
+    }
+
+    /// Rejects tokens issued longer ago than the policy allows for the route
+    pub fn with_max_token_age(mut self, policy: TokenAgePolicy) -> Self {
+        self.max_age = Some(policy);
+        self
+    }
+
+    /// Rejects tokens for restricted audiences signed with another key
+    pub fn with_audience_keys(mut self, policy: AudienceKeyPolicy) -> Self {
+        self.audience_keys = Some(policy);
+        self
+    }
+
+    /// Restores claims the token service moved into `claims_ref` entries
+    ///
+    /// Required claims are checked against the restored claims.
+    pub fn with_claims_refs(mut self, claims_refs: ClaimsRefResolver) -> Self {
+        self.claims_refs = Some(claims_refs);
+        self
+    }
+
+    /// Rejects tokens whose payload exceeds `limits` before decoding claims
+    pub fn with_decode_limits(mut self, limits: DecodeLimits) -> Self {
+        self.decode_limits = limits;
+        self
+    }
+
+    /// Named claim expressions available to `required_claims`
+    pub fn claim_expressions(&self) -> &ClaimExpressions {
+        &self.expressions
+    }
+
+    /// Revocation checker, if configured
+    pub fn revocation(&self) -> Option<&RevocationChecker> {
+        self.revocation.as_ref()
+    }
+
+    /// Drops a revoked token from the validated-token cache
+    ///
+    /// Returns whether a cached entry was removed.
+    pub fn invalidate_cached(&self, jti: &str) -> bool {
+        self.cache
+            .as_ref()
+            .is_some_and(|cache| cache.invalidate_jti(jti))
+    }
+
+    /// Drops every cached token whose claims match
+    ///
+    /// Returns the number of cached entries removed.
+    pub fn invalidate_cached_matching(&self, matches: impl Fn(&Claims) -> bool) -> usize {
+        self.cache
+            .as_ref()
+            .map_or(0, |cache| cache.invalidate_matching(matches))
+    }
+
+    /// Drops every token from the validated-token cache
+    ///
+    /// Returns the number of cached entries removed.
+    pub fn clear_cached(&self) -> usize {
+        self.cache.as_ref().map_or(0, ValidationCache::clear)
+    }
+
+    /// Trusted issuer registry, if configured
+    pub(crate) fn issuers(&self) -> Option<&IssuerRegistry> {
+        self.issuers.as_ref()
+    }
+
+    /// Key set used when no issuer registry is configured
+    pub(crate) fn default_keys(&self) -> Arc<JwkCache> {
+        self.jwk_cache.load_full()
+    }
+
+    /// Swaps the key set used when no issuer registry is configured
+    ///
+    /// Validations already in flight finish against the previous key set.
+    pub fn replace_jwk_cache(&self, jwk_cache: Arc<JwkCache>) {
+        self.jwk_cache.store(jwk_cache);
     }
 
     /// Validates a JWT token using the type-state pattern
     /// 
     /// Returns a fully validated Token<Validated> that guarantees
-    /// claims can only be accessed after validation.
+    /// claims can only be accessed after validation. `cel:<name>` entries in
+    /// `required_claims` must satisfy the named claim expression.
     pub async fn validate_token(
         &self,
         raw_token: &str,
         required_claims: &[&str],
     ) -> Result<Token<Validated>, AuthEdgeError> {
+        self.validate_token_for_route(raw_token, required_claims, "").await
+    }
+
+    /// Validates a JWT token for a route such as `GET /v1/orders/42`
+    ///
+    /// Like [`Self::validate_token`], but applies the route's maximum token
+    /// age override; an empty route gets the default maximum age.
+    pub async fn validate_token_for_route(
+        &self,
+        raw_token: &str,
+        required_claims: &[&str],
+        route: &str,
+    ) -> Result<Token<Validated>, AuthEdgeError> {
+        match &self.coalescer {
+            Some(coalescer) => {
+                coalescer
+                    .coalesce(raw_token, required_claims, route, || {
+                        self.validate_uncoalesced(raw_token, required_claims, route)
+                    })
+                    .await
+            }
+            None => self.validate_uncoalesced(raw_token, required_claims, route).await,
+        }
+    }
+
+    async fn validate_uncoalesced(
+        &self,
+        raw_token: &str,
+        required_claims: &[&str],
+        route: &str,
+    ) -> Result<Token<Validated>, AuthEdgeError> {
+        let (expressions, required_claims): (Vec<&str>, Vec<&str>) = required_claims
+            .iter()
+            .partition(|claim| claim.starts_with(EXPRESSION_PREFIX));
+        let validated = match &self.claims_refs {
+            Some(claims_refs) => {
+                let mut validated = self.verify_token(raw_token, &[]).await?;
+                claims_refs.resolve(validated.claims_mut()).await?;
+                let missing = validated.claims().unmet(&required_claims, &self.expressions);
+                if !missing.is_empty() {
+                    return Err(AuthEdgeError::ClaimsInvalid { claims: missing });
+                }
+                validated
+            }
+            None => self.verify_token(raw_token, &required_claims).await?,
+        };
+
+        // Bypass tokens are signed with the emergency key, never an audience key
+        if let Some(audience_keys) = &self.audience_keys {
+            if !validated.is_emergency_bypass() {
+                audience_keys.check(validated.header().kid.as_deref(), validated.claims())?;
+            }
+        }
+
+        if let Some(max_age) = &self.max_age {
+            max_age.check(validated.claims(), route)?;
+        }
+
+        let unmet = validated.claims().unmet(&expressions, &self.expressions);
+        if !unmet.is_empty() {
+            return Err(AuthEdgeError::ClaimsInvalid { claims: unmet });
+        }
+
+        if let Some(denylist) = &self.denylist {
+            if denylist.is_denied(validated.claims()) {
+                self.invalidate_cached(&validated.claims().jti);
+                return Err(AuthEdgeError::TokenRevoked);
+            }
+        }
+
+        // Revocation is only consulted once signature and claims hold
+        if let Some(revocation) = &self.revocation {
+            let jti = &validated.claims().jti;
+            if let Err(err) = revocation.check(jti).await {
+                if matches!(err, AuthEdgeError::TokenRevoked) {
+                    self.invalidate_cached(jti);
+                }
+                return Err(err);
+            }
+        }
+
+        Ok(validated)
+    }
+
+    /// Verifies signature, issuer policy and claims, reusing cached verifications
+    async fn verify_token(
+        &self,
+        raw_token: &str,
+        required_claims: &[&str],
+    ) -> Result<Token<Validated>, AuthEdgeError> {
+        // Signature and issuer policy were already verified for cached tokens
+        if let Some((header, claims)) = self.cache.as_ref().and_then(|cache| cache.get(raw_token)) {
+            return Token::<SignatureValidated>::from_verified(raw_token, header, claims)
+                .validate_claims(required_claims);
+        }
+
         // Parse token (Unvalidated state)
-        let unvalidated = Token::<Unvalidated>::parse(raw_token)?;
+        let unvalidated = Token::<Unvalidated>::parse_with_limits(raw_token, &self.decode_limits)?;
+
+        // Bypass tokens never go through JWKS; reject them if no emergency key is configured
+        if unvalidated.is_emergency_bypass() {
+            return match &self.bypass {
+                Some(bypass) => bypass.validate(unvalidated, required_claims),
+                None => Err(AuthEdgeError::TokenInvalid),
+            };
+        }
         
         // Validate signature (SignatureValidated state)
-        let signature_validated = unvalidated.validate_signature(&self.jwk_cache).await?;
+        let signature_validated = match &self.issuers {
+            Some(issuers) => {
+                let trusted = issuers.resolve(&unvalidated.unverified_issuer()?)?;
+                trusted.policy.check_algorithm(unvalidated.algorithm())?;
+                let signature_validated = unvalidated.validate_signature(&trusted.keys).await?;
+                if let Some(claims) = signature_validated.peek_claims() {
+                    trusted.policy.check_claims(claims)?;
+                }
+                signature_validated
+            }
+            None => {
+                if let Some(algorithms) = &self.algorithms {
+                    if !algorithms.contains(&unvalidated.algorithm()) {
+                        return Err(AuthEdgeError::TokenInvalid);
+                    }
+                }
+                unvalidated.validate_signature(&self.jwk_cache.load_full()).await?
+            }
+        };
         
         // Validate claims (Validated state)
         let validated = signature_validated.validate_claims(required_claims)?;
-        
+        if let Some(cache) = &self.cache {
+            cache.insert(raw_token, validated.header(), validated.claims());
+        }
+
         Ok(validated)
     }
 
+    /// Validates a JWT against a fixed key, skipping the JWK cache
+    pub fn validate_token_with_key(
+        raw_token: &str,
+        key: &DecodingKey,
+        required_claims: &[&str],
+    ) -> Result<Token<Validated>, AuthEdgeError> {
+        Token::<Unvalidated>::parse(raw_token)?
+            .validate_signature_with_key(key)?
+            .validate_claims(required_claims)
+    }
+
     /// Legacy validation method for backward compatibility
     pub async fn validate(&self, token: &str, required_claims: &[String]) -> Result<Claims, AuthEdgeError> {
         let required_refs: Vec<&str> = required_claims.iter().map(|s| s.as_str()).collect();
@@ -6,16 +6,25 @@
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
+#![cfg_attr(not(test), deny(clippy::unwrap_used))]
 
+pub mod api_key;
+pub mod authz;
+pub mod caep;
+pub mod chaos;
 pub mod config;
 pub mod crypto;
 pub mod error;
+pub mod federation;
+pub mod gateway;
 pub mod grpc;
 pub mod jwt;
 pub mod middleware;
 pub mod mtls;
 pub mod observability;
 pub mod rate_limiter;
+pub mod reload;
+pub mod risk;
 pub mod shutdown;
 
 // Include generated protobuf code
@@ -27,11 +36,28 @@ pub mod proto {
         }
     }
 
-    // auth-edge server
+    // auth-edge server and token-service client
     pub mod auth {
         pub mod v1 {
             tonic::include_proto!("auth.v1");
+
+            /// Encoded descriptors of the served protos, for server reflection
+            pub const FILE_DESCRIPTOR_SET: &[u8] =
+                tonic::include_file_descriptor_set!("auth_edge_descriptor");
+        }
+
+        pub mod common {
+            tonic::include_proto!("auth.common");
         }
+
+        pub mod token {
+            tonic::include_proto!("auth.token");
+        }
+    }
+
+    // SPIFFE Workload API client (package-less, as served by SPIRE)
+    pub mod workload {
+        tonic::include_proto!("_");
     }
 }
 
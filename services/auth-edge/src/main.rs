@@ -3,75 +3,235 @@
 //! Provides JWT validation, SPIFFE identity extraction, and token introspection
 //! with modern observability and graceful shutdown.
 
-mod config;
-mod jwt;
-mod mtls;
-mod grpc;
-mod error;
-mod rate_limiter;
-mod middleware;
-mod observability;
-mod shutdown;
-
-use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
 use tonic::transport::Server;
-use tracing::info;
-
-use crate::config::Config;
-use crate::grpc::AuthEdgeServiceImpl;
-use crate::observability::{init_telemetry, TelemetryConfig, shutdown_telemetry};
-use crate::shutdown::{ShutdownCoordinator, run_with_graceful_shutdown};
-
-pub mod proto {
-    pub mod common {
-        tonic::include_proto!("auth.common");
-    }
-    pub mod edge {
-        tonic::include_proto!("auth.edge");
-    }
-}
-
-use proto::edge::auth_edge_service_server::AuthEdgeServiceServer;
+use tracing::{error, info, warn};
+
+use auth_edge::api_key::ApiKeyAuthenticator;
+use auth_edge::chaos;
+use auth_edge::config::Config;
+use auth_edge::gateway::{self, GatewayState};
+use auth_edge::grpc::admin::AdminServiceImpl;
+use auth_edge::grpc::AuthEdgeServiceImpl;
+use auth_edge::middleware::{
+    ApiKeyLayer, ConcurrencyLimit, ConcurrencyLimitLayer, InFlightLayer, LoadShedLayer,
+    RateLimitMetadataLayer, TracingLayer, extract_trace_context,
+};
+use auth_edge::mtls::connection::{self, ClientAuth, ServerTls};
+use auth_edge::mtls::{WorkloadApiClient, WorkloadIdentity};
+use auth_edge::observability::{ConcurrencyMetrics, ConfigReloadMetrics, RateLimiterMetrics};
+use auth_edge::proto::auth::v1::auth_edge_admin_service_server::AuthEdgeAdminServiceServer;
+use auth_edge::proto::auth::v1::auth_edge_service_server::AuthEdgeServiceServer;
+use auth_edge::proto::auth::v1::FILE_DESCRIPTOR_SET;
+use auth_edge::rate_limiter::{AdaptiveRateLimiter, InFlightRequests, LoadSampler};
+use auth_edge::reload::ConfigReloader;
+use auth_edge::shutdown::{
+    HookPriority, ShutdownCoordinator, TELEMETRY_FLUSH_TIMEOUT, run_with_graceful_shutdown,
+};
+use rust_common::{GrpcMetrics, GrpcMetricsLayer, TracingConfig, init_telemetry};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load configuration
     let config = Config::from_env()?;
 
-    // Initialize OpenTelemetry
-    let telemetry_config = TelemetryConfig {
-        service_name: "auth-edge-service".to_string(),
-        otlp_endpoint: config.otlp_endpoint_str().to_string(),
-        sampling_ratio: 1.0,
-        enable_console: true,
-    };
-    init_telemetry(&telemetry_config)?;
+    // Initialize logging and tracing
+    let mut tracing_config = TracingConfig::default()
+        .with_service_name("auth-edge-service")
+        .with_service_version(env!("CARGO_PKG_VERSION"))
+        .with_json_output()
+        .with_sampling_ratio(config.otlp_sampling_ratio)
+        .with_resource_attributes(config.otlp_resource_attributes.clone());
+    if let Some(spiffe_id) = &config.workload_spiffe_id {
+        tracing_config = tracing_config.with_spiffe_id(spiffe_id);
+    }
+    if config.otlp_enabled {
+        tracing_config = tracing_config
+            .with_otlp_endpoint(config.otlp_endpoint_str())
+            .with_otlp_headers(config.otlp_headers.clone());
+    }
+    let telemetry = init_telemetry(&tracing_config)?;
+    let log_level = telemetry
+        .log_level()
+        .clone()
+        .with_max_duration(Duration::from_secs(config.log_level_override_max_secs));
 
     info!("Starting Auth Edge Service");
 
-    let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
-
-    // Create service implementation
-    let auth_edge_service = AuthEdgeServiceImpl::new(config.clone()).await?;
+    // Game days only: admins may slow down or fail internal components
+    chaos::install(&config);
 
-    info!("Auth Edge Service listening on {}", addr);
+    let addrs = config.listen_addrs(config.port)?;
 
     // Create shutdown coordinator
-    let shutdown_coordinator = ShutdownCoordinator::new();
+    let mut shutdown_coordinator = ShutdownCoordinator::new();
     let shutdown_timeout = Duration::from_secs(config.shutdown_timeout_seconds);
 
-    // Build and run server with graceful shutdown
-    let server = Server::builder()
-        .add_service(AuthEdgeServiceServer::new(auth_edge_service))
-        .serve(addr);
+    // Serve with the X.509-SVID issued by the SPIRE agent, when configured
+    let workload_identity = match &config.spiffe_endpoint_socket {
+        Some(endpoint) => {
+            let client = WorkloadApiClient::new(endpoint)?;
+            let (identity, watch) = WorkloadIdentity::start(client, ClientAuth::from_config(&config)).await?;
+            shutdown_coordinator.spawn("svid-rotation", watch);
+            Some(identity)
+        }
+        None => None,
+    };
+
+    // Create service implementation
+    let mut service = AuthEdgeServiceImpl::new(config.clone()).await?;
+    if let Some(identity) = &workload_identity {
+        service = service.with_workload_identity(identity.clone());
+    }
+    let auth_edge_service = Arc::new(service);
+    shutdown_coordinator = shutdown_coordinator.with_logger(auth_edge_service.logger().clone());
+    if let Some(audit_log) = auth_edge_service.audit_log() {
+        let interval = Duration::from_secs(config.audit_log_flush_interval_secs);
+        shutdown_coordinator.spawn("audit-log-flush", audit_log.clone().run(interval));
+        shutdown_coordinator = shutdown_coordinator.with_audit_log(audit_log.clone());
+    }
+    let api_keys = ApiKeyAuthenticator::from_config(&config).await?.map(Arc::new);
+    if api_keys.is_some() {
+        info!("API key caller authentication enabled");
+    }
+
+    info!(addrs = ?addrs, "Auth Edge Service listening");
+
+    // Rate limits, circuit breakers and JWKS settings follow config reloads
+    let rate_limiter_metrics = RateLimiterMetrics::new(prometheus::default_registry())?;
+    let rate_limiter = Arc::new(
+        AdaptiveRateLimiter::new(config.rate_limit_config()).with_metrics(rate_limiter_metrics),
+    );
+    let reload_metrics = ConfigReloadMetrics::new(prometheus::default_registry())?;
+    let mut reloader = ConfigReloader::new(config.clone(), auth_edge_service.jwt_validator().clone())
+        .with_rate_limiter(rate_limiter.clone())
+        .with_keyed_rate_limiter(auth_edge_service.keyed_rate_limits().clone())
+        .with_metrics(reload_metrics);
+    for circuit_breaker in auth_edge_service.circuit_breakers() {
+        reloader = reloader.with_circuit_breaker(circuit_breaker.clone());
+    }
+    let reloader = Arc::new(reloader);
+    shutdown_coordinator.spawn("config-reload", reloader.clone().run());
+
+    // Operators inspect and control the instance over mTLS
+    let admin_service =
+        AdminServiceImpl::from_config(&config, auth_edge_service.clone(), rate_limiter.clone())
+            .map(|admin| AuthEdgeAdminServiceServer::new(admin.with_reloader(reloader)));
+    if admin_service.is_some() {
+        info!("Admin API enabled");
+    }
 
-    run_with_graceful_shutdown(server, shutdown_coordinator, shutdown_timeout).await;
+    // Load reduction follows CPU usage, the runtime queue and requests in flight
+    let in_flight = InFlightRequests::default();
+    // Both listeners share the requests handled at once and shed beyond the queue
+    let concurrency = ConcurrencyLimit::from_config(&config)
+        .with_metrics(ConcurrencyMetrics::new(prometheus::default_registry())?);
+    let load_sampler = LoadSampler::from_config(&config, in_flight.clone())
+        .with_limiter(rate_limiter.clone())
+        .with_keyed(auth_edge_service.keyed_rate_limits().clone());
+    shutdown_coordinator.spawn("load-sampler", load_sampler.run());
+    shutdown_coordinator.spawn(
+        "tenant-metrics",
+        auth_edge_service
+            .tenant_metrics()
+            .clone()
+            .run(Duration::from_secs(config.tenant_metrics_window_secs)),
+    );
+
+    // HTTP/REST gateway for consumers that cannot speak gRPC
+    if let Some(port) = config.http_gateway_port {
+        let gateway_addrs = config.listen_addrs(port)?;
+        let state = GatewayState::new(
+            auth_edge_service.clone(),
+            api_keys.clone(),
+            rate_limiter,
+        )
+        .with_in_flight(in_flight.clone())
+        .with_concurrency(concurrency.clone())
+        .with_log_level(log_level);
+        shutdown_coordinator.spawn("http-gateway", async move {
+            if let Err(e) = gateway::serve(&gateway_addrs, gateway::router(state)).await {
+                error!(error = %e, "HTTP gateway stopped");
+            }
+        });
+    }
+
+    // Lets grpcurl and gateways discover the API without the proto files
+    let (reflection, reflection_v1alpha) = if config.grpc_reflection_enabled {
+        info!("gRPC server reflection enabled");
+        let builder = || {
+            tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        };
+        (Some(builder().build_v1()?), Some(builder().build_v1alpha()?))
+    } else {
+        (None, None)
+    };
+
+    // Spans are flushed last, after every other subsystem cleaned up
+    shutdown_coordinator.register_hook(
+        "telemetry",
+        HookPriority::TELEMETRY,
+        TELEMETRY_FLUSH_TIMEOUT,
+        move || async move {
+            // The OTLP exporter flushes synchronously
+            let _ = tokio::task::spawn_blocking(move || telemetry.shutdown()).await;
+        },
+    );
 
-    // Cleanup OpenTelemetry
-    shutdown_telemetry();
+    // Build and run server with graceful shutdown
+    let grpc_metrics = GrpcMetrics::new("auth_edge", prometheus::default_registry())?;
+    let router = Server::builder()
+        .layer(rust_common::TraceContextLayer)
+        .layer(tonic::service::interceptor(extract_trace_context))
+        .layer(TracingLayer::new("auth-edge"))
+        .layer(rust_common::AuthSpanLayer)
+        .layer(GrpcMetricsLayer::new(grpc_metrics))
+        .layer(InFlightLayer::new(in_flight))
+        .layer(LoadShedLayer::new(concurrency.clone()))
+        .layer(ConcurrencyLimitLayer::new(concurrency))
+        .layer(ApiKeyLayer::new(api_keys))
+        .layer(RateLimitMetadataLayer)
+        .add_service(AuthEdgeServiceServer::from_arc(auth_edge_service))
+        .add_optional_service(admin_service)
+        .add_optional_service(reflection)
+        .add_optional_service(reflection_v1alpha);
+
+    // Terminating TLS here exposes client certificates to the handlers
+    let server_tls = match &workload_identity {
+        Some(identity) => Some(identity.server_tls()),
+        None => connection::server_config(&config)?.map(|server| {
+            // Certificates read from files follow rotations on disk
+            let tls = ServerTls::new(server);
+            shutdown_coordinator.spawn(
+                "tls-reload",
+                connection::reload_on_change(tls.clone(), config.clone()),
+            );
+            tls
+        }),
+    };
+    match server_tls {
+        Some(tls) => {
+            info!(
+                client_auth = workload_identity.is_some() || config.tls_client_ca_path.is_some(),
+                spiffe = workload_identity.is_some(),
+                "gRPC server TLS enabled"
+            );
+            let listeners = rust_common::listen::bind(&addrs)?;
+            let server = router.serve_with_incoming(connection::incoming(listeners, tls));
+            run_with_graceful_shutdown(server, shutdown_coordinator, shutdown_timeout).await;
+        }
+        None => {
+            if config.require_mtls {
+                warn!("REQUIRE_MTLS is set but neither TLS_CERT_PATH nor SPIFFE_ENDPOINT_SOCKET is; expecting TLS to be terminated by the mesh");
+            }
+            let listeners = rust_common::listen::bind(&addrs)?;
+            let server = router.serve_with_incoming(rust_common::listen::incoming(listeners));
+            run_with_graceful_shutdown(server, shutdown_coordinator, shutdown_timeout).await;
+        }
+    }
 
     info!("Auth Edge Service stopped");
 
@@ -0,0 +1,109 @@
+//! API Key Tower Layer
+//!
+//! Authenticates callers presenting an `x-api-key` header at the HTTP/2
+//! level, before the gRPC request is decoded. Requests without the header
+//! pass through unchanged and rely on mTLS instead.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use tonic::body::BoxBody;
+use tonic::codegen::http;
+use tower::{Layer, Service};
+use tracing::warn;
+
+use crate::api_key::{ApiKeyAuthenticator, API_KEY_HEADER};
+
+/// API key authentication layer for the tonic server
+#[derive(Clone, Default)]
+pub struct ApiKeyLayer {
+    authenticator: Option<Arc<ApiKeyAuthenticator>>,
+}
+
+impl ApiKeyLayer {
+    /// Creates a layer; `None` disables API key authentication
+    pub fn new(authenticator: Option<Arc<ApiKeyAuthenticator>>) -> Self {
+        Self { authenticator }
+    }
+}
+
+impl<S> Layer<S> for ApiKeyLayer {
+    type Service = ApiKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyService {
+            inner,
+            authenticator: self.authenticator.clone(),
+        }
+    }
+}
+
+/// API key authentication service wrapper
+#[derive(Clone)]
+pub struct ApiKeyService<S> {
+    inner: S,
+    authenticator: Option<Arc<ApiKeyAuthenticator>>,
+}
+
+impl<S, B> Service<http::Request<B>> for ApiKeyService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        // Take the ready service and leave a fresh clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let Some(authenticator) = self.authenticator.clone() else {
+            return Box::pin(inner.call(req));
+        };
+        let presented = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .map(|value| value.to_str().unwrap_or_default().to_string());
+        let Some(presented) = presented else {
+            return Box::pin(inner.call(req));
+        };
+
+        Box::pin(async move {
+            let rpc = rpc_name(req.uri().path()).to_string();
+            match authenticator.authenticate(&presented, &rpc).await {
+                Ok(identity) => {
+                    req.extensions_mut().insert(identity);
+                    inner.call(req).await
+                }
+                Err(err) => {
+                    warn!(rpc = %rpc, error = %err, "API key authentication failed");
+                    Ok(err.to_status().into_http())
+                }
+            }
+        })
+    }
+}
+
+/// Extracts the method name from a gRPC path like `/auth.v1.AuthEdgeService/ValidateToken`
+fn rpc_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_name_from_path() {
+        assert_eq!(rpc_name("/auth.v1.AuthEdgeService/ValidateToken"), "ValidateToken");
+        assert_eq!(rpc_name("ValidateToken"), "ValidateToken");
+    }
+}
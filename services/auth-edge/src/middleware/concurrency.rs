@@ -0,0 +1,399 @@
+//! Concurrency Limit and Load Shedding Tower Layers
+//!
+//! [`ConcurrencyLimitLayer`] lets at most `CONCURRENCY_LIMIT` requests reach
+//! the inner service at once; the rest wait for a free slot. Once
+//! `CONCURRENCY_MAX_QUEUE` requests are waiting, [`LoadShedLayer`] rejects new
+//! ones straight away with `RESOURCE_EXHAUSTED` and a retry-after instead of
+//! letting the queue grow. The gRPC server and the HTTP gateway share one
+//! [`ConcurrencyLimit`].
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tonic::body::BoxBody;
+use tonic::codegen::http;
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::middleware::tracing::CorrelationId;
+use crate::observability::ConcurrencyMetrics;
+
+/// Responses a shed request is answered with
+pub trait ShedResponse {
+    /// Rejection telling the caller to retry after `retry_after` seconds
+    fn shed(retry_after: u64) -> Self;
+}
+
+impl ShedResponse for http::Response<BoxBody> {
+    fn shed(retry_after: u64) -> Self {
+        let correlation_id = CorrelationId::current().map_or_else(Uuid::new_v4, |id| id.0);
+        AuthEdgeError::RateLimited { retry_after }
+            .to_status(correlation_id)
+            .into_http()
+    }
+}
+
+/// Slots shared by a [`ConcurrencyLimitLayer`] and its [`LoadShedLayer`]
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    /// `None` when unlimited
+    slots: Option<Arc<Semaphore>>,
+    queued: Arc<AtomicUsize>,
+    max_queue: usize,
+    retry_after: u64,
+    metrics: Option<ConcurrencyMetrics>,
+}
+
+impl ConcurrencyLimit {
+    /// Admits `max_in_flight` requests at once (0 is unlimited) with up to
+    /// `max_queue` more waiting
+    pub fn new(max_in_flight: usize, max_queue: usize) -> Self {
+        Self {
+            slots: (max_in_flight > 0).then(|| Arc::new(Semaphore::new(max_in_flight))),
+            queued: Arc::new(AtomicUsize::new(0)),
+            max_queue,
+            retry_after: 1,
+            metrics: None,
+        }
+    }
+
+    /// Builds the limit from `CONCURRENCY_*` and `LOAD_SHED_RETRY_AFTER`
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.concurrency_limit, config.concurrency_max_queue)
+            .with_retry_after(config.load_shed_retry_after_secs)
+    }
+
+    /// Tells shed callers to retry after `secs` seconds
+    pub fn with_retry_after(mut self, secs: u64) -> Self {
+        self.retry_after = secs;
+        self
+    }
+
+    /// Records queue depth and shed requests
+    pub fn with_metrics(mut self, metrics: ConcurrencyMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Requests waiting for a free slot
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Whether every slot is taken and the queue is full
+    pub fn is_saturated(&self) -> bool {
+        self.slots.as_ref().is_some_and(|slots| {
+            slots.available_permits() == 0 && self.queue_depth() >= self.max_queue
+        })
+    }
+
+    /// Waits for a free slot, held until the permit is dropped
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let slots = self.slots.clone()?;
+        if let Ok(permit) = slots.clone().try_acquire_owned() {
+            return Some(permit);
+        }
+        let _waiting = Waiting::new(self);
+        slots.acquire_owned().await.ok()
+    }
+
+    /// Counts a shed request and builds its rejection
+    fn shed<R: ShedResponse>(&self) -> R {
+        if let Some(metrics) = &self.metrics {
+            metrics.shed.inc();
+        }
+        R::shed(self.retry_after)
+    }
+}
+
+/// A request counted in the queue until it gets a slot or is dropped
+struct Waiting<'a> {
+    limit: &'a ConcurrencyLimit,
+}
+
+impl<'a> Waiting<'a> {
+    fn new(limit: &'a ConcurrencyLimit) -> Self {
+        limit.queued.fetch_add(1, Ordering::Relaxed);
+        if let Some(metrics) = &limit.metrics {
+            metrics.queue_depth.inc();
+        }
+        Self { limit }
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.limit.queued.fetch_sub(1, Ordering::Relaxed);
+        if let Some(metrics) = &self.limit.metrics {
+            metrics.queue_depth.dec();
+        }
+    }
+}
+
+/// Layer bounding the requests handled at once
+#[derive(Clone)]
+pub struct ConcurrencyLimitLayer {
+    limit: ConcurrencyLimit,
+}
+
+impl ConcurrencyLimitLayer {
+    /// Creates a layer taking slots from `limit`
+    pub fn new(limit: ConcurrencyLimit) -> Self {
+        Self { limit }
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyLimitService {
+            inner,
+            limit: self.limit.clone(),
+        }
+    }
+}
+
+/// Concurrency limit service wrapper
+#[derive(Clone)]
+pub struct ConcurrencyLimitService<S> {
+    inner: S,
+    limit: ConcurrencyLimit,
+}
+
+impl<S, Req> Service<Req> for ConcurrencyLimitService<S>
+where
+    S: Service<Req> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    Req: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let limit = self.limit.clone();
+        // Take the ready service and leave a fresh clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let _permit = limit.acquire().await;
+            inner.call(req).await
+        })
+    }
+}
+
+/// Layer rejecting requests while a [`ConcurrencyLimit`] is saturated
+#[derive(Clone)]
+pub struct LoadShedLayer {
+    limit: ConcurrencyLimit,
+}
+
+impl LoadShedLayer {
+    /// Creates a layer shedding while `limit` is saturated
+    pub fn new(limit: ConcurrencyLimit) -> Self {
+        Self { limit }
+    }
+}
+
+impl<S> Layer<S> for LoadShedLayer {
+    type Service = LoadShedService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LoadShedService {
+            inner,
+            limit: self.limit.clone(),
+        }
+    }
+}
+
+/// Load shedding service wrapper
+#[derive(Clone)]
+pub struct LoadShedService<S> {
+    inner: S,
+    limit: ConcurrencyLimit,
+}
+
+impl<S, Req> Service<Req> for LoadShedService<S>
+where
+    S: Service<Req>,
+    S::Response: ShedResponse + Send + 'static,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        if self.limit.is_saturated() {
+            let response = self.limit.shed();
+            return Box::pin(async move { Ok(response) });
+        }
+        Box::pin(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::workload::spiffe_workload_api_client::SpiffeWorkloadApiClient;
+    use crate::proto::workload::spiffe_workload_api_server::{
+        SpiffeWorkloadApi, SpiffeWorkloadApiServer,
+    };
+    use crate::proto::workload::{X509svidRequest, X509svidResponse};
+    use futures::Stream;
+    use prometheus::Registry;
+    use std::convert::Infallible;
+    use std::pin::Pin;
+    use std::time::Duration;
+    use tonic::{Request, Response, Status};
+    use tower::ServiceExt;
+
+    /// Retry-after of a shed request, `None` when it was served
+    type Shed = Option<u64>;
+
+    impl ShedResponse for Shed {
+        fn shed(retry_after: u64) -> Self {
+            Some(retry_after)
+        }
+    }
+
+    /// Layered service whose requests finish once `gate` gets a permit each
+    fn gated(
+        limit: ConcurrencyLimit,
+        gate: Arc<Semaphore>,
+    ) -> impl Service<
+        (),
+        Response = Shed,
+        Error = Infallible,
+        Future = BoxFuture<'static, Result<Shed, Infallible>>,
+    > + Clone {
+        let inner = tower::service_fn(move |()| {
+            let gate = gate.clone();
+            async move {
+                gate.acquire().await.unwrap().forget();
+                Ok::<_, Infallible>(None)
+            }
+        });
+        LoadShedLayer::new(limit.clone()).layer(ConcurrencyLimitLayer::new(limit).layer(inner))
+    }
+
+    type SvidStream = Pin<Box<dyn Stream<Item = Result<X509svidResponse, Status>> + Send>>;
+
+    /// gRPC service answering once `gate` gets a permit per request
+    struct Gated {
+        gate: Arc<Semaphore>,
+    }
+
+    #[tonic::async_trait]
+    impl SpiffeWorkloadApi for Gated {
+        type FetchX509SVIDStream = SvidStream;
+
+        async fn fetch_x509svid(
+            &self,
+            _request: Request<X509svidRequest>,
+        ) -> Result<Response<SvidStream>, Status> {
+            self.gate.acquire().await.unwrap().forget();
+            Ok(Response::new(Box::pin(futures::stream::empty())))
+        }
+    }
+
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    #[tokio::test]
+    async fn test_queues_then_sheds_when_saturated() {
+        let metrics = ConcurrencyMetrics::new(&Registry::new()).unwrap();
+        let limit = ConcurrencyLimit::new(1, 1)
+            .with_retry_after(5)
+            .with_metrics(metrics.clone());
+        let gate = Arc::new(Semaphore::new(0));
+        let service = gated(limit.clone(), gate.clone());
+
+        let first = tokio::spawn(service.clone().oneshot(()));
+        settle().await;
+        assert!(!limit.is_saturated());
+        let second = tokio::spawn(service.clone().oneshot(()));
+        settle().await;
+        assert_eq!(limit.queue_depth(), 1);
+        assert_eq!(metrics.queue_depth.get(), 1.0);
+        assert!(limit.is_saturated());
+
+        assert_eq!(service.clone().oneshot(()).await.unwrap(), Some(5));
+        assert_eq!(metrics.shed.get(), 1.0);
+
+        gate.add_permits(2);
+        assert_eq!(first.await.unwrap().unwrap(), None);
+        assert_eq!(second.await.unwrap().unwrap(), None);
+        assert_eq!(limit.queue_depth(), 0);
+        assert_eq!(metrics.queue_depth.get(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_never_saturates() {
+        let limit = ConcurrencyLimit::new(0, 0);
+        let gate = Arc::new(Semaphore::new(0));
+        let service = gated(limit.clone(), gate.clone());
+
+        let pending: Vec<_> = (0..3).map(|_| tokio::spawn(service.clone().oneshot(()))).collect();
+        settle().await;
+        assert!(!limit.is_saturated());
+        assert_eq!(limit.queue_depth(), 0);
+
+        gate.add_permits(3);
+        for request in pending {
+            assert_eq!(request.await.unwrap().unwrap(), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_sheds_requests_beyond_queue() {
+        let limit = ConcurrencyLimit::new(1, 1).with_retry_after(5);
+        let gate = Arc::new(Semaphore::new(0));
+        let listeners = rust_common::listen::bind(&["127.0.0.1:0".parse().unwrap()]).unwrap();
+        let addr = listeners[0].local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .layer(LoadShedLayer::new(limit.clone()))
+                .layer(ConcurrencyLimitLayer::new(limit.clone()))
+                .add_service(SpiffeWorkloadApiServer::new(Gated { gate: gate.clone() }))
+                .serve_with_incoming(rust_common::listen::incoming(listeners)),
+        );
+        let client = SpiffeWorkloadApiClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap();
+        let fetch = || {
+            let mut client = client.clone();
+            async move { client.fetch_x509svid(X509svidRequest {}).await.map(|_| ()) }
+        };
+
+        let first = tokio::spawn(fetch());
+        settle().await;
+        let second = tokio::spawn(fetch());
+        settle().await;
+        assert!(limit.is_saturated());
+
+        let status = fetch().await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        gate.add_permits(2);
+        first.await.unwrap().unwrap();
+        second.await.unwrap().unwrap();
+    }
+}
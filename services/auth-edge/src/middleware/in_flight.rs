@@ -0,0 +1,87 @@
+//! In-Flight Request Tower Layer
+//!
+//! Counts requests from arrival until their response is produced, for the
+//! in-flight signal of the system load fed to the adaptive rate limiter.
+
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::rate_limiter::InFlightRequests;
+
+/// Layer counting requests in [`InFlightRequests`]
+#[derive(Clone, Default)]
+pub struct InFlightLayer {
+    requests: InFlightRequests,
+}
+
+impl InFlightLayer {
+    /// Creates a layer counting into `requests`
+    pub fn new(requests: InFlightRequests) -> Self {
+        Self { requests }
+    }
+}
+
+impl<S> Layer<S> for InFlightLayer {
+    type Service = InFlightService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InFlightService {
+            inner,
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+/// In-flight counting service wrapper
+#[derive(Clone)]
+pub struct InFlightService<S> {
+    inner: S,
+    requests: InFlightRequests,
+}
+
+impl<S, Req> Service<Req> for InFlightService<S>
+where
+    S: Service<Req>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let guard = self.requests.start();
+        let response = self.inner.call(req);
+        Box::pin(async move {
+            let response = response.await;
+            drop(guard);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_counts_request_until_response() {
+        let requests = InFlightRequests::default();
+        let observed = requests.clone();
+        let service = InFlightLayer::new(requests.clone()).layer(tower::service_fn(
+            move |_: ()| {
+                let count = observed.count();
+                async move { Ok::<_, std::convert::Infallible>(count) }
+            },
+        ));
+
+        assert_eq!(service.oneshot(()).await.unwrap(), 1);
+        assert_eq!(requests.count(), 0);
+    }
+}
@@ -2,12 +2,25 @@
 //!
 //! Composable middleware layers for the auth edge service.
 
+pub mod api_key;
+pub mod concurrency;
+pub mod in_flight;
+pub mod rate_limit_metadata;
 pub mod rate_limiter;
 pub mod timeout;
+pub mod trace_context;
 pub mod tracing;
 pub mod stack;
 
-pub use rate_limiter::{RateLimiterLayer, RateLimiterService};
+pub use api_key::{ApiKeyLayer, ApiKeyService};
+pub use concurrency::{
+    ConcurrencyLimit, ConcurrencyLimitLayer, ConcurrencyLimitService, LoadShedLayer,
+    LoadShedService, ShedResponse,
+};
+pub use in_flight::{InFlightLayer, InFlightService};
+pub use rate_limit_metadata::{RateLimitMetadataLayer, RateLimitMetadataService, RateLimitSlot};
+pub use rate_limiter::{RateLimitHeaders, RateLimiterLayer, RateLimiterService};
 pub use timeout::TimeoutLayer;
-pub use tracing::TracingLayer;
+pub use trace_context::extract_trace_context;
+pub use tracing::{CorrelationId, TracingLayer, CORRELATION_ID_HEADER};
 pub use stack::build_service_stack;
@@ -0,0 +1,148 @@
+//! Rate Limit Metadata Tower Layer
+//!
+//! Gives each request a [`RateLimitSlot`] that handlers fill in as they
+//! check the caller's rate limits, and copies it into the response: the
+//! `ratelimit-*` entries of the binding limit and, when the caller nears a
+//! limit, the `x-quota-warning` level, i.e. gRPC response metadata.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use tonic::codegen::http::{self, HeaderValue};
+use tower::{Layer, Service};
+
+use super::rate_limiter::RateLimitHeaders;
+use crate::rate_limiter::QUOTA_WARNING_HEADER;
+
+/// Rate limit state set while a request is handled, read once it completes
+///
+/// Inserted into request extensions by [`RateLimitMetadataLayer`]; the
+/// highest warning level and the limit with the fewest remaining requests
+/// win.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitSlot {
+    level: Arc<AtomicU8>,
+    limits: Arc<Mutex<Option<RateLimitHeaders>>>,
+}
+
+impl RateLimitSlot {
+    /// Raises the warning to `level` percent
+    pub fn raise(&self, level: u8) {
+        self.level.fetch_max(level, Ordering::Relaxed);
+    }
+
+    /// Header value of the warning, e.g. `90%`, if one was raised
+    pub fn header_value(&self) -> Option<String> {
+        match self.level.load(Ordering::Relaxed) {
+            0 => None,
+            level => Some(format!("{level}%")),
+        }
+    }
+
+    /// Records a limit the request was checked against
+    pub fn set_limits(&self, headers: RateLimitHeaders) {
+        let mut limits = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+        let binding = limits.as_ref().is_none_or(|current| {
+            (headers.remaining, std::cmp::Reverse(headers.reset))
+                < (current.remaining, std::cmp::Reverse(current.reset))
+        });
+        if binding {
+            *limits = Some(headers);
+        }
+    }
+
+    /// The binding limit recorded, if any
+    pub fn limits(&self) -> Option<RateLimitHeaders> {
+        self.limits.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Rate limit metadata layer for the tonic server
+#[derive(Clone, Copy, Default)]
+pub struct RateLimitMetadataLayer;
+
+impl<S> Layer<S> for RateLimitMetadataLayer {
+    type Service = RateLimitMetadataService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitMetadataService { inner }
+    }
+}
+
+/// Rate limit metadata service wrapper
+#[derive(Clone)]
+pub struct RateLimitMetadataService<S> {
+    inner: S,
+}
+
+impl<S, B, R> Service<http::Request<B>> for RateLimitMetadataService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<R>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        let slot = RateLimitSlot::default();
+        req.extensions_mut().insert(slot.clone());
+        let response = self.inner.call(req);
+
+        Box::pin(async move {
+            let mut response = response.await?;
+            if let Some(limits) = slot.limits() {
+                limits.insert_headers(response.headers_mut());
+            }
+            if let Some(value) = slot.header_value().and_then(|v| HeaderValue::try_from(v).ok()) {
+                response.headers_mut().insert(QUOTA_WARNING_HEADER, value);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::rate_limiter::{RATELIMIT_LIMIT, RATELIMIT_REMAINING};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_slot_keeps_highest_level_and_binding_limit() {
+        let slot = RateLimitSlot::default();
+        assert_eq!(slot.header_value(), None);
+        assert_eq!(slot.limits(), None);
+
+        slot.raise(90);
+        slot.clone().raise(80);
+        assert_eq!(slot.header_value().as_deref(), Some("90%"));
+
+        slot.set_limits(RateLimitHeaders::new(40, 100, 30));
+        slot.set_limits(RateLimitHeaders::new(50, 60, 60));
+        slot.set_limits(RateLimitHeaders::new(40, 1000, 3600));
+        assert_eq!(slot.limits(), Some(RateLimitHeaders::new(40, 1000, 3600)));
+    }
+
+    #[tokio::test]
+    async fn test_slot_becomes_headers() {
+        let service = RateLimitMetadataLayer.layer(tower::service_fn(|req: http::Request<()>| async move {
+            if let Some(slot) = req.extensions().get::<RateLimitSlot>() {
+                slot.raise(90);
+                slot.set_limits(RateLimitHeaders::new(5, 50, 10));
+            }
+            Ok::<_, std::convert::Infallible>(http::Response::new(()))
+        }));
+
+        let response = service.oneshot(http::Request::new(())).await.unwrap();
+        assert_eq!(response.headers()[QUOTA_WARNING_HEADER], "90%");
+        assert_eq!(response.headers()[RATELIMIT_LIMIT], "50");
+        assert_eq!(response.headers()[RATELIMIT_REMAINING], "5");
+    }
+}
@@ -1,18 +1,21 @@
 //! Rate Limiter Tower Layer
 //!
-//! Implements rate limiting as a Tower Layer with HTTP headers.
+//! Implements rate limiting as a Tower Layer. Allowed responses carry
+//! `ratelimit-limit`, `ratelimit-remaining` and `ratelimit-reset` metadata
+//! built from [`RateLimitInfo`], so clients can back off before they are
+//! denied.
 
-use std::future::Future;
-use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::future::BoxFuture;
+use tonic::codegen::http::{self, HeaderMap, HeaderValue};
+use tonic::metadata::{MetadataMap, MetadataValue};
 use tower::{Layer, Service};
 
 use crate::error::AuthEdgeError;
-use crate::rate_limiter::{AdaptiveRateLimiter, RateLimitConfig, RateLimitDecision};
+use crate::rate_limiter::{AdaptiveRateLimiter, RateLimitConfig, RateLimitDecision, RateLimitInfo};
 
 /// Rate limiter layer for Tower
 pub struct RateLimiterLayer {
@@ -70,7 +73,7 @@ pub struct RateLimitedResponse<T> {
 impl<S, Req> Service<Req> for RateLimiterService<S>
 where
     S: Service<Req> + Clone + Send + 'static,
-    S::Response: Send + 'static,
+    S::Response: RateLimitMetadata + Send + 'static,
     S::Error: Into<AuthEdgeError> + Send + 'static,
     S::Future: Send + 'static,
     Req: Send + 'static,
@@ -97,22 +100,37 @@ where
                     
                     // Record outcome for adaptive rate limiting
                     limiter.record_outcome(client_id, result.is_ok()).await;
-                    
-                    result.map_err(Into::into)
+
+                    let mut response = result.map_err(Into::into)?;
+                    let info = limiter.get_limit_info(client_id).await;
+                    response.insert_rate_limit(&RateLimitHeaders::from_info(&info));
+                    Ok(response)
                 }
                 RateLimitDecision::Denied { retry_after } => {
-                    Err(AuthEdgeError::RateLimited { retry_after })
+                    Err(AuthEdgeError::RateLimited { retry_after: retry_after.as_secs().max(1) })
                 }
             }
         })
     }
 }
 
+/// `ratelimit-limit` header / metadata key
+pub const RATELIMIT_LIMIT: &str = "ratelimit-limit";
+/// `ratelimit-remaining` header / metadata key
+pub const RATELIMIT_REMAINING: &str = "ratelimit-remaining";
+/// `ratelimit-reset` header / metadata key, in seconds from now
+pub const RATELIMIT_RESET: &str = "ratelimit-reset";
+/// `ratelimit-policy` header of the HTTP gateway
+pub const RATELIMIT_POLICY: &str = "ratelimit-policy";
+
 /// Extracts rate limit headers from a response
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RateLimitHeaders {
     pub remaining: u32,
     pub limit: u32,
     pub reset: u64,
+    /// Seconds of the window `limit` applies to, if known
+    pub window: Option<u64>,
 }
 
 impl RateLimitHeaders {
@@ -122,6 +140,40 @@ impl RateLimitHeaders {
             remaining,
             limit,
             reset: reset_secs,
+            window: None,
+        }
+    }
+
+    /// Headers of a client's binding window, resetting in whole seconds
+    pub fn from_info(info: &RateLimitInfo) -> Self {
+        let reset = info.reset_at.saturating_duration_since(Instant::now());
+        Self {
+            remaining: info.remaining,
+            limit: info.limit,
+            reset: reset.as_secs() + u64::from(reset.subsec_nanos() > 0),
+            window: Some(info.window.as_secs()),
+        }
+    }
+
+    /// Returns the RateLimit-Policy header value, e.g. `100;w=60`
+    pub fn policy_header(&self) -> Option<String> {
+        self.window.map(|window| format!("{};w={}", self.limit, window))
+    }
+
+    /// Adds `ratelimit-*` entries to gRPC metadata
+    pub fn insert_metadata(&self, metadata: &mut MetadataMap) {
+        metadata.insert(RATELIMIT_LIMIT, MetadataValue::from(self.limit));
+        metadata.insert(RATELIMIT_REMAINING, MetadataValue::from(self.remaining));
+        metadata.insert(RATELIMIT_RESET, MetadataValue::from(self.reset));
+    }
+
+    /// Adds the draft `RateLimit-*` header fields, with the policy when known
+    pub fn insert_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(RATELIMIT_LIMIT, HeaderValue::from(self.limit));
+        headers.insert(RATELIMIT_REMAINING, HeaderValue::from(self.remaining));
+        headers.insert(RATELIMIT_RESET, HeaderValue::from(self.reset));
+        if let Some(policy) = self.policy_header().and_then(|p| HeaderValue::try_from(p).ok()) {
+            headers.insert(RATELIMIT_POLICY, policy);
         }
     }
 
@@ -140,3 +192,76 @@ impl RateLimitHeaders {
         self.reset.to_string()
     }
 }
+
+/// Responses that can carry rate limit headers or metadata
+pub trait RateLimitMetadata {
+    /// Attaches `headers` to the response
+    fn insert_rate_limit(&mut self, headers: &RateLimitHeaders);
+}
+
+impl<T> RateLimitMetadata for tonic::Response<T> {
+    fn insert_rate_limit(&mut self, headers: &RateLimitHeaders) {
+        headers.insert_metadata(self.metadata_mut());
+    }
+}
+
+impl<B> RateLimitMetadata for http::Response<B> {
+    fn insert_rate_limit(&mut self, headers: &RateLimitHeaders) {
+        headers.insert_headers(self.headers_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_headers_from_info() {
+        let info = RateLimitInfo {
+            limit: 100,
+            window: Duration::from_secs(60),
+            remaining: 40,
+            reset_at: Instant::now() + Duration::from_millis(29_500),
+            trust_level: crate::rate_limiter::TrustLevel::Normal,
+            system_load: 0.0,
+            banned_until: None,
+            utilization: 0.6,
+        };
+        let headers = RateLimitHeaders::from_info(&info);
+        assert_eq!((headers.limit, headers.remaining, headers.reset), (100, 40, 30));
+        assert_eq!(headers.policy_header().as_deref(), Some("100;w=60"));
+
+        let mut http_headers = HeaderMap::new();
+        headers.insert_headers(&mut http_headers);
+        assert_eq!(http_headers[RATELIMIT_REMAINING], "40");
+        assert_eq!(http_headers[RATELIMIT_POLICY], "100;w=60");
+
+        let mut metadata = MetadataMap::new();
+        headers.insert_metadata(&mut metadata);
+        assert_eq!(metadata.get(RATELIMIT_RESET).unwrap(), "30");
+        assert!(metadata.get(RATELIMIT_POLICY).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_layer_attaches_metadata_to_allowed_responses() {
+        let layer = RateLimiterLayer::new(RateLimitConfig {
+            base_limit: 4,
+            warmup_period: Duration::ZERO,
+            burst_capacity: 0,
+            trust_multiplier: 1.0,
+            ..Default::default()
+        });
+        let service = layer.layer(tower::service_fn(|_: ()| async {
+            Ok::<_, AuthEdgeError>(tonic::Response::new(()))
+        }));
+
+        // The first success makes the client Normal, so the full 4 apply
+        let response = service.clone().oneshot(()).await.unwrap();
+        assert_eq!(response.metadata().get(RATELIMIT_LIMIT).unwrap(), "4");
+        assert_eq!(response.metadata().get(RATELIMIT_REMAINING).unwrap(), "3");
+
+        let response = service.oneshot(()).await.unwrap();
+        assert_eq!(response.metadata().get(RATELIMIT_REMAINING).unwrap(), "2");
+    }
+}
@@ -10,7 +10,6 @@ use crate::config::Config;
 use crate::middleware::rate_limiter::RateLimiterLayer;
 use crate::middleware::timeout::TimeoutLayer;
 use crate::middleware::tracing::TracingLayer;
-use crate::rate_limiter::RateLimitConfig;
 
 /// Builds the complete service stack with all middleware layers
 /// 
@@ -38,7 +37,7 @@ where
     ServiceBuilder::new()
         .layer(TracingLayer::new("auth-edge-service"))
         .layer(TimeoutLayer::from_secs(config.timeout_secs()))
-        .layer(RateLimiterLayer::new(RateLimitConfig::default()))
+        .layer(RateLimiterLayer::new(config.rate_limit_config()))
         .service(inner)
 }
 
@@ -0,0 +1,155 @@
+//! W3C Trace Context Propagation
+//!
+//! [`extract_trace_context`] is a tonic interceptor storing the caller's
+//! `traceparent`/`tracestate` metadata in the request extensions. Handlers
+//! run the work that calls other services inside [`scope`], which records
+//! the trace on a tracing span and makes it the current trace of the task.
+//! Requests to the JWKS endpoint and the crypto-service then carry it onward
+//! with a fresh parent span ID, so one trace spans the caller, auth-edge and
+//! its dependencies.
+
+use std::future::Future;
+
+use auth_linkerd::TraceContext;
+use tonic::metadata::{AsciiMetadataValue, MetadataMap};
+use tonic::{Request, Status};
+use tracing::{debug, info_span, Instrument};
+
+use super::tracing::{CorrelationId, CORRELATION_ID_HEADER};
+
+/// Metadata carrying the W3C trace parent
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Metadata carrying vendor-specific trace state
+pub const TRACESTATE: &str = "tracestate";
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// Reads a valid trace context from gRPC metadata.
+pub fn from_metadata(metadata: &MetadataMap) -> Option<TraceContext> {
+    let traceparent = metadata.get(TRACEPARENT)?.to_str().ok()?;
+    let mut context = TraceContext::new(traceparent);
+    if !context.is_valid() {
+        debug!(traceparent, "Ignoring invalid traceparent");
+        return None;
+    }
+    if let Some(tracestate) = metadata.get(TRACESTATE).and_then(|v| v.to_str().ok()) {
+        context = context.with_tracestate(tracestate);
+    }
+    Some(context)
+}
+
+/// Interceptor storing the caller's trace context in the request extensions.
+///
+/// Requests without a valid `traceparent` pass through unchanged.
+pub fn extract_trace_context(mut request: Request<()>) -> Result<Request<()>, Status> {
+    if let Some(context) = from_metadata(request.metadata()) {
+        request.extensions_mut().insert(context);
+    }
+    Ok(request)
+}
+
+/// Runs `future` as part of the caller's trace, when there is one.
+pub async fn scope<F: Future>(context: Option<TraceContext>, future: F) -> F::Output {
+    let Some(context) = context else {
+        return future.await;
+    };
+    let span = info_span!(
+        "trace_context",
+        trace_id = context.trace_id().unwrap_or_default(),
+        parent_span_id = context.parent_id().unwrap_or_default(),
+    );
+    CURRENT.scope(context, future.instrument(span)).await
+}
+
+/// Trace context for an outgoing call: the current trace under a new span ID.
+pub fn outgoing() -> Option<TraceContext> {
+    CURRENT
+        .try_with(|context| context.propagate(&format!("{:016x}", rand::random::<u64>().max(1))))
+        .ok()
+}
+
+/// Adds the current trace to the metadata of an outgoing gRPC call.
+pub fn inject_metadata(metadata: &mut MetadataMap) {
+    let Some(context) = outgoing() else {
+        return;
+    };
+    if let Ok(value) = AsciiMetadataValue::try_from(context.traceparent.as_str()) {
+        metadata.insert(TRACEPARENT, value);
+    }
+    if let Some(value) = context
+        .tracestate
+        .as_deref()
+        .and_then(|tracestate| AsciiMetadataValue::try_from(tracestate).ok())
+    {
+        metadata.insert(TRACESTATE, value);
+    }
+}
+
+/// Wraps an outgoing gRPC message with the current trace and correlation ID
+/// in its metadata.
+pub fn traced<T>(message: T) -> Request<T> {
+    let mut request = Request::new(message);
+    inject_metadata(request.metadata_mut());
+    if let Some(value) = CorrelationId::current()
+        .and_then(|id| AsciiMetadataValue::try_from(id.to_string()).ok())
+    {
+        request.metadata_mut().insert(CORRELATION_ID_HEADER, value);
+    }
+    request
+}
+
+/// Adds a trace context to an outgoing HTTP request.
+pub fn inject_headers(
+    builder: reqwest::RequestBuilder,
+    context: Option<&TraceContext>,
+) -> reqwest::RequestBuilder {
+    let Some(context) = context else {
+        return builder;
+    };
+    let builder = builder.header(TRACEPARENT, &context.traceparent);
+    match &context.tracestate {
+        Some(tracestate) => builder.header(TRACESTATE, tracestate),
+        None => builder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    fn incoming(traceparent: &str) -> Request<()> {
+        let mut request = Request::new(());
+        request.metadata_mut().insert(TRACEPARENT, traceparent.parse().unwrap());
+        request.metadata_mut().insert(TRACESTATE, "vendor=value".parse().unwrap());
+        request
+    }
+
+    #[test]
+    fn test_interceptor_stores_valid_trace() {
+        let request = extract_trace_context(incoming(PARENT)).unwrap();
+        let context = request.extensions().get::<TraceContext>().unwrap();
+        assert_eq!(context.traceparent, PARENT);
+        assert_eq!(context.tracestate.as_deref(), Some("vendor=value"));
+
+        let request = extract_trace_context(incoming("00-not-a-trace-01")).unwrap();
+        assert!(request.extensions().get::<TraceContext>().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_outgoing_calls_continue_the_trace() {
+        assert!(traced(()).metadata().get(TRACEPARENT).is_none());
+
+        let context = from_metadata(incoming(PARENT).metadata());
+        let metadata = scope(context, async { traced(()).metadata().clone() }).await;
+        let propagated = from_metadata(&metadata).unwrap();
+        assert_eq!(propagated.trace_id(), Some("4bf92f3577b34da6a3ce929d0e0e4736"));
+        assert_ne!(propagated.parent_id(), Some("00f067aa0ba902b7"));
+        assert!(propagated.is_sampled());
+        assert_eq!(propagated.tracestate.as_deref(), Some("vendor=value"));
+    }
+}
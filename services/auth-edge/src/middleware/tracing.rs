@@ -1,19 +1,116 @@
 //! Tracing Tower Layer with OpenTelemetry Integration
 //!
-//! Provides W3C trace context propagation and structured error recording.
+//! Provides request correlation IDs and structured error recording.
 
-use std::future::Future;
-use std::pin::Pin;
+use std::fmt;
 use std::task::{Context, Poll};
 
 use futures::future::BoxFuture;
+use tonic::codegen::http::{self, HeaderValue};
+use tonic::metadata::MetadataValue;
 use tower::{Layer, Service};
-use tracing::{info_span, instrument, Instrument, Span};
+use tracing::{debug, info_span, Instrument};
 use uuid::Uuid;
 
-use crate::error::AuthEdgeError;
+
+/// Metadata carrying the correlation ID of a request and its response
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+tokio::task_local! {
+    static CURRENT: CorrelationId;
+}
+
+/// Correlation ID of a request, stored in its extensions by [`TracingLayer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CorrelationId(pub Uuid);
+
+impl CorrelationId {
+    /// Honors a caller's `x-correlation-id` when it is a UUID, else
+    /// generates one.
+    pub fn from_header(value: Option<&str>) -> Self {
+        match value.map(Uuid::parse_str) {
+            Some(Ok(id)) => Self(id),
+            Some(Err(_)) => {
+                debug!(correlation_id = ?value, "Ignoring correlation ID that is not a UUID");
+                Self(Uuid::new_v4())
+            }
+            None => Self(Uuid::new_v4()),
+        }
+    }
+
+    /// Correlation ID of the request being handled by this task, if any
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|id| *id).ok()
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Requests whose correlation ID the tracing layer reads and stores
+pub trait CorrelatedRequest {
+    /// The caller's `x-correlation-id`, if sent
+    fn correlation_header(&self) -> Option<&str>;
+
+    /// Stores the request's correlation ID in its extensions
+    fn insert_correlation_id(&mut self, id: CorrelationId);
+}
+
+impl<T> CorrelatedRequest for tonic::Request<T> {
+    fn correlation_header(&self) -> Option<&str> {
+        self.metadata()
+            .get(CORRELATION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    fn insert_correlation_id(&mut self, id: CorrelationId) {
+        self.extensions_mut().insert(id);
+    }
+}
+
+impl<B> CorrelatedRequest for http::Request<B> {
+    fn correlation_header(&self) -> Option<&str> {
+        self.headers()
+            .get(CORRELATION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    fn insert_correlation_id(&mut self, id: CorrelationId) {
+        self.extensions_mut().insert(id);
+    }
+}
+
+/// Responses echoing the correlation ID of their request
+pub trait CorrelatedResponse {
+    /// Sets `x-correlation-id`
+    fn set_correlation_id(&mut self, id: CorrelationId);
+}
+
+impl<T> CorrelatedResponse for tonic::Response<T> {
+    fn set_correlation_id(&mut self, id: CorrelationId) {
+        if let Ok(value) = MetadataValue::try_from(id.to_string()) {
+            self.metadata_mut().insert(CORRELATION_ID_HEADER, value);
+        }
+    }
+}
+
+impl<B> CorrelatedResponse for http::Response<B> {
+    fn set_correlation_id(&mut self, id: CorrelationId) {
+        if let Ok(value) = HeaderValue::try_from(id.to_string()) {
+            self.headers_mut().insert(CORRELATION_ID_HEADER, value);
+        }
+    }
+}
 
 /// Tracing layer for Tower with OpenTelemetry integration
+///
+/// Assigns each request one [`CorrelationId`]: it is stored in the request
+/// extensions, recorded on the request span, current for the task handling
+/// the request, and returned in the response's `x-correlation-id`.
+#[derive(Clone)]
 pub struct TracingLayer {
     service_name: String,
 }
@@ -56,23 +153,26 @@ impl<S: Clone> Clone for TracingService<S> {
 impl<S, Req> Service<Req> for TracingService<S>
 where
     S: Service<Req> + Clone + Send + 'static,
-    S::Response: Send + 'static,
-    S::Error: Into<AuthEdgeError> + std::fmt::Debug + Send + 'static,
+    S::Response: CorrelatedResponse + Send + 'static,
+    S::Error: std::fmt::Debug + Send + 'static,
     S::Future: Send + 'static,
-    Req: Send + 'static,
+    Req: CorrelatedRequest + Send + 'static,
 {
     type Response = S::Response;
-    type Error = AuthEdgeError;
+    type Error = S::Error;
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.inner.poll_ready(cx).map_err(Into::into)
+        self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: Req) -> Self::Future {
-        let correlation_id = Uuid::new_v4();
+    fn call(&mut self, mut req: Req) -> Self::Future {
+        let correlation_id = CorrelationId::from_header(req.correlation_header());
+        req.insert_correlation_id(correlation_id);
         let service_name = self.service_name.clone();
-        let mut inner = self.inner.clone();
+        // Take the ready service and leave a fresh clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
 
         let span = info_span!(
             "request",
@@ -81,12 +181,14 @@ where
             otel.kind = "server"
         );
 
-        Box::pin(
+        Box::pin(CURRENT.scope(
+            correlation_id,
             async move {
-                let result = inner.call(req).await;
+                let mut result = inner.call(req).await;
 
-                match &result {
-                    Ok(_) => {
+                match &mut result {
+                    Ok(response) => {
+                        response.set_correlation_id(correlation_id);
                         tracing::info!(
                             correlation_id = %correlation_id,
                             "Request completed successfully"
@@ -102,10 +204,10 @@ where
                     }
                 }
 
-                result.map_err(Into::into)
+                result
             }
             .instrument(span),
-        )
+        ))
     }
 }
 
@@ -140,3 +242,49 @@ impl ErrorEventAttributes {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+
+    /// Responds with the correlation ID seen by the handler
+    async fn call(header: Option<&str>) -> http::Response<Option<CorrelationId>> {
+        let service = TracingLayer::new("test").layer(tower::service_fn(
+            |req: http::Request<()>| async move {
+                let stored = req.extensions().get::<CorrelationId>().copied();
+                assert_eq!(stored, CorrelationId::current());
+                Ok::<_, Infallible>(http::Response::new(stored))
+            },
+        ));
+        let mut req = http::Request::new(());
+        if let Some(header) = header {
+            req.headers_mut().insert(CORRELATION_ID_HEADER, header.parse().unwrap());
+        }
+        service.oneshot(req).await.unwrap()
+    }
+
+    fn echoed(response: &http::Response<Option<CorrelationId>>) -> String {
+        response.headers()[CORRELATION_ID_HEADER].to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_honors_caller_correlation_id() {
+        let id = Uuid::new_v4();
+        let response = call(Some(&id.to_string())).await;
+        assert_eq!(*response.body(), Some(CorrelationId(id)));
+        assert_eq!(echoed(&response), id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_generates_one_correlation_id_otherwise() {
+        for header in [None, Some("req-42")] {
+            let response = call(header).await;
+            let id = response.body().expect("correlation ID stored");
+            assert_ne!(id.0, Uuid::nil());
+            assert_eq!(echoed(&response), id.to_string());
+        }
+        assert_eq!(CorrelationId::current(), None);
+    }
+}
@@ -0,0 +1,155 @@
+//! Certificate-Bound Access Tokens (RFC 8705)
+//!
+//! A token whose `cnf` claim carries `x5t#S256` may only be used together
+//! with the client certificate of that SHA-256 thumbprint. The certificate
+//! is the caller's own mTLS certificate, or the one forwarded in
+//! `client_certificate_pem` by a proxy listed in `CERTIFICATE_FORWARDERS`.
+
+use std::io::Cursor;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::error::AuthEdgeError;
+use crate::jwt::Claims;
+
+/// Confirmation claim (RFC 7800)
+pub const CONFIRMATION_CLAIM: &str = "cnf";
+
+/// Confirmation member holding the certificate thumbprint
+pub const X5T_S256: &str = "x5t#S256";
+
+/// Binding type reported in `TokenBinding.type`
+pub const MTLS_BINDING: &str = "mtls";
+
+/// Base64url-encoded SHA-256 thumbprint of a DER certificate
+pub fn certificate_thumbprint(certificate_der: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(certificate_der))
+}
+
+/// Decodes the first certificate of a PEM document
+pub fn certificate_der_from_pem(certificate_pem: &str) -> Result<Vec<u8>, AuthEdgeError> {
+    rustls_pemfile::certs(&mut Cursor::new(certificate_pem.as_bytes()))
+        .next()
+        .ok_or_else(|| AuthEdgeError::CertificateError {
+            reason: "no PEM certificate found".to_string(),
+        })?
+        .map(|der| der.to_vec())
+        .map_err(|e| AuthEdgeError::CertificateError {
+            reason: format!("invalid PEM certificate: {e}"),
+        })
+}
+
+/// Returns the `cnf.x5t#S256` thumbprint the token is bound to, if any
+pub fn bound_thumbprint(claims: &Claims) -> Result<Option<&str>, AuthEdgeError> {
+    let Some(confirmation) = claims.custom.get(CONFIRMATION_CLAIM) else {
+        return Ok(None);
+    };
+    match confirmation.get(X5T_S256) {
+        None => Ok(None),
+        Some(thumbprint) => thumbprint.as_str().map(Some).ok_or(AuthEdgeError::TokenMalformed {
+            reason: format!("{CONFIRMATION_CLAIM}.{X5T_S256} must be a string"),
+        }),
+    }
+}
+
+/// Checks a certificate-bound token against the presented certificate
+///
+/// Returns the verified thumbprint for bound tokens and `None` for unbound
+/// ones. A bound token presented without a certificate, or with a different
+/// one, is rejected.
+pub fn verify_certificate_binding(
+    claims: &Claims,
+    certificate_der: Option<&[u8]>,
+) -> Result<Option<String>, AuthEdgeError> {
+    let Some(expected) = bound_thumbprint(claims)? else {
+        return Ok(None);
+    };
+    let certificate_der = certificate_der.ok_or(AuthEdgeError::TokenBindingMismatch)?;
+
+    let presented = certificate_thumbprint(certificate_der);
+    if bool::from(presented.as_bytes().ct_eq(expected.as_bytes())) {
+        Ok(Some(presented))
+    } else {
+        Err(AuthEdgeError::TokenBindingMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const CERTIFICATE_DER: &[u8] = b"test certificate der";
+
+    fn claims(cnf: Option<serde_json::Value>) -> Claims {
+        let mut custom = HashMap::new();
+        if let Some(cnf) = cnf {
+            custom.insert(CONFIRMATION_CLAIM.to_string(), cnf);
+        }
+        Claims {
+            iss: "https://auth.example.com".to_string(),
+            sub: "client-1".to_string(),
+            aud: vec!["api".to_string()],
+            exp: chrono::Utc::now().timestamp() + 300,
+            iat: chrono::Utc::now().timestamp(),
+            nbf: None,
+            jti: "jti-1".to_string(),
+            session_id: None,
+            scopes: None,
+            custom,
+        }
+    }
+
+    #[test]
+    fn test_thumbprint_is_base64url_sha256() {
+        // RFC 8705 thumbprints are unpadded base64url of 32 bytes
+        let thumbprint = certificate_thumbprint(CERTIFICATE_DER);
+        assert_eq!(thumbprint.len(), 43);
+        assert!(!thumbprint.contains(['=', '+', '/']));
+    }
+
+    #[test]
+    fn test_bound_token_requires_matching_certificate() {
+        let bound = claims(Some(serde_json::json!({
+            X5T_S256: certificate_thumbprint(CERTIFICATE_DER),
+        })));
+
+        assert_eq!(
+            verify_certificate_binding(&bound, Some(CERTIFICATE_DER)).unwrap(),
+            Some(certificate_thumbprint(CERTIFICATE_DER))
+        );
+        assert!(matches!(
+            verify_certificate_binding(&bound, Some(b"other certificate")),
+            Err(AuthEdgeError::TokenBindingMismatch)
+        ));
+        assert!(matches!(
+            verify_certificate_binding(&bound, None),
+            Err(AuthEdgeError::TokenBindingMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_unbound_and_malformed_confirmation() {
+        assert_eq!(verify_certificate_binding(&claims(None), None).unwrap(), None);
+        assert_eq!(
+            verify_certificate_binding(&claims(Some(serde_json::json!({"jkt": "abc"}))), None)
+                .unwrap(),
+            None
+        );
+        assert!(matches!(
+            verify_certificate_binding(&claims(Some(serde_json::json!({X5T_S256: 42}))), None),
+            Err(AuthEdgeError::TokenMalformed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_pem_rejected() {
+        assert!(matches!(
+            certificate_der_from_pem("not a certificate"),
+            Err(AuthEdgeError::CertificateError { .. })
+        ));
+    }
+}
@@ -0,0 +1,734 @@
+//! TLS Connection Identity
+//!
+//! Terminates TLS for the gRPC server with rustls and exposes the client
+//! certificate chain of the handshake as tonic connect info. Handlers read
+//! the caller's certificate from the connection a request arrived on instead
+//! of trusting a PEM pasted into the request. The server configuration sits
+//! behind a [`ServerTls`] handle so rotated certificates apply to new
+//! connections without restarting the listener; certificates read from files
+//! are reloaded when the files change. With a restrictive SPIFFE
+//! policy, client certificates whose SPIFFE ID is not allowed fail the
+//! handshake.
+
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use arc_swap::ArcSwap;
+use futures::Stream;
+use rustls::crypto::CryptoProvider;
+use rustls::client::danger::HandshakeSignatureValid;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, UnixTime};
+use rustls::server::WebPkiClientVerifier;
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::{CertificateError, DigitallySignedStruct, DistinguishedName, RootCertStore, ServerConfig, SignatureScheme};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+use tonic::Request;
+use tonic::transport::server::Connected;
+use tracing::{debug, info, warn};
+
+use super::spiffe::SpiffeValidator;
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+
+/// ALPN protocol of gRPC over HTTP/2
+const ALPN_H2: &[u8] = b"h2";
+
+/// Completed handshakes buffered for the server
+const HANDSHAKE_BACKLOG: usize = 128;
+
+/// Time a client has to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed `accept`, e.g. when out of file descriptors
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Client certificates presented on a TLS connection
+#[derive(Debug, Clone)]
+pub struct PeerCertificates {
+    remote_addr: Option<SocketAddr>,
+    certificates: Arc<[CertificateDer<'static>]>,
+}
+
+impl PeerCertificates {
+    /// Creates connect info from a handshake's certificate chain, leaf first
+    pub fn new(
+        remote_addr: Option<SocketAddr>,
+        certificates: Vec<CertificateDer<'static>>,
+    ) -> Self {
+        Self {
+            remote_addr,
+            certificates: certificates.into(),
+        }
+    }
+
+    /// Returns the peer certificates of the connection a request arrived on
+    pub fn of<T>(request: &Request<T>) -> Option<&Self> {
+        request.extensions().get::<Self>()
+    }
+
+    /// Remote address of the connection
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// DER of the client's leaf certificate, if it presented one
+    pub fn leaf(&self) -> Option<&[u8]> {
+        self.certificates.first().map(|cert| cert.as_ref())
+    }
+
+    /// Client certificate chain, leaf first
+    pub fn chain(&self) -> &[CertificateDer<'static>] {
+        &self.certificates
+    }
+}
+
+/// Server side of a TLS connection, carrying its peer certificates
+pub struct TlsConnection {
+    stream: TlsStream<TcpStream>,
+    peer: PeerCertificates,
+}
+
+impl TlsConnection {
+    fn new(stream: TlsStream<TcpStream>) -> Self {
+        let (tcp, session) = stream.get_ref();
+        let certificates = session
+            .peer_certificates()
+            .map(|chain| chain.iter().map(|cert| cert.clone().into_owned()).collect())
+            .unwrap_or_default();
+        let peer = PeerCertificates::new(tcp.peer_addr().ok(), certificates);
+        Self { stream, peer }
+    }
+}
+
+impl Connected for TlsConnection {
+    type ConnectInfo = PeerCertificates;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.peer.clone()
+    }
+}
+
+impl AsyncRead for TlsConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsConnection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+}
+
+/// Server TLS configuration that can be replaced while serving
+///
+/// Each handshake uses the configuration current when it starts; established
+/// connections keep the one they were accepted with.
+#[derive(Clone)]
+pub struct ServerTls(Arc<ArcSwap<ServerConfig>>);
+
+impl ServerTls {
+    /// Wraps the initial configuration
+    pub fn new(config: Arc<ServerConfig>) -> Self {
+        Self(Arc::new(ArcSwap::new(config)))
+    }
+
+    /// Configuration used for the next handshake
+    pub fn current(&self) -> Arc<ServerConfig> {
+        self.0.load_full()
+    }
+
+    /// Replaces the configuration for subsequent handshakes
+    pub fn replace(&self, config: Arc<ServerConfig>) {
+        self.0.store(config);
+    }
+}
+
+/// Client certificate requirements of the TLS server
+#[derive(Clone, Default)]
+pub struct ClientAuth {
+    /// Refuse connections without a client certificate
+    pub required: bool,
+    /// Refuse client certificates whose SPIFFE ID the validator rejects
+    pub spiffe: Option<Arc<SpiffeValidator>>,
+}
+
+impl ClientAuth {
+    /// Client authentication from `REQUIRE_MTLS` and the SPIFFE policy
+    ///
+    /// SPIFFE IDs are only checked during the handshake when the policy
+    /// restricts workloads beyond the trust-domain allowlist.
+    pub fn from_config(config: &Config) -> Self {
+        let validator = SpiffeValidator::new(config.allowed_spiffe_domains.clone())
+            .with_policy(config.spiffe_policy());
+        Self {
+            required: config.require_mtls,
+            spiffe: validator.has_restrictive_policy().then(|| Arc::new(validator)),
+        }
+    }
+}
+
+/// Builds the rustls server configuration from the `TLS_*` settings
+///
+/// Returns `None` when TLS is terminated elsewhere, e.g. by a mesh sidecar.
+pub fn server_config(config: &Config) -> Result<Option<Arc<ServerConfig>>, AuthEdgeError> {
+    let (Some(cert_path), Some(key_path)) = (&config.tls_cert_path, &config.tls_key_path) else {
+        return Ok(None);
+    };
+    let client_ca = config
+        .tls_client_ca_path
+        .as_deref()
+        .map(read_pem)
+        .transpose()?;
+
+    server_config_from_pem(
+        &read_pem(cert_path)?,
+        &read_pem(key_path)?,
+        client_ca.as_deref(),
+        &ClientAuth::from_config(config),
+    )
+    .map(Some)
+}
+
+/// Rebuilds the server configuration whenever a `TLS_*` file changes
+///
+/// Files are polled every `TLS_RELOAD_INTERVAL` seconds. A configuration that
+/// fails to build, e.g. while a certificate and its key are replaced one after
+/// the other, is logged and retried on the next change; the previous one
+/// stays in use meanwhile.
+pub async fn reload_on_change(tls: ServerTls, config: Config) {
+    if config.tls_reload_interval_secs == 0 {
+        return;
+    }
+    let interval = Duration::from_secs(config.tls_reload_interval_secs);
+    let mut modified = tls_files_modified(&config);
+    loop {
+        tokio::time::sleep(interval).await;
+        let latest = tls_files_modified(&config);
+        if latest == modified {
+            continue;
+        }
+        modified = latest;
+
+        match server_config(&config) {
+            Ok(Some(server)) => {
+                tls.replace(server);
+                info!("Reloaded server TLS certificates");
+            }
+            Ok(None) => {}
+            Err(e) => warn!(error = %e, "Failed to reload server TLS certificates"),
+        }
+    }
+}
+
+/// Modification times of the `TLS_*` files
+fn tls_files_modified(config: &Config) -> Vec<Option<SystemTime>> {
+    [&config.tls_cert_path, &config.tls_key_path, &config.tls_client_ca_path]
+        .into_iter()
+        .flatten()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Builds the rustls server configuration from PEM documents
+///
+/// Client certificates are verified against `client_ca_pem` and, when
+/// `client_auth` requires one, connections without one are refused.
+pub fn server_config_from_pem(
+    cert_pem: &[u8],
+    key_pem: &[u8],
+    client_ca_pem: Option<&[u8]>,
+    client_auth: &ClientAuth,
+) -> Result<Arc<ServerConfig>, AuthEdgeError> {
+    let client_roots = client_ca_pem.map(parse_certificates).transpose()?;
+    server_config_from_der(
+        parse_certificates(cert_pem)?,
+        parse_private_key(key_pem)?,
+        client_roots,
+        client_auth,
+    )
+}
+
+/// Builds the rustls server configuration from DER certificates and key
+///
+/// Client certificates are verified against `client_roots`, e.g. the trust
+/// bundle delivered with an X.509-SVID.
+pub fn server_config_from_der(
+    certificates: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    client_roots: Option<Vec<CertificateDer<'static>>>,
+    client_auth: &ClientAuth,
+) -> Result<Arc<ServerConfig>, AuthEdgeError> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = match client_roots {
+        Some(roots) => client_verifier(roots, client_auth, provider.clone())?,
+        None => WebPkiClientVerifier::no_client_auth(),
+    };
+
+    let mut server = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(tls_error)?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certificates, key)
+        .map_err(tls_error)?;
+    server.alpn_protocols = vec![ALPN_H2.to_vec()];
+    Ok(Arc::new(server))
+}
+
+/// Accepts TLS connections on every listener for `Server::serve_with_incoming`
+///
+/// Handshakes run concurrently so a slow client cannot stall accepting;
+/// failed or timed-out handshakes are logged and dropped.
+pub fn incoming(
+    listeners: Vec<TcpListener>,
+    tls: ServerTls,
+) -> impl Stream<Item = Result<TlsConnection, io::Error>> {
+    let (tx, rx) = mpsc::channel(HANDSHAKE_BACKLOG);
+    for listener in listeners {
+        tokio::spawn(accept_loop(listener, tls.clone(), tx.clone()));
+    }
+
+    futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|connection| (connection, rx))
+    })
+}
+
+/// Accepts connections on one listener until the incoming stream is dropped
+async fn accept_loop(
+    listener: TcpListener,
+    tls: ServerTls,
+    tx: mpsc::Sender<Result<TlsConnection, io::Error>>,
+) {
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = tx.closed() => break,
+        };
+        let (tcp, remote_addr) = match accepted {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!(error = %e, "Failed to accept connection");
+                tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                continue;
+            }
+        };
+
+        let acceptor = TlsAcceptor::from(tls.current());
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(tcp)).await {
+                Ok(Ok(stream)) => {
+                    let _ = tx.send(Ok(TlsConnection::new(stream))).await;
+                }
+                Ok(Err(e)) => debug!(%remote_addr, error = %e, "TLS handshake failed"),
+                Err(_) => debug!(%remote_addr, "TLS handshake timed out"),
+            }
+        });
+    }
+}
+
+fn client_verifier(
+    client_roots: Vec<CertificateDer<'static>>,
+    client_auth: &ClientAuth,
+    provider: Arc<CryptoProvider>,
+) -> Result<Arc<dyn ClientCertVerifier>, AuthEdgeError> {
+    let mut roots = RootCertStore::empty();
+    for certificate in client_roots {
+        roots.add(certificate).map_err(tls_error)?;
+    }
+
+    let builder = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+    let builder = if client_auth.required {
+        builder
+    } else {
+        builder.allow_unauthenticated()
+    };
+    let webpki = builder.build().map_err(tls_error)?;
+    Ok(match &client_auth.spiffe {
+        Some(validator) => Arc::new(SpiffeClientVerifier {
+            inner: webpki,
+            validator: validator.clone(),
+        }),
+        None => webpki,
+    })
+}
+
+/// Checks the SPIFFE ID of chain-verified client certificates
+struct SpiffeClientVerifier {
+    inner: Arc<dyn ClientCertVerifier>,
+    validator: Arc<SpiffeValidator>,
+}
+
+impl std::fmt::Debug for SpiffeClientVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpiffeClientVerifier")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientCertVerifier for SpiffeClientVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.inner.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        self.inner.client_auth_mandatory()
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        self.inner.root_hint_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let verified = self.inner.verify_client_cert(end_entity, intermediates, now)?;
+        if let Err(e) = self.validator.extract_from_der(end_entity) {
+            debug!(error = %e, "Client certificate rejected by SPIFFE policy");
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ));
+        }
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, AuthEdgeError> {
+    std::fs::read(path).map_err(|e| AuthEdgeError::CertificateError {
+        reason: format!("failed to read {path}: {e}"),
+    })
+}
+
+fn parse_certificates(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>, AuthEdgeError> {
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(pem))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(tls_error)?;
+    if certificates.is_empty() {
+        return Err(AuthEdgeError::CertificateError {
+            reason: "no PEM certificate found".to_string(),
+        });
+    }
+    Ok(certificates)
+}
+
+fn parse_private_key(pem: &[u8]) -> Result<PrivateKeyDer<'static>, AuthEdgeError> {
+    rustls_pemfile::private_key(&mut BufReader::new(pem))
+        .map_err(tls_error)?
+        .ok_or_else(|| AuthEdgeError::CertificateError {
+            reason: "no PEM private key found".to_string(),
+        })
+}
+
+fn tls_error(e: impl std::fmt::Display) -> AuthEdgeError {
+    AuthEdgeError::CertificateError {
+        reason: format!("invalid TLS configuration: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mtls::{OwnedSpiffeId, SpiffePolicy};
+    use futures::StreamExt;
+    use rcgen::{
+        BasicConstraints, Certificate, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+        SanType,
+    };
+    use rustls::ClientConfig;
+    use rustls::pki_types::{PrivatePkcs8KeyDer, ServerName};
+    use tokio_rustls::TlsConnector;
+
+    const WORKLOAD: &str = "spiffe://example.org/ns/orders/sa/api";
+
+    struct Pki {
+        ca: Certificate,
+        server: (Certificate, KeyPair),
+        client: (Certificate, KeyPair),
+    }
+
+    fn pki() -> Pki {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let server_key = KeyPair::generate().unwrap();
+        let mut server_params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        server_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+        let server = server_params.signed_by(&server_key, &ca, &ca_key).unwrap();
+
+        let client_key = KeyPair::generate().unwrap();
+        let mut client_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        client_params.subject_alt_names = vec![SanType::URI(WORKLOAD.try_into().unwrap())];
+        client_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        let client = client_params.signed_by(&client_key, &ca, &ca_key).unwrap();
+
+        Pki {
+            ca,
+            server: (server, server_key),
+            client: (client, client_key),
+        }
+    }
+
+    fn server(pki: &Pki) -> Arc<ServerConfig> {
+        server_with(pki, ClientAuth { required: true, spiffe: None })
+    }
+
+    fn server_with(pki: &Pki, client_auth: ClientAuth) -> Arc<ServerConfig> {
+        server_config_from_pem(
+            pki.server.0.pem().as_bytes(),
+            pki.server.1.serialize_pem().as_bytes(),
+            Some(pki.ca.pem().as_bytes()),
+            &client_auth,
+        )
+        .unwrap()
+    }
+
+    fn connector(pki: &Pki, with_certificate: bool) -> TlsConnector {
+        let mut roots = RootCertStore::empty();
+        roots.add(pki.ca.der().clone()).unwrap();
+        let builder =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots);
+        let config = if with_certificate {
+            let key = PrivatePkcs8KeyDer::from(pki.client.1.serialize_der());
+            builder
+                .with_client_auth_cert(vec![pki.client.0.der().clone()], key.into())
+                .unwrap()
+        } else {
+            builder.with_no_client_auth()
+        };
+        TlsConnector::from(Arc::new(config))
+    }
+
+    #[tokio::test]
+    async fn test_connect_info_carries_client_certificate() {
+        let pki = pki();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut connections = Box::pin(incoming(vec![listener], ServerTls::new(server(&pki))));
+
+        let connector = connector(&pki, true);
+        let client = tokio::spawn(async move {
+            let tcp = TcpStream::connect(addr).await.unwrap();
+            connector
+                .connect(ServerName::try_from("localhost").unwrap(), tcp)
+                .await
+                .unwrap()
+        });
+
+        let connection = connections.next().await.unwrap().unwrap();
+        let _client = client.await.unwrap();
+        let peer = connection.connect_info();
+
+        assert_eq!(peer.leaf(), Some(pki.client.0.der().as_ref()));
+        assert_eq!(peer.chain().len(), 1);
+        assert_eq!(peer.remote_addr().unwrap().ip(), addr.ip());
+
+        let validator = SpiffeValidator::new(vec!["example.org".to_string()]);
+        assert_eq!(
+            validator.extract_from_connection(&peer).unwrap(),
+            OwnedSpiffeId::parse(WORKLOAD).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_without_certificate_refused_when_required() {
+        let pki = pki();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut connections = Box::pin(incoming(vec![listener], ServerTls::new(server(&pki))));
+
+        let connector = connector(&pki, false);
+        tokio::spawn(async move {
+            let tcp = TcpStream::connect(addr).await.unwrap();
+            let _ = connector
+                .connect(ServerName::try_from("localhost").unwrap(), tcp)
+                .await;
+        });
+
+        let next = tokio::time::timeout(Duration::from_millis(500), connections.next()).await;
+        assert!(
+            next.is_err(),
+            "handshake without a client certificate must not be accepted"
+        );
+    }
+
+    async fn handshake(config: Arc<ServerConfig>, connector: TlsConnector) -> Option<TlsConnection> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut connections = Box::pin(incoming(vec![listener], ServerTls::new(config)));
+        tokio::spawn(async move {
+            let tcp = TcpStream::connect(addr).await.unwrap();
+            let _ = connector
+                .connect(ServerName::try_from("localhost").unwrap(), tcp)
+                .await;
+        });
+        tokio::time::timeout(Duration::from_millis(500), connections.next())
+            .await
+            .ok()
+            .flatten()
+            .map(Result::unwrap)
+    }
+
+    #[tokio::test]
+    async fn test_spiffe_policy_enforced_during_handshake() {
+        let pki = pki();
+        let client_auth = |pattern: &str| ClientAuth {
+            required: true,
+            spiffe: Some(Arc::new(
+                SpiffeValidator::new(vec!["example.org".to_string()]).with_policy(
+                    SpiffePolicy::new(vec![pattern.parse().unwrap()], true),
+                ),
+            )),
+        };
+
+        let allowed = server_with(&pki, client_auth("spiffe://example.org/ns/orders/**"));
+        assert!(handshake(allowed, connector(&pki, true)).await.is_some());
+
+        let denied = server_with(&pki, client_auth("spiffe://example.org/ns/payments/**"));
+        assert!(
+            handshake(denied, connector(&pki, true)).await.is_none(),
+            "certificate outside the SPIFFE policy must not be accepted"
+        );
+    }
+
+    #[test]
+    fn test_invalid_pem_rejected() {
+        let pki = pki();
+        assert!(matches!(
+            server_config_from_pem(
+                b"not a certificate",
+                pki.server.1.serialize_pem().as_bytes(),
+                None,
+                &ClientAuth::default()
+            ),
+            Err(AuthEdgeError::CertificateError { .. })
+        ));
+        assert!(matches!(
+            server_config_from_pem(pki.server.0.pem().as_bytes(), b"", None, &ClientAuth::default()),
+            Err(AuthEdgeError::CertificateError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_missing_connection_certificate() {
+        let validator = SpiffeValidator::new(vec!["example.org".to_string()]);
+        let peer = PeerCertificates::new(None, Vec::new());
+        assert!(peer.leaf().is_none());
+        assert!(matches!(
+            validator.extract_from_connection(&peer),
+            Err(crate::mtls::SpiffeError::MissingCertificate)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reloads_changed_certificate_files() {
+        let dir = std::env::temp_dir().join(format!("tls-reload-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).display().to_string();
+        let write = |pki: &Pki| {
+            std::fs::write(path("tls.crt"), pki.server.0.pem()).unwrap();
+            std::fs::write(path("tls.key"), pki.server.1.serialize_pem()).unwrap();
+            std::fs::write(path("ca.crt"), pki.ca.pem()).unwrap();
+        };
+        write(&pki());
+
+        let config = Config::from_source(&crate::config::EnvSource::with_overrides(
+            [
+                ("TLS_CERT_PATH", path("tls.crt")),
+                ("TLS_KEY_PATH", path("tls.key")),
+                ("TLS_CLIENT_CA_PATH", path("ca.crt")),
+                ("TLS_RELOAD_INTERVAL", "1".to_string()),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+        ))
+        .unwrap();
+        let tls = ServerTls::new(server_config(&config).unwrap().unwrap());
+        let initial = tls.current();
+        tokio::spawn(reload_on_change(tls.clone(), config));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Unchanged files are not reloaded
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(Arc::ptr_eq(&initial, &tls.current()));
+
+        write(&pki());
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let rotated = tls.current();
+        assert!(!Arc::ptr_eq(&initial, &rotated));
+
+        // A half-written rotation keeps the last good configuration
+        std::fs::write(path("tls.key"), "not a key").unwrap();
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(Arc::ptr_eq(&rotated, &tls.current()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
\ No newline at end of file
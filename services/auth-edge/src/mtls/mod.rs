@@ -1,6 +1,16 @@
+pub mod binding;
+pub mod connection;
+pub mod policy;
+pub mod revocation;
 pub mod spiffe;
 pub mod verifier;
+pub mod workload;
 
 // Re-export commonly used types
+pub use binding::{certificate_thumbprint, verify_certificate_binding};
+pub use connection::{ClientAuth, PeerCertificates, ServerTls, TlsConnection};
+pub use policy::{SpiffeIdPattern, SpiffePolicy};
 pub use spiffe::{SpiffeValidator, SpiffeId, OwnedSpiffeId, SpiffeError};
-pub use verifier::CertificateVerifier;
+pub use revocation::{CertRevocationChecker, RevocationMode, RevocationStatus};
+pub use verifier::{CertificateVerifier, ChainVerifier};
+pub use workload::{WorkloadApiClient, WorkloadIdentity, X509Svid};
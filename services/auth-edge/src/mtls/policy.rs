@@ -0,0 +1,230 @@
+//! SPIFFE ID Allowlist Policy
+//!
+//! Narrows the workloads accepted from allowed trust domains. Patterns are
+//! SPIFFE URIs whose path segments may be `*` (exactly one segment) or, as
+//! the last segment, `**` (any remaining segments), e.g.
+//! `spiffe://example.org/ns/payments/sa/*`. A trust domain without patterns
+//! accepts any workload unless the policy denies by default, in which case
+//! every ID must match a pattern.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::spiffe::OwnedSpiffeId;
+
+/// Path segment of a SPIFFE ID pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PatternSegment {
+    /// Matches this segment only
+    Literal(String),
+    /// `*`: matches any single segment
+    Any,
+    /// `**`: matches the remaining segments, if any
+    Rest,
+}
+
+/// SPIFFE ID pattern with path wildcards
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpiffeIdPattern {
+    trust_domain: String,
+    segments: Vec<PatternSegment>,
+}
+
+impl SpiffeIdPattern {
+    /// Trust domain the pattern applies to
+    pub fn trust_domain(&self) -> &str {
+        &self.trust_domain
+    }
+
+    /// Whether the SPIFFE ID matches the pattern
+    pub fn matches(&self, spiffe_id: &OwnedSpiffeId) -> bool {
+        if spiffe_id.trust_domain != self.trust_domain {
+            return false;
+        }
+
+        let mut path = spiffe_id.path.iter();
+        for segment in &self.segments {
+            match segment {
+                PatternSegment::Rest => return true,
+                PatternSegment::Any => {
+                    if path.next().is_none() {
+                        return false;
+                    }
+                }
+                PatternSegment::Literal(literal) => {
+                    if path.next() != Some(literal) {
+                        return false;
+                    }
+                }
+            }
+        }
+        path.next().is_none()
+    }
+}
+
+impl FromStr for SpiffeIdPattern {
+    type Err = String;
+
+    /// Parses `spiffe://trust-domain/path/with/*/or/**`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .trim()
+            .strip_prefix("spiffe://")
+            .ok_or_else(|| format!("expected a spiffe:// pattern, got {s:?}"))?;
+        let (trust_domain, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if trust_domain.is_empty() || trust_domain.contains('*') {
+            return Err(format!("pattern {s:?} needs a literal trust domain"));
+        }
+
+        let segments: Vec<PatternSegment> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment {
+                "*" => PatternSegment::Any,
+                "**" => PatternSegment::Rest,
+                literal => PatternSegment::Literal(literal.to_string()),
+            })
+            .collect();
+        if let Some(position) = segments.iter().position(|s| *s == PatternSegment::Rest) {
+            if position + 1 != segments.len() {
+                return Err(format!("`**` must be the last segment in {s:?}"));
+            }
+        }
+        if segments
+            .iter()
+            .any(|s| matches!(s, PatternSegment::Literal(l) if l.contains('*')))
+        {
+            return Err(format!("wildcards must span a whole segment in {s:?}"));
+        }
+
+        Ok(Self {
+            trust_domain: trust_domain.to_string(),
+            segments,
+        })
+    }
+}
+
+impl fmt::Display for SpiffeIdPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "spiffe://{}", self.trust_domain)?;
+        for segment in &self.segments {
+            match segment {
+                PatternSegment::Literal(literal) => write!(f, "/{literal}")?,
+                PatternSegment::Any => write!(f, "/*")?,
+                PatternSegment::Rest => write!(f, "/**")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Workload allowlist applied after the trust-domain allowlist
+#[derive(Debug, Clone, Default)]
+pub struct SpiffePolicy {
+    patterns: Vec<SpiffeIdPattern>,
+    deny_by_default: bool,
+}
+
+impl SpiffePolicy {
+    /// Creates a policy from allowed ID patterns
+    pub fn new(patterns: Vec<SpiffeIdPattern>, deny_by_default: bool) -> Self {
+        Self {
+            patterns,
+            deny_by_default,
+        }
+    }
+
+    /// Whether the policy restricts anything beyond the trust domain
+    pub fn is_restrictive(&self) -> bool {
+        self.deny_by_default || !self.patterns.is_empty()
+    }
+
+    /// Whether the SPIFFE ID is allowed
+    ///
+    /// IDs must match a pattern of their trust domain; trust domains without
+    /// patterns are open unless the policy denies by default.
+    pub fn allows(&self, spiffe_id: &OwnedSpiffeId) -> bool {
+        let mut scoped = self
+            .patterns
+            .iter()
+            .filter(|pattern| pattern.trust_domain == spiffe_id.trust_domain)
+            .peekable();
+        if scoped.peek().is_none() {
+            return !self.deny_by_default;
+        }
+        scoped.any(|pattern| pattern.matches(spiffe_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(uri: &str) -> OwnedSpiffeId {
+        OwnedSpiffeId::parse(uri).unwrap()
+    }
+
+    fn pattern(s: &str) -> SpiffeIdPattern {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_pattern_wildcards() {
+        let service = pattern("spiffe://example.org/ns/*/sa/orders");
+        assert!(service.matches(&id("spiffe://example.org/ns/prod/sa/orders")));
+        assert!(!service.matches(&id("spiffe://example.org/ns/prod/sa/billing")));
+        assert!(!service.matches(&id("spiffe://example.org/ns/prod/sa/orders/extra")));
+        assert!(!service.matches(&id("spiffe://other.org/ns/prod/sa/orders")));
+
+        let namespace = pattern("spiffe://example.org/ns/payments/**");
+        assert!(namespace.matches(&id("spiffe://example.org/ns/payments/sa/api")));
+        assert!(namespace.matches(&id("spiffe://example.org/ns/payments")));
+        assert!(!namespace.matches(&id("spiffe://example.org/ns/orders/sa/api")));
+
+        assert_eq!(namespace.to_string(), "spiffe://example.org/ns/payments/**");
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!("example.org/ns/*".parse::<SpiffeIdPattern>().is_err());
+        assert!("spiffe://*.example.org/ns".parse::<SpiffeIdPattern>().is_err());
+        assert!("spiffe://example.org/**/sa/api".parse::<SpiffeIdPattern>().is_err());
+        assert!("spiffe://example.org/ns/pay*".parse::<SpiffeIdPattern>().is_err());
+    }
+
+    #[test]
+    fn test_policy_scopes_patterns_to_their_trust_domain() {
+        let policy = SpiffePolicy::new(vec![pattern("spiffe://example.org/ns/payments/**")], false);
+        assert!(policy.allows(&id("spiffe://example.org/ns/payments/sa/api")));
+        assert!(!policy.allows(&id("spiffe://example.org/ns/orders/sa/api")));
+        assert!(policy.allows(&id("spiffe://partner.org/ns/orders/sa/api")));
+    }
+
+    #[test]
+    fn test_deny_by_default() {
+        let policy = SpiffePolicy::new(vec![pattern("spiffe://example.org/ns/payments/**")], true);
+        assert!(policy.allows(&id("spiffe://example.org/ns/payments/sa/api")));
+        assert!(!policy.allows(&id("spiffe://partner.org/ns/orders/sa/api")));
+
+        assert!(!SpiffePolicy::new(vec![], true).allows(&id("spiffe://example.org/sa/api")));
+        assert!(SpiffePolicy::default().allows(&id("spiffe://example.org/sa/api")));
+        assert!(!SpiffePolicy::default().is_restrictive());
+    }
+
+    #[test]
+    fn test_validator_applies_policy() {
+        use super::super::spiffe::{SpiffeError, SpiffeValidator};
+
+        let validator = SpiffeValidator::new(vec!["example.org".to_string()])
+            .with_policy(SpiffePolicy::new(vec![pattern("spiffe://example.org/ns/*/sa/api")], true));
+        assert!(validator.parse_and_validate("spiffe://example.org/ns/orders/sa/api").is_ok());
+        assert!(matches!(
+            validator.parse_and_validate_owned("spiffe://example.org/ns/orders/sa/worker"),
+            Err(SpiffeError::NotAllowed(_))
+        ));
+        assert!(matches!(
+            validator.parse_and_validate_owned("spiffe://partner.org/ns/orders/sa/api"),
+            Err(SpiffeError::UntrustedDomain(_))
+        ));
+    }
+}
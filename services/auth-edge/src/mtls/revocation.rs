@@ -0,0 +1,713 @@
+//! Certificate Revocation Checking
+//!
+//! Determines whether a client certificate was revoked, either from an OCSP
+//! response stapled by the caller or from the CRLs named in the certificate's
+//! distribution points. Outcomes are cached per issuer and serial number so
+//! revocation sources are consulted at most once per cache window; fetched
+//! CRLs are cached per URL for the same window.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use ring::digest;
+use tracing::{debug, warn};
+use x509_parser::der_parser::asn1_rs::{Any, BitString, Class, FromDer, Oid, Tag};
+use x509_parser::prelude::*;
+
+use crate::error::AuthEdgeError;
+
+/// Timeout of a CRL download
+const CRL_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tolerated clock skew for OCSP `thisUpdate`/`nextUpdate`
+const OCSP_CLOCK_SKEW_SECS: i64 = 300;
+
+const OID_OCSP_BASIC: &str = "1.3.6.1.5.5.7.48.1.1";
+const OID_SHA1: &str = "1.3.14.3.2.26";
+const OID_SHA256: &str = "2.16.840.1.101.3.4.2.1";
+
+/// How unknown revocation status is treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RevocationMode {
+    /// Revocation is not checked
+    #[default]
+    Off,
+    /// Revoked certificates are rejected, unknown status is accepted
+    SoftFail,
+    /// Only certificates known not to be revoked are accepted
+    HardFail,
+}
+
+impl FromStr for RevocationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" | "" => Ok(Self::Off),
+            "soft" | "soft-fail" => Ok(Self::SoftFail),
+            "hard" | "hard-fail" => Ok(Self::HardFail),
+            other => Err(format!("unknown revocation mode {other:?}, expected off, soft or hard")),
+        }
+    }
+}
+
+/// Revocation status of a certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevocationStatus {
+    /// A revocation source vouched for the certificate
+    Good,
+    /// The certificate was revoked
+    Revoked,
+    /// No usable revocation source covered the certificate
+    Unknown,
+}
+
+/// Cached statuses and CRLs of revocation checks
+pub struct CertRevocationChecker {
+    http: reqwest::Client,
+    cache_ttl: Duration,
+    statuses: Mutex<HashMap<String, (RevocationStatus, Instant)>>,
+    crls: Mutex<HashMap<String, (Vec<u8>, Instant)>>,
+}
+
+impl CertRevocationChecker {
+    /// Creates a checker caching results for `cache_ttl`
+    pub fn new(cache_ttl: Duration) -> Result<Self, AuthEdgeError> {
+        let http = reqwest::Client::builder()
+            .timeout(CRL_FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| AuthEdgeError::CertificateError {
+                reason: format!("Failed to create HTTP client: {e}"),
+            })?;
+
+        Ok(Self {
+            http,
+            cache_ttl,
+            statuses: Mutex::new(HashMap::new()),
+            crls: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Revocation status of `certificate`, issued by `issuer`
+    ///
+    /// A stapled OCSP response takes precedence over CRLs. Good and revoked
+    /// outcomes are cached; unknown ones are retried on the next check.
+    pub async fn status(
+        &self,
+        certificate: &X509Certificate<'_>,
+        issuer: &X509Certificate<'_>,
+        stapled_ocsp: Option<&[u8]>,
+    ) -> RevocationStatus {
+        let key = cache_key(certificate);
+        if let Some(status) = self.cached(&key) {
+            return status;
+        }
+
+        let now = ASN1Time::now().timestamp();
+        let status = match stapled_ocsp {
+            Some(response) => ocsp_status(certificate, issuer, response, now).unwrap_or_else(|e| {
+                warn!(error = %e, "Ignoring stapled OCSP response");
+                RevocationStatus::Unknown
+            }),
+            None => crl_status(certificate, issuer, &self.crls_for(certificate).await, now),
+        };
+        if status != RevocationStatus::Unknown {
+            self.remember(key, status);
+        }
+        status
+    }
+
+    /// Status cached for the key within the cache window
+    fn cached(&self, key: &str) -> Option<RevocationStatus> {
+        let statuses = self.statuses.lock();
+        statuses
+            .get(key)
+            .filter(|(_, checked_at)| checked_at.elapsed() < self.cache_ttl)
+            .map(|(status, _)| *status)
+    }
+
+    /// Caches a status for the key
+    fn remember(&self, key: String, status: RevocationStatus) {
+        let mut statuses = self.statuses.lock();
+        let ttl = self.cache_ttl;
+        statuses.retain(|_, (_, checked_at)| checked_at.elapsed() < ttl);
+        statuses.insert(key, (status, Instant::now()));
+    }
+
+    /// CRLs from the HTTP distribution points of the certificate
+    ///
+    /// Distribution points that cannot be fetched are skipped, leaving the
+    /// status unknown unless another CRL covers the certificate.
+    async fn crls_for(
+        &self,
+        certificate: &X509Certificate<'_>,
+    ) -> Vec<Vec<u8>> {
+        let mut crls = Vec::new();
+        for url in distribution_points(certificate) {
+            let cached = self
+                .crls
+                .lock()
+                .get(&url)
+                .filter(|(_, fetched_at)| fetched_at.elapsed() < self.cache_ttl)
+                .map(|(crl, _)| crl.clone());
+            if let Some(crl) = cached {
+                crls.push(crl);
+                continue;
+            }
+
+            match self.fetch_crl(&url).await {
+                Ok(crl) => {
+                    self.crls.lock().insert(url, (crl.clone(), Instant::now()));
+                    crls.push(crl);
+                }
+                Err(e) => warn!(url = %url, error = %e, "Failed to fetch CRL"),
+            }
+        }
+        crls
+    }
+
+    async fn fetch_crl(&self, url: &str) -> Result<Vec<u8>, AuthEdgeError> {
+        let fetch_error = |reason: String| AuthEdgeError::CertificateError { reason };
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| fetch_error(e.to_string()))?;
+        let body = response.bytes().await.map_err(|e| fetch_error(e.to_string()))?;
+
+        // Distribution points serve DER; tolerate PEM as well
+        let der = match ::pem::parse(&body) {
+            Ok(pem) if pem.tag() == "X509 CRL" => pem.into_contents(),
+            _ => body.to_vec(),
+        };
+        debug!(url = %url, bytes = der.len(), "Fetched CRL");
+        Ok(der)
+    }
+}
+
+/// Cache key of a certificate: its issuer and serial number
+fn cache_key(certificate: &X509Certificate<'_>) -> String {
+    format!("{}/{}", certificate.issuer(), certificate.raw_serial_as_string())
+}
+
+/// HTTP(S) URLs of the certificate's CRL distribution points
+fn distribution_points(certificate: &X509Certificate<'_>) -> Vec<String> {
+    let mut urls = Vec::new();
+    for extension in certificate.extensions() {
+        let ParsedExtension::CRLDistributionPoints(points) = extension.parsed_extension() else {
+            continue;
+        };
+        for point in points.iter() {
+            let Some(DistributionPointName::FullName(names)) = &point.distribution_point else {
+                continue;
+            };
+            for name in names {
+                if let GeneralName::URI(uri) = name {
+                    if uri.starts_with("http://") || uri.starts_with("https://") {
+                        urls.push(uri.to_string());
+                    }
+                }
+            }
+        }
+    }
+    urls
+}
+
+/// Status of `certificate` according to DER-encoded CRLs
+///
+/// Only current CRLs of `issuer` carrying its signature are authoritative;
+/// without one the status is [`RevocationStatus::Unknown`].
+pub fn crl_status(
+    certificate: &X509Certificate<'_>,
+    issuer: &X509Certificate<'_>,
+    crls: &[Vec<u8>],
+    now: i64,
+) -> RevocationStatus {
+    let serial = trim_integer(certificate.raw_serial());
+    let mut status = RevocationStatus::Unknown;
+    for der in crls {
+        let Ok((_, crl)) = CertificateRevocationList::from_der(der) else {
+            warn!("Ignoring malformed CRL");
+            continue;
+        };
+        if crl.issuer().as_raw() != issuer.subject().as_raw()
+            || crl.next_update().is_some_and(|next_update| next_update.timestamp() < now)
+            || crl.verify_signature(issuer.public_key()).is_err()
+        {
+            continue;
+        }
+        if crl
+            .iter_revoked_certificates()
+            .any(|revoked| trim_integer(revoked.raw_serial()) == serial)
+        {
+            return RevocationStatus::Revoked;
+        }
+        status = RevocationStatus::Good;
+    }
+    status
+}
+
+/// Status of `certificate` according to a stapled OCSP response
+///
+/// The response must be signed by `issuer` or by a responder certificate
+/// that `issuer` delegated OCSP signing to. A response that is not yet or no
+/// longer valid at `now` (Unix seconds) yields [`RevocationStatus::Unknown`].
+pub fn ocsp_status(
+    certificate: &X509Certificate<'_>,
+    issuer: &X509Certificate<'_>,
+    response: &[u8],
+    now: i64,
+) -> Result<RevocationStatus, AuthEdgeError> {
+    let ocsp_error = |reason: String| AuthEdgeError::CertificateError {
+        reason: format!("invalid OCSP response: {reason}"),
+    };
+    let basic = BasicOcspResponse::parse(response).map_err(ocsp_error)?;
+    basic.verify_signature(issuer).map_err(ocsp_error)?;
+
+    let single = basic
+        .responses
+        .iter()
+        .find(|single| single.is_for(certificate, issuer))
+        .ok_or_else(|| ocsp_error("no response for the certificate".to_string()))?;
+
+    if single.this_update > now + OCSP_CLOCK_SKEW_SECS
+        || single
+            .next_update
+            .is_some_and(|next_update| next_update < now - OCSP_CLOCK_SKEW_SECS)
+    {
+        return Ok(RevocationStatus::Unknown);
+    }
+    Ok(single.status)
+}
+
+/// Signed content of an OCSP response
+struct BasicOcspResponse<'a> {
+    tbs_response_data: &'a [u8],
+    signature_algorithm: AlgorithmIdentifier<'a>,
+    signature: BitString<'a>,
+    certificates: Vec<X509Certificate<'a>>,
+    responses: Vec<SingleResponse<'a>>,
+}
+
+/// Status of one certificate in an OCSP response
+struct SingleResponse<'a> {
+    hash_algorithm: String,
+    issuer_name_hash: &'a [u8],
+    issuer_key_hash: &'a [u8],
+    serial: &'a [u8],
+    status: RevocationStatus,
+    this_update: i64,
+    next_update: Option<i64>,
+}
+
+impl<'a> BasicOcspResponse<'a> {
+    /// Parses an `OCSPResponse` carrying a basic response (RFC 6960)
+    fn parse(response: &'a [u8]) -> Result<Self, String> {
+        let mut outer = contents(response, Tag::Sequence)?;
+        let status = next(&mut outer)?;
+        if status.header.tag() != Tag::Enumerated || status.data != [0] {
+            return Err("response status is not successful".to_string());
+        }
+        let mut response_bytes = contents(explicit(&next(&mut outer)?, 0)?, Tag::Sequence)?;
+        if oid(&next(&mut response_bytes)?)? != OID_OCSP_BASIC {
+            return Err("not a basic OCSP response".to_string());
+        }
+        let basic = expect(next(&mut response_bytes)?, Tag::OctetString)?.data;
+
+        let mut basic = contents(basic, Tag::Sequence)?;
+        let tbs_response_data = raw_next(&mut basic)?;
+        let (rest, signature_algorithm) =
+            AlgorithmIdentifier::from_der(basic).map_err(|e| e.to_string())?;
+        let (rest, signature) = BitString::from_der(rest).map_err(|e| e.to_string())?;
+        let mut certificates = Vec::new();
+        if !rest.is_empty() {
+            let mut certs = rest;
+            let mut certs = contents(explicit(&next(&mut certs)?, 0)?, Tag::Sequence)?;
+            while !certs.is_empty() {
+                let (rest, certificate) =
+                    X509Certificate::from_der(certs).map_err(|e| e.to_string())?;
+                certificates.push(certificate);
+                certs = rest;
+            }
+        }
+
+        let mut data = contents(tbs_response_data, Tag::Sequence)?;
+        let mut field = next(&mut data)?;
+        if is_context(&field, 0) {
+            field = next(&mut data)?;
+        }
+        if !is_context(&field, 1) && !is_context(&field, 2) {
+            return Err("missing responder ID".to_string());
+        }
+        expect(next(&mut data)?, Tag::GeneralizedTime)?;
+        let mut singles = contents_of(expect(next(&mut data)?, Tag::Sequence)?)?;
+        let mut responses = Vec::new();
+        while !singles.is_empty() {
+            responses.push(SingleResponse::parse(raw_next(&mut singles)?)?);
+        }
+
+        Ok(Self {
+            tbs_response_data,
+            signature_algorithm,
+            signature,
+            certificates,
+            responses,
+        })
+    }
+
+    /// Verifies the response was signed by the issuer or its OCSP responder
+    fn verify_signature(&self, issuer: &X509Certificate<'_>) -> Result<(), String> {
+        let delegated = self.certificates.iter().filter(|responder| {
+            responder.issuer().as_raw() == issuer.subject().as_raw()
+                && responder.verify_signature(Some(issuer.public_key())).is_ok()
+                && matches!(
+                    responder.extended_key_usage(),
+                    Ok(Some(eku)) if eku.value.ocsp_signing
+                )
+        });
+
+        std::iter::once(issuer)
+            .chain(delegated)
+            .any(|signer| {
+                x509_parser::verify::verify_signature(
+                    signer.public_key(),
+                    &self.signature_algorithm,
+                    &self.signature,
+                    self.tbs_response_data,
+                )
+                .is_ok()
+            })
+            .then_some(())
+            .ok_or_else(|| "signature not made by the issuer or its responder".to_string())
+    }
+}
+
+impl<'a> SingleResponse<'a> {
+    fn parse(single: &'a [u8]) -> Result<Self, String> {
+        let mut single = contents(single, Tag::Sequence)?;
+        let mut cert_id = contents_of(expect(next(&mut single)?, Tag::Sequence)?)?;
+        let mut algorithm = contents_of(expect(next(&mut cert_id)?, Tag::Sequence)?)?;
+        let hash_algorithm = oid(&next(&mut algorithm)?)?;
+        let issuer_name_hash = expect(next(&mut cert_id)?, Tag::OctetString)?.data;
+        let issuer_key_hash = expect(next(&mut cert_id)?, Tag::OctetString)?.data;
+        let serial = expect(next(&mut cert_id)?, Tag::Integer)?.data;
+
+        let status = next(&mut single)?;
+        let status = match (status.header.class(), status.header.tag().0) {
+            (Class::ContextSpecific, 0) => RevocationStatus::Good,
+            (Class::ContextSpecific, 1) => RevocationStatus::Revoked,
+            (Class::ContextSpecific, 2) => RevocationStatus::Unknown,
+            _ => return Err("invalid certificate status".to_string()),
+        };
+        let this_update = time(raw_next(&mut single)?)?;
+        let mut next_update = None;
+        if !single.is_empty() {
+            let field = next(&mut single)?;
+            if is_context(&field, 0) {
+                next_update = Some(time(field.data)?);
+            }
+        }
+
+        Ok(Self {
+            hash_algorithm,
+            issuer_name_hash,
+            issuer_key_hash,
+            serial,
+            status,
+            this_update,
+            next_update,
+        })
+    }
+
+    /// Whether the response's `CertID` names the certificate
+    fn is_for(&self, certificate: &X509Certificate<'_>, issuer: &X509Certificate<'_>) -> bool {
+        let algorithm = match self.hash_algorithm.as_str() {
+            OID_SHA1 => &digest::SHA1_FOR_LEGACY_USE_ONLY,
+            OID_SHA256 => &digest::SHA256,
+            _ => return false,
+        };
+        let name_hash = digest::digest(algorithm, issuer.subject().as_raw());
+        let key_hash = digest::digest(algorithm, &issuer.public_key().subject_public_key.data);
+
+        certificate.issuer().as_raw() == issuer.subject().as_raw()
+            && name_hash.as_ref() == self.issuer_name_hash
+            && key_hash.as_ref() == self.issuer_key_hash
+            && trim_integer(self.serial) == trim_integer(certificate.raw_serial())
+    }
+}
+
+/// Reads the next DER element
+fn next<'a>(input: &mut &'a [u8]) -> Result<Any<'a>, String> {
+    let (rest, any) = Any::from_der(input).map_err(|e| e.to_string())?;
+    *input = rest;
+    Ok(any)
+}
+
+/// Reads the next DER element, returning its encoding
+fn raw_next<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    let start = *input;
+    next(input)?;
+    Ok(&start[..start.len() - input.len()])
+}
+
+/// Contents of a single DER element with the expected tag
+fn contents(input: &[u8], tag: Tag) -> Result<&[u8], String> {
+    let mut input = input;
+    contents_of(expect(next(&mut input)?, tag)?)
+}
+
+fn contents_of(any: Any<'_>) -> Result<&[u8], String> {
+    Ok(any.data)
+}
+
+fn expect(any: Any<'_>, tag: Tag) -> Result<Any<'_>, String> {
+    if any.header.class() != Class::Universal || any.header.tag() != tag {
+        return Err(format!("expected {tag}, found {}", any.header.tag()));
+    }
+    Ok(any)
+}
+
+fn is_context(any: &Any<'_>, tag: u32) -> bool {
+    any.header.class() == Class::ContextSpecific && any.header.tag().0 == tag
+}
+
+/// Contents of an explicitly tagged `[tag]` element
+fn explicit<'a>(any: &Any<'a>, tag: u32) -> Result<&'a [u8], String> {
+    if !is_context(any, tag) {
+        return Err(format!("expected [{tag}]"));
+    }
+    Ok(any.data)
+}
+
+fn oid(any: &Any<'_>) -> Result<String, String> {
+    if any.header.tag() != Tag::Oid {
+        return Err("expected an OID".to_string());
+    }
+    Ok(Oid::new(Cow::Borrowed(any.data)).to_id_string())
+}
+
+fn time(der: &[u8]) -> Result<i64, String> {
+    ASN1Time::from_der(der)
+        .map(|(_, time)| time.timestamp())
+        .map_err(|e| e.to_string())
+}
+
+fn trim_integer(bytes: &[u8]) -> &[u8] {
+    let leading_zeros = bytes.iter().take_while(|b| **b == 0).count();
+    &bytes[leading_zeros.min(bytes.len().saturating_sub(1))..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{
+        BasicConstraints, CertificateParams, CertificateRevocationListParams, IsCa, KeyIdMethod,
+        KeyPair, RevocationReason, RevokedCertParams, SerialNumber, date_time_ymd,
+    };
+    use ring::rand::SystemRandom;
+    use ring::signature::{ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair};
+
+    /// 2026-01-08T00:00:00Z
+    const NOW: i64 = 1_767_830_400;
+
+    const OID_OCSP_BASIC_DER: &[u8] = &[0x06, 0x09, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+    const OID_SHA256_DER: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+    const OID_ECDSA_SHA256_DER: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+
+    struct Pki {
+        ca: rcgen::Certificate,
+        ca_key: KeyPair,
+        leaf: rcgen::Certificate,
+    }
+
+    fn issue(serial: u8) -> Pki {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+        let leaf = issue_leaf(&ca, &ca_key, serial);
+        Pki { ca, ca_key, leaf }
+    }
+
+    fn issue_leaf(ca: &rcgen::Certificate, ca_key: &KeyPair, serial: u8) -> rcgen::Certificate {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.serial_number = Some(SerialNumber::from_slice(&[serial]));
+        params.signed_by(&KeyPair::generate().unwrap(), ca, ca_key).unwrap()
+    }
+
+    fn parse(der: &[u8]) -> X509Certificate<'_> {
+        X509Certificate::from_der(der).unwrap().1
+    }
+
+    /// Encodes a DER element
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        let len = content.len();
+        if len < 0x80 {
+            out.push(len as u8);
+        } else {
+            let bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+            out.push(0x80 | bytes.len() as u8);
+            out.extend(bytes);
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn generalized_time(time: &str) -> Vec<u8> {
+        tlv(0x18, time.as_bytes())
+    }
+
+    /// Builds an OCSP response for the leaf signed with `signer`
+    fn ocsp_response(pki: &Pki, revoked: bool, next_update: &str, signer: &KeyPair) -> Vec<u8> {
+        let issuer = parse(pki.ca.der());
+        let leaf = parse(pki.leaf.der());
+        let name_hash = digest::digest(&digest::SHA256, issuer.subject().as_raw());
+        let key_hash = digest::digest(&digest::SHA256, &issuer.public_key().subject_public_key.data);
+
+        let cert_id = tlv(
+            0x30,
+            &[
+                tlv(0x30, &[OID_SHA256_DER, &[0x05, 0x00]].concat()),
+                tlv(0x04, name_hash.as_ref()),
+                tlv(0x04, key_hash.as_ref()),
+                tlv(0x02, leaf.raw_serial()),
+            ]
+            .concat(),
+        );
+        let status = if revoked {
+            tlv(0xa1, &generalized_time("20260105000000Z"))
+        } else {
+            vec![0x80, 0x00]
+        };
+        let single = tlv(
+            0x30,
+            &[
+                cert_id,
+                status,
+                generalized_time("20260101000000Z"),
+                tlv(0xa0, &generalized_time(next_update)),
+            ]
+            .concat(),
+        );
+        let tbs = tlv(
+            0x30,
+            &[
+                tlv(0xa1, issuer.subject().as_raw()),
+                generalized_time("20260101000000Z"),
+                tlv(0x30, &single),
+            ]
+            .concat(),
+        );
+
+        let key = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_ASN1_SIGNING,
+            &signer.serialize_der(),
+            &SystemRandom::new(),
+        )
+        .unwrap();
+        let signature = key.sign(&SystemRandom::new(), &tbs).unwrap();
+        let basic = tlv(
+            0x30,
+            &[
+                tbs,
+                tlv(0x30, OID_ECDSA_SHA256_DER),
+                tlv(0x03, &[&[0x00], signature.as_ref()].concat()),
+            ]
+            .concat(),
+        );
+        let response_bytes = tlv(0x30, &[OID_OCSP_BASIC_DER, &tlv(0x04, &basic)].concat());
+        tlv(0x30, &[tlv(0x0a, &[0]), tlv(0xa0, &response_bytes)].concat())
+    }
+
+    #[test]
+    fn test_revocation_mode_parsing() {
+        assert_eq!("off".parse::<RevocationMode>().unwrap(), RevocationMode::Off);
+        assert_eq!("soft".parse::<RevocationMode>().unwrap(), RevocationMode::SoftFail);
+        assert_eq!("Hard-Fail".parse::<RevocationMode>().unwrap(), RevocationMode::HardFail);
+        assert!("strict".parse::<RevocationMode>().is_err());
+    }
+
+    #[test]
+    fn test_status_cache_expires() {
+        let checker = CertRevocationChecker::new(Duration::from_millis(20)).unwrap();
+        checker.remember("ca/01".to_string(), RevocationStatus::Revoked);
+        assert_eq!(checker.cached("ca/01"), Some(RevocationStatus::Revoked));
+        assert_eq!(checker.cached("ca/02"), None);
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(checker.cached("ca/01"), None);
+    }
+
+    #[test]
+    fn test_ocsp_status() {
+        let pki = issue(7);
+        let (leaf, issuer) = (parse(pki.leaf.der()), parse(pki.ca.der()));
+
+        let good = ocsp_response(&pki, false, "20260201000000Z", &pki.ca_key);
+        assert_eq!(ocsp_status(&leaf, &issuer, &good, NOW).unwrap(), RevocationStatus::Good);
+
+        let revoked = ocsp_response(&pki, true, "20260201000000Z", &pki.ca_key);
+        assert_eq!(ocsp_status(&leaf, &issuer, &revoked, NOW).unwrap(), RevocationStatus::Revoked);
+
+        let stale = ocsp_response(&pki, false, "20260102000000Z", &pki.ca_key);
+        assert_eq!(ocsp_status(&leaf, &issuer, &stale, NOW).unwrap(), RevocationStatus::Unknown);
+
+        let forged = ocsp_response(&pki, false, "20260201000000Z", &KeyPair::generate().unwrap());
+        assert!(ocsp_status(&leaf, &issuer, &forged, NOW).is_err());
+
+        let other = issue(8);
+        assert!(ocsp_status(&parse(other.leaf.der()), &issuer, &good, NOW).is_err());
+    }
+
+    #[test]
+    fn test_malformed_ocsp_rejected() {
+        assert!(BasicOcspResponse::parse(b"not der").is_err());
+        // OCSPResponse with status tryLater (3)
+        let try_later = tlv(0x30, &tlv(0x0a, &[3]));
+        assert!(BasicOcspResponse::parse(&try_later).is_err());
+    }
+
+    #[test]
+    fn test_crl_status() {
+        let pki = issue(7);
+        let crl = CertificateRevocationListParams {
+            this_update: date_time_ymd(2026, 1, 1),
+            next_update: date_time_ymd(2026, 2, 1),
+            crl_number: SerialNumber::from_slice(&[1]),
+            issuing_distribution_point: None,
+            revoked_certs: vec![RevokedCertParams {
+                serial_number: SerialNumber::from_slice(&[7]),
+                revocation_time: date_time_ymd(2026, 1, 5),
+                reason_code: Some(RevocationReason::KeyCompromise),
+                invalidity_date: None,
+            }],
+            key_identifier_method: KeyIdMethod::Sha256,
+        }
+        .signed_by(&pki.ca, &pki.ca_key)
+        .unwrap();
+        let crls = vec![crl.der().to_vec()];
+        let issuer = parse(pki.ca.der());
+
+        let revoked = parse(pki.leaf.der());
+        assert_eq!(crl_status(&revoked, &issuer, &crls, NOW), RevocationStatus::Revoked);
+
+        let other = issue(8);
+        let good = issue_leaf(&pki.ca, &pki.ca_key, 8);
+        assert_eq!(crl_status(&parse(good.der()), &issuer, &crls, NOW), RevocationStatus::Good);
+
+        // A CRL of another CA is not authoritative
+        let foreign = parse(other.ca.der());
+        assert_eq!(crl_status(&revoked, &foreign, &crls, NOW), RevocationStatus::Unknown);
+        // Nor is an expired one
+        assert_eq!(
+            crl_status(&revoked, &issuer, &crls, NOW + 60 * 86_400),
+            RevocationStatus::Unknown
+        );
+    }
+}
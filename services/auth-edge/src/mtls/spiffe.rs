@@ -8,6 +8,9 @@
 use std::borrow::Cow;
 use std::collections::HashSet;
 
+use super::connection::PeerCertificates;
+use super::policy::SpiffePolicy;
+
 /// SPIFFE ID structure with zero-copy support
 #[derive(Debug, Clone, PartialEq)]
 pub struct SpiffeId<'a> {
@@ -43,6 +46,12 @@ pub enum SpiffeError {
     
     #[error("Invalid path segment")]
     InvalidPath,
+
+    #[error("No client certificate presented on the connection")]
+    MissingCertificate,
+
+    #[error("SPIFFE ID not allowed by policy: {0}")]
+    NotAllowed(String),
 }
 
 impl<'a> SpiffeId<'a> {
@@ -180,20 +189,31 @@ impl OwnedSpiffeId {
 /// SPIFFE ID validator with trust domain allowlist
 pub struct SpiffeValidator {
     allowed_domains: HashSet<String>,
+    policy: SpiffePolicy,
 }
 
 impl SpiffeValidator {
     pub fn new(allowed_domains: Vec<String>) -> Self {
         SpiffeValidator {
             allowed_domains: allowed_domains.into_iter().collect(),
+            policy: SpiffePolicy::default(),
         }
     }
 
+    /// Restricts accepted IDs to the policy's workload allowlist
+    pub fn with_policy(mut self, policy: SpiffePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     /// Validates a SPIFFE ID against the allowlist
     pub fn validate<'a>(&self, spiffe_id: &SpiffeId<'a>) -> Result<(), SpiffeError> {
         if !self.allowed_domains.contains(spiffe_id.trust_domain.as_ref()) {
             return Err(SpiffeError::UntrustedDomain(spiffe_id.trust_domain.to_string()));
         }
+        if self.policy.is_restrictive() && !self.policy.allows(&spiffe_id.to_owned()) {
+            return Err(SpiffeError::NotAllowed(spiffe_id.to_uri()));
+        }
         Ok(())
     }
 
@@ -202,9 +222,17 @@ impl SpiffeValidator {
         if !self.allowed_domains.contains(&spiffe_id.trust_domain) {
             return Err(SpiffeError::UntrustedDomain(spiffe_id.trust_domain.clone()));
         }
+        if !self.policy.allows(spiffe_id) {
+            return Err(SpiffeError::NotAllowed(spiffe_id.to_uri()));
+        }
         Ok(())
     }
 
+    /// Whether IDs are restricted beyond their trust domain
+    pub fn has_restrictive_policy(&self) -> bool {
+        self.policy.is_restrictive()
+    }
+
     /// Parses and validates a SPIFFE URI
     pub fn parse_and_validate<'a>(&self, uri: &'a str) -> Result<SpiffeId<'a>, SpiffeError> {
         let spiffe_id = SpiffeId::parse(uri)?;
@@ -239,13 +267,25 @@ impl SpiffeValidator {
         self.parse_and_validate_owned(&spiffe_uri)
     }
 
+    /// Extracts SPIFFE ID from a DER-encoded certificate, such as a TLS peer certificate.
+    pub fn extract_from_der(&self, certificate_der: &[u8]) -> Result<OwnedSpiffeId, SpiffeError> {
+        let spiffe_uri = Self::extract_san_uri_der(certificate_der)?;
+        self.parse_and_validate_owned(&spiffe_uri)
+    }
+
+    /// Extracts the SPIFFE ID of the client certificate a TLS connection was
+    /// established with.
+    pub fn extract_from_connection(&self, peer: &PeerCertificates) -> Result<OwnedSpiffeId, SpiffeError> {
+        let leaf = peer.leaf().ok_or(SpiffeError::MissingCertificate)?;
+        self.extract_from_der(leaf)
+    }
+
     /// Extracts the SPIFFE URI from certificate SAN extension using proper ASN.1 parsing.
     ///
     /// SECURITY: This function uses x509-parser to properly parse the certificate
     /// and extract the URI from the Subject Alternative Name (SAN) extension.
     /// Previous implementation used string searching which was vulnerable to manipulation.
     fn extract_san_uri(pem: &str) -> Result<String, SpiffeError> {
-        use x509_parser::prelude::*;
         use std::io::Cursor;
 
         // Parse PEM-encoded certificate using rustls-pemfile
@@ -261,8 +301,15 @@ impl SpiffeValidator {
                 SpiffeError::InvalidPath
             })?;
 
+        Self::extract_san_uri_der(&cert_der)
+    }
+
+    /// Extracts the SPIFFE URI from the SAN extension of a DER-encoded certificate.
+    fn extract_san_uri_der(cert_der: &[u8]) -> Result<String, SpiffeError> {
+        use x509_parser::prelude::*;
+
         // Parse X.509 certificate from DER bytes
-        let (_, cert) = X509Certificate::from_der(&cert_der)
+        let (_, cert) = X509Certificate::from_der(cert_der)
             .map_err(|e| {
                 tracing::error!("Failed to parse X.509 certificate: {:?}", e);
                 SpiffeError::InvalidPath
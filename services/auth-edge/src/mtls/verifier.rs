@@ -1,15 +1,61 @@
+use super::policy::SpiffePolicy;
+use super::revocation::{CertRevocationChecker, RevocationMode, RevocationStatus};
+use super::spiffe::OwnedSpiffeId;
+use crate::config::Config;
 use crate::error::AuthEdgeError;
+use rustls::pki_types::{CertificateDer, UnixTime};
+use rustls::server::WebPkiClientVerifier;
+use rustls::server::danger::ClientCertVerifier;
+use rustls::RootCertStore;
+use std::io::{BufReader, Cursor};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
 use x509_parser::prelude::*;
-use std::time::SystemTime;
-use std::io::Cursor;
 
 pub struct CertificateVerifier {
     trust_domain: String,
+    policy: SpiffePolicy,
+    chain: Option<ChainVerifier>,
 }
 
 impl CertificateVerifier {
     pub fn new(trust_domain: String) -> Self {
-        CertificateVerifier { trust_domain }
+        CertificateVerifier { trust_domain, policy: SpiffePolicy::default(), chain: None }
+    }
+
+    /// Restricts accepted SPIFFE IDs to the policy's workload allowlist
+    pub fn with_policy(mut self, policy: SpiffePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Validates presented chains against a trust bundle in `verify_chain`
+    pub fn with_chain_verifier(mut self, chain: ChainVerifier) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    /// Verifies a presented chain, leaf first, and its leaf's SPIFFE ID
+    ///
+    /// Path validation, EKUs and revocation are only checked when a chain
+    /// verifier was configured.
+    pub async fn verify_chain(
+        &self,
+        chain: &[CertificateDer<'_>],
+        stapled_ocsp: Option<&[u8]>,
+    ) -> Result<(), AuthEdgeError> {
+        let leaf = chain.first().ok_or_else(|| AuthEdgeError::CertificateError {
+            reason: "No certificate presented".to_string(),
+        })?;
+        if let Some(verifier) = &self.chain {
+            verifier.verify(chain, stapled_ocsp).await?;
+        }
+
+        let (_, cert) = X509Certificate::from_der(leaf)
+            .map_err(|e| AuthEdgeError::CertificateError { reason: format!("Failed to parse certificate: {}", e) })?;
+        self.check_validity(&cert)?;
+        self.verify_trust_domain(&cert)
     }
 
     pub fn verify_certificate(&self, cert_pem: &str) -> Result<(), AuthEdgeError> {
@@ -35,7 +81,7 @@ impl CertificateVerifier {
     fn check_validity(&self, cert: &X509Certificate) -> Result<(), AuthEdgeError> {
         let now = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
+            .map_err(|_| AuthEdgeError::CertificateError { reason: "System clock is before the Unix epoch".to_string() })?
             .as_secs() as i64;
 
         let not_before = cert.validity().not_before.timestamp();
@@ -60,7 +106,7 @@ impl CertificateVerifier {
                         if uri.starts_with("spiffe://") {
                             let expected_prefix = format!("spiffe://{}/", self.trust_domain);
                             if uri.starts_with(&expected_prefix) {
-                                return Ok(());
+                                return self.verify_policy(uri);
                             }
                         }
                     }
@@ -72,4 +118,357 @@ impl CertificateVerifier {
             reason: format!("Certificate not from trusted domain: {}", self.trust_domain)
         })
     }
+
+    fn verify_policy(&self, uri: &str) -> Result<(), AuthEdgeError> {
+        let spiffe_id = OwnedSpiffeId::parse(uri)
+            .map_err(|e| AuthEdgeError::CertificateError { reason: e.to_string() })?;
+        if !self.policy.allows(&spiffe_id) {
+            return Err(AuthEdgeError::CertificateError {
+                reason: format!("SPIFFE ID not allowed by policy: {}", uri)
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Validates client certificate chains against a trust bundle
+///
+/// Checks the path to a trust anchor, the leaf's extended key usages and,
+/// unless revocation checking is off, the leaf's revocation status.
+pub struct ChainVerifier {
+    roots: Vec<CertificateDer<'static>>,
+    verifier: Arc<dyn ClientCertVerifier>,
+    required_ekus: Vec<String>,
+    revocation: Option<(CertRevocationChecker, RevocationMode)>,
+}
+
+impl ChainVerifier {
+    /// Creates a verifier trusting the given root certificates
+    pub fn new(roots: Vec<CertificateDer<'static>>) -> Result<Self, AuthEdgeError> {
+        let mut store = RootCertStore::empty();
+        for root in &roots {
+            store.add(root.clone()).map_err(chain_error)?;
+        }
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(store), provider)
+            .build()
+            .map_err(chain_error)?;
+
+        Ok(Self {
+            roots,
+            verifier,
+            required_ekus: Vec::new(),
+            revocation: None,
+        })
+    }
+
+    /// Creates a verifier trusting the certificates of a PEM bundle
+    pub fn from_pem(bundle: &[u8]) -> Result<Self, AuthEdgeError> {
+        let roots = rustls_pemfile::certs(&mut BufReader::new(bundle))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(chain_error)?;
+        if roots.is_empty() {
+            return Err(chain_error("trust bundle holds no certificates"));
+        }
+        Self::new(roots)
+    }
+
+    /// Creates the verifier from `MTLS_TRUST_BUNDLE_PATH`, if configured
+    pub fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        let Some(path) = &config.mtls_trust_bundle_path else {
+            return Ok(None);
+        };
+        let bundle = std::fs::read(path)
+            .map_err(|e| chain_error(format!("failed to read trust bundle {path}: {e}")))?;
+
+        let mut verifier = Self::from_pem(&bundle)?.with_required_ekus(config.mtls_required_ekus.clone());
+        if config.cert_revocation_mode != RevocationMode::Off {
+            let checker = CertRevocationChecker::new(Duration::from_secs(
+                config.cert_revocation_cache_ttl_secs,
+            ))?;
+            verifier = verifier.with_revocation(checker, config.cert_revocation_mode);
+        }
+        Ok(Some(verifier))
+    }
+
+    /// Requires leaf certificates to carry these extended key usages
+    ///
+    /// Accepts `serverAuth`, `clientAuth`, `codeSigning`, `emailProtection`,
+    /// `timeStamping`, `OCSPSigning` or dotted OIDs.
+    pub fn with_required_ekus(mut self, ekus: Vec<String>) -> Self {
+        self.required_ekus = ekus.iter().map(|eku| eku_oid(eku)).collect();
+        self
+    }
+
+    /// Checks leaf revocation status in the given mode
+    pub fn with_revocation(mut self, checker: CertRevocationChecker, mode: RevocationMode) -> Self {
+        self.revocation = (mode != RevocationMode::Off).then_some((checker, mode));
+        self
+    }
+
+    /// Verifies a chain, leaf first, with an optional stapled OCSP response
+    pub async fn verify(
+        &self,
+        chain: &[CertificateDer<'_>],
+        stapled_ocsp: Option<&[u8]>,
+    ) -> Result<(), AuthEdgeError> {
+        let (leaf_der, intermediates) = chain
+            .split_first()
+            .ok_or_else(|| chain_error("no certificate presented"))?;
+        let (_, leaf) = X509Certificate::from_der(leaf_der).map_err(chain_error)?;
+
+        self.verify_ekus(&leaf)?;
+        self.verifier
+            .verify_client_cert(leaf_der, intermediates, UnixTime::now())
+            .map_err(chain_error)?;
+
+        let Some((checker, mode)) = &self.revocation else {
+            return Ok(());
+        };
+        let issuer = self.issuer_of(&leaf, intermediates)?;
+        match checker.status(&leaf, &issuer, stapled_ocsp).await {
+            RevocationStatus::Good => Ok(()),
+            RevocationStatus::Revoked => Err(chain_error("certificate revoked")),
+            RevocationStatus::Unknown if *mode == RevocationMode::HardFail => {
+                Err(chain_error("certificate revocation status unknown"))
+            }
+            RevocationStatus::Unknown => {
+                warn!(
+                    serial = %leaf.raw_serial_as_string(),
+                    "Certificate revocation status unknown, accepting"
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn verify_ekus(&self, leaf: &X509Certificate<'_>) -> Result<(), AuthEdgeError> {
+        if self.required_ekus.is_empty() {
+            return Ok(());
+        }
+        let present: Vec<String> = match leaf.extended_key_usage().map_err(chain_error)? {
+            Some(eku) => {
+                let eku = eku.value;
+                [
+                    (eku.server_auth, "serverAuth"),
+                    (eku.client_auth, "clientAuth"),
+                    (eku.code_signing, "codeSigning"),
+                    (eku.email_protection, "emailProtection"),
+                    (eku.time_stamping, "timeStamping"),
+                    (eku.ocsp_signing, "OCSPSigning"),
+                ]
+                .into_iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, name)| eku_oid(name))
+                .chain(eku.other.iter().map(|oid| oid.to_id_string()))
+                .collect()
+            }
+            None => Vec::new(),
+        };
+
+        match self.required_ekus.iter().find(|eku| !present.contains(eku)) {
+            Some(missing) => Err(chain_error(format!("missing extended key usage {missing}"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Issuer of the leaf among the intermediates and trust anchors
+    ///
+    /// The issuer must have signed the leaf, so a certificate merely reusing
+    /// the issuer's name cannot vouch for revocation status.
+    fn issuer_of<'a>(
+        &'a self,
+        leaf: &X509Certificate<'_>,
+        intermediates: &'a [CertificateDer<'_>],
+    ) -> Result<X509Certificate<'a>, AuthEdgeError> {
+        intermediates
+            .iter()
+            .chain(self.roots.iter())
+            .filter_map(|der| X509Certificate::from_der(der).ok().map(|(_, cert)| cert))
+            .find(|candidate| {
+                candidate.subject().as_raw() == leaf.issuer().as_raw()
+                    && leaf.verify_signature(Some(candidate.public_key())).is_ok()
+            })
+            .ok_or_else(|| chain_error("issuer of the certificate not found"))
+    }
+}
+
+/// Dotted OID of an extended key usage name
+fn eku_oid(eku: &str) -> String {
+    match eku {
+        "serverAuth" => "1.3.6.1.5.5.7.3.1",
+        "clientAuth" => "1.3.6.1.5.5.7.3.2",
+        "codeSigning" => "1.3.6.1.5.5.7.3.3",
+        "emailProtection" => "1.3.6.1.5.5.7.3.4",
+        "timeStamping" => "1.3.6.1.5.5.7.3.8",
+        "OCSPSigning" => "1.3.6.1.5.5.7.3.9",
+        oid => oid,
+    }
+    .to_string()
+}
+
+/// Whether the value names a known extended key usage or is a dotted OID
+pub fn is_valid_eku(eku: &str) -> bool {
+    eku_oid(eku) != eku
+        || (eku.split('.').count() > 1
+            && eku.split('.').all(|arc| !arc.is_empty() && arc.bytes().all(|b| b.is_ascii_digit())))
+}
+
+fn chain_error(e: impl std::fmt::Display) -> AuthEdgeError {
+    AuthEdgeError::CertificateError {
+        reason: format!("certificate chain rejected: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{
+        BasicConstraints, Certificate, CertificateParams, CertificateRevocationListParams,
+        CrlDistributionPoint, ExtendedKeyUsagePurpose, IsCa, KeyIdMethod, KeyPair,
+        RevokedCertParams, SerialNumber, date_time_ymd,
+    };
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    struct Ca {
+        certificate: Certificate,
+        key: KeyPair,
+    }
+
+    fn ca() -> Ca {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        Ca { certificate: params.self_signed(&key).unwrap(), key }
+    }
+
+    fn leaf(
+        ca: &Ca,
+        serial: u8,
+        ekus: Vec<ExtendedKeyUsagePurpose>,
+        crl_url: Option<&str>,
+    ) -> CertificateDer<'static> {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.serial_number = Some(SerialNumber::from_slice(&[serial]));
+        params.extended_key_usages = ekus;
+        params.crl_distribution_points = crl_url
+            .map(|url| vec![CrlDistributionPoint { uris: vec![url.to_string()] }])
+            .unwrap_or_default();
+        let certificate = params
+            .signed_by(&KeyPair::generate().unwrap(), &ca.certificate, &ca.key)
+            .unwrap();
+        certificate.der().clone()
+    }
+
+    fn client_leaf(ca: &Ca, serial: u8, crl_url: Option<&str>) -> CertificateDer<'static> {
+        leaf(ca, serial, vec![ExtendedKeyUsagePurpose::ClientAuth], crl_url)
+    }
+
+    fn crl_revoking(ca: &Ca, serial: u8) -> Vec<u8> {
+        use chrono::Datelike;
+        let date = |days: i64| {
+            let day = (chrono::Utc::now() + chrono::Duration::days(days)).date_naive();
+            date_time_ymd(day.year(), day.month() as u8, day.day() as u8)
+        };
+        CertificateRevocationListParams {
+            this_update: date(-1),
+            next_update: date(7),
+            crl_number: SerialNumber::from_slice(&[1]),
+            issuing_distribution_point: None,
+            revoked_certs: vec![RevokedCertParams {
+                serial_number: SerialNumber::from_slice(&[serial]),
+                revocation_time: date(-1),
+                reason_code: None,
+                invalidity_date: None,
+            }],
+            key_identifier_method: KeyIdMethod::Sha256,
+        }
+        .signed_by(&ca.certificate, &ca.key)
+        .unwrap()
+        .der()
+        .to_vec()
+    }
+
+    fn verifier(ca: &Ca) -> ChainVerifier {
+        ChainVerifier::from_pem(ca.certificate.pem().as_bytes()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_chain_validated_against_trust_bundle() {
+        let (trusted, foreign) = (ca(), ca());
+        let verifier = verifier(&trusted);
+
+        assert!(verifier.verify(&[client_leaf(&trusted, 1, None)], None).await.is_ok());
+        assert!(verifier.verify(&[client_leaf(&foreign, 1, None)], None).await.is_err());
+        assert!(verifier.verify(&[], None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_required_ekus_enforced() {
+        let ca = ca();
+        let verifier = verifier(&ca).with_required_ekus(vec![
+            "clientAuth".to_string(),
+            "emailProtection".to_string(),
+        ]);
+
+        let both = leaf(
+            &ca,
+            1,
+            vec![ExtendedKeyUsagePurpose::ClientAuth, ExtendedKeyUsagePurpose::EmailProtection],
+            None,
+        );
+        assert!(verifier.verify(&[both], None).await.is_ok());
+
+        let client_only = client_leaf(&ca, 2, None);
+        let err = verifier.verify(&[client_only], None).await.unwrap_err();
+        assert!(err.to_string().contains("1.3.6.1.5.5.7.3.4"));
+    }
+
+    #[tokio::test]
+    async fn test_crl_revocation_cached_per_serial() {
+        let ca = ca();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ca.crl"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(crl_revoking(&ca, 7)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let crl_url = format!("{}/ca.crl", server.uri());
+
+        let checker = CertRevocationChecker::new(Duration::from_secs(60)).unwrap();
+        let verifier = verifier(&ca).with_revocation(checker, RevocationMode::HardFail);
+
+        let revoked = client_leaf(&ca, 7, Some(&crl_url));
+        let err = verifier.verify(std::slice::from_ref(&revoked), None).await.unwrap_err();
+        assert!(err.to_string().contains("revoked"));
+        assert!(verifier.verify(&[revoked], None).await.is_err());
+
+        let good = client_leaf(&ca, 8, Some(&crl_url));
+        assert!(verifier.verify(&[good], None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_revocation_status_by_mode() {
+        let ca = ca();
+        let unchecked = client_leaf(&ca, 1, None);
+
+        let checker = CertRevocationChecker::new(Duration::from_secs(60)).unwrap();
+        let hard = verifier(&ca).with_revocation(checker, RevocationMode::HardFail);
+        assert!(hard.verify(std::slice::from_ref(&unchecked), None).await.is_err());
+
+        let checker = CertRevocationChecker::new(Duration::from_secs(60)).unwrap();
+        let soft = verifier(&ca).with_revocation(checker, RevocationMode::SoftFail);
+        assert!(soft.verify(&[unchecked], None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_certificate_verifier_checks_chain() {
+        let ca = ca();
+        let verifier = CertificateVerifier::new("example.org".to_string())
+            .with_chain_verifier(verifier(&ca));
+        let err = verifier.verify_chain(&[client_leaf(&ca, 1, None)], None).await.unwrap_err();
+        assert!(err.to_string().contains("trusted domain"));
+    }
 }
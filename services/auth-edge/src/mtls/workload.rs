@@ -0,0 +1,474 @@
+//! SPIFFE Workload API Client
+//!
+//! Streams the service's X.509-SVID and trust bundle from the SPIRE agent
+//! over its Unix domain socket. Every update replaces the server TLS
+//! configuration in place, so rotated SVIDs apply to new connections without
+//! a restart, and becomes the identity reported by [`WorkloadIdentity`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use futures::{Stream, StreamExt};
+use hyper_util::rt::TokioIo;
+use rust_common::PlatformError;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use tokio::net::UnixStream;
+use tonic::transport::{Channel, Endpoint, Uri};
+use tower::service_fn;
+use tracing::{info, warn};
+
+use super::connection::{server_config_from_der, ClientAuth, ServerTls};
+use super::spiffe::OwnedSpiffeId;
+use crate::error::AuthEdgeError;
+use crate::proto::workload::spiffe_workload_api_client::SpiffeWorkloadApiClient;
+use crate::proto::workload::{X509svidRequest, X509svidResponse};
+
+/// Metadata header the Workload API requires on every call
+pub const WORKLOAD_API_HEADER: &str = "workload.spiffe.io";
+
+/// First delay before reconnecting to the agent
+const RECONNECT_INITIAL: Duration = Duration::from_secs(1);
+
+/// Longest delay between reconnect attempts
+const RECONNECT_MAX: Duration = Duration::from_secs(30);
+
+/// X.509-SVID of this workload with its trust bundles
+pub struct X509Svid {
+    spiffe_id: OwnedSpiffeId,
+    cert_chain: Vec<CertificateDer<'static>>,
+    private_key: PrivatePkcs8KeyDer<'static>,
+    bundle: Vec<CertificateDer<'static>>,
+    federated_bundles: HashMap<String, Vec<CertificateDer<'static>>>,
+}
+
+impl X509Svid {
+    /// Parses the default (first) SVID of a Workload API response
+    pub fn from_response(response: X509svidResponse) -> Result<Self, AuthEdgeError> {
+        let svid = response
+            .svids
+            .into_iter()
+            .next()
+            .ok_or_else(|| svid_error("Workload API response carries no SVID"))?;
+
+        let spiffe_id = OwnedSpiffeId::parse(&svid.spiffe_id).map_err(|e| {
+            AuthEdgeError::SpiffeError {
+                reason: format!("invalid SVID SPIFFE ID: {e}"),
+            }
+        })?;
+        if svid.x509_svid_key.is_empty() {
+            return Err(svid_error("SVID has no private key"));
+        }
+        let federated_bundles = response
+            .federated_bundles
+            .into_iter()
+            .map(|(trust_domain, bundle)| Ok((trust_domain, parse_der_certificates(&bundle)?)))
+            .collect::<Result<_, AuthEdgeError>>()?;
+
+        Ok(Self {
+            spiffe_id,
+            cert_chain: parse_der_certificates(&svid.x509_svid)?,
+            private_key: PrivatePkcs8KeyDer::from(svid.x509_svid_key),
+            bundle: parse_der_certificates(&svid.bundle)?,
+            federated_bundles,
+        })
+    }
+
+    /// SPIFFE ID of the workload
+    pub fn spiffe_id(&self) -> &OwnedSpiffeId {
+        &self.spiffe_id
+    }
+
+    /// Certificate chain, leaf first
+    pub fn cert_chain(&self) -> &[CertificateDer<'static>] {
+        &self.cert_chain
+    }
+
+    /// DER of the leaf certificate
+    pub fn leaf(&self) -> &[u8] {
+        &self.cert_chain[0]
+    }
+
+    /// Trust bundle of the workload's own trust domain
+    pub fn bundle(&self) -> &[CertificateDer<'static>] {
+        &self.bundle
+    }
+
+    /// Trust bundles of federated trust domains, keyed by trust domain ID
+    pub fn federated_bundles(&self) -> &HashMap<String, Vec<CertificateDer<'static>>> {
+        &self.federated_bundles
+    }
+
+    /// Builds a server configuration presenting this SVID
+    ///
+    /// Client certificates are verified against the own and every federated
+    /// trust bundle.
+    pub fn server_config(
+        &self,
+        client_auth: &ClientAuth,
+    ) -> Result<Arc<rustls::ServerConfig>, AuthEdgeError> {
+        let roots = self
+            .bundle
+            .iter()
+            .chain(self.federated_bundles.values().flatten())
+            .cloned()
+            .collect();
+        server_config_from_der(
+            self.cert_chain.clone(),
+            PrivateKeyDer::Pkcs8(self.private_key.clone_key()),
+            Some(roots),
+            client_auth,
+        )
+    }
+}
+
+/// Client of the SPIRE agent's Workload API
+#[derive(Debug, Clone)]
+pub struct WorkloadApiClient {
+    socket_path: PathBuf,
+}
+
+impl WorkloadApiClient {
+    /// Creates a client for a `unix:///path` (or `unix:path`) socket address
+    pub fn new(endpoint: &str) -> Result<Self, AuthEdgeError> {
+        let path = endpoint
+            .strip_prefix("unix://")
+            .or_else(|| endpoint.strip_prefix("unix:"))
+            .filter(|path| path.starts_with('/'))
+            .ok_or_else(|| {
+                AuthEdgeError::Platform(PlatformError::InvalidInput(format!(
+                    "SPIFFE endpoint must be an absolute unix socket address: {endpoint}"
+                )))
+            })?;
+        Ok(Self {
+            socket_path: PathBuf::from(path),
+        })
+    }
+
+    /// Streams X.509-SVID updates; the first item is the current SVID
+    pub async fn stream_x509_svids(
+        &self,
+    ) -> Result<impl Stream<Item = Result<X509Svid, AuthEdgeError>> + use<>, AuthEdgeError> {
+        let mut client = SpiffeWorkloadApiClient::new(self.connect().await?);
+        let mut request = tonic::Request::new(X509svidRequest {});
+        request
+            .metadata_mut()
+            .insert(WORKLOAD_API_HEADER, "true".parse().expect("static header value"));
+
+        let stream = client.fetch_x509svid(request).await.map_err(PlatformError::from)?;
+        Ok(stream.into_inner().map(|response| {
+            X509Svid::from_response(response.map_err(PlatformError::from)?)
+        }))
+    }
+
+    /// Fetches the current X.509-SVID
+    pub async fn fetch_x509_svid(&self) -> Result<X509Svid, AuthEdgeError> {
+        let mut stream = Box::pin(self.stream_x509_svids().await?);
+        stream
+            .next()
+            .await
+            .unwrap_or_else(|| Err(svid_error("Workload API stream ended before an SVID")))
+    }
+
+    async fn connect(&self) -> Result<Channel, AuthEdgeError> {
+        let path = self.socket_path.clone();
+        // The URI is required by tonic but unused by the connector
+        Endpoint::from_static("http://localhost")
+            .connect_with_connector(service_fn(move |_: Uri| {
+                let path = path.clone();
+                async move { UnixStream::connect(path).await.map(TokioIo::new) }
+            }))
+            .await
+            .map_err(|e| {
+                AuthEdgeError::Platform(PlatformError::Unavailable(format!(
+                    "SPIRE agent at {}: {e}",
+                    self.socket_path.display()
+                )))
+            })
+    }
+}
+
+/// Current X.509-SVID of the service and the TLS configuration built from it
+#[derive(Clone)]
+pub struct WorkloadIdentity {
+    svid: Arc<ArcSwap<X509Svid>>,
+    tls: ServerTls,
+    client_auth: ClientAuth,
+}
+
+impl WorkloadIdentity {
+    /// Creates the identity from an initial SVID
+    pub fn new(svid: X509Svid, client_auth: ClientAuth) -> Result<Self, AuthEdgeError> {
+        let tls = ServerTls::new(svid.server_config(&client_auth)?);
+        Ok(Self {
+            svid: Arc::new(ArcSwap::from_pointee(svid)),
+            tls,
+            client_auth,
+        })
+    }
+
+    /// Fetches the first SVID and returns the identity with a task that
+    /// follows rotations
+    ///
+    /// The task reconnects with backoff when the agent restarts or the
+    /// stream fails; until then the last SVID stays in use.
+    pub async fn start(
+        client: WorkloadApiClient,
+        client_auth: ClientAuth,
+    ) -> Result<(Self, impl Future<Output = ()> + Send + use<>), AuthEdgeError> {
+        let mut stream = Box::pin(client.stream_x509_svids().await?);
+        let first = stream
+            .next()
+            .await
+            .unwrap_or_else(|| Err(svid_error("Workload API stream ended before an SVID")))?;
+        let identity = Self::new(first, client_auth)?;
+        info!(spiffe_id = %identity.spiffe_id().to_uri(), "Fetched X.509-SVID from SPIRE agent");
+
+        let watcher = identity.clone();
+        let watch = async move {
+            let mut delay = RECONNECT_INITIAL;
+            loop {
+                while let Some(update) = stream.next().await {
+                    match update {
+                        Ok(svid) => {
+                            delay = RECONNECT_INITIAL;
+                            if let Err(e) = watcher.rotate(svid) {
+                                warn!(error = %e, "Ignoring unusable X.509-SVID update");
+                            }
+                        }
+                        Err(e) => {
+                            warn!(error = %e, "Workload API stream failed");
+                            break;
+                        }
+                    }
+                }
+
+                loop {
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(RECONNECT_MAX);
+                    match client.stream_x509_svids().await {
+                        Ok(reconnected) => {
+                            stream = Box::pin(reconnected);
+                            break;
+                        }
+                        Err(e) => warn!(error = %e, "Reconnecting to SPIRE agent failed"),
+                    }
+                }
+            }
+        };
+
+        Ok((identity, watch))
+    }
+
+    /// Current SVID
+    pub fn current(&self) -> Arc<X509Svid> {
+        self.svid.load_full()
+    }
+
+    /// SPIFFE ID of the current SVID
+    pub fn spiffe_id(&self) -> OwnedSpiffeId {
+        self.svid.load().spiffe_id.clone()
+    }
+
+    /// Server TLS configuration following SVID rotations
+    pub fn server_tls(&self) -> ServerTls {
+        self.tls.clone()
+    }
+
+    /// Switches to a new SVID; the previous one stays if it cannot be used
+    pub fn rotate(&self, svid: X509Svid) -> Result<(), AuthEdgeError> {
+        let config = svid.server_config(&self.client_auth)?;
+        let spiffe_id = svid.spiffe_id.to_uri();
+        self.svid.store(Arc::new(svid));
+        self.tls.replace(config);
+        info!(spiffe_id = %spiffe_id, "Rotated X.509-SVID");
+        Ok(())
+    }
+}
+
+/// Splits concatenated ASN.1 DER certificates
+fn parse_der_certificates(der: &[u8]) -> Result<Vec<CertificateDer<'static>>, AuthEdgeError> {
+    let mut certificates = Vec::new();
+    let mut rest = der;
+    while !rest.is_empty() {
+        let (remaining, _) = x509_parser::parse_x509_certificate(rest)
+            .map_err(|e| svid_error(&format!("invalid DER certificate: {e}")))?;
+        let length = rest.len() - remaining.len();
+        certificates.push(CertificateDer::from(rest[..length].to_vec()));
+        rest = remaining;
+    }
+    if certificates.is_empty() {
+        return Err(svid_error("no DER certificate found"));
+    }
+    Ok(certificates)
+}
+
+fn svid_error(reason: &str) -> AuthEdgeError {
+    AuthEdgeError::CertificateError {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::workload::spiffe_workload_api_server::{
+        SpiffeWorkloadApi, SpiffeWorkloadApiServer,
+    };
+    use crate::proto::workload::X509svid;
+    use rcgen::{
+        BasicConstraints, Certificate, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+        SanType,
+    };
+    use std::pin::Pin;
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+    use tonic::{Request, Response, Status};
+
+    const WORKLOAD: &str = "spiffe://example.org/ns/auth/sa/auth-edge";
+
+    struct Ca {
+        cert: Certificate,
+        key: KeyPair,
+    }
+
+    fn ca() -> Ca {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        Ca {
+            cert: params.self_signed(&key).unwrap(),
+            key,
+        }
+    }
+
+    fn svid_response(ca: &Ca, spiffe_id: &str) -> X509svidResponse {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.subject_alt_names = vec![SanType::URI(spiffe_id.try_into().unwrap())];
+        params.extended_key_usages = vec![
+            ExtendedKeyUsagePurpose::ServerAuth,
+            ExtendedKeyUsagePurpose::ClientAuth,
+        ];
+        let cert = params.signed_by(&key, &ca.cert, &ca.key).unwrap();
+
+        let mut chain = cert.der().to_vec();
+        chain.extend_from_slice(ca.cert.der());
+        X509svidResponse {
+            svids: vec![X509svid {
+                spiffe_id: spiffe_id.to_string(),
+                x509_svid: chain,
+                x509_svid_key: key.serialize_der(),
+                bundle: ca.cert.der().to_vec(),
+                hint: String::new(),
+            }],
+            crl: vec![],
+            federated_bundles: HashMap::new(),
+        }
+    }
+
+    type SvidStream = Pin<Box<dyn Stream<Item = Result<X509svidResponse, Status>> + Send>>;
+
+    /// Agent that streams whatever the test sends on its channel
+    struct Agent {
+        updates: parking_lot::Mutex<Option<mpsc::Receiver<X509svidResponse>>>,
+    }
+
+    #[tonic::async_trait]
+    impl SpiffeWorkloadApi for Agent {
+        type FetchX509SVIDStream = SvidStream;
+
+        async fn fetch_x509svid(
+            &self,
+            request: Request<X509svidRequest>,
+        ) -> Result<Response<SvidStream>, Status> {
+            if request.metadata().get(WORKLOAD_API_HEADER).is_none() {
+                return Err(Status::invalid_argument("missing security header"));
+            }
+            let updates = self.updates.lock().take().expect("single stream");
+            let stream = futures::stream::unfold(updates, |mut updates| async move {
+                updates.recv().await.map(|update| (Ok(update), updates))
+            });
+            Ok(Response::new(Box::pin(stream)))
+        }
+    }
+
+    async fn agent() -> (WorkloadApiClient, mpsc::Sender<X509svidResponse>, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("spire-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("agent.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let (tx, rx) = mpsc::channel(4);
+
+        let service = SpiffeWorkloadApiServer::new(Agent {
+            updates: parking_lot::Mutex::new(Some(rx)),
+        });
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            let accepted = listener.accept().await.map(|(stream, _)| stream);
+            Some((accepted, listener))
+        });
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming),
+        );
+
+        let client = WorkloadApiClient::new(&format!("unix://{}", socket.display())).unwrap();
+        (client, tx, dir)
+    }
+
+    #[test]
+    fn test_endpoint_must_be_unix_socket() {
+        assert!(WorkloadApiClient::new("unix:///run/spire/agent.sock").is_ok());
+        assert!(WorkloadApiClient::new("unix:/run/spire/agent.sock").is_ok());
+        assert!(WorkloadApiClient::new("unix://relative.sock").is_err());
+        assert!(WorkloadApiClient::new("tcp://127.0.0.1:8081").is_err());
+    }
+
+    #[test]
+    fn test_response_parsing() {
+        let ca = ca();
+        let svid = X509Svid::from_response(svid_response(&ca, WORKLOAD)).unwrap();
+
+        assert_eq!(svid.spiffe_id().to_uri(), WORKLOAD);
+        assert_eq!(svid.cert_chain().len(), 2);
+        assert_eq!(svid.bundle(), &[ca.cert.der().clone()]);
+        assert!(svid.server_config(&ClientAuth::default()).is_ok());
+
+        let mut empty = svid_response(&ca, WORKLOAD);
+        empty.svids.clear();
+        assert!(X509Svid::from_response(empty).is_err());
+
+        let mut truncated = svid_response(&ca, WORKLOAD);
+        truncated.svids[0].x509_svid.truncate(10);
+        assert!(X509Svid::from_response(truncated).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotation_replaces_identity_and_tls() {
+        let ca = ca();
+        let (client, updates, dir) = agent().await;
+        updates.send(svid_response(&ca, WORKLOAD)).await.unwrap();
+
+        let (identity, watch) = WorkloadIdentity::start(client, ClientAuth::default()).await.unwrap();
+        let watcher = tokio::spawn(watch);
+        let first_leaf = identity.current().leaf().to_vec();
+        let first_tls = identity.server_tls().current();
+        assert_eq!(identity.spiffe_id().to_uri(), WORKLOAD);
+
+        updates.send(svid_response(&ca, WORKLOAD)).await.unwrap();
+        let rotated = async {
+            while identity.current().leaf() == first_leaf.as_slice() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), rotated).await.unwrap();
+        assert!(!Arc::ptr_eq(&first_tls, &identity.server_tls().current()));
+
+        watcher.abort();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
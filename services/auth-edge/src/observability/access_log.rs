@@ -0,0 +1,234 @@
+//! Privacy-preserving access log.
+//!
+//! Emits one sampled record per validation decision through Logging_Service.
+//! Subjects are replaced by a keyed HMAC-SHA256 so records can be correlated
+//! internally without being reversible; the salt is read from Vault and never
+//! leaves the process.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use auth_vault_client::{SecretProvider, VaultClient, VaultConfig};
+use ring::hmac;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::config::Config;
+use crate::error::{AuthEdgeError, ErrorCode};
+use crate::observability::logging::AuthEdgeLogger;
+use crate::rate_limiter::TrustLevel;
+use rust_common::PlatformError;
+
+/// Bytes of the HMAC kept in the subject hash
+const SUBJECT_HASH_BYTES: usize = 16;
+
+/// Vault KV secret holding the access log salt
+#[derive(Deserialize)]
+struct SaltSecret {
+    salt: String,
+}
+
+/// Keyed, non-reversible subject hashing
+pub struct SubjectHasher {
+    key: hmac::Key,
+}
+
+impl SubjectHasher {
+    /// Creates a hasher from a salt
+    pub fn new(salt: &[u8]) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, salt),
+        }
+    }
+
+    /// Loads the salt from a Vault KV path
+    pub async fn from_vault(vault: &VaultClient, path: &str) -> Result<Self, AuthEdgeError> {
+        let (secret, _) = vault.get_secret::<SaltSecret>(path).await.map_err(|e| {
+            AuthEdgeError::Platform(PlatformError::Unavailable(format!(
+                "access log salt unavailable: {e}"
+            )))
+        })?;
+        if secret.salt.len() < 16 {
+            return Err(AuthEdgeError::Platform(PlatformError::InvalidInput(
+                "access log salt must be at least 16 bytes".to_string(),
+            )));
+        }
+        Ok(Self::new(secret.salt.as_bytes()))
+    }
+
+    /// Hashes a subject to a stable hex identifier
+    pub fn hash(&self, subject: &str) -> String {
+        let tag = hmac::sign(&self.key, subject.as_bytes());
+        tag.as_ref()[..SUBJECT_HASH_BYTES]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+/// Validation decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessDecision {
+    /// Token accepted
+    Allow,
+    /// Token rejected with the given error code
+    Deny(ErrorCode),
+}
+
+/// One validation decision, before hashing
+#[derive(Debug, Clone)]
+pub struct AccessEvent<'a> {
+    /// Token subject, if the token could be decoded
+    pub subject: Option<&'a str>,
+    /// Calling client (API key ID or peer address)
+    pub client: &'a str,
+    /// RPC method name
+    pub rpc: &'a str,
+    /// Decision taken
+    pub decision: AccessDecision,
+    /// Time spent deciding
+    pub latency: Duration,
+    /// Rate limiter trust level of the client, when known
+    pub trust_level: Option<TrustLevel>,
+    /// Request correlation ID
+    pub correlation_id: &'a str,
+}
+
+/// Access log record as emitted; contains no raw subject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessLogRecord {
+    /// Keyed hash of the subject
+    pub subject_hash: Option<String>,
+    /// Calling client
+    pub client: String,
+    /// RPC method name
+    pub rpc: String,
+    /// `allow` or `deny`
+    pub decision: &'static str,
+    /// Error code for denials
+    pub error_code: Option<&'static str>,
+    /// Latency in microseconds
+    pub latency_us: u128,
+    /// Trust level name, when known
+    pub trust_level: Option<&'static str>,
+    /// Request correlation ID
+    pub correlation_id: String,
+}
+
+/// Sampled access log writer
+pub struct AccessLog {
+    logger: Arc<AuthEdgeLogger>,
+    hasher: SubjectHasher,
+    sample_rate: f64,
+}
+
+impl AccessLog {
+    /// Creates an access log sampling `sample_rate` (0.0-1.0) of decisions
+    pub fn new(logger: Arc<AuthEdgeLogger>, hasher: SubjectHasher, sample_rate: f64) -> Self {
+        Self {
+            logger,
+            hasher,
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Builds the access log from configuration, or `None` when the sample
+    /// rate is zero
+    pub async fn from_config(
+        config: &Config,
+        logger: Arc<AuthEdgeLogger>,
+    ) -> Result<Option<Self>, AuthEdgeError> {
+        if config.access_log_sample_rate <= 0.0 {
+            return Ok(None);
+        }
+
+        let vault = VaultClient::new(VaultConfig::default()).map_err(|e| {
+            AuthEdgeError::Platform(PlatformError::Internal(e.to_string()))
+        })?;
+        let hasher = SubjectHasher::from_vault(&vault, &config.access_log_salt_path).await?;
+        info!(
+            sample_rate = config.access_log_sample_rate,
+            "Access log enabled"
+        );
+        Ok(Some(Self::new(logger, hasher, config.access_log_sample_rate)))
+    }
+
+    /// Records a decision if it falls within the sample
+    pub async fn record(&self, event: &AccessEvent<'_>) {
+        if self.sample_rate < 1.0 && rand::random::<f64>() >= self.sample_rate {
+            return;
+        }
+        self.logger
+            .log_access(&AccessLogRecord::from_event(event, &self.hasher))
+            .await;
+    }
+}
+
+impl AccessLogRecord {
+    /// Converts an event into its privacy-preserving record
+    pub fn from_event(event: &AccessEvent<'_>, hasher: &SubjectHasher) -> Self {
+        let (decision, error_code) = match event.decision {
+            AccessDecision::Allow => ("allow", None),
+            AccessDecision::Deny(code) => ("deny", Some(code.as_str())),
+        };
+
+        AccessLogRecord {
+            subject_hash: event.subject.map(|subject| hasher.hash(subject)),
+            client: event.client.to_string(),
+            rpc: event.rpc.to_string(),
+            decision,
+            error_code,
+            latency_us: event.latency.as_micros(),
+            trust_level: event.trust_level.map(trust_level_name),
+            correlation_id: event.correlation_id.to_string(),
+        }
+    }
+}
+
+fn trust_level_name(level: TrustLevel) -> &'static str {
+    match level {
+        TrustLevel::Unknown => "unknown",
+        TrustLevel::Suspicious => "suspicious",
+        TrustLevel::Normal => "normal",
+        TrustLevel::Trusted => "trusted",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_hash_is_stable_and_salted() {
+        let hasher = SubjectHasher::new(b"salt-one-0123456789");
+        let other = SubjectHasher::new(b"salt-two-0123456789");
+
+        let hash = hasher.hash("user-123");
+        assert_eq!(hash, hasher.hash("user-123"));
+        assert_eq!(hash.len(), SUBJECT_HASH_BYTES * 2);
+        assert_ne!(hash, hasher.hash("user-124"));
+        assert_ne!(hash, other.hash("user-123"));
+        assert!(!hash.contains("user"));
+    }
+
+    #[test]
+    fn test_record_from_denial() {
+        let hasher = SubjectHasher::new(b"salt-one-0123456789");
+        let event = AccessEvent {
+            subject: Some("user-123"),
+            client: "api-key:legacy-batch",
+            rpc: "ValidateToken",
+            decision: AccessDecision::Deny(ErrorCode::TokenExpired),
+            latency: Duration::from_micros(850),
+            trust_level: Some(TrustLevel::Normal),
+            correlation_id: "c-1",
+        };
+
+        let record = AccessLogRecord::from_event(&event, &hasher);
+        assert_eq!(record.subject_hash, Some(hasher.hash("user-123")));
+        assert_eq!(record.decision, "deny");
+        assert_eq!(record.error_code, Some("AUTH_TOKEN_EXPIRED"));
+        assert_eq!(record.latency_us, 850);
+        assert_eq!(record.trust_level, Some("normal"));
+    }
+}
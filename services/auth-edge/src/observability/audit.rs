@@ -0,0 +1,292 @@
+//! Audit log of validation decisions.
+//!
+//! Compliance requires a record of every allow and deny decision, so unlike
+//! the access log the audit log is never sampled and names the subject and
+//! the caller's SPIFFE ID. Events are batched and sent to Logging_Service
+//! under their own service ID, apart from the service's debug logs. A batch
+//! Logging_Service does not accept is appended to a local JSON-lines file
+//! instead, to be shipped once it recovers.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rust_common::{LogEntry, LogLevel, LoggingClient, LoggingClientConfig};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::observability::access_log::AccessDecision;
+
+/// Service ID the audit stream is sent under
+pub const AUDIT_SERVICE_ID: &str = "auth-edge-audit";
+
+/// Reason recorded for accepted tokens
+const ALLOW_REASON: &str = "TOKEN_VALID";
+
+/// One validation decision
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditEvent {
+    /// When the decision was taken
+    pub timestamp: DateTime<Utc>,
+    /// `allow` or `deny`
+    pub decision: &'static str,
+    /// Error code of a denial, `TOKEN_VALID` otherwise
+    pub reason: &'static str,
+    /// Token subject, if the token could be decoded
+    pub subject: Option<String>,
+    /// SPIFFE ID of the calling workload, if it presented one
+    pub caller_spiffe_id: Option<String>,
+    /// Calling client (API key ID or peer address)
+    pub client: String,
+    /// RPC method name
+    pub method: String,
+    /// Request correlation ID
+    pub correlation_id: String,
+}
+
+impl AuditEvent {
+    /// Creates an event for a decision taken now
+    pub fn new(
+        decision: AccessDecision,
+        method: &str,
+        client: &str,
+        correlation_id: &str,
+    ) -> Self {
+        let (decision, reason) = match decision {
+            AccessDecision::Allow => ("allow", ALLOW_REASON),
+            AccessDecision::Deny(code) => ("deny", code.as_str()),
+        };
+        Self {
+            timestamp: Utc::now(),
+            decision,
+            reason,
+            subject: None,
+            caller_spiffe_id: None,
+            client: client.to_string(),
+            method: method.to_string(),
+            correlation_id: correlation_id.to_string(),
+        }
+    }
+
+    /// Sets the token subject
+    pub fn with_subject(mut self, subject: Option<&str>) -> Self {
+        self.subject = subject.map(str::to_string);
+        self
+    }
+
+    /// Sets the caller's SPIFFE ID
+    pub fn with_caller_spiffe_id(mut self, spiffe_id: Option<&str>) -> Self {
+        self.caller_spiffe_id = spiffe_id.map(str::to_string);
+        self
+    }
+
+    /// Logging_Service entry of the event
+    fn to_log_entry(&self) -> LogEntry {
+        let level = match self.decision {
+            "allow" => LogLevel::Info,
+            _ => LogLevel::Warn,
+        };
+        let mut entry = LogEntry::new(level, "Validation decision", AUDIT_SERVICE_ID)
+            .with_correlation_id(&self.correlation_id)
+            .with_metadata("event_type", "audit")
+            .with_metadata("decision", self.decision)
+            .with_metadata("reason", self.reason)
+            .with_metadata("client", &self.client)
+            .with_metadata("method", &self.method);
+        entry.timestamp = self.timestamp;
+        if let Some(subject) = &self.subject {
+            entry = entry.with_metadata("subject", subject);
+        }
+        if let Some(spiffe_id) = &self.caller_spiffe_id {
+            entry = entry.with_metadata("caller_spiffe_id", spiffe_id);
+        }
+        entry
+    }
+}
+
+/// Batched audit log writer with a local file fallback
+pub struct AuditLog {
+    client: LoggingClient,
+    batch: Mutex<Vec<AuditEvent>>,
+    batch_size: usize,
+    fallback_path: PathBuf,
+}
+
+impl AuditLog {
+    /// Creates an audit log sending batches of `batch_size` events
+    pub fn new(
+        client: LoggingClient,
+        batch_size: usize,
+        fallback_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            client,
+            batch: Mutex::new(Vec::with_capacity(batch_size)),
+            batch_size: batch_size.max(1),
+            fallback_path: fallback_path.into(),
+        }
+    }
+
+    /// Builds the audit log from configuration, or `None` when disabled
+    pub async fn from_config(config: &Config) -> Result<Option<Self>, AuthEdgeError> {
+        if !config.audit_log_enabled {
+            return Ok(None);
+        }
+
+        let client = LoggingClient::new(
+            LoggingClientConfig::default()
+                .with_address(config.logging_service_url_str())
+                .with_service_id(AUDIT_SERVICE_ID)
+                .with_batch_size(config.audit_log_batch_size),
+        )
+        .await
+        .map_err(AuthEdgeError::Platform)?;
+        info!(
+            batch_size = config.audit_log_batch_size,
+            fallback_path = %config.audit_log_fallback_path,
+            "Audit log enabled"
+        );
+        Ok(Some(Self::new(
+            client,
+            config.audit_log_batch_size,
+            &config.audit_log_fallback_path,
+        )))
+    }
+
+    /// Records a decision, sending the batch once it is full
+    pub async fn record(&self, event: AuditEvent) {
+        let full = {
+            let mut batch = self.batch.lock();
+            batch.push(event);
+            batch.len() >= self.batch_size
+        };
+        if full {
+            self.flush().await;
+        }
+    }
+
+    /// Sends the pending events, appending them to the fallback file if
+    /// Logging_Service does not accept them
+    pub async fn flush(&self) {
+        let events = std::mem::take(&mut *self.batch.lock());
+        if events.is_empty() {
+            return;
+        }
+
+        let entries = events.iter().map(AuditEvent::to_log_entry).collect();
+        if let Err(err) = self.client.send_batch(entries).await {
+            warn!(
+                error = %err,
+                events = events.len(),
+                path = %self.fallback_path.display(),
+                "Audit batch undelivered, writing it to the fallback file"
+            );
+            if let Err(err) = append_json_lines(&self.fallback_path, &events).await {
+                error!(
+                    error = %err,
+                    events = events.len(),
+                    path = %self.fallback_path.display(),
+                    "Audit events lost: fallback file unwritable"
+                );
+            }
+        }
+    }
+
+    /// Events waiting for the next batch
+    pub fn pending(&self) -> usize {
+        self.batch.lock().len()
+    }
+
+    /// Flushes partial batches every `interval`
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            self.flush().await;
+        }
+    }
+}
+
+/// Appends events to `path`, one JSON object per line
+async fn append_json_lines(path: &Path, events: &[AuditEvent]) -> std::io::Result<()> {
+    let mut lines = Vec::new();
+    for event in events {
+        serde_json::to_writer(&mut lines, event)?;
+        lines.push(b'\n');
+    }
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(&lines).await?;
+    file.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    fn denial() -> AuditEvent {
+        let decision = AccessDecision::Deny(ErrorCode::TokenExpired);
+        AuditEvent::new(decision, "ValidateToken", "10.0.0.1", "c-1")
+            .with_subject(Some("user-123"))
+            .with_caller_spiffe_id(Some("spiffe://example.org/ns/web/sa/frontend"))
+    }
+
+    #[test]
+    fn test_event_carries_decision_and_caller() {
+        let event = denial();
+        assert_eq!(event.decision, "deny");
+        assert_eq!(event.reason, "AUTH_TOKEN_EXPIRED");
+
+        let entry = event.to_log_entry();
+        assert_eq!(entry.service_id, AUDIT_SERVICE_ID);
+        assert_eq!(entry.correlation_id.as_deref(), Some("c-1"));
+        assert_eq!(entry.metadata["subject"], "user-123");
+        assert_eq!(entry.metadata["caller_spiffe_id"], "spiffe://example.org/ns/web/sa/frontend");
+        assert_eq!(entry.metadata["method"], "ValidateToken");
+
+        let allow = AuditEvent::new(AccessDecision::Allow, "IntrospectToken", "10.0.0.1", "c-2");
+        assert_eq!((allow.decision, allow.reason), ("allow", ALLOW_REASON));
+    }
+
+    #[tokio::test]
+    async fn test_full_batch_is_sent() {
+        let client = LoggingClient::new(LoggingClientConfig::default()).await.unwrap();
+        let audit_log = AuditLog::new(client, 2, "/nonexistent/audit.jsonl");
+
+        audit_log.record(denial()).await;
+        assert_eq!(audit_log.pending(), 1);
+        audit_log.record(denial()).await;
+        assert_eq!(audit_log.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("audit-{}.jsonl", uuid::Uuid::new_v4()));
+        append_json_lines(&path, &[denial()]).await.unwrap();
+        append_json_lines(&path, &[denial()]).await.unwrap();
+
+        let written = tokio::fs::read_to_string(&path).await.unwrap();
+        let _ = tokio::fs::remove_file(&path).await;
+        let lines: Vec<serde_json::Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["reason"], "AUTH_TOKEN_EXPIRED");
+        assert_eq!(lines[1]["subject"], "user-123");
+    }
+}
@@ -0,0 +1,44 @@
+//! Circuit breaker state change reporting.
+//!
+//! Feeds transitions of rust-common circuit breakers to
+//! [`CircuitBreakerMetrics`] and the Logging_Service so flapping circuits
+//! can be alerted on.
+
+use crate::observability::logging::AuthEdgeLogger;
+use crate::observability::metrics::CircuitBreakerMetrics;
+use rust_common::{CircuitBreakerObserver, CircuitState};
+use std::sync::Arc;
+
+/// Observer reporting the state changes of one circuit.
+pub struct CircuitBreakerReporter {
+    circuit: &'static str,
+    metrics: Arc<CircuitBreakerMetrics>,
+    logger: Arc<AuthEdgeLogger>,
+}
+
+impl CircuitBreakerReporter {
+    /// Creates a reporter for the circuit guarding `circuit`.
+    pub fn new(
+        circuit: &'static str,
+        metrics: Arc<CircuitBreakerMetrics>,
+        logger: Arc<AuthEdgeLogger>,
+    ) -> Self {
+        Self { circuit, metrics, logger }
+    }
+}
+
+impl CircuitBreakerObserver for CircuitBreakerReporter {
+    fn on_state_change(&self, from: CircuitState, to: CircuitState) {
+        self.metrics
+            .record_state_change(self.circuit, from.as_str(), to.as_str());
+
+        // Remote logging is async; breakers notify from within a call
+        let logger = self.logger.clone();
+        let circuit = self.circuit;
+        tokio::spawn(async move {
+            logger
+                .log_circuit_breaker_change(circuit, from.as_str(), to.as_str())
+                .await;
+        });
+    }
+}
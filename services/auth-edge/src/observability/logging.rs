@@ -5,6 +5,7 @@
 
 use crate::config::Config;
 use crate::error::AuthEdgeError;
+use crate::observability::access_log::AccessLogRecord;
 use rust_common::{LogEntry, LogLevel, LoggingClient, LoggingClientConfig, PlatformError};
 use tracing::{error, info, Span};
 
@@ -116,6 +117,31 @@ impl AuthEdgeLogger {
         self.client.log(entry).await;
     }
 
+    /// Logs an accepted emergency bypass token as a high-severity audit event.
+    pub async fn log_emergency_bypass(
+        &self,
+        subject: &str,
+        jti: &str,
+        expires_at: i64,
+        correlation_id: &str,
+    ) {
+        let (trace_id, span_id) = Self::extract_trace_context();
+
+        let entry = LogEntry::new(
+            LogLevel::Fatal,
+            "Emergency bypass token used",
+            "auth-edge-service",
+        )
+        .with_correlation_id(correlation_id)
+        .with_trace_context(&trace_id, &span_id)
+        .with_metadata("subject", subject)
+        .with_metadata("jti", jti)
+        .with_metadata("expires_at", expires_at.to_string())
+        .with_metadata("event_type", "emergency_bypass_used");
+
+        self.client.log(entry).await;
+    }
+
     /// Logs a rate limit event.
     pub async fn log_rate_limited(&self, client_id: &str, correlation_id: &str) {
         let (trace_id, span_id) = Self::extract_trace_context();
@@ -133,6 +159,31 @@ impl AuthEdgeLogger {
         self.client.log(entry).await;
     }
 
+    /// Logs a sampled access log record.
+    pub async fn log_access(&self, record: &AccessLogRecord) {
+        let (trace_id, span_id) = Self::extract_trace_context();
+
+        let mut entry = LogEntry::new(LogLevel::Info, "Access decision", "auth-edge-service")
+            .with_correlation_id(&record.correlation_id)
+            .with_trace_context(&trace_id, &span_id)
+            .with_metadata("event_type", "access")
+            .with_metadata("client", &record.client)
+            .with_metadata("rpc", &record.rpc)
+            .with_metadata("decision", record.decision)
+            .with_metadata("latency_us", record.latency_us.to_string());
+        if let Some(subject_hash) = &record.subject_hash {
+            entry = entry.with_metadata("subject_hash", subject_hash);
+        }
+        if let Some(error_code) = record.error_code {
+            entry = entry.with_metadata("error_code", error_code);
+        }
+        if let Some(trust_level) = record.trust_level {
+            entry = entry.with_metadata("trust_level", trust_level);
+        }
+
+        self.client.log(entry).await;
+    }
+
     /// Flushes the log buffer.
     pub async fn flush(&self) {
         self.client.flush().await;
@@ -2,8 +2,7 @@
 //!
 //! Provides Prometheus metrics for circuit breaker state changes and service health.
 
-use prometheus::{Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramVec, Opts, Registry};
-use std::sync::Arc;
+use prometheus::{Counter, CounterVec, Gauge, GaugeVec, Opts, Registry};
 
 /// Circuit breaker metrics
 pub struct CircuitBreakerMetrics {
@@ -84,82 +83,242 @@ impl CircuitBreakerMetrics {
     }
 }
 
-/// Service metrics
-pub struct ServiceMetrics {
-    /// Request latency histogram
-    pub request_latency: HistogramVec,
-    /// Request count
-    pub request_count: CounterVec,
-    /// Error count
-    pub error_count: CounterVec,
-    /// Active requests gauge
-    pub active_requests: Gauge,
+/// Emergency bypass token metrics
+pub struct BypassMetrics {
+    /// Accepted bypass tokens
+    pub accepted: Counter,
+    /// Rejected bypass tokens by error code
+    pub rejected: CounterVec,
 }
 
-impl ServiceMetrics {
-    /// Creates new service metrics
+impl BypassMetrics {
+    /// Creates new emergency bypass metrics
     pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
-        let request_latency = HistogramVec::new(
-            prometheus::HistogramOpts::new(
-                "request_latency_seconds",
-                "Request latency in seconds",
+        let accepted = Counter::with_opts(
+            Opts::new("emergency_bypass_accepted_total", "Total accepted emergency bypass tokens")
+                .namespace("auth_edge"),
+        )?;
+        registry.register(Box::new(accepted.clone()))?;
+
+        let rejected = CounterVec::new(
+            Opts::new("emergency_bypass_rejected_total", "Total rejected emergency bypass tokens")
+                .namespace("auth_edge"),
+            &["error_code"],
+        )?;
+        registry.register(Box::new(rejected.clone()))?;
+
+        Ok(Self { accepted, rejected })
+    }
+
+    /// Records an accepted bypass token
+    pub fn record_accepted(&self) {
+        self.accepted.inc();
+    }
+
+    /// Records a rejected bypass token
+    pub fn record_rejected(&self, error_code: &str) {
+        self.rejected.with_label_values(&[error_code]).inc();
+    }
+}
+
+/// Validated-token cache metrics
+pub struct ValidationCacheMetrics {
+    /// Cache lookups by result (`hit` or `miss`)
+    pub lookups: CounterVec,
+    /// Entries removed by reason (`expired`, `evicted`, `revoked` or `flushed`)
+    pub removals: CounterVec,
+}
+
+impl ValidationCacheMetrics {
+    /// Creates new validated-token cache metrics
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let lookups = CounterVec::new(
+            Opts::new("validation_cache_lookups_total", "Validated-token cache lookups")
+                .namespace("auth_edge"),
+            &["result"],
+        )?;
+        registry.register(Box::new(lookups.clone()))?;
+
+        let removals = CounterVec::new(
+            Opts::new("validation_cache_removals_total", "Validated-token cache removals")
+                .namespace("auth_edge"),
+            &["reason"],
+        )?;
+        registry.register(Box::new(removals.clone()))?;
+
+        Ok(Self { lookups, removals })
+    }
+
+    /// Records a cache hit
+    pub fn record_hit(&self) {
+        self.lookups.with_label_values(&["hit"]).inc();
+    }
+
+    /// Records a cache miss
+    pub fn record_miss(&self) {
+        self.lookups.with_label_values(&["miss"]).inc();
+    }
+
+    /// Records a removed entry
+    pub fn record_removal(&self, reason: &str) {
+        self.removals.with_label_values(&[reason]).inc();
+    }
+}
+
+/// In-flight validation coalescing metrics
+pub struct ValidationCoalescerMetrics {
+    /// Validations that waited for an identical one already in flight
+    pub coalesced: Counter,
+}
+
+impl ValidationCoalescerMetrics {
+    /// Creates new validation coalescing metrics
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let coalesced = Counter::with_opts(
+            Opts::new(
+                "validations_coalesced_total",
+                "Validations sharing the result of an identical in-flight validation",
             )
-            .namespace("auth_edge")
-            .buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
-            &["method", "status"],
+            .namespace("auth_edge"),
+        )?;
+        registry.register(Box::new(coalesced.clone()))?;
+
+        Ok(Self { coalesced })
+    }
+
+    /// Records a validation that joined one in flight
+    pub fn record_coalesced(&self) {
+        self.coalesced.inc();
+    }
+}
+
+/// Revocation check metrics
+pub struct RevocationMetrics {
+    /// Revocation checks by result (`revoked`, `cached_revoked`, `clean`,
+    /// `filter_clean`, `fail_open` or `fail_closed`)
+    pub checks: CounterVec,
+}
+
+impl RevocationMetrics {
+    /// Creates new revocation check metrics
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let checks = CounterVec::new(
+            Opts::new("revocation_checks_total", "Token revocation checks")
+                .namespace("auth_edge"),
+            &["result"],
         )?;
-        registry.register(Box::new(request_latency.clone()))?;
+        registry.register(Box::new(checks.clone()))?;
+
+        Ok(Self { checks })
+    }
+
+    /// Records a revocation check result
+    pub fn record(&self, result: &str) {
+        self.checks.with_label_values(&[result]).inc();
+    }
+}
+
+/// Rate limiter client table metrics
+pub struct RateLimiterMetrics {
+    /// Clients forgotten by reason (`idle` or `capacity`)
+    pub evictions: CounterVec,
+    /// Clients currently tracked
+    pub clients: Gauge,
+    /// Clients banned after repeated denials or failures
+    pub bans: Counter,
+}
 
-        let request_count = CounterVec::new(
-            Opts::new("requests_total", "Total requests")
+impl RateLimiterMetrics {
+    /// Creates new rate limiter metrics
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let evictions = CounterVec::new(
+            Opts::new("rate_limiter_evictions_total", "Rate limiter clients forgotten")
                 .namespace("auth_edge"),
-            &["method", "status"],
+            &["reason"],
         )?;
-        registry.register(Box::new(request_count.clone()))?;
+        registry.register(Box::new(evictions.clone()))?;
 
-        let error_count = CounterVec::new(
-            Opts::new("errors_total", "Total errors")
+        let clients = Gauge::with_opts(
+            Opts::new("rate_limiter_clients", "Clients tracked by the rate limiter")
                 .namespace("auth_edge"),
-            &["error_type"],
         )?;
-        registry.register(Box::new(error_count.clone()))?;
+        registry.register(Box::new(clients.clone()))?;
 
-        let active_requests = Gauge::new(
-            "active_requests",
-            "Number of active requests",
+        let bans = Counter::with_opts(
+            Opts::new("rate_limiter_bans_total", "Rate limiter clients banned")
+                .namespace("auth_edge"),
         )?;
-        registry.register(Box::new(active_requests.clone()))?;
+        registry.register(Box::new(bans.clone()))?;
 
-        Ok(Self {
-            request_latency,
-            request_count,
-            error_count,
-            active_requests,
-        })
+        Ok(Self { evictions, clients, bans })
     }
 
-    /// Records a request
-    pub fn record_request(&self, method: &str, status: &str, latency_secs: f64) {
-        self.request_latency
-            .with_label_values(&[method, status])
-            .observe(latency_secs);
-        self.request_count
-            .with_label_values(&[method, status])
-            .inc();
+    /// Records forgotten clients
+    pub fn record_evictions(&self, reason: &str, count: usize) {
+        self.evictions.with_label_values(&[reason]).inc_by(count as f64);
+        self.clients.sub(count as f64);
+    }
+
+    /// Records a newly tracked client
+    pub fn record_new_client(&self) {
+        self.clients.inc();
     }
 
-    /// Records an error
-    pub fn record_error(&self, error_type: &str) {
-        self.error_count.with_label_values(&[error_type]).inc();
+    /// Records a banned client
+    pub fn record_ban(&self) {
+        self.bans.inc();
     }
+}
+
+/// Concurrency limit and load shedding metrics
+#[derive(Clone)]
+pub struct ConcurrencyMetrics {
+    /// Requests waiting for a free slot
+    pub queue_depth: Gauge,
+    /// Requests rejected while saturated
+    pub shed: Counter,
+}
+
+impl ConcurrencyMetrics {
+    /// Creates new concurrency metrics
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let queue_depth = Gauge::with_opts(
+            Opts::new("concurrency_queue_depth", "Requests waiting for a free slot")
+                .namespace("auth_edge"),
+        )?;
+        registry.register(Box::new(queue_depth.clone()))?;
+
+        let shed = Counter::with_opts(
+            Opts::new("load_shed_total", "Requests shed while saturated")
+                .namespace("auth_edge"),
+        )?;
+        registry.register(Box::new(shed.clone()))?;
+
+        Ok(Self { queue_depth, shed })
+    }
+}
+
+/// Configuration reload metrics
+pub struct ConfigReloadMetrics {
+    /// Reload attempts by result (`applied`, `unchanged` or `failed`)
+    pub reloads: CounterVec,
+}
+
+impl ConfigReloadMetrics {
+    /// Creates new configuration reload metrics
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let reloads = CounterVec::new(
+            Opts::new("config_reloads_total", "Configuration reload attempts")
+                .namespace("auth_edge"),
+            &["result"],
+        )?;
+        registry.register(Box::new(reloads.clone()))?;
 
-    /// Increments active requests
-    pub fn inc_active(&self) {
-        self.active_requests.inc();
+        Ok(Self { reloads })
     }
 
-    /// Decrements active requests
-    pub fn dec_active(&self) {
-        self.active_requests.dec();
+    /// Records a reload attempt result
+    pub fn record(&self, result: &str) {
+        self.reloads.with_label_values(&[result]).inc();
     }
 }
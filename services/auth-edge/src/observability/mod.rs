@@ -1,13 +1,19 @@
 //! OpenTelemetry Observability Module
 //!
-//! Provides tracing, metrics, and structured logging with OpenTelemetry integration.
+//! Provides metrics and structured logging. Tracing is set up by
+//! `rust_common::init_telemetry`.
 
-#[cfg(feature = "otel")]
-pub mod telemetry;
+pub mod access_log;
+pub mod audit;
+pub mod circuit_breaker;
 pub mod metrics;
 pub mod logging;
 
-#[cfg(feature = "otel")]
-pub use telemetry::{init_telemetry, TelemetryConfig, shutdown_telemetry};
-pub use metrics::CircuitBreakerMetrics;
+pub use metrics::{
+    BypassMetrics, CircuitBreakerMetrics, ConcurrencyMetrics, ConfigReloadMetrics,
+    RateLimiterMetrics, RevocationMetrics, ValidationCacheMetrics, ValidationCoalescerMetrics,
+};
+pub use circuit_breaker::CircuitBreakerReporter;
+pub use access_log::{AccessDecision, AccessEvent, AccessLog, AccessLogRecord, SubjectHasher};
+pub use audit::{AuditEvent, AuditLog};
 pub use logging::AuthEdgeLogger;
@@ -0,0 +1,595 @@
+//! Composite Rate Limit Keys
+//!
+//! A [`RateLimitPolicy`] lists rules that limit requests per combination of
+//! caller dimensions — SPIFFE ID, token subject, source IP, the calling client
+//! however it identified itself, and gRPC method — optionally only for some
+//! RPCs. Each rule has its own limit and its own
+//! [`AdaptiveRateLimiter`], so e.g. a per-IP limit on every RPC and a
+//! per-subject-and-method limit on `ValidateToken` are enforced side by side.
+//!
+//! The token subject is only known once the token is validated, so rules
+//! keyed by subject are checked after validation and the others when the
+//! request arrives.
+
+use std::fmt::Write as _;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Deserializer};
+
+use super::{AdaptiveRateLimiter, RateLimitConfig, RateLimitDecision, RateLimitInfo, RateWindow};
+
+/// Request attribute a rate limit can be keyed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyDimension {
+    /// SPIFFE ID of the caller's mTLS certificate
+    SpiffeId,
+    /// `sub` claim of the validated token
+    Subject,
+    /// Peer IP address
+    SourceIp,
+    /// Calling client: its SPIFFE ID, else its API key, else its peer IP
+    Caller,
+    /// gRPC method, e.g. `ValidateToken`
+    Method,
+}
+
+impl KeyDimension {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::SpiffeId => "spiffe_id",
+            Self::Subject => "subject",
+            Self::SourceIp => "source_ip",
+            Self::Caller => "caller",
+            Self::Method => "method",
+        }
+    }
+}
+
+/// One limit of a [`RateLimitPolicy`]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RateLimitRule {
+    /// RPCs the rule applies to; empty applies it to every RPC
+    #[serde(default)]
+    pub methods: Vec<String>,
+    /// Dimensions whose combined values form the client key
+    pub dimensions: Vec<KeyDimension>,
+    /// Requests allowed per window, e.g. `100/1m`
+    #[serde(deserialize_with = "deserialize_window")]
+    pub limit: RateWindow,
+}
+
+impl RateLimitRule {
+    fn applies_to(&self, method: &str) -> bool {
+        self.methods.is_empty() || self.methods.iter().any(|m| m == method)
+    }
+
+    fn keyed_by_subject(&self) -> bool {
+        self.dimensions.contains(&KeyDimension::Subject)
+    }
+
+    /// Client key of `key` under this rule, or `None` if a dimension is unknown
+    fn client_key(&self, key: &RateLimitKey) -> Option<String> {
+        let mut client = String::new();
+        for dimension in &self.dimensions {
+            let value = match dimension {
+                KeyDimension::SpiffeId => key.spiffe_id.clone()?,
+                KeyDimension::Subject => key.subject.clone()?,
+                KeyDimension::SourceIp => key.source_ip?.to_string(),
+                KeyDimension::Caller => key.caller()?,
+                KeyDimension::Method => key.method.clone(),
+            };
+            if !client.is_empty() {
+                client.push('|');
+            }
+            let _ = write!(client, "{}={}", dimension.as_str(), value);
+        }
+        Some(client)
+    }
+}
+
+/// Rules from `RATE_LIMIT_POLICY`, as a JSON array
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct RateLimitPolicy {
+    /// Rules, each enforced independently
+    pub rules: Vec<RateLimitRule>,
+}
+
+impl RateLimitPolicy {
+    /// Checks every rule names at least one dimension, each at most once
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.dimensions.is_empty() {
+                return Err(format!("rule {i} has no dimensions"));
+            }
+            for (j, dimension) in rule.dimensions.iter().enumerate() {
+                if rule.dimensions[..j].contains(dimension) {
+                    return Err(format!(
+                        "rule {i} lists dimension {} twice",
+                        dimension.as_str()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn deserialize_window<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RateWindow, D::Error> {
+    let window = String::deserialize(deserializer)?;
+    RateWindow::from_str(&window).map_err(serde::de::Error::custom)
+}
+
+/// What is known about a request when its rate limits are checked
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitKey {
+    /// gRPC method called
+    pub method: String,
+    /// Caller's SPIFFE ID
+    pub spiffe_id: Option<String>,
+    /// Peer IP address
+    pub source_ip: Option<IpAddr>,
+    /// ID of the API key the caller authenticated with
+    pub api_key: Option<String>,
+    /// Subject of the validated token
+    pub subject: Option<String>,
+}
+
+impl RateLimitKey {
+    /// Key of a request to `method`
+    pub fn new(method: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the caller's SPIFFE ID, if it presented one
+    pub fn with_spiffe_id(mut self, spiffe_id: Option<String>) -> Self {
+        self.spiffe_id = spiffe_id;
+        self
+    }
+
+    /// Sets the peer IP address, if known
+    pub fn with_source_ip(mut self, source_ip: Option<IpAddr>) -> Self {
+        self.source_ip = source_ip;
+        self
+    }
+
+    /// Sets the ID of the caller's API key, if it presented one
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Identity of the calling client, the strongest one it presented
+    fn caller(&self) -> Option<String> {
+        self.spiffe_id
+            .clone()
+            .or_else(|| self.api_key.as_ref().map(|id| format!("api-key:{id}")))
+            .or_else(|| self.source_ip.map(|ip| ip.to_string()))
+    }
+
+    /// The same request, once its token has been validated for `subject`
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+}
+
+type KeyedRule = (RateLimitRule, Arc<AdaptiveRateLimiter>);
+
+/// Enforces a [`RateLimitPolicy`], one adaptive limiter per rule
+pub struct KeyedRateLimiter {
+    rules: ArcSwap<Vec<KeyedRule>>,
+}
+
+impl KeyedRateLimiter {
+    /// Creates limiters for `policy`; each rule's limit replaces the windows
+    /// of `base`, whose trust, warm-up and client bounds it keeps
+    pub fn new(policy: &RateLimitPolicy, base: &RateLimitConfig) -> Self {
+        let rules = policy
+            .rules
+            .iter()
+            .map(|rule| {
+                let limiter = AdaptiveRateLimiter::new(Self::rule_config(rule, base));
+                (rule.clone(), Arc::new(limiter))
+            })
+            .collect();
+        Self {
+            rules: ArcSwap::from_pointee(rules),
+        }
+    }
+
+    /// Builds the limiter from configuration; without rules it admits
+    /// everything until a reload adds some
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self::new(&config.rate_limit_policy, &config.rate_limit_config())
+    }
+
+    fn rule_config(rule: &RateLimitRule, base: &RateLimitConfig) -> RateLimitConfig {
+        RateLimitConfig {
+            base_limit: rule.limit.limit,
+            window: rule.limit.window,
+            extra_windows: Vec::new(),
+            ..base.clone()
+        }
+    }
+
+    /// Whether the policy has no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.load().is_empty()
+    }
+
+    /// Replaces the policy, e.g. on a config reload
+    ///
+    /// Rules present before and after keep their limiter and its per-client
+    /// state, reconfigured from `base`; new rules start fresh and removed
+    /// rules forget their clients. Checks in flight finish against the
+    /// previous rules.
+    pub async fn update_policy(&self, policy: &RateLimitPolicy, base: &RateLimitConfig) {
+        let mut previous: Vec<KeyedRule> = self.rules.load().iter().cloned().collect();
+        let mut rules = Vec::with_capacity(policy.rules.len());
+        for rule in &policy.rules {
+            let config = Self::rule_config(rule, base);
+            let limiter = match previous.iter().position(|(kept, _)| kept == rule) {
+                Some(i) => {
+                    let (_, limiter) = previous.swap_remove(i);
+                    limiter.update_config(config).await;
+                    limiter
+                }
+                None => Arc::new(AdaptiveRateLimiter::new(config)),
+            };
+            rules.push((rule.clone(), limiter));
+        }
+        self.rules.store(Arc::new(rules));
+    }
+
+    /// Rules checked for `key`, with the client key under each
+    ///
+    /// A key without a subject is checked against the rules not keyed by
+    /// subject, and a key with one only against those keyed by subject, so a
+    /// request checked on arrival and again after validation is counted once
+    /// per rule. Rules needing a dimension the key lacks are skipped.
+    fn matching(&self, key: &RateLimitKey) -> Vec<(Arc<AdaptiveRateLimiter>, String)> {
+        self.rules
+            .load()
+            .iter()
+            .filter_map(|(rule, limiter)| {
+                if !rule.applies_to(&key.method)
+                    || rule.keyed_by_subject() != key.subject.is_some()
+                {
+                    return None;
+                }
+                rule.client_key(key).map(|client| (limiter.clone(), client))
+            })
+            .collect()
+    }
+
+    /// Checks `key` against every matching rule; the longest wait wins
+    ///
+    /// Rules that allow the request count it even when another denies it.
+    pub async fn check(&self, key: &RateLimitKey) -> RateLimitDecision {
+        let mut retry_after = None;
+        for (limiter, client) in self.matching(key) {
+            if let RateLimitDecision::Denied { retry_after: wait } = limiter.check(&client).await {
+                retry_after = retry_after.max(Some(wait));
+            }
+        }
+        match retry_after {
+            Some(retry_after) => RateLimitDecision::Denied { retry_after },
+            None => RateLimitDecision::Allowed,
+        }
+    }
+
+    /// Checks `key` as `requests` requests at once (at least one), e.g. the
+    /// tokens of a batch; the first denial stops the count
+    ///
+    /// Requests counted before the denial stay counted.
+    pub async fn check_requests(&self, key: &RateLimitKey, requests: usize) -> RateLimitDecision {
+        for _ in 1..requests {
+            if let denied @ RateLimitDecision::Denied { .. } = self.check(key).await {
+                return denied;
+            }
+        }
+        self.check(key).await
+    }
+
+    /// Share of the limit `key` has spent under its most used matching rule
+    pub async fn utilization(&self, key: &RateLimitKey) -> f64 {
+        let mut utilization: f64 = 0.0;
+        for (limiter, client) in self.matching(key) {
+            utilization = utilization.max(limiter.get_limit_info(&client).await.utilization);
+        }
+        utilization
+    }
+
+    /// Limit info of the binding rule for `key`: the one with the fewest
+    /// requests remaining, the latest reset breaking ties
+    pub async fn limit_info(&self, key: &RateLimitKey) -> Option<RateLimitInfo> {
+        let mut binding: Option<RateLimitInfo> = None;
+        for (limiter, client) in self.matching(key) {
+            let info = limiter.get_limit_info(&client).await;
+            let tighter = binding.as_ref().is_none_or(|current| {
+                (info.remaining, std::cmp::Reverse(info.reset_at))
+                    < (current.remaining, std::cmp::Reverse(current.reset_at))
+            });
+            if tighter {
+                binding = Some(info);
+            }
+        }
+        binding
+    }
+
+    /// Records a request outcome for the trust level under every matching rule
+    pub async fn record_outcome(&self, key: &RateLimitKey, success: bool) {
+        for (limiter, client) in self.matching(key) {
+            limiter.record_outcome(&client, success).await;
+        }
+    }
+
+    /// Updates the system load of every rule's limiter
+    pub async fn update_system_load(&self, load: f64) {
+        for (_, limiter) in self.rules.load_full().iter() {
+            limiter.update_system_load(load).await;
+        }
+    }
+
+    /// Lifts bans under every rule, or only those of clients with a
+    /// dimension equal to `value` (e.g. an IP or SPIFFE ID)
+    ///
+    /// Returns the number of bans lifted.
+    pub async fn clear_bans(&self, value: Option<&str>) -> usize {
+        let mut cleared = 0;
+        for (_, limiter) in self.rules.load_full().iter() {
+            cleared += limiter
+                .clear_bans(|client| {
+                    value.is_none_or(|value| {
+                        client
+                            .split('|')
+                            .any(|part| part.split_once('=').is_some_and(|(_, v)| v == value))
+                    })
+                })
+                .await;
+        }
+        cleared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn policy(json: &str) -> RateLimitPolicy {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn limiter(json: &str) -> KeyedRateLimiter {
+        let base = RateLimitConfig {
+            burst_capacity: 0,
+            warmup_period: Duration::ZERO,
+            trust_multiplier: 1.0,
+            ..Default::default()
+        };
+        KeyedRateLimiter::new(&policy(json), &base)
+    }
+
+    fn key(method: &str, ip: &str) -> RateLimitKey {
+        RateLimitKey::new(method).with_source_ip(Some(ip.parse().unwrap()))
+    }
+
+    #[test]
+    fn test_policy_parses_and_validates() {
+        let parsed = policy(
+            r#"[{"methods": ["ValidateToken"], "dimensions": ["subject", "method"], "limit": "100/1m"},
+                {"dimensions": ["source_ip"], "limit": "10/1s"}]"#,
+        );
+        assert_eq!(parsed.rules.len(), 2);
+        assert_eq!(parsed.rules[1].limit, RateWindow::new(10, Duration::from_secs(1)));
+        assert!(parsed.rules[1].methods.is_empty());
+        assert!(parsed.validate().is_ok());
+
+        assert!(policy(r#"[{"dimensions": [], "limit": "1/1s"}]"#).validate().is_err());
+        assert!(policy(r#"[{"dimensions": ["method", "method"], "limit": "1/1s"}]"#)
+            .validate()
+            .is_err());
+        assert!(serde_json::from_str::<RateLimitPolicy>(
+            r#"[{"dimensions": ["tenant"], "limit": "1/1s"}]"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<RateLimitPolicy>(
+            r#"[{"dimensions": ["method"], "limit": "fast"}]"#
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_limits_each_dimension_combination() {
+        // Unknown clients get 0.75x without warm-up, so 4/1m allows 3
+        let limiter = limiter(
+            r#"[{"methods": ["ValidateToken"], "dimensions": ["source_ip", "method"], "limit": "4/1m"}]"#,
+        );
+        for _ in 0..3 {
+            assert!(matches!(
+                limiter.check(&key("ValidateToken", "10.0.0.1")).await,
+                RateLimitDecision::Allowed
+            ));
+        }
+        assert!(matches!(
+            limiter.check(&key("ValidateToken", "10.0.0.1")).await,
+            RateLimitDecision::Denied { .. }
+        ));
+
+        // Other IPs and unlisted methods have their own budgets
+        assert!(matches!(
+            limiter.check(&key("ValidateToken", "10.0.0.2")).await,
+            RateLimitDecision::Allowed
+        ));
+        assert!(matches!(
+            limiter.check(&key("IntrospectToken", "10.0.0.1")).await,
+            RateLimitDecision::Allowed
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_caller_dimension_prefers_strongest_identity() {
+        let limiter = limiter(r#"[{"dimensions": ["caller", "method"], "limit": "4/1m"}]"#);
+        let api_key = |ip| key("IntrospectToken", ip).with_api_key(Some("orders".to_string()));
+
+        // One API key is one caller whatever its address
+        for ip in ["10.0.0.1", "10.0.0.2", "10.0.0.3"] {
+            assert!(matches!(limiter.check(&api_key(ip)).await, RateLimitDecision::Allowed));
+        }
+        assert!(matches!(
+            limiter.check(&api_key("10.0.0.4")).await,
+            RateLimitDecision::Denied { .. }
+        ));
+
+        // Other callers keep their own budget
+        assert!(matches!(
+            limiter.check(&key("IntrospectToken", "10.0.0.1")).await,
+            RateLimitDecision::Allowed
+        ));
+        let workload = api_key("10.0.0.1")
+            .with_spiffe_id(Some("spiffe://example.org/sa/orders".to_string()));
+        assert!(matches!(limiter.check(&workload).await, RateLimitDecision::Allowed));
+        assert_eq!(limiter.clear_bans(Some("api-key:orders")).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_check_requests_counts_each_request() {
+        // 8/1m allows 6 for an unknown client
+        let limiter = limiter(r#"[{"dimensions": ["source_ip"], "limit": "8/1m"}]"#);
+        let key = key("IntrospectBatch", "10.0.0.1");
+
+        assert!(matches!(limiter.check_requests(&key, 5).await, RateLimitDecision::Allowed));
+        assert!(matches!(
+            limiter.check_requests(&key, 2).await,
+            RateLimitDecision::Denied { .. }
+        ));
+        assert!(matches!(
+            limiter.check_requests(&key, 0).await,
+            RateLimitDecision::Denied { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_limit_info_reports_binding_rule() {
+        let limiter = limiter(
+            r#"[{"dimensions": ["source_ip"], "limit": "40/1m"},
+                {"dimensions": ["method"], "limit": "4/1s"}]"#,
+        );
+        let key = key("ValidateToken", "10.0.0.1");
+        assert!(limiter.limit_info(&RateLimitKey::new("ValidateToken").with_subject("alice")).await.is_none());
+
+        limiter.check(&key).await;
+        let info = limiter.limit_info(&key).await.unwrap();
+        assert_eq!((info.limit, info.remaining), (3, 2));
+        assert_eq!(info.window, Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_clear_bans_by_dimension_value() {
+        let base = RateLimitConfig {
+            ban_threshold: 2,
+            ..Default::default()
+        };
+        let limiter = KeyedRateLimiter::new(
+            &policy(r#"[{"dimensions": ["source_ip", "method"], "limit": "100/1m"}]"#),
+            &base,
+        );
+        for ip in ["10.0.0.1", "10.0.0.2"] {
+            limiter.check(&key("ValidateToken", ip)).await;
+            for _ in 0..2 {
+                limiter.record_outcome(&key("ValidateToken", ip), false).await;
+            }
+        }
+        assert!(matches!(
+            limiter.check(&key("ValidateToken", "10.0.0.1")).await,
+            RateLimitDecision::Denied { .. }
+        ));
+
+        assert_eq!(limiter.clear_bans(Some("10.0.0.1")).await, 1);
+        assert!(matches!(
+            limiter.check(&key("ValidateToken", "10.0.0.1")).await,
+            RateLimitDecision::Allowed
+        ));
+        assert_eq!(limiter.clear_bans(None).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_subject_rules_apply_after_validation() {
+        let limiter = limiter(
+            r#"[{"dimensions": ["subject"], "limit": "2/1m"},
+                {"dimensions": ["spiffe_id"], "limit": "2/1m"}]"#,
+        );
+        let arrival = key("ValidateToken", "10.0.0.1");
+
+        // Neither rule applies before validation without a SPIFFE ID
+        for _ in 0..5 {
+            assert!(matches!(limiter.check(&arrival).await, RateLimitDecision::Allowed));
+        }
+
+        let validated = arrival.clone().with_subject("alice");
+        assert!(matches!(limiter.check(&validated).await, RateLimitDecision::Allowed));
+        assert!(matches!(
+            limiter.check(&validated).await,
+            RateLimitDecision::Denied { .. }
+        ));
+        assert!(matches!(
+            limiter.check(&arrival.with_subject("bob")).await,
+            RateLimitDecision::Allowed
+        ));
+
+        // Subject rules do not count arrival checks of mTLS callers
+        let workload = key("ValidateToken", "10.0.0.3")
+            .with_spiffe_id(Some("spiffe://example.org/sa/orders".to_string()));
+        assert!(matches!(limiter.check(&workload).await, RateLimitDecision::Allowed));
+        assert!(matches!(
+            limiter.check(&workload).await,
+            RateLimitDecision::Denied { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_policy_keeps_state_of_unchanged_rules() {
+        let limiter = limiter(
+            r#"[{"dimensions": ["source_ip"], "limit": "4/1m"},
+                {"dimensions": ["method"], "limit": "4/1m"}]"#,
+        );
+        let key = key("ValidateToken", "10.0.0.1");
+        for _ in 0..3 {
+            limiter.check(&key).await;
+        }
+
+        let base = RateLimitConfig {
+            burst_capacity: 0,
+            warmup_period: Duration::ZERO,
+            trust_multiplier: 1.0,
+            ..Default::default()
+        };
+        limiter
+            .update_policy(
+                &policy(
+                    r#"[{"dimensions": ["source_ip"], "limit": "4/1m"},
+                        {"dimensions": ["method"], "limit": "8/1m"}]"#,
+                ),
+                &base,
+            )
+            .await;
+
+        // The per-IP rule kept its count; the new per-method rule did not
+        let info = limiter.limit_info(&key).await.unwrap();
+        assert_eq!((info.limit, info.remaining), (3, 0));
+        assert!(matches!(limiter.check(&key).await, RateLimitDecision::Denied { .. }));
+
+        limiter.update_policy(&RateLimitPolicy::default(), &base).await;
+        assert!(limiter.is_empty());
+        assert!(matches!(limiter.check(&key).await, RateLimitDecision::Allowed));
+    }
+}
@@ -0,0 +1,246 @@
+//! System Load Sampling
+//!
+//! [`LoadSampler`] periodically scores how busy the service is and feeds the
+//! score to the adaptive limiters, whose limits drop by
+//! `load_reduction_factor` while it exceeds `load_threshold`.
+//!
+//! The score is the most saturated of three signals, each scaled to 0.0-1.0:
+//! host CPU usage since the previous sample (from `/proc/stat`, ignored where
+//! unavailable), tasks waiting in the tokio runtime's global queue relative to
+//! `LOAD_MAX_QUEUE_DEPTH`, and requests in flight relative to
+//! `LOAD_MAX_IN_FLIGHT`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::debug;
+
+use super::{AdaptiveRateLimiter, KeyedRateLimiter};
+
+/// Number of requests currently being handled
+#[derive(Debug, Clone, Default)]
+pub struct InFlightRequests {
+    count: Arc<AtomicUsize>,
+}
+
+impl InFlightRequests {
+    /// Counts a request until the returned guard is dropped
+    pub fn start(&self) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard {
+            count: self.count.clone(),
+        }
+    }
+
+    /// Requests currently in flight
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// Keeps a request counted in [`InFlightRequests`] while alive
+#[derive(Debug)]
+pub struct InFlightGuard {
+    count: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Load signals of one sample, each 0.0-1.0
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadSample {
+    /// Host CPU busy fraction, if known
+    pub cpu: Option<f64>,
+    /// Runtime global queue depth relative to its maximum
+    pub queue: f64,
+    /// Requests in flight relative to their maximum
+    pub in_flight: f64,
+}
+
+impl LoadSample {
+    /// Load score: the most saturated signal
+    pub fn score(&self) -> f64 {
+        self.cpu
+            .unwrap_or(0.0)
+            .max(self.queue)
+            .max(self.in_flight)
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// Busy and total CPU time of the aggregate `cpu` line of `/proc/stat`
+fn parse_cpu_times(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    let times: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    // user nice system idle iowait irq softirq steal [guest guest_nice]
+    let total: u64 = times.iter().take(8).sum();
+    let idle = times.get(3)? + times.get(4).copied().unwrap_or(0);
+    Some((total.saturating_sub(idle), total))
+}
+
+/// Feeds a load score to the adaptive limiters at a fixed interval
+pub struct LoadSampler {
+    limiters: Vec<Arc<AdaptiveRateLimiter>>,
+    keyed: Option<Arc<KeyedRateLimiter>>,
+    in_flight: InFlightRequests,
+    interval: Duration,
+    max_in_flight: usize,
+    max_queue_depth: usize,
+    /// CPU times of the previous sample
+    cpu_times: Option<(u64, u64)>,
+}
+
+impl LoadSampler {
+    /// Creates a sampler scoring `in_flight` against `max_in_flight` requests
+    pub fn new(in_flight: InFlightRequests, interval: Duration, max_in_flight: usize) -> Self {
+        Self {
+            limiters: Vec::new(),
+            keyed: None,
+            in_flight,
+            interval,
+            max_in_flight,
+            max_queue_depth: 256,
+            cpu_times: None,
+        }
+    }
+
+    /// Creates a sampler from configuration
+    pub fn from_config(config: &crate::config::Config, in_flight: InFlightRequests) -> Self {
+        Self::new(
+            in_flight,
+            Duration::from_millis(config.load_sample_interval_ms),
+            config.load_max_in_flight,
+        )
+        .with_max_queue_depth(config.load_max_queue_depth)
+    }
+
+    /// Scores the runtime's global queue against `max_queue_depth` tasks
+    pub fn with_max_queue_depth(mut self, max_queue_depth: usize) -> Self {
+        self.max_queue_depth = max_queue_depth;
+        self
+    }
+
+    /// Feeds the score to `limiter`
+    pub fn with_limiter(mut self, limiter: Arc<AdaptiveRateLimiter>) -> Self {
+        self.limiters.push(limiter);
+        self
+    }
+
+    /// Feeds the score to every rule of `keyed`
+    pub fn with_keyed(mut self, keyed: Arc<KeyedRateLimiter>) -> Self {
+        self.keyed = Some(keyed);
+        self
+    }
+
+    /// Samples until the future is dropped; a zero interval disables sampling
+    pub async fn run(mut self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let mut ticks = tokio::time::interval(self.interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            let queue_depth = tokio::runtime::Handle::current().metrics().global_queue_depth();
+            let cpu = std::fs::read_to_string("/proc/stat")
+                .ok()
+                .and_then(|stat| parse_cpu_times(&stat));
+            let sample = self.sample(cpu, queue_depth);
+            debug!(?sample, score = sample.score(), "System load sampled");
+            self.feed(sample.score()).await;
+        }
+    }
+
+    /// Builds a sample from the current CPU times and runtime queue depth
+    fn sample(&mut self, cpu_times: Option<(u64, u64)>, queue_depth: usize) -> LoadSample {
+        let cpu = match (self.cpu_times, cpu_times) {
+            (Some((busy_before, total_before)), Some((busy, total))) if total > total_before => {
+                Some(busy.saturating_sub(busy_before) as f64 / (total - total_before) as f64)
+            }
+            _ => None,
+        };
+        if cpu_times.is_some() {
+            self.cpu_times = cpu_times;
+        }
+
+        LoadSample {
+            cpu,
+            queue: ratio(queue_depth, self.max_queue_depth),
+            in_flight: ratio(self.in_flight.count(), self.max_in_flight),
+        }
+    }
+
+    async fn feed(&self, score: f64) {
+        for limiter in &self.limiters {
+            limiter.update_system_load(score).await;
+        }
+        if let Some(keyed) = &self.keyed {
+            keyed.update_system_load(score).await;
+        }
+    }
+}
+
+/// `value` relative to `max`, capped at 1.0; a zero maximum ignores the signal
+fn ratio(value: usize, max: usize) -> f64 {
+    if max == 0 {
+        return 0.0;
+    }
+    (value as f64 / max as f64).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limiter::RateLimitConfig;
+
+    #[test]
+    fn test_parse_cpu_times() {
+        let stat = "cpu  100 0 50 800 50 0 0 0 0 0\ncpu0 50 0 25 400 25 0 0 0 0 0\n";
+        assert_eq!(parse_cpu_times(stat), Some((150, 1000)));
+        assert_eq!(parse_cpu_times("intr 1 2 3"), None);
+    }
+
+    #[test]
+    fn test_sample_scores_most_saturated_signal() {
+        let in_flight = InFlightRequests::default();
+        let guards: Vec<_> = (0..50).map(|_| in_flight.start()).collect();
+        let mut sampler = LoadSampler::new(in_flight.clone(), Duration::from_secs(1), 100)
+            .with_max_queue_depth(10);
+
+        // The first sample has no CPU baseline
+        let first = sampler.sample(Some((100, 1000)), 2);
+        assert_eq!(first.cpu, None);
+        assert_eq!(first.score(), 0.5);
+
+        let second = sampler.sample(Some((1000, 2000)), 20);
+        assert_eq!(second.cpu, Some(0.9));
+        assert_eq!(second.queue, 1.0);
+        assert_eq!(second.score(), 1.0);
+
+        drop(guards);
+        assert_eq!(in_flight.count(), 0);
+        assert_eq!(sampler.sample(None, 0).score(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_feed_updates_limiters() {
+        let limiter = Arc::new(AdaptiveRateLimiter::new(RateLimitConfig::default()));
+        let sampler = LoadSampler::new(InFlightRequests::default(), Duration::from_secs(1), 100)
+            .with_limiter(limiter.clone());
+
+        sampler.feed(0.95).await;
+        let info = limiter.get_limit_info("client").await;
+        assert_eq!(info.system_load, 0.95);
+        assert_eq!(info.limit, 37);
+    }
+}
@@ -2,11 +2,66 @@
 //!
 //! Implements rate limiting with HTTP 429 responses and adaptive adjustment
 //! based on system load and client trust level.
+//!
+//! New clients start at `warmup_initial_factor` of the base limit and ramp
+//! linearly to the full limit over `warmup_period` of good behavior. A
+//! per-client burst allowance absorbs short spikes above the limit and
+//! refills at `burst_capacity` requests per window.
+//!
+//! Extra windows (e.g. per-second and per-hour) can be enforced alongside the
+//! primary one; the most restrictive window decides.
+//!
+//! The configuration can be replaced at runtime with
+//! [`AdaptiveRateLimiter::update_config`]; client state is kept.
+//!
+//! Client state is bounded: clients idle for `client_idle_ttl` are swept, and
+//! once `max_clients` are tracked the least recently seen one is discarded,
+//! so random client IDs from scanners cannot grow memory without limit.
+//!
+//! Clients are spread over independently locked shards by a hash of their
+//! ID, so concurrent checks for different clients rarely contend. A shard
+//! lock is only held for the in-memory update, never across an await.
+//!
+//! Clients denied `ban_threshold` times in a row, or failing that many
+//! requests in a row (e.g. presenting invalid tokens), are banned: every
+//! request is denied without further accounting until the ban expires. Each
+//! ban of the same client lasts twice as long as the previous one, up to
+//! `max_ban_duration`. [`AdaptiveRateLimiter::clear_bans`] lifts bans early.
+//!
+//! [`KeyedRateLimiter`] enforces limits keyed by combinations of SPIFFE ID,
+//! token subject, source IP and gRPC method on top of this limiter.
+//!
+//! [`LoadSampler`] feeds the system load that triggers load reduction, and
+//! [`QuotaWarnings`] turns a client's utilization into advisory warnings.
+//!
+//! [`AdaptiveRateLimiter::export_snapshot`] and
+//! [`AdaptiveRateLimiter::import_snapshot`] carry client state across a
+//! blue/green deploy.
+
+mod keys;
+mod load;
+mod quota;
+mod snapshot;
 
-use std::collections::HashMap;
+pub use keys::{KeyDimension, KeyedRateLimiter, RateLimitKey, RateLimitPolicy, RateLimitRule};
+pub use load::{InFlightGuard, InFlightRequests, LoadSample, LoadSampler};
+pub use quota::{QuotaWarnings, QUOTA_WARNING_HEADER};
+pub use snapshot::{ClientSnapshot, RateLimiterSnapshot, WindowSnapshot, SNAPSHOT_VERSION};
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+
+use arc_swap::ArcSwap;
+use lru::LruCache;
+use parking_lot::{Mutex, MutexGuard};
+use serde::{Deserialize, Serialize};
+
+use crate::observability::RateLimiterMetrics;
 
 /// Rate limit decision
 #[derive(Debug, Clone)]
@@ -18,7 +73,8 @@ pub enum RateLimitDecision {
 }
 
 /// Client trust level for adaptive rate limiting
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TrustLevel {
     /// Unknown or new client
     Unknown,
@@ -30,6 +86,62 @@ pub enum TrustLevel {
     Trusted,
 }
 
+/// A request limit over a fixed window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateWindow {
+    /// Base requests allowed per window
+    pub limit: u32,
+    /// Window duration
+    pub window: Duration,
+}
+
+impl RateWindow {
+    /// Creates a window allowing `limit` requests per `window`
+    pub fn new(limit: u32, window: Duration) -> Self {
+        RateWindow { limit, window }
+    }
+}
+
+impl FromStr for RateWindow {
+    type Err = String;
+
+    /// Parses `limit/duration`, e.g. `100/1s`, `2000/1m` or `50000/1h`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (limit, window) = s
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| format!("expected limit/duration, got {s:?}"))?;
+        let limit: u32 = limit
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid limit {limit:?}: {e}"))?;
+
+        let window = window.trim();
+        let unit_at = window
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(window.len());
+        let (amount, unit) = window.split_at(unit_at);
+        let amount: u64 = if amount.is_empty() {
+            1
+        } else {
+            amount
+                .parse()
+                .map_err(|e| format!("invalid duration {window:?}: {e}"))?
+        };
+        let secs = match unit {
+            "s" => amount,
+            "m" => amount * 60,
+            "h" => amount * 3600,
+            _ => return Err(format!("unknown duration unit in {window:?}")),
+        };
+
+        if limit == 0 || secs == 0 {
+            return Err(format!("limit and duration must be greater than 0 in {s:?}"));
+        }
+        Ok(RateWindow::new(limit, Duration::from_secs(secs)))
+    }
+}
+
 /// Rate limit configuration
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
@@ -45,6 +157,64 @@ pub struct RateLimitConfig {
     pub trust_multiplier: f64,
     /// Suspicious client reduction factor
     pub suspicious_reduction_factor: f64,
+    /// Extra requests a client may spend above its limit (0 disables bursts)
+    pub burst_capacity: u32,
+    /// Time of good behavior before a new client reaches the full limit
+    /// (zero disables the warm-up ramp)
+    pub warmup_period: Duration,
+    /// Fraction of the limit granted when a client is first seen (0.0-1.0)
+    pub warmup_initial_factor: f64,
+    /// Windows enforced alongside `base_limit`/`window`
+    ///
+    /// Trust, load and warm-up scale every window alike. Bursts only extend
+    /// the primary window, so these act as hard caps.
+    pub extra_windows: Vec<RateWindow>,
+    /// Clients not seen for this long are forgotten (zero keeps them)
+    ///
+    /// Clients under a trust downgrade hold are kept until it expires.
+    pub client_idle_ttl: Duration,
+    /// Most clients tracked at once (0 is unbounded)
+    ///
+    /// The bound is approximate: it is split evenly across shards, and beyond
+    /// its share a shard discards its least recently seen client, so up to
+    /// one client more per shard may be kept, or fewer when clients hash
+    /// unevenly. Small bounds use fewer shards so each holds at least
+    /// [`MIN_SHARD_CLIENTS`]; a bound lowered by [`AdaptiveRateLimiter::update_config`]
+    /// keeps the shard count and that minimum.
+    pub max_clients: usize,
+    /// Consecutive denials or failed requests that ban a client (0 disables bans)
+    pub ban_threshold: u32,
+    /// Length of a client's first ban; each further ban doubles it
+    pub ban_duration: Duration,
+    /// Longest ban
+    pub max_ban_duration: Duration,
+}
+
+impl RateLimitConfig {
+    /// All enforced windows, primary first
+    fn windows(&self) -> impl Iterator<Item = RateWindow> + '_ {
+        std::iter::once(RateWindow::new(self.base_limit, self.window))
+            .chain(self.extra_windows.iter().copied())
+    }
+
+    /// Shards to use out of `requested`, so each holds at least
+    /// [`MIN_SHARD_CLIENTS`] of a bounded `max_clients`
+    fn shard_count(&self, requested: usize) -> usize {
+        let requested = requested.max(1);
+        match self.max_clients {
+            0 => requested,
+            max_clients => requested.min(max_clients / MIN_SHARD_CLIENTS).max(1),
+        }
+    }
+
+    /// Capacity of each of `shards` client tables
+    fn shard_capacity(&self, shards: usize) -> NonZeroUsize {
+        let capacity = self
+            .max_clients
+            .div_ceil(shards)
+            .max(MIN_SHARD_CLIENTS.min(self.max_clients));
+        NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MAX)
+    }
 }
 
 impl Default for RateLimitConfig {
@@ -56,78 +226,381 @@ impl Default for RateLimitConfig {
             load_reduction_factor: 0.5,
             trust_multiplier: 2.0,
             suspicious_reduction_factor: 0.25,
+            burst_capacity: 10,
+            warmup_period: Duration::from_secs(300),
+            warmup_initial_factor: 0.75,
+            extra_windows: Vec::new(),
+            client_idle_ttl: Duration::from_secs(3600),
+            max_clients: 100_000,
+            ban_threshold: 0,
+            ban_duration: Duration::from_secs(60),
+            max_ban_duration: Duration::from_secs(3600),
         }
     }
 }
 
+/// Request count within one window
+#[derive(Debug, Clone, Copy)]
+struct WindowCounter {
+    count: u32,
+    start: Instant,
+}
+
 /// Client rate limit state
 #[derive(Debug, Clone)]
 struct ClientState {
-    request_count: u32,
-    window_start: Instant,
+    /// One counter per configured window, primary first
+    windows: Vec<WindowCounter>,
     trust_level: TrustLevel,
     last_request: Instant,
+    /// Last check, allowed or not; drives idle eviction
+    last_seen: Instant,
+    /// Start of the current stretch of good behavior, drives the warm-up ramp
+    good_since: Instant,
+    burst_tokens: f64,
+    burst_refilled_at: Instant,
+    /// Good behavior cannot lift a downgrade before this instant
+    downgraded_until: Option<Instant>,
+    /// Requests denied since the last allowed one
+    denied_streak: u32,
+    /// Failed requests since the last successful one
+    failed_streak: u32,
+    /// Bans so far, which double the length of the next one
+    bans: u32,
+    /// Every request is denied before this instant
+    banned_until: Option<Instant>,
 }
 
+impl ClientState {
+    fn new(now: Instant, config: &RateLimitConfig) -> Self {
+        ClientState {
+            windows: config
+                .windows()
+                .map(|_| WindowCounter { count: 0, start: now })
+                .collect(),
+            trust_level: TrustLevel::Unknown,
+            last_request: now,
+            last_seen: now,
+            good_since: now,
+            burst_tokens: f64::from(config.burst_capacity),
+            burst_refilled_at: now,
+            downgraded_until: None,
+            denied_streak: 0,
+            failed_streak: 0,
+            bans: 0,
+            banned_until: None,
+        }
+    }
+
+    /// Remaining ban, if the client is banned at `now`
+    fn ban_remaining(&self, now: Instant) -> Option<Duration> {
+        self.banned_until
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Whether the client may be forgotten after `ttl` without requests
+    fn is_idle(&self, ttl: Duration, now: Instant) -> bool {
+        let held = self.downgraded_until.is_some_and(|until| now < until)
+            || self.ban_remaining(now).is_some();
+        !ttl.is_zero() && !held && now.duration_since(self.last_seen) >= ttl
+    }
+}
+
+/// Tracked clients of one shard, least recently seen first to be discarded
+struct ClientTable {
+    entries: LruCache<String, ClientState>,
+    swept_at: Instant,
+}
+
+/// Number of client table shards used by [`AdaptiveRateLimiter::new`]
+pub const DEFAULT_SHARDS: usize = 64;
+
+/// Fewest clients a shard holds when `max_clients` is bounded
+pub const MIN_SHARD_CLIENTS: usize = 64;
+
 /// Adaptive Rate Limiter
 pub struct AdaptiveRateLimiter {
-    config: RateLimitConfig,
-    clients: Arc<RwLock<HashMap<String, ClientState>>>,
-    system_load: Arc<RwLock<f64>>,
+    config: ArcSwap<RateLimitConfig>,
+    shards: Box<[Mutex<ClientTable>]>,
+    hasher: RandomState,
+    /// System load as `f64` bits
+    system_load: AtomicU64,
+    metrics: Option<RateLimiterMetrics>,
 }
 
 impl AdaptiveRateLimiter {
     pub fn new(config: RateLimitConfig) -> Self {
+        Self::with_shards(config, DEFAULT_SHARDS)
+    }
+
+    /// Creates a limiter spreading clients over up to `shards` locked tables
+    ///
+    /// One shard serializes every check behind a single lock. Fewer shards
+    /// are used when `max_clients` would leave each with fewer than
+    /// [`MIN_SHARD_CLIENTS`].
+    pub fn with_shards(config: RateLimitConfig, shards: usize) -> Self {
+        let shards = config.shard_count(shards);
+        let capacity = config.shard_capacity(shards);
+        let now = Instant::now();
         AdaptiveRateLimiter {
-            config,
-            clients: Arc::new(RwLock::new(HashMap::new())),
-            system_load: Arc::new(RwLock::new(0.0)),
+            config: ArcSwap::from_pointee(config),
+            shards: (0..shards)
+                .map(|_| {
+                    Mutex::new(ClientTable {
+                        entries: LruCache::new(capacity),
+                        swept_at: now,
+                    })
+                })
+                .collect(),
+            hasher: RandomState::new(),
+            system_load: AtomicU64::new(0.0_f64.to_bits()),
+            metrics: None,
+        }
+    }
+
+    /// Reports evictions and the number of tracked clients
+    pub fn with_metrics(mut self, metrics: RateLimiterMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Number of clients currently tracked
+    pub fn client_count(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().entries.len()).sum()
+    }
+
+    /// Tracked and banned client counts and the current system load
+    pub fn stats(&self) -> RateLimiterStats {
+        self.stats_at(Instant::now())
+    }
+
+    fn stats_at(&self, now: Instant) -> RateLimiterStats {
+        let mut stats = RateLimiterStats {
+            system_load: self.system_load(),
+            ..Default::default()
+        };
+        for shard in self.shards.iter() {
+            let clients = shard.lock();
+            stats.tracked_clients += clients.entries.len();
+            stats.banned_clients += clients
+                .entries
+                .iter()
+                .filter(|(_, state)| state.ban_remaining(now).is_some())
+                .count();
+        }
+        stats
+    }
+
+    /// Locks the shard tracking `client_id`
+    fn shard(&self, client_id: &str) -> MutexGuard<'_, ClientTable> {
+        let index = self.hasher.hash_one(client_id) as usize % self.shards.len();
+        self.shards[index].lock()
+    }
+
+    /// Returns the client's state, tracking it first if unseen
+    ///
+    /// Sweeps the shard's idle clients at most every half TTL, so they are
+    /// forgotten within one and a half `client_idle_ttl` of shard traffic.
+    fn client_entry<'a>(
+        &self,
+        clients: &'a mut ClientTable,
+        client_id: &str,
+        config: &RateLimitConfig,
+        now: Instant,
+    ) -> &'a mut ClientState {
+        let ttl = config.client_idle_ttl;
+        if !ttl.is_zero() && now.duration_since(clients.swept_at) >= ttl / 2 {
+            self.evict_idle(clients, ttl, now);
+        }
+
+        if !clients.entries.contains(client_id) {
+            let evicted = clients
+                .entries
+                .push(client_id.to_string(), ClientState::new(now, config));
+            if evicted.is_some() {
+                self.record_evictions("capacity", 1);
+            }
+            if let Some(metrics) = &self.metrics {
+                metrics.record_new_client();
+            }
+        }
+        clients
+            .entries
+            .get_mut(client_id)
+            .expect("client was just inserted")
+    }
+
+    /// Forgets clients idle for at least `ttl`
+    fn evict_idle(&self, clients: &mut ClientTable, ttl: Duration, now: Instant) {
+        let idle: Vec<String> = clients
+            .entries
+            .iter()
+            .filter(|(_, state)| state.is_idle(ttl, now))
+            .map(|(client_id, _)| client_id.clone())
+            .collect();
+        for client_id in &idle {
+            clients.entries.pop(client_id);
+        }
+        clients.swept_at = now;
+        self.record_evictions("idle", idle.len());
+    }
+
+    fn record_evictions(&self, reason: &str, count: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_evictions(reason, count);
         }
     }
 
     /// Checks if a request should be allowed
     pub async fn check(&self, client_id: &str) -> RateLimitDecision {
-        let mut clients = self.clients.write().await;
-        let now = Instant::now();
+        self.check_at(client_id, Instant::now()).await
+    }
+
+    async fn check_at(&self, client_id: &str, now: Instant) -> RateLimitDecision {
+        let mut clients = self.shard(client_id);
+        let config = self.config.load();
         
-        let state = clients.entry(client_id.to_string()).or_insert(ClientState {
-            request_count: 0,
-            window_start: now,
-            trust_level: TrustLevel::Unknown,
-            last_request: now,
-        });
+        let state = self.client_entry(&mut clients, client_id, &config, now);
+        state.last_seen = now;
 
-        // Reset window if expired
-        if now.duration_since(state.window_start) >= self.config.window {
-            state.request_count = 0;
-            state.window_start = now;
+        // Banned clients are turned away before any accounting
+        if let Some(retry_after) = state.ban_remaining(now) {
+            return RateLimitDecision::Denied { retry_after };
         }
 
-        // Calculate effective limit
-        let effective_limit = self.calculate_effective_limit(state.trust_level).await;
+        // Reset windows if expired
+        for (counter, window) in state.windows.iter_mut().zip(config.windows()) {
+            if now.duration_since(counter.start) >= window.window {
+                counter.count = 0;
+                counter.start = now;
+            }
+        }
+        Self::refill_burst(&config, state, now);
 
-        // Check if limit exceeded
-        if state.request_count >= effective_limit {
-            let retry_after = self.config.window
-                .checked_sub(now.duration_since(state.window_start))
+        // Calculate effective limits
+        let warmup = Self::warmup_factor(&config, state, now);
+        let factor = self.calculate_limit_factor(&config, state.trust_level, warmup);
+
+        // Every exceeded window must reset before the request can pass
+        let mut primary_exceeded = false;
+        let mut retry_after = None;
+        for (i, (counter, window)) in state.windows.iter().zip(config.windows()).enumerate() {
+            if counter.count < scale_limit(window.limit, factor) {
+                continue;
+            }
+            if i == 0 {
+                primary_exceeded = true;
+                continue;
+            }
+            let wait = window.window
+                .checked_sub(now.duration_since(counter.start))
                 .unwrap_or(Duration::from_secs(1));
-            
-            return RateLimitDecision::Denied { retry_after };
+            retry_after = retry_after.max(Some(wait));
+        }
+
+        // Spend burst allowance before denying on the primary window
+        if primary_exceeded {
+            if retry_after.is_none()
+                && state.trust_level != TrustLevel::Suspicious
+                && state.burst_tokens >= 1.0
+            {
+                state.burst_tokens -= 1.0;
+            } else {
+                let wait = config.window
+                    .checked_sub(now.duration_since(state.windows[0].start))
+                    .unwrap_or(Duration::from_secs(1));
+                retry_after = retry_after.max(Some(wait));
+            }
+        }
+        if let Some(retry_after) = retry_after {
+            state.denied_streak = state.denied_streak.saturating_add(1);
+            let ban = self.ban_if_exceeded(&config, state, now);
+            return RateLimitDecision::Denied { retry_after: retry_after.max(ban.unwrap_or_default()) };
         }
 
         // Allow request
-        state.request_count += 1;
+        for counter in &mut state.windows {
+            counter.count += 1;
+        }
         state.last_request = now;
+        state.denied_streak = 0;
         
         RateLimitDecision::Allowed
     }
 
+    /// Refills burst allowance at `burst_capacity` requests per window
+    fn refill_burst(config: &RateLimitConfig, state: &mut ClientState, now: Instant) {
+        let capacity = f64::from(config.burst_capacity);
+        let elapsed = now.duration_since(state.burst_refilled_at).as_secs_f64();
+        let rate = capacity / config.window.as_secs_f64().max(f64::EPSILON);
+        state.burst_tokens = (state.burst_tokens + elapsed * rate).min(capacity);
+        state.burst_refilled_at = now;
+    }
+
+    /// Bans the client once either streak reaches `ban_threshold`
+    ///
+    /// Returns the length of the new ban, if one was imposed.
+    fn ban_if_exceeded(
+        &self,
+        config: &RateLimitConfig,
+        state: &mut ClientState,
+        now: Instant,
+    ) -> Option<Duration> {
+        let threshold = config.ban_threshold;
+        if threshold == 0 || state.denied_streak.max(state.failed_streak) < threshold {
+            return None;
+        }
+
+        let ban = config
+            .ban_duration
+            .saturating_mul(2_u32.saturating_pow(state.bans))
+            .min(config.max_ban_duration);
+        state.bans = state.bans.saturating_add(1);
+        state.denied_streak = 0;
+        state.failed_streak = 0;
+        state.banned_until = Some(now + ban);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_ban();
+        }
+        Some(ban)
+    }
+
+    /// Returns the warm-up ramp factor, or `None` once the client is warmed up
+    fn warmup_factor(config: &RateLimitConfig, state: &ClientState, now: Instant) -> Option<f64> {
+        let period = config.warmup_period;
+        let elapsed = now.duration_since(state.good_since);
+        if period.is_zero() || elapsed >= period {
+            return None;
+        }
+
+        let initial = config.warmup_initial_factor.clamp(0.0, 1.0);
+        let progress = elapsed.as_secs_f64() / period.as_secs_f64();
+        Some(initial + (1.0 - initial) * progress)
+    }
+
     /// Records request outcome for trust level adjustment
     pub async fn record_outcome(&self, client_id: &str, success: bool) {
-        let mut clients = self.clients.write().await;
-        
-        if let Some(state) = clients.get_mut(client_id) {
+        self.record_outcome_at(client_id, success, Instant::now()).await;
+    }
+
+    async fn record_outcome_at(&self, client_id: &str, success: bool, now: Instant) {
+        let mut clients = self.shard(client_id);
+        let config = self.config.load();
+
+        if let Some(state) = clients.entries.peek_mut(client_id) {
+            if success {
+                state.failed_streak = 0;
+            } else {
+                state.failed_streak = state.failed_streak.saturating_add(1);
+                self.ban_if_exceeded(&config, state, now);
+            }
+
             // Adjust trust level based on behavior
+            let held = state.downgraded_until.is_some_and(|until| now < until);
+            if success && held {
+                return;
+            }
             if success {
                 state.trust_level = match state.trust_level {
                     TrustLevel::Unknown => TrustLevel::Normal,
@@ -142,77 +615,759 @@ impl AdaptiveRateLimiter {
                     TrustLevel::Unknown => TrustLevel::Suspicious,
                     TrustLevel::Suspicious => TrustLevel::Suspicious,
                 };
+                // Suspicious clients restart the warm-up once they recover
+                if state.trust_level == TrustLevel::Suspicious {
+                    state.good_since = now;
+                }
+            }
+        }
+    }
+
+    /// Replaces the configuration at runtime
+    ///
+    /// Clients keep their trust level, warm-up progress and the counters of
+    /// windows whose length is unchanged; other windows start fresh, and burst
+    /// allowances are capped at the new capacity.
+    pub async fn update_config(&self, config: RateLimitConfig) {
+        self.update_config_at(config, Instant::now()).await;
+    }
+
+    async fn update_config_at(&self, config: RateLimitConfig, now: Instant) {
+        // Every shard stays locked until the new config is stored, so no
+        // check sees converted state with the old config
+        let mut shards: Vec<_> = self.shards.iter().map(|shard| shard.lock()).collect();
+        let old = self.config.load();
+        let capacity = config.shard_capacity(shards.len());
+
+        for clients in &mut shards {
+            // Shrinking a shard discards its least recently seen clients
+            let evicted = clients.entries.len().saturating_sub(capacity.get());
+            self.record_evictions("capacity", evicted);
+            clients.entries.resize(capacity);
+
+            for (_, state) in clients.entries.iter_mut() {
+                state.windows = config
+                    .windows()
+                    .enumerate()
+                    .map(|(i, window)| match (state.windows.get(i), old.windows().nth(i)) {
+                        (Some(counter), Some(previous)) if previous.window == window.window => {
+                            *counter
+                        }
+                        _ => WindowCounter { count: 0, start: now },
+                    })
+                    .collect();
+                state.burst_tokens = state.burst_tokens.min(f64::from(config.burst_capacity));
             }
         }
+        self.config.store(Arc::new(config));
+    }
+
+    /// Returns the current configuration
+    pub fn config(&self) -> Arc<RateLimitConfig> {
+        self.config.load_full()
     }
 
     /// Updates system load metric
     pub async fn update_system_load(&self, load: f64) {
-        let mut system_load = self.system_load.write().await;
-        *system_load = load.clamp(0.0, 1.0);
+        self.system_load
+            .store(load.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    fn system_load(&self) -> f64 {
+        f64::from_bits(self.system_load.load(Ordering::Relaxed))
     }
 
     /// Sets trust level for a client
     pub async fn set_trust_level(&self, client_id: &str, level: TrustLevel) {
-        let mut clients = self.clients.write().await;
+        let mut clients = self.shard(client_id);
         
-        if let Some(state) = clients.get_mut(client_id) {
+        if let Some(state) = clients.entries.peek_mut(client_id) {
             state.trust_level = level;
         }
     }
 
-    /// Calculates effective limit based on trust and load
-    async fn calculate_effective_limit(&self, trust_level: TrustLevel) -> u32 {
-        let base = self.config.base_limit as f64;
-        let load = *self.system_load.read().await;
+    /// Marks a client suspicious for at least `hold`
+    ///
+    /// Used for downgrades reported by other services. Unlike a failed
+    /// request, successful requests cannot restore trust until the hold
+    /// expires; clients not seen yet are downgraded on their first request.
+    pub async fn downgrade_trust(&self, client_id: &str, hold: Duration) {
+        self.downgrade_trust_at(client_id, hold, Instant::now()).await;
+    }
+
+    async fn downgrade_trust_at(&self, client_id: &str, hold: Duration, now: Instant) {
+        let mut clients = self.shard(client_id);
+        let config = self.config.load();
+
+        let state = self.client_entry(&mut clients, client_id, &config, now);
+        state.trust_level = TrustLevel::Suspicious;
+        state.good_since = now;
+        state.downgraded_until = state.downgraded_until.max(Some(now + hold));
+    }
+
+    /// Lifts the ban of every client whose ID matches
+    ///
+    /// Lifted clients keep their ban count, so a repeat offender's next ban
+    /// is still longer. Returns the number of bans lifted.
+    pub async fn clear_bans(&self, matches: impl Fn(&str) -> bool) -> usize {
+        self.clear_bans_at(matches, Instant::now()).await
+    }
+
+    async fn clear_bans_at(&self, matches: impl Fn(&str) -> bool, now: Instant) -> usize {
+        let mut cleared = 0;
+        for shard in self.shards.iter() {
+            let mut clients = shard.lock();
+            for (client_id, state) in clients.entries.iter_mut() {
+                if state.ban_remaining(now).is_some() && matches(client_id) {
+                    state.banned_until = None;
+                    cleared += 1;
+                }
+            }
+        }
+        cleared
+    }
+
+    /// Lifts the ban of one client; returns whether it was banned
+    pub async fn clear_ban(&self, client_id: &str) -> bool {
+        self.clear_bans(|id| id == client_id).await > 0
+    }
+
+    /// Calculates the limit multiplier based on trust, warm-up and load
+    ///
+    /// While warming up, the ramp replaces the trust adjustment for every
+    /// non-suspicious client, so trust flips cannot make the limit oscillate.
+    /// With warm-up enabled the ramp also replaces the fixed penalty for
+    /// unknown clients, so the limit never drops when the ramp completes.
+    fn calculate_limit_factor(
+        &self,
+        config: &RateLimitConfig,
+        trust_level: TrustLevel,
+        warmup: Option<f64>,
+    ) -> f64 {
+        let load = self.system_load();
 
         // Apply load reduction if threshold exceeded
-        let load_adjusted = if load > self.config.load_threshold {
-            base * self.config.load_reduction_factor
+        let load_adjusted = if load > config.load_threshold {
+            config.load_reduction_factor
         } else {
-            base
+            1.0
         };
 
         // Apply trust level adjustment
-        let trust_adjusted = match trust_level {
-            TrustLevel::Trusted => load_adjusted * self.config.trust_multiplier,
-            TrustLevel::Normal => load_adjusted,
-            TrustLevel::Unknown => load_adjusted * 0.75,
-            TrustLevel::Suspicious => load_adjusted * self.config.suspicious_reduction_factor,
-        };
-
-        trust_adjusted.max(1.0) as u32
+        match (trust_level, warmup) {
+            (TrustLevel::Suspicious, _) => load_adjusted * config.suspicious_reduction_factor,
+            (_, Some(factor)) => load_adjusted * factor,
+            (TrustLevel::Trusted, None) => load_adjusted * config.trust_multiplier,
+            (TrustLevel::Normal, None) => load_adjusted,
+            (TrustLevel::Unknown, None) if config.warmup_period.is_zero() => {
+                load_adjusted * 0.75
+            }
+            (TrustLevel::Unknown, None) => load_adjusted,
+        }
     }
 
     /// Gets current rate limit info for a client
     pub async fn get_limit_info(&self, client_id: &str) -> RateLimitInfo {
-        let clients = self.clients.read().await;
-        let load = *self.system_load.read().await;
-
-        let (remaining, reset_at, trust_level) = if let Some(state) = clients.get(client_id) {
-            let effective_limit = self.calculate_effective_limit(state.trust_level).await;
-            let remaining = effective_limit.saturating_sub(state.request_count);
-            let reset_at = state.window_start + self.config.window;
-            (remaining, reset_at, state.trust_level)
-        } else {
-            let effective_limit = self.calculate_effective_limit(TrustLevel::Unknown).await;
-            (effective_limit, Instant::now() + self.config.window, TrustLevel::Unknown)
+        self.limit_info_at(client_id, Instant::now()).await
+    }
+
+    async fn limit_info_at(&self, client_id: &str, now: Instant) -> RateLimitInfo {
+        let clients = self.shard(client_id);
+        let load = self.system_load();
+        let config = self.config.load();
+
+        let fresh;
+        let state = match clients.entries.peek(client_id) {
+            Some(state) => state,
+            None => {
+                fresh = ClientState::new(now, &config);
+                &fresh
+            }
+        };
+        let warmup = Self::warmup_factor(&config, state, now);
+        let factor = self.calculate_limit_factor(&config, state.trust_level, warmup);
+        let trust_level = state.trust_level;
+        let banned_until = state.ban_remaining(now).map(|remaining| now + remaining);
+
+        let windows: Vec<_> = state
+            .windows
+            .iter()
+            .zip(config.windows())
+            .map(|(counter, window)| {
+                let limit = scale_limit(window.limit, factor);
+                let expired = now.duration_since(counter.start) >= window.window;
+                let (count, start) = if expired { (0, now) } else { (counter.count, counter.start) };
+                (limit, limit.saturating_sub(count), start + window.window, window.window)
+            })
+            .collect();
+
+        // Report the binding window: fewest remaining requests, then the
+        // latest reset since that is how long the client is held back
+        let (limit, remaining, reset_at, window) = windows
+            .iter()
+            .copied()
+            .min_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)))
+            .expect("at least the primary window is configured");
+        let utilization = windows
+            .iter()
+            .map(|(limit, remaining, ..)| f64::from(limit - remaining) / f64::from(*limit))
+            .fold(0.0, f64::max);
+
+        // A banned client has nothing left until both the ban and window end
+        let (remaining, reset_at, utilization) = match banned_until {
+            Some(until) => (0, reset_at.max(until), 1.0),
+            None => (remaining, reset_at, utilization),
         };
 
         RateLimitInfo {
+            limit,
             remaining,
             reset_at,
+            window,
             trust_level,
             system_load: load,
+            banned_until,
+            utilization,
         }
     }
 }
 
+/// Applies the limit multiplier to a window's base limit
+fn scale_limit(limit: u32, factor: f64) -> u32 {
+    (f64::from(limit) * factor).max(1.0) as u32
+}
+
 /// Rate limit information for headers
 #[derive(Debug, Clone)]
 pub struct RateLimitInfo {
+    pub limit: u32,
+    pub window: Duration,
     pub remaining: u32,
     pub reset_at: Instant,
     pub trust_level: TrustLevel,
     pub system_load: f64,
+    /// End of the client's ban, if it is banned
+    pub banned_until: Option<Instant>,
+    /// Share of the limit spent in the most used window (0.0-1.0)
+    pub utilization: f64,
+}
+
+/// Summary of an [`AdaptiveRateLimiter`]'s clients
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateLimiterStats {
+    /// Clients currently tracked
+    pub tracked_clients: usize,
+    /// Tracked clients currently banned
+    pub banned_clients: usize,
+    /// System load limits are scaled by (0.0-1.0)
+    pub system_load: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(burst_capacity: u32, warmup_secs: u64) -> RateLimitConfig {
+        RateLimitConfig {
+            base_limit: 100,
+            window: Duration::from_secs(60),
+            burst_capacity,
+            warmup_period: Duration::from_secs(warmup_secs),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warmup_ramps_limit_for_new_client() {
+        let limiter = AdaptiveRateLimiter::new(config(0, 300));
+        let start = Instant::now();
+
+        limiter.check_at("client", start).await;
+        let initial = limiter.limit_info_at("client", start).await.limit;
+        let halfway = limiter.limit_info_at("client", start + Duration::from_secs(150)).await.limit;
+        let warmed = limiter.limit_info_at("client", start + Duration::from_secs(300)).await.limit;
+
+        assert_eq!(initial, 75);
+        assert_eq!(halfway, 87);
+        assert_eq!(warmed, 100);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_warmup_does_not_oscillate() {
+        let limiter = AdaptiveRateLimiter::new(config(10, 300));
+        let start = Instant::now();
+        let mut previous = 0;
+
+        // One request per second through the warm-up, every 20th request fails
+        for second in 0..=300u64 {
+            let now = start + Duration::from_secs(second);
+            let decision = limiter.check_at("client", now).await;
+            assert!(matches!(decision, RateLimitDecision::Allowed), "denied at {second}s");
+            limiter.record_outcome_at("client", second % 20 != 19, now).await;
+
+            let limit = limiter.limit_info_at("client", now).await.limit;
+            assert!(limit >= previous, "limit dropped from {previous} to {limit} at {second}s");
+            previous = limit;
+        }
+        assert!(previous >= 100, "warm-up ended at {previous}");
+    }
+
+    #[tokio::test]
+    async fn test_burst_absorbs_spike_but_not_sustained_overload() {
+        let limiter = AdaptiveRateLimiter::new(config(10, 0));
+        let start = Instant::now();
+        limiter.check_at("client", start).await;
+        limiter.set_trust_level("client", TrustLevel::Normal).await;
+
+        let mut allowed = 1;
+        for _ in 0..200 {
+            if matches!(limiter.check_at("client", start).await, RateLimitDecision::Allowed) {
+                allowed += 1;
+            }
+        }
+        assert_eq!(allowed, 110);
+
+        // The burst refills at ten requests per window, not instantly
+        let next_window = start + Duration::from_secs(60);
+        let mut allowed = 0;
+        for _ in 0..200 {
+            if matches!(limiter.check_at("client", next_window).await, RateLimitDecision::Allowed) {
+                allowed += 1;
+            }
+        }
+        assert_eq!(allowed, 110);
+    }
+
+    #[tokio::test]
+    async fn test_update_config_keeps_unchanged_window_counters() {
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            extra_windows: vec![RateWindow::new(1000, Duration::from_secs(1))],
+            ..config(10, 0)
+        });
+        let start = Instant::now();
+        limiter.check_at("client", start).await;
+        limiter.set_trust_level("client", TrustLevel::Normal).await;
+        for _ in 0..49 {
+            limiter.check_at("client", start).await;
+        }
+
+        limiter
+            .update_config_at(
+                RateLimitConfig {
+                    base_limit: 60,
+                    burst_capacity: 2,
+                    extra_windows: vec![RateWindow::new(1000, Duration::from_secs(3600))],
+                    ..config(0, 0)
+                },
+                start,
+            )
+            .await;
+        assert_eq!(limiter.config().base_limit, 60);
+
+        // The primary window keeps its 50 requests; the resized extra window
+        // starts fresh and the burst is capped at the new capacity
+        let mut allowed = 0;
+        for _ in 0..100 {
+            if matches!(limiter.check_at("client", start).await, RateLimitDecision::Allowed) {
+                allowed += 1;
+            }
+        }
+        assert_eq!(allowed, 12);
+    }
+
+    #[test]
+    fn test_rate_window_parsing() {
+        assert_eq!("100/1s".parse(), Ok(RateWindow::new(100, Duration::from_secs(1))));
+        assert_eq!("50000/h".parse(), Ok(RateWindow::new(50_000, Duration::from_secs(3600))));
+        assert_eq!(" 300 / 5m ".parse(), Ok(RateWindow::new(300, Duration::from_secs(300))));
+        assert!("0/1s".parse::<RateWindow>().is_err());
+        assert!("100/1d".parse::<RateWindow>().is_err());
+        assert!("100".parse::<RateWindow>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_most_restrictive_window_wins() {
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            base_limit: 10,
+            window: Duration::from_secs(1),
+            extra_windows: vec![RateWindow::new(25, Duration::from_secs(3600))],
+            ..config(0, 0)
+        });
+        let start = Instant::now();
+        limiter.check_at("client", start).await;
+        limiter.set_trust_level("client", TrustLevel::Normal).await;
+
+        // Per-second window binds first
+        let mut allowed = 1;
+        while matches!(limiter.check_at("client", start).await, RateLimitDecision::Allowed) {
+            allowed += 1;
+        }
+        assert_eq!(allowed, 10);
+        let info = limiter.limit_info_at("client", start).await;
+        assert_eq!((info.limit, info.remaining), (10, 0));
+        assert_eq!(info.window, Duration::from_secs(1));
+
+        // Later seconds drain the hourly window, which then binds
+        let mut now = start;
+        for _ in 0..2 {
+            now += Duration::from_secs(1);
+            while matches!(limiter.check_at("client", now).await, RateLimitDecision::Allowed) {}
+        }
+        let info = limiter.limit_info_at("client", now + Duration::from_secs(1)).await;
+        assert_eq!((info.limit, info.remaining), (25, 0));
+        assert_eq!(info.window, Duration::from_secs(3600));
+
+        match limiter.check_at("client", now + Duration::from_secs(1)).await {
+            RateLimitDecision::Denied { retry_after } => {
+                assert!(retry_after > Duration::from_secs(3500));
+            }
+            RateLimitDecision::Allowed => panic!("hourly window should deny"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_burst_does_not_extend_extra_windows() {
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            base_limit: 10,
+            extra_windows: vec![RateWindow::new(12, Duration::from_secs(3600))],
+            ..config(10, 0)
+        });
+        let start = Instant::now();
+        limiter.check_at("client", start).await;
+        limiter.set_trust_level("client", TrustLevel::Normal).await;
+
+        let mut allowed = 1;
+        for _ in 0..50 {
+            if matches!(limiter.check_at("client", start).await, RateLimitDecision::Allowed) {
+                allowed += 1;
+            }
+        }
+        assert_eq!(allowed, 12);
+    }
+
+    #[tokio::test]
+    async fn test_suspicious_client_gets_no_burst_and_restarts_warmup() {
+        let limiter = AdaptiveRateLimiter::new(config(10, 300));
+        let start = Instant::now();
+        limiter.check_at("client", start).await;
+
+        let later = start + Duration::from_secs(200);
+        limiter.record_outcome_at("client", false, later).await;
+        assert_eq!(limiter.limit_info_at("client", later).await.limit, 25);
+
+        for _ in 0..30 {
+            limiter.check_at("client", later).await;
+        }
+        assert!(matches!(
+            limiter.check_at("client", later).await,
+            RateLimitDecision::Denied { .. }
+        ));
+
+        limiter.record_outcome_at("client", true, later).await;
+        assert_eq!(limiter.limit_info_at("client", later).await.limit, 75);
+    }
+
+    #[tokio::test]
+    async fn test_downgrade_holds_until_expiry() {
+        let limiter = AdaptiveRateLimiter::new(config(10, 300));
+        let start = Instant::now();
+        limiter.check_at("client", start).await;
+        limiter.record_outcome_at("client", true, start).await;
+
+        limiter
+            .downgrade_trust_at("client", Duration::from_secs(60), start)
+            .await;
+        assert_eq!(limiter.limit_info_at("client", start).await.limit, 25);
+
+        let held = start + Duration::from_secs(30);
+        limiter.record_outcome_at("client", true, held).await;
+        assert_eq!(limiter.limit_info_at("client", held).await.limit, 25);
+
+        let expired = start + Duration::from_secs(60);
+        limiter.record_outcome_at("client", true, expired).await;
+        assert_eq!(limiter.limit_info_at("client", expired).await.limit, 80);
+
+        limiter
+            .downgrade_trust_at("unseen", Duration::from_secs(60), start)
+            .await;
+        assert_eq!(limiter.limit_info_at("unseen", start).await.limit, 25);
+    }
+
+    fn ban_config(threshold: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            base_limit: 2,
+            ban_threshold: threshold,
+            ban_duration: Duration::from_secs(10),
+            max_ban_duration: Duration::from_secs(25),
+            ..config(0, 0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_denials_ban_with_doubling_duration() {
+        // Unknown clients get 0.75x without warm-up, so 2/1m allows 1
+        let limiter = AdaptiveRateLimiter::new(ban_config(3));
+        let mut now = Instant::now();
+
+        for expected_ban in [10, 20, 25] {
+            assert!(matches!(limiter.check_at("client", now).await, RateLimitDecision::Allowed));
+            for _ in 0..3 {
+                limiter.check_at("client", now).await;
+            }
+            let info = limiter.limit_info_at("client", now).await;
+            assert_eq!(info.banned_until, Some(now + Duration::from_secs(expected_ban)));
+            assert_eq!(info.remaining, 0);
+
+            // Both the ban and the window are over a minute later
+            now += Duration::from_secs(60);
+        }
+
+        // A denial short of the threshold does not ban
+        assert!(matches!(limiter.check_at("client", now).await, RateLimitDecision::Allowed));
+        limiter.check_at("client", now).await;
+        assert!(limiter.limit_info_at("client", now).await.banned_until.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_utilization_tracks_most_used_window() {
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            extra_windows: vec![RateWindow::new(10, Duration::from_secs(1))],
+            ..config(0, 0)
+        });
+        let start = Instant::now();
+        assert_eq!(limiter.limit_info_at("client", start).await.utilization, 0.0);
+
+        // Unknown clients get 0.75x, so the per-second window fills after 7
+        for _ in 0..9 {
+            limiter.check_at("client", start).await;
+        }
+        assert_eq!(limiter.limit_info_at("client", start).await.utilization, 1.0);
+
+        let info = limiter.limit_info_at("client", start + Duration::from_secs(1)).await;
+        assert_eq!(info.utilization, 7.0 / 75.0);
+    }
+
+    #[tokio::test]
+    async fn test_ban_denies_before_accounting() {
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            ban_duration: Duration::from_secs(120),
+            max_ban_duration: Duration::from_secs(120),
+            ..ban_config(2)
+        });
+        let start = Instant::now();
+        limiter.check_at("client", start).await;
+        limiter.check_at("client", start).await;
+        limiter.check_at("client", start).await;
+
+        let later = start + Duration::from_secs(90);
+        match limiter.check_at("client", later).await {
+            RateLimitDecision::Denied { retry_after } => {
+                assert_eq!(retry_after, Duration::from_secs(30));
+            }
+            RateLimitDecision::Allowed => panic!("banned client was allowed"),
+        }
+        assert!(matches!(
+            limiter.check_at("client", start + Duration::from_secs(120)).await,
+            RateLimitDecision::Allowed
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_requests_ban_until_cleared() {
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            base_limit: 100,
+            ..ban_config(3)
+        });
+        let start = Instant::now();
+
+        // Successes in between reset the streak
+        for success in [false, false, true, false, false] {
+            limiter.check_at("client", start).await;
+            limiter.record_outcome_at("client", success, start).await;
+        }
+        assert!(limiter.limit_info_at("client", start).await.banned_until.is_none());
+
+        limiter.record_outcome_at("client", false, start).await;
+        assert!(matches!(
+            limiter.check_at("client", start).await,
+            RateLimitDecision::Denied { .. }
+        ));
+        limiter.record_outcome_at("client", true, start).await;
+        assert!(limiter.limit_info_at("client", start).await.banned_until.is_some());
+        assert_eq!(limiter.stats_at(start).banned_clients, 1);
+
+        assert!(!limiter.clear_ban("other").await);
+        assert!(limiter.clear_ban("client").await);
+        assert!(!limiter.clear_ban("client").await);
+        assert_eq!(limiter.stats_at(start).banned_clients, 0);
+        assert!(matches!(limiter.check_at("client", start).await, RateLimitDecision::Allowed));
+    }
+
+    #[tokio::test]
+    async fn test_idle_clients_are_forgotten_unless_held() {
+        let registry = prometheus::Registry::new();
+        let limiter = AdaptiveRateLimiter::with_shards(
+            RateLimitConfig {
+                client_idle_ttl: Duration::from_secs(600),
+                ..config(10, 300)
+            },
+            1,
+        )
+        .with_metrics(RateLimiterMetrics::new(&registry).unwrap());
+        let start = Instant::now();
+        limiter.check_at("idle", start).await;
+        limiter.check_at("active", start).await;
+        limiter
+            .downgrade_trust_at("held", Duration::from_secs(3600), start)
+            .await;
+
+        // Denied requests still count as activity
+        let later = start + Duration::from_secs(400);
+        for _ in 0..200 {
+            limiter.check_at("active", later).await;
+        }
+
+        limiter.check_at("new", start + Duration::from_secs(700)).await;
+        assert_eq!(limiter.client_count(), 3);
+        assert!(!limiter.shard("idle").entries.contains("idle"));
+        assert!(limiter.shard("held").entries.contains("held"));
+
+        let metrics = limiter.metrics.as_ref().unwrap();
+        assert_eq!(metrics.evictions.with_label_values(&["idle"]).get(), 1.0);
+        assert_eq!(metrics.clients.get(), 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_max_clients_discards_least_recently_seen() {
+        let registry = prometheus::Registry::new();
+        let limiter = AdaptiveRateLimiter::with_shards(
+            RateLimitConfig {
+                max_clients: 3,
+                ..config(10, 300)
+            },
+            1,
+        )
+        .with_metrics(RateLimiterMetrics::new(&registry).unwrap());
+        let start = Instant::now();
+        for client in ["a", "b", "c"] {
+            limiter.check_at(client, start).await;
+        }
+        limiter.check_at("a", start).await;
+
+        limiter.check_at("scanner-1", start).await;
+        assert!(limiter.shard("a").entries.contains("a"));
+        assert!(!limiter.shard("b").entries.contains("b"));
+
+        limiter
+            .update_config_at(RateLimitConfig { max_clients: 1, ..config(10, 300) }, start)
+            .await;
+        assert_eq!(limiter.client_count(), 1);
+
+        let metrics = limiter.metrics.as_ref().unwrap();
+        assert_eq!(metrics.evictions.with_label_values(&["capacity"]).get(), 3.0);
+        assert_eq!(metrics.clients.get(), 1.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_checks_across_shards() {
+        let limiter = Arc::new(AdaptiveRateLimiter::new(RateLimitConfig {
+            max_clients: 1000,
+            ..config(0, 0)
+        }));
+        limiter.check_at("shared", Instant::now()).await;
+        limiter.set_trust_level("shared", TrustLevel::Normal).await;
+
+        let tasks: Vec<_> = (0..8)
+            .map(|task| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move {
+                    let mut allowed = 0;
+                    for i in 0..500 {
+                        limiter.check(&format!("client-{task}-{i}")).await;
+                        if matches!(limiter.check("shared").await, RateLimitDecision::Allowed) {
+                            allowed += 1;
+                        }
+                    }
+                    allowed
+                })
+            })
+            .collect();
+        let mut allowed = 1;
+        for task in tasks {
+            allowed += task.await.unwrap();
+        }
+
+        // The shared client's limit holds under contention, and the bound
+        // exceeds max_clients by at most the rounding of each shard's share
+        assert_eq!(allowed, 100);
+        let shards = limiter.shards.len();
+        assert_eq!(shards, 1000 / MIN_SHARD_CLIENTS);
+        assert!(limiter.client_count() < 1000 + shards);
+    }
+
+    #[tokio::test]
+    async fn test_small_max_clients_uses_fewer_shards() {
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            max_clients: 3,
+            ..config(10, 300)
+        });
+        assert_eq!(limiter.shards.len(), 1);
+        for i in 0..10 {
+            limiter.check(&format!("client-{i}")).await;
+        }
+        assert_eq!(limiter.client_count(), 3);
+
+        // A lowered bound keeps the shard count and its minimum share
+        let limiter = AdaptiveRateLimiter::new(RateLimitConfig {
+            max_clients: 640,
+            ..config(10, 300)
+        });
+        assert_eq!(limiter.shards.len(), 10);
+        let lowered = RateLimitConfig {
+            max_clients: 100,
+            ..config(10, 300)
+        };
+        assert_eq!(lowered.shard_capacity(10).get(), MIN_SHARD_CLIENTS);
+        assert_eq!(lowered.shard_capacity(1).get(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restores_trust_counters_and_bans() {
+        let old = AdaptiveRateLimiter::new(config(0, 0));
+        let start = Instant::now();
+        for _ in 0..3 {
+            old.check_at("trusted", start).await;
+        }
+        old.set_trust_level("trusted", TrustLevel::Trusted).await;
+        old.downgrade_trust_at("reported", Duration::from_secs(600), start).await;
+
+        let exported_at = start + Duration::from_secs(10);
+        let json = serde_json::to_string(&old.export_snapshot_at(exported_at)).unwrap();
+        let snapshot: RateLimiterSnapshot = serde_json::from_str(&json).unwrap();
+
+        let new = AdaptiveRateLimiter::new(config(0, 0));
+        let imported_at = Instant::now() + Duration::from_secs(3600);
+        assert_eq!(new.import_snapshot_at(&snapshot, imported_at), Ok(2));
+
+        let before = old.limit_info_at("trusted", exported_at).await;
+        let after = new.limit_info_at("trusted", imported_at).await;
+        assert_eq!(after.trust_level, TrustLevel::Trusted);
+        assert_eq!((after.limit, after.remaining), (before.limit, before.remaining));
+        assert_eq!(after.remaining, 197);
+
+        // The hold still has 590s to run, so success cannot restore trust
+        new.record_outcome_at("reported", true, imported_at).await;
+        let reported = new.limit_info_at("reported", imported_at).await;
+        assert_eq!(reported.trust_level, TrustLevel::Suspicious);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_of_other_version_is_rejected() {
+        let limiter = AdaptiveRateLimiter::new(config(0, 0));
+        let snapshot = RateLimiterSnapshot {
+            version: SNAPSHOT_VERSION + 1,
+            clients: Vec::new(),
+        };
+        assert!(limiter.import_snapshot(&snapshot).is_err());
+    }
 }
@@ -0,0 +1,60 @@
+//! Soft Quota Warnings
+//!
+//! Before a client is rate limited, responses carry an advisory
+//! `x-quota-warning` header with the highest `QUOTA_WARNING_THRESHOLDS`
+//! percentage its utilization has crossed, e.g. `x-quota-warning: 90%`, so
+//! well-behaved clients can slow down on their own.
+
+/// Header, or gRPC response metadata key, carrying the warning
+pub const QUOTA_WARNING_HEADER: &str = "x-quota-warning";
+
+/// Utilization percentages that trigger a warning
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuotaWarnings {
+    /// Thresholds in ascending order
+    thresholds: Vec<u8>,
+}
+
+impl QuotaWarnings {
+    /// Warns at each of `thresholds` percent (1-100)
+    pub fn new(mut thresholds: Vec<u8>) -> Self {
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        Self { thresholds }
+    }
+
+    /// Builds the thresholds from configuration
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self::new(config.quota_warning_thresholds.clone())
+    }
+
+    /// Whether any threshold is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.thresholds.is_empty()
+    }
+
+    /// Highest threshold crossed at `utilization` (0.0-1.0), if any
+    pub fn level(&self, utilization: f64) -> Option<u8> {
+        let percent = utilization * 100.0;
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|&&threshold| percent >= f64::from(threshold))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_is_highest_threshold_crossed() {
+        let warnings = QuotaWarnings::new(vec![90, 80, 90]);
+        assert!(warnings.is_enabled());
+        assert_eq!(warnings.level(0.5), None);
+        assert_eq!(warnings.level(0.8), Some(80));
+        assert_eq!(warnings.level(0.95), Some(90));
+        assert_eq!(QuotaWarnings::default().level(1.0), None);
+    }
+}
@@ -0,0 +1,189 @@
+//! Portable snapshots of client trust and limit state
+//!
+//! On blue/green deploys the old replica set exports its clients and the new
+//! one imports them, so known clients keep their trust, warm-up progress,
+//! counters and bans instead of starting cold. Instants are stored relative
+//! to the export, and fields at their default are left out to keep the
+//! snapshot compact.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{AdaptiveRateLimiter, ClientState, RateLimitConfig, TrustLevel, WindowCounter};
+
+/// Snapshot format written by this build
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Client state of an [`AdaptiveRateLimiter`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimiterSnapshot {
+    /// Snapshot format, see [`SNAPSHOT_VERSION`]
+    pub version: u32,
+    /// Tracked clients
+    pub clients: Vec<ClientSnapshot>,
+}
+
+/// State of one client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientSnapshot {
+    /// Rate limiter key of the client
+    pub id: String,
+    /// Trust level
+    pub trust: TrustLevel,
+    /// Seconds of good behavior so far, for the warm-up ramp
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub good_secs: u64,
+    /// Counters of the configured windows, primary first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<WindowSnapshot>,
+    /// Burst allowance left
+    pub burst: f64,
+    /// Seconds left of a trust downgrade hold
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub held_secs: u64,
+    /// Bans so far
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub bans: u32,
+    /// Seconds left of the current ban
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub banned_secs: u64,
+}
+
+/// Request count of one window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    /// Window length in seconds; counters of resized windows are dropped
+    pub window_secs: u64,
+    /// Requests counted in the current window
+    pub count: u32,
+    /// Milliseconds since the current window started
+    pub age_ms: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_zero_u32(value: &u32) -> bool {
+    *value == 0
+}
+
+/// `now` moved back by `elapsed`, or `now` if the clock does not go back that far
+fn before(now: Instant, elapsed: Duration) -> Instant {
+    now.checked_sub(elapsed).unwrap_or(now)
+}
+
+impl ClientState {
+    fn snapshot(&self, id: &str, config: &RateLimitConfig, now: Instant) -> ClientSnapshot {
+        let remaining = |until: Option<Instant>| {
+            until.map_or(0, |until| until.saturating_duration_since(now).as_secs())
+        };
+        ClientSnapshot {
+            id: id.to_string(),
+            trust: self.trust_level,
+            good_secs: now.saturating_duration_since(self.good_since).as_secs(),
+            windows: self
+                .windows
+                .iter()
+                .zip(config.windows())
+                .map(|(counter, window)| WindowSnapshot {
+                    window_secs: window.window.as_secs(),
+                    count: counter.count,
+                    age_ms: now.saturating_duration_since(counter.start).as_millis() as u64,
+                })
+                .collect(),
+            burst: self.burst_tokens,
+            held_secs: remaining(self.downgraded_until),
+            bans: self.bans,
+            banned_secs: remaining(self.banned_until),
+        }
+    }
+
+    fn restore(snapshot: &ClientSnapshot, config: &RateLimitConfig, now: Instant) -> Self {
+        let mut state = ClientState::new(now, config);
+        state.trust_level = snapshot.trust;
+        state.good_since = before(now, Duration::from_secs(snapshot.good_secs));
+        for ((counter, window), saved) in state
+            .windows
+            .iter_mut()
+            .zip(config.windows())
+            .zip(&snapshot.windows)
+        {
+            let age = Duration::from_millis(saved.age_ms);
+            if saved.window_secs == window.window.as_secs() && age < window.window {
+                *counter = WindowCounter {
+                    count: saved.count,
+                    start: before(now, age),
+                };
+            }
+        }
+        state.burst_tokens = snapshot.burst.clamp(0.0, f64::from(config.burst_capacity));
+        state.downgraded_until =
+            (snapshot.held_secs > 0).then(|| now + Duration::from_secs(snapshot.held_secs));
+        state.bans = snapshot.bans;
+        state.banned_until =
+            (snapshot.banned_secs > 0).then(|| now + Duration::from_secs(snapshot.banned_secs));
+        state
+    }
+}
+
+impl AdaptiveRateLimiter {
+    /// Captures the state of every tracked client
+    ///
+    /// Shards are captured one after another, so checks can proceed while
+    /// the snapshot is taken.
+    pub fn export_snapshot(&self) -> RateLimiterSnapshot {
+        self.export_snapshot_at(Instant::now())
+    }
+
+    pub(super) fn export_snapshot_at(&self, now: Instant) -> RateLimiterSnapshot {
+        let config = self.config.load();
+        let clients = self
+            .shards
+            .iter()
+            .flat_map(|shard| {
+                shard
+                    .lock()
+                    .entries
+                    .iter()
+                    .map(|(id, state)| state.snapshot(id, &config, now))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        RateLimiterSnapshot {
+            version: SNAPSHOT_VERSION,
+            clients,
+        }
+    }
+
+    /// Replaces the state of the snapshot's clients with the exported one
+    ///
+    /// Clients not in the snapshot are kept. Counters of windows whose length
+    /// differs from the current configuration start fresh. Returns the number
+    /// of clients imported.
+    pub fn import_snapshot(&self, snapshot: &RateLimiterSnapshot) -> Result<usize, String> {
+        self.import_snapshot_at(snapshot, Instant::now())
+    }
+
+    pub(super) fn import_snapshot_at(
+        &self,
+        snapshot: &RateLimiterSnapshot,
+        now: Instant,
+    ) -> Result<usize, String> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "unsupported snapshot version {} (expected {SNAPSHOT_VERSION})",
+                snapshot.version
+            ));
+        }
+
+        let config = self.config.load();
+        for client in &snapshot.clients {
+            let mut clients = self.shard(&client.id);
+            let state = self.client_entry(&mut clients, &client.id, &config, now);
+            *state = ClientState::restore(client, &config, now);
+        }
+        Ok(snapshot.clients.len())
+    }
+}
@@ -0,0 +1,348 @@
+//! Configuration Reload
+//!
+//! Re-reads the configuration on SIGHUP, or when the file named by
+//! `CONFIG_FILE` changes, and applies the settings that can change without a
+//! restart: rate limits, circuit breaker thresholds and the JWKS URL and cache
+//! TTL. Other changed settings are logged and keep their startup values until
+//! the next restart. An invalid configuration is rejected as a whole and the
+//! running one stays in effect.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use arc_swap::ArcSwap;
+use rust_common::{CircuitBreaker, PlatformError};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::error::AuthEdgeError;
+use crate::jwt::{JwkCache, JwtValidator};
+use crate::observability::ConfigReloadMetrics;
+use crate::rate_limiter::{AdaptiveRateLimiter, KeyedRateLimiter};
+
+/// Result of a successful reload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReloadOutcome {
+    /// Reloadable settings changed and were applied
+    Applied,
+    /// No reloadable setting changed
+    Unchanged,
+}
+
+impl ReloadOutcome {
+    /// Metric label for this outcome
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Applied => "applied",
+            Self::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Applies configuration changes to the running components
+pub struct ConfigReloader {
+    current: ArcSwap<Config>,
+    jwt_validator: Arc<JwtValidator>,
+    rate_limiters: Vec<Arc<AdaptiveRateLimiter>>,
+    keyed_rate_limiters: Vec<Arc<KeyedRateLimiter>>,
+    circuit_breakers: Vec<Arc<CircuitBreaker>>,
+    metrics: Option<ConfigReloadMetrics>,
+}
+
+impl ConfigReloader {
+    /// Creates a reloader starting from the configuration in effect
+    pub fn new(config: Config, jwt_validator: Arc<JwtValidator>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(config),
+            jwt_validator,
+            rate_limiters: Vec::new(),
+            keyed_rate_limiters: Vec::new(),
+            circuit_breakers: Vec::new(),
+            metrics: None,
+        }
+    }
+
+    /// Adds a rate limiter to reconfigure
+    pub fn with_rate_limiter(mut self, limiter: Arc<AdaptiveRateLimiter>) -> Self {
+        self.rate_limiters.push(limiter);
+        self
+    }
+
+    /// Adds a keyed rate limiter to reconfigure, including its `RATE_LIMIT_POLICY`
+    pub fn with_keyed_rate_limiter(mut self, limiter: Arc<KeyedRateLimiter>) -> Self {
+        self.keyed_rate_limiters.push(limiter);
+        self
+    }
+
+    /// Adds a circuit breaker to reconfigure
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breakers.push(circuit_breaker);
+        self
+    }
+
+    /// Records reload attempts
+    pub fn with_metrics(mut self, metrics: ConfigReloadMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Configuration currently in effect
+    pub fn current(&self) -> Arc<Config> {
+        self.current.load_full()
+    }
+
+    /// Re-reads the configuration from the environment and `CONFIG_FILE`
+    pub async fn reload(&self) -> Result<ReloadOutcome, AuthEdgeError> {
+        let result = match Config::from_env() {
+            Ok(next) => self.apply(next).await,
+            Err(e) => Err(AuthEdgeError::Platform(PlatformError::InvalidInput(format!(
+                "invalid configuration: {e}"
+            )))),
+        };
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record(result.as_ref().map_or("failed", |outcome| outcome.as_str()));
+        }
+        match &result {
+            Ok(outcome) => info!(outcome = outcome.as_str(), "Configuration reloaded"),
+            Err(e) => error!(error = %e, "Configuration reload failed, keeping current settings"),
+        }
+        result
+    }
+
+    /// Applies the reloadable settings of `next`
+    pub async fn apply(&self, next: Config) -> Result<ReloadOutcome, AuthEdgeError> {
+        let current = self.current.load_full();
+        if next.requires_restart(&current) {
+            warn!("Configuration changes besides rate limits, circuit breakers and JWKS take effect on restart");
+        }
+
+        let jwks_changed = next.jwks_url != current.jwks_url
+            || next.jwks_cache_ttl_seconds != current.jwks_cache_ttl_seconds
+            || next.jwks_refetch_interval_seconds != current.jwks_refetch_interval_seconds
+            || next.jwks_negative_cache_ttl_seconds != current.jwks_negative_cache_ttl_seconds;
+        if jwks_changed {
+            let jwk_cache = JwkCache::new(&next).await?;
+            self.jwt_validator.replace_jwk_cache(Arc::new(jwk_cache));
+            // Cache hits skip the signature check, so tokens verified against
+            // the old key set must be checked again
+            let flushed = self.jwt_validator.clear_cached();
+            info!(flushed, "Validated-token cache flushed after JWKS change");
+        }
+
+        let rate_limits_changed = next.rate_limit_base != current.rate_limit_base
+            || next.rate_limit_burst != current.rate_limit_burst
+            || next.rate_limit_warmup_secs != current.rate_limit_warmup_secs
+            || next.rate_limit_windows != current.rate_limit_windows;
+        if rate_limits_changed {
+            for limiter in &self.rate_limiters {
+                limiter.update_config(next.rate_limit_config()).await;
+            }
+        }
+        let policy_changed = next.rate_limit_policy != current.rate_limit_policy;
+        if rate_limits_changed || policy_changed {
+            for limiter in &self.keyed_rate_limiters {
+                limiter
+                    .update_policy(&next.rate_limit_policy, &next.rate_limit_config())
+                    .await;
+            }
+        }
+
+        let breakers_changed = next.circuit_breaker_failure_threshold
+            != current.circuit_breaker_failure_threshold
+            || next.circuit_breaker_timeout_seconds != current.circuit_breaker_timeout_seconds;
+        if breakers_changed {
+            for circuit_breaker in &self.circuit_breakers {
+                circuit_breaker.update_config(next.circuit_breaker_config());
+            }
+        }
+
+        let outcome = if jwks_changed || rate_limits_changed || policy_changed || breakers_changed {
+            ReloadOutcome::Applied
+        } else {
+            ReloadOutcome::Unchanged
+        };
+        // Stored even when only restart-bound settings changed, so the
+        // warning is not repeated on every later reload
+        self.current.store(Arc::new(next));
+        Ok(outcome)
+    }
+
+    /// Reloads on SIGHUP and, when `CONFIG_FILE` is set, whenever the file's
+    /// modification time changes
+    pub async fn run(self: Arc<Self>) {
+        #[cfg(unix)]
+        let mut hangup =
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                Ok(hangup) => Some(hangup),
+                Err(e) => {
+                    warn!(error = %e, "Failed to install SIGHUP handler");
+                    None
+                }
+            };
+
+        let mut modified = self.current().config_file.as_deref().and_then(modified_at);
+        loop {
+            let config = self.current();
+            let poll = async {
+                match (&config.config_file, config.config_watch_interval_secs) {
+                    (Some(_), secs) if secs > 0 => {
+                        tokio::time::sleep(Duration::from_secs(secs)).await;
+                    }
+                    _ => std::future::pending::<()>().await,
+                }
+            };
+
+            #[cfg(unix)]
+            let sighup = async {
+                match hangup.as_mut() {
+                    Some(hangup) => hangup.recv().await,
+                    None => std::future::pending().await,
+                }
+            };
+            #[cfg(not(unix))]
+            let sighup = std::future::pending::<Option<()>>();
+
+            tokio::select! {
+                _ = sighup => {
+                    info!("Received SIGHUP, reloading configuration");
+                }
+                () = poll => {
+                    let latest = config.config_file.as_deref().and_then(modified_at);
+                    if latest == modified {
+                        continue;
+                    }
+                    info!("Config file changed, reloading configuration");
+                }
+            }
+
+            let _ = self.reload().await;
+            modified = self.current().config_file.as_deref().and_then(modified_at);
+        }
+    }
+}
+
+/// Modification time of a file, if it can be read
+fn modified_at(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(Path::new(path)).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EnvSource;
+    use crate::jwt::{Claims, ValidationCache};
+    use crate::observability::ValidationCacheMetrics;
+    use jsonwebtoken::Header;
+    use prometheus::Registry;
+    use std::collections::HashMap;
+    use std::num::NonZeroUsize;
+
+    fn config(vars: &[(&str, &str)]) -> Config {
+        let overrides: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Config::from_source(&EnvSource::with_overrides(overrides)).unwrap()
+    }
+
+    async fn reloader(config: Config) -> ConfigReloader {
+        let jwk_cache = Arc::new(JwkCache::new(&config).await.unwrap());
+        let limiter = Arc::new(AdaptiveRateLimiter::new(config.rate_limit_config()));
+        let keyed = Arc::new(KeyedRateLimiter::from_config(&config));
+        let circuit_breaker = Arc::new(CircuitBreaker::new(config.circuit_breaker_config()));
+        ConfigReloader::new(config, Arc::new(JwtValidator::new(jwk_cache)))
+            .with_rate_limiter(limiter)
+            .with_keyed_rate_limiter(keyed)
+            .with_circuit_breaker(circuit_breaker)
+    }
+
+    #[tokio::test]
+    async fn test_apply_updates_reloadable_components() {
+        let reloader = reloader(config(&[("RATE_LIMIT_BASE", "100")])).await;
+        let keys = reloader.jwt_validator.default_keys();
+
+        let outcome = reloader
+            .apply(config(&[
+                ("RATE_LIMIT_BASE", "40"),
+                ("CB_FAILURE_THRESHOLD", "2"),
+                ("JWKS_URL", "http://keys.internal/jwks.json"),
+            ]))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome, ReloadOutcome::Applied);
+        assert_eq!(reloader.rate_limiters[0].config().base_limit, 40);
+        assert_eq!(reloader.circuit_breakers[0].config().failure_threshold, 2);
+        assert!(!Arc::ptr_eq(&keys, &reloader.jwt_validator.default_keys()));
+        assert_eq!(reloader.current().rate_limit_base, 40);
+    }
+
+    #[tokio::test]
+    async fn test_apply_replaces_rate_limit_policy() {
+        let reloader = reloader(config(&[])).await;
+        assert!(reloader.keyed_rate_limiters[0].is_empty());
+
+        let outcome = reloader
+            .apply(config(&[(
+                "RATE_LIMIT_POLICY",
+                r#"[{"dimensions": ["source_ip"], "limit": "10/1s"}]"#,
+            )]))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome, ReloadOutcome::Applied);
+        assert!(!reloader.keyed_rate_limiters[0].is_empty());
+        assert!(!reloader.current().requires_restart(&config(&[])));
+    }
+
+    #[tokio::test]
+    async fn test_jwks_change_flushes_validation_cache() {
+        let config = config(&[]);
+        let jwk_cache = Arc::new(JwkCache::new(&config).await.unwrap());
+        let cache = ValidationCache::new(
+            NonZeroUsize::new(4).unwrap(),
+            Duration::from_secs(60),
+            ValidationCacheMetrics::new(&Registry::new()).unwrap(),
+        );
+        for jti in ["a", "b"] {
+            let claims = Claims {
+                iss: "https://idp.example".to_string(),
+                sub: "user".to_string(),
+                aud: vec![],
+                exp: chrono::Utc::now().timestamp() + 600,
+                iat: chrono::Utc::now().timestamp(),
+                nbf: None,
+                jti: jti.to_string(),
+                session_id: None,
+                scopes: None,
+                custom: HashMap::new(),
+            };
+            cache.insert(jti, &Header::default(), &claims);
+        }
+        let validator = Arc::new(JwtValidator::new(jwk_cache).with_cache(cache));
+        let reloader = ConfigReloader::new(config, validator.clone());
+
+        reloader.apply(self::config(&[("PORT", "50099")])).await.unwrap();
+        assert!(validator.invalidate_cached("a"));
+
+        reloader
+            .apply(self::config(&[("JWKS_URL", "http://keys.internal/jwks.json")]))
+            .await
+            .unwrap();
+        assert!(!validator.invalidate_cached("b"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_unchanged_keeps_key_set() {
+        let reloader = reloader(config(&[])).await;
+        let keys = reloader.jwt_validator.default_keys();
+
+        let outcome = reloader.apply(config(&[("PORT", "50099")])).await.unwrap();
+
+        assert_eq!(outcome, ReloadOutcome::Unchanged);
+        assert!(Arc::ptr_eq(&keys, &reloader.jwt_validator.default_keys()));
+        assert_eq!(reloader.current().port, 50099);
+    }
+}
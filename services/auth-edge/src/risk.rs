@@ -0,0 +1,184 @@
+//! Risk Signals
+//!
+//! A valid token whose holder may call the route can still arrive in a risky
+//! context: a peer address with a poor reputation, a second country minutes
+//! after the first, an unmanaged device. Once a token is validated and its
+//! route authorized, a [`RiskEvaluator`] may veto the request or ask for a
+//! step-up, in which case the response names the `acr` values the holder
+//! must re-authenticate with (RFC 9470). The default evaluator allows every
+//! request; deployments plug in their own with
+//! `AuthEdgeServiceImpl::with_risk_evaluator`. Like route authorization,
+//! evaluation fails closed.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::AuthEdgeError;
+use crate::jwt::claims::Claims;
+
+/// Everything a risk evaluator may base its decision on
+#[derive(Debug, Clone, Copy)]
+pub struct RiskInput<'a> {
+    /// Validated claims
+    pub claims: &'a Claims,
+    /// Route being called; empty when the consumer sent none
+    pub route: &'a str,
+    /// Tenant the request targets; empty when the consumer sent none
+    pub tenant: &'a str,
+    /// Calling client (API key ID or peer IP)
+    pub client: &'a str,
+    /// End user's IP address, when known
+    pub client_ip: Option<IpAddr>,
+    /// Further signals forwarded by the consumer, such as device posture
+    pub context: &'a HashMap<String, String>,
+}
+
+impl RiskInput<'_> {
+    /// Authentication context class of the token (`acr` claim)
+    pub fn acr(&self) -> Option<&str> {
+        self.claims.custom.get("acr").and_then(|acr| acr.as_str())
+    }
+}
+
+/// Outcome of a risk evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskDecision {
+    /// The request may proceed
+    Allow,
+    /// The request is refused whatever the authentication
+    Deny {
+        /// Why the request was refused
+        reason: String,
+    },
+    /// The holder must authenticate again, more strongly
+    StepUp {
+        /// Acceptable `acr` values, in order of preference
+        acr_values: Vec<String>,
+        /// Maximum age of the new authentication, if restricted
+        max_age: Option<Duration>,
+    },
+}
+
+impl RiskDecision {
+    /// A deny decision
+    pub fn deny(reason: impl Into<String>) -> Self {
+        Self::Deny {
+            reason: reason.into(),
+        }
+    }
+
+    /// A step-up decision accepting any of `acr_values`
+    pub fn step_up<I, S>(acr_values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::StepUp {
+            acr_values: acr_values.into_iter().map(Into::into).collect(),
+            max_age: None,
+        }
+    }
+
+    /// Converts the decision into an error for the holder of `claims`
+    ///
+    /// A step-up the token already satisfies, because its `acr` is one of
+    /// the requested values, is not asked for again.
+    pub fn into_result(self, input: &RiskInput<'_>) -> Result<(), AuthEdgeError> {
+        match self {
+            Self::Allow => Ok(()),
+            Self::Deny { reason } => Err(AuthEdgeError::AccessDenied { reason }),
+            Self::StepUp { acr_values, .. }
+                if input.acr().is_some_and(|acr| acr_values.iter().any(|v| v == acr)) =>
+            {
+                Ok(())
+            }
+            Self::StepUp {
+                acr_values,
+                max_age,
+            } => Err(AuthEdgeError::StepUpRequired {
+                acr_values,
+                max_age,
+            }),
+        }
+    }
+}
+
+/// Vetoes or steps up otherwise valid tokens based on risk signals
+#[async_trait]
+pub trait RiskEvaluator: Send + Sync {
+    /// Evaluates the request; errors mean the risk could not be assessed
+    async fn evaluate(&self, input: &RiskInput<'_>) -> Result<RiskDecision, AuthEdgeError>;
+}
+
+/// Evaluator allowing every request
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopRiskEvaluator;
+
+#[async_trait]
+impl RiskEvaluator for NoopRiskEvaluator {
+    async fn evaluate(&self, _input: &RiskInput<'_>) -> Result<RiskDecision, AuthEdgeError> {
+        Ok(RiskDecision::Allow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(acr: Option<&str>) -> Claims {
+        serde_json::from_value(serde_json::json!({
+            "iss": "https://auth.platform",
+            "sub": "user-1",
+            "aud": "api",
+            "exp": 2_000,
+            "iat": 1_000,
+            "acr": acr,
+        }))
+        .unwrap()
+    }
+
+    fn input<'a>(claims: &'a Claims, context: &'a HashMap<String, String>) -> RiskInput<'a> {
+        RiskInput {
+            claims,
+            route: "POST /v1/payments",
+            tenant: "",
+            client: "10.0.0.1",
+            client_ip: Some(IpAddr::from([203, 0, 113, 7])),
+            context,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_noop_evaluator_allows() {
+        let (claims, context) = (claims(None), HashMap::new());
+        let input = input(&claims, &context);
+        let decision = NoopRiskEvaluator.evaluate(&input).await.unwrap();
+        assert_eq!(decision, RiskDecision::Allow);
+        assert!(decision.into_result(&input).is_ok());
+    }
+
+    #[test]
+    fn test_step_up_names_acr_values() {
+        let (claims, context) = (claims(Some("urn:acr:pwd")), HashMap::new());
+        let input = input(&claims, &context);
+        assert!(matches!(
+            RiskDecision::step_up(["urn:acr:mfa"]).into_result(&input),
+            Err(AuthEdgeError::StepUpRequired { acr_values, max_age: None }) if acr_values == ["urn:acr:mfa"]
+        ));
+        assert!(matches!(
+            RiskDecision::deny("IP on blocklist").into_result(&input),
+            Err(AuthEdgeError::AccessDenied { reason }) if reason == "IP on blocklist"
+        ));
+    }
+
+    #[test]
+    fn test_satisfied_step_up_is_not_repeated() {
+        let (claims, context) = (claims(Some("urn:acr:mfa")), HashMap::new());
+        let input = input(&claims, &context);
+        assert_eq!(input.acr(), Some("urn:acr:mfa"));
+        assert!(RiskDecision::step_up(["urn:acr:hwk", "urn:acr:mfa"]).into_result(&input).is_ok());
+    }
+}
@@ -1,18 +1,178 @@
 //! Graceful Shutdown Module
 //!
 //! Provides structured concurrency with JoinSet and signal handling.
-//! Includes cleanup for LoggingClient and CacheClient.
+//! Once background tasks stop, subsystems clean up through shutdown hooks:
+//! async callbacks run in priority order, each bounded by its own timeout,
+//! so a stuck exporter cannot hold back the flushes that come after it.
 
 use std::future::Future;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
 use tokio::signal;
 use tokio::sync::{broadcast, watch};
 use tokio::task::JoinSet;
 use tracing::{info, warn, error};
 
-use crate::observability::AuthEdgeLogger;
+use crate::observability::{AuditLog, AuthEdgeLogger};
+
+/// Timeout of the log flush hooks registered by [`ShutdownCoordinator::with_logger`]
+/// and [`ShutdownCoordinator::with_audit_log`]
+pub const LOGGER_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timeout for flushing spans to the OTLP collector
+pub const TELEMETRY_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Order of a shutdown hook: lower priorities run first, equal ones concurrently
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HookPriority(pub u16);
+
+impl HookPriority {
+    /// Receivers feeding the service new work, e.g. the CAEP receiver
+    pub const RECEIVERS: Self = Self(100);
+    /// Background maintenance such as JWK refresh and rate limiter eviction
+    pub const MAINTENANCE: Self = Self(200);
+    /// Log sinks, flushed once the hooks above stopped producing entries
+    pub const LOGGING: Self = Self(300);
+    /// Telemetry exporters, flushed last so every span is exported
+    pub const TELEMETRY: Self = Self(400);
+}
+
+struct ShutdownHook {
+    name: &'static str,
+    priority: HookPriority,
+    timeout: Duration,
+    run: Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>,
+}
+
+/// How the shutdown hooks ended, each list in registration order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookReport {
+    /// Hooks that finished in time
+    pub completed: Vec<&'static str>,
+    /// Hooks that panicked
+    pub failed: Vec<&'static str>,
+    /// Hooks aborted at their timeout
+    pub timed_out: Vec<&'static str>,
+}
+
+/// Async cleanup hooks of the service's subsystems
+#[derive(Default)]
+pub struct ShutdownHooks {
+    hooks: Vec<ShutdownHook>,
+}
+
+impl ShutdownHooks {
+    /// Creates an empty hook list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `hook` to run at `priority`, aborted after `timeout`
+    pub fn register<F, Fut>(
+        &mut self,
+        name: &'static str,
+        priority: HookPriority,
+        timeout: Duration,
+        hook: F,
+    ) where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks.push(ShutdownHook {
+            name,
+            priority,
+            timeout,
+            run: Box::new(move || hook().boxed()),
+        });
+    }
+
+    /// Returns the number of registered hooks
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Whether no hooks are registered
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Runs the hooks by ascending priority
+    ///
+    /// Hooks sharing a priority run concurrently and the next priority starts
+    /// once all of them finished, panicked or timed out. A timed out hook is
+    /// aborted and logged as a straggler.
+    pub async fn run(mut self) -> HookReport {
+        // Stable, so equal priorities keep their registration order
+        self.hooks.sort_by_key(|hook| hook.priority);
+        let mut report = HookReport::default();
+        let mut hooks = self.hooks.into_iter().peekable();
+
+        while let Some(first) = hooks.next() {
+            let priority = first.priority;
+            let mut phase = vec![first];
+            while let Some(hook) = hooks.next_if(|hook| hook.priority == priority) {
+                phase.push(hook);
+            }
+
+            for (name, outcome) in join_all(phase.into_iter().map(Self::run_hook)).await {
+                match outcome {
+                    HookOutcome::Completed => report.completed.push(name),
+                    HookOutcome::Failed => report.failed.push(name),
+                    HookOutcome::TimedOut => report.timed_out.push(name),
+                }
+            }
+        }
+
+        if !report.timed_out.is_empty() || !report.failed.is_empty() {
+            warn!(
+                timed_out = ?report.timed_out,
+                failed = ?report.failed,
+                "Shutdown hooks did not all complete"
+            );
+        }
+        report
+    }
+
+    async fn run_hook(hook: ShutdownHook) -> (&'static str, HookOutcome) {
+        let start = Instant::now();
+        let mut task = tokio::spawn((hook.run)());
+        let outcome = match tokio::time::timeout(hook.timeout, &mut task).await {
+            Ok(Ok(())) => {
+                info!(
+                    hook = hook.name,
+                    priority = hook.priority.0,
+                    elapsed_ms = start.elapsed().as_millis() as u64,
+                    "Shutdown hook completed"
+                );
+                HookOutcome::Completed
+            }
+            Ok(Err(e)) => {
+                error!(hook = hook.name, priority = hook.priority.0, error = %e, "Shutdown hook panicked");
+                HookOutcome::Failed
+            }
+            Err(_) => {
+                task.abort();
+                warn!(
+                    hook = hook.name,
+                    priority = hook.priority.0,
+                    timeout_ms = hook.timeout.as_millis() as u64,
+                    "Shutdown hook timed out, aborting it"
+                );
+                HookOutcome::TimedOut
+            }
+        };
+        (hook.name, outcome)
+    }
+}
+
+enum HookOutcome {
+    Completed,
+    Failed,
+    TimedOut,
+}
 
 /// Shutdown coordinator for graceful termination
 pub struct ShutdownCoordinator {
@@ -22,8 +182,8 @@ pub struct ShutdownCoordinator {
     completion_tx: watch::Sender<bool>,
     /// JoinSet for tracking background tasks
     tasks: JoinSet<()>,
-    /// Optional logger for cleanup
-    logger: Option<Arc<AuthEdgeLogger>>,
+    /// Cleanup hooks run after the background tasks stopped
+    hooks: ShutdownHooks,
 }
 
 impl ShutdownCoordinator {
@@ -36,16 +196,40 @@ impl ShutdownCoordinator {
             shutdown_tx,
             completion_tx,
             tasks: JoinSet::new(),
-            logger: None,
+            hooks: ShutdownHooks::new(),
         }
     }
 
-    /// Sets the logger for cleanup during shutdown
+    /// Flushes the logger's buffer during shutdown
     pub fn with_logger(mut self, logger: Arc<AuthEdgeLogger>) -> Self {
-        self.logger = Some(logger);
+        self.register_hook("logger", HookPriority::LOGGING, LOGGER_FLUSH_TIMEOUT, move || async move {
+            logger.flush().await;
+        });
         self
     }
 
+    /// Sends the audit log's pending events during shutdown
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.register_hook("audit-log", HookPriority::LOGGING, LOGGER_FLUSH_TIMEOUT, move || async move {
+            audit_log.flush().await;
+        });
+        self
+    }
+
+    /// Registers a cleanup hook, see [`ShutdownHooks::register`]
+    pub fn register_hook<F, Fut>(
+        &mut self,
+        name: &'static str,
+        priority: HookPriority,
+        timeout: Duration,
+        hook: F,
+    ) where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks.register(name, priority, timeout, hook);
+    }
+
     /// Gets a shutdown receiver
     pub fn subscribe(&self) -> ShutdownSignal {
         ShutdownSignal {
@@ -79,12 +263,6 @@ impl ShutdownCoordinator {
         // Send shutdown signal
         let _ = self.shutdown_tx.send(());
         
-        // Flush logger buffer before shutdown
-        if let Some(logger) = &self.logger {
-            info!("Flushing logger buffer");
-            logger.flush().await;
-        }
-        
         // Wait for tasks with timeout
         let shutdown_result = tokio::time::timeout(timeout, async {
             while let Some(result) = self.tasks.join_next().await {
@@ -104,6 +282,12 @@ impl ShutdownCoordinator {
             }
         }
 
+        // Subsystem cleanup, e.g. flushing logs and spans
+        if !self.hooks.is_empty() {
+            info!(hooks = self.hooks.len(), "Running shutdown hooks");
+            std::mem::take(&mut self.hooks).run().await;
+        }
+
         // Signal completion
         let _ = self.completion_tx.send(true);
         
@@ -114,6 +298,11 @@ impl ShutdownCoordinator {
     pub fn task_count(&self) -> usize {
         self.tasks.len()
     }
+
+    /// Returns the number of registered shutdown hooks
+    pub fn hook_count(&self) -> usize {
+        self.hooks.len()
+    }
 }
 
 impl Default for ShutdownCoordinator {
@@ -191,3 +380,75 @@ pub async fn run_with_graceful_shutdown<F, S>(
 
     shutdown_coordinator.shutdown(shutdown_timeout).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    fn recorder(
+        hooks: &mut ShutdownHooks,
+        log: &Arc<Mutex<Vec<&'static str>>>,
+        name: &'static str,
+        priority: HookPriority,
+        delay: Duration,
+    ) {
+        let log = log.clone();
+        hooks.register(name, priority, Duration::from_secs(1), move || async move {
+            tokio::time::sleep(delay).await;
+            log.lock().push(name);
+        });
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_hooks_run_in_priority_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = ShutdownHooks::new();
+        recorder(&mut hooks, &log, "telemetry", HookPriority::TELEMETRY, Duration::ZERO);
+        recorder(&mut hooks, &log, "jwk-refresh", HookPriority::MAINTENANCE, Duration::from_millis(50));
+        recorder(&mut hooks, &log, "caep", HookPriority::RECEIVERS, Duration::from_millis(100));
+        recorder(&mut hooks, &log, "evictor", HookPriority::MAINTENANCE, Duration::ZERO);
+
+        let report = hooks.run().await;
+
+        // Same-priority hooks run concurrently, so the faster one ends first
+        assert_eq!(*log.lock(), vec!["caep", "evictor", "jwk-refresh", "telemetry"]);
+        assert_eq!(report.completed, vec!["caep", "jwk-refresh", "evictor", "telemetry"]);
+        assert!(report.timed_out.is_empty() && report.failed.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stragglers_do_not_block_later_hooks() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = ShutdownHooks::new();
+        hooks.register("stuck", HookPriority::RECEIVERS, Duration::from_millis(100), || {
+            std::future::pending::<()>()
+        });
+        hooks.register("panics", HookPriority::RECEIVERS, Duration::from_secs(1), || async {
+            panic!("cleanup failed");
+        });
+        recorder(&mut hooks, &log, "logger", HookPriority::LOGGING, Duration::ZERO);
+
+        let report = hooks.run().await;
+
+        assert_eq!(report.timed_out, vec!["stuck"]);
+        assert_eq!(report.failed, vec!["panics"]);
+        assert_eq!(report.completed, vec!["logger"]);
+        assert_eq!(*log.lock(), vec!["logger"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_coordinator_runs_registered_hooks() {
+        let flushed = Arc::new(Mutex::new(false));
+        let mut coordinator = ShutdownCoordinator::new();
+        coordinator.spawn("worker", std::future::pending::<()>());
+        let hook_flushed = flushed.clone();
+        coordinator.register_hook("flush", HookPriority::LOGGING, Duration::from_secs(1), move || async move {
+            *hook_flushed.lock() = true;
+        });
+        assert_eq!(coordinator.hook_count(), 1);
+
+        coordinator.shutdown(Duration::from_secs(1)).await;
+        assert!(*flushed.lock());
+    }
+}
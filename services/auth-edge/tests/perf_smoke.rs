@@ -0,0 +1,172 @@
+//! Validation latency gate.
+//!
+//! Serves `AuthEdgeService` in-process on a loopback port, backed by a mock
+//! JWKS endpoint, and validates 10k distinct EdDSA tokens over gRPC one
+//! after another. Every token is new, so each call verifies a signature
+//! instead of hitting the validation cache. Fails when the p99 latency
+//! exceeds the budget.
+//!
+//! Timings on shared CI runners are noisy, so the gate only runs when
+//! `PERF_SMOKE=1`:
+//!
+//! ```bash
+//! PERF_SMOKE=1 cargo test --release --test perf_smoke -- --nocapture
+//! ```
+//!
+//! `PERF_SMOKE_P99_MS` sets the p99 budget in milliseconds (default 5).
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use auth_edge::config::{Config, EnvSource};
+use auth_edge::grpc::AuthEdgeServiceImpl;
+use auth_edge::proto::auth::v1::auth_edge_service_client::AuthEdgeServiceClient;
+use auth_edge::proto::auth::v1::auth_edge_service_server::AuthEdgeServiceServer;
+use auth_edge::proto::auth::v1::ValidateTokenRequest;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde_json::json;
+use tonic::transport::Server;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const VALIDATIONS: usize = 10_000;
+const WARMUP: usize = 500;
+const DEFAULT_P99_BUDGET_MS: f64 = 5.0;
+const KID: &str = "perf-smoke";
+
+/// Whether `PERF_SMOKE` asks for the gate to run
+fn enabled() -> bool {
+    std::env::var("PERF_SMOKE").is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
+
+/// p99 budget from `PERF_SMOKE_P99_MS`
+fn p99_budget() -> Duration {
+    let millis = std::env::var("PERF_SMOKE_P99_MS")
+        .ok()
+        .map(|value| value.parse::<f64>().expect("PERF_SMOKE_P99_MS must be a number"))
+        .unwrap_or(DEFAULT_P99_BUDGET_MS);
+    Duration::from_secs_f64(millis / 1_000.0)
+}
+
+/// Nearest-rank percentile of sorted latencies
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let rank = (sorted.len() as f64 * p).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Publishes the public half of `key_pair` as the only key of a mock JWKS
+async fn jwks_server(key_pair: &Ed25519KeyPair) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/jwks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "keys": [{
+                "kty": "OKP",
+                "kid": KID,
+                "crv": "Ed25519",
+                "alg": "EdDSA",
+                "use": "sig",
+                "x": URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+            }],
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Signs `count` tokens with distinct subjects and JTIs
+fn tokens(pkcs8: &[u8], count: usize) -> Vec<String> {
+    let key = EncodingKey::from_ed_der(pkcs8);
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(KID.to_string());
+    let now = chrono::Utc::now().timestamp();
+    (0..count)
+        .map(|i| {
+            let claims = json!({
+                "iss": "https://auth.example.com",
+                "sub": format!("user-{i}"),
+                "aud": "api",
+                "exp": now + 600,
+                "iat": now,
+                "jti": format!("perf-{i}"),
+                "scope": "openid orders:read",
+            });
+            jsonwebtoken::encode(&header, &claims, &key).unwrap()
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_validation_p99_within_budget() {
+    if !enabled() {
+        eprintln!("skipped: set PERF_SMOKE=1 to run the validation latency gate");
+        return;
+    }
+    let budget = p99_budget();
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let jwks = jwks_server(&key_pair).await;
+    let tokens = tokens(pkcs8.as_ref(), WARMUP + VALIDATIONS);
+
+    // Revocation checks would need the cache service; the validation cache
+    // stays enabled but never hits since every token is new
+    let config = Config::from_source(&EnvSource::with_overrides(HashMap::from([
+        ("JWKS_URL".to_string(), format!("{}/jwks", jwks.uri())),
+        ("REVOCATION_CHECK_ENABLED".to_string(), "false".to_string()),
+    ])))
+    .unwrap();
+    let service = AuthEdgeServiceImpl::new(config).await.unwrap();
+
+    let listeners = rust_common::listen::bind(&["127.0.0.1:0".parse().unwrap()]).unwrap();
+    let addr = listeners[0].local_addr().unwrap();
+    tokio::spawn(
+        Server::builder()
+            .add_service(AuthEdgeServiceServer::new(service))
+            .serve_with_incoming(rust_common::listen::incoming(listeners)),
+    );
+    let mut client = AuthEdgeServiceClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let mut latencies = Vec::with_capacity(VALIDATIONS);
+    for (i, token) in tokens.into_iter().enumerate() {
+        let request = ValidateTokenRequest {
+            token,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let response = client.validate_token(request).await.unwrap().into_inner();
+        let elapsed = start.elapsed();
+        assert!(response.valid, "token {i} rejected: {:?}", response.error);
+        if i >= WARMUP {
+            latencies.push(elapsed);
+        }
+    }
+    latencies.sort_unstable();
+
+    let p50 = percentile(&latencies, 0.50);
+    let p99 = percentile(&latencies, 0.99);
+    println!("\nValidation Latency ({VALIDATIONS} ValidateToken calls over loopback gRPC):");
+    println!("  p50: {p50:?}");
+    println!("  p99: {p99:?} (budget {budget:?})");
+    println!("  max: {:?}", latencies[latencies.len() - 1]);
+    if cfg!(debug_assertions) {
+        println!("  note: debug build, run with --release for meaningful numbers");
+    }
+
+    assert!(p99 <= budget, "p99 {p99:?} exceeds the {budget:?} budget");
+}
+
+#[test]
+fn test_percentile_nearest_rank() {
+    let sorted: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+    assert_eq!(percentile(&sorted, 0.50), Duration::from_millis(50));
+    assert_eq!(percentile(&sorted, 0.99), Duration::from_millis(99));
+    assert_eq!(percentile(&sorted, 0.0), Duration::from_millis(1));
+    assert_eq!(percentile(&sorted[..1], 0.99), Duration::from_millis(1));
+}
@@ -5,7 +5,9 @@
 
 use auth_edge::error::{AuthEdgeError, ErrorCode};
 use proptest::prelude::*;
-use rust_common::{CircuitBreaker, CircuitBreakerConfig, CircuitState, PlatformError};
+use rust_common::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitState, PlatformError, TripPolicy,
+};
 use std::time::Duration;
 
 proptest! {
@@ -129,6 +131,7 @@ mod unit_tests {
             success_threshold: 2,
             timeout: Duration::from_millis(10),
             half_open_max_requests: 3,
+            trip: TripPolicy::ConsecutiveFailures,
         };
         
         let cb = CircuitBreaker::new(config);
@@ -5,6 +5,7 @@
 
 use auth_edge::error::{sanitize_message, ErrorResponse, AuthEdgeError, SENSITIVE_PATTERNS};
 use proptest::prelude::*;
+use tonic_types::StatusExt;
 use uuid::Uuid;
 
 mod generators {
@@ -136,6 +137,8 @@ mod unit_tests {
         
         assert_eq!(response.correlation_id, correlation_id);
         let status = response.to_status();
-        assert!(status.message().contains(&correlation_id.to_string()));
+        let details = status.get_error_details();
+        let info = details.error_info().unwrap();
+        assert_eq!(info.metadata["correlation_id"], correlation_id.to_string());
     }
 }
@@ -0,0 +1,95 @@
+//! Access token issuance throughput benchmarks.
+//!
+//! Compares `JwtSerializer::serialize`, which writes header and claims JSON
+//! into one reused buffer and base64url-encodes it straight onto the token,
+//! against `jsonwebtoken::encode`, which allocates each part's JSON, its
+//! encoding and the joined signing input separately.
+//!
+//! Run with `cargo test --release --bench issuance_bench -- --nocapture`;
+//! throughput is only asserted in release builds.
+
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use std::time::{Duration, Instant};
+use token_service::jwt::{Claims, JwtBuilder, JwtSerializer};
+
+const SAMPLE_SIZE: usize = 20_000;
+const WARMUP: usize = 1_000;
+
+fn claims() -> Claims {
+    let mut builder = JwtBuilder::new("https://auth.example.com".to_string())
+        .subject("user-123".to_string())
+        .audience(vec!["api".to_string()])
+        .session_id("session-456".to_string())
+        .scopes(vec![
+            "orders:read".to_string(),
+            "orders:write".to_string(),
+            "profile".to_string(),
+        ]);
+    for (key, value) in [("tenant_id", "acme"), ("department", "sales"), ("locale", "en-US")] {
+        builder = builder.custom_claim(key.to_string(), serde_json::json!(value));
+    }
+    builder.build().unwrap()
+}
+
+fn key() -> EncodingKey {
+    EncodingKey::from_secret(b"mock-kms-secret-key-for-testing-purposes-only!")
+}
+
+/// Time `SAMPLE_SIZE` issuances after a warmup
+fn measure(mut issue: impl FnMut() -> String) -> Duration {
+    for _ in 0..WARMUP {
+        std::hint::black_box(issue());
+    }
+    let start = Instant::now();
+    for _ in 0..SAMPLE_SIZE {
+        std::hint::black_box(issue());
+    }
+    start.elapsed()
+}
+
+fn tokens_per_sec(elapsed: Duration) -> f64 {
+    SAMPLE_SIZE as f64 / elapsed.as_secs_f64()
+}
+
+#[test]
+fn test_issuance_throughput() {
+    let claims = claims();
+    let key = key();
+    let serializer = JwtSerializer::new(Algorithm::HS256);
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some("default-key".to_string());
+
+    let serialized = measure(|| serializer.serialize(&claims, &key, Some("default-key")).unwrap());
+    let baseline = measure(|| jsonwebtoken::encode(&header, &claims, &key).unwrap());
+
+    println!("\nAccess Token Issuance Benchmark (HS256):");
+    println!("  Samples: {}", SAMPLE_SIZE);
+    println!("  jsonwebtoken::encode: {:.0} tokens/s", tokens_per_sec(baseline));
+    println!("  JwtSerializer:        {:.0} tokens/s", tokens_per_sec(serialized));
+    println!(
+        "  Speedup: {:.2}x",
+        baseline.as_secs_f64() / serialized.as_secs_f64()
+    );
+
+    if !cfg!(debug_assertions) {
+        assert!(
+            serialized < baseline,
+            "JwtSerializer ({:?}) should beat jsonwebtoken::encode ({:?})",
+            serialized,
+            baseline
+        );
+    }
+}
+
+#[test]
+fn test_streamed_token_is_identical() {
+    let claims = claims();
+    let key = key();
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some("default-key".to_string());
+
+    let serialized = JwtSerializer::new(Algorithm::HS256)
+        .serialize(&claims, &key, Some("default-key"))
+        .unwrap();
+    assert_eq!(serialized, jsonwebtoken::encode(&header, &claims, &key).unwrap());
+}
@@ -0,0 +1,94 @@
+//! JWKS serving benchmarks.
+//!
+//! Compares `JwksPublisher::rendered`, which hands out the key set
+//! serialized when it last changed, against collecting and serializing the
+//! keys on every request with `get_jwks().to_json()`.
+//!
+//! Run with `cargo test --release --bench jwks_bench -- --nocapture`;
+//! throughput is only asserted in release builds.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+use token_service::jwks::{Jwk, JwksPublisher};
+
+const SAMPLE_SIZE: usize = 20_000;
+const WARMUP: usize = 1_000;
+
+fn key(kid: &str) -> Jwk {
+    Jwk {
+        kty: "RSA".to_string(),
+        kid: kid.to_string(),
+        key_use: "sig".to_string(),
+        alg: "RS256".to_string(),
+        n: Some("0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw".to_string()),
+        e: Some("AQAB".to_string()),
+        x: None,
+        y: None,
+        crv: None,
+    }
+}
+
+/// Time `SAMPLE_SIZE` requests after a warmup
+fn measure<F, T>(serve: impl Fn() -> F) -> Duration
+where
+    F: Future<Output = T>,
+{
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        for _ in 0..WARMUP {
+            std::hint::black_box(serve().await);
+        }
+        let start = Instant::now();
+        for _ in 0..SAMPLE_SIZE {
+            std::hint::black_box(serve().await);
+        }
+        start.elapsed()
+    })
+}
+
+fn requests_per_sec(elapsed: Duration) -> f64 {
+    SAMPLE_SIZE as f64 / elapsed.as_secs_f64()
+}
+
+#[test]
+fn test_jwks_serving_throughput() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let publisher = JwksPublisher::new();
+    runtime.block_on(async {
+        publisher.add_key(key("key-1")).await;
+        publisher.rotate_keys(key("key-2")).await;
+        publisher.rotate_keys(key("key-3")).await;
+    });
+
+    let rendered = measure(|| publisher.rendered());
+    let baseline = measure(|| async { publisher.get_jwks().await.to_json() });
+
+    println!("\nJWKS Serving Benchmark (3 RSA keys):");
+    println!("  Samples: {}", SAMPLE_SIZE);
+    println!("  get_jwks().to_json(): {:.0} requests/s", requests_per_sec(baseline));
+    println!("  rendered():           {:.0} requests/s", requests_per_sec(rendered));
+    println!(
+        "  Speedup: {:.2}x",
+        baseline.as_secs_f64() / rendered.as_secs_f64()
+    );
+
+    if !cfg!(debug_assertions) {
+        assert!(
+            rendered < baseline,
+            "rendered ({:?}) should beat serializing per request ({:?})",
+            rendered,
+            baseline
+        );
+    }
+}
+
+#[test]
+fn test_rendered_matches_serialized_keys() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let publisher = JwksPublisher::new();
+        publisher.add_key(key("key-1")).await;
+        publisher.rotate_keys(key("key-2")).await;
+        assert_eq!(publisher.rendered().await.json, publisher.get_jwks().await.to_json());
+    });
+}
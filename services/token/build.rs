@@ -1,16 +1,21 @@
+use std::path::PathBuf;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Compile token service proto
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+
+    // Compile token service proto, keeping its descriptors for server reflection
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
+        .file_descriptor_set_path(out_dir.join("token_service_descriptor.bin"))
         .compile_protos(
             &["../../api/proto/auth/token_service.proto"],
             &["../../api/proto/auth"],
         )?;
 
-    // Compile crypto service proto (client only)
+    // Compile crypto service proto (server stubs back the test fake)
     tonic_build::configure()
-        .build_server(false)
+        .build_server(true)
         .build_client(true)
         .compile_protos(
             &["proto/crypto_service.proto"],
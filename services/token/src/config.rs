@@ -4,8 +4,11 @@
 //! at startup. Platform library configurations are included.
 
 use crate::error::TokenError;
-use rust_common::{CacheClientConfig, CircuitBreakerConfig, LoggingClientConfig};
+use rust_common::{CacheClientConfig, CircuitBreakerConfig, ListenHost, LoggingClientConfig};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::time::Duration;
 
 /// JWT signing algorithm.
@@ -53,22 +56,112 @@ pub enum KmsProvider {
     Mock,
 }
 
+/// Token family storage backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageMode {
+    /// Platform cache only
+    #[default]
+    Cache,
+    /// Platform cache as primary, legacy Redis kept in sync during migration
+    DualWrite,
+}
+
+impl StorageMode {
+    /// Parse storage mode from string.
+    pub fn from_str(s: &str) -> Result<Self, TokenError> {
+        match s.to_lowercase().as_str() {
+            "cache" => Ok(Self::Cache),
+            "dual-write" | "dual_write" => Ok(Self::DualWrite),
+            _ => Err(TokenError::config(format!("Invalid STORAGE_MODE: {}", s))),
+        }
+    }
+}
+
+/// Issuer identity hosted by this deployment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuerProfile {
+    /// JWT issuer claim
+    pub issuer: String,
+    /// KMS key ID signing this issuer's tokens
+    pub kms_key_id: String,
+}
+
+/// Audience whose tokens are signed with a dedicated key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudienceKey {
+    /// JWT audience claim
+    pub audience: String,
+    /// KMS key ID signing tokens for this audience
+    pub kms_key_id: String,
+}
+
+/// Client allowed to request access tokens for a set of audiences.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RegisteredClient {
+    /// Client ID, as sent in `IssueTokenRequest.client_id`
+    pub client_id: String,
+    /// Audiences the client may request, in the order tokens list them
+    pub audiences: Vec<AudienceClaims>,
+}
+
+/// Custom claims tokens for an audience may carry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AudienceClaims {
+    /// JWT audience claim
+    pub audience: String,
+    /// Custom claim names; omitted allows every custom claim
+    #[serde(default)]
+    pub claims: Option<Vec<String>>,
+}
+
+/// Relying party registered for OIDC back-channel logout.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BackchannelLogoutClient {
+    /// Client ID, used as the logout token audience
+    pub client_id: String,
+    /// RP back-channel logout endpoint
+    pub backchannel_logout_uri: String,
+    /// Send one logout token per session, each carrying `sid`
+    #[serde(default)]
+    pub backchannel_logout_session_required: bool,
+    /// Delivery retries, overriding `BACKCHANNEL_LOGOUT_RETRIES`
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+}
+
 /// Token Service configuration.
 #[derive(Debug, Clone)]
 pub struct Config {
     // Server settings
-    /// Host to bind to
-    pub host: String,
+    /// Host to bind to: an IP address (IPv6 with or without brackets) or `localhost`
+    pub host: ListenHost,
+    /// Also listen on the other address family's wildcard or loopback address
+    pub dual_stack: bool,
     /// Port to listen on
     pub port: u16,
+    /// Serve gRPC server reflection so tools can discover the API
+    pub grpc_reflection_enabled: bool,
 
     // JWT settings
     /// JWT issuer claim
     pub jwt_issuer: String,
     /// JWT signing algorithm
     pub jwt_algorithm: JwtAlgorithm,
+    /// Audience of access tokens issued without one
+    pub jwt_default_audience: String,
     /// Access token TTL
     pub access_token_ttl: Duration,
+    /// Largest encoded access token in bytes (0 disables the check)
+    pub max_access_token_bytes: usize,
+    /// Move the largest custom claims of oversized access tokens into a
+    /// `claims_ref` cache entry instead of failing
+    pub claims_by_reference: bool,
+    /// Custom claims encrypted through the crypto-service before signing
+    pub private_claims: Vec<String>,
+    /// Crypto-service key encrypting private claims
+    pub private_claims_key_id: String,
+    /// Version of the private claims key
+    pub private_claims_key_version: u32,
     /// Refresh token TTL
     pub refresh_token_ttl: Duration,
 
@@ -81,6 +174,12 @@ pub struct Config {
     pub kms_fallback_enabled: bool,
     /// Fallback timeout duration
     pub kms_fallback_timeout: Duration,
+    /// Issuer profiles; the first is `jwt_issuer` signed with `kms_key_id`
+    pub issuers: Vec<IssuerProfile>,
+    /// Audiences signed with their own key instead of the issuer's
+    pub audience_keys: Vec<AudienceKey>,
+    /// Clients issuing multi-audience tokens, with per-audience claims
+    pub clients: Vec<RegisteredClient>,
 
     // DPoP settings
     /// Maximum clock skew for DPoP validation
@@ -93,9 +192,89 @@ pub struct Config {
     pub cache: CacheClientConfig,
     /// Logging client configuration
     pub logging: LoggingClientConfig,
+    /// OpenTelemetry collector receiving spans and metrics, if any
+    pub otlp_endpoint: Option<String>,
+    /// gRPC metadata sent with every OTLP export
+    pub otlp_headers: Vec<(String, String)>,
+    /// Share of traces sampled (0.0 to 1.0)
+    pub otlp_sampling_ratio: f64,
+    /// Extra resource attributes of exported spans and metrics
+    pub otlp_resource_attributes: Vec<(String, String)>,
+    /// SPIFFE ID of this workload, exported as `service.spiffe_id`
+    pub spiffe_id: Option<String>,
+    /// Busiest tenants given their own metric series
+    pub tenant_metrics_top_n: usize,
+    /// How often the busiest tenants are recomputed
+    pub tenant_metrics_window: Duration,
     /// Circuit breaker configuration
     pub circuit_breaker: CircuitBreakerConfig,
 
+    // Revoked-JTI filter
+    /// Journal revoked JTIs and serve a bloom filter of them
+    pub revocation_filter_enabled: bool,
+    /// How often the filter is rebuilt from the replicas' journals
+    pub revocation_filter_interval: Duration,
+    /// False-positive rate the filter is sized for
+    pub revocation_filter_fp_rate: f64,
+
+    // Back-channel logout
+    /// Relying parties notified when all of a user's tokens are revoked
+    pub backchannel_logout_clients: Vec<BackchannelLogoutClient>,
+    /// Default delivery retries per logout token
+    pub backchannel_logout_retries: u32,
+    /// Timeout of a logout endpoint request
+    pub backchannel_logout_timeout: Duration,
+
+    // Refresh token replay anomalies
+    /// Window over which replays are counted per client
+    pub replay_anomaly_window: Duration,
+    /// Share of replayed refresh attempts that flags a client
+    pub replay_anomaly_threshold: f64,
+    /// Refresh attempts required in a window before a client is flagged
+    pub replay_anomaly_min_events: u32,
+    /// Edge endpoint that downgrades a flagged client's rate-limit trust
+    pub trust_downgrade_url: Option<String>,
+    /// API key presented to the trust downgrade endpoint
+    pub trust_downgrade_api_key: Option<String>,
+    /// How long a flagged client stays downgraded
+    pub trust_downgrade_ttl: Duration,
+
+    // Legacy storage migration
+    /// Token family storage backend
+    pub storage_mode: StorageMode,
+    /// Legacy Redis URL, required in dual-write mode
+    pub legacy_redis_url: Option<String>,
+    /// Copy legacy Redis data into the cache on startup
+    pub legacy_migrate_on_start: bool,
+    /// Lease held in Redis by the replica running a background job
+    pub job_lease_ttl: Duration,
+
+    // Storage schema
+    /// Apply pending storage schema migrations on startup
+    pub schema_migrate_on_start: bool,
+    /// Lease held by the replica running schema migrations
+    pub schema_lock_ttl: Duration,
+    /// How long to wait for another replica's schema migration
+    pub schema_lock_wait: Duration,
+
+    // Warm standby
+    /// Start read-only and stay there regardless of storage health
+    pub read_only: bool,
+    /// Consecutive storage write failures that switch to read-only mode
+    pub standby_failure_threshold: u32,
+    /// Consecutive successful write probes that leave read-only mode
+    pub standby_recovery_threshold: u32,
+    /// Interval between storage write probes
+    pub standby_probe_interval: Duration,
+
+    // Startup
+    /// How long a dependency may keep failing transiently at startup
+    pub startup_retry_budget: Duration,
+    /// Longest wait between startup attempts on a dependency
+    pub startup_retry_max_backoff: Duration,
+    /// Per-dependency overrides of `startup_retry_budget`
+    pub startup_retry_budgets: HashMap<String, Duration>,
+
     // Security
     /// Encryption key for cached data (32 bytes for AES-256)
     pub encryption_key: [u8; 32],
@@ -110,14 +289,32 @@ impl Config {
     pub fn from_env() -> Result<Self, TokenError> {
         dotenvy::dotenv().ok();
 
-        let host = env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
+        let host = parse_env("HOST", ListenHost::Ip(Ipv4Addr::UNSPECIFIED.into()))?;
+        let dual_stack = parse_env("DUAL_STACK", false)?;
         let port = parse_env("PORT", 50051)?;
+        host.socket_addrs(port, dual_stack)
+            .map_err(|e| TokenError::config(format!("Invalid DUAL_STACK: {e}")))?;
+        let grpc_reflection_enabled = parse_env("GRPC_REFLECTION_ENABLED", false)?;
 
         let jwt_issuer = env::var("JWT_ISSUER").unwrap_or_else(|_| "auth-platform".to_string());
         let jwt_algorithm = JwtAlgorithm::from_str(
             &env::var("JWT_ALGORITHM").unwrap_or_else(|_| "RS256".to_string()),
         )?;
+        let jwt_default_audience =
+            env::var("JWT_DEFAULT_AUDIENCE").unwrap_or_else(|_| "api".to_string());
         let access_token_ttl = Duration::from_secs(parse_env("ACCESS_TOKEN_TTL", 900)?);
+        let max_access_token_bytes = parse_env("MAX_ACCESS_TOKEN_BYTES", 0)?;
+        let claims_by_reference = parse_env("CLAIMS_BY_REFERENCE", false)?;
+        if claims_by_reference && max_access_token_bytes == 0 {
+            return Err(TokenError::config(
+                "CLAIMS_BY_REFERENCE requires MAX_ACCESS_TOKEN_BYTES",
+            ));
+        }
+        let private_claims =
+            parse_private_claims(env::var("PRIVATE_CLAIMS").ok().as_deref())?;
+        let private_claims_key_id =
+            env::var("PRIVATE_CLAIMS_KEY_ID").unwrap_or_else(|_| "private-claims".to_string());
+        let private_claims_key_version = parse_env("PRIVATE_CLAIMS_KEY_VERSION", 1)?;
         let refresh_token_ttl = Duration::from_secs(parse_env("REFRESH_TOKEN_TTL", 604800)?);
 
         let kms_provider = match env::var("KMS_PROVIDER")
@@ -133,6 +330,14 @@ impl Config {
         let kms_key_id = env::var("KMS_KEY_ID").unwrap_or_else(|_| "default-key".to_string());
         let kms_fallback_enabled = parse_env("KMS_FALLBACK_ENABLED", false)?;
         let kms_fallback_timeout = Duration::from_secs(parse_env("KMS_FALLBACK_TIMEOUT", 300)?);
+        let issuers = parse_issuer_profiles(
+            &jwt_issuer,
+            &kms_key_id,
+            env::var("JWT_ADDITIONAL_ISSUERS").ok().as_deref(),
+        )?;
+        let audience_keys =
+            parse_audience_keys(&issuers, env::var("AUDIENCE_SIGNING_KEYS").ok().as_deref())?;
+        let clients = parse_client_registry(env::var("CLIENT_REGISTRY").ok().as_deref())?;
 
         let dpop_clock_skew = Duration::from_secs(parse_env("DPOP_CLOCK_SKEW", 60)?);
         let dpop_jti_ttl = Duration::from_secs(parse_env("DPOP_JTI_TTL", 300)?);
@@ -153,31 +358,433 @@ impl Config {
         let logging = LoggingClientConfig::default()
             .with_address(logging_address)
             .with_service_id("token-service");
+        let otlp_endpoint = env::var("OTLP_ENDPOINT").ok().filter(|e| !e.is_empty());
+        let otlp_headers = parse_key_values_env("OTLP_HEADERS")?;
+        let otlp_sampling_ratio: f64 = parse_env("OTLP_SAMPLING_RATIO", 1.0)?;
+        if !(0.0..=1.0).contains(&otlp_sampling_ratio) {
+            return Err(TokenError::config(
+                "OTLP_SAMPLING_RATIO must be between 0.0 and 1.0",
+            ));
+        }
+        let otlp_resource_attributes = parse_key_values_env("OTLP_RESOURCE_ATTRIBUTES")?;
+        let spiffe_id = env::var("SPIFFE_ID").ok().filter(|id| !id.is_empty());
+        let tenant_metrics_top_n = parse_env("TENANT_METRICS_TOP_N", 20)?;
+        let tenant_metrics_window = Duration::from_secs(parse_env("TENANT_METRICS_WINDOW", 300)?);
+        if tenant_metrics_window.is_zero() {
+            return Err(TokenError::config("TENANT_METRICS_WINDOW must be greater than 0"));
+        }
+
+        let revocation_filter_enabled = parse_env("REVOCATION_FILTER_ENABLED", false)?;
+        let revocation_filter_interval =
+            Duration::from_secs(parse_env("REVOCATION_FILTER_INTERVAL", 30)?);
+        if revocation_filter_interval.is_zero() {
+            return Err(TokenError::config("REVOCATION_FILTER_INTERVAL must be greater than 0"));
+        }
+        let revocation_filter_fp_rate: f64 = parse_env("REVOCATION_FILTER_FP_RATE", 0.001)?;
+        if !(revocation_filter_fp_rate > 0.0 && revocation_filter_fp_rate < 1.0) {
+            return Err(TokenError::config(
+                "REVOCATION_FILTER_FP_RATE must be between 0.0 and 1.0 (exclusive)",
+            ));
+        }
 
         let circuit_breaker = CircuitBreakerConfig::default()
             .with_failure_threshold(parse_env("CB_FAILURE_THRESHOLD", 5)?)
             .with_success_threshold(parse_env("CB_SUCCESS_THRESHOLD", 2)?)
             .with_timeout(Duration::from_secs(parse_env("CB_TIMEOUT", 30)?));
 
+        let backchannel_logout_clients = parse_backchannel_logout_clients(
+            env::var("BACKCHANNEL_LOGOUT_CLIENTS").ok().as_deref(),
+        )?;
+        let backchannel_logout_retries = parse_env("BACKCHANNEL_LOGOUT_RETRIES", 3)?;
+        let backchannel_logout_timeout =
+            Duration::from_secs(parse_env("BACKCHANNEL_LOGOUT_TIMEOUT", 5)?);
+
+        let replay_anomaly_window =
+            Duration::from_secs(parse_env("REPLAY_ANOMALY_WINDOW", 300)?);
+        let replay_anomaly_threshold = parse_replay_anomaly_threshold(parse_env(
+            "REPLAY_ANOMALY_THRESHOLD",
+            0.2,
+        )?)?;
+        let replay_anomaly_min_events = parse_env("REPLAY_ANOMALY_MIN_EVENTS", 10)?;
+        let trust_downgrade_url = env::var("TRUST_DOWNGRADE_URL").ok().filter(|s| !s.is_empty());
+        let trust_downgrade_api_key =
+            env::var("TRUST_DOWNGRADE_API_KEY").ok().filter(|s| !s.is_empty());
+        let trust_downgrade_ttl = Duration::from_secs(parse_env("TRUST_DOWNGRADE_TTL", 900)?);
+
+        let storage_mode = StorageMode::from_str(
+            &env::var("STORAGE_MODE").unwrap_or_else(|_| "cache".to_string()),
+        )?;
+        let legacy_redis_url = env::var("LEGACY_REDIS_URL").ok().filter(|s| !s.is_empty());
+        if storage_mode == StorageMode::DualWrite && legacy_redis_url.is_none() {
+            return Err(TokenError::config(
+                "LEGACY_REDIS_URL is required when STORAGE_MODE=dual-write",
+            ));
+        }
+        let legacy_migrate_on_start = parse_env("LEGACY_MIGRATE_ON_START", false)?;
+        let job_lease_ttl = Duration::from_secs(parse_env("JOB_LEASE_TTL", 30)?);
+        if job_lease_ttl.is_zero() {
+            return Err(TokenError::config("JOB_LEASE_TTL must be greater than 0"));
+        }
+
+        let schema_migrate_on_start = parse_env("SCHEMA_MIGRATE_ON_START", true)?;
+        let schema_lock_ttl = Duration::from_secs(parse_env("SCHEMA_LOCK_TTL", 300)?);
+        let schema_lock_wait = Duration::from_secs(parse_env("SCHEMA_LOCK_WAIT", 600)?);
+        if schema_lock_ttl.is_zero() {
+            return Err(TokenError::config("SCHEMA_LOCK_TTL must be greater than 0"));
+        }
+
+        let read_only = parse_env("READ_ONLY", false)?;
+        let standby_failure_threshold = parse_env("STANDBY_FAILURE_THRESHOLD", 3)?;
+        let standby_recovery_threshold = parse_env("STANDBY_RECOVERY_THRESHOLD", 3)?;
+        let standby_probe_interval = Duration::from_secs(parse_env("STANDBY_PROBE_INTERVAL", 5)?);
+        if standby_failure_threshold == 0 || standby_recovery_threshold == 0 {
+            return Err(TokenError::config(
+                "STANDBY_FAILURE_THRESHOLD and STANDBY_RECOVERY_THRESHOLD must be greater than 0",
+            ));
+        }
+        if standby_probe_interval.is_zero() {
+            return Err(TokenError::config("STANDBY_PROBE_INTERVAL must be greater than 0"));
+        }
+
+        let startup_retry_budget = Duration::from_secs(parse_env("STARTUP_RETRY_BUDGET", 120)?);
+        let startup_retry_max_backoff =
+            Duration::from_secs(parse_env("STARTUP_RETRY_MAX_BACKOFF", 10)?);
+        if startup_retry_max_backoff.is_zero() {
+            return Err(TokenError::config("STARTUP_RETRY_MAX_BACKOFF must be greater than 0"));
+        }
+        let startup_retry_budgets =
+            parse_startup_retry_budgets(env::var("STARTUP_RETRY_BUDGETS").ok().as_deref())?;
+
         Ok(Self {
             host,
+            dual_stack,
             port,
+            grpc_reflection_enabled,
             jwt_issuer,
             jwt_algorithm,
+            jwt_default_audience,
             access_token_ttl,
+            max_access_token_bytes,
+            claims_by_reference,
+            private_claims,
+            private_claims_key_id,
+            private_claims_key_version,
             refresh_token_ttl,
             kms_provider,
             kms_key_id,
             kms_fallback_enabled,
             kms_fallback_timeout,
+            issuers,
+            audience_keys,
+            clients,
             dpop_clock_skew,
             dpop_jti_ttl,
             cache,
             logging,
+            otlp_endpoint,
+            otlp_headers,
+            otlp_sampling_ratio,
+            otlp_resource_attributes,
+            spiffe_id,
+            tenant_metrics_top_n,
+            tenant_metrics_window,
             circuit_breaker,
+            revocation_filter_enabled,
+            revocation_filter_interval,
+            revocation_filter_fp_rate,
+            backchannel_logout_clients,
+            backchannel_logout_retries,
+            backchannel_logout_timeout,
+            replay_anomaly_window,
+            replay_anomaly_threshold,
+            replay_anomaly_min_events,
+            trust_downgrade_url,
+            trust_downgrade_api_key,
+            trust_downgrade_ttl,
+            storage_mode,
+            legacy_redis_url,
+            legacy_migrate_on_start,
+            job_lease_ttl,
+            schema_migrate_on_start,
+            schema_lock_ttl,
+            schema_lock_wait,
+            read_only,
+            standby_failure_threshold,
+            standby_recovery_threshold,
+            standby_probe_interval,
+            startup_retry_budget,
+            startup_retry_max_backoff,
+            startup_retry_budgets,
             encryption_key,
         })
     }
+
+    /// Socket addresses to listen on, one per address family when dual-stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if dual-stack listening is set for a specific address.
+    pub fn listen_addrs(&self) -> Result<Vec<SocketAddr>, TokenError> {
+        self.host
+            .socket_addrs(self.port, self.dual_stack)
+            .map_err(TokenError::config)
+    }
+
+    /// Look up an issuer profile; an empty issuer selects the default.
+    #[must_use]
+    pub fn issuer_profile(&self, issuer: &str) -> Option<&IssuerProfile> {
+        if issuer.is_empty() {
+            return self.issuers.first();
+        }
+        self.issuers.iter().find(|p| p.issuer == issuer)
+    }
+
+    /// Look up the dedicated signing key of an audience.
+    #[must_use]
+    pub fn audience_key(&self, audience: &str) -> Option<&AudienceKey> {
+        self.audience_keys.iter().find(|k| k.audience == audience)
+    }
+}
+
+/// Parse issuer profiles from `issuer=kms_key_id,...`.
+///
+/// The default issuer always comes first. Issuers and key IDs must be
+/// unique so that every issuer publishes its own key set.
+fn parse_issuer_profiles(
+    default_issuer: &str,
+    default_key_id: &str,
+    additional: Option<&str>,
+) -> Result<Vec<IssuerProfile>, TokenError> {
+    let mut profiles = vec![IssuerProfile {
+        issuer: default_issuer.to_string(),
+        kms_key_id: default_key_id.to_string(),
+    }];
+
+    for entry in additional
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+    {
+        let (issuer, key_id) = entry
+            .split_once('=')
+            .map(|(i, k)| (i.trim(), k.trim()))
+            .filter(|(i, k)| !i.is_empty() && !k.is_empty())
+            .ok_or_else(|| {
+                TokenError::config(format!(
+                    "Invalid JWT_ADDITIONAL_ISSUERS entry '{}': expected issuer=kms_key_id",
+                    entry
+                ))
+            })?;
+
+        if profiles.iter().any(|p| p.issuer == issuer) {
+            return Err(TokenError::config(format!(
+                "Duplicate issuer in JWT_ADDITIONAL_ISSUERS: {}",
+                issuer
+            )));
+        }
+        if profiles.iter().any(|p| p.kms_key_id == key_id) {
+            return Err(TokenError::config(format!(
+                "KMS key {} is already used by another issuer",
+                key_id
+            )));
+        }
+
+        profiles.push(IssuerProfile {
+            issuer: issuer.to_string(),
+            kms_key_id: key_id.to_string(),
+        });
+    }
+
+    Ok(profiles)
+}
+
+/// Parse audience signing keys from `audience=kms_key_id,...`.
+///
+/// Every audience gets a key of its own: keys may not be shared with
+/// another audience or an issuer, so a leaked key only mints tokens for
+/// one audience.
+fn parse_audience_keys(
+    issuers: &[IssuerProfile],
+    spec: Option<&str>,
+) -> Result<Vec<AudienceKey>, TokenError> {
+    let mut keys: Vec<AudienceKey> = Vec::new();
+
+    for entry in spec
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+    {
+        let (audience, key_id) = entry
+            .split_once('=')
+            .map(|(a, k)| (a.trim(), k.trim()))
+            .filter(|(a, k)| !a.is_empty() && !k.is_empty())
+            .ok_or_else(|| {
+                TokenError::config(format!(
+                    "Invalid AUDIENCE_SIGNING_KEYS entry '{}': expected audience=kms_key_id",
+                    entry
+                ))
+            })?;
+
+        if keys.iter().any(|k| k.audience == audience) {
+            return Err(TokenError::config(format!(
+                "Duplicate audience in AUDIENCE_SIGNING_KEYS: {}",
+                audience
+            )));
+        }
+        if keys.iter().any(|k| k.kms_key_id == key_id)
+            || issuers.iter().any(|p| p.kms_key_id == key_id)
+        {
+            return Err(TokenError::config(format!(
+                "KMS key {} is already used by another audience or issuer",
+                key_id
+            )));
+        }
+
+        keys.push(AudienceKey {
+            audience: audience.to_string(),
+            kms_key_id: key_id.to_string(),
+        });
+    }
+
+    Ok(keys)
+}
+
+/// Parse the client registry from a JSON array.
+///
+/// Client IDs must be unique and every client needs at least one audience,
+/// each listed once.
+fn parse_client_registry(json: Option<&str>) -> Result<Vec<RegisteredClient>, TokenError> {
+    let json = match json.map(str::trim) {
+        Some(json) if !json.is_empty() => json,
+        _ => return Ok(Vec::new()),
+    };
+    let clients: Vec<RegisteredClient> = serde_json::from_str(json)
+        .map_err(|e| TokenError::config(format!("Invalid CLIENT_REGISTRY: {}", e)))?;
+
+    for (i, client) in clients.iter().enumerate() {
+        if client.client_id.is_empty() || client.audiences.is_empty() {
+            return Err(TokenError::config(format!(
+                "CLIENT_REGISTRY[{}] requires client_id and audiences",
+                i
+            )));
+        }
+        if clients[..i].iter().any(|c| c.client_id == client.client_id) {
+            return Err(TokenError::config(format!(
+                "Duplicate client in CLIENT_REGISTRY: {}",
+                client.client_id
+            )));
+        }
+        for (j, audience) in client.audiences.iter().enumerate() {
+            if audience.audience.is_empty()
+                || client.audiences[..j].iter().any(|a| a.audience == audience.audience)
+            {
+                return Err(TokenError::config(format!(
+                    "CLIENT_REGISTRY client {} has an empty or duplicate audience",
+                    client.client_id
+                )));
+            }
+        }
+    }
+
+    Ok(clients)
+}
+
+/// Parse back-channel logout clients from a JSON array.
+///
+/// Client IDs must be unique and every client needs a logout endpoint.
+fn parse_backchannel_logout_clients(
+    json: Option<&str>,
+) -> Result<Vec<BackchannelLogoutClient>, TokenError> {
+    let json = match json.map(str::trim) {
+        Some(json) if !json.is_empty() => json,
+        _ => return Ok(Vec::new()),
+    };
+    let clients: Vec<BackchannelLogoutClient> = serde_json::from_str(json)
+        .map_err(|e| TokenError::config(format!("Invalid BACKCHANNEL_LOGOUT_CLIENTS: {}", e)))?;
+
+    for (i, client) in clients.iter().enumerate() {
+        if client.client_id.is_empty() || client.backchannel_logout_uri.is_empty() {
+            return Err(TokenError::config(format!(
+                "BACKCHANNEL_LOGOUT_CLIENTS[{}] requires client_id and backchannel_logout_uri",
+                i
+            )));
+        }
+        if clients[..i].iter().any(|c| c.client_id == client.client_id) {
+            return Err(TokenError::config(format!(
+                "Duplicate client in BACKCHANNEL_LOGOUT_CLIENTS: {}",
+                client.client_id
+            )));
+        }
+    }
+
+    Ok(clients)
+}
+
+/// Parse per-dependency startup retry budgets, e.g. `storage=300,cache=60`
+/// in seconds.
+fn parse_startup_retry_budgets(value: Option<&str>) -> Result<HashMap<String, Duration>, TokenError> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let invalid = || {
+                TokenError::config(format!(
+                    "Invalid STARTUP_RETRY_BUDGETS entry {:?}, expected dependency=seconds",
+                    entry
+                ))
+            };
+            let (name, secs) = entry.split_once('=').ok_or_else(invalid)?;
+            let secs: u64 = secs.trim().parse().map_err(|_| invalid())?;
+            if name.trim().is_empty() {
+                return Err(invalid());
+            }
+            Ok((name.trim().to_string(), Duration::from_secs(secs)))
+        })
+        .collect()
+}
+
+/// Claims the token itself relies on, which can never be encrypted.
+const REGISTERED_CLAIMS: &[&str] = &[
+    "iss", "sub", "aud", "exp", "iat", "nbf", "jti", "nonce", "auth_time", "acr", "amr", "azp",
+    "cnf", "session_id", "scope", "scopes", "claims_ref",
+];
+
+/// Parse the comma-separated custom claims that are encrypted before signing.
+fn parse_private_claims(value: Option<&str>) -> Result<Vec<String>, TokenError> {
+    let mut claims: Vec<String> = Vec::new();
+    for name in value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        if REGISTERED_CLAIMS.contains(&name) {
+            return Err(TokenError::config(format!(
+                "PRIVATE_CLAIMS may not include the registered claim {}",
+                name
+            )));
+        }
+        if !claims.iter().any(|claim| claim == name) {
+            claims.push(name.to_string());
+        }
+    }
+    Ok(claims)
+}
+
+/// Validate the replay rate that flags a client.
+fn parse_replay_anomaly_threshold(threshold: f64) -> Result<f64, TokenError> {
+    if threshold > 0.0 && threshold <= 1.0 {
+        Ok(threshold)
+    } else {
+        Err(TokenError::config(format!(
+            "REPLAY_ANOMALY_THRESHOLD must be in (0, 1], got {}",
+            threshold
+        )))
+    }
 }
 
 /// Parse environment variable with default value.
@@ -193,6 +800,15 @@ where
     }
 }
 
+/// Parse a `key=value,...` list from environment, e.g. `OTLP_HEADERS`.
+fn parse_key_values_env(name: &str) -> Result<Vec<(String, String)>, TokenError> {
+    match env::var(name) {
+        Ok(val) => rust_common::tracing_config::parse_key_values(&val)
+            .map_err(|e| TokenError::config(format!("Invalid {}: {}", name, e))),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
 /// Parse encryption key from environment.
 fn parse_encryption_key() -> Result<[u8; 32], TokenError> {
     match env::var("ENCRYPTION_KEY") {
@@ -244,6 +860,14 @@ mod tests {
         assert_eq!(JwtAlgorithm::ES256.as_str(), "ES256");
     }
 
+    #[test]
+    fn test_storage_mode_parsing() {
+        assert_eq!(StorageMode::from_str("cache").unwrap(), StorageMode::Cache);
+        assert_eq!(StorageMode::from_str("dual-write").unwrap(), StorageMode::DualWrite);
+        assert_eq!(StorageMode::from_str("DUAL_WRITE").unwrap(), StorageMode::DualWrite);
+        assert!(StorageMode::from_str("redis").is_err());
+    }
+
     #[test]
     fn test_config_from_env_defaults() {
         // Clear any existing env vars
@@ -253,9 +877,148 @@ mod tests {
 
         let config = Config::from_env().unwrap();
 
-        assert_eq!(config.host, "0.0.0.0");
+        assert_eq!(config.host.to_string(), "0.0.0.0");
+        assert_eq!(config.listen_addrs().unwrap()[0].to_string(), "0.0.0.0:50051");
         assert_eq!(config.port, 50051);
+        assert!(!config.grpc_reflection_enabled);
         assert_eq!(config.jwt_issuer, "auth-platform");
         assert_eq!(config.jwt_algorithm, JwtAlgorithm::RS256);
+        assert_eq!(config.storage_mode, StorageMode::Cache);
+        assert_eq!(config.job_lease_ttl, Duration::from_secs(30));
+        assert_eq!(config.startup_retry_budget, Duration::from_secs(120));
+        assert_eq!(config.tenant_metrics_top_n, 20);
+        assert_eq!(config.tenant_metrics_window, Duration::from_secs(300));
+        assert!(!config.revocation_filter_enabled);
+        assert_eq!(config.revocation_filter_interval, Duration::from_secs(30));
+        assert_eq!(config.issuers.len(), 1);
+        assert_eq!(config.issuer_profile("").unwrap().issuer, "auth-platform");
+    }
+
+    #[test]
+    fn test_issuer_profiles_parsing() {
+        let profiles = parse_issuer_profiles(
+            "https://auth.example.com",
+            "example-key",
+            Some(" https://auth.partner.com = partner-key ,"),
+        )
+        .unwrap();
+
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].issuer, "https://auth.example.com");
+        assert_eq!(profiles[1].issuer, "https://auth.partner.com");
+        assert_eq!(profiles[1].kms_key_id, "partner-key");
+
+        let issuer = "https://auth.example.com";
+        assert!(parse_issuer_profiles(issuer, "k", Some("https://auth.partner.com")).is_err());
+        assert!(parse_issuer_profiles(issuer, "k", Some("https://auth.example.com=k2")).is_err());
+        assert!(parse_issuer_profiles(issuer, "k", Some("https://auth.partner.com=k")).is_err());
+    }
+
+    #[test]
+    fn test_audience_keys_parsing() {
+        let issuers = parse_issuer_profiles("https://auth.example.com", "issuer-key", None).unwrap();
+        assert!(parse_audience_keys(&issuers, None).unwrap().is_empty());
+
+        let keys = parse_audience_keys(
+            &issuers,
+            Some("internal-api=internal-key, partner-api = partner-key"),
+        )
+        .unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[1].audience, "partner-api");
+        assert_eq!(keys[1].kms_key_id, "partner-key");
+
+        assert!(parse_audience_keys(&issuers, Some("internal-api")).is_err());
+        assert!(parse_audience_keys(&issuers, Some("a=k1,a=k2")).is_err());
+        assert!(parse_audience_keys(&issuers, Some("a=k1,b=k1")).is_err());
+        assert!(parse_audience_keys(&issuers, Some("a=issuer-key")).is_err());
+    }
+
+    #[test]
+    fn test_client_registry_parsing() {
+        assert!(parse_client_registry(None).unwrap().is_empty());
+
+        let clients = parse_client_registry(Some(
+            r#"[{"client_id":"web","audiences":[
+                {"audience":"api","claims":["tenant_id"]},
+                {"audience":"internal-api"}
+            ]}]"#,
+        ))
+        .unwrap();
+        assert_eq!(clients[0].audiences.len(), 2);
+        assert_eq!(clients[0].audiences[0].claims, Some(vec!["tenant_id".to_string()]));
+        assert_eq!(clients[0].audiences[1].claims, None);
+
+        assert!(parse_client_registry(Some(r#"[{"client_id":"web","audiences":[]}]"#)).is_err());
+        assert!(parse_client_registry(Some(
+            r#"[{"client_id":"web","audiences":[{"audience":"api"},{"audience":"api"}]}]"#
+        ))
+        .is_err());
+        assert!(parse_client_registry(Some(
+            r#"[{"client_id":"web","audiences":[{"audience":"api"}]},
+                {"client_id":"web","audiences":[{"audience":"api"}]}]"#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_backchannel_logout_clients_parsing() {
+        assert!(parse_backchannel_logout_clients(None).unwrap().is_empty());
+
+        let clients = parse_backchannel_logout_clients(Some(
+            r#"[
+                {"client_id": "web", "backchannel_logout_uri": "https://web.example.com/logout",
+                 "backchannel_logout_session_required": true},
+                {"client_id": "admin", "backchannel_logout_uri": "https://admin.example.com/logout",
+                 "max_retries": 0}
+            ]"#,
+        ))
+        .unwrap();
+
+        assert_eq!(clients.len(), 2);
+        assert!(clients[0].backchannel_logout_session_required);
+        assert_eq!(clients[0].max_retries, None);
+        assert!(!clients[1].backchannel_logout_session_required);
+        assert_eq!(clients[1].max_retries, Some(0));
+
+        let duplicate = r#"[{"client_id": "web", "backchannel_logout_uri": "https://a/logout"},
+                            {"client_id": "web", "backchannel_logout_uri": "https://b/logout"}]"#;
+        assert!(parse_backchannel_logout_clients(Some(duplicate)).is_err());
+        assert!(parse_backchannel_logout_clients(Some(
+            r#"[{"client_id": "web", "backchannel_logout_uri": ""}]"#
+        ))
+        .is_err());
+        assert!(parse_backchannel_logout_clients(Some("not json")).is_err());
+    }
+
+    #[test]
+    fn test_startup_retry_budgets_parsing() {
+        let budgets = parse_startup_retry_budgets(Some("storage=300, cache=60")).unwrap();
+        assert_eq!(budgets["storage"], Duration::from_secs(300));
+        assert_eq!(budgets["cache"], Duration::from_secs(60));
+        assert!(parse_startup_retry_budgets(None).unwrap().is_empty());
+        assert!(parse_startup_retry_budgets(Some("storage")).is_err());
+        assert!(parse_startup_retry_budgets(Some("storage=soon")).is_err());
+        assert!(parse_startup_retry_budgets(Some("=30")).is_err());
+    }
+
+    #[test]
+    fn test_private_claims_parsing() {
+        assert_eq!(
+            parse_private_claims(Some("national_id, account_ref,national_id")).unwrap(),
+            vec!["national_id", "account_ref"]
+        );
+        assert!(parse_private_claims(None).unwrap().is_empty());
+        assert!(parse_private_claims(Some("national_id,sub")).is_err());
+        assert!(parse_private_claims(Some("claims_ref")).is_err());
+    }
+
+    #[test]
+    fn test_replay_anomaly_threshold_bounds() {
+        assert_eq!(parse_replay_anomaly_threshold(0.2).unwrap(), 0.2);
+        assert_eq!(parse_replay_anomaly_threshold(1.0).unwrap(), 1.0);
+        assert!(parse_replay_anomaly_threshold(0.0).is_err());
+        assert!(parse_replay_anomaly_threshold(1.5).is_err());
+        assert!(parse_replay_anomaly_threshold(f64::NAN).is_err());
     }
 }
@@ -1,26 +1,27 @@
 //! CryptoClient implementation for Crypto Service integration.
 
 use super::config::CryptoClientConfig;
-use super::error::CryptoError;
+use super::error::{CryptoError, RetrySafety};
 use super::fallback::FallbackHandler;
 use super::metrics::CryptoMetrics;
+use super::namespace::NamespacePolicy;
 use super::models::{
     EncryptResult, EncryptedData, KeyAlgorithm, KeyId, KeyMetadata, KeyRotationResult, SignResult,
 };
 use super::proto::{
     crypto_service_client::CryptoServiceClient, DecryptRequest, EncryptRequest,
-    GenerateKeyRequest, GetKeyMetadataRequest, HashAlgorithm, RotateKeyRequest, SignRequest,
-    VerifyRequest,
+    GenerateKeyRequest, GetKeyMetadataRequest, HashAlgorithm, KeyEvent, KeyEventType,
+    RotateKeyRequest, SignRequest, VerifyRequest, WatchKeyEventsRequest,
 };
 use async_trait::async_trait;
-use governor::{Quota, RateLimiter as GovRateLimiter};
+use governor::RateLimiter as GovRateLimiter;
 use lru::LruCache;
-use rust_common::CircuitBreaker;
-use std::num::NonZeroU32;
+use rust_common::{CircuitBreaker, CircuitBreakerRegistry, RetryPolicy};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::Instant;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tonic::transport::Channel;
 use tracing::{error, info, instrument, warn};
 use uuid::Uuid;
@@ -44,28 +45,34 @@ type RateLimiter = GovRateLimiter<governor::state::NotKeyed, governor::state::In
 /// CryptoClient implementation with circuit breaker and fallback.
 pub struct CryptoClientCore {
     grpc_client: RwLock<Option<CryptoServiceClient<Channel>>>,
-    circuit_breaker: Arc<CircuitBreaker>,
+    /// One breaker per Crypto Service method
+    circuit_breakers: Arc<CircuitBreakerRegistry>,
+    retry: RetryPolicy,
     rate_limiter: Arc<RateLimiter>,
     metadata_cache: Arc<RwLock<LruCache<String, CachedMetadata>>>,
     fallback: Arc<FallbackHandler>,
     config: CryptoClientConfig,
+    policy: NamespacePolicy,
     metrics: Arc<CryptoMetrics>,
     request_counter: AtomicU64,
+    /// Task applying key events to the metadata cache, aborted on drop
+    key_event_watcher: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl CryptoClientCore {
     pub async fn new(config: CryptoClientConfig, fallback: FallbackHandler) -> Result<Self, CryptoError> {
         config.validate().map_err(|e| CryptoError::internal(e.to_string()))?;
-        let circuit_breaker = Arc::new(CircuitBreaker::new(config.circuit_breaker.clone()));
-        let quota = Quota::per_second(NonZeroU32::new(config.rate_limit).unwrap_or(NonZeroU32::new(1000).unwrap()));
+        let circuit_breakers = Arc::new(CircuitBreakerRegistry::new("crypto-service", config.circuit_breaker.clone()));
+        let quota = config.rate_limit_quota().map_err(|e| CryptoError::internal(e.to_string()))?;
         let rate_limiter = Arc::new(GovRateLimiter::direct(quota));
-        let metadata_cache = Arc::new(RwLock::new(LruCache::new(
-            std::num::NonZeroUsize::new(config.metadata_cache_size).unwrap_or(std::num::NonZeroUsize::new(100).unwrap()),
-        )));
+        let capacity = config.metadata_cache_capacity().map_err(|e| CryptoError::internal(e.to_string()))?;
+        let metadata_cache = Arc::new(RwLock::new(LruCache::new(capacity)));
+        let policy = config.namespace_policy();
+        let retry = RetryPolicy::new(config.retry.clone());
         Ok(Self {
-            grpc_client: RwLock::new(None), circuit_breaker, rate_limiter, metadata_cache,
-            fallback: Arc::new(fallback), config, metrics: Arc::new(CryptoMetrics::new()),
-            request_counter: AtomicU64::new(0),
+            grpc_client: RwLock::new(None), circuit_breakers, retry, rate_limiter, metadata_cache,
+            fallback: Arc::new(fallback), config, policy, metrics: Arc::new(CryptoMetrics::new()),
+            request_counter: AtomicU64::new(0), key_event_watcher: std::sync::Mutex::new(None),
         })
     }
 
@@ -87,18 +94,56 @@ impl CryptoClientCore {
         format!("token-{}-{}", Uuid::new_v4(), self.request_counter.fetch_add(1, Ordering::Relaxed))
     }
 
+    pub(crate) fn check_namespace(&self, namespace: &str) -> Result<(), CryptoError> {
+        self.policy.authorize(namespace).map_err(|e| {
+            warn!(namespace = %self.config.namespace, requested = %namespace, "Key namespace denied by policy");
+            e
+        })
+    }
+
     pub(crate) fn check_rate_limit(&self) -> Result<(), CryptoError> {
         if self.rate_limiter.check().is_err() { self.metrics.record_rate_limited(); return Err(CryptoError::RateLimited); }
         Ok(())
     }
 
-    pub(crate) async fn check_circuit_breaker(&self) -> Result<(), CryptoError> {
-        if !self.circuit_breaker.allow_request().await { self.metrics.record_circuit_breaker_open(); return Err(CryptoError::CircuitBreakerOpen); }
-        Ok(())
+    /// Breaker of `method`, or `CircuitBreakerOpen` while it rejects calls.
+    pub(crate) async fn check_circuit_breaker(&self, method: &str) -> Result<Arc<CircuitBreaker>, CryptoError> {
+        let circuit_breaker = self.circuit_breakers.get(method);
+        if !circuit_breaker.allow_request().await { self.metrics.record_circuit_breaker_open(method); return Err(CryptoError::CircuitBreakerOpen); }
+        Ok(circuit_breaker)
+    }
+
+    /// Records an outcome on `circuit_breaker` and refreshes the open circuit count.
+    async fn record_outcome(&self, circuit_breaker: &CircuitBreaker, ok: bool) {
+        if ok { circuit_breaker.record_success().await } else { circuit_breaker.record_failure().await }
+        self.metrics.set_open_circuits(self.circuit_breakers.stats().await.open);
+    }
+
+    /// Decide whether a failed call may be retried.
+    ///
+    /// Calls that never reached the service are always retried. Timeouts are
+    /// ambiguous and only retried for idempotent calls: read-only operations,
+    /// or writes carrying an idempotency key the service deduplicates on.
+    pub(crate) fn should_retry(&self, operation: &str, idempotent: bool, error: &CryptoError) -> bool {
+        let retry = match error.retry_safety() {
+            RetrySafety::Safe => true,
+            RetrySafety::Ambiguous => idempotent,
+            RetrySafety::Unsafe => false,
+        };
+        if retry {
+            let reason = if matches!(error, CryptoError::Timeout) { "timeout" } else { "unavailable" };
+            self.metrics.record_retry(operation, reason);
+            warn!(operation = operation, error = %error, "Retrying Crypto Service call");
+        }
+        retry
+    }
+
+    fn metadata_cache_key(key_id: &KeyId) -> String {
+        format!("{}:{}:{}", key_id.namespace, key_id.id, key_id.version)
     }
 
     async fn get_cached_metadata(&self, key_id: &KeyId) -> Option<KeyMetadata> {
-        let cache_key = format!("{}:{}:{}", key_id.namespace, key_id.id, key_id.version);
+        let cache_key = Self::metadata_cache_key(key_id);
         let cache = self.metadata_cache.read().await;
         if let Some(cached) = cache.peek(&cache_key) {
             if cached.cached_at.elapsed() < self.config.metadata_cache_ttl { return Some(cached.metadata.clone()); }
@@ -107,11 +152,82 @@ impl CryptoClientCore {
     }
 
     async fn cache_metadata(&self, key_id: &KeyId, metadata: KeyMetadata) {
-        let cache_key = format!("{}:{}:{}", key_id.namespace, key_id.id, key_id.version);
+        let cache_key = Self::metadata_cache_key(key_id);
         let mut cache = self.metadata_cache.write().await;
         cache.put(cache_key, CachedMetadata { metadata, cached_at: Instant::now() });
     }
 
+    /// Drop cached metadata for a key version so the next lookup refetches it.
+    pub async fn invalidate_metadata(&self, key_id: &KeyId) {
+        let removed = self.metadata_cache.write().await.pop(&Self::metadata_cache_key(key_id));
+        if removed.is_some() {
+            self.metrics.record_cache_invalidation("metadata");
+        }
+    }
+
+    /// Apply a key lifecycle event to the metadata cache.
+    ///
+    /// Both the affected version and the version it replaced are invalidated;
+    /// fresh metadata carried by the event is cached for the affected version.
+    pub async fn handle_key_event(&self, event: &KeyEvent) {
+        let Some(key_id) = event.key_id.as_ref().map(KeyId::from_proto) else { return };
+        self.invalidate_metadata(&key_id).await;
+        if let Some(previous) = event.previous_key_id.as_ref().map(KeyId::from_proto) {
+            self.invalidate_metadata(&previous).await;
+        }
+        let deleted = event.event_type == KeyEventType::KeyDeleted as i32;
+        if let (Some(metadata), false) = (event.metadata.as_ref(), deleted) {
+            self.cache_metadata(&key_id, KeyMetadata::from_proto(metadata)).await;
+        }
+        info!(key_id = %key_id.id, version = key_id.version, event_type = event.event_type, "Key event applied to metadata cache");
+    }
+
+    /// Consume `WatchKeyEvents` until the stream ends or the client is dropped.
+    async fn watch_key_events(client: &Weak<Self>) -> Result<(), tonic::Status> {
+        let mut stream = {
+            let Some(core) = client.upgrade() else { return Ok(()) };
+            let mut grpc = core.connect().await.map_err(|e| tonic::Status::unavailable(e.to_string()))?;
+            let request = WatchKeyEventsRequest { namespace: core.config.namespace.clone(), correlation_id: core.generate_correlation_id() };
+            grpc.watch_key_events(request).await?.into_inner()
+        };
+        while let Some(event) = stream.message().await? {
+            let Some(core) = client.upgrade() else { return Ok(()) };
+            core.handle_key_event(&event).await;
+        }
+        Ok(())
+    }
+
+    /// Start a task that keeps the metadata cache in sync with key events.
+    ///
+    /// Does nothing when neither signing nor encryption uses the Crypto
+    /// Service. The task only holds a weak reference and is aborted when the
+    /// client is dropped. It reconnects after `key_event_reconnect_delay` and
+    /// stops quietly if the Crypto Service does not implement
+    /// `WatchKeyEvents`, leaving TTL expiry and local rotation invalidation in
+    /// place.
+    pub fn spawn_key_event_watcher(self: &Arc<Self>) {
+        if !self.config.signing_enabled && !self.config.encryption_enabled { return; }
+        let client = Arc::downgrade(self);
+        let reconnect_delay = self.config.key_event_reconnect_delay;
+        let watcher = tokio::spawn(async move {
+            loop {
+                let result = Self::watch_key_events(&client).await;
+                if client.strong_count() == 0 { return; }
+                match result {
+                    Ok(()) => info!("Key event stream ended, reconnecting"),
+                    Err(status) if status.code() == tonic::Code::Unimplemented => {
+                        info!("Crypto Service does not support WatchKeyEvents, relying on metadata TTL");
+                        return;
+                    }
+                    Err(status) => warn!(code = ?status.code(), "Key event stream failed, reconnecting"),
+                }
+                tokio::time::sleep(reconnect_delay).await;
+            }
+        });
+        let previous = self.key_event_watcher.lock().unwrap_or_else(std::sync::PoisonError::into_inner).replace(watcher);
+        if let Some(previous) = previous { previous.abort(); }
+    }
+
     async fn validate_key_for_signing(&self, key_id: &KeyId) -> Result<(), CryptoError> {
         let metadata = self.get_key_metadata(key_id).await?;
         if !metadata.state.can_sign() {
@@ -121,112 +237,129 @@ impl CryptoClientCore {
     }
 }
 
+impl Drop for CryptoClientCore {
+    fn drop(&mut self) {
+        let watcher = self.key_event_watcher.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner).take();
+        if let Some(watcher) = watcher { watcher.abort(); }
+    }
+}
+
 #[async_trait]
 impl CryptoClient for CryptoClientCore {
     #[instrument(skip(self, data), fields(key_id = %key_id.id))]
     async fn sign(&self, data: &[u8], key_id: &KeyId) -> Result<SignResult, CryptoError> {
+        self.check_namespace(&key_id.namespace)?;
         if !self.config.signing_enabled { return self.fallback.sign_local(data, key_id).await; }
         self.check_rate_limit()?;
-        self.check_circuit_breaker().await?;
+        let circuit_breaker = self.check_circuit_breaker("Sign").await?;
         self.validate_key_for_signing(key_id).await?;
         let correlation_id = self.generate_correlation_id();
         let start = Instant::now();
-        let result = async {
+        // The correlation ID doubles as idempotency key and is reused on every retry
+        let cid = correlation_id.as_str();
+        let result = self.retry.execute_when(|| async move {
             let mut client = self.connect().await?;
-            let request = SignRequest { data: data.to_vec(), key_id: Some(key_id.to_proto()), hash_algorithm: HashAlgorithm::Sha256 as i32, correlation_id: correlation_id.clone() };
+            let request = SignRequest { data: data.to_vec(), key_id: Some(key_id.to_proto()), hash_algorithm: HashAlgorithm::Sha256 as i32, correlation_id: cid.to_string(), idempotency_key: cid.to_string() };
             let response = client.sign(request).await.map_err(CryptoError::from)?.into_inner();
             let result_key_id = response.key_id.map(|k| KeyId::from_proto(&k)).unwrap_or_else(|| key_id.clone());
             Ok(SignResult { signature: response.signature, key_id: result_key_id, algorithm: response.algorithm })
-        }.await;
+        }, |e| self.should_retry("sign", true, e)).await;
         self.metrics.record_operation("sign", result.is_ok(), start.elapsed());
         match result {
-            Ok(r) => { self.circuit_breaker.record_success().await; Ok(r) }
+            Ok(r) => { self.record_outcome(&circuit_breaker, true).await; Ok(r) }
             Err(e) if e.is_transient() && self.config.fallback_enabled => {
-                self.circuit_breaker.record_failure().await; warn!(error = %e, "Sign failed, using fallback");
+                self.record_outcome(&circuit_breaker, false).await; warn!(error = %e, "Sign failed, using fallback");
                 self.metrics.record_fallback_activation("sign"); self.fallback.sign_local(data, key_id).await
             }
-            Err(e) => { self.circuit_breaker.record_failure().await; error!(error = %e, correlation_id = %correlation_id, "Sign failed"); Err(e) }
+            Err(e) => { self.record_outcome(&circuit_breaker, false).await; error!(error = %e, correlation_id = %correlation_id, "Sign failed"); Err(e) }
         }
     }
 
     #[instrument(skip(self, data, signature), fields(key_id = %key_id.id))]
     async fn verify(&self, data: &[u8], signature: &[u8], key_id: &KeyId) -> Result<bool, CryptoError> {
+        self.check_namespace(&key_id.namespace)?;
         if !self.config.signing_enabled { return self.fallback.verify_local(data, signature, key_id).await; }
         self.check_rate_limit()?;
-        self.check_circuit_breaker().await?;
+        let circuit_breaker = self.check_circuit_breaker("Verify").await?;
         let correlation_id = self.generate_correlation_id();
         let start = Instant::now();
-        let result = async {
+        let cid = correlation_id.as_str();
+        let result = self.retry.execute_when(|| async move {
             let mut client = self.connect().await?;
-            let request = VerifyRequest { data: data.to_vec(), signature: signature.to_vec(), key_id: Some(key_id.to_proto()), hash_algorithm: HashAlgorithm::Sha256 as i32, correlation_id: correlation_id.clone() };
+            let request = VerifyRequest { data: data.to_vec(), signature: signature.to_vec(), key_id: Some(key_id.to_proto()), hash_algorithm: HashAlgorithm::Sha256 as i32, correlation_id: cid.to_string() };
             Ok(client.verify(request).await.map_err(CryptoError::from)?.into_inner().valid)
-        }.await;
+        }, |e| self.should_retry("verify", true, e)).await;
         self.metrics.record_operation("verify", result.is_ok(), start.elapsed());
         match result {
-            Ok(v) => { self.circuit_breaker.record_success().await; Ok(v) }
+            Ok(v) => { self.record_outcome(&circuit_breaker, true).await; Ok(v) }
             Err(e) if e.is_transient() && self.config.fallback_enabled => {
-                self.circuit_breaker.record_failure().await; warn!(error = %e, "Verify failed, using fallback");
+                self.record_outcome(&circuit_breaker, false).await; warn!(error = %e, "Verify failed, using fallback");
                 self.metrics.record_fallback_activation("verify"); self.fallback.verify_local(data, signature, key_id).await
             }
-            Err(e) => { self.circuit_breaker.record_failure().await; error!(error = %e, correlation_id = %correlation_id, "Verify failed"); Err(e) }
+            Err(e) => { self.record_outcome(&circuit_breaker, false).await; error!(error = %e, correlation_id = %correlation_id, "Verify failed"); Err(e) }
         }
     }
 
     #[instrument(skip(self, plaintext, aad), fields(key_id = %key_id.id))]
     async fn encrypt(&self, plaintext: &[u8], key_id: &KeyId, aad: Option<&[u8]>) -> Result<EncryptResult, CryptoError> {
+        self.check_namespace(&key_id.namespace)?;
         if !self.config.encryption_enabled { return self.fallback.encrypt_local(plaintext, aad).await; }
         self.check_rate_limit()?;
-        self.check_circuit_breaker().await?;
+        let circuit_breaker = self.check_circuit_breaker("Encrypt").await?;
         let correlation_id = self.generate_correlation_id();
         let start = Instant::now();
-        let result = async {
+        let cid = correlation_id.as_str();
+        let result = self.retry.execute_when(|| async move {
             let mut client = self.connect().await?;
-            let request = EncryptRequest { plaintext: plaintext.to_vec(), key_id: Some(key_id.to_proto()), aad: aad.map(|a| a.to_vec()).unwrap_or_default(), correlation_id: correlation_id.clone() };
+            let request = EncryptRequest { plaintext: plaintext.to_vec(), key_id: Some(key_id.to_proto()), aad: aad.map(|a| a.to_vec()).unwrap_or_default(), correlation_id: cid.to_string(), idempotency_key: cid.to_string() };
             let response = client.encrypt(request).await.map_err(CryptoError::from)?.into_inner();
             let result_key_id = response.key_id.map(|k| KeyId::from_proto(&k)).unwrap_or_else(|| key_id.clone());
             Ok(EncryptResult { ciphertext: response.ciphertext, iv: response.iv, tag: response.tag, key_id: result_key_id, algorithm: response.algorithm })
-        }.await;
+        }, |e| self.should_retry("encrypt", true, e)).await;
         self.metrics.record_operation("encrypt", result.is_ok(), start.elapsed());
         match result {
-            Ok(r) => { self.circuit_breaker.record_success().await; Ok(r) }
+            Ok(r) => { self.record_outcome(&circuit_breaker, true).await; Ok(r) }
             Err(e) if e.is_transient() && self.config.fallback_enabled => {
-                self.circuit_breaker.record_failure().await; warn!(error = %e, "Encrypt failed, using fallback");
+                self.record_outcome(&circuit_breaker, false).await; warn!(error = %e, "Encrypt failed, using fallback");
                 self.metrics.record_fallback_activation("encrypt"); self.fallback.encrypt_local(plaintext, aad).await
             }
-            Err(e) => { self.circuit_breaker.record_failure().await; error!(error = %e, correlation_id = %correlation_id, "Encrypt failed"); Err(e) }
+            Err(e) => { self.record_outcome(&circuit_breaker, false).await; error!(error = %e, correlation_id = %correlation_id, "Encrypt failed"); Err(e) }
         }
     }
 
     #[instrument(skip(self, encrypted, aad), fields(key_id = %key_id.id))]
     async fn decrypt(&self, encrypted: &EncryptedData, key_id: &KeyId, aad: Option<&[u8]>) -> Result<Vec<u8>, CryptoError> {
+        self.check_namespace(&key_id.namespace)?;
         if !self.config.encryption_enabled { return self.fallback.decrypt_local(encrypted, aad).await; }
         self.check_rate_limit()?;
-        self.check_circuit_breaker().await?;
+        let circuit_breaker = self.check_circuit_breaker("Decrypt").await?;
         let correlation_id = self.generate_correlation_id();
         let start = Instant::now();
-        let result = async {
+        let cid = correlation_id.as_str();
+        let result = self.retry.execute_when(|| async move {
             let mut client = self.connect().await?;
-            let request = DecryptRequest { ciphertext: encrypted.ciphertext.clone(), iv: encrypted.iv.clone(), tag: encrypted.tag.clone(), key_id: Some(key_id.to_proto()), aad: aad.map(|a| a.to_vec()).unwrap_or_default(), correlation_id: correlation_id.clone() };
+            let request = DecryptRequest { ciphertext: encrypted.ciphertext.clone(), iv: encrypted.iv.clone(), tag: encrypted.tag.clone(), key_id: Some(key_id.to_proto()), aad: aad.map(|a| a.to_vec()).unwrap_or_default(), correlation_id: cid.to_string() };
             Ok(client.decrypt(request).await.map_err(CryptoError::from)?.into_inner().plaintext)
-        }.await;
+        }, |e| self.should_retry("decrypt", true, e)).await;
         self.metrics.record_operation("decrypt", result.is_ok(), start.elapsed());
         match result {
-            Ok(p) => { self.circuit_breaker.record_success().await; Ok(p) }
+            Ok(p) => { self.record_outcome(&circuit_breaker, true).await; Ok(p) }
             Err(e) if e.is_transient() && self.config.fallback_enabled => {
-                self.circuit_breaker.record_failure().await; warn!(error = %e, "Decrypt failed, using fallback");
+                self.record_outcome(&circuit_breaker, false).await; warn!(error = %e, "Decrypt failed, using fallback");
                 self.metrics.record_fallback_activation("decrypt"); self.fallback.decrypt_local(encrypted, aad).await
             }
-            Err(e) => { self.circuit_breaker.record_failure().await; error!(error = %e, correlation_id = %correlation_id, "Decrypt failed"); Err(e) }
+            Err(e) => { self.record_outcome(&circuit_breaker, false).await; error!(error = %e, correlation_id = %correlation_id, "Decrypt failed"); Err(e) }
         }
     }
 
     #[instrument(skip(self), fields(algorithm = ?algorithm, namespace = namespace))]
     async fn generate_key(&self, algorithm: KeyAlgorithm, namespace: &str) -> Result<KeyId, CryptoError> {
+        self.check_namespace(namespace)?;
         self.check_rate_limit()?;
-        self.check_circuit_breaker().await?;
+        let circuit_breaker = self.check_circuit_breaker("GenerateKey").await?;
         let correlation_id = self.generate_correlation_id();
         let start = Instant::now();
-        let result = async {
+        let result: Result<_, CryptoError> = async {
             let mut client = self.connect().await?;
             let request = GenerateKeyRequest { algorithm: algorithm.to_proto(), namespace: namespace.to_string(), metadata: std::collections::HashMap::new(), correlation_id: correlation_id.clone() };
             let response = client.generate_key(request).await.map_err(CryptoError::from)?.into_inner();
@@ -235,40 +368,44 @@ impl CryptoClient for CryptoClientCore {
             Ok(key_id)
         }.await;
         self.metrics.record_operation("generate_key", result.is_ok(), start.elapsed());
-        match &result { Ok(_) => self.circuit_breaker.record_success().await, Err(_) => self.circuit_breaker.record_failure().await }
+        self.record_outcome(&circuit_breaker, result.is_ok()).await;
         result
     }
 
     #[instrument(skip(self), fields(key_id = %key_id.id))]
     async fn rotate_key(&self, key_id: &KeyId) -> Result<KeyRotationResult, CryptoError> {
+        self.check_namespace(&key_id.namespace)?;
         self.check_rate_limit()?;
-        self.check_circuit_breaker().await?;
+        let circuit_breaker = self.check_circuit_breaker("RotateKey").await?;
         let correlation_id = self.generate_correlation_id();
         let start = Instant::now();
-        let result = async {
+        let result: Result<_, CryptoError> = async {
             let mut client = self.connect().await?;
             let request = RotateKeyRequest { key_id: Some(key_id.to_proto()), correlation_id: correlation_id.clone() };
             let response = client.rotate_key(request).await.map_err(CryptoError::from)?.into_inner();
             let new_key_id = response.new_key_id.map(|k| KeyId::from_proto(&k)).ok_or_else(|| CryptoError::internal("No new_key_id"))?;
             let old_key_id = response.old_key_id.map(|k| KeyId::from_proto(&k)).ok_or_else(|| CryptoError::internal("No old_key_id"))?;
             let metadata = response.metadata.map(|m| KeyMetadata::from_proto(&m)).ok_or_else(|| CryptoError::internal("No metadata"))?;
+            self.invalidate_metadata(&old_key_id).await;
+            if key_id != &old_key_id { self.invalidate_metadata(key_id).await; }
             self.cache_metadata(&new_key_id, metadata.clone()).await;
             Ok(KeyRotationResult { new_key_id, old_key_id, metadata })
         }.await;
         self.metrics.record_operation("rotate_key", result.is_ok(), start.elapsed());
-        match &result { Ok(_) => self.circuit_breaker.record_success().await, Err(_) => self.circuit_breaker.record_failure().await }
+        self.record_outcome(&circuit_breaker, result.is_ok()).await;
         result
     }
 
     #[instrument(skip(self), fields(key_id = %key_id.id))]
     async fn get_key_metadata(&self, key_id: &KeyId) -> Result<KeyMetadata, CryptoError> {
+        self.check_namespace(&key_id.namespace)?;
         if let Some(cached) = self.get_cached_metadata(key_id).await { self.metrics.record_cache_hit("metadata"); return Ok(cached); }
         self.metrics.record_cache_miss("metadata");
         self.check_rate_limit()?;
-        self.check_circuit_breaker().await?;
+        let circuit_breaker = self.check_circuit_breaker("GetKeyMetadata").await?;
         let correlation_id = self.generate_correlation_id();
         let start = Instant::now();
-        let result = async {
+        let result: Result<_, CryptoError> = async {
             let mut client = self.connect().await?;
             let request = GetKeyMetadataRequest { key_id: Some(key_id.to_proto()), correlation_id: correlation_id.clone() };
             let response = client.get_key_metadata(request).await.map_err(CryptoError::from)?.into_inner();
@@ -277,7 +414,7 @@ impl CryptoClient for CryptoClientCore {
             Ok(metadata)
         }.await;
         self.metrics.record_operation("get_key_metadata", result.is_ok(), start.elapsed());
-        match &result { Ok(_) => self.circuit_breaker.record_success().await, Err(_) => self.circuit_breaker.record_failure().await }
+        self.record_outcome(&circuit_breaker, result.is_ok()).await;
         result
     }
 }
@@ -293,6 +430,92 @@ mod tests {
         assert!(CryptoClientCore::new(config, fallback).await.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_create_client_rejects_degenerate_config() {
+        let zero_cache = CryptoClientConfig { metadata_cache_size: 0, ..CryptoClientConfig::default() };
+        let zero_rate = CryptoClientConfig::default().with_rate_limit(0);
+        for config in [zero_cache, zero_rate] {
+            let result = CryptoClientCore::new(config, FallbackHandler::new_disabled()).await;
+            assert!(matches!(result, Err(CryptoError::Internal(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_method_does_not_open_other_circuits() {
+        let mut config = CryptoClientConfig { address: "http://127.0.0.1:1".to_string(), ..CryptoClientConfig::default() };
+        config.circuit_breaker = config.circuit_breaker.with_failure_threshold(1);
+        let client = CryptoClientCore::new(config, FallbackHandler::new_disabled()).await.unwrap();
+
+        assert!(client.rotate_key(&KeyId::new("token", "signing", 1)).await.is_err());
+        assert!(matches!(client.check_circuit_breaker("RotateKey").await, Err(CryptoError::CircuitBreakerOpen)));
+        assert!(client.check_circuit_breaker("Sign").await.is_ok());
+        assert_eq!(client.circuit_breakers.stats().await.open, 1);
+    }
+
+    #[tokio::test]
+    async fn test_foreign_namespace_rejected_before_network() {
+        let config = CryptoClientConfig::default().with_namespace("token");
+        let client = CryptoClientCore::new(config, FallbackHandler::new_disabled()).await.unwrap();
+        let foreign = KeyId::new("auth-edge", "cache-kek", 1);
+
+        assert!(matches!(client.sign(b"data", &foreign).await, Err(CryptoError::NamespaceDenied(_))));
+        assert!(matches!(client.rotate_key(&foreign).await, Err(CryptoError::NamespaceDenied(_))));
+        assert!(matches!(
+            client.generate_key(KeyAlgorithm::Aes256Gcm, "auth-edge").await,
+            Err(CryptoError::NamespaceDenied(_))
+        ));
+    }
+
+    fn metadata(key_id: &KeyId, state: i32) -> super::super::proto::KeyMetadata {
+        super::super::proto::KeyMetadata { id: Some(key_id.to_proto()), algorithm: 8, state, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_rotation_event_invalidates_previous_version() {
+        let client = CryptoClientCore::new(CryptoClientConfig::default(), FallbackHandler::new_disabled()).await.unwrap();
+        let v1 = KeyId::new("token", "signing", 1);
+        let v2 = KeyId::new("token", "signing", 2);
+        client.cache_metadata(&v1, KeyMetadata::from_proto(&metadata(&v1, 2))).await;
+
+        client.handle_key_event(&KeyEvent {
+            event_type: KeyEventType::KeyRotated as i32,
+            key_id: Some(v2.to_proto()),
+            previous_key_id: Some(v1.to_proto()),
+            metadata: Some(metadata(&v2, 2)),
+            occurred_at: 0,
+        }).await;
+
+        assert!(client.get_cached_metadata(&v1).await.is_none());
+        assert_eq!(client.get_cached_metadata(&v2).await.unwrap().id, v2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_event_does_not_recache() {
+        let client = CryptoClientCore::new(CryptoClientConfig::default(), FallbackHandler::new_disabled()).await.unwrap();
+        let v1 = KeyId::new("token", "signing", 1);
+        client.cache_metadata(&v1, KeyMetadata::from_proto(&metadata(&v1, 2))).await;
+
+        client.handle_key_event(&KeyEvent {
+            event_type: KeyEventType::KeyDeleted as i32,
+            key_id: Some(v1.to_proto()),
+            previous_key_id: None,
+            metadata: Some(metadata(&v1, 5)),
+            occurred_at: 0,
+        }).await;
+
+        assert!(client.get_cached_metadata(&v1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_should_retry_classification() {
+        let client = CryptoClientCore::new(CryptoClientConfig::default(), FallbackHandler::new_disabled()).await.unwrap();
+
+        assert!(client.should_retry("sign", true, &CryptoError::Timeout));
+        assert!(!client.should_retry("sign", false, &CryptoError::Timeout));
+        assert!(client.should_retry("sign", false, &CryptoError::connection("refused")));
+        assert!(!client.should_retry("sign", true, &CryptoError::signing("bad key")));
+    }
+
     #[tokio::test]
     async fn test_rate_limit_check() {
         let config = CryptoClientConfig::default().with_rate_limit(1);
@@ -1,6 +1,10 @@
 //! Configuration for CryptoClient.
 
-use rust_common::CircuitBreakerConfig;
+use super::namespace::NamespacePolicy;
+use governor::Quota;
+use rust_common::{CircuitBreakerConfig, RetryConfig};
+use std::collections::HashMap;
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::time::Duration;
 
 /// Configuration for CryptoClient.
@@ -18,6 +22,8 @@ pub struct CryptoClientConfig {
     pub fallback_enabled: bool,
     /// Circuit breaker configuration
     pub circuit_breaker: CircuitBreakerConfig,
+    /// Retry policy for sign/verify/encrypt/decrypt calls
+    pub retry: RetryConfig,
     /// Rate limit (requests per second)
     pub rate_limit: u32,
     /// Connection timeout
@@ -28,6 +34,14 @@ pub struct CryptoClientConfig {
     pub metadata_cache_ttl: Duration,
     /// Maximum cache size
     pub metadata_cache_size: usize,
+    /// Delay before reconnecting the key event stream
+    pub key_event_reconnect_delay: Duration,
+    /// Additional key namespace prefixes this service may use
+    pub allowed_namespace_prefixes: Vec<String>,
+    /// SPIFFE ID of this workload
+    pub spiffe_id: Option<String>,
+    /// SPIFFE ID patterns mapped to the namespace prefixes they grant
+    pub spiffe_namespaces: HashMap<String, Vec<String>>,
 }
 
 impl Default for CryptoClientConfig {
@@ -39,11 +53,19 @@ impl Default for CryptoClientConfig {
             encryption_enabled: true,
             fallback_enabled: true,
             circuit_breaker: CircuitBreakerConfig::default(),
+            retry: RetryConfig::default()
+                .with_max_retries(2)
+                .with_initial_delay(Duration::from_millis(50))
+                .with_max_delay(Duration::from_secs(1)),
             rate_limit: 1000,
             connect_timeout: Duration::from_secs(5),
             request_timeout: Duration::from_secs(30),
             metadata_cache_ttl: Duration::from_secs(300),
             metadata_cache_size: 100,
+            key_event_reconnect_delay: Duration::from_secs(5),
+            allowed_namespace_prefixes: Vec::new(),
+            spiffe_id: None,
+            spiffe_namespaces: HashMap::new(),
         }
     }
 }
@@ -78,6 +100,20 @@ impl CryptoClientConfig {
             config.rate_limit = val.parse().unwrap_or(1000);
         }
 
+        if let Ok(val) = std::env::var("CRYPTO_MAX_RETRIES") {
+            config.retry = config.retry.with_max_retries(val.parse().unwrap_or(2));
+        }
+
+        if let Ok(val) = std::env::var("CRYPTO_ALLOWED_NAMESPACES") {
+            config.allowed_namespace_prefixes = val.split(',').map(|s| s.trim().to_string()).collect();
+        }
+
+        config.spiffe_id = std::env::var("CRYPTO_SPIFFE_ID").ok();
+
+        if let Ok(val) = std::env::var("CRYPTO_SPIFFE_NAMESPACES") {
+            config.spiffe_namespaces = parse_namespace_map(&val);
+        }
+
         config
     }
 
@@ -95,13 +131,30 @@ impl CryptoClientConfig {
             return Err(ConfigValidationError::MissingNamespace);
         }
 
-        if self.rate_limit == 0 {
-            return Err(ConfigValidationError::InvalidRateLimit);
+        self.rate_limit_quota()?;
+        self.metadata_cache_capacity()?;
+
+        if let Some(spiffe_id) = &self.spiffe_id {
+            if !spiffe_id.starts_with("spiffe://") {
+                return Err(ConfigValidationError::InvalidSpiffeId(spiffe_id.clone()));
+            }
         }
 
         Ok(())
     }
 
+    /// Per-second quota of the client-side rate limiter.
+    pub fn rate_limit_quota(&self) -> Result<Quota, ConfigValidationError> {
+        NonZeroU32::new(self.rate_limit)
+            .map(Quota::per_second)
+            .ok_or(ConfigValidationError::InvalidRateLimit)
+    }
+
+    /// Capacity of the key metadata cache.
+    pub fn metadata_cache_capacity(&self) -> Result<NonZeroUsize, ConfigValidationError> {
+        NonZeroUsize::new(self.metadata_cache_size).ok_or(ConfigValidationError::InvalidMetadataCacheSize)
+    }
+
     /// Set address.
     #[must_use]
     pub fn with_address(mut self, address: impl Into<String>) -> Self {
@@ -137,6 +190,43 @@ impl CryptoClientConfig {
         self
     }
 
+    /// Set additional allowed namespace prefixes.
+    #[must_use]
+    pub fn with_allowed_namespace_prefixes(mut self, prefixes: Vec<String>) -> Self {
+        self.allowed_namespace_prefixes = prefixes;
+        self
+    }
+
+    /// Set the workload SPIFFE ID and its namespace mapping.
+    #[must_use]
+    pub fn with_spiffe_namespaces(
+        mut self,
+        spiffe_id: Option<String>,
+        mapping: HashMap<String, Vec<String>>,
+    ) -> Self {
+        self.spiffe_id = spiffe_id;
+        self.spiffe_namespaces = mapping;
+        self
+    }
+
+    /// Build the key namespace policy for this service.
+    #[must_use]
+    pub fn namespace_policy(&self) -> NamespacePolicy {
+        let policy = NamespacePolicy::own(&self.namespace)
+            .with_prefixes(self.allowed_namespace_prefixes.iter().cloned());
+        match &self.spiffe_id {
+            Some(spiffe_id) => policy.with_spiffe_identity(spiffe_id, &self.spiffe_namespaces),
+            None => policy,
+        }
+    }
+
+    /// Set retry policy.
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Set rate limit.
     #[must_use]
     pub const fn with_rate_limit(mut self, rate_limit: u32) -> Self {
@@ -156,6 +246,28 @@ pub enum ConfigValidationError {
 
     #[error("CRYPTO_RATE_LIMIT must be greater than 0")]
     InvalidRateLimit,
+
+    #[error("metadata cache size must be greater than 0")]
+    InvalidMetadataCacheSize,
+
+    #[error("CRYPTO_SPIFFE_ID is not a SPIFFE ID: {0}")]
+    InvalidSpiffeId(String),
+}
+
+/// Parse a `pattern=ns1|ns2,pattern2=ns3` namespace mapping.
+fn parse_namespace_map(value: &str) -> HashMap<String, Vec<String>> {
+    value
+        .split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(pattern, namespaces)| {
+            let namespaces = namespaces
+                .split('|')
+                .map(|ns| ns.trim().to_string())
+                .filter(|ns| !ns.is_empty())
+                .collect();
+            (pattern.trim().to_string(), namespaces)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -199,12 +311,67 @@ mod tests {
         assert!(matches!(result, Err(ConfigValidationError::InvalidRateLimit)));
     }
 
+    #[test]
+    fn test_validate_invalid_metadata_cache_size() {
+        let config = CryptoClientConfig {
+            metadata_cache_size: 0,
+            ..CryptoClientConfig::default()
+        };
+
+        assert!(matches!(config.validate(), Err(ConfigValidationError::InvalidMetadataCacheSize)));
+        assert!(matches!(
+            config.metadata_cache_capacity(),
+            Err(ConfigValidationError::InvalidMetadataCacheSize)
+        ));
+    }
+
+    #[test]
+    fn test_fallible_constructors() {
+        let config = CryptoClientConfig::default();
+        assert!(config.rate_limit_quota().is_ok());
+        assert_eq!(config.metadata_cache_capacity().unwrap().get(), 100);
+        assert!(matches!(
+            config.with_rate_limit(0).rate_limit_quota(),
+            Err(ConfigValidationError::InvalidRateLimit)
+        ));
+    }
+
     #[test]
     fn test_validate_success() {
         let config = CryptoClientConfig::default();
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_validate_invalid_spiffe_id() {
+        let config = CryptoClientConfig::default()
+            .with_spiffe_namespaces(Some("token-service".to_string()), HashMap::new());
+
+        let result = config.validate();
+        assert!(matches!(result, Err(ConfigValidationError::InvalidSpiffeId(_))));
+    }
+
+    #[test]
+    fn test_parse_namespace_map() {
+        let map = parse_namespace_map("spiffe://prod/ns/auth/*=shared|audit, spiffe://prod/sa/x=x");
+        assert_eq!(map["spiffe://prod/ns/auth/*"], vec!["shared", "audit"]);
+        assert_eq!(map["spiffe://prod/sa/x"], vec!["x"]);
+    }
+
+    #[test]
+    fn test_namespace_policy_from_config() {
+        let mapping = HashMap::from([("spiffe://prod/ns/auth/*".to_string(), vec!["shared".to_string()])]);
+        let policy = CryptoClientConfig::default()
+            .with_allowed_namespace_prefixes(vec!["audit".to_string()])
+            .with_spiffe_namespaces(Some("spiffe://prod/ns/auth/sa/token".to_string()), mapping)
+            .namespace_policy();
+
+        assert!(policy.is_allowed("token"));
+        assert!(policy.is_allowed("audit/tenant-a"));
+        assert!(policy.is_allowed("shared"));
+        assert!(!policy.is_allowed("auth-edge"));
+    }
+
     #[test]
     fn test_disabled_services_no_address_required() {
         let config = CryptoClientConfig::default()
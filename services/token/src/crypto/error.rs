@@ -30,11 +30,36 @@ pub enum CryptoError {
     CircuitBreakerOpen,
     /// Request timeout
     Timeout,
+    /// Key namespace outside this service's namespace policy
+    NamespaceDenied(String),
     /// Internal error
     Internal(String),
 }
 
+/// Whether a failed Crypto Service call may be repeated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetrySafety {
+    /// The request did not reach the service; always safe to retry
+    Safe,
+    /// The service may have performed the operation; retry only with an idempotency key
+    Ambiguous,
+    /// The service rejected the request; retrying cannot help
+    Unsafe,
+}
+
 impl CryptoError {
+    /// Classify whether the failed call may be retried.
+    #[must_use]
+    pub fn retry_safety(&self) -> RetrySafety {
+        match self {
+            CryptoError::Connection(_) | CryptoError::CircuitBreakerOpen | CryptoError::RateLimited => {
+                RetrySafety::Safe
+            }
+            CryptoError::Timeout => RetrySafety::Ambiguous,
+            _ => RetrySafety::Unsafe,
+        }
+    }
+
     /// Check if error is transient (suitable for fallback).
     #[must_use]
     pub fn is_transient(&self) -> bool {
@@ -108,6 +133,7 @@ impl fmt::Display for CryptoError {
             CryptoError::RateLimited => write!(f, "Rate limited"),
             CryptoError::CircuitBreakerOpen => write!(f, "Circuit breaker open"),
             CryptoError::Timeout => write!(f, "Request timeout"),
+            CryptoError::NamespaceDenied(ns) => write!(f, "Key namespace not authorized: {}", ns),
             CryptoError::Internal(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -139,6 +165,15 @@ mod tests {
         assert!(CryptoError::RateLimited.is_transient());
         assert!(!CryptoError::Signing("test".to_string()).is_transient());
         assert!(!CryptoError::KeyNotFound("test".to_string()).is_transient());
+        assert!(!CryptoError::NamespaceDenied("test".to_string()).is_transient());
+    }
+
+    #[test]
+    fn test_retry_safety() {
+        assert_eq!(CryptoError::connection("refused").retry_safety(), RetrySafety::Safe);
+        assert_eq!(CryptoError::RateLimited.retry_safety(), RetrySafety::Safe);
+        assert_eq!(CryptoError::Timeout.retry_safety(), RetrySafety::Ambiguous);
+        assert_eq!(CryptoError::signing("bad key").retry_safety(), RetrySafety::Unsafe);
     }
 
     #[test]
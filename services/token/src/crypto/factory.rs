@@ -25,7 +25,7 @@ impl CryptoClientFactory {
     ) -> Result<Arc<dyn CryptoClient>, CryptoError> {
         let fallback = FallbackHandler::new(signing_key, encryption_key);
         let client = CryptoClientCore::new(config, fallback).await?;
-        Ok(Arc::new(client))
+        Ok(Self::watched(client))
     }
 
     /// Create a CryptoClient with disabled fallback (for testing).
@@ -38,7 +38,14 @@ impl CryptoClientFactory {
     ) -> Result<Arc<dyn CryptoClient>, CryptoError> {
         let fallback = FallbackHandler::new_disabled();
         let client = CryptoClientCore::new(config, fallback).await?;
-        Ok(Arc::new(client))
+        Ok(Self::watched(client))
+    }
+
+    /// Share `client`, keeping its metadata cache in sync with key events.
+    fn watched(client: CryptoClientCore) -> Arc<dyn CryptoClient> {
+        let client = Arc::new(client);
+        client.spawn_key_event_watcher();
+        client
     }
 
     /// Create a CryptoSigner for JWT signing.
@@ -63,6 +70,102 @@ impl CryptoClientFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::proto::crypto_service_server::{CryptoService, CryptoServiceServer};
+    use crate::crypto::proto::{
+        DecryptRequest, DecryptResponse, DeleteKeyRequest, DeleteKeyResponse, EncryptRequest,
+        EncryptResponse, GenerateKeyRequest, GenerateKeyResponse, GetKeyMetadataRequest,
+        GetKeyMetadataResponse, HealthCheckRequest, HealthCheckResponse, KeyEvent, KeyEventType,
+        KeyMetadata, RotateKeyRequest, RotateKeyResponse, SignRequest, SignResponse,
+        VerifyRequest, VerifyResponse, WatchKeyEventsRequest,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+    use tonic::{Request, Response, Status};
+
+    /// Crypto Service answering metadata lookups and streaming queued key events
+    struct FakeCryptoService {
+        metadata_lookups: Arc<AtomicUsize>,
+        events: Mutex<Option<mpsc::Receiver<Result<KeyEvent, Status>>>>,
+    }
+
+    #[tonic::async_trait]
+    impl CryptoService for FakeCryptoService {
+        type WatchKeyEventsStream = ReceiverStream<Result<KeyEvent, Status>>;
+
+        async fn get_key_metadata(&self, request: Request<GetKeyMetadataRequest>) -> Result<Response<GetKeyMetadataResponse>, Status> {
+            self.metadata_lookups.fetch_add(1, Ordering::SeqCst);
+            let metadata = KeyMetadata { id: request.into_inner().key_id, algorithm: 8, state: 2, ..Default::default() };
+            Ok(Response::new(GetKeyMetadataResponse { metadata: Some(metadata) }))
+        }
+
+        async fn watch_key_events(&self, _request: Request<WatchKeyEventsRequest>) -> Result<Response<Self::WatchKeyEventsStream>, Status> {
+            let events = self.events.lock().unwrap().take().ok_or_else(|| Status::unimplemented("already watched"))?;
+            Ok(Response::new(ReceiverStream::new(events)))
+        }
+
+        async fn encrypt(&self, _: Request<EncryptRequest>) -> Result<Response<EncryptResponse>, Status> { Err(Status::unimplemented("fake")) }
+        async fn decrypt(&self, _: Request<DecryptRequest>) -> Result<Response<DecryptResponse>, Status> { Err(Status::unimplemented("fake")) }
+        async fn sign(&self, _: Request<SignRequest>) -> Result<Response<SignResponse>, Status> { Err(Status::unimplemented("fake")) }
+        async fn verify(&self, _: Request<VerifyRequest>) -> Result<Response<VerifyResponse>, Status> { Err(Status::unimplemented("fake")) }
+        async fn generate_key(&self, _: Request<GenerateKeyRequest>) -> Result<Response<GenerateKeyResponse>, Status> { Err(Status::unimplemented("fake")) }
+        async fn rotate_key(&self, _: Request<RotateKeyRequest>) -> Result<Response<RotateKeyResponse>, Status> { Err(Status::unimplemented("fake")) }
+        async fn delete_key(&self, _: Request<DeleteKeyRequest>) -> Result<Response<DeleteKeyResponse>, Status> { Err(Status::unimplemented("fake")) }
+        async fn health_check(&self, _: Request<HealthCheckRequest>) -> Result<Response<HealthCheckResponse>, Status> { Err(Status::unimplemented("fake")) }
+    }
+
+    /// Serve `service` on a local port and return its address.
+    async fn serve(service: FakeCryptoService) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(CryptoServiceServer::new(service))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        address
+    }
+
+    #[tokio::test]
+    async fn test_streamed_rotation_evicts_cached_metadata() {
+        let (events, receiver) = mpsc::channel(1);
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let address = serve(FakeCryptoService {
+            metadata_lookups: Arc::clone(&lookups),
+            events: Mutex::new(Some(receiver)),
+        })
+        .await;
+        let config = CryptoClientConfig::default().with_address(address);
+        let client = CryptoClientFactory::create_without_fallback(config).await.unwrap();
+        let v1 = KeyId::new("token", "signing", 1);
+        let v2 = KeyId::new("token", "signing", 2);
+
+        client.get_key_metadata(&v1).await.unwrap();
+        client.get_key_metadata(&v1).await.unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        events
+            .send(Ok(KeyEvent {
+                event_type: KeyEventType::KeyRotated as i32,
+                key_id: Some(v2.to_proto()),
+                previous_key_id: Some(v1.to_proto()),
+                metadata: None,
+                occurred_at: 0,
+            }))
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while lookups.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                client.get_key_metadata(&v1).await.unwrap();
+            }
+        })
+        .await
+        .expect("rotation event did not evict cached metadata");
+    }
 
     #[tokio::test]
     async fn test_create_client() {
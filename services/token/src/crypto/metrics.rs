@@ -55,11 +55,20 @@ static CRYPTO_RATE_LIMITED: Lazy<prometheus::Counter> = Lazy::new(|| {
 static CRYPTO_CIRCUIT_BREAKER: Lazy<Gauge> = Lazy::new(|| {
     register_gauge!(
         "token_service_crypto_circuit_breaker_open",
-        "Circuit breaker state (1 = open, 0 = closed)"
+        "Crypto Service methods whose circuit breaker is open"
     )
     .expect("Failed to register crypto_circuit_breaker metric")
 });
 
+static CRYPTO_CIRCUIT_BREAKER_REJECTED: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "token_service_crypto_circuit_breaker_rejected_total",
+        "Calls rejected by an open circuit breaker",
+        &["method"]
+    )
+    .expect("Failed to register crypto_circuit_breaker_rejected metric")
+});
+
 static CRYPTO_SECURITY_EVENTS: Lazy<CounterVec> = Lazy::new(|| {
     register_counter_vec!(
         "token_service_crypto_security_events_total",
@@ -69,6 +78,15 @@ static CRYPTO_SECURITY_EVENTS: Lazy<CounterVec> = Lazy::new(|| {
     .expect("Failed to register crypto_security_events metric")
 });
 
+static CRYPTO_RETRIES: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "token_service_crypto_retries_total",
+        "Crypto Service call retries",
+        &["operation", "reason"]
+    )
+    .expect("Failed to register crypto_retries metric")
+});
+
 /// Metrics collector for CryptoClient.
 pub struct CryptoMetrics;
 
@@ -84,6 +102,7 @@ impl CryptoMetrics {
         Lazy::force(&CRYPTO_RATE_LIMITED);
         Lazy::force(&CRYPTO_CIRCUIT_BREAKER);
         Lazy::force(&CRYPTO_SECURITY_EVENTS);
+        Lazy::force(&CRYPTO_RETRIES);
         Self
     }
 
@@ -113,19 +132,31 @@ impl CryptoMetrics {
         CRYPTO_CACHE.with_label_values(&[cache_type, "miss"]).inc();
     }
 
+    /// Record cache invalidation.
+    pub fn record_cache_invalidation(&self, cache_type: &str) {
+        CRYPTO_CACHE.with_label_values(&[cache_type, "invalidated"]).inc();
+    }
+
+    /// Record a retried call.
+    pub fn record_retry(&self, operation: &str, reason: &str) {
+        CRYPTO_RETRIES.with_label_values(&[operation, reason]).inc();
+    }
+
     /// Record rate limited request.
     pub fn record_rate_limited(&self) {
         CRYPTO_RATE_LIMITED.inc();
     }
 
-    /// Record circuit breaker open.
-    pub fn record_circuit_breaker_open(&self) {
-        CRYPTO_CIRCUIT_BREAKER.set(1.0);
+    /// Record a call rejected by the open circuit of `method`.
+    pub fn record_circuit_breaker_open(&self, method: &str) {
+        CRYPTO_CIRCUIT_BREAKER_REJECTED
+            .with_label_values(&[method])
+            .inc();
     }
 
-    /// Record circuit breaker closed.
-    pub fn record_circuit_breaker_closed(&self) {
-        CRYPTO_CIRCUIT_BREAKER.set(0.0);
+    /// Record how many methods have an open circuit.
+    pub fn set_open_circuits(&self, open: usize) {
+        CRYPTO_CIRCUIT_BREAKER.set(open as f64);
     }
 
     /// Record security event.
@@ -153,8 +184,11 @@ mod tests {
         metrics.record_fallback_activation("sign");
         metrics.record_cache_hit("metadata");
         metrics.record_cache_miss("metadata");
+        metrics.record_cache_invalidation("metadata");
         metrics.record_rate_limited();
-        metrics.record_circuit_breaker_open();
+        metrics.record_retry("sign", "timeout");
+        metrics.record_circuit_breaker_open("Sign");
+        metrics.set_open_circuits(1);
         metrics.record_security_event("invalid_algorithm");
     }
 }
@@ -11,16 +11,18 @@ pub mod factory;
 pub mod fallback;
 pub mod metrics;
 pub mod models;
+pub mod namespace;
 pub mod signer;
 
 // Re-exports
 pub use client::{CryptoClient, CryptoClientCore};
 pub use config::CryptoClientConfig;
 pub use encryptor::CryptoEncryptor;
-pub use error::CryptoError;
+pub use error::{CryptoError, RetrySafety};
 pub use factory::CryptoClientFactory;
 pub use fallback::FallbackHandler;
 pub use models::{EncryptResult, EncryptedData, KeyId, KeyMetadata, KeyState, SignResult};
+pub use namespace::NamespacePolicy;
 pub use signer::CryptoSigner;
 
 /// Generated protobuf types for Crypto Service
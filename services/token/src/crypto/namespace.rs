@@ -0,0 +1,143 @@
+//! Key namespace policy.
+//!
+//! Restricts which Crypto Service key namespaces this service may use. The
+//! configured namespace is always allowed; extra prefixes come from
+//! `CRYPTO_ALLOWED_NAMESPACES` or from the SPIFFE identity mapping.
+//! Prefixes match whole `/` segments, so `token` covers `token/tenant-a`
+//! but not `token-admin`.
+
+use super::error::CryptoError;
+use super::models::KeyId;
+use std::collections::HashMap;
+
+/// Namespace prefixes this service is authorized for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespacePolicy {
+    allowed_prefixes: Vec<String>,
+}
+
+impl NamespacePolicy {
+    /// Create a policy allowing only the service's own namespace.
+    #[must_use]
+    pub fn own(namespace: impl Into<String>) -> Self {
+        Self {
+            allowed_prefixes: vec![namespace.into()],
+        }
+    }
+
+    /// Add allowed namespace prefixes.
+    #[must_use]
+    pub fn with_prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for prefix in prefixes {
+            let prefix = prefix.into();
+            if !prefix.is_empty() && !self.allowed_prefixes.contains(&prefix) {
+                self.allowed_prefixes.push(prefix);
+            }
+        }
+        self
+    }
+
+    /// Add the prefixes mapped to every SPIFFE pattern the identity matches.
+    ///
+    /// Patterns are exact SPIFFE IDs or `/*` wildcards.
+    #[must_use]
+    pub fn with_spiffe_identity(
+        self,
+        spiffe_id: &str,
+        mapping: &HashMap<String, Vec<String>>,
+    ) -> Self {
+        let mapped: Vec<String> = mapping
+            .iter()
+            .filter(|(pattern, _)| spiffe_matches(spiffe_id, pattern))
+            .flat_map(|(_, prefixes)| prefixes.iter().cloned())
+            .collect();
+        self.with_prefixes(mapped)
+    }
+
+    /// Check whether a namespace falls under an allowed prefix.
+    #[must_use]
+    pub fn is_allowed(&self, namespace: &str) -> bool {
+        self.allowed_prefixes.iter().any(|prefix| {
+            namespace == prefix
+                || namespace
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// Authorize a namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::NamespaceDenied` if no prefix covers it.
+    pub fn authorize(&self, namespace: &str) -> Result<(), CryptoError> {
+        if self.is_allowed(namespace) {
+            Ok(())
+        } else {
+            Err(CryptoError::NamespaceDenied(namespace.to_string()))
+        }
+    }
+
+    /// Authorize the namespace of a key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::NamespaceDenied` for keys in foreign namespaces.
+    pub fn authorize_key(&self, key_id: &KeyId) -> Result<(), CryptoError> {
+        self.authorize(&key_id.namespace)
+    }
+
+    /// Allowed prefixes.
+    #[must_use]
+    pub fn allowed_prefixes(&self) -> &[String] {
+        &self.allowed_prefixes
+    }
+}
+
+/// Match a SPIFFE ID against an exact or `/*` wildcard pattern.
+fn spiffe_matches(spiffe_id: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(prefix) => spiffe_id
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/')),
+        None => spiffe_id == pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_own_namespace_and_tenants() {
+        let policy = NamespacePolicy::own("token");
+        assert!(policy.is_allowed("token"));
+        assert!(policy.is_allowed("token/tenant-a"));
+        assert!(!policy.is_allowed("token-admin"));
+        assert!(!policy.is_allowed("auth-edge"));
+    }
+
+    #[test]
+    fn test_authorize_key_foreign_namespace() {
+        let policy = NamespacePolicy::own("token");
+        let result = policy.authorize_key(&KeyId::new("auth-edge", "cache-kek", 1));
+        assert!(matches!(result, Err(CryptoError::NamespaceDenied(_))));
+    }
+
+    #[test]
+    fn test_spiffe_mapping() {
+        let mapping = HashMap::from([
+            ("spiffe://prod.example/ns/auth/*".to_string(), vec!["shared".to_string()]),
+            ("spiffe://prod.example/ns/billing/*".to_string(), vec!["billing".to_string()]),
+        ]);
+        let policy = NamespacePolicy::own("token")
+            .with_spiffe_identity("spiffe://prod.example/ns/auth/sa/token-service", &mapping);
+
+        assert!(policy.is_allowed("shared/signing"));
+        assert!(!policy.is_allowed("billing"));
+    }
+}
@@ -163,10 +163,30 @@ impl KmsSigner for CryptoSigner {
     }
 }
 
+/// Lets the JWS helpers in [`crate::jwt::jws`] sign through the Crypto Service.
+impl crate::jwt::JwtSigner for CryptoSigner {
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TokenError> {
+        KmsSigner::sign(self, data).await
+    }
+
+    fn get_encoding_key(&self) -> Result<EncodingKey, TokenError> {
+        KmsSigner::get_encoding_key(self)
+    }
+
+    fn key_id(&self) -> &str {
+        KmsSigner::key_id(self)
+    }
+
+    fn algorithm(&self) -> &str {
+        KmsSigner::algorithm(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use super::super::models::{KeyAlgorithm, SignResult};
+    use crate::jwt::jws;
     use chrono::Utc;
 
     /// Mock CryptoClient for testing.
@@ -329,4 +349,33 @@ mod tests {
         let cache = signer.cached_metadata.read().await;
         assert!(cache.is_none());
     }
+
+    #[tokio::test]
+    async fn test_detached_jws() {
+        let client = Arc::new(MockCryptoClient::new_success());
+        let signer = CryptoSigner::new(client, KeyId::new("test", "audit", 1), "PS256");
+
+        let jws = jws::sign_detached(&signer, b"audit record").await.unwrap();
+        let (_, signature) = jws.split_once("..").unwrap();
+        assert_eq!(signature, "AQIDBA");
+    }
+
+    #[tokio::test]
+    async fn test_dual_signature_jws() {
+        let client: Arc<dyn CryptoClient> = Arc::new(MockCryptoClient::new_success());
+        let old = CryptoSigner::new(Arc::clone(&client), KeyId::new("test", "key", 1), "PS256");
+        let new = CryptoSigner::new(client, KeyId::new("test", "key-next", 1), "PS256");
+
+        let jws = jws::sign_general(&[&old, &new], b"audit record").await.unwrap();
+        assert_eq!(jws.signatures.len(), 2);
+        assert_ne!(jws.signatures[0].protected, jws.signatures[1].protected);
+    }
+
+    #[tokio::test]
+    async fn test_jws_rejects_deprecated_key() {
+        let client = Arc::new(MockCryptoClient::new_success().with_state(KeyState::Deprecated));
+        let signer = CryptoSigner::new(client, KeyId::new("test", "key", 1), "PS256");
+
+        assert!(jws::sign_detached(&signer, b"audit record").await.is_err());
+    }
 }
@@ -73,6 +73,28 @@ pub enum TokenError {
     #[error("Internal error: {0}")]
     Internal(String),
 
+    /// Encoded access token exceeds the configured size budget
+    #[error(
+        "Access token is {size} bytes, over the {max} byte limit; largest claims: {}",
+        format_claim_sizes(largest_claims)
+    )]
+    TokenTooLarge {
+        /// Encoded token size in bytes
+        size: usize,
+        /// Configured maximum in bytes
+        max: usize,
+        /// Largest claims and their JSON size in bytes, largest first
+        largest_claims: Vec<(String, usize)>,
+    },
+
+    /// Client is unregistered or may not request an audience
+    #[error("Audience not allowed: {0}")]
+    AudienceNotAllowed(String),
+
+    /// Writes are disabled while the service is read-only
+    #[error("Service is in read-only mode")]
+    ReadOnly,
+
     /// Legacy Redis error (deprecated)
     #[deprecated(since = "2.0.0", note = "Use Cache variant")]
     #[error("Redis error: {0}")]
@@ -92,6 +114,7 @@ impl TokenError {
             Self::RateLimited => true,
             Self::Cache(_) => true,
             Self::RedisError(_) => true,
+            Self::ReadOnly => true,
             _ => false,
         }
     }
@@ -182,6 +205,16 @@ impl From<TokenError> for Status {
             TokenError::RateLimited => {
                 Status::resource_exhausted("RATE_LIMITED")
             }
+            TokenError::ReadOnly => {
+                Status::unavailable("READ_ONLY")
+            }
+            TokenError::AudienceNotAllowed(_) => {
+                Status::permission_denied("AUDIENCE_NOT_ALLOWED")
+            }
+            TokenError::TokenTooLarge { ref largest_claims, .. } => {
+                let names: Vec<&str> = largest_claims.iter().map(|(name, _)| name.as_str()).collect();
+                Status::invalid_argument(format!("TOKEN_TOO_LARGE: {}", names.join(",")))
+            }
             TokenError::Cache(_) | TokenError::RedisError(_) if err.is_retryable() => {
                 Status::unavailable("CACHE_UNAVAILABLE")
             }
@@ -196,6 +229,15 @@ impl From<TokenError> for Status {
     }
 }
 
+/// Format `name (n bytes)` pairs for error messages.
+fn format_claim_sizes(claims: &[(String, usize)]) -> String {
+    claims
+        .iter()
+        .map(|(name, size)| format!("{} ({} bytes)", name, size))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl From<serde_json::Error> for TokenError {
     fn from(err: serde_json::Error) -> Self {
         Self::Serialization(err.to_string())
@@ -249,6 +291,21 @@ mod tests {
         assert_eq!(status.code(), tonic::Code::ResourceExhausted);
     }
 
+    #[test]
+    fn test_token_too_large_lists_claims() {
+        let err = TokenError::TokenTooLarge {
+            size: 9000,
+            max: 8192,
+            largest_claims: vec![("groups".to_string(), 6000), ("roles".to_string(), 900)],
+        };
+        assert!(!err.is_retryable());
+        assert!(err.to_string().contains("groups (6000 bytes), roles (900 bytes)"));
+
+        let status: Status = err.into();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(status.message(), "TOKEN_TOO_LARGE: groups,roles");
+    }
+
     #[test]
     fn test_error_messages_do_not_expose_internals() {
         let status: Status = TokenError::Kms("secret key error".to_string()).into();
@@ -2,84 +2,373 @@
 //!
 //! Integrates JWT, DPoP, refresh tokens, JWKS, and KMS modules.
 
-use crate::config::Config;
+use crate::config::{Config, IssuerProfile, StorageMode};
 use crate::error::TokenError;
-use crate::jwks::{Jwk, JwksPublisher};
-use crate::jwt::{JwtBuilder, JwtSerializer};
+use crate::jwks::{Jwk, JwksPublisher, JwksRegistry, JWKS_CACHE_CONTROL};
+use crate::jwt::{
+    AudienceGrant, ClientAudiences, JwtBuilder, JwtSerializer, PrivateClaimEncryptor,
+    TokenSizeBudget, CLAIMS_REF_CLAIM,
+};
 use crate::kms::{KmsSigner, MockKms};
-use crate::proto::common::Empty;
+use crate::logout::BackchannelLogoutEmitter;
 use crate::proto::token::token_service_server::TokenService;
 use crate::proto::token::*;
-use crate::refresh::{RefreshTokenGenerator, RefreshTokenRotator};
-use crate::storage::CacheStorage;
+use crate::refresh::{
+    RefreshTokenGenerator, RefreshTokenRotator, ReplayAnomalyDetector, TokenFamily,
+};
+use crate::revocation::RevocationJournal;
+use crate::standby::StandbyController;
+use crate::storage::{
+    CacheStorage, DualWriteStorage, FamilyStore, MigrationOptions, StorageMigrator,
+};
 use jsonwebtoken::Algorithm;
-use rust_common::{CacheClient, LoggingClient};
+use rust_common::span_attributes::ALLOW;
+use rust_common::{
+    record_auth, AuthSpan, CacheClient, DistributedLock, LeaseConfig, LoggingClient,
+    RedisLeaseStore,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tonic::metadata::MetadataValue;
 use tonic::{Request, Response, Status};
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
+
+/// Redis key of the lease held by the replica migrating legacy storage.
+pub const LEGACY_MIGRATION_LOCK_KEY: &str = "token:jobs:legacy_migration";
 
 /// Token Service gRPC implementation.
 pub struct TokenServiceImpl {
     config: Config,
-    storage: Arc<CacheStorage>,
+    storage: Arc<dyn FamilyStore>,
     rotator: RefreshTokenRotator,
-    jwks_publisher: JwksPublisher,
-    kms: MockKms,
+    jwks: JwksRegistry,
+    signers: HashMap<String, MockKms>,
+    audience_signers: HashMap<String, MockKms>,
+    client_audiences: ClientAudiences,
+    size_budget: TokenSizeBudget,
+    private_claims: Option<PrivateClaimEncryptor>,
+    cache: Arc<CacheClient>,
+    backchannel_logout: Option<Arc<BackchannelLogoutEmitter>>,
+    revocation_journal: Option<Arc<RevocationJournal>>,
+    standby: Arc<StandbyController>,
     #[allow(dead_code)]
     logger: Arc<LoggingClient>,
 }
 
 impl TokenServiceImpl {
-    /// Create a new Token Service with platform clients.
+    /// Create a new Token Service with platform clients and family storage
+    /// from [`Self::build_storage`].
     pub async fn new(
         config: Config,
         cache_client: Arc<CacheClient>,
         logger: Arc<LoggingClient>,
+        storage: Arc<dyn FamilyStore>,
     ) -> Result<Self, TokenError> {
-        let storage = Arc::new(
-            CacheStorage::new(config.cache.clone())
-                .await
-                .map_err(|e| TokenError::cache(e.to_string()))?,
-        );
-
         let rotator = RefreshTokenRotator::new(
             storage.clone(),
             logger.clone(),
             config.refresh_token_ttl,
-        );
+        )
+        .with_anomaly_detector(Arc::new(ReplayAnomalyDetector::from_config(&config)?));
+
+        // One key set and signer per issuer, each initialized with its key
+        let mut jwks = JwksRegistry::new(config.jwt_issuer.clone(), JwksPublisher::new());
+        let mut signers = HashMap::new();
+        for profile in &config.issuers {
+            let publisher = JwksPublisher::new();
+            publisher
+                .add_key(Self::signing_key(&config, &profile.kms_key_id))
+                .await;
+            jwks.insert(profile.issuer.clone(), publisher);
+            signers.insert(
+                profile.issuer.clone(),
+                MockKms::new(profile.kms_key_id.clone()),
+            );
+        }
 
-        let jwks_publisher = JwksPublisher::new();
-        let kms = MockKms::new(config.kms_key_id.clone());
+        // Audiences with a dedicated key publish it in a key set of their own
+        let mut audience_signers = HashMap::new();
+        for audience_key in &config.audience_keys {
+            let publisher = JwksPublisher::new();
+            publisher
+                .add_key(Self::signing_key(&config, &audience_key.kms_key_id))
+                .await;
+            jwks.insert_audience(audience_key.audience.clone(), publisher);
+            audience_signers.insert(
+                audience_key.audience.clone(),
+                MockKms::new(audience_key.kms_key_id.clone()),
+            );
+        }
 
-        // Initialize with a default key
-        let initial_key = Jwk {
-            kty: "oct".to_string(),
-            kid: config.kms_key_id.clone(),
-            key_use: "sig".to_string(),
-            alg: config.jwt_algorithm.as_str().to_string(),
-            n: None,
-            e: None,
-            x: None,
-            y: None,
-            crv: None,
-        };
-        jwks_publisher.add_key(initial_key).await;
+        let backchannel_logout = BackchannelLogoutEmitter::from_config(&config)?.map(Arc::new);
+
+        let standby = Arc::new(StandbyController::from_config(&config));
+        tokio::spawn(standby.clone().watch(storage.clone()));
+
+        let revocation_journal =
+            RevocationJournal::from_config(&config, cache_client.clone()).map(Arc::new);
+        if let Some(journal) = &revocation_journal {
+            info!("Revoked-JTI filter enabled");
+            tokio::spawn(journal.clone().run(config.revocation_filter_interval));
+        }
 
         info!(
             cache_namespace = %cache_client.namespace(),
+            issuers = ?config.issuers.iter().map(|p| p.issuer.as_str()).collect::<Vec<_>>(),
+            audience_keys = ?config.audience_keys.iter().map(|k| k.audience.as_str()).collect::<Vec<_>>(),
             "Token service initialized"
         );
 
+        let size_budget = TokenSizeBudget::from_config(&config);
+        let private_claims = PrivateClaimEncryptor::from_config(&config).await?;
+        if private_claims.is_some() {
+            info!(claims = ?config.private_claims, "Private claims encrypted at issuance");
+        }
+
         Ok(Self {
+            client_audiences: ClientAudiences::from_config(&config),
             config,
             storage,
             rotator,
-            jwks_publisher,
-            kms,
+            jwks,
+            signers,
+            audience_signers,
+            size_budget,
+            private_claims,
+            cache: cache_client,
+            backchannel_logout,
+            revocation_journal,
+            standby,
             logger,
         })
     }
 
+    /// Build family storage for the configured storage mode.
+    #[allow(deprecated)]
+    pub async fn build_storage(config: &Config) -> Result<Arc<dyn FamilyStore>, TokenError> {
+        let cache: Arc<dyn FamilyStore> = Arc::new(
+            CacheStorage::new(config.cache.clone())
+                .await
+                .map_err(|e| TokenError::cache(e.to_string()))?,
+        );
+
+        let redis_url = match config.legacy_redis_url.as_deref() {
+            Some(url)
+                if config.storage_mode == StorageMode::DualWrite
+                    || config.legacy_migrate_on_start =>
+            {
+                url
+            }
+            _ => return Ok(cache),
+        };
+        let legacy = Arc::new(crate::storage::redis::RedisStorage::new(redis_url).await?);
+
+        if config.legacy_migrate_on_start {
+            let migrator = StorageMigrator::new(
+                legacy.clone(),
+                cache.clone(),
+                MigrationOptions {
+                    default_ttl: config.refresh_token_ttl,
+                    ..MigrationOptions::default()
+                },
+            );
+            // One replica copies the data; the others leave it to the holder
+            let leases = RedisLeaseStore::connect(redis_url)
+                .await
+                .map_err(|e| TokenError::RedisError(e.to_string()))?;
+            let lock = DistributedLock::new(Arc::new(leases), LEGACY_MIGRATION_LOCK_KEY)
+                .with_config(LeaseConfig::with_ttl(config.job_lease_ttl))
+                .on_lost(|lost| {
+                    warn!(
+                        fencing_token = lost.fencing_token,
+                        reason = ?lost.reason,
+                        "Legacy storage migration lease lost, stopping"
+                    )
+                });
+            tokio::spawn(async move {
+                match lock.run_exclusive(migrator.run()).await {
+                    Ok(None) => info!("Legacy storage migration running on another replica"),
+                    Ok(Some(Ok(report))) if report.is_verified() => {}
+                    Ok(Some(Ok(report))) => error!(
+                        failures = ?report.verification_failures,
+                        "Legacy storage migration finished with verification failures"
+                    ),
+                    Ok(Some(Err(e))) => error!(error = %e, "Legacy storage migration failed"),
+                    Err(e) => error!(error = %e, "Legacy storage migration lease failed"),
+                }
+            });
+        }
+
+        match config.storage_mode {
+            StorageMode::Cache => Ok(cache),
+            StorageMode::DualWrite => {
+                info!("Dual-write storage enabled, legacy Redis kept in sync");
+                Ok(Arc::new(DualWriteStorage::new(
+                    cache,
+                    legacy,
+                    config.refresh_token_ttl,
+                )))
+            }
+        }
+    }
+
+    /// Build the published JWK for a signing key.
+    fn signing_key(config: &Config, kid: &str) -> Jwk {
+        Jwk {
+            kty: "oct".to_string(),
+            kid: kid.to_string(),
+            key_use: "sig".to_string(),
+            alg: config.jwt_algorithm.as_str().to_string(),
+            n: None,
+            e: None,
+            x: None,
+            y: None,
+            crv: None,
+        }
+    }
+
+    /// Resolve a requested issuer; empty selects the default.
+    fn issuer_profile(&self, issuer: &str) -> Result<&IssuerProfile, Status> {
+        self.config
+            .issuer_profile(issuer)
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown issuer: {}", issuer)))
+    }
+
+    /// Audiences of an issued token: the registered client's, else the
+    /// requested or default audience.
+    fn token_audiences(&self, req: &IssueTokenRequest) -> Result<AudienceGrant, Status> {
+        if req.client_id.is_empty() {
+            if !req.audiences.is_empty() {
+                return Err(Status::invalid_argument("audiences requires a registered client_id"));
+            }
+            let audience = if req.audience.is_empty() {
+                self.config.jwt_default_audience.clone()
+            } else {
+                req.audience.clone()
+            };
+            return Ok(self.client_audiences.audience_grant(&audience));
+        }
+        if !req.audience.is_empty() {
+            return Err(Status::invalid_argument("Registered clients set audiences, not audience"));
+        }
+
+        let grant = self.client_audiences.grant(&req.client_id, &req.audiences)?;
+        if grant.audiences.len() > 1 {
+            if let Some(dedicated) = grant.audiences.iter().find(|a| self.audience_signers.contains_key(*a)) {
+                return Err(Status::invalid_argument(format!(
+                    "Audience {} has a dedicated signing key and cannot share a token",
+                    dedicated
+                )));
+            }
+        }
+        Ok(grant)
+    }
+
+    /// Audience selecting a dedicated signing key; multi-audience tokens use
+    /// the issuer's key.
+    fn signing_audience(audiences: &[String]) -> &str {
+        match audiences {
+            [audience] => audience,
+            _ => "",
+        }
+    }
+
+    /// Sign access token claims with the audience's dedicated key, if it has
+    /// one, else with the issuer's key.
+    fn sign_access_token(
+        &self,
+        profile: &IssuerProfile,
+        audience: &str,
+        claims: &crate::jwt::Claims,
+    ) -> Result<String, Status> {
+        let kms = match self.audience_signers.get(audience) {
+            Some(kms) => kms,
+            None => self.signers.get(&profile.issuer).ok_or_else(|| {
+                Status::internal(format!("No signer for issuer {}", profile.issuer))
+            })?,
+        };
+
+        let encoding_key = kms
+            .get_encoding_key()
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        JwtSerializer::new(Algorithm::HS256)
+            .serialize(claims, &encoding_key, Some(kms.key_id()))
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+
+    /// Sign an access token within the size budget, storing any claims moved
+    /// out of it until the token expires.
+    async fn issue_access_token(
+        &self,
+        profile: &IssuerProfile,
+        audience: &str,
+        claims: crate::jwt::Claims,
+        ttl: Duration,
+    ) -> Result<String, Status> {
+        let fitted = self
+            .size_budget
+            .fit(claims, |claims| self.sign_access_token(profile, audience, claims))?;
+
+        if let Some(overflow) = &fitted.overflow {
+            let value = serde_json::to_vec(overflow).map_err(|e| Status::internal(e.to_string()))?;
+            self.cache
+                .set(&overflow.key(), &value, Some(ttl))
+                .await
+                .map_err(|e| -> Status { TokenError::cache(e.to_string()).into() })?;
+            info!(
+                claims_ref = %overflow.id,
+                claims = ?overflow.claims.keys().collect::<Vec<_>>(),
+                "Moved access token claims by reference"
+            );
+        }
+        Ok(fitted.token)
+    }
+
+    /// Send back-channel logout tokens for revoked families in the background.
+    ///
+    /// Each session is logged out by the issuer that minted it. A user
+    /// without token families is still logged out at the default issuer's
+    /// relying parties.
+    fn emit_backchannel_logout(&self, user_id: &str, families: &[TokenFamily]) {
+        let Some(emitter) = &self.backchannel_logout else {
+            return;
+        };
+
+        let mut sessions: HashMap<&str, Vec<String>> = HashMap::new();
+        for family in families {
+            let issuer = family.issuer.as_deref().unwrap_or(&self.config.jwt_issuer);
+            let session_ids = sessions.entry(issuer).or_default();
+            if !family.session_id.is_empty() && !session_ids.contains(&family.session_id) {
+                session_ids.push(family.session_id.clone());
+            }
+        }
+        if sessions.is_empty() {
+            sessions.insert(&self.config.jwt_issuer, Vec::new());
+        }
+
+        let mut deliveries = Vec::new();
+        for (issuer, session_ids) in sessions {
+            let Some(kms) = self.signers.get(issuer) else {
+                warn!(issuer = %issuer, "No signer for back-channel logout issuer");
+                continue;
+            };
+            match emitter.logout_tokens(issuer, user_id, &session_ids, kms) {
+                Ok(tokens) => deliveries.extend(tokens),
+                Err(e) => error!(
+                    issuer = %issuer,
+                    error = %e,
+                    "Failed to sign back-channel logout tokens"
+                ),
+            }
+        }
+
+        if !deliveries.is_empty() {
+            tokio::spawn(emitter.clone().deliver(deliveries));
+        }
+    }
+
     /// Extract correlation ID from request metadata.
     fn get_correlation_id<T>(request: &Request<T>) -> Option<String> {
         request
@@ -96,6 +385,8 @@ impl TokenService for TokenServiceImpl {
         &self,
         request: Request<IssueTokenRequest>,
     ) -> Result<Response<TokenPairResponse>, Status> {
+        let span = AuthSpan::of(&request);
+        self.standby.ensure_writable()?;
         let correlation_id = Self::get_correlation_id(&request);
         let req = request.into_inner();
 
@@ -111,10 +402,14 @@ impl TokenService for TokenServiceImpl {
             self.config.refresh_token_ttl.as_secs() as i64
         };
 
+        let profile = self.issuer_profile(&req.issuer)?;
+        let grant = self.token_audiences(&req)?;
+        let audiences = grant.audiences.clone();
+
         // Build access token claims
-        let mut builder = JwtBuilder::new(self.config.jwt_issuer.clone())
+        let mut builder = JwtBuilder::new(profile.issuer.clone())
             .subject(req.user_id.clone())
-            .audience(vec!["api".to_string()])
+            .audience(audiences.clone())
             .ttl_seconds(access_ttl)
             .scopes(req.scopes.clone());
 
@@ -122,38 +417,68 @@ impl TokenService for TokenServiceImpl {
             builder = builder.session_id(req.session_id.clone());
         }
 
+        if req.custom_claims.contains_key(CLAIMS_REF_CLAIM) {
+            return Err(Status::invalid_argument(format!(
+                "Custom claim {} is reserved",
+                CLAIMS_REF_CLAIM
+            )));
+        }
+        // Custom claims reach the token only if every audience may see them
+        let mut withheld = Vec::new();
         for (key, value) in req.custom_claims {
+            if !grant.allows_claim(&key) {
+                withheld.push(key);
+                continue;
+            }
             builder = builder.custom_claim(key, serde_json::Value::String(value));
         }
+        if !withheld.is_empty() {
+            debug!(client_id = %req.client_id, claims = ?withheld, "Withheld custom claims from audiences");
+        }
 
-        let claims = builder.build().map_err(|e| Status::invalid_argument(e))?;
-
-        // Serialize access token
-        let encoding_key = self
-            .kms
-            .get_encoding_key()
-            .map_err(|e| Status::internal(e.to_string()))?;
+        let mut claims = builder.build().map_err(|e| Status::invalid_argument(e))?;
+        if let Some(private_claims) = &self.private_claims {
+            private_claims.encrypt(&mut claims).await?;
+        }
 
-        let access_token = JwtSerializer::new(Algorithm::HS256)
-            .serialize(&claims, &encoding_key, Some(&self.config.kms_key_id))
-            .map_err(|e| Status::internal(e.to_string()))?;
+        let access_token = self
+            .issue_access_token(
+                profile,
+                Self::signing_audience(&audiences),
+                claims,
+                Duration::from_secs(access_ttl as u64),
+            )
+            .await?;
 
-        // Create refresh token family
-        let (refresh_token, _family) = self
+        // Create refresh token family, remembering non-default issuers and audiences
+        let family_issuer = (profile.issuer != self.config.jwt_issuer)
+            .then_some(profile.issuer.as_str());
+        let family_audiences: &[String] = if audiences == [self.config.jwt_default_audience.as_str()] {
+            &[]
+        } else {
+            &audiences
+        };
+        let created = self
             .rotator
-            .create_token_family(
+            .create_scoped_token_family(
                 &req.user_id,
                 &req.session_id,
+                family_issuer,
+                family_audiences,
                 correlation_id.as_deref(),
             )
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?;
+            .await;
+        self.standby.observe(&created);
+        let (refresh_token, _family) = created.map_err(|e| Status::internal(e.to_string()))?;
 
         let expires_at = chrono::Utc::now().timestamp() + access_ttl;
 
+        record_auth!(span, decision = ALLOW);
         info!(
             user_id = %req.user_id,
             session_id = %req.session_id,
+            issuer = %profile.issuer,
+            audience = %audiences.join(" "),
             "Issued token pair"
         );
 
@@ -170,37 +495,54 @@ impl TokenService for TokenServiceImpl {
         &self,
         request: Request<RefreshRequest>,
     ) -> Result<Response<TokenPairResponse>, Status> {
+        let span = AuthSpan::of(&request);
+        self.standby.ensure_writable()?;
         let correlation_id = Self::get_correlation_id(&request);
         let req = request.into_inner();
+        let client_id = Some(req.client_id.as_str()).filter(|id| !id.is_empty());
+        if let Some(client_id) = client_id {
+            record_auth!(span, client_id = client_id);
+        }
 
-        let (new_refresh_token, family) = self
+        let rotated = self
             .rotator
-            .rotate(&req.refresh_token, correlation_id.as_deref())
-            .await
-            .map_err(|e| -> Status { e.into() })?;
-
-        // Build new access token
-        let claims = JwtBuilder::new(self.config.jwt_issuer.clone())
+            .rotate_for_client(&req.refresh_token, client_id, correlation_id.as_deref())
+            .await;
+        self.standby.observe(&rotated);
+        let (new_refresh_token, family) = rotated.map_err(|e| -> Status { e.into() })?;
+
+        // Build new access token for the issuer and audience the family was created with
+        let profile = self.issuer_profile(family.issuer.as_deref().unwrap_or_default())?;
+        let audiences = if family.audiences.is_empty() {
+            vec![family
+                .audience
+                .clone()
+                .unwrap_or_else(|| self.config.jwt_default_audience.clone())]
+        } else {
+            family.audiences.clone()
+        };
+        let claims = JwtBuilder::new(profile.issuer.clone())
             .subject(family.user_id.clone())
-            .audience(vec!["api".to_string()])
+            .audience(audiences.clone())
             .ttl_seconds(self.config.access_token_ttl.as_secs() as i64)
             .session_id(family.session_id.clone())
             .scopes(req.scopes)
             .build()
             .map_err(|e| Status::internal(e))?;
 
-        let encoding_key = self
-            .kms
-            .get_encoding_key()
-            .map_err(|e| Status::internal(e.to_string()))?;
-
-        let access_token = JwtSerializer::new(Algorithm::HS256)
-            .serialize(&claims, &encoding_key, Some(&self.config.kms_key_id))
-            .map_err(|e| Status::internal(e.to_string()))?;
+        let access_token = self
+            .issue_access_token(
+                profile,
+                Self::signing_audience(&audiences),
+                claims,
+                self.config.access_token_ttl,
+            )
+            .await?;
 
         let expires_at =
             chrono::Utc::now().timestamp() + self.config.access_token_ttl.as_secs() as i64;
 
+        record_auth!(span, decision = ALLOW);
         info!(
             user_id = %family.user_id,
             rotation_count = %family.rotation_count,
@@ -220,6 +562,7 @@ impl TokenService for TokenServiceImpl {
         &self,
         request: Request<RevokeRequest>,
     ) -> Result<Response<RevokeResponse>, Status> {
+        let span = AuthSpan::of(&request);
         let correlation_id = Self::get_correlation_id(&request);
         let req = request.into_inner();
 
@@ -234,12 +577,23 @@ impl TokenService for TokenServiceImpl {
                     .map_err(|e| Status::internal(e.to_string()))?;
             }
         } else {
-            self.storage
+            let revoked = self
+                .storage
                 .add_to_revocation_list(&req.token, self.config.access_token_ttl)
-                .await
-                .map_err(|e| Status::internal(e.to_string()))?;
+                .await;
+            self.standby.observe(&revoked);
+            revoked.map_err(|e| Status::internal(e.to_string()))?;
+            // Filter consumers skip lookups of JTIs it lacks, so the
+            // revocation fails unless it is journaled as well
+            if let Some(journal) = &self.revocation_journal {
+                journal
+                    .record(&req.token, self.config.access_token_ttl)
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?;
+            }
         }
 
+        record_auth!(span, decision = ALLOW);
         info!("Revoked token");
         Ok(Response::new(RevokeResponse { success: true }))
     }
@@ -248,26 +602,52 @@ impl TokenService for TokenServiceImpl {
         &self,
         request: Request<RevokeAllRequest>,
     ) -> Result<Response<RevokeResponse>, Status> {
+        let span = AuthSpan::of(&request);
         let correlation_id = Self::get_correlation_id(&request);
         let req = request.into_inner();
 
-        self.rotator
-            .revoke_all_user_tokens(&req.user_id, correlation_id.as_deref())
+        let families = self
+            .rotator
+            .revoke_user_token_families(&req.user_id, correlation_id.as_deref())
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
+        self.emit_backchannel_logout(&req.user_id, &families);
+
+        record_auth!(span, decision = ALLOW);
         info!(user_id = %req.user_id, "Revoked all user tokens");
         Ok(Response::new(RevokeResponse { success: true }))
     }
 
     async fn get_jwks(
         &self,
-        _request: Request<Empty>,
+        request: Request<JwksRequest>,
     ) -> Result<Response<JwksResponse>, Status> {
-        let jwks = self.jwks_publisher.get_jwks().await;
-        Ok(Response::new(JwksResponse {
-            keys_json: jwks.to_json(),
-        }))
+        let req = request.into_inner();
+        let rendered = if req.audience.is_empty() {
+            self.jwks
+                .issuer_rendered(&req.issuer)
+                .await
+                .ok_or_else(|| Status::not_found(format!("Unknown issuer: {}", req.issuer)))?
+        } else {
+            self.jwks
+                .audience_publisher(&req.audience)
+                .ok_or_else(|| {
+                    Status::not_found(format!("No dedicated keys for audience: {}", req.audience))
+                })?
+                .rendered()
+                .await
+        };
+
+        // The key set was serialized when it last changed; only the copy
+        // into the message is made per request
+        let mut response = Response::new(JwksResponse {
+            keys_json: rendered.json.clone(),
+        });
+        let metadata = response.metadata_mut();
+        metadata.insert("etag", rendered.etag.clone());
+        metadata.insert("cache-control", MetadataValue::from_static(JWKS_CACHE_CONTROL));
+        Ok(response)
     }
 
     async fn rotate_signing_key(
@@ -276,25 +656,46 @@ impl TokenService for TokenServiceImpl {
     ) -> Result<Response<RotateKeyResponse>, Status> {
         let req = request.into_inner();
 
-        let new_key = Jwk {
-            kty: "oct".to_string(),
-            kid: req.key_id.clone(),
-            key_use: "sig".to_string(),
-            alg: self.config.jwt_algorithm.as_str().to_string(),
-            n: None,
-            e: None,
-            x: None,
-            y: None,
-            crv: None,
-        };
+        let profile = self.issuer_profile(&req.issuer)?;
+        let publisher = self
+            .jwks
+            .publisher(&profile.issuer)
+            .ok_or_else(|| Status::internal(format!("No key set for issuer {}", profile.issuer)))?;
 
-        self.jwks_publisher.rotate_keys(new_key).await;
+        publisher
+            .rotate_keys(Self::signing_key(&self.config, &req.key_id))
+            .await;
 
-        info!(new_key_id = %req.key_id, "Rotated signing key");
+        info!(
+            new_key_id = %req.key_id,
+            issuer = %profile.issuer,
+            "Rotated signing key"
+        );
 
         Ok(Response::new(RotateKeyResponse {
             success: true,
             new_key_id: req.key_id,
         }))
     }
+
+    async fn get_revocation_filter(
+        &self,
+        _request: Request<RevocationFilterRequest>,
+    ) -> Result<Response<RevocationFilterResponse>, Status> {
+        let journal = self
+            .revocation_journal
+            .as_ref()
+            .ok_or_else(|| Status::failed_precondition("Revoked-JTI filter is disabled"))?;
+        let built = journal
+            .filter()
+            .await
+            .ok_or_else(|| Status::unavailable("Revoked-JTI filter not built yet"))?;
+
+        Ok(Response::new(RevocationFilterResponse {
+            bits: built.filter.to_bytes(),
+            hashes: built.filter.hashes(),
+            entries: built.filter.len() as u64,
+            built_at: built.built_at,
+        }))
+    }
 }
@@ -1,3 +1,5 @@
 pub mod publisher;
+pub mod registry;
 
-pub use publisher::{Jwk, Jwks, JwksPublisher};
+pub use publisher::{Jwk, Jwks, JwksPublisher, RenderedJwks, JWKS_CACHE_CONTROL};
+pub use registry::JwksRegistry;
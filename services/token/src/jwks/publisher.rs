@@ -1,12 +1,21 @@
 //! JWKS Publisher per RFC 7517.
 //!
 //! Publishes JSON Web Key Sets with support for key rotation,
-//! retaining previous keys during transition period.
+//! retaining previous keys during transition period. The published set is
+//! serialized once per change into a [`RenderedJwks`], so serving it does
+//! not re-serialize the keys on every request.
 
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use tonic::metadata::{Ascii, MetadataValue};
+
+/// `Cache-Control` of served key sets; consumers refetch early on unknown `kid`s.
+pub const JWKS_CACHE_CONTROL: &str = "public, max-age=300";
 
 /// JSON Web Key per RFC 7517.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +84,39 @@ impl Default for Jwks {
     }
 }
 
+/// Key set serialized once and shared by every request until it changes.
+#[derive(Debug)]
+pub struct RenderedJwks {
+    /// JSON of the key set.
+    pub json: String,
+    /// Strong `ETag` of `json`, quoted.
+    pub etag: MetadataValue<Ascii>,
+    /// Increases each time the key set is rendered again.
+    pub version: u64,
+    /// When the oldest retained key expires and the set must be rendered again.
+    expires_at: Option<Instant>,
+}
+
+impl RenderedJwks {
+    /// Serialize a key set.
+    #[must_use]
+    pub fn new(jwks: &Jwks, version: u64, expires_at: Option<Instant>) -> Self {
+        let json = jwks.to_json();
+        let digest = Sha256::digest(json.as_bytes());
+        let etag = format!("\"{}\"", URL_SAFE_NO_PAD.encode(&digest[..16]));
+        Self {
+            json,
+            etag: MetadataValue::try_from(etag).expect("base64url is valid metadata"),
+            version,
+            expires_at,
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
 /// Key with rotation metadata.
 struct RotatedKey {
     key: Jwk,
@@ -86,6 +128,9 @@ pub struct JwksPublisher {
     current_keys: Arc<RwLock<Jwks>>,
     previous_keys: Arc<RwLock<Vec<RotatedKey>>>,
     retention_period: Duration,
+    rendered: std::sync::RwLock<Arc<RenderedJwks>>,
+    /// Serializes renders, so the last one stored saw the latest keys
+    render_lock: Mutex<()>,
 }
 
 impl JwksPublisher {
@@ -102,13 +147,15 @@ impl JwksPublisher {
             current_keys: Arc::new(RwLock::new(Jwks::new())),
             previous_keys: Arc::new(RwLock::new(Vec::new())),
             retention_period,
+            rendered: std::sync::RwLock::new(Arc::new(RenderedJwks::new(&Jwks::new(), 0, None))),
+            render_lock: Mutex::new(()),
         }
     }
 
     /// Add a key to the current set.
     pub async fn add_key(&self, key: Jwk) {
-        let mut current = self.current_keys.write().await;
-        current.add_key(key);
+        self.current_keys.write().await.add_key(key);
+        self.render().await;
     }
 
     /// Rotate keys, moving current to previous.
@@ -134,6 +181,39 @@ impl JwksPublisher {
             *current = Jwks::new();
             current.add_key(new_key);
         }
+        self.render().await;
+    }
+
+    /// Get the serialized JWKS, rendering it again only once a retained key
+    /// has expired.
+    pub async fn rendered(&self) -> Arc<RenderedJwks> {
+        let rendered = self.load_rendered();
+        if !rendered.is_expired(Instant::now()) {
+            return rendered;
+        }
+        self.render().await;
+        self.load_rendered()
+    }
+
+    fn load_rendered(&self) -> Arc<RenderedJwks> {
+        self.rendered.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Serialize the published keys and swap them in.
+    async fn render(&self) {
+        let _guard = self.render_lock.lock().await;
+        let jwks = self.get_jwks().await;
+        let expires_at = self
+            .previous_keys
+            .read()
+            .await
+            .iter()
+            .map(|k| k.rotated_at + self.retention_period)
+            .filter(|at| *at > Instant::now())
+            .min();
+        let version = self.load_rendered().version + 1;
+        let rendered = Arc::new(RenderedJwks::new(&jwks, version, expires_at));
+        *self.rendered.write().unwrap_or_else(|e| e.into_inner()) = rendered;
     }
 
     /// Get combined JWKS (current + retained previous).
@@ -232,6 +312,28 @@ mod tests {
         assert_eq!(publisher.get_current_key_id().await, Some("key-2".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_rendered_follows_changes_only() {
+        let publisher = JwksPublisher::with_retention(Duration::from_millis(20));
+        publisher.add_key(create_test_key("key-1")).await;
+
+        let first = publisher.rendered().await;
+        assert_eq!(first.json, publisher.get_jwks().await.to_json());
+        assert!(Arc::ptr_eq(&first, &publisher.rendered().await));
+
+        publisher.rotate_keys(create_test_key("key-2")).await;
+        let rotated = publisher.rendered().await;
+        assert!(rotated.version > first.version);
+        assert_ne!(rotated.etag, first.etag);
+        assert!(rotated.json.contains("key-1") && rotated.json.contains("key-2"));
+
+        // The retained key drops out once its retention ends
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let expired = publisher.rendered().await;
+        assert!(!expired.json.contains("key-1"));
+        assert!(Arc::ptr_eq(&expired, &publisher.rendered().await));
+    }
+
     #[tokio::test]
     async fn test_find_key() {
         let jwks = Jwks {
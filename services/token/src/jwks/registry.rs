@@ -0,0 +1,216 @@
+//! Per-issuer JWKS publishers.
+//!
+//! A deployment hosting several issuer identities keeps one
+//! [`JwksPublisher`] per issuer so that each publishes and rotates its
+//! own key set. Audiences signed with a dedicated key get a publisher of
+//! their own, so the key set exposed to one audience's consumers holds
+//! only that audience's keys.
+
+use super::publisher::{Jwks, JwksPublisher, RenderedJwks};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// JWKS publishers keyed by issuer and by audience.
+pub struct JwksRegistry {
+    default_issuer: String,
+    publishers: HashMap<String, JwksPublisher>,
+    audiences: HashMap<String, JwksPublisher>,
+    /// Serialized issuer key sets combined with audience keys, by issuer
+    rendered: RwLock<HashMap<String, Arc<RenderedJwks>>>,
+}
+
+impl JwksRegistry {
+    /// Create a registry with a publisher for the default issuer.
+    #[must_use]
+    pub fn new(default_issuer: impl Into<String>, publisher: JwksPublisher) -> Self {
+        let default_issuer = default_issuer.into();
+        let mut publishers = HashMap::new();
+        publishers.insert(default_issuer.clone(), publisher);
+        Self {
+            default_issuer,
+            publishers,
+            audiences: HashMap::new(),
+            rendered: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Add or replace the publisher of an issuer.
+    pub fn insert(&mut self, issuer: impl Into<String>, publisher: JwksPublisher) {
+        self.publishers.insert(issuer.into(), publisher);
+    }
+
+    /// Get the default issuer.
+    #[must_use]
+    pub fn default_issuer(&self) -> &str {
+        &self.default_issuer
+    }
+
+    /// Get the publisher of an issuer; an empty issuer selects the default.
+    #[must_use]
+    pub fn publisher(&self, issuer: &str) -> Option<&JwksPublisher> {
+        if issuer.is_empty() {
+            return self.publishers.get(&self.default_issuer);
+        }
+        self.publishers.get(issuer)
+    }
+
+    /// Get all hosted issuers.
+    pub fn issuers(&self) -> impl Iterator<Item = &str> {
+        self.publishers.keys().map(String::as_str)
+    }
+
+    /// Add or replace the publisher of an audience's dedicated keys.
+    pub fn insert_audience(&mut self, audience: impl Into<String>, publisher: JwksPublisher) {
+        self.audiences.insert(audience.into(), publisher);
+    }
+
+    /// Get the publisher of an audience's dedicated keys.
+    #[must_use]
+    pub fn audience_publisher(&self, audience: &str) -> Option<&JwksPublisher> {
+        self.audiences.get(audience)
+    }
+
+    /// Get the key set verifying every token of an issuer.
+    ///
+    /// Combines the issuer's keys with all audience keys, since tokens of
+    /// any audience may carry the issuer's `iss`. Returns `None` for an
+    /// unknown issuer; an empty issuer selects the default.
+    pub async fn issuer_jwks(&self, issuer: &str) -> Option<Jwks> {
+        let mut jwks = self.publisher(issuer)?.get_jwks().await;
+        for publisher in self.audiences.values() {
+            jwks.keys.extend(publisher.get_jwks().await.keys);
+        }
+        Some(jwks)
+    }
+
+    /// Get the serialized key set of [`issuer_jwks`](Self::issuer_jwks).
+    ///
+    /// Publisher versions only grow, so their sum identifies the combined
+    /// set and it is serialized again only after one of them changed.
+    pub async fn issuer_rendered(&self, issuer: &str) -> Option<Arc<RenderedJwks>> {
+        let publisher = self.publisher(issuer)?;
+        if self.audiences.is_empty() {
+            return Some(publisher.rendered().await);
+        }
+
+        let mut version = publisher.rendered().await.version;
+        for audience in self.audiences.values() {
+            version += audience.rendered().await.version;
+        }
+        let issuer = if issuer.is_empty() { self.default_issuer.as_str() } else { issuer };
+        let cached = self
+            .rendered
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(issuer)
+            .filter(|rendered| rendered.version == version)
+            .cloned();
+        if cached.is_some() {
+            return cached;
+        }
+
+        let rendered = Arc::new(RenderedJwks::new(&self.issuer_jwks(issuer).await?, version, None));
+        let mut cache = self.rendered.write().unwrap_or_else(|e| e.into_inner());
+        let newest = cache
+            .entry(issuer.to_string())
+            .and_modify(|cached| {
+                if cached.version < version {
+                    *cached = rendered.clone();
+                }
+            })
+            .or_insert_with(|| rendered.clone());
+        Some(newest.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwks::Jwk;
+
+    fn create_test_key(kid: &str) -> Jwk {
+        Jwk {
+            kty: "oct".to_string(),
+            kid: kid.to_string(),
+            key_use: "sig".to_string(),
+            alg: "HS256".to_string(),
+            n: None,
+            e: None,
+            x: None,
+            y: None,
+            crv: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_issuers_publish_separate_key_sets() {
+        let mut registry = JwksRegistry::new("https://auth.example.com", JwksPublisher::new());
+        registry.insert("https://auth.partner.com", JwksPublisher::new());
+
+        registry
+            .publisher("https://auth.example.com")
+            .unwrap()
+            .add_key(create_test_key("example-key"))
+            .await;
+        registry
+            .publisher("https://auth.partner.com")
+            .unwrap()
+            .rotate_keys(create_test_key("partner-key"))
+            .await;
+
+        let example = registry.publisher("").unwrap().get_jwks().await;
+        let partner = registry
+            .publisher("https://auth.partner.com")
+            .unwrap()
+            .get_jwks()
+            .await;
+
+        assert!(example.find_key("example-key").is_some());
+        assert!(example.find_key("partner-key").is_none());
+        assert!(partner.find_key("partner-key").is_some());
+        assert!(partner.find_key("example-key").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_audience_key_sets_are_partitioned() {
+        let mut registry = JwksRegistry::new("https://auth.example.com", JwksPublisher::new());
+        registry.publisher("").unwrap().add_key(create_test_key("issuer-key")).await;
+        for (audience, kid) in [("internal-api", "internal-key"), ("partner-api", "partner-key")] {
+            let publisher = JwksPublisher::new();
+            publisher.add_key(create_test_key(kid)).await;
+            registry.insert_audience(audience, publisher);
+        }
+
+        let partner = registry.audience_publisher("partner-api").unwrap().get_jwks().await;
+        assert!(partner.find_key("partner-key").is_some());
+        assert!(partner.find_key("internal-key").is_none());
+        assert!(partner.find_key("issuer-key").is_none());
+        assert!(registry.audience_publisher("unknown-api").is_none());
+
+        let issuer = registry.issuer_jwks("").await.unwrap();
+        assert_eq!(issuer.keys.len(), 3);
+
+        let rendered = registry.issuer_rendered("").await.unwrap();
+        assert_eq!(rendered.json, issuer.to_json());
+        assert!(Arc::ptr_eq(&rendered, &registry.issuer_rendered("").await.unwrap()));
+        registry
+            .audience_publisher("partner-api")
+            .unwrap()
+            .rotate_keys(create_test_key("partner-key-2"))
+            .await;
+        let rotated = registry.issuer_rendered("").await.unwrap();
+        assert!(rotated.version > rendered.version);
+        assert!(rotated.json.contains("partner-key-2"));
+        assert!(registry.issuer_rendered("https://auth.unknown.com").await.is_none());
+        assert!(registry.issuer_jwks("https://auth.unknown.com").await.is_none());
+    }
+
+    #[test]
+    fn test_unknown_issuer() {
+        let registry = JwksRegistry::new("https://auth.example.com", JwksPublisher::new());
+
+        assert_eq!(registry.default_issuer(), "https://auth.example.com");
+        assert_eq!(registry.issuers().count(), 1);
+        assert!(registry.publisher("https://auth.unknown.com").is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! Multi-audience access tokens.
+//!
+//! Clients in `CLIENT_REGISTRY` may request a token valid for several
+//! audiences at once. Every audience lists the custom claims its tokens may
+//! carry, and since all audiences read the same token, a claim is only
+//! included when each requested audience allows it. Internal claims thus
+//! never reach tokens that also name an external audience. Tokens requested
+//! without a client follow the rules every client registers for their
+//! audience.
+
+use crate::config::{AudienceClaims, Config, RegisteredClient};
+use crate::error::TokenError;
+use std::collections::{HashMap, HashSet};
+
+/// Audiences of a token and the custom claims it may carry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudienceGrant {
+    /// Audiences of the token, in registry order
+    pub audiences: Vec<String>,
+    /// Custom claims allowed by every audience; `None` allows all
+    allowed_claims: Option<HashSet<String>>,
+}
+
+impl AudienceGrant {
+    /// Whether the token may carry the custom claim `name`.
+    #[must_use]
+    pub fn allows_claim(&self, name: &str) -> bool {
+        self.allowed_claims
+            .as_ref()
+            .map_or(true, |allowed| allowed.contains(name))
+    }
+}
+
+/// Registered clients and the audiences they may request.
+#[derive(Debug, Clone, Default)]
+pub struct ClientAudiences {
+    clients: HashMap<String, RegisteredClient>,
+}
+
+impl ClientAudiences {
+    /// Build the registry from `CLIENT_REGISTRY`.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.clients.clone())
+    }
+
+    /// Build the registry from registered clients.
+    #[must_use]
+    pub fn new(clients: Vec<RegisteredClient>) -> Self {
+        Self {
+            clients: clients
+                .into_iter()
+                .map(|client| (client.client_id.clone(), client))
+                .collect(),
+        }
+    }
+
+    /// Grant `client_id` a token for the `requested` audiences, or for all
+    /// of its audiences when none are requested.
+    pub fn grant(&self, client_id: &str, requested: &[String]) -> Result<AudienceGrant, TokenError> {
+        let client = self
+            .clients
+            .get(client_id)
+            .ok_or_else(|| TokenError::AudienceNotAllowed(format!("unknown client {}", client_id)))?;
+
+        if let Some(unknown) = requested
+            .iter()
+            .find(|audience| !client.audiences.iter().any(|a| &a.audience == *audience))
+        {
+            return Err(TokenError::AudienceNotAllowed(format!(
+                "client {} may not request {}",
+                client_id, unknown
+            )));
+        }
+        let granted: Vec<&AudienceClaims> = client
+            .audiences
+            .iter()
+            .filter(|a| requested.is_empty() || requested.contains(&a.audience))
+            .collect();
+
+        Ok(AudienceGrant {
+            audiences: granted.iter().map(|a| a.audience.clone()).collect(),
+            allowed_claims: Self::allowed_claims(granted),
+        })
+    }
+
+    /// Grant a token for `audience` requested without a client.
+    ///
+    /// Custom claims must be allowed by every client registering the
+    /// audience; audiences no client registers carry any custom claim.
+    #[must_use]
+    pub fn audience_grant(&self, audience: &str) -> AudienceGrant {
+        let rules = self
+            .clients
+            .values()
+            .flat_map(|client| &client.audiences)
+            .filter(|a| a.audience == audience);
+
+        AudienceGrant {
+            audiences: vec![audience.to_string()],
+            allowed_claims: Self::allowed_claims(rules),
+        }
+    }
+
+    /// Custom claims listed by all of `audiences`; `None` if none restricts them.
+    fn allowed_claims<'a>(
+        audiences: impl IntoIterator<Item = &'a AudienceClaims>,
+    ) -> Option<HashSet<String>> {
+        let mut allowed_claims: Option<HashSet<String>> = None;
+        for claims in audiences.into_iter().filter_map(|a| a.claims.as_ref()) {
+            let claims: HashSet<String> = claims.iter().cloned().collect();
+            allowed_claims = Some(match allowed_claims {
+                Some(allowed) => allowed.intersection(&claims).cloned().collect(),
+                None => claims,
+            });
+        }
+        allowed_claims
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> ClientAudiences {
+        ClientAudiences::new(vec![RegisteredClient {
+            client_id: "web".to_string(),
+            audiences: vec![
+                AudienceClaims {
+                    audience: "api".to_string(),
+                    claims: Some(vec!["tenant_id".to_string()]),
+                },
+                AudienceClaims {
+                    audience: "internal-api".to_string(),
+                    claims: Some(vec!["tenant_id".to_string(), "cost_center".to_string()]),
+                },
+                AudienceClaims {
+                    audience: "search".to_string(),
+                    claims: None,
+                },
+            ],
+        }])
+    }
+
+    #[test]
+    fn test_claims_allowed_by_every_audience() {
+        let registry = registry();
+
+        let internal = registry.grant("web", &["internal-api".to_string()]).unwrap();
+        assert_eq!(internal.audiences, vec!["internal-api"]);
+        assert!(internal.allows_claim("cost_center"));
+
+        let mixed = registry
+            .grant("web", &["internal-api".to_string(), "api".to_string()])
+            .unwrap();
+        assert_eq!(mixed.audiences, vec!["api", "internal-api"]);
+        assert!(mixed.allows_claim("tenant_id"));
+        assert!(!mixed.allows_claim("cost_center"));
+
+        let open = registry.grant("web", &["search".to_string()]).unwrap();
+        assert!(open.allows_claim("anything"));
+    }
+
+    #[test]
+    fn test_empty_request_grants_every_audience() {
+        let grant = registry().grant("web", &[]).unwrap();
+        assert_eq!(grant.audiences, vec!["api", "internal-api", "search"]);
+        assert!(!grant.allows_claim("cost_center"));
+    }
+
+    #[test]
+    fn test_clientless_grant_follows_audience_rules() {
+        let mut registry = registry();
+        registry.clients.insert(
+            "batch".to_string(),
+            RegisteredClient {
+                client_id: "batch".to_string(),
+                audiences: vec![AudienceClaims {
+                    audience: "internal-api".to_string(),
+                    claims: Some(vec!["cost_center".to_string()]),
+                }],
+            },
+        );
+
+        let api = registry.audience_grant("api");
+        assert_eq!(api.audiences, vec!["api"]);
+        assert!(api.allows_claim("tenant_id"));
+        assert!(!api.allows_claim("cost_center"));
+
+        let internal = registry.audience_grant("internal-api");
+        assert!(internal.allows_claim("cost_center"));
+        assert!(!internal.allows_claim("tenant_id"));
+
+        assert!(registry.audience_grant("search").allows_claim("anything"));
+        assert!(registry.audience_grant("billing").allows_claim("anything"));
+    }
+
+    #[test]
+    fn test_unregistered_requests_rejected() {
+        let registry = registry();
+        assert!(registry.grant("mobile", &[]).is_err());
+        assert!(registry.grant("web", &["billing".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Access token size budget.
+//!
+//! Tokens travel in HTTP headers, which proxies commonly cap at 8 KiB.
+//! [`TokenSizeBudget`] rejects access tokens whose encoded size exceeds
+//! `MAX_ACCESS_TOKEN_BYTES`, naming the largest claims. With
+//! `CLAIMS_BY_REFERENCE`, the largest custom claims are instead moved into a
+//! [`ClaimsOverflow`] stored under `claims_ref:{id}`, and the token carries
+//! a `claims_ref` claim that auth-edge resolves.
+
+use crate::config::Config;
+use crate::error::TokenError;
+use crate::jwt::claims::Claims;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Claim naming the cache entry that holds a token's overflow claims.
+pub const CLAIMS_REF_CLAIM: &str = "claims_ref";
+
+/// Number of claims listed when a token is too large.
+const REPORTED_CLAIMS: usize = 5;
+
+/// Custom claims moved out of an access token.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClaimsOverflow {
+    /// Value of the token's `claims_ref` claim
+    #[serde(skip)]
+    pub id: String,
+    /// `jti` of the token the claims belong to
+    pub jti: String,
+    /// Moved claims
+    pub claims: HashMap<String, serde_json::Value>,
+}
+
+impl ClaimsOverflow {
+    /// Cache key of the overflow entry.
+    #[must_use]
+    pub fn key(&self) -> String {
+        format!("{}:{}", CLAIMS_REF_CLAIM, self.id)
+    }
+}
+
+/// Signed access token within the size budget.
+#[derive(Debug)]
+pub struct FittedToken {
+    /// Encoded token
+    pub token: String,
+    /// Claims to store under [`ClaimsOverflow::key`], if any were moved
+    pub overflow: Option<ClaimsOverflow>,
+}
+
+/// Maximum encoded access token size.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenSizeBudget {
+    max_bytes: usize,
+    by_reference: bool,
+}
+
+impl TokenSizeBudget {
+    /// Budget of `max_bytes` (0 disables it); with `by_reference`, custom
+    /// claims are moved out of oversized tokens.
+    #[must_use]
+    pub fn new(max_bytes: usize, by_reference: bool) -> Self {
+        Self {
+            max_bytes,
+            by_reference,
+        }
+    }
+
+    /// Budget from `MAX_ACCESS_TOKEN_BYTES` and `CLAIMS_BY_REFERENCE`.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.max_access_token_bytes, config.claims_by_reference)
+    }
+
+    /// Sign `claims`, moving custom claims out of the token if it is too large.
+    ///
+    /// Claims are moved largest first until the token fits. The signature
+    /// length does not depend on the payload, so sizes are computed from the
+    /// payload and `sign` runs at most twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::TokenTooLarge`] if the token does not fit, or
+    /// the error of `sign`.
+    pub fn fit<E: From<TokenError>>(
+        &self,
+        mut claims: Claims,
+        mut sign: impl FnMut(&Claims) -> Result<String, E>,
+    ) -> Result<FittedToken, E> {
+        let token = sign(&claims)?;
+        if self.max_bytes == 0 || token.len() <= self.max_bytes {
+            return Ok(FittedToken {
+                token,
+                overflow: None,
+            });
+        }
+
+        let too_large = TokenError::TokenTooLarge {
+            size: token.len(),
+            max: self.max_bytes,
+            largest_claims: largest_claims(&claims, REPORTED_CLAIMS),
+        };
+        if !self.by_reference || claims.custom.is_empty() {
+            return Err(too_large.into());
+        }
+
+        // Header and signature keep their size when the payload shrinks
+        let overhead = token.len() - encoded_len(payload_len(&claims)?);
+        let mut overflow = ClaimsOverflow {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            jti: claims.jti.clone(),
+            claims: HashMap::new(),
+        };
+        let mut movable: Vec<(String, usize)> = claims
+            .custom
+            .iter()
+            .map(|(name, value)| (name.clone(), claim_len(name, value)))
+            .collect();
+        movable.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        claims.custom.insert(
+            CLAIMS_REF_CLAIM.to_string(),
+            serde_json::Value::String(overflow.id.clone()),
+        );
+
+        for (name, _) in movable {
+            if let Some(value) = claims.custom.remove(&name) {
+                overflow.claims.insert(name, value);
+            }
+            if overhead + encoded_len(payload_len(&claims)?) <= self.max_bytes {
+                return Ok(FittedToken {
+                    token: sign(&claims)?,
+                    overflow: Some(overflow),
+                });
+            }
+        }
+        Err(too_large.into())
+    }
+}
+
+/// The `limit` largest claims by JSON size, largest first.
+#[must_use]
+pub fn largest_claims(claims: &Claims, limit: usize) -> Vec<(String, usize)> {
+    let mut sizes: Vec<(String, usize)> = match serde_json::to_value(claims) {
+        Ok(serde_json::Value::Object(map)) => map
+            .iter()
+            .map(|(name, value)| (name.clone(), claim_len(name, value)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sizes.truncate(limit);
+    sizes
+}
+
+/// JSON size of `"name":value`.
+fn claim_len(name: &str, value: &serde_json::Value) -> usize {
+    serde_json::to_string(name).map_or(0, |n| n.len()) + 1 + value.to_string().len()
+}
+
+fn payload_len(claims: &Claims) -> Result<usize, TokenError> {
+    Ok(serde_json::to_vec(claims)?.len())
+}
+
+/// Length of `len` bytes in unpadded base64url.
+fn encoded_len(len: usize) -> usize {
+    (len * 4).div_ceil(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::{JwtBuilder, JwtSerializer};
+    use jsonwebtoken::{Algorithm, EncodingKey};
+
+    fn claims() -> Claims {
+        JwtBuilder::new("https://auth.example.com".to_string())
+            .subject("user-123".to_string())
+            .audience(vec!["api".to_string()])
+            .custom_claim("tenant".to_string(), serde_json::json!("acme"))
+            .custom_claim("groups".to_string(), serde_json::json!(vec!["g".repeat(40); 40]))
+            .custom_claim("roles".to_string(), serde_json::json!(vec!["r".repeat(20); 10]))
+            .build()
+            .unwrap()
+    }
+
+    fn sign(claims: &Claims) -> Result<String, TokenError> {
+        JwtSerializer::new(Algorithm::HS256).serialize(
+            claims,
+            &EncodingKey::from_secret(b"budget-test-secret"),
+            Some("key-1"),
+        )
+    }
+
+    #[test]
+    fn test_rejects_oversized_token_listing_largest_claims() {
+        assert!(TokenSizeBudget::default().fit(claims(), sign).unwrap().overflow.is_none());
+
+        match TokenSizeBudget::new(1024, false).fit(claims(), sign) {
+            Err(TokenError::TokenTooLarge { size, max, largest_claims }) => {
+                assert!(size > max);
+                assert_eq!(max, 1024);
+                assert_eq!(largest_claims[0].0, "groups");
+                assert_eq!(largest_claims[1].0, "roles");
+                assert!(largest_claims.len() <= REPORTED_CLAIMS);
+            }
+            other => panic!("expected TokenTooLarge, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_moves_largest_claims_by_reference() {
+        let fitted = TokenSizeBudget::new(1024, true).fit(claims(), sign).unwrap();
+        assert!(fitted.token.len() <= 1024);
+
+        let overflow = fitted.overflow.unwrap();
+        assert_eq!(overflow.claims.keys().collect::<Vec<_>>(), vec!["groups"]);
+        assert!(overflow.key().starts_with("claims_ref:"));
+
+        let decoded = JwtSerializer::new(Algorithm::HS256)
+            .deserialize_unverified(&fitted.token)
+            .unwrap();
+        assert_eq!(decoded.custom[CLAIMS_REF_CLAIM], serde_json::json!(overflow.id));
+        assert_eq!(decoded.jti, overflow.jti);
+        assert!(decoded.custom.contains_key("roles"));
+        assert!(!decoded.custom.contains_key("groups"));
+
+        // Standard claims alone cannot fit
+        assert!(matches!(
+            TokenSizeBudget::new(64, true).fit(claims(), sign),
+            Err(TokenError::TokenTooLarge { .. })
+        ));
+    }
+}
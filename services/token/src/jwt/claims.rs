@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use rust_common::scope::ScopeClaim;
+use serde::{Deserialize, Serialize, Serializer};
 use std::collections::HashMap;
 
 /// DPoP Confirmation claim (cnf) for token binding per RFC 9449
@@ -8,7 +9,7 @@ pub struct Confirmation {
     pub jkt: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 pub struct Claims {
     // Standard JWT claims
     pub iss: String,
@@ -38,12 +39,76 @@ pub struct Claims {
     // Custom claims
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Issued as the space-delimited `scope` claim; the legacy `scopes`
+    /// array is still read
+    #[serde(
+        rename = "scope",
+        alias = "scopes",
+        default,
+        deserialize_with = "rust_common::scope::deserialize"
+    )]
     pub scopes: Option<Vec<String>>,
     #[serde(flatten)]
     pub custom: HashMap<String, serde_json::Value>,
 }
 
+/// Borrowed view of [`Claims`] that serde serializes straight to the output.
+///
+/// Custom claims are flattened from a borrowed map, so encoding a token
+/// neither clones the claims nor builds a `serde_json::Value` tree.
+#[derive(Serialize)]
+struct ClaimsRef<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a [String],
+    exp: i64,
+    iat: i64,
+    nbf: Option<i64>,
+    jti: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    acr: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amr: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    azp: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cnf: Option<&'a Confirmation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<ScopeClaim<'a, String>>,
+    #[serde(flatten)]
+    custom: &'a HashMap<String, serde_json::Value>,
+}
+
+impl Serialize for Claims {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ClaimsRef {
+            iss: &self.iss,
+            sub: &self.sub,
+            aud: &self.aud,
+            exp: self.exp,
+            iat: self.iat,
+            nbf: self.nbf,
+            jti: &self.jti,
+            nonce: self.nonce.as_deref(),
+            auth_time: self.auth_time,
+            acr: self.acr.as_deref(),
+            amr: self.amr.as_deref(),
+            azp: self.azp.as_deref(),
+            cnf: self.cnf.as_ref(),
+            session_id: self.session_id.as_deref(),
+            scope: self.scopes.as_deref().map(ScopeClaim),
+            custom: &self.custom,
+        }
+        .serialize(serializer)
+    }
+}
+
 impl Claims {
     pub fn new(issuer: String, subject: String, audience: Vec<String>, ttl_seconds: i64) -> Self {
         let now = chrono::Utc::now().timestamp();
@@ -156,4 +221,49 @@ mod tests {
         assert_eq!(claims.session_id, Some("session-456".to_string()));
         assert_eq!(claims.scopes, Some(vec!["read".to_string(), "write".to_string()]));
     }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        let claims = Claims::new(
+            "test-issuer".to_string(),
+            "user-123".to_string(),
+            vec!["api".to_string()],
+            900,
+        )
+        .with_dpop_binding("thumbprint".to_string())
+        .with_custom_claim("tenant".to_string(), serde_json::json!("acme"));
+
+        let json = serde_json::to_string(&claims).unwrap();
+        assert!(json.contains(r#""tenant":"acme""#));
+        assert!(!json.contains("nonce"));
+        assert!(!json.contains("custom"));
+
+        let restored: Claims = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, claims);
+    }
+
+    #[test]
+    fn test_scopes_issued_as_scope_string() {
+        let claims = Claims::new(
+            "test-issuer".to_string(),
+            "user-123".to_string(),
+            vec!["api".to_string()],
+            900,
+        )
+        .with_scopes(vec!["openid".to_string(), "orders:read".to_string()]);
+
+        let json = serde_json::to_value(&claims).unwrap();
+        assert_eq!(json["scope"], "openid orders:read");
+        assert!(json.get("scopes").is_none());
+        assert_eq!(serde_json::from_value::<Claims>(json).unwrap(), claims);
+
+        // Tokens issued before the switch carry a `scopes` array
+        let mut legacy = serde_json::to_value(&claims).unwrap();
+        let object = legacy.as_object_mut().unwrap();
+        object.remove("scope");
+        object.insert("scopes".to_string(), serde_json::json!(["openid", "orders:read"]));
+        let restored: Claims = serde_json::from_value(legacy).unwrap();
+        assert_eq!(restored.scopes, claims.scopes);
+        assert!(restored.custom.is_empty());
+    }
 }
@@ -0,0 +1,228 @@
+//! Detached and multi-signature JWS.
+//!
+//! Audit artifacts are signed with a detached payload (RFC 7515 Appendix F):
+//! the compact JWS is `header..signature` and the payload travels on its own.
+//! `sign_detached_unencoded` signs the raw payload bytes instead of their
+//! base64url form (RFC 7797), so verifiers never need to re-encode large
+//! artifacts.
+//!
+//! During key migration the same payload is signed with the old and the new
+//! key and emitted in the General JWS JSON Serialization (RFC 7515 §7.2.1),
+//! so verifiers that only know one of the keys still accept it.
+
+use crate::error::TokenError;
+use crate::jwt::signer::JwtSigner;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// JWS protected header.
+#[derive(Serialize)]
+struct ProtectedHeader<'a> {
+    alg: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    kid: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    b64: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crit: Option<[&'a str; 1]>,
+}
+
+impl<'a> ProtectedHeader<'a> {
+    fn new<S: JwtSigner>(signer: &'a S) -> Self {
+        Self {
+            alg: signer.algorithm(),
+            kid: signer.key_id(),
+            b64: None,
+            crit: None,
+        }
+    }
+
+    /// Marks the payload as unencoded (RFC 7797).
+    fn unencoded(mut self) -> Self {
+        self.b64 = Some(false);
+        self.crit = Some(["b64"]);
+        self
+    }
+
+    /// base64url-encoded header JSON.
+    fn encode(&self) -> Result<String, TokenError> {
+        let json = serde_json::to_vec(self).map_err(|e| TokenError::jwt_encoding(e.to_string()))?;
+        Ok(URL_SAFE_NO_PAD.encode(json))
+    }
+}
+
+/// One signature of a General JWS JSON Serialization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwsSignature {
+    /// base64url-encoded protected header
+    pub protected: String,
+    /// base64url-encoded signature
+    pub signature: String,
+}
+
+/// General JWS JSON Serialization carrying one signature per key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneralJws {
+    /// base64url-encoded payload; `None` when detached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+    /// Signatures over the same payload
+    pub signatures: Vec<JwsSignature>,
+}
+
+impl GeneralJws {
+    /// Drop the payload, leaving a detached JWS.
+    #[must_use]
+    pub fn detach(mut self) -> Self {
+        self.payload = None;
+        self
+    }
+
+    /// Serialize to JSON.
+    pub fn to_json(&self) -> Result<String, TokenError> {
+        serde_json::to_string(self).map_err(|e| TokenError::jwt_encoding(e.to_string()))
+    }
+}
+
+/// Sign `payload` as a compact JWS with a detached payload: `header..signature`.
+pub async fn sign_detached<S: JwtSigner>(
+    signer: &S,
+    payload: &[u8],
+) -> Result<String, TokenError> {
+    let protected = ProtectedHeader::new(signer).encode()?;
+    let signing_input = format!("{}.{}", protected, URL_SAFE_NO_PAD.encode(payload));
+    let signature = signer.sign(signing_input.as_bytes()).await?;
+    Ok(format!("{}..{}", protected, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Sign the raw bytes of `payload` as a compact JWS with a detached,
+/// unencoded payload (`"b64": false`).
+pub async fn sign_detached_unencoded<S: JwtSigner>(
+    signer: &S,
+    payload: &[u8],
+) -> Result<String, TokenError> {
+    let protected = ProtectedHeader::new(signer).unencoded().encode()?;
+    let mut signing_input = Vec::with_capacity(protected.len() + 1 + payload.len());
+    signing_input.extend_from_slice(protected.as_bytes());
+    signing_input.push(b'.');
+    signing_input.extend_from_slice(payload);
+    let signature = signer.sign(&signing_input).await?;
+    Ok(format!("{}..{}", protected, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Sign `payload` with every signer, in order, as a General JWS JSON
+/// Serialization.
+pub async fn sign_general<S: JwtSigner>(
+    signers: &[&S],
+    payload: &[u8],
+) -> Result<GeneralJws, TokenError> {
+    if signers.is_empty() {
+        return Err(TokenError::signing("JWS requires at least one signer"));
+    }
+
+    let encoded_payload = URL_SAFE_NO_PAD.encode(payload);
+    let mut signatures = Vec::with_capacity(signers.len());
+    for signer in signers {
+        let protected = ProtectedHeader::new(*signer).encode()?;
+        let signing_input = format!("{}.{}", protected, encoded_payload);
+        let signature = signer.sign(signing_input.as_bytes()).await?;
+        signatures.push(JwsSignature {
+            protected,
+            signature: URL_SAFE_NO_PAD.encode(signature),
+        });
+    }
+
+    Ok(GeneralJws {
+        payload: Some(encoded_payload),
+        signatures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::signer::MockSigner;
+    use ring::hmac;
+
+    const PAYLOAD: &[u8] = br#"{"event":"token_revoked","jti":"abc"}"#;
+
+    fn verify(secret: &[u8], signing_input: &[u8], signature: &str) -> bool {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+        let signature = URL_SAFE_NO_PAD.decode(signature).unwrap();
+        hmac::verify(&key, signing_input, &signature).is_ok()
+    }
+
+    fn header(protected: &str) -> serde_json::Value {
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(protected).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_detached_payload_signs_encoded_payload() {
+        let signer = MockSigner::new("audit-key");
+        let jws = sign_detached(&signer, PAYLOAD).await.unwrap();
+
+        let parts: Vec<&str> = jws.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(parts[1].is_empty());
+        assert_eq!(header(parts[0]), serde_json::json!({"alg": "HS256", "kid": "audit-key"}));
+
+        let signing_input = format!("{}.{}", parts[0], URL_SAFE_NO_PAD.encode(PAYLOAD));
+        assert!(verify(
+            b"mock-secret-key-for-testing-only-32bytes!",
+            signing_input.as_bytes(),
+            parts[2]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unencoded_payload_signs_raw_bytes() {
+        let signer = MockSigner::new("audit-key");
+        let jws = sign_detached_unencoded(&signer, PAYLOAD).await.unwrap();
+
+        let (protected, signature) = jws.split_once("..").unwrap();
+        assert_eq!(
+            header(protected),
+            serde_json::json!({"alg": "HS256", "kid": "audit-key", "b64": false, "crit": ["b64"]})
+        );
+
+        let mut signing_input = format!("{}.", protected).into_bytes();
+        signing_input.extend_from_slice(PAYLOAD);
+        assert!(verify(
+            b"mock-secret-key-for-testing-only-32bytes!",
+            &signing_input,
+            signature
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_general_serialization_signs_with_every_key() {
+        let old = MockSigner::new("key-1").with_secret(b"old-secret".to_vec());
+        let new = MockSigner::new("key-2").with_secret(b"new-secret".to_vec());
+        let jws = sign_general(&[&old, &new], PAYLOAD).await.unwrap();
+
+        let payload = jws.payload.clone().unwrap();
+        assert_eq!(URL_SAFE_NO_PAD.decode(&payload).unwrap(), PAYLOAD);
+        assert_eq!(jws.signatures.len(), 2);
+        for (signature, (kid, secret)) in jws
+            .signatures
+            .iter()
+            .zip([("key-1", b"old-secret"), ("key-2", b"new-secret")])
+        {
+            assert_eq!(header(&signature.protected)["kid"], kid);
+            let signing_input = format!("{}.{}", signature.protected, payload);
+            assert!(verify(secret, signing_input.as_bytes(), &signature.signature));
+        }
+
+        let detached: serde_json::Value =
+            serde_json::from_str(&jws.detach().to_json().unwrap()).unwrap();
+        assert!(detached.get("payload").is_none());
+        assert_eq!(detached["signatures"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_general_serialization_requires_signer() {
+        let signers: [&MockSigner; 0] = [];
+        assert!(sign_general(&signers, PAYLOAD).await.is_err());
+    }
+}
@@ -1,9 +1,17 @@
+pub mod audience;
+pub mod budget;
 pub mod builder;
 pub mod claims;
+pub mod jws;
+pub mod private_claims;
 pub mod serializer;
 pub mod signer;
 
+pub use audience::{AudienceGrant, ClientAudiences};
+pub use budget::{ClaimsOverflow, FittedToken, TokenSizeBudget, CLAIMS_REF_CLAIM};
 pub use builder::JwtBuilder;
 pub use claims::{Claims, Confirmation};
+pub use jws::{GeneralJws, JwsSignature};
+pub use private_claims::PrivateClaimEncryptor;
 pub use serializer::JwtSerializer;
 pub use signer::JwtSigner;
@@ -0,0 +1,207 @@
+//! Private claims.
+//!
+//! Some custom claims (national IDs, internal account references) must stay
+//! unreadable to the intermediaries a token passes through, while the token
+//! itself remains a signed JWT. Before signing, every claim named in
+//! `PRIVATE_CLAIMS` is encrypted with AES-GCM through the crypto-service and
+//! its value replaced by a compact [`ClaimEnvelope`] bound to the token's
+//! `jti` and the claim name. auth-edge decrypts the envelopes for callers
+//! allowed to read private claims.
+
+use crate::config::Config;
+use crate::crypto::{CryptoClient, CryptoClientConfig, CryptoClientFactory, KeyId};
+use crate::error::TokenError;
+use crate::jwt::claims::Claims;
+use rust_common::claim_envelope::{aad, ClaimEnvelope};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Encrypts private claims with a crypto-service key.
+pub struct PrivateClaimEncryptor {
+    client: Arc<dyn CryptoClient>,
+    key_id: KeyId,
+    claims: HashSet<String>,
+}
+
+impl PrivateClaimEncryptor {
+    /// Create an encryptor for the named claims.
+    #[must_use]
+    pub fn new(client: Arc<dyn CryptoClient>, key_id: KeyId, claims: &[String]) -> Self {
+        Self {
+            client,
+            key_id,
+            claims: claims.iter().cloned().collect(),
+        }
+    }
+
+    /// Build the encryptor from `PRIVATE_CLAIMS`, or `None` when no claim is
+    /// private.
+    ///
+    /// The local fallback stays disabled: auth-edge could not decrypt
+    /// envelopes sealed with this service's fallback key, so issuance fails
+    /// while the crypto-service is unavailable.
+    pub async fn from_config(config: &Config) -> Result<Option<Self>, TokenError> {
+        if config.private_claims.is_empty() {
+            return Ok(None);
+        }
+        let crypto = CryptoClientConfig::from_env();
+        let key_id = KeyId::new(
+            crypto.namespace.clone(),
+            config.private_claims_key_id.clone(),
+            config.private_claims_key_version,
+        );
+        let client = CryptoClientFactory::create_without_fallback(crypto)
+            .await
+            .map_err(|e| TokenError::config(format!("Private claims crypto client: {}", e)))?;
+        Ok(Some(Self::new(client, key_id, &config.private_claims)))
+    }
+
+    /// Replace every private custom claim with its encrypted envelope.
+    pub async fn encrypt(&self, claims: &mut Claims) -> Result<(), TokenError> {
+        for (name, value) in claims
+            .custom
+            .iter_mut()
+            .filter(|(name, _)| self.claims.contains(*name))
+        {
+            let plaintext =
+                serde_json::to_vec(&*value).map_err(|e| TokenError::encryption(e.to_string()))?;
+            let result = self
+                .client
+                .encrypt(&plaintext, &self.key_id, Some(&aad(&claims.jti, name)))
+                .await
+                .map_err(|e| TokenError::encryption(e.to_string()))?;
+
+            let envelope = ClaimEnvelope {
+                key_namespace: result.key_id.namespace,
+                key_id: result.key_id.id,
+                key_version: result.key_id.version,
+                iv: result.iv,
+                ciphertext: result.ciphertext,
+                tag: result.tag,
+            };
+            *value = serde_json::Value::String(envelope.to_compact());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::models::{KeyAlgorithm, KeyRotationResult};
+    use crate::crypto::{CryptoError, EncryptResult, EncryptedData, KeyMetadata, SignResult};
+    use crate::jwt::JwtBuilder;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Records the AAD of each call and "encrypts" by reversing the bytes.
+    #[derive(Default)]
+    struct ReversingClient {
+        aads: Mutex<Vec<Vec<u8>>>,
+        unavailable: bool,
+    }
+
+    #[async_trait]
+    impl CryptoClient for ReversingClient {
+        async fn sign(&self, _data: &[u8], _key_id: &KeyId) -> Result<SignResult, CryptoError> {
+            unimplemented!()
+        }
+
+        async fn verify(
+            &self,
+            _data: &[u8],
+            _signature: &[u8],
+            _key_id: &KeyId,
+        ) -> Result<bool, CryptoError> {
+            unimplemented!()
+        }
+
+        async fn encrypt(
+            &self,
+            plaintext: &[u8],
+            key_id: &KeyId,
+            aad: Option<&[u8]>,
+        ) -> Result<EncryptResult, CryptoError> {
+            if self.unavailable {
+                return Err(CryptoError::connection("crypto-service down"));
+            }
+            self.aads.lock().unwrap().push(aad.unwrap_or_default().to_vec());
+            Ok(EncryptResult {
+                ciphertext: plaintext.iter().rev().copied().collect(),
+                iv: vec![0; 12],
+                tag: vec![1; 16],
+                key_id: key_id.clone(),
+                algorithm: "AES-256-GCM".to_string(),
+            })
+        }
+
+        async fn decrypt(
+            &self,
+            _encrypted: &EncryptedData,
+            _key_id: &KeyId,
+            _aad: Option<&[u8]>,
+        ) -> Result<Vec<u8>, CryptoError> {
+            unimplemented!()
+        }
+
+        async fn generate_key(
+            &self,
+            _algorithm: KeyAlgorithm,
+            _namespace: &str,
+        ) -> Result<KeyId, CryptoError> {
+            unimplemented!()
+        }
+
+        async fn rotate_key(&self, _key_id: &KeyId) -> Result<KeyRotationResult, CryptoError> {
+            unimplemented!()
+        }
+
+        async fn get_key_metadata(&self, _key_id: &KeyId) -> Result<KeyMetadata, CryptoError> {
+            unimplemented!()
+        }
+    }
+
+    fn claims() -> Claims {
+        JwtBuilder::new("auth-platform".to_string())
+            .subject("user-1".to_string())
+            .audience(vec!["api".to_string()])
+            .custom_claim("national_id".to_string(), serde_json::json!("123-45-6789"))
+            .custom_claim("tenant".to_string(), serde_json::json!("acme"))
+            .build()
+            .unwrap()
+    }
+
+    fn encryptor(client: Arc<ReversingClient>) -> PrivateClaimEncryptor {
+        PrivateClaimEncryptor::new(
+            client,
+            KeyId::new("token", "private-claims", 2),
+            &["national_id".to_string()],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_replaces_private_claims_with_envelopes() {
+        let client = Arc::new(ReversingClient::default());
+        let mut claims = claims();
+        encryptor(client.clone()).encrypt(&mut claims).await.unwrap();
+
+        assert_eq!(claims.custom["tenant"], serde_json::json!("acme"));
+        let envelope = ClaimEnvelope::parse(claims.custom["national_id"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            (envelope.key_namespace.as_str(), envelope.key_id.as_str(), envelope.key_version),
+            ("token", "private-claims", 2)
+        );
+        let plaintext: Vec<u8> = envelope.ciphertext.iter().rev().copied().collect();
+        assert_eq!(plaintext, br#""123-45-6789""#);
+        assert_eq!(*client.aads.lock().unwrap(), vec![aad(&claims.jti, "national_id")]);
+    }
+
+    #[tokio::test]
+    async fn test_crypto_failure_fails_issuance() {
+        let client = Arc::new(ReversingClient {
+            unavailable: true,
+            ..ReversingClient::default()
+        });
+        assert!(encryptor(client).encrypt(&mut claims()).await.is_err());
+    }
+}
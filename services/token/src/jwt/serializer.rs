@@ -2,7 +2,14 @@
 
 use crate::error::TokenError;
 use crate::jwt::claims::Claims;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::{crypto, decode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::Serialize;
+
+/// Initial capacity of the JSON and token buffers; fits typical access
+/// tokens without reallocating.
+const TOKEN_CAPACITY: usize = 1024;
 
 /// JWT serializer with configurable algorithm.
 pub struct JwtSerializer {
@@ -37,6 +44,11 @@ impl JwtSerializer {
     }
 
     /// Serialize claims to a JWT string.
+    ///
+    /// Header and claims share one JSON buffer and are base64url-encoded
+    /// straight onto the output, which then holds the signing input; the
+    /// signature is appended in place. `key` must belong to the serializer's
+    /// algorithm family, as created by the issuer's signer.
     pub fn serialize(
         &self,
         claims: &Claims,
@@ -48,7 +60,17 @@ impl JwtSerializer {
             header.kid = Some(kid.to_string());
         }
 
-        encode(&header, claims, key).map_err(|e| TokenError::jwt_encoding(e.to_string()))
+        let mut json = Vec::with_capacity(TOKEN_CAPACITY);
+        let mut token = String::with_capacity(TOKEN_CAPACITY);
+        encode_part(&mut json, &mut token, &header)?;
+        token.push('.');
+        encode_part(&mut json, &mut token, claims)?;
+
+        let signature = crypto::sign(token.as_bytes(), key, self.algorithm)
+            .map_err(|e| TokenError::jwt_encoding(e.to_string()))?;
+        token.push('.');
+        token.push_str(&signature);
+        Ok(token)
     }
 
     /// Deserialize and verify a JWT string.
@@ -93,6 +115,19 @@ impl JwtSerializer {
     }
 }
 
+/// Append the base64url-encoded JSON of `value` to `out`, reusing `json`.
+fn encode_part<T: Serialize>(
+    json: &mut Vec<u8>,
+    out: &mut String,
+    value: &T,
+) -> Result<(), TokenError> {
+    json.clear();
+    serde_json::to_writer(&mut *json, value)
+        .map_err(|e| TokenError::jwt_encoding(e.to_string()))?;
+    URL_SAFE_NO_PAD.encode_string(&json, out);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +164,29 @@ mod tests {
         assert_eq!(claims.jti, decoded.jti);
     }
 
+    #[test]
+    fn test_matches_jsonwebtoken_encoding() {
+        let serializer = JwtSerializer::new(Algorithm::HS256);
+        let (encoding_key, _) = generate_test_keys();
+
+        let claims = JwtBuilder::new("test-issuer".to_string())
+            .subject("user-123".to_string())
+            .audience(vec!["api".to_string()])
+            .scopes(vec!["read".to_string()])
+            .custom_claim("tenant".to_string(), serde_json::json!("acme"))
+            .build()
+            .unwrap();
+
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("key-1".to_string());
+        let expected = jsonwebtoken::encode(&header, &claims, &encoding_key).unwrap();
+
+        let token = serializer
+            .serialize(&claims, &encoding_key, Some("key-1"))
+            .unwrap();
+        assert_eq!(token, expected);
+    }
+
     #[test]
     fn test_deserialize_unverified() {
         let serializer = JwtSerializer::new(Algorithm::HS256);
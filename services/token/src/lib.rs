@@ -5,6 +5,7 @@
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
+#![cfg_attr(not(test), deny(clippy::unwrap_used))]
 
 pub mod config;
 pub mod crypto;
@@ -13,8 +14,12 @@ pub mod error;
 pub mod jwks;
 pub mod jwt;
 pub mod kms;
+pub mod logout;
 pub mod metrics;
 pub mod refresh;
+pub mod revocation;
+pub mod standby;
+pub mod startup;
 pub mod storage;
 
 // Re-exports for convenience
@@ -0,0 +1,409 @@
+//! Back-channel logout delivery.
+//!
+//! Logout tokens are POSTed as the `logout_token` form parameter to each
+//! relying party's logout endpoint. Failed deliveries are retried with
+//! backoff, and every relying party has its own circuit breaker so that an
+//! unreachable endpoint does not hold up logouts at the others.
+
+use super::token::LogoutClaims;
+use crate::config::{BackchannelLogoutClient, Config};
+use crate::error::TokenError;
+use crate::kms::KmsSigner;
+use reqwest::StatusCode;
+use rust_common::{
+    build_http_client, CircuitBreaker, CircuitBreakerConfig, HttpConfig, RetryConfig, RetryPolicy,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+/// Signed logout token addressed to a relying party.
+#[derive(Debug, Clone)]
+pub struct LogoutDelivery {
+    /// Relying party client ID
+    pub client_id: String,
+    /// Signed logout token
+    pub token: String,
+}
+
+/// Outcome of delivering a batch of logout tokens.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LogoutReport {
+    /// Tokens accepted by the relying party
+    pub delivered: u32,
+    /// Tokens rejected or undeliverable after retries
+    pub failed: u32,
+    /// Tokens not sent because the relying party's circuit is open
+    pub skipped: u32,
+}
+
+/// Reason a single delivery attempt failed.
+#[derive(Debug)]
+enum DeliveryError {
+    UnknownClient,
+    CircuitOpen,
+    Transport(reqwest::Error),
+    Status(StatusCode),
+}
+
+impl DeliveryError {
+    /// Whether the endpoint may accept the token on a later attempt.
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::UnknownClient | Self::CircuitOpen => false,
+            Self::Transport(_) => true,
+            Self::Status(status) => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownClient => write!(f, "unknown client"),
+            Self::CircuitOpen => write!(f, "circuit open"),
+            Self::Transport(e) => write!(f, "{}", e),
+            Self::Status(status) => write!(f, "endpoint returned {}", status),
+        }
+    }
+}
+
+/// Registered relying party with its delivery policy.
+struct RelyingParty {
+    client: BackchannelLogoutClient,
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
+}
+
+/// Sends back-channel logout tokens to registered relying parties.
+pub struct BackchannelLogoutEmitter {
+    http: reqwest::Client,
+    parties: Vec<RelyingParty>,
+}
+
+impl BackchannelLogoutEmitter {
+    /// Create an emitter for the given relying parties.
+    ///
+    /// `retry` applies to clients without their own `max_retries`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be built.
+    pub fn new(
+        clients: &[BackchannelLogoutClient],
+        retry: RetryConfig,
+        circuit_breaker: CircuitBreakerConfig,
+        timeout: Duration,
+    ) -> Result<Self, TokenError> {
+        let http = build_http_client(
+            &HttpConfig::default()
+                .with_timeout(timeout)
+                .with_user_agent("token-service/backchannel-logout"),
+        )
+        .map_err(|e| TokenError::internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        let parties = clients
+            .iter()
+            .map(|client| RelyingParty {
+                retry: RetryPolicy::new(
+                    retry
+                        .clone()
+                        .with_max_retries(client.max_retries.unwrap_or(retry.max_retries)),
+                ),
+                breaker: CircuitBreaker::new(circuit_breaker.clone()),
+                client: client.clone(),
+            })
+            .collect();
+
+        Ok(Self { http, parties })
+    }
+
+    /// Build the emitter from configuration; `None` when no relying
+    /// party is registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be built.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, TokenError> {
+        if config.backchannel_logout_clients.is_empty() {
+            return Ok(None);
+        }
+        Self::new(
+            &config.backchannel_logout_clients,
+            RetryConfig::default().with_max_retries(config.backchannel_logout_retries),
+            config.circuit_breaker.clone(),
+            config.backchannel_logout_timeout,
+        )
+        .map(Some)
+    }
+
+    /// Sign logout tokens for every relying party.
+    ///
+    /// Relying parties requiring `sid` get one token per session and none
+    /// when no session is known; the others get a single token naming the
+    /// user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token cannot be signed.
+    pub fn logout_tokens(
+        &self,
+        issuer: &str,
+        subject: &str,
+        session_ids: &[String],
+        signer: &dyn KmsSigner,
+    ) -> Result<Vec<LogoutDelivery>, TokenError> {
+        let mut deliveries = Vec::new();
+        for party in &self.parties {
+            let client_id = &party.client.client_id;
+            let claims = if party.client.backchannel_logout_session_required {
+                session_ids
+                    .iter()
+                    .map(|sid| {
+                        LogoutClaims::new(
+                            issuer,
+                            client_id.as_str(),
+                            Some(subject.to_string()),
+                            Some(sid.clone()),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                vec![LogoutClaims::new(
+                    issuer,
+                    client_id.as_str(),
+                    Some(subject.to_string()),
+                    None,
+                )?]
+            };
+
+            for claims in claims {
+                deliveries.push(LogoutDelivery {
+                    client_id: client_id.clone(),
+                    token: claims.sign(signer)?,
+                });
+            }
+        }
+        Ok(deliveries)
+    }
+
+    /// Deliver logout tokens, all relying parties in parallel.
+    pub async fn deliver(self: Arc<Self>, deliveries: Vec<LogoutDelivery>) -> LogoutReport {
+        let mut tasks = JoinSet::new();
+        for delivery in deliveries {
+            let emitter = self.clone();
+            tasks.spawn(async move { emitter.deliver_one(&delivery).await });
+        }
+
+        let mut report = LogoutReport::default();
+        while let Some(result) = tasks.join_next().await {
+            match result {
+                Ok(Ok(())) => report.delivered += 1,
+                Ok(Err(DeliveryError::CircuitOpen)) => report.skipped += 1,
+                Ok(Err(_)) | Err(_) => report.failed += 1,
+            }
+        }
+
+        info!(
+            delivered = report.delivered,
+            failed = report.failed,
+            skipped = report.skipped,
+            "Back-channel logout delivered"
+        );
+        report
+    }
+
+    /// Deliver one logout token with retries.
+    async fn deliver_one(&self, delivery: &LogoutDelivery) -> Result<(), DeliveryError> {
+        let Some(party) = self
+            .parties
+            .iter()
+            .find(|p| p.client.client_id == delivery.client_id)
+        else {
+            return Err(DeliveryError::UnknownClient);
+        };
+
+        let result = party
+            .retry
+            .execute_when(
+                || self.post(party, &delivery.token),
+                DeliveryError::is_retryable,
+            )
+            .await;
+
+        if let Err(e) = &result {
+            warn!(
+                client_id = %party.client.client_id,
+                error = %e,
+                "Back-channel logout delivery failed"
+            );
+        }
+        result
+    }
+
+    /// Make one delivery attempt, guarded by the relying party's breaker.
+    async fn post(&self, party: &RelyingParty, token: &str) -> Result<(), DeliveryError> {
+        if !party.breaker.allow_request().await {
+            return Err(DeliveryError::CircuitOpen);
+        }
+
+        let result = match self
+            .http
+            .post(&party.client.backchannel_logout_uri)
+            .form(&[("logout_token", token)])
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Err(DeliveryError::Status(response.status())),
+            Err(e) => Err(DeliveryError::Transport(e)),
+        };
+
+        // Only an unavailable endpoint counts against the breaker; a
+        // relying party rejecting the token is still up
+        match &result {
+            Err(e) if e.is_retryable() => party.breaker.record_failure().await,
+            _ => party.breaker.record_success().await,
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kms::MockKms;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer, client_id: &str, session_required: bool) -> BackchannelLogoutClient {
+        BackchannelLogoutClient {
+            client_id: client_id.to_string(),
+            backchannel_logout_uri: format!("{}/{}/logout", server.uri(), client_id),
+            backchannel_logout_session_required: session_required,
+            max_retries: None,
+        }
+    }
+
+    fn emitter(clients: &[BackchannelLogoutClient], breaker: CircuitBreakerConfig) -> Arc<BackchannelLogoutEmitter> {
+        let retry = RetryConfig::default()
+            .with_max_retries(2)
+            .with_initial_delay(Duration::from_millis(1))
+            .without_jitter();
+        Arc::new(
+            BackchannelLogoutEmitter::new(clients, retry, breaker, Duration::from_secs(5)).unwrap(),
+        )
+    }
+
+    fn sessions() -> Vec<String> {
+        vec!["session-1".to_string(), "session-2".to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_tokens_per_session_only_when_required() {
+        let server = MockServer::start().await;
+        let emitter = emitter(
+            &[client(&server, "web", true), client(&server, "admin", false)],
+            CircuitBreakerConfig::default(),
+        );
+        let signer = MockKms::new("issuer-key");
+
+        let deliveries = emitter
+            .logout_tokens("https://auth.example.com", "user-1", &sessions(), &signer)
+            .unwrap();
+        assert_eq!(deliveries.iter().filter(|d| d.client_id == "web").count(), 2);
+        assert_eq!(deliveries.iter().filter(|d| d.client_id == "admin").count(), 1);
+
+        let deliveries = emitter
+            .logout_tokens("https://auth.example.com", "user-1", &[], &signer)
+            .unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].client_id, "admin");
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retried_until_accepted() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/web/logout"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/web/logout"))
+            .and(body_string_contains("logout_token="))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let emitter = emitter(&[client(&server, "web", false)], CircuitBreakerConfig::default());
+        let deliveries = emitter
+            .logout_tokens("https://auth.example.com", "user-1", &[], &MockKms::new("k"))
+            .unwrap();
+
+        let report = emitter.deliver(deliveries).await;
+        assert_eq!(report, LogoutReport { delivered: 1, failed: 0, skipped: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_rejected_token_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/web/logout"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let emitter = emitter(&[client(&server, "web", false)], CircuitBreakerConfig::default());
+        let deliveries = emitter
+            .logout_tokens("https://auth.example.com", "user-1", &[], &MockKms::new("k"))
+            .unwrap();
+
+        let report = emitter.deliver(deliveries).await;
+        assert_eq!(report.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_skips_only_that_relying_party() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/web/logout"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(3)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/admin/logout"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let breaker = CircuitBreakerConfig::default()
+            .with_failure_threshold(3)
+            .with_timeout(Duration::from_secs(60));
+        let emitter = emitter(
+            &[client(&server, "web", false), client(&server, "admin", false)],
+            breaker,
+        );
+        let signer = MockKms::new("k");
+
+        // Three failed attempts open the web circuit
+        let deliveries = emitter
+            .logout_tokens("https://auth.example.com", "user-1", &[], &signer)
+            .unwrap();
+        let report = emitter.clone().deliver(deliveries).await;
+        assert_eq!(report, LogoutReport { delivered: 1, failed: 1, skipped: 0 });
+
+        let deliveries = emitter
+            .logout_tokens("https://auth.example.com", "user-2", &[], &signer)
+            .unwrap();
+        let report = emitter.deliver(deliveries).await;
+        assert_eq!(report, LogoutReport { delivered: 1, failed: 0, skipped: 1 });
+    }
+}
@@ -0,0 +1,10 @@
+//! OIDC back-channel logout.
+//!
+//! When all of a user's tokens are revoked, registered relying parties
+//! receive signed logout tokens so they can end their own sessions too.
+
+pub mod emitter;
+pub mod token;
+
+pub use emitter::{BackchannelLogoutEmitter, LogoutDelivery, LogoutReport};
+pub use token::{LogoutClaims, BACKCHANNEL_LOGOUT_EVENT, LOGOUT_TOKEN_TYPE};
@@ -0,0 +1,145 @@
+//! Back-channel logout tokens.
+//!
+//! A logout token is a JWT of type `logout+jwt` naming the user (`sub`)
+//! and/or the session (`sid`) a relying party must log out
+//! (OpenID Connect Back-Channel Logout 1.0, section 2.4).
+
+use crate::error::TokenError;
+use crate::kms::KmsSigner;
+use jsonwebtoken::{encode, Algorithm, Header};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Event marking a JWT as a back-channel logout token.
+pub const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// JWT `typ` header of logout tokens.
+pub const LOGOUT_TOKEN_TYPE: &str = "logout+jwt";
+
+/// Logout token lifetime in seconds.
+const LOGOUT_TOKEN_TTL: i64 = 120;
+
+/// Logout token claims.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogoutClaims {
+    /// Issuer
+    pub iss: String,
+    /// Subject being logged out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Relying party client ID
+    pub aud: String,
+    /// Issued at (Unix timestamp)
+    pub iat: i64,
+    /// Expiration time (Unix timestamp)
+    pub exp: i64,
+    /// Token ID
+    pub jti: String,
+    /// Back-channel logout event
+    pub events: HashMap<String, serde_json::Value>,
+    /// Session being logged out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+}
+
+impl LogoutClaims {
+    /// Create logout claims for a relying party.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither a subject nor a session is given.
+    pub fn new(
+        issuer: impl Into<String>,
+        client_id: impl Into<String>,
+        subject: Option<String>,
+        session_id: Option<String>,
+    ) -> Result<Self, TokenError> {
+        if subject.is_none() && session_id.is_none() {
+            return Err(TokenError::jwt_encoding(
+                "Logout token requires sub or sid",
+            ));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut events = HashMap::new();
+        events.insert(BACKCHANNEL_LOGOUT_EVENT.to_string(), serde_json::json!({}));
+
+        Ok(Self {
+            iss: issuer.into(),
+            sub: subject,
+            aud: client_id.into(),
+            iat: now,
+            exp: now + LOGOUT_TOKEN_TTL,
+            jti: uuid::Uuid::new_v4().to_string(),
+            events,
+            sid: session_id,
+        })
+    }
+
+    /// Sign the logout token with an issuer's key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signer's algorithm or key is unusable.
+    pub fn sign(&self, signer: &dyn KmsSigner) -> Result<String, TokenError> {
+        let algorithm: Algorithm = signer.algorithm().parse().map_err(|_| {
+            TokenError::jwt_encoding(format!("Unsupported algorithm: {}", signer.algorithm()))
+        })?;
+
+        let mut header = Header::new(algorithm);
+        header.typ = Some(LOGOUT_TOKEN_TYPE.to_string());
+        header.kid = Some(signer.key_id().to_string());
+
+        encode(&header, self, &signer.get_encoding_key()?)
+            .map_err(|e| TokenError::jwt_encoding(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kms::MockKms;
+    use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
+
+    #[test]
+    fn test_logout_token_signed_with_issuer_key() {
+        let signer = MockKms::new("issuer-key").with_secret(b"logout-secret".to_vec());
+        let claims = LogoutClaims::new(
+            "https://auth.example.com",
+            "web",
+            Some("user-1".to_string()),
+            Some("session-1".to_string()),
+        )
+        .unwrap();
+
+        let token = claims.sign(&signer).unwrap();
+
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.typ.as_deref(), Some(LOGOUT_TOKEN_TYPE));
+        assert_eq!(header.kid.as_deref(), Some("issuer-key"));
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&["web"]);
+        let decoded = decode::<LogoutClaims>(
+            &token,
+            &DecodingKey::from_secret(b"logout-secret"),
+            &validation,
+        )
+        .unwrap()
+        .claims;
+
+        assert_eq!(decoded, claims);
+        assert!(decoded.events.contains_key(BACKCHANNEL_LOGOUT_EVENT));
+        assert_eq!(decoded.sid.as_deref(), Some("session-1"));
+    }
+
+    #[test]
+    fn test_logout_token_requires_sub_or_sid() {
+        assert!(LogoutClaims::new("iss", "web", None, None).is_err());
+
+        let claims = LogoutClaims::new("iss", "web", None, Some("s".to_string())).unwrap();
+        let json = serde_json::to_value(&claims).unwrap();
+        assert!(json.get("sub").is_none());
+        assert!(json.get("nonce").is_none());
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Uses platform libraries for caching, logging, and circuit breaker.
 
+#![cfg_attr(not(test), deny(clippy::unwrap_used))]
+
 mod config;
 mod dpop;
 mod error;
@@ -9,18 +11,25 @@ mod grpc;
 mod jwks;
 mod jwt;
 mod kms;
+mod logout;
 pub mod metrics;
 mod refresh;
+mod revocation;
+mod standby;
+mod startup;
 mod storage;
 
 use crate::config::Config;
 use crate::grpc::TokenServiceImpl;
-use rust_common::{CacheClient, LoggingClient};
-use std::net::SocketAddr;
+use crate::startup::Startup;
+use crate::storage::SchemaMigrator;
+use rust_common::{
+    init_telemetry, AuthSpanLayer, CacheClient, GrpcMetrics, GrpcMetricsLayer, LoggingClient,
+    TenantBuckets, TenantMetrics, TenantMetricsLayer, TraceContextLayer, TracingConfig,
+};
 use std::sync::Arc;
 use tonic::transport::Server;
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use tracing::info;
 
 pub mod proto {
     pub mod common {
@@ -29,34 +38,61 @@ pub mod proto {
     pub mod token {
         tonic::include_proto!("auth.token");
     }
+
+    /// Encoded descriptors of the served protos, for server reflection
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("token_service_descriptor");
 }
 
 use proto::token::token_service_server::TokenServiceServer;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    let _guard = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .json()
-        .try_init();
+    let config = Config::from_env()?;
+
+    // Initialize logging and tracing
+    let mut tracing_config = TracingConfig::default()
+        .with_service_name("token-service")
+        .with_service_version(env!("CARGO_PKG_VERSION"))
+        .with_json_output()
+        .with_sampling_ratio(config.otlp_sampling_ratio)
+        .with_resource_attributes(config.otlp_resource_attributes.clone());
+    if let Some(spiffe_id) = &config.spiffe_id {
+        tracing_config = tracing_config.with_spiffe_id(spiffe_id);
+    }
+    if let Some(endpoint) = &config.otlp_endpoint {
+        tracing_config = tracing_config
+            .with_otlp_endpoint(endpoint)
+            .with_otlp_headers(config.otlp_headers.clone());
+    }
+    let telemetry = init_telemetry(&tracing_config)?;
 
     info!("Starting Token Service");
 
-    let config = Config::from_env()?;
-    let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
+    // `--migrate-only` applies storage schema migrations and exits
+    let migrate_only = std::env::args().skip(1).any(|arg| arg == "--migrate-only");
+
+    let addrs = config.listen_addrs()?;
+    let reflection_enabled = config.grpc_reflection_enabled;
+    let tenant_buckets = Arc::new(TenantBuckets::new(config.tenant_metrics_top_n));
+    let tenant_metrics_window = config.tenant_metrics_window;
+
+    // Bring dependencies up in order, waiting out ones still starting
+    let mut startup = Startup::from_config(&config);
 
     // Initialize platform clients
     let cache_client = Arc::new(
-        CacheClient::new(config.cache.clone())
-            .await
-            .expect("Failed to create cache client"),
+        startup
+            .init("cache", || async { Ok(CacheClient::new(config.cache.clone()).await?) })
+            .await?,
     );
 
     let logging_client = Arc::new(
-        LoggingClient::new(config.logging.clone())
-            .await
-            .expect("Failed to create logging client"),
+        startup
+            .init("logging", || async {
+                Ok(LoggingClient::new(config.logging.clone()).await?)
+            })
+            .await?,
     );
 
     info!(
@@ -65,13 +101,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "Platform clients initialized"
     );
 
+    // Bring the storage schema up to date before serving; a read-only
+    // standby only checks that it understands the stored version
+    let schema = SchemaMigrator::from_config(&config, cache_client.clone());
+    if migrate_only {
+        let report = startup.init("schema", || schema.run()).await?;
+        startup.finish();
+        info!(
+            version = report.to_version,
+            applied = ?report.applied,
+            "Storage schema migrated, exiting"
+        );
+        telemetry.shutdown();
+        return Ok(());
+    }
+    if config.read_only || !config.schema_migrate_on_start {
+        let version = startup.init("schema", || schema.check()).await?;
+        info!(version, "Storage schema migrations skipped on startup");
+    } else {
+        startup.init("schema", || schema.run()).await?;
+    }
+
+    let storage = startup
+        .init("storage", || TokenServiceImpl::build_storage(&config))
+        .await?;
+    startup.finish();
+
     let token_service = TokenServiceImpl::new(
         config,
         cache_client,
         logging_client,
+        storage,
     ).await?;
 
-    info!("Token Service listening on {}", addr);
+    let listeners = rust_common::listen::bind(&addrs)?;
+    info!(addrs = ?addrs, "Token Service listening");
 
     // Graceful shutdown handling
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
@@ -84,13 +148,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let _ = shutdown_tx.send(());
     });
 
+    let grpc_metrics = GrpcMetrics::new("token_service", prometheus::default_registry())?;
+    let tenant_metrics =
+        TenantMetrics::new("token_service", prometheus::default_registry(), tenant_buckets)?;
+    tokio::spawn(tenant_metrics.clone().run(tenant_metrics_window));
+
+    // Lets grpcurl and gateways discover the API without the proto files
+    let (reflection, reflection_v1alpha) = if reflection_enabled {
+        info!("gRPC server reflection enabled");
+        let builder = || {
+            tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
+        };
+        (Some(builder().build_v1()?), Some(builder().build_v1alpha()?))
+    } else {
+        (None, None)
+    };
+
     Server::builder()
+        .layer(TraceContextLayer)
+        .layer(AuthSpanLayer)
+        .layer(GrpcMetricsLayer::new(grpc_metrics))
+        .layer(TenantMetricsLayer::new(tenant_metrics))
         .add_service(TokenServiceServer::new(token_service))
-        .serve_with_shutdown(addr, async {
+        .add_optional_service(reflection)
+        .add_optional_service(reflection_v1alpha)
+        .serve_with_incoming_shutdown(rust_common::listen::incoming(listeners), async {
             shutdown_rx.await.ok();
         })
         .await?;
 
     info!("Token Service shutdown complete");
+    telemetry.shutdown();
     Ok(())
 }
@@ -4,7 +4,7 @@
 
 use once_cell::sync::Lazy;
 use prometheus::{
-    register_counter_vec, register_histogram_vec, CounterVec, HistogramVec,
+    register_counter_vec, register_gauge, register_histogram, CounterVec, Gauge, Histogram,
 };
 
 /// Tokens issued counter.
@@ -47,17 +47,6 @@ pub static DPOP_VALIDATIONS: Lazy<CounterVec> = Lazy::new(|| {
     .expect("Failed to register dpop_validations metric")
 });
 
-/// gRPC method latency histogram.
-pub static GRPC_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
-    register_histogram_vec!(
-        "token_service_grpc_latency_seconds",
-        "gRPC method latency in seconds",
-        &["method"],
-        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]
-    )
-    .expect("Failed to register grpc_latency metric")
-});
-
 /// KMS operations counter.
 pub static KMS_OPERATIONS: Lazy<CounterVec> = Lazy::new(|| {
     register_counter_vec!(
@@ -88,6 +77,65 @@ pub static SECURITY_EVENTS: Lazy<CounterVec> = Lazy::new(|| {
     .expect("Failed to register security_events metric")
 });
 
+/// Refresh token rotations counter, per client.
+pub static REFRESH_ROTATIONS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "token_service_refresh_rotations_total",
+        "Total number of refresh token rotations",
+        &["client_id"]
+    )
+    .expect("Failed to register refresh_rotations metric")
+});
+
+/// Refresh token replay detections counter, per client.
+pub static REFRESH_REPLAYS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "token_service_refresh_replays_total",
+        "Total number of refresh token replays detected",
+        &["client_id"]
+    )
+    .expect("Failed to register refresh_replays metric")
+});
+
+/// Token family revocations counter.
+pub static FAMILY_REVOCATIONS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "token_service_family_revocations_total",
+        "Total number of token family revocations",
+        &["cause"]
+    )
+    .expect("Failed to register family_revocations metric")
+});
+
+/// Age of token families when they are revoked.
+pub static FAMILY_AGE: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "token_service_family_age_seconds",
+        "Age of token families at revocation in seconds",
+        vec![60.0, 300.0, 900.0, 3600.0, 14400.0, 86400.0, 259200.0, 604800.0, 2592000.0]
+    )
+    .expect("Failed to register family_age metric")
+});
+
+/// Clients flagged for an anomalous replay rate.
+pub static REPLAY_ANOMALIES: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "token_service_replay_anomalies_total",
+        "Total number of clients flagged for anomalous refresh token replay rates",
+        &["client_id", "downgrade"]
+    )
+    .expect("Failed to register replay_anomalies metric")
+});
+
+/// Whether the service is in read-only mode (1) or accepts writes (0).
+pub static READ_ONLY_MODE: Lazy<Gauge> = Lazy::new(|| {
+    register_gauge!(
+        "token_service_read_only",
+        "Whether issuance and rotation are disabled by read-only mode"
+    )
+    .expect("Failed to register read_only metric")
+});
+
 /// Record a token issuance.
 pub fn record_token_issued(token_type: &str, algorithm: &str) {
     TOKENS_ISSUED
@@ -112,11 +160,6 @@ pub fn record_dpop_validation(status: &str, error_type: &str) {
         .inc();
 }
 
-/// Record gRPC method latency.
-pub fn record_grpc_latency(method: &str, duration_secs: f64) {
-    GRPC_LATENCY.with_label_values(&[method]).observe(duration_secs);
-}
-
 /// Record a KMS operation.
 pub fn record_kms_operation(operation: &str, status: &str) {
     KMS_OPERATIONS
@@ -136,6 +179,34 @@ pub fn record_security_event(event_type: &str) {
     SECURITY_EVENTS.with_label_values(&[event_type]).inc();
 }
 
+/// Record a refresh token rotation.
+pub fn record_refresh_rotation(client_id: &str) {
+    REFRESH_ROTATIONS.with_label_values(&[client_id]).inc();
+}
+
+/// Record a refresh token replay detection.
+pub fn record_refresh_replay(client_id: &str) {
+    REFRESH_REPLAYS.with_label_values(&[client_id]).inc();
+}
+
+/// Record a token family revocation and the family's age.
+pub fn record_family_revocation(cause: &str, age_secs: f64) {
+    FAMILY_REVOCATIONS.with_label_values(&[cause]).inc();
+    FAMILY_AGE.observe(age_secs.max(0.0));
+}
+
+/// Record a replay anomaly and the outcome of the trust downgrade.
+pub fn record_replay_anomaly(client_id: &str, downgrade: &str) {
+    REPLAY_ANOMALIES
+        .with_label_values(&[client_id, downgrade])
+        .inc();
+}
+
+/// Record the service mode.
+pub fn record_service_mode(read_only: bool) {
+    READ_ONLY_MODE.set(if read_only { 1.0 } else { 0.0 });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,12 +220,6 @@ mod tests {
         assert!(value > 0.0);
     }
 
-    #[test]
-    fn test_record_grpc_latency() {
-        record_grpc_latency("IssueTokenPair", 0.05);
-        // Histogram observation doesn't have a simple getter
-    }
-
     #[test]
     fn test_record_security_event() {
         record_security_event("REPLAY_ATTACK");
@@ -163,4 +228,12 @@ mod tests {
             .get();
         assert!(value > 0.0);
     }
+
+    #[test]
+    fn test_record_family_revocation() {
+        record_family_revocation("replay", 120.0);
+        let value = FAMILY_REVOCATIONS.with_label_values(&["replay"]).get();
+        assert!(value > 0.0);
+        assert!(FAMILY_AGE.get_sample_count() > 0);
+    }
 }
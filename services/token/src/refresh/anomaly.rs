@@ -0,0 +1,379 @@
+//! Refresh token replay anomaly detection.
+//!
+//! Rotations and replays are counted per client over a fixed window. A
+//! client whose share of replays crosses the threshold is flagged once per
+//! window and reported to a [`TrustDowngradeHook`], which lowers the
+//! client's trust level in the edge rate limiter.
+
+use crate::config::Config;
+use crate::error::TokenError;
+use crate::metrics;
+use async_trait::async_trait;
+use rust_common::{build_http_client, HttpConfig};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Timeout of a trust downgrade request.
+const DOWNGRADE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Replay anomaly thresholds.
+#[derive(Debug, Clone)]
+pub struct AnomalyConfig {
+    /// Window over which rotations and replays are counted
+    pub window: Duration,
+    /// Share of replays among refresh attempts that flags a client
+    pub replay_rate_threshold: f64,
+    /// Refresh attempts required in a window before a client can be flagged
+    pub min_events: u32,
+    /// How long the edge keeps a flagged client downgraded
+    pub downgrade_ttl: Duration,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(300),
+            replay_rate_threshold: 0.2,
+            min_events: 10,
+            downgrade_ttl: Duration::from_secs(900),
+        }
+    }
+}
+
+/// Client flagged for an anomalous replay rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayAnomaly {
+    /// Client presenting the replayed tokens
+    pub client_id: String,
+    /// Successful rotations in the window
+    pub rotations: u32,
+    /// Replays detected in the window
+    pub replays: u32,
+    /// Replays as a share of all refresh attempts in the window
+    pub replay_rate: f64,
+}
+
+/// Lowers the trust of a flagged client in another service.
+#[async_trait]
+pub trait TrustDowngradeHook: Send + Sync {
+    /// Downgrade the client for `ttl`.
+    async fn downgrade(&self, anomaly: &ReplayAnomaly, ttl: Duration) -> Result<(), TokenError>;
+}
+
+/// Body of a trust downgrade request.
+#[derive(Debug, Serialize)]
+struct DowngradeRequest<'a> {
+    client_id: &'a str,
+    reason: &'static str,
+    replay_rate: f64,
+    hold_seconds: u64,
+}
+
+/// Posts trust downgrades to the edge service's `/rate-limit/trust` endpoint.
+pub struct HttpTrustDowngradeHook {
+    http: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl HttpTrustDowngradeHook {
+    /// Create a hook posting to `url`, authenticated with `api_key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be built.
+    pub fn new(url: impl Into<String>, api_key: Option<String>) -> Result<Self, TokenError> {
+        let http = build_http_client(
+            &HttpConfig::default()
+                .with_timeout(DOWNGRADE_TIMEOUT)
+                .with_user_agent("token-service/trust-downgrade"),
+        )
+        .map_err(|e| TokenError::internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            url: url.into(),
+            api_key,
+        })
+    }
+}
+
+#[async_trait]
+impl TrustDowngradeHook for HttpTrustDowngradeHook {
+    async fn downgrade(&self, anomaly: &ReplayAnomaly, ttl: Duration) -> Result<(), TokenError> {
+        let mut request = self.http.post(&self.url).json(&DowngradeRequest {
+            client_id: &anomaly.client_id,
+            reason: "refresh_token_replay",
+            replay_rate: anomaly.replay_rate,
+            hold_seconds: ttl.as_secs(),
+        });
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TokenError::internal(format!("Trust downgrade failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(TokenError::internal(format!(
+                "Trust downgrade rejected with {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Refresh attempts of a client in the current window.
+struct ClientWindow {
+    start: Instant,
+    rotations: u32,
+    replays: u32,
+    flagged: bool,
+}
+
+/// Flags clients with anomalous refresh token replay rates.
+pub struct ReplayAnomalyDetector {
+    config: AnomalyConfig,
+    clients: Mutex<HashMap<String, ClientWindow>>,
+    hook: Option<Arc<dyn TrustDowngradeHook>>,
+}
+
+impl ReplayAnomalyDetector {
+    /// Create a detector that only records anomalies.
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            clients: Mutex::new(HashMap::new()),
+            hook: None,
+        }
+    }
+
+    /// Report flagged clients to `hook`.
+    pub fn with_hook(mut self, hook: Arc<dyn TrustDowngradeHook>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// Build the detector from configuration, with an HTTP hook when
+    /// `TRUST_DOWNGRADE_URL` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be built.
+    pub fn from_config(config: &Config) -> Result<Self, TokenError> {
+        let detector = Self::new(AnomalyConfig {
+            window: config.replay_anomaly_window,
+            replay_rate_threshold: config.replay_anomaly_threshold,
+            min_events: config.replay_anomaly_min_events,
+            downgrade_ttl: config.trust_downgrade_ttl,
+        });
+        match &config.trust_downgrade_url {
+            Some(url) => Ok(detector.with_hook(Arc::new(HttpTrustDowngradeHook::new(
+                url.clone(),
+                config.trust_downgrade_api_key.clone(),
+            )?))),
+            None => Ok(detector),
+        }
+    }
+
+    /// Record a successful rotation by `client_id`.
+    pub fn record_rotation(&self, client_id: &str) {
+        self.observe_at(client_id, false, Instant::now());
+    }
+
+    /// Record a replay by `client_id`, returning the anomaly if this replay
+    /// flags the client.
+    ///
+    /// The downgrade hook runs in the background.
+    pub fn record_replay(&self, client_id: &str) -> Option<ReplayAnomaly> {
+        let anomaly = self.observe_at(client_id, true, Instant::now())?;
+        warn!(
+            client_id = %anomaly.client_id,
+            replays = anomaly.replays,
+            rotations = anomaly.rotations,
+            replay_rate = anomaly.replay_rate,
+            "Refresh token replay rate anomaly"
+        );
+
+        match &self.hook {
+            Some(hook) => {
+                let hook = hook.clone();
+                let ttl = self.config.downgrade_ttl;
+                let flagged = anomaly.clone();
+                tokio::spawn(async move {
+                    let outcome = match hook.downgrade(&flagged, ttl).await {
+                        Ok(()) => "ok",
+                        Err(e) => {
+                            warn!(client_id = %flagged.client_id, error = %e, "Trust downgrade failed");
+                            "failed"
+                        }
+                    };
+                    metrics::record_replay_anomaly(&flagged.client_id, outcome);
+                });
+            }
+            None => metrics::record_replay_anomaly(&anomaly.client_id, "disabled"),
+        }
+        Some(anomaly)
+    }
+
+    /// Count a refresh attempt and flag the client if its window crosses
+    /// the threshold for the first time.
+    fn observe_at(&self, client_id: &str, replay: bool, now: Instant) -> Option<ReplayAnomaly> {
+        if client_id.is_empty() {
+            return None;
+        }
+        let window = self.config.window;
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if !clients.contains_key(client_id) {
+            clients.retain(|_, w| now.duration_since(w.start) < window);
+        }
+
+        let counts = clients
+            .entry(client_id.to_string())
+            .or_insert_with(|| ClientWindow {
+                start: now,
+                rotations: 0,
+                replays: 0,
+                flagged: false,
+            });
+        if now.duration_since(counts.start) >= window {
+            *counts = ClientWindow {
+                start: now,
+                rotations: 0,
+                replays: 0,
+                flagged: false,
+            };
+        }
+
+        if replay {
+            counts.replays += 1;
+        } else {
+            counts.rotations += 1;
+        }
+
+        let events = counts.rotations + counts.replays;
+        let replay_rate = f64::from(counts.replays) / f64::from(events);
+        if counts.flagged
+            || events < self.config.min_events
+            || replay_rate < self.config.replay_rate_threshold
+        {
+            return None;
+        }
+
+        counts.flagged = true;
+        Some(ReplayAnomaly {
+            client_id: client_id.to_string(),
+            rotations: counts.rotations,
+            replays: counts.replays,
+            replay_rate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn detector() -> ReplayAnomalyDetector {
+        ReplayAnomalyDetector::new(AnomalyConfig {
+            window: Duration::from_secs(60),
+            replay_rate_threshold: 0.25,
+            min_events: 4,
+            downgrade_ttl: Duration::from_secs(600),
+        })
+    }
+
+    #[test]
+    fn test_flags_client_once_per_window() {
+        let detector = detector();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(detector.observe_at("client-a", false, start).is_none());
+        }
+        let anomaly = detector.observe_at("client-a", true, start).unwrap();
+        assert_eq!(anomaly.client_id, "client-a");
+        assert_eq!((anomaly.rotations, anomaly.replays), (3, 1));
+        assert_eq!(anomaly.replay_rate, 0.25);
+
+        assert!(detector.observe_at("client-a", true, start).is_none());
+        assert!(detector.observe_at("client-b", true, start).is_none());
+
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(detector.observe_at("client-a", true, later).is_none());
+        }
+        assert!(detector.observe_at("client-a", true, later).is_some());
+    }
+
+    #[test]
+    fn test_low_replay_rate_and_anonymous_clients_are_not_flagged() {
+        let detector = detector();
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert!(detector.observe_at("client-a", false, now).is_none());
+        }
+        assert!(detector.observe_at("client-a", true, now).is_none());
+
+        for _ in 0..10 {
+            assert!(detector.observe_at("", true, now).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_hook_posts_downgrade() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rate-limit/trust"))
+            .and(header("x-api-key", "secret"))
+            .and(body_json(serde_json::json!({
+                "client_id": "client-a",
+                "reason": "refresh_token_replay",
+                "replay_rate": 0.5,
+                "hold_seconds": 600
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let hook = HttpTrustDowngradeHook::new(
+            format!("{}/rate-limit/trust", server.uri()),
+            Some("secret".to_string()),
+        )
+        .unwrap();
+        let anomaly = ReplayAnomaly {
+            client_id: "client-a".to_string(),
+            rotations: 2,
+            replays: 2,
+            replay_rate: 0.5,
+        };
+        hook.downgrade(&anomaly, Duration::from_secs(600)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_http_hook_reports_rejection() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let hook = HttpTrustDowngradeHook::new(server.uri(), None).unwrap();
+        let anomaly = ReplayAnomaly {
+            client_id: "client-a".to_string(),
+            rotations: 0,
+            replays: 4,
+            replay_rate: 1.0,
+        };
+        assert!(hook.downgrade(&anomaly, Duration::from_secs(60)).await.is_err());
+    }
+}
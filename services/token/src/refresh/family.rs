@@ -11,6 +11,13 @@ pub struct TokenFamily {
     pub created_at: DateTime<Utc>,
     pub revoked: bool,
     pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Audiences of a multi-audience family; `audience` is unset then
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audiences: Vec<String>,
 }
 
 impl TokenFamily {
@@ -24,9 +31,27 @@ impl TokenFamily {
             created_at: Utc::now(),
             revoked: false,
             revoked_at: None,
+            issuer: None,
+            audience: None,
+            audiences: Vec::new(),
         }
     }
 
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    pub fn with_audiences(mut self, audiences: Vec<String>) -> Self {
+        self.audiences = audiences;
+        self
+    }
+
     pub fn rotate(&mut self, new_token_hash: String) {
         self.current_token_hash = new_token_hash;
         self.rotation_count += 1;
@@ -95,4 +120,31 @@ mod tests {
         assert!(family.revoked);
         assert!(!family.is_valid_token("hash-1"));
     }
+
+    #[test]
+    fn test_issuer_defaults_when_absent() {
+        let family = TokenFamily::new(
+            "family-1".to_string(),
+            "user-1".to_string(),
+            "session-1".to_string(),
+            "hash-1".to_string(),
+        );
+
+        let json = serde_json::to_string(&family).unwrap();
+        assert!(!json.contains("issuer"));
+        let restored: TokenFamily = serde_json::from_str(&json).unwrap();
+        assert!(restored.issuer.is_none());
+
+        let partner = family.with_issuer("https://auth.partner.com");
+        let json = serde_json::to_string(&partner).unwrap();
+        let restored: TokenFamily = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.issuer.as_deref(), Some("https://auth.partner.com"));
+        assert!(restored.audience.is_none());
+
+        let json = serde_json::to_string(&partner.with_audience("partner-api")).unwrap();
+        assert!(!json.contains("audiences"));
+        let restored: TokenFamily = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.audience.as_deref(), Some("partner-api"));
+        assert!(restored.audiences.is_empty());
+    }
 }
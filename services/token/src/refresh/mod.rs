@@ -1,7 +1,9 @@
+pub mod anomaly;
 pub mod generator;
 pub mod rotator;
 pub mod family;
 
+pub use anomaly::{ReplayAnomalyDetector, TrustDowngradeHook};
 pub use generator::RefreshTokenGenerator;
 pub use rotator::RefreshTokenRotator;
 pub use family::TokenFamily;
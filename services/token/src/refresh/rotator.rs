@@ -1,11 +1,15 @@
 //! Refresh token rotation with replay detection.
 //!
-//! Uses CacheStorage for persistence and LoggingClient for security events.
+//! Uses a FamilyStore for persistence and LoggingClient for security events.
+//! Rotations, replays and revocations are recorded as metrics, and replays
+//! feed the optional ReplayAnomalyDetector.
 
 use crate::error::TokenError;
+use crate::metrics;
+use crate::refresh::anomaly::ReplayAnomalyDetector;
 use crate::refresh::family::TokenFamily;
 use crate::refresh::generator::RefreshTokenGenerator;
-use crate::storage::CacheStorage;
+use crate::storage::FamilyStore;
 use rust_common::{LogEntry, LogLevel, LoggingClient};
 use std::sync::Arc;
 use std::time::Duration;
@@ -13,15 +17,19 @@ use tracing::{info, warn};
 
 /// Refresh token rotator with replay detection.
 pub struct RefreshTokenRotator {
-    storage: Arc<CacheStorage>,
+    storage: Arc<dyn FamilyStore>,
     logger: Arc<LoggingClient>,
     default_ttl: Duration,
+    anomaly: Option<Arc<ReplayAnomalyDetector>>,
 }
 
+/// Metric label for refresh attempts without a client ID.
+const UNKNOWN_CLIENT: &str = "unknown";
+
 impl RefreshTokenRotator {
-    /// Create a new rotator with cache storage and logging.
+    /// Create a new rotator with family storage and logging.
     pub fn new(
-        storage: Arc<CacheStorage>,
+        storage: Arc<dyn FamilyStore>,
         logger: Arc<LoggingClient>,
         default_ttl: Duration,
     ) -> Self {
@@ -29,26 +37,72 @@ impl RefreshTokenRotator {
             storage,
             logger,
             default_ttl,
+            anomaly: None,
         }
     }
 
+    /// Feed rotations and replays to an anomaly detector.
+    pub fn with_anomaly_detector(mut self, detector: Arc<ReplayAnomalyDetector>) -> Self {
+        self.anomaly = Some(detector);
+        self
+    }
+
     /// Create a new token family for a user session.
     pub async fn create_token_family(
         &self,
         user_id: &str,
         session_id: &str,
         correlation_id: Option<&str>,
+    ) -> Result<(String, TokenFamily), TokenError> {
+        self.create_issuer_token_family(user_id, session_id, None, correlation_id)
+            .await
+    }
+
+    /// Create a new token family whose tokens are signed for `issuer`.
+    ///
+    /// `None` leaves the family on the default issuer.
+    pub async fn create_issuer_token_family(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        issuer: Option<&str>,
+        correlation_id: Option<&str>,
+    ) -> Result<(String, TokenFamily), TokenError> {
+        self.create_scoped_token_family(user_id, session_id, issuer, &[], correlation_id)
+            .await
+    }
+
+    /// Create a new token family whose tokens are signed for `issuer` and
+    /// `audiences`.
+    ///
+    /// `None` and no audiences leave the family on the default issuer or
+    /// audience.
+    pub async fn create_scoped_token_family(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        issuer: Option<&str>,
+        audiences: &[String],
+        correlation_id: Option<&str>,
     ) -> Result<(String, TokenFamily), TokenError> {
         let token = RefreshTokenGenerator::generate();
         let token_hash = RefreshTokenGenerator::hash(&token);
         let family_id = RefreshTokenGenerator::generate_family_id();
 
-        let family = TokenFamily::new(
+        let mut family = TokenFamily::new(
             family_id.clone(),
             user_id.to_string(),
             session_id.to_string(),
             token_hash,
         );
+        if let Some(issuer) = issuer {
+            family = family.with_issuer(issuer);
+        }
+        match audiences {
+            [] => {}
+            [audience] => family = family.with_audience(audience.as_str()),
+            _ => family = family.with_audiences(audiences.to_vec()),
+        }
 
         self.storage
             .store_token_family(&family, Some(self.default_ttl))
@@ -77,6 +131,21 @@ impl RefreshTokenRotator {
         refresh_token: &str,
         correlation_id: Option<&str>,
     ) -> Result<(String, TokenFamily), TokenError> {
+        self.rotate_for_client(refresh_token, None, correlation_id)
+            .await
+    }
+
+    /// Rotate a refresh token presented by `client_id`.
+    ///
+    /// The client ID labels rotation metrics and is tracked for anomalous
+    /// replay rates; `None` is recorded as an unknown client.
+    pub async fn rotate_for_client(
+        &self,
+        refresh_token: &str,
+        client_id: Option<&str>,
+        correlation_id: Option<&str>,
+    ) -> Result<(String, TokenFamily), TokenError> {
+        let client_label = client_id.unwrap_or(UNKNOWN_CLIENT);
         let token_hash = RefreshTokenGenerator::hash(refresh_token);
 
         let mut family = self.storage
@@ -108,6 +177,12 @@ impl RefreshTokenRotator {
                 correlation_id,
             ).await;
 
+            metrics::record_refresh_replay(client_label);
+            Self::record_revocation("replay", &family);
+            if let (Some(detector), Some(client_id)) = (&self.anomaly, client_id) {
+                detector.record_replay(client_id);
+            }
+
             return Err(TokenError::RefreshReplay);
         }
 
@@ -132,6 +207,11 @@ impl RefreshTokenRotator {
             correlation_id,
         ).await;
 
+        metrics::record_refresh_rotation(client_label);
+        if let (Some(detector), Some(client_id)) = (&self.anomaly, client_id) {
+            detector.record_rotation(client_id);
+        }
+
         Ok((new_token, family))
     }
 
@@ -154,6 +234,7 @@ impl RefreshTokenRotator {
                 &family,
                 correlation_id,
             ).await;
+            Self::record_revocation("explicit", &family);
         }
         Ok(())
     }
@@ -164,24 +245,43 @@ impl RefreshTokenRotator {
         user_id: &str,
         correlation_id: Option<&str>,
     ) -> Result<u32, TokenError> {
-        let families = self.storage.get_user_token_families(user_id).await?;
+        let families = self
+            .revoke_user_token_families(user_id, correlation_id)
+            .await?;
+        Ok(families.len() as u32)
+    }
+
+    /// Revoke all token families for a user, returning the revoked families.
+    pub async fn revoke_user_token_families(
+        &self,
+        user_id: &str,
+        correlation_id: Option<&str>,
+    ) -> Result<Vec<TokenFamily>, TokenError> {
+        let mut families = self.storage.get_user_token_families(user_id).await?;
         let count = families.len() as u32;
 
-        for mut family in families {
+        for family in &mut families {
             family.revoke();
             self.storage
-                .store_token_family(&family, Some(Duration::from_secs(86400)))
+                .store_token_family(family, Some(Duration::from_secs(86400)))
                 .await?;
 
             self.log_security_event(
                 "TOKEN_FAMILY_REVOKED",
-                &family,
+                family,
                 correlation_id,
             ).await;
+            Self::record_revocation("user", family);
         }
 
         info!(user_id = %user_id, count = %count, "Revoked all user token families");
-        Ok(count)
+        Ok(families)
+    }
+
+    /// Record a family revocation by cause, with the family's age.
+    fn record_revocation(cause: &str, family: &TokenFamily) {
+        let age = chrono::Utc::now() - family.created_at;
+        metrics::record_family_revocation(cause, age.num_milliseconds() as f64 / 1000.0);
     }
 
     /// Log a security event to the centralized logging service.
@@ -213,6 +313,7 @@ impl RefreshTokenRotator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::CacheStorage;
     use rust_common::{CacheClientConfig, LoggingClientConfig};
 
     async fn create_test_rotator() -> RefreshTokenRotator {
@@ -258,6 +359,37 @@ mod tests {
         assert_eq!(family2.rotation_count, 1);
     }
 
+    #[tokio::test]
+    async fn test_rotation_keeps_issuer() {
+        let rotator = create_test_rotator().await;
+
+        let (token1, family1) = rotator
+            .create_issuer_token_family("user-5", "session-5", Some("https://auth.partner.com"), None)
+            .await
+            .unwrap();
+        let (_, family2) = rotator.rotate(&token1, None).await.unwrap();
+
+        assert_eq!(family1.issuer.as_deref(), Some("https://auth.partner.com"));
+        assert_eq!(family2.issuer, family1.issuer);
+
+        let (token3, family3) = rotator
+            .create_scoped_token_family("user-5", "session-6", None, &["partner-api".to_string()], None)
+            .await
+            .unwrap();
+        let (_, family4) = rotator.rotate(&token3, None).await.unwrap();
+        assert!(family3.issuer.is_none());
+        assert_eq!(family4.audience.as_deref(), Some("partner-api"));
+
+        let audiences = vec!["api".to_string(), "internal-api".to_string()];
+        let (token5, _) = rotator
+            .create_scoped_token_family("user-5", "session-7", None, &audiences, None)
+            .await
+            .unwrap();
+        let (_, family6) = rotator.rotate(&token5, None).await.unwrap();
+        assert!(family6.audience.is_none());
+        assert_eq!(family6.audiences, audiences);
+    }
+
     #[tokio::test]
     async fn test_replay_detection() {
         let rotator = create_test_rotator().await;
@@ -275,6 +407,42 @@ mod tests {
         assert!(matches!(result, Err(TokenError::RefreshReplay)));
     }
 
+    #[tokio::test]
+    async fn test_replay_feeds_anomaly_detector() {
+        use crate::refresh::anomaly::AnomalyConfig;
+
+        let detector = Arc::new(ReplayAnomalyDetector::new(AnomalyConfig {
+            min_events: 2,
+            replay_rate_threshold: 0.5,
+            ..AnomalyConfig::default()
+        }));
+        let rotator = create_test_rotator()
+            .await
+            .with_anomaly_detector(detector);
+
+        let (token1, _) = rotator
+            .create_token_family("user-anomaly", "session-anomaly", None)
+            .await
+            .unwrap();
+        rotator
+            .rotate_for_client(&token1, Some("client-anomaly"), None)
+            .await
+            .unwrap();
+        let result = rotator
+            .rotate_for_client(&token1, Some("client-anomaly"), None)
+            .await;
+        assert!(matches!(result, Err(TokenError::RefreshReplay)));
+
+        let replays = metrics::REFRESH_REPLAYS
+            .with_label_values(&["client-anomaly"])
+            .get();
+        let anomalies = metrics::REPLAY_ANOMALIES
+            .with_label_values(&["client-anomaly", "disabled"])
+            .get();
+        assert_eq!(replays, 1.0);
+        assert_eq!(anomalies, 1.0);
+    }
+
     #[tokio::test]
     async fn test_revoke_family() {
         let rotator = create_test_rotator().await;
@@ -289,4 +457,29 @@ mod tests {
         let result = rotator.rotate(&token, None).await;
         assert!(matches!(result, Err(TokenError::FamilyRevoked)));
     }
+
+    #[tokio::test]
+    async fn test_revoke_user_token_families_returns_sessions() {
+        let rotator = create_test_rotator().await;
+
+        let (token, _) = rotator
+            .create_token_family("user-6", "session-6a", None)
+            .await
+            .unwrap();
+        rotator
+            .create_token_family("user-6", "session-6b", None)
+            .await
+            .unwrap();
+
+        let families = rotator
+            .revoke_user_token_families("user-6", None)
+            .await
+            .unwrap();
+
+        let mut sessions: Vec<_> = families.iter().map(|f| f.session_id.as_str()).collect();
+        sessions.sort_unstable();
+        assert_eq!(sessions, ["session-6a", "session-6b"]);
+        assert!(families.iter().all(|f| f.revoked));
+        assert!(matches!(rotator.rotate(&token, None).await, Err(TokenError::FamilyRevoked)));
+    }
 }
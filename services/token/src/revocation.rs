@@ -0,0 +1,209 @@
+//! Revoked-JTI filter.
+//!
+//! Consumers that validate access tokens locally want to skip the
+//! revocation list lookup for tokens that were never revoked. Every replica
+//! journals the access token JTIs it revokes to the cache
+//! (`revocation_journal:{replica}`, listed under `revocation_journals`), and
+//! every `REVOCATION_FILTER_INTERVAL` rebuilds a [`RevocationFilter`] over
+//! the unexpired JTIs of all journals. `GetRevocationFilter` serves the
+//! latest build; JTIs revoked by this replica are added to it at once, those
+//! revoked by other replicas at the next rebuild.
+
+use crate::config::Config;
+use crate::error::TokenError;
+use chrono::Utc;
+use rust_common::{CacheClient, RevocationFilter};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, warn};
+
+/// Cache key listing the replicas with a journal, and when each last wrote it.
+const JOURNALS_KEY: &str = "revocation_journals";
+
+/// Revoked JTIs mapped to when their tokens expire (Unix seconds).
+type Journal = HashMap<String, i64>;
+
+/// Filter built from the journals, with its build time (Unix seconds).
+#[derive(Debug, Clone)]
+pub struct BuiltFilter {
+    /// Filter over the revoked JTIs
+    pub filter: RevocationFilter,
+    /// When the filter was built
+    pub built_at: i64,
+}
+
+/// Journal of this replica's revocations and the filter built from all
+/// replicas' journals.
+pub struct RevocationJournal {
+    cache: Arc<CacheClient>,
+    replica_id: String,
+    /// Lifetime of access tokens; a JTI is dropped once its token expired
+    retention: Duration,
+    false_positive_rate: f64,
+    entries: Mutex<Journal>,
+    filter: RwLock<Option<Arc<BuiltFilter>>>,
+}
+
+impl RevocationJournal {
+    /// Creates the journal of this replica, or `None` when the filter is
+    /// disabled.
+    pub fn from_config(config: &Config, cache: Arc<CacheClient>) -> Option<Self> {
+        config.revocation_filter_enabled.then(|| {
+            Self::new(cache, config.access_token_ttl, config.revocation_filter_fp_rate)
+        })
+    }
+
+    fn new(cache: Arc<CacheClient>, retention: Duration, false_positive_rate: f64) -> Self {
+        Self {
+            cache,
+            replica_id: uuid::Uuid::new_v4().to_string(),
+            retention,
+            false_positive_rate,
+            entries: Mutex::new(HashMap::new()),
+            filter: RwLock::new(None),
+        }
+    }
+
+    /// Records a revoked access token JTI for `ttl`.
+    ///
+    /// The journal is written before returning, so the revocation reaches
+    /// other replicas' filters even if this one stops.
+    pub async fn record(&self, jti: &str, ttl: Duration) -> Result<(), TokenError> {
+        let expires_at = Utc::now().timestamp() + i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX);
+        self.entries.lock().await.insert(jti.to_string(), expires_at);
+        self.persist().await?;
+
+        if let Some(built) = self.filter.write().await.as_mut() {
+            Arc::make_mut(built).filter.insert(jti);
+        }
+        Ok(())
+    }
+
+    /// Latest filter, `None` until the first build.
+    pub async fn filter(&self) -> Option<Arc<BuiltFilter>> {
+        self.filter.read().await.clone()
+    }
+
+    /// Rebuilds the filter from all replicas' journals.
+    ///
+    /// Returns the number of revoked JTIs in the new filter.
+    pub async fn rebuild(&self) -> Result<usize, TokenError> {
+        self.persist().await?;
+        let now = Utc::now().timestamp();
+
+        let mut replicas: Journal = self.read(JOURNALS_KEY).await?;
+        let stale_after = i64::try_from(self.retention.as_secs()).unwrap_or(i64::MAX);
+        replicas.retain(|_, seen| now - *seen <= stale_after);
+        replicas.insert(self.replica_id.clone(), now);
+        self.write(JOURNALS_KEY, &replicas).await?;
+
+        let mut revoked = HashSet::new();
+        for replica in replicas.keys() {
+            let journal: Journal = self.read(&journal_key(replica)).await?;
+            revoked.extend(journal.into_iter().filter(|(_, exp)| *exp > now).map(|(jti, _)| jti));
+        }
+        let mut filter = RevocationFilter::with_capacity(revoked.len(), self.false_positive_rate);
+        for jti in &revoked {
+            filter.insert(jti);
+        }
+        // Keep JTIs this replica journaled while the journals were read
+        for jti in self.entries.lock().await.keys() {
+            if !revoked.contains(jti) {
+                filter.insert(jti);
+            }
+        }
+
+        let entries = filter.len();
+        *self.filter.write().await = Some(Arc::new(BuiltFilter {
+            filter,
+            built_at: now,
+        }));
+        debug!(entries, replicas = replicas.len(), "Revocation filter rebuilt");
+        Ok(entries)
+    }
+
+    /// Rebuilds the filter every `interval`, forever.
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.rebuild().await {
+                warn!(error = %e, "Revocation filter rebuild failed, serving the previous filter");
+            }
+        }
+    }
+
+    /// Writes this replica's unexpired journal entries to the cache.
+    async fn persist(&self) -> Result<(), TokenError> {
+        let now = Utc::now().timestamp();
+        let mut entries = self.entries.lock().await;
+        entries.retain(|_, exp| *exp > now);
+        self.write(&journal_key(&self.replica_id), &entries).await
+    }
+
+    async fn read(&self, key: &str) -> Result<Journal, TokenError> {
+        match self.cache.get(key).await {
+            Ok(Some(data)) => serde_json::from_slice(&data)
+                .map_err(|e| TokenError::internal(format!("Deserialization failed: {}", e))),
+            Ok(None) => Ok(HashMap::new()),
+            Err(e) => Err(TokenError::cache(e.to_string())),
+        }
+    }
+
+    async fn write(&self, key: &str, journal: &Journal) -> Result<(), TokenError> {
+        let data = serde_json::to_vec(journal)
+            .map_err(|e| TokenError::internal(format!("Serialization failed: {}", e)))?;
+        self.cache
+            .set(key, &data, Some(self.retention))
+            .await
+            .map_err(|e| TokenError::cache(e.to_string()))
+    }
+}
+
+fn journal_key(replica_id: &str) -> String {
+    format!("revocation_journal:{}", replica_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_common::CacheClientConfig;
+
+    fn journal(cache: Arc<CacheClient>) -> RevocationJournal {
+        RevocationJournal::new(cache, Duration::from_secs(900), 0.001)
+    }
+
+    #[tokio::test]
+    async fn test_filter_covers_every_replica() {
+        let cache = Arc::new(CacheClient::new(CacheClientConfig::default()).await.unwrap());
+        let first = journal(cache.clone());
+        let second = journal(cache);
+        assert!(first.filter().await.is_none());
+
+        first.record("jti-1", Duration::from_secs(60)).await.unwrap();
+        second.record("jti-2", Duration::from_secs(60)).await.unwrap();
+        second.rebuild().await.unwrap();
+        assert_eq!(first.rebuild().await.unwrap(), 2);
+
+        let built = first.filter().await.unwrap();
+        assert!(built.filter.contains("jti-1"));
+        assert!(built.filter.contains("jti-2"));
+        assert!(!built.filter.contains("jti-3"));
+
+        // Recorded revocations are served before the next rebuild
+        first.record("jti-3", Duration::from_secs(60)).await.unwrap();
+        assert!(first.filter().await.unwrap().filter.contains("jti-3"));
+    }
+
+    #[tokio::test]
+    async fn test_expired_revocations_are_dropped() {
+        let cache = Arc::new(CacheClient::new(CacheClientConfig::default()).await.unwrap());
+        let journal = journal(cache);
+
+        journal.record("expired", Duration::ZERO).await.unwrap();
+        assert_eq!(journal.rebuild().await.unwrap(), 0);
+        assert!(!journal.filter().await.unwrap().filter.contains("expired"));
+    }
+}
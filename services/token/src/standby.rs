@@ -0,0 +1,243 @@
+//! Warm standby read-only mode.
+//!
+//! While family storage cannot take writes, e.g. during a storage failover,
+//! the service stops issuing and rotating tokens but keeps serving JWKS and
+//! revocation lookups. Read-only mode is entered after consecutive storage
+//! write failures and left once a background write probe succeeds
+//! repeatedly. A standby deployment can also be pinned read-only.
+
+use crate::config::Config;
+use crate::error::TokenError;
+use crate::metrics;
+use crate::storage::FamilyStore;
+use rust_common::PlatformError;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Read-only mode thresholds.
+#[derive(Debug, Clone)]
+pub struct StandbyConfig {
+    /// Stay read-only regardless of storage health
+    pub forced: bool,
+    /// Consecutive storage write failures that switch to read-only
+    pub failure_threshold: u32,
+    /// Consecutive successful write probes that switch back
+    pub recovery_threshold: u32,
+    /// Interval between storage write probes
+    pub probe_interval: Duration,
+}
+
+impl Default for StandbyConfig {
+    fn default() -> Self {
+        Self {
+            forced: false,
+            failure_threshold: 3,
+            recovery_threshold: 3,
+            probe_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Whether the service accepts writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceMode {
+    /// Issuance and rotation are available
+    Active,
+    /// Only reads are served
+    ReadOnly,
+}
+
+/// Consecutive storage outcomes.
+struct Health {
+    mode: ServiceMode,
+    failures: u32,
+    successes: u32,
+}
+
+/// Switches the service between active and read-only mode on storage health.
+pub struct StandbyController {
+    config: StandbyConfig,
+    health: Mutex<Health>,
+}
+
+impl StandbyController {
+    /// Create a controller, starting read-only if forced.
+    pub fn new(config: StandbyConfig) -> Self {
+        let mode = if config.forced {
+            ServiceMode::ReadOnly
+        } else {
+            ServiceMode::Active
+        };
+        metrics::record_service_mode(mode == ServiceMode::ReadOnly);
+        Self {
+            config,
+            health: Mutex::new(Health {
+                mode,
+                failures: 0,
+                successes: 0,
+            }),
+        }
+    }
+
+    /// Build the controller from configuration.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(StandbyConfig {
+            forced: config.read_only,
+            failure_threshold: config.standby_failure_threshold,
+            recovery_threshold: config.standby_recovery_threshold,
+            probe_interval: config.standby_probe_interval,
+        })
+    }
+
+    /// Current mode.
+    pub fn mode(&self) -> ServiceMode {
+        self.health.lock().unwrap_or_else(|e| e.into_inner()).mode
+    }
+
+    /// Reject writes while read-only.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::ReadOnly`] in read-only mode.
+    pub fn ensure_writable(&self) -> Result<(), TokenError> {
+        match self.mode() {
+            ServiceMode::Active => Ok(()),
+            ServiceMode::ReadOnly => Err(TokenError::ReadOnly),
+        }
+    }
+
+    /// Feed the outcome of a storage write into the health signal.
+    ///
+    /// Errors unrelated to storage availability are ignored.
+    pub fn observe<T>(&self, result: &Result<T, TokenError>) {
+        match result {
+            Ok(_) => self.record(true),
+            Err(e) if is_storage_failure(e) => self.record(false),
+            Err(_) => {}
+        }
+    }
+
+    /// Returns a task probing storage writes every probe interval.
+    ///
+    /// Probes run in both modes, so an idle service still notices a failover
+    /// and a read-only one notices recovery. A forced standby never probes.
+    pub async fn watch(self: Arc<Self>, storage: Arc<dyn FamilyStore>) {
+        if self.config.forced {
+            return;
+        }
+        let mut interval = tokio::time::interval(self.config.probe_interval);
+        loop {
+            interval.tick().await;
+            let result = storage.check_writable().await;
+            if let Err(e) = &result {
+                warn!(error = %e, "Storage write probe failed");
+            }
+            self.observe(&result);
+        }
+    }
+
+    /// Count a storage outcome and switch modes when a threshold is reached.
+    fn record(&self, success: bool) {
+        if self.config.forced {
+            return;
+        }
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        if success {
+            health.failures = 0;
+            health.successes += 1;
+        } else {
+            health.successes = 0;
+            health.failures += 1;
+        }
+
+        let next = match health.mode {
+            ServiceMode::Active if health.failures >= self.config.failure_threshold => {
+                warn!(
+                    failures = health.failures,
+                    "Storage unavailable for writes, switching to read-only mode"
+                );
+                ServiceMode::ReadOnly
+            }
+            ServiceMode::ReadOnly if health.successes >= self.config.recovery_threshold => {
+                info!("Storage accepts writes again, leaving read-only mode");
+                ServiceMode::Active
+            }
+            mode => mode,
+        };
+        if next != health.mode {
+            health.mode = next;
+            metrics::record_service_mode(next == ServiceMode::ReadOnly);
+        }
+    }
+}
+
+/// Whether an error means storage is unavailable rather than the request
+/// being invalid.
+#[allow(deprecated)]
+fn is_storage_failure(error: &TokenError) -> bool {
+    match error {
+        TokenError::Cache(_) | TokenError::RedisError(_) => true,
+        TokenError::Platform(e) => {
+            matches!(e, PlatformError::CircuitOpen { .. }) || e.is_retryable()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller(forced: bool) -> StandbyController {
+        StandbyController::new(StandbyConfig {
+            forced,
+            failure_threshold: 2,
+            recovery_threshold: 2,
+            probe_interval: Duration::from_millis(10),
+        })
+    }
+
+    fn storage_down() -> Result<(), TokenError> {
+        Err(TokenError::cache("connection refused"))
+    }
+
+    #[test]
+    fn test_switches_to_read_only_and_back() {
+        let standby = controller(false);
+        assert!(standby.ensure_writable().is_ok());
+
+        standby.observe(&storage_down());
+        standby.observe(&Ok(()));
+        standby.observe(&storage_down());
+        assert_eq!(standby.mode(), ServiceMode::Active);
+
+        standby.observe(&storage_down());
+        assert_eq!(standby.mode(), ServiceMode::ReadOnly);
+        assert!(matches!(standby.ensure_writable(), Err(TokenError::ReadOnly)));
+
+        standby.observe(&Ok(()));
+        assert_eq!(standby.mode(), ServiceMode::ReadOnly);
+        standby.observe(&Ok(()));
+        assert_eq!(standby.mode(), ServiceMode::Active);
+    }
+
+    #[test]
+    fn test_request_errors_are_not_storage_signals() {
+        let standby = controller(false);
+        for _ in 0..5 {
+            standby.observe::<()>(&Err(TokenError::RefreshInvalid));
+            standby.observe::<()>(&Err(TokenError::RefreshReplay));
+        }
+        assert_eq!(standby.mode(), ServiceMode::Active);
+    }
+
+    #[test]
+    fn test_forced_standby_stays_read_only() {
+        let standby = controller(true);
+        for _ in 0..5 {
+            standby.observe(&Ok(()));
+        }
+        assert_eq!(standby.mode(), ServiceMode::ReadOnly);
+    }
+}
@@ -0,0 +1,372 @@
+//! Startup dependency initialization.
+//!
+//! During cluster bootstrap the cache, logging service or legacy Redis may
+//! come up after the token service. [`Startup::init`] brings each dependency
+//! up in order, retrying transient failures with exponential backoff until
+//! the dependency's [`RetryBudget`] is spent and logging every attempt.
+//! Configuration errors are never retried. Whatever the outcome, the
+//! [`StartupReport`] records which dependencies are ready, which are
+//! misconfigured and which were unavailable.
+
+use crate::config::Config;
+use crate::error::TokenError;
+use rust_common::{RetryConfig, RetryPolicy};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// How long a dependency may keep failing transiently, and how often it is
+/// retried meanwhile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryBudget {
+    /// Time after the first attempt beyond which no retry starts
+    pub max_elapsed: Duration,
+    /// Wait after the first failure, doubling per attempt
+    pub initial_backoff: Duration,
+    /// Longest wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        Self {
+            max_elapsed: Duration::from_secs(120),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryBudget {
+    fn policy(&self) -> RetryPolicy {
+        RetryPolicy::new(
+            RetryConfig::default()
+                .with_max_retries(u32::MAX)
+                .with_initial_delay(self.initial_backoff)
+                .with_max_delay(self.max_backoff),
+        )
+    }
+}
+
+/// Outcome of initializing one dependency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyStatus {
+    /// Initialized
+    Ready,
+    /// Rejected by configuration; retrying cannot help
+    Misconfigured(String),
+    /// Failed permanently for another reason
+    Failed(String),
+    /// Still failing transiently when its retry budget ran out
+    Unavailable(String),
+}
+
+impl DependencyStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ready => "ready",
+            Self::Misconfigured(_) => "misconfigured",
+            Self::Failed(_) => "failed",
+            Self::Unavailable(_) => "unavailable",
+        }
+    }
+
+    fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Ready => None,
+            Self::Misconfigured(reason) | Self::Failed(reason) | Self::Unavailable(reason) => {
+                Some(reason)
+            }
+        }
+    }
+}
+
+/// Initialization record of one dependency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyOutcome {
+    /// Dependency name, e.g. `cache`
+    pub name: String,
+    /// Attempts made
+    pub attempts: u32,
+    /// Time from the first attempt to the outcome
+    pub elapsed: Duration,
+    /// How initialization ended
+    pub status: DependencyStatus,
+}
+
+/// Outcomes of the dependencies initialized so far, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StartupReport {
+    /// One entry per dependency
+    pub dependencies: Vec<DependencyOutcome>,
+}
+
+impl StartupReport {
+    /// The dependency that stopped startup, if any.
+    #[must_use]
+    pub fn failure(&self) -> Option<&DependencyOutcome> {
+        self.dependencies
+            .iter()
+            .find(|outcome| outcome.status != DependencyStatus::Ready)
+    }
+
+    /// Whether startup stopped on a configuration error.
+    #[must_use]
+    pub fn is_config_error(&self) -> bool {
+        matches!(
+            self.failure().map(|outcome| &outcome.status),
+            Some(DependencyStatus::Misconfigured(_))
+        )
+    }
+
+    /// Log one structured line per dependency.
+    fn log(&self) {
+        for outcome in &self.dependencies {
+            let elapsed_ms = outcome.elapsed.as_millis() as u64;
+            match outcome.status.reason() {
+                None => info!(
+                    dependency = %outcome.name,
+                    status = outcome.status.as_str(),
+                    attempts = outcome.attempts,
+                    elapsed_ms,
+                    "Startup dependency report"
+                ),
+                Some(reason) => error!(
+                    dependency = %outcome.name,
+                    status = outcome.status.as_str(),
+                    attempts = outcome.attempts,
+                    elapsed_ms,
+                    reason,
+                    "Startup dependency report"
+                ),
+            }
+        }
+    }
+}
+
+/// Startup stopped because a dependency could not be initialized.
+#[derive(Debug)]
+pub struct StartupError {
+    /// Outcomes up to and including the failed dependency
+    pub report: StartupReport,
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(failure) = self.report.failure() else {
+            return write!(f, "startup failed");
+        };
+        let reason = failure.status.reason().unwrap_or_default();
+        match &failure.status {
+            DependencyStatus::Misconfigured(_) => {
+                write!(f, "configuration error in {}: {}", failure.name, reason)
+            }
+            DependencyStatus::Unavailable(_) => write!(
+                f,
+                "{} unavailable after {} attempts over {}s: {}",
+                failure.name,
+                failure.attempts,
+                failure.elapsed.as_secs(),
+                reason
+            ),
+            _ => write!(f, "{} failed: {}", failure.name, reason),
+        }
+    }
+}
+
+impl std::error::Error for StartupError {}
+
+/// Brings dependencies up in order under their retry budgets.
+#[derive(Debug, Default)]
+pub struct Startup {
+    budget: RetryBudget,
+    budgets: HashMap<String, Duration>,
+    report: StartupReport,
+}
+
+impl Startup {
+    /// Create a startup sequence retrying every dependency within `budget`.
+    #[must_use]
+    pub fn new(budget: RetryBudget) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    /// Create a startup sequence from `STARTUP_RETRY_*` configuration.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        let startup = Self::new(RetryBudget {
+            max_elapsed: config.startup_retry_budget,
+            max_backoff: config.startup_retry_max_backoff,
+            ..RetryBudget::default()
+        });
+        config
+            .startup_retry_budgets
+            .iter()
+            .fold(startup, |startup, (name, budget)| {
+                startup.with_budget(name.clone(), *budget)
+            })
+    }
+
+    /// Let `dependency` keep failing transiently for `max_elapsed` instead.
+    #[must_use]
+    pub fn with_budget(mut self, dependency: impl Into<String>, max_elapsed: Duration) -> Self {
+        self.budgets.insert(dependency.into(), max_elapsed);
+        self
+    }
+
+    /// Initialize `name` with `init`, retrying transient failures.
+    ///
+    /// # Errors
+    ///
+    /// Returns the report up to `name` if it is misconfigured, fails
+    /// permanently or is still unavailable when its budget runs out.
+    pub async fn init<T, F, Fut>(&mut self, name: &str, mut init: F) -> Result<T, StartupError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, TokenError>>,
+    {
+        let budget = RetryBudget {
+            max_elapsed: self.budgets.get(name).copied().unwrap_or(self.budget.max_elapsed),
+            ..self.budget
+        };
+        let policy = budget.policy();
+        let started = Instant::now();
+        let mut attempts = 0;
+
+        let status = loop {
+            attempts += 1;
+            let err = match init().await {
+                Ok(value) => {
+                    info!(dependency = name, attempts, "Startup dependency ready");
+                    self.record(name, attempts, started, DependencyStatus::Ready);
+                    return Ok(value);
+                }
+                Err(err) => err,
+            };
+            if let TokenError::Config(reason) = err {
+                break DependencyStatus::Misconfigured(reason);
+            }
+            if !err.is_retryable() {
+                break DependencyStatus::Failed(err.to_string());
+            }
+
+            let delay = policy.delay_for_attempt(attempts - 1);
+            let elapsed = started.elapsed();
+            if elapsed + delay > budget.max_elapsed {
+                break DependencyStatus::Unavailable(err.to_string());
+            }
+            warn!(
+                dependency = name,
+                attempts,
+                elapsed_ms = elapsed.as_millis() as u64,
+                retry_in_ms = delay.as_millis() as u64,
+                budget_secs = budget.max_elapsed.as_secs(),
+                error = %err,
+                "Startup dependency not ready, retrying"
+            );
+            tokio::time::sleep(delay).await;
+        };
+
+        self.record(name, attempts, started, status);
+        self.report.log();
+        Err(StartupError {
+            report: std::mem::take(&mut self.report),
+        })
+    }
+
+    /// Log and return the report once every dependency is ready.
+    pub fn finish(self) -> StartupReport {
+        self.report.log();
+        self.report
+    }
+
+    fn record(&mut self, name: &str, attempts: u32, started: Instant, status: DependencyStatus) {
+        self.report.dependencies.push(DependencyOutcome {
+            name: name.to_string(),
+            attempts,
+            elapsed: started.elapsed(),
+            status,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn budget(max_elapsed: Duration) -> RetryBudget {
+        RetryBudget {
+            max_elapsed,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures_until_ready() {
+        let mut startup = Startup::new(budget(Duration::from_secs(5)));
+        let calls = AtomicU32::new(0);
+        let value = startup
+            .init("cache", || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(TokenError::cache("connection refused")),
+                    _ => Ok(42),
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(value, 42);
+        let report = startup.finish();
+        assert_eq!(report.dependencies[0].attempts, 3);
+        assert_eq!(report.dependencies[0].status, DependencyStatus::Ready);
+        assert!(report.failure().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_config_errors_fail_without_retry() {
+        let mut startup = Startup::new(budget(Duration::from_secs(5)));
+        let calls = AtomicU32::new(0);
+        let err = startup
+            .init("storage", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(TokenError::config("invalid LEGACY_REDIS_URL"))
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(err.report.is_config_error());
+        assert_eq!(
+            err.to_string(),
+            "configuration error in storage: invalid LEGACY_REDIS_URL"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_outage_reported_once_budget_is_spent() {
+        let mut startup = Startup::new(budget(Duration::from_secs(5)))
+            .with_budget("storage", Duration::from_millis(20));
+        startup.init("cache", || async { Ok(()) }).await.unwrap();
+        let err = startup
+            .init("storage", || async {
+                Err::<(), _>(TokenError::cache("connection refused"))
+            })
+            .await
+            .unwrap_err();
+
+        let failure = err.report.failure().unwrap();
+        assert_eq!(failure.name, "storage");
+        assert!(failure.attempts > 1);
+        assert!(matches!(failure.status, DependencyStatus::Unavailable(_)));
+        assert!(!err.report.is_config_error());
+        assert_eq!(err.report.dependencies.len(), 2);
+        assert!(err.to_string().starts_with("storage unavailable after"));
+    }
+}
@@ -101,21 +101,24 @@ impl CacheStorage {
         &self,
         user_id: &str,
     ) -> Result<Vec<TokenFamily>, TokenError> {
+        let family_ids = self.user_family_ids(user_id).await?;
+
+        let mut families = Vec::with_capacity(family_ids.len());
+        for id in family_ids {
+            if let Some(family) = self.get_token_family(&id).await? {
+                families.push(family);
+            }
+        }
+        Ok(families)
+    }
+
+    /// Get the family IDs indexed for a user.
+    pub(crate) async fn user_family_ids(&self, user_id: &str) -> Result<Vec<String>, TokenError> {
         let key = format!("user_families:{}", user_id);
 
         match self.cache.get(&key).await {
-            Ok(Some(data)) => {
-                let family_ids: Vec<String> = serde_json::from_slice(&data)
-                    .map_err(|e| TokenError::internal(format!("Deserialization failed: {}", e)))?;
-
-                let mut families = Vec::with_capacity(family_ids.len());
-                for id in family_ids {
-                    if let Some(family) = self.get_token_family(&id).await? {
-                        families.push(family);
-                    }
-                }
-                Ok(families)
-            }
+            Ok(Some(data)) => serde_json::from_slice(&data)
+                .map_err(|e| TokenError::internal(format!("Deserialization failed: {}", e))),
             Ok(None) => Ok(Vec::new()),
             Err(e) => Err(TokenError::cache(e.to_string())),
         }
@@ -180,6 +183,12 @@ impl CacheStorage {
             .map_err(|e| TokenError::cache(e.to_string()))
     }
 
+    /// Default TTL applied when callers don't pass one.
+    #[must_use]
+    pub fn default_ttl(&self) -> Duration {
+        self.default_ttl
+    }
+
     /// Get the underlying cache client for advanced operations.
     #[must_use]
     pub fn cache_client(&self) -> &CacheClient {
@@ -187,7 +196,7 @@ impl CacheStorage {
     }
 
     /// Add family ID to user's family list.
-    async fn add_to_user_families(
+    pub(crate) async fn add_to_user_families(
         &self,
         user_id: &str,
         family_id: &str,
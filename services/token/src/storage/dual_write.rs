@@ -0,0 +1,195 @@
+//! Dual-write storage for the RedisStorage → CacheStorage transition.
+//!
+//! Writes go to the primary store (must succeed) and to the legacy store
+//! (best effort). Reads prefer the primary store and fall back to the legacy
+//! store on a miss, backfilling the primary so data migrates lazily.
+
+use crate::error::TokenError;
+use crate::refresh::family::TokenFamily;
+use super::store::FamilyStore;
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Storage that keeps the legacy backend in sync during migration.
+pub struct DualWriteStorage {
+    primary: Arc<dyn FamilyStore>,
+    legacy: Arc<dyn FamilyStore>,
+    backfill_ttl: Duration,
+}
+
+impl DualWriteStorage {
+    /// Create dual-write storage over a primary and a legacy backend.
+    pub fn new(
+        primary: Arc<dyn FamilyStore>,
+        legacy: Arc<dyn FamilyStore>,
+        backfill_ttl: Duration,
+    ) -> Self {
+        Self {
+            primary,
+            legacy,
+            backfill_ttl,
+        }
+    }
+
+    /// Copy a family found only in the legacy store into the primary store.
+    async fn backfill(&self, family: &TokenFamily) {
+        if let Err(e) = self
+            .primary
+            .store_token_family(family, Some(self.backfill_ttl))
+            .await
+        {
+            warn!(family_id = %family.family_id, error = %e, "Backfill to primary storage failed");
+        }
+    }
+}
+
+#[async_trait]
+impl FamilyStore for DualWriteStorage {
+    async fn store_token_family(
+        &self,
+        family: &TokenFamily,
+        ttl: Option<Duration>,
+    ) -> Result<(), TokenError> {
+        self.primary.store_token_family(family, ttl).await?;
+
+        if let Err(e) = self.legacy.store_token_family(family, ttl).await {
+            warn!(family_id = %family.family_id, error = %e, "Legacy dual-write failed");
+        }
+        Ok(())
+    }
+
+    async fn get_token_family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError> {
+        if let Some(family) = self.primary.get_token_family(family_id).await? {
+            return Ok(Some(family));
+        }
+
+        let legacy = self.legacy.get_token_family(family_id).await.unwrap_or_else(|e| {
+            warn!(family_id = %family_id, error = %e, "Legacy read failed");
+            None
+        });
+        if let Some(ref family) = legacy {
+            self.backfill(family).await;
+        }
+        Ok(legacy)
+    }
+
+    async fn find_family_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<TokenFamily>, TokenError> {
+        if let Some(family) = self.primary.find_family_by_token_hash(token_hash).await? {
+            return Ok(Some(family));
+        }
+
+        let legacy = self
+            .legacy
+            .find_family_by_token_hash(token_hash)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Legacy read failed");
+                None
+            });
+        if let Some(ref family) = legacy {
+            self.backfill(family).await;
+        }
+        Ok(legacy)
+    }
+
+    async fn get_user_token_families(&self, user_id: &str) -> Result<Vec<TokenFamily>, TokenError> {
+        let mut families = self.primary.get_user_token_families(user_id).await?;
+        let known: HashSet<String> = families.iter().map(|f| f.family_id.clone()).collect();
+
+        let legacy = self
+            .legacy
+            .get_user_token_families(user_id)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(user_id = %user_id, error = %e, "Legacy read failed");
+                Vec::new()
+            });
+        for family in legacy {
+            if !known.contains(&family.family_id) {
+                self.backfill(&family).await;
+                families.push(family);
+            }
+        }
+        Ok(families)
+    }
+
+    async fn add_to_revocation_list(&self, jti: &str, ttl: Duration) -> Result<(), TokenError> {
+        self.primary.add_to_revocation_list(jti, ttl).await?;
+
+        if let Err(e) = self.legacy.add_to_revocation_list(jti, ttl).await {
+            warn!(jti = %jti, error = %e, "Legacy dual-write failed");
+        }
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, TokenError> {
+        if self.primary.is_token_revoked(jti).await? {
+            return Ok(true);
+        }
+
+        // Fail closed: a legacy read error must not make a revoked token valid again
+        self.legacy.is_token_revoked(jti).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::CacheStorage;
+    use rust_common::CacheClientConfig;
+
+    async fn stores(name: &str) -> (Arc<CacheStorage>, Arc<CacheStorage>, DualWriteStorage) {
+        let primary = Arc::new(
+            CacheStorage::new(CacheClientConfig::default().with_namespace(format!("{name}-primary")))
+                .await
+                .unwrap(),
+        );
+        let legacy = Arc::new(
+            CacheStorage::new(CacheClientConfig::default().with_namespace(format!("{name}-legacy")))
+                .await
+                .unwrap(),
+        );
+        let dual = DualWriteStorage::new(primary.clone(), legacy.clone(), Duration::from_secs(3600));
+        (primary, legacy, dual)
+    }
+
+    fn family(id: &str, user: &str) -> TokenFamily {
+        TokenFamily::new(id.to_string(), user.to_string(), "session".to_string(), format!("hash-{id}"))
+    }
+
+    #[tokio::test]
+    async fn test_writes_reach_both_stores() {
+        let (primary, legacy, dual) = stores("dual-write").await;
+
+        FamilyStore::store_token_family(&dual, &family("dw-1", "user-dw"), None).await.unwrap();
+
+        assert!(primary.get_token_family("dw-1").await.unwrap().is_some());
+        assert!(legacy.get_token_family("dw-1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_hit_is_backfilled() {
+        let (primary, legacy, dual) = stores("dual-backfill").await;
+        legacy.store_token_family(&family("dw-2", "user-bf"), None).await.unwrap();
+
+        let found = FamilyStore::find_family_by_token_hash(&dual, "hash-dw-2").await.unwrap();
+
+        assert_eq!(found.unwrap().family_id, "dw-2");
+        assert!(primary.get_token_family("dw-2").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_revocation_visible_from_either_store() {
+        let (_primary, legacy, dual) = stores("dual-revoke").await;
+        legacy.add_to_revocation_list("jti-legacy", Duration::from_secs(60)).await.unwrap();
+
+        assert!(FamilyStore::is_token_revoked(&dual, "jti-legacy").await.unwrap());
+        assert!(!FamilyStore::is_token_revoked(&dual, "jti-unknown").await.unwrap());
+    }
+}
@@ -85,6 +85,12 @@ impl EncryptedCacheStorage {
             .await
             .map_err(|e| TokenError::cache(e.to_string()))?;
 
+        // Index by user for revocation queries
+        let index_ttl = ttl.unwrap_or_else(|| self.cache.default_ttl());
+        self.cache
+            .add_to_user_families(&family.user_id, &family.family_id, index_ttl)
+            .await?;
+
         Ok(())
     }
 
@@ -134,6 +140,22 @@ impl EncryptedCacheStorage {
         }
     }
 
+    /// Get all token families for a user with optional decryption.
+    pub async fn get_user_token_families(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<TokenFamily>, TokenError> {
+        let family_ids = self.cache.user_family_ids(user_id).await?;
+
+        let mut families = Vec::with_capacity(family_ids.len());
+        for id in family_ids {
+            if let Some(family) = self.get_token_family(&id).await? {
+                families.push(family);
+            }
+        }
+        Ok(families)
+    }
+
     /// Delegate to underlying cache for non-encrypted operations.
     pub async fn add_to_revocation_list(&self, jti: &str, ttl: Duration) -> Result<(), TokenError> {
         self.cache.add_to_revocation_list(jti, ttl).await
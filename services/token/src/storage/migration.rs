@@ -0,0 +1,310 @@
+//! One-shot migration from the legacy Redis layout.
+//!
+//! Copies token families and revocations from `RedisStorage` keys
+//! (`token_family:*`, `revoked:*`) into any [`FamilyStore`], preserving the
+//! remaining TTL, and optionally reads every record back to verify it.
+
+use crate::error::TokenError;
+use crate::refresh::family::TokenFamily;
+use super::store::FamilyStore;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Enumerable view of the legacy keyspace.
+#[async_trait]
+pub trait LegacySource: Send + Sync {
+    /// All token family IDs.
+    async fn family_ids(&self) -> Result<Vec<String>, TokenError>;
+
+    /// Load a token family by ID.
+    async fn family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError>;
+
+    /// Remaining TTL of a token family.
+    async fn family_ttl(&self, family_id: &str) -> Result<Option<Duration>, TokenError>;
+
+    /// All revoked JTIs.
+    async fn revoked_jtis(&self) -> Result<Vec<String>, TokenError>;
+
+    /// Remaining TTL of a revocation entry.
+    async fn revocation_ttl(&self, jti: &str) -> Result<Option<Duration>, TokenError>;
+}
+
+#[allow(deprecated)]
+#[async_trait]
+impl LegacySource for super::redis::RedisStorage {
+    async fn family_ids(&self) -> Result<Vec<String>, TokenError> {
+        self.scan_family_ids().await
+    }
+
+    async fn family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError> {
+        self.get_token_family(family_id).await
+    }
+
+    async fn family_ttl(&self, family_id: &str) -> Result<Option<Duration>, TokenError> {
+        super::redis::RedisStorage::family_ttl(self, family_id).await
+    }
+
+    async fn revoked_jtis(&self) -> Result<Vec<String>, TokenError> {
+        self.scan_revoked_jtis().await
+    }
+
+    async fn revocation_ttl(&self, jti: &str) -> Result<Option<Duration>, TokenError> {
+        super::redis::RedisStorage::revocation_ttl(self, jti).await
+    }
+}
+
+/// Migration settings.
+#[derive(Debug, Clone)]
+pub struct MigrationOptions {
+    /// Read every record back from the target after writing it
+    pub verify: bool,
+    /// Scan and report without writing
+    pub dry_run: bool,
+    /// TTL used for legacy keys that have no expiry
+    pub default_ttl: Duration,
+}
+
+impl Default for MigrationOptions {
+    fn default() -> Self {
+        Self {
+            verify: true,
+            dry_run: false,
+            default_ttl: Duration::from_secs(604800),
+        }
+    }
+}
+
+/// Outcome of a migration run.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    /// Families found in the legacy store
+    pub families_scanned: usize,
+    /// Families written to the target
+    pub families_migrated: usize,
+    /// Families that expired between scan and copy
+    pub families_skipped: usize,
+    /// Revocations found in the legacy store
+    pub revocations_scanned: usize,
+    /// Revocations written to the target
+    pub revocations_migrated: usize,
+    /// Records that did not read back identically
+    pub verification_failures: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Whether every migrated record was verified.
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        self.verification_failures.is_empty()
+    }
+}
+
+/// Copies legacy Redis data into the platform cache storage.
+pub struct StorageMigrator {
+    source: Arc<dyn LegacySource>,
+    target: Arc<dyn FamilyStore>,
+    options: MigrationOptions,
+}
+
+impl StorageMigrator {
+    /// Create a migrator between a legacy source and a target store.
+    pub fn new(
+        source: Arc<dyn LegacySource>,
+        target: Arc<dyn FamilyStore>,
+        options: MigrationOptions,
+    ) -> Self {
+        Self {
+            source,
+            target,
+            options,
+        }
+    }
+
+    /// Run the migration.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first read or write error; verification mismatches are
+    /// collected in the report instead.
+    pub async fn run(&self) -> Result<MigrationReport, TokenError> {
+        let mut report = MigrationReport::default();
+        self.migrate_families(&mut report).await?;
+        self.migrate_revocations(&mut report).await?;
+
+        info!(
+            families = report.families_migrated,
+            revocations = report.revocations_migrated,
+            failures = report.verification_failures.len(),
+            dry_run = self.options.dry_run,
+            "Legacy storage migration finished"
+        );
+        Ok(report)
+    }
+
+    async fn migrate_families(&self, report: &mut MigrationReport) -> Result<(), TokenError> {
+        let ids = self.source.family_ids().await?;
+        report.families_scanned = ids.len();
+
+        for id in ids {
+            let Some(family) = self.source.family(&id).await? else {
+                report.families_skipped += 1;
+                continue;
+            };
+            if self.options.dry_run {
+                continue;
+            }
+
+            let ttl = self
+                .source
+                .family_ttl(&id)
+                .await?
+                .unwrap_or(self.options.default_ttl);
+            self.target.store_token_family(&family, Some(ttl)).await?;
+            report.families_migrated += 1;
+
+            if self.options.verify && !self.verify_family(&family).await? {
+                warn!(family_id = %id, "Migrated family failed verification");
+                report.verification_failures.push(format!("family:{}", id));
+            }
+        }
+        Ok(())
+    }
+
+    async fn migrate_revocations(&self, report: &mut MigrationReport) -> Result<(), TokenError> {
+        let jtis = self.source.revoked_jtis().await?;
+        report.revocations_scanned = jtis.len();
+
+        for jti in jtis {
+            if self.options.dry_run {
+                continue;
+            }
+
+            let ttl = self
+                .source
+                .revocation_ttl(&jti)
+                .await?
+                .unwrap_or(self.options.default_ttl);
+            self.target.add_to_revocation_list(&jti, ttl).await?;
+            report.revocations_migrated += 1;
+
+            if self.options.verify && !self.target.is_token_revoked(&jti).await? {
+                warn!(jti = %jti, "Migrated revocation failed verification");
+                report.verification_failures.push(format!("revoked:{}", jti));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the family reads back unchanged by ID and by token hash.
+    async fn verify_family(&self, expected: &TokenFamily) -> Result<bool, TokenError> {
+        let by_id = self.target.get_token_family(&expected.family_id).await?;
+        let by_hash = self
+            .target
+            .find_family_by_token_hash(&expected.current_token_hash)
+            .await?;
+
+        let matches = |found: Option<TokenFamily>| {
+            found.is_some_and(|f| {
+                serde_json::to_value(&f).ok() == serde_json::to_value(expected).ok()
+            })
+        };
+        Ok(matches(by_id) && matches(by_hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::CacheStorage;
+    use rust_common::CacheClientConfig;
+    use std::collections::HashMap;
+
+    /// In-memory stand-in for the legacy Redis keyspace.
+    #[derive(Default)]
+    struct MemorySource {
+        families: HashMap<String, TokenFamily>,
+        revoked: Vec<String>,
+    }
+
+    #[async_trait]
+    impl LegacySource for MemorySource {
+        async fn family_ids(&self) -> Result<Vec<String>, TokenError> {
+            Ok(self.families.keys().cloned().collect())
+        }
+
+        async fn family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError> {
+            Ok(self.families.get(family_id).cloned())
+        }
+
+        async fn family_ttl(&self, _family_id: &str) -> Result<Option<Duration>, TokenError> {
+            Ok(Some(Duration::from_secs(600)))
+        }
+
+        async fn revoked_jtis(&self) -> Result<Vec<String>, TokenError> {
+            Ok(self.revoked.clone())
+        }
+
+        async fn revocation_ttl(&self, _jti: &str) -> Result<Option<Duration>, TokenError> {
+            Ok(None)
+        }
+    }
+
+    fn source() -> MemorySource {
+        let mut source = MemorySource::default();
+        for i in 0..3 {
+            let family = TokenFamily::new(
+                format!("mig-family-{i}"),
+                "mig-user".to_string(),
+                format!("session-{i}"),
+                format!("mig-hash-{i}"),
+            );
+            source.families.insert(family.family_id.clone(), family);
+        }
+        source.revoked = vec!["mig-jti-1".to_string(), "mig-jti-2".to_string()];
+        source
+    }
+
+    async fn target(namespace: &str) -> Arc<CacheStorage> {
+        Arc::new(
+            CacheStorage::new(CacheClientConfig::default().with_namespace(namespace))
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_migrates_and_verifies() {
+        let target = target("migration-test").await;
+        let migrator =
+            StorageMigrator::new(Arc::new(source()), target.clone(), MigrationOptions::default());
+
+        let report = migrator.run().await.unwrap();
+
+        assert_eq!(report.families_scanned, 3);
+        assert_eq!(report.families_migrated, 3);
+        assert_eq!(report.revocations_migrated, 2);
+        assert!(report.is_verified());
+        assert!(target.find_family_by_token_hash("mig-hash-2").await.unwrap().is_some());
+        assert!(target.is_token_revoked("mig-jti-1").await.unwrap());
+        assert_eq!(target.get_user_token_families("mig-user").await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let target = target("migration-dry-run").await;
+        let options = MigrationOptions {
+            dry_run: true,
+            ..MigrationOptions::default()
+        };
+        let migrator = StorageMigrator::new(Arc::new(source()), target.clone(), options);
+
+        let report = migrator.run().await.unwrap();
+
+        assert_eq!(report.families_scanned, 3);
+        assert_eq!(report.families_migrated, 0);
+        assert_eq!(report.revocations_scanned, 2);
+        assert!(target.get_token_family("mig-family-0").await.unwrap().is_none());
+    }
+}
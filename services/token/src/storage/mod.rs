@@ -1,13 +1,19 @@
 pub mod cache;
+pub mod dual_write;
 pub mod encrypted_cache;
+pub mod migration;
+pub mod schema;
+pub mod store;
 
-// Legacy Redis module - deprecated, use CacheStorage
+// Legacy Redis module - deprecated, use CacheStorage.
+// Only reachable through `migration` and `DualWriteStorage` until the
+// legacy keyspace has been migrated.
 #[deprecated(since = "2.0.0", note = "Use CacheStorage with rust-common::CacheClient")]
 pub mod redis;
 
 pub use cache::CacheStorage;
+pub use dual_write::DualWriteStorage;
 pub use encrypted_cache::EncryptedCacheStorage;
-
-// Re-export for backward compatibility during migration
-#[allow(deprecated)]
-pub use redis::RedisStorage;
+pub use migration::{LegacySource, MigrationOptions, MigrationReport, StorageMigrator};
+pub use schema::{MigrationRegistry, SchemaMigration, SchemaMigrator, SchemaStore};
+pub use store::FamilyStore;
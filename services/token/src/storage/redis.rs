@@ -3,6 +3,7 @@ use crate::refresh::family::TokenFamily;
 use redis::aio::ConnectionManager;
 use redis::AsyncCommands;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 pub struct RedisStorage {
@@ -12,7 +13,7 @@ pub struct RedisStorage {
 impl RedisStorage {
     pub async fn new(redis_url: &str) -> Result<Self, TokenError> {
         let client = redis::Client::open(redis_url)
-            .map_err(|e| TokenError::RedisError(e.to_string()))?;
+            .map_err(|e| TokenError::config(format!("Invalid LEGACY_REDIS_URL: {}", e)))?;
         
         let conn = ConnectionManager::new(client)
             .await
@@ -119,4 +120,48 @@ impl RedisStorage {
 
         Ok(exists)
     }
+
+    /// List all token family IDs in the legacy keyspace (used by migration).
+    pub async fn scan_family_ids(&self) -> Result<Vec<String>, TokenError> {
+        self.scan_suffixes("token_family:").await
+    }
+
+    /// List all revoked JTIs in the legacy keyspace (used by migration).
+    pub async fn scan_revoked_jtis(&self) -> Result<Vec<String>, TokenError> {
+        self.scan_suffixes("revoked:").await
+    }
+
+    /// Remaining TTL of a token family, `None` if the key has no expiry or is gone.
+    pub async fn family_ttl(&self, family_id: &str) -> Result<Option<Duration>, TokenError> {
+        self.key_ttl(&format!("token_family:{}", family_id)).await
+    }
+
+    /// Remaining TTL of a revocation entry, `None` if the key has no expiry or is gone.
+    pub async fn revocation_ttl(&self, jti: &str) -> Result<Option<Duration>, TokenError> {
+        self.key_ttl(&format!("revoked:{}", jti)).await
+    }
+
+    async fn scan_suffixes(&self, prefix: &str) -> Result<Vec<String>, TokenError> {
+        let mut conn = self.conn.write().await;
+        let mut iter = conn.scan_match::<_, String>(format!("{}*", prefix))
+            .await
+            .map_err(|e| TokenError::RedisError(e.to_string()))?;
+
+        let mut ids = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            if let Some(id) = key.strip_prefix(prefix) {
+                ids.push(id.to_string());
+            }
+        }
+        Ok(ids)
+    }
+
+    async fn key_ttl(&self, key: &str) -> Result<Option<Duration>, TokenError> {
+        let mut conn = self.conn.write().await;
+        let ttl: i64 = conn.ttl(key)
+            .await
+            .map_err(|e| TokenError::RedisError(e.to_string()))?;
+
+        Ok(u64::try_from(ttl).ok().filter(|t| *t > 0).map(Duration::from_secs))
+    }
 }
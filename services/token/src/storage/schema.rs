@@ -0,0 +1,447 @@
+//! Versioned storage schema.
+//!
+//! The storage layout version lives under [`SCHEMA_VERSION_KEY`]. At startup
+//! the [`SchemaMigrator`] applies every registered migration newer than the
+//! stored version, in order, recording the version after each step so an
+//! interrupted run resumes where it stopped. A lease under
+//! [`MIGRATION_LOCK_KEY`] keeps replicas from migrating concurrently; the
+//! others wait for the holder to finish.
+
+use crate::config::Config;
+use crate::error::TokenError;
+use async_trait::async_trait;
+use rust_common::CacheClient;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Key holding the current schema version.
+pub const SCHEMA_VERSION_KEY: &str = "schema:version";
+
+/// Key holding the migration lease.
+pub const MIGRATION_LOCK_KEY: &str = "schema:migration_lock";
+
+/// Key-value access needed to version the schema.
+#[async_trait]
+pub trait SchemaStore: Send + Sync {
+    /// Read a value.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, TokenError>;
+
+    /// Write a value, expiring after `ttl` if given.
+    async fn set(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> Result<(), TokenError>;
+
+    /// Delete a value.
+    async fn delete(&self, key: &str) -> Result<(), TokenError>;
+}
+
+#[async_trait]
+impl SchemaStore for CacheClient {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, TokenError> {
+        CacheClient::get(self, key)
+            .await
+            .map_err(|e| TokenError::cache(e.to_string()))
+    }
+
+    async fn set(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> Result<(), TokenError> {
+        CacheClient::set(self, key, value, ttl)
+            .await
+            .map_err(|e| TokenError::cache(e.to_string()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), TokenError> {
+        CacheClient::delete(self, key)
+            .await
+            .map_err(|e| TokenError::cache(e.to_string()))
+    }
+}
+
+/// A step from the previous schema version to [`SchemaMigration::version`].
+#[async_trait]
+pub trait SchemaMigration: Send + Sync {
+    /// Version the storage is at once the migration has run.
+    fn version(&self) -> u32;
+
+    /// Short description for logs.
+    fn description(&self) -> &str;
+
+    /// Rewrite stored data into the new layout.
+    ///
+    /// Must be safe to re-run: a replica that loses its lease mid-migration
+    /// leaves the step to be applied again.
+    async fn up(&self, store: &dyn SchemaStore) -> Result<(), TokenError>;
+}
+
+/// Layout introduced with cache-backed family storage; nothing to rewrite.
+struct Baseline;
+
+#[async_trait]
+impl SchemaMigration for Baseline {
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn description(&self) -> &str {
+        "cache-backed token families and revocation list"
+    }
+
+    async fn up(&self, _store: &dyn SchemaStore) -> Result<(), TokenError> {
+        Ok(())
+    }
+}
+
+/// Ordered set of schema migrations.
+pub struct MigrationRegistry {
+    migrations: Vec<Arc<dyn SchemaMigration>>,
+}
+
+impl MigrationRegistry {
+    /// Registry without migrations.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Migrations shipped with this build.
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self::empty().with(Arc::new(Baseline))
+    }
+
+    /// Add a migration, keeping the registry ordered by version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a migration with the same version is already registered.
+    #[must_use]
+    pub fn with(mut self, migration: Arc<dyn SchemaMigration>) -> Self {
+        assert!(
+            self.migrations.iter().all(|m| m.version() != migration.version()),
+            "duplicate schema migration version {}",
+            migration.version()
+        );
+        self.migrations.push(migration);
+        self.migrations.sort_by_key(|m| m.version());
+        self
+    }
+
+    /// Version the registry migrates to.
+    #[must_use]
+    pub fn latest_version(&self) -> u32 {
+        self.migrations.last().map_or(0, |m| m.version())
+    }
+
+    fn pending(&self, current: u32) -> impl Iterator<Item = &Arc<dyn SchemaMigration>> {
+        self.migrations.iter().filter(move |m| m.version() > current)
+    }
+}
+
+/// Lease and wait settings.
+#[derive(Debug, Clone)]
+pub struct SchemaOptions {
+    /// Lease held while migrating; a crashed holder's lease expires after it
+    pub lock_ttl: Duration,
+    /// How long to wait for another replica's migration
+    pub lock_wait: Duration,
+    /// Interval between lease acquisition attempts
+    pub poll_interval: Duration,
+}
+
+impl Default for SchemaOptions {
+    fn default() -> Self {
+        Self {
+            lock_ttl: Duration::from_secs(300),
+            lock_wait: Duration::from_secs(600),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Outcome of a schema migration run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaReport {
+    /// Version found in storage
+    pub from_version: u32,
+    /// Version storage is at now
+    pub to_version: u32,
+    /// Versions applied by this replica
+    pub applied: Vec<u32>,
+}
+
+/// Brings the storage schema up to the registry's latest version.
+pub struct SchemaMigrator {
+    store: Arc<dyn SchemaStore>,
+    registry: MigrationRegistry,
+    options: SchemaOptions,
+    owner: String,
+}
+
+impl SchemaMigrator {
+    /// Create a migrator over a store.
+    pub fn new(
+        store: Arc<dyn SchemaStore>,
+        registry: MigrationRegistry,
+        options: SchemaOptions,
+    ) -> Self {
+        Self {
+            store,
+            registry,
+            options,
+            owner: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// Build the migrator with the builtin migrations from configuration.
+    pub fn from_config(config: &Config, store: Arc<dyn SchemaStore>) -> Self {
+        Self::new(
+            store,
+            MigrationRegistry::builtin(),
+            SchemaOptions {
+                lock_ttl: config.schema_lock_ttl,
+                lock_wait: config.schema_lock_wait,
+                ..SchemaOptions::default()
+            },
+        )
+    }
+
+    /// Version recorded in storage; 0 before the first migration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage is unreachable or the version is corrupt.
+    pub async fn current_version(&self) -> Result<u32, TokenError> {
+        match self.store.get(SCHEMA_VERSION_KEY).await? {
+            None => Ok(0),
+            Some(raw) => std::str::from_utf8(&raw)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .ok_or_else(|| TokenError::internal("Corrupt storage schema version")),
+        }
+    }
+
+    /// Check that this build understands the stored schema, without writing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage is at a newer version than this build
+    /// supports.
+    pub async fn check(&self) -> Result<u32, TokenError> {
+        let current = self.current_version().await?;
+        if current > self.registry.latest_version() {
+            return Err(TokenError::config(format!(
+                "Storage schema version {} is newer than supported version {}",
+                current,
+                self.registry.latest_version()
+            )));
+        }
+        Ok(current)
+    }
+
+    /// Apply pending migrations, waiting for another replica's run first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage is newer than this build, a migration
+    /// fails, or the lease could not be acquired within the wait time.
+    pub async fn run(&self) -> Result<SchemaReport, TokenError> {
+        let from_version = self.check().await?;
+        let latest = self.registry.latest_version();
+        let mut report = SchemaReport {
+            from_version,
+            to_version: from_version,
+            applied: Vec::new(),
+        };
+        if from_version == latest {
+            return Ok(report);
+        }
+
+        let deadline = Instant::now() + self.options.lock_wait;
+        while !self.try_lock().await? {
+            if Instant::now() >= deadline {
+                return Err(TokenError::internal(
+                    "Timed out waiting for the storage schema migration lock",
+                ));
+            }
+            tokio::time::sleep(self.options.poll_interval).await;
+            // The lease holder may have finished meanwhile
+            report.to_version = self.check().await?;
+            if report.to_version == latest {
+                return Ok(report);
+            }
+        }
+
+        let result = self.migrate(&mut report).await;
+        if let Err(e) = self.unlock().await {
+            warn!(error = %e, "Failed to release storage schema migration lock");
+        }
+        result.map(|()| report)
+    }
+
+    /// Apply pending migrations under the lease.
+    async fn migrate(&self, report: &mut SchemaReport) -> Result<(), TokenError> {
+        // Re-read under the lease: another replica may have migrated between
+        // the first check and acquiring the lease
+        report.to_version = self.check().await?;
+        for migration in self.registry.pending(report.to_version) {
+            info!(
+                version = migration.version(),
+                description = migration.description(),
+                "Applying storage schema migration"
+            );
+            migration.up(self.store.as_ref()).await?;
+            self.store
+                .set(
+                    SCHEMA_VERSION_KEY,
+                    migration.version().to_string().as_bytes(),
+                    None,
+                )
+                .await?;
+            report.to_version = migration.version();
+            report.applied.push(migration.version());
+
+            // Extend the lease for the next step
+            self.store
+                .set(MIGRATION_LOCK_KEY, self.owner.as_bytes(), Some(self.options.lock_ttl))
+                .await?;
+        }
+
+        info!(
+            from = report.from_version,
+            to = report.to_version,
+            applied = ?report.applied,
+            "Storage schema up to date"
+        );
+        Ok(())
+    }
+
+    /// Take the lease if it is free; confirms ownership by reading it back.
+    async fn try_lock(&self) -> Result<bool, TokenError> {
+        if self.store.get(MIGRATION_LOCK_KEY).await?.is_some() {
+            return Ok(false);
+        }
+        self.store
+            .set(MIGRATION_LOCK_KEY, self.owner.as_bytes(), Some(self.options.lock_ttl))
+            .await?;
+        let holder = self.store.get(MIGRATION_LOCK_KEY).await?;
+        Ok(holder.as_deref() == Some(self.owner.as_bytes()))
+    }
+
+    /// Release the lease if this migrator still holds it.
+    async fn unlock(&self) -> Result<(), TokenError> {
+        let holder = self.store.get(MIGRATION_LOCK_KEY).await?;
+        if holder.as_deref() == Some(self.owner.as_bytes()) {
+            self.store.delete(MIGRATION_LOCK_KEY).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_common::CacheClientConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts its runs and writes a marker key.
+    struct Recording {
+        version: u32,
+        runs: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl SchemaMigration for Recording {
+        fn version(&self) -> u32 {
+            self.version
+        }
+
+        fn description(&self) -> &str {
+            "test migration"
+        }
+
+        async fn up(&self, store: &dyn SchemaStore) -> Result<(), TokenError> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            store
+                .set(&format!("migrated:{}", self.version), b"1", None)
+                .await
+        }
+    }
+
+    async fn store(namespace: &str) -> Arc<CacheClient> {
+        Arc::new(
+            CacheClient::new(CacheClientConfig::default().with_namespace(namespace))
+                .await
+                .unwrap(),
+        )
+    }
+
+    fn registry(runs: &Arc<AtomicUsize>) -> MigrationRegistry {
+        MigrationRegistry::builtin()
+            .with(Arc::new(Recording { version: 3, runs: runs.clone() }))
+            .with(Arc::new(Recording { version: 2, runs: runs.clone() }))
+    }
+
+    fn options() -> SchemaOptions {
+        SchemaOptions {
+            lock_ttl: Duration::from_secs(30),
+            lock_wait: Duration::from_millis(200),
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_applies_pending_migrations_in_order() {
+        let store = store("schema-apply").await;
+        let runs = Arc::new(AtomicUsize::new(0));
+        let migrator = SchemaMigrator::new(store.clone(), registry(&runs), options());
+
+        let report = migrator.run().await.unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, 3);
+        assert_eq!(report.applied, vec![1, 2, 3]);
+        assert_eq!(migrator.current_version().await.unwrap(), 3);
+        assert!(SchemaStore::get(store.as_ref(), "migrated:2").await.unwrap().is_some());
+        assert!(SchemaStore::get(store.as_ref(), MIGRATION_LOCK_KEY).await.unwrap().is_none());
+
+        // Up to date: nothing reapplied
+        let report = migrator.run().await.unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_lock_holder() {
+        let store = store("schema-lock").await;
+        let runs = Arc::new(AtomicUsize::new(0));
+        SchemaStore::set(store.as_ref(), MIGRATION_LOCK_KEY, b"other-replica", None)
+            .await
+            .unwrap();
+
+        let migrator = SchemaMigrator::new(store.clone(), registry(&runs), options());
+        assert!(migrator.run().await.is_err());
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        // The holder finishes while this replica waits
+        let waiting = tokio::spawn(async move { migrator.run().await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        SchemaStore::set(store.as_ref(), SCHEMA_VERSION_KEY, b"3", None)
+            .await
+            .unwrap();
+        let report = waiting.await.unwrap().unwrap();
+        assert_eq!(report.to_version, 3);
+        assert!(report.applied.is_empty());
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_newer_schema() {
+        let store = store("schema-newer").await;
+        SchemaStore::set(store.as_ref(), SCHEMA_VERSION_KEY, b"9", None)
+            .await
+            .unwrap();
+
+        let migrator = SchemaMigrator::new(store, MigrationRegistry::builtin(), options());
+        assert!(matches!(migrator.check().await, Err(TokenError::Config(_))));
+        assert!(migrator.run().await.is_err());
+    }
+}
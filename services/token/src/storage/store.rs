@@ -0,0 +1,160 @@
+//! Storage abstraction used by handlers.
+//!
+//! Lets the refresh rotator and gRPC handlers run against `CacheStorage`,
+//! `EncryptedCacheStorage` or the transitional `DualWriteStorage` without
+//! knowing which backend is active.
+
+use crate::error::TokenError;
+use crate::refresh::family::TokenFamily;
+use super::cache::CacheStorage;
+use super::encrypted_cache::EncryptedCacheStorage;
+#[allow(deprecated)]
+use super::redis::RedisStorage;
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// Token family and revocation persistence.
+#[async_trait]
+pub trait FamilyStore: Send + Sync {
+    /// Store a token family.
+    async fn store_token_family(
+        &self,
+        family: &TokenFamily,
+        ttl: Option<Duration>,
+    ) -> Result<(), TokenError>;
+
+    /// Get a token family by ID.
+    async fn get_token_family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError>;
+
+    /// Find token family by current token hash.
+    async fn find_family_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<TokenFamily>, TokenError>;
+
+    /// Get all token families for a user.
+    async fn get_user_token_families(&self, user_id: &str) -> Result<Vec<TokenFamily>, TokenError>;
+
+    /// Add JTI to revocation list.
+    async fn add_to_revocation_list(&self, jti: &str, ttl: Duration) -> Result<(), TokenError>;
+
+    /// Check if token is revoked.
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, TokenError>;
+
+    /// Check that the store accepts writes.
+    ///
+    /// Writes a short-lived probe entry to the revocation list.
+    async fn check_writable(&self) -> Result<(), TokenError> {
+        self.add_to_revocation_list(WRITE_PROBE_JTI, Duration::from_secs(1))
+            .await
+    }
+}
+
+/// Revocation list entry written by [`FamilyStore::check_writable`].
+const WRITE_PROBE_JTI: &str = "__write_probe__";
+
+#[async_trait]
+impl FamilyStore for CacheStorage {
+    async fn store_token_family(
+        &self,
+        family: &TokenFamily,
+        ttl: Option<Duration>,
+    ) -> Result<(), TokenError> {
+        CacheStorage::store_token_family(self, family, ttl).await
+    }
+
+    async fn get_token_family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError> {
+        CacheStorage::get_token_family(self, family_id).await
+    }
+
+    async fn find_family_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<TokenFamily>, TokenError> {
+        CacheStorage::find_family_by_token_hash(self, token_hash).await
+    }
+
+    async fn get_user_token_families(&self, user_id: &str) -> Result<Vec<TokenFamily>, TokenError> {
+        CacheStorage::get_user_token_families(self, user_id).await
+    }
+
+    async fn add_to_revocation_list(&self, jti: &str, ttl: Duration) -> Result<(), TokenError> {
+        CacheStorage::add_to_revocation_list(self, jti, ttl).await
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, TokenError> {
+        CacheStorage::is_token_revoked(self, jti).await
+    }
+}
+
+#[async_trait]
+impl FamilyStore for EncryptedCacheStorage {
+    async fn store_token_family(
+        &self,
+        family: &TokenFamily,
+        ttl: Option<Duration>,
+    ) -> Result<(), TokenError> {
+        EncryptedCacheStorage::store_token_family(self, family, ttl).await
+    }
+
+    async fn get_token_family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError> {
+        EncryptedCacheStorage::get_token_family(self, family_id).await
+    }
+
+    async fn find_family_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<TokenFamily>, TokenError> {
+        EncryptedCacheStorage::find_family_by_token_hash(self, token_hash).await
+    }
+
+    async fn get_user_token_families(&self, user_id: &str) -> Result<Vec<TokenFamily>, TokenError> {
+        EncryptedCacheStorage::get_user_token_families(self, user_id).await
+    }
+
+    async fn add_to_revocation_list(&self, jti: &str, ttl: Duration) -> Result<(), TokenError> {
+        EncryptedCacheStorage::add_to_revocation_list(self, jti, ttl).await
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, TokenError> {
+        EncryptedCacheStorage::is_token_revoked(self, jti).await
+    }
+}
+
+#[allow(deprecated)]
+#[async_trait]
+impl FamilyStore for RedisStorage {
+    async fn store_token_family(
+        &self,
+        family: &TokenFamily,
+        ttl: Option<Duration>,
+    ) -> Result<(), TokenError> {
+        let ttl = ttl.unwrap_or(Duration::from_secs(86400));
+        let ttl_seconds = i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX);
+        RedisStorage::store_token_family(self, family, ttl_seconds).await
+    }
+
+    async fn get_token_family(&self, family_id: &str) -> Result<Option<TokenFamily>, TokenError> {
+        RedisStorage::get_token_family(self, family_id).await
+    }
+
+    async fn find_family_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<TokenFamily>, TokenError> {
+        RedisStorage::find_family_by_token_hash(self, token_hash).await
+    }
+
+    async fn get_user_token_families(&self, user_id: &str) -> Result<Vec<TokenFamily>, TokenError> {
+        RedisStorage::get_user_token_families(self, user_id).await
+    }
+
+    async fn add_to_revocation_list(&self, jti: &str, ttl: Duration) -> Result<(), TokenError> {
+        let ttl_seconds = i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX);
+        RedisStorage::add_to_revocation_list(self, jti, ttl_seconds).await
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, TokenError> {
+        RedisStorage::is_token_revoked(self, jti).await
+    }
+}
@@ -0,0 +1,174 @@
+//! End-to-end token lifecycle scenario.
+//!
+//! Wires MockKms, in-memory CacheStorage and the real RefreshTokenRotator
+//! into `test_utils::scenarios::TokenLifecycle` and runs the shared
+//! issue → validate → refresh → replay-detect → revoke → validate-fails
+//! scenario against them.
+
+use jsonwebtoken::{Algorithm, DecodingKey};
+use rust_common::{CacheClientConfig, LoggingClient, LoggingClientConfig};
+use std::sync::Arc;
+use std::time::Duration;
+use test_utils::scenarios::{
+    IssuedTokens, LifecycleScenario, Rejection, TokenLifecycle, ValidatedToken,
+};
+use token_service::jwt::{Claims, JwtSerializer};
+use token_service::kms::{KmsSigner, MockKms};
+use token_service::refresh::RefreshTokenRotator;
+use token_service::storage::CacheStorage;
+use token_service::TokenError;
+
+const SECRET: &[u8] = b"lifecycle-scenario-secret-key-32bytes!";
+const ACCESS_TTL_SECONDS: i64 = 900;
+
+/// Token service components under test.
+struct TokenServiceLifecycle {
+    kms: MockKms,
+    serializer: JwtSerializer,
+    decoding_key: DecodingKey,
+    storage: Arc<CacheStorage>,
+    rotator: RefreshTokenRotator,
+}
+
+impl TokenServiceLifecycle {
+    async fn new() -> Self {
+        let cache_config = CacheClientConfig::default()
+            .with_namespace(format!("lifecycle-test-{}", uuid::Uuid::new_v4()));
+        let storage = Arc::new(CacheStorage::new(cache_config).await.unwrap());
+
+        let log_config = LoggingClientConfig::default().with_service_id("token-service-test");
+        let logger = Arc::new(LoggingClient::new(log_config).await.unwrap());
+
+        let rotator = RefreshTokenRotator::new(
+            storage.clone(),
+            logger,
+            Duration::from_secs(604800),
+        );
+
+        Self {
+            kms: MockKms::new("lifecycle-key").with_secret(SECRET.to_vec()),
+            serializer: JwtSerializer::new(Algorithm::HS256),
+            decoding_key: DecodingKey::from_secret(SECRET),
+            storage,
+            rotator,
+        }
+    }
+
+    fn sign_access_token(
+        &self,
+        subject: &str,
+        session_id: &str,
+        family_id: String,
+        refresh_token: String,
+    ) -> Result<IssuedTokens, TokenError> {
+        let claims = Claims::new(
+            "https://auth.test".to_string(),
+            subject.to_string(),
+            vec!["api".to_string()],
+            ACCESS_TTL_SECONDS,
+        )
+        .with_session_id(session_id.to_string());
+
+        let access_token = self.serializer.serialize(
+            &claims,
+            &self.kms.get_encoding_key()?,
+            Some(self.kms.key_id()),
+        )?;
+
+        Ok(IssuedTokens {
+            access_token,
+            refresh_token,
+            jti: claims.jti,
+            family_id,
+        })
+    }
+}
+
+impl TokenLifecycle for TokenServiceLifecycle {
+    type Error = TokenError;
+
+    async fn issue(&self, subject: &str, session_id: &str) -> Result<IssuedTokens, TokenError> {
+        let (refresh_token, family) = self
+            .rotator
+            .create_token_family(subject, session_id, None)
+            .await?;
+        self.sign_access_token(subject, session_id, family.family_id, refresh_token)
+    }
+
+    async fn validate(&self, access_token: &str) -> Result<ValidatedToken, TokenError> {
+        let claims = self.serializer.deserialize(access_token, &self.decoding_key)?;
+        if self.storage.is_token_revoked(&claims.jti).await? {
+            return Err(TokenError::FamilyRevoked);
+        }
+        Ok(ValidatedToken {
+            subject: claims.sub,
+            jti: claims.jti,
+        })
+    }
+
+    async fn refresh(&self, refresh_token: &str) -> Result<IssuedTokens, TokenError> {
+        let (new_token, family) = self.rotator.rotate(refresh_token, None).await?;
+        self.sign_access_token(
+            &family.user_id,
+            &family.session_id,
+            family.family_id.clone(),
+            new_token,
+        )
+    }
+
+    async fn revoke(&self, tokens: &IssuedTokens) -> Result<(), TokenError> {
+        self.storage
+            .add_to_revocation_list(&tokens.jti, Duration::from_secs(ACCESS_TTL_SECONDS as u64))
+            .await?;
+        self.rotator.revoke_family(&tokens.family_id, None).await
+    }
+
+    fn rejection(&self, error: &TokenError) -> Rejection {
+        match error {
+            TokenError::RefreshReplay => Rejection::Replay,
+            TokenError::FamilyRevoked => Rejection::Revoked,
+            TokenError::RefreshInvalid
+            | TokenError::RefreshExpired
+            | TokenError::JwtDecoding(_) => Rejection::Invalid,
+            _ => Rejection::Other,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_full_token_lifecycle() {
+    let lifecycle = TokenServiceLifecycle::new().await;
+
+    let report = LifecycleScenario::default()
+        .run(&lifecycle)
+        .await
+        .unwrap_or_else(|e| panic!("lifecycle invariant violated: {e}"));
+
+    assert_eq!(report.issued.family_id, report.refreshed.family_id);
+}
+
+#[tokio::test]
+async fn test_tampered_access_token_is_rejected() {
+    let lifecycle = TokenServiceLifecycle::new().await;
+    let tokens = lifecycle.issue("user-tamper", "session-tamper").await.unwrap();
+
+    let tampered = format!("{}x", tokens.access_token);
+    let error = lifecycle.validate(&tampered).await.unwrap_err();
+
+    assert_eq!(lifecycle.rejection(&error), Rejection::Invalid);
+}
+
+#[tokio::test]
+async fn test_scenario_runs_per_session() {
+    let lifecycle = TokenServiceLifecycle::new().await;
+
+    // Two sessions for one user must not interfere with each other.
+    for session_id in ["session-a", "session-b"] {
+        if let Err(e) = LifecycleScenario::new("user-multi", session_id)
+            .run(&lifecycle)
+            .await
+        {
+            panic!("{session_id}: {e}");
+        }
+    }
+}
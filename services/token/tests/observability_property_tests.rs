@@ -67,7 +67,9 @@ proptest! {
         token_service::metrics::record_token_refreshed("success");
         token_service::metrics::record_token_revoked("user_request");
         token_service::metrics::record_dpop_validation("success", "none");
-        token_service::metrics::record_grpc_latency(&method, latency);
+        rust_common::GrpcMetrics::new("token_service", &prometheus::Registry::new())
+            .unwrap()
+            .record(&format!("/auth.token.TokenService/{method}"), tonic::Code::Ok, latency);
         token_service::metrics::record_kms_operation("sign", "success");
         token_service::metrics::record_cache_operation("get", "hit");
         token_service::metrics::record_security_event(&event_type);
@@ -150,7 +152,6 @@ mod unit_tests {
         let _ = &*token_service::metrics::TOKENS_REFRESHED;
         let _ = &*token_service::metrics::TOKENS_REVOKED;
         let _ = &*token_service::metrics::DPOP_VALIDATIONS;
-        let _ = &*token_service::metrics::GRPC_LATENCY;
         let _ = &*token_service::metrics::KMS_OPERATIONS;
         let _ = &*token_service::metrics::CACHE_OPERATIONS;
         let _ = &*token_service::metrics::SECURITY_EVENTS;
@@ -184,9 +185,11 @@ mod unit_tests {
     #[test]
     fn test_latency_histogram_buckets() {
         // Record various latencies to test bucket distribution
-        token_service::metrics::record_grpc_latency("test", 0.001);
-        token_service::metrics::record_grpc_latency("test", 0.01);
-        token_service::metrics::record_grpc_latency("test", 0.1);
-        token_service::metrics::record_grpc_latency("test", 1.0);
+        let metrics =
+            rust_common::GrpcMetrics::new("token_service", &prometheus::Registry::new()).unwrap();
+        for latency in [0.001, 0.01, 0.1, 1.0] {
+            metrics.record("/auth.token.TokenService/Test", tonic::Code::Ok, latency);
+        }
+        assert_eq!(metrics.requests("auth.token.TokenService", "Test", tonic::Code::Ok), 4);
     }
 }